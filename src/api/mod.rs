@@ -1,59 +1,364 @@
-use crate::models::{AccountInfo, Database, Nav, Note};
-use crate::storage::{TOKEN_KEY, USER_KEY};
+use crate::models::{AccountInfo, Database, Nav, Note, NoteTemplate, Settings};
+use crate::storage::{load_json_from_storage, SETTINGS_KEY, TOKEN_KEY, USER_KEY};
+use crate::util::{
+    decode_base64url, nav_copy_order, next_available_daily_note_title_for_date, note_previews,
+    now_ms, template_nav_order, ROOT_CONTAINER_PARENT_ID,
+};
+use leptos::prelude::{RwSignal, Set, Update, WithUntracked};
 use serde::{Deserialize, Serialize};
-
+use std::cell::RefCell;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::task::{Context, Poll, Waker};
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::JsCast;
+
+/// Failure of an `ApiClient` call, classified so callers can react per case.
 #[derive(Clone, Debug, PartialEq, Eq)]
-pub(crate) enum ApiErrorKind {
+pub(crate) enum ApiError {
+    /// The request never reached the server (or the browser is offline).
+    Network(String),
+    /// 401: missing or expired session.
     Unauthorized,
-    Network,
-    Http,
-    Parse,
-}
-
-#[derive(Clone, Debug)]
-pub(crate) struct ApiError {
-    pub kind: ApiErrorKind,
-    pub message: String,
+    /// 403: the account may not do this (on login: rejected credentials).
+    Forbidden,
+    /// 404: unknown endpoint or record.
+    NotFound,
+    /// Any other 4xx; carries the server's message.
+    Validation(String),
+    /// 5xx (or any other non-success status).
+    Server { status: u16, body: String },
+    /// The response could not be decoded, or lacks a field the client needs.
+    Parse(String),
+    /// A multi-request operation (copy/move) stopped partway; says how far it got.
+    Incomplete(String),
+    /// No response within `ApiClient::timeout_ms`; the fetch was aborted.
+    TimedOut,
+    /// Aborted through an [`AbortHandle`] (a newer request superseded this one).
+    Cancelled,
 }
 
 impl std::fmt::Display for ApiError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.message)
+        match self {
+            Self::Network(m) if m == OFFLINE_MESSAGE => write!(f, "{m}"),
+            Self::Network(m) => write!(f, "Check your connection and try again. ({m})"),
+            Self::Unauthorized => write!(f, "Your session has expired. Please log in again."),
+            Self::Forbidden => write!(f, "You don't have permission to do that."),
+            Self::NotFound => write!(f, "Not found."),
+            Self::Validation(m) => write!(f, "Request rejected: {m}"),
+            Self::Server { status, body } => write!(f, "Server error ({status}): {body}"),
+            Self::Parse(m) => write!(
+                f,
+                "Unexpected response from the server ({m}). Please report this as a bug."
+            ),
+            Self::Incomplete(m) => write!(f, "{m}"),
+            Self::TimedOut => write!(f, "Request timed out. The server may be busy; try again."),
+            Self::Cancelled => write!(f, "Request cancelled."),
+        }
     }
 }
 
 impl ApiError {
     fn network(e: reqwest::Error) -> Self {
-        Self {
-            kind: ApiErrorKind::Network,
-            message: e.to_string(),
-        }
+        Self::Network(e.to_string())
     }
 
     fn parse(e: impl std::fmt::Display) -> Self {
-        Self {
-            kind: ApiErrorKind::Parse,
-            message: e.to_string(),
-        }
+        Self::Parse(e.to_string())
     }
 
-    fn unauthorized() -> Self {
-        Self {
-            kind: ApiErrorKind::Unauthorized,
-            message: "Unauthorized".to_string(),
+    /// Returned without touching the network while the browser reports no connection.
+    pub(crate) fn offline() -> Self {
+        Self::Network(OFFLINE_MESSAGE.to_string())
+    }
+
+    pub(crate) fn is_offline(&self) -> bool {
+        matches!(self, Self::Network(m) if m == OFFLINE_MESSAGE)
+    }
+
+    /// Classify a non-success response.
+    pub(crate) fn from_status(status: u16, body: String) -> Self {
+        match status {
+            401 => Self::Unauthorized,
+            403 => Self::Forbidden,
+            404 => Self::NotFound,
+            400..=499 => Self::Validation(body),
+            _ => Self::Server { status, body },
         }
     }
 
-    fn http(status: reqwest::StatusCode, body: String, ctx: &str) -> Self {
-        Self {
-            kind: ApiErrorKind::Http,
-            message: format!("{ctx} ({status}): {body}"),
+    /// Worth retrying on its own: the server or the connection may recover. Offline is
+    /// excluded; the `online` event resumes work instead.
+    pub(crate) fn is_transient(&self) -> bool {
+        match self {
+            Self::Network(_) => !self.is_offline(),
+            Self::Server { .. } | Self::TimedOut => true,
+            _ => false,
         }
     }
 }
 
 pub(crate) type ApiResult<T> = Result<T, ApiError>;
 
+/// Copy for a failed login or signup, by cause: unreachable server, rejected request (401/403
+/// get `rejected`, other 4xx the server's own message) or server trouble.
+pub(crate) fn auth_error_message(e: &ApiError, rejected: &str) -> String {
+    match e {
+        _ if e.is_offline() => "You are offline. Reconnect and try again.".to_string(),
+        ApiError::Network(_) | ApiError::TimedOut => {
+            "Cannot reach the server. Check your connection and the API URL.".to_string()
+        }
+        ApiError::Unauthorized | ApiError::Forbidden => rejected.to_string(),
+        ApiError::Validation(m) if !m.trim().is_empty() => m.trim().to_string(),
+        ApiError::Validation(_) => rejected.to_string(),
+        ApiError::Server { status, .. } => {
+            format!("The server ran into a problem ({status}). Please try again later.")
+        }
+        _ => e.to_string(),
+    }
+}
+
+/// Default for [`ApiClient::timeout_ms`].
+pub(crate) const DEFAULT_REQUEST_TIMEOUT_MS: u32 = 15_000;
+
+pub(crate) const OFFLINE_MESSAGE: &str =
+    "Offline: this needs a connection. Try again once you are back online";
+
+/// `navigator.onLine == false`. Requests are refused up front in that case: block edits stay
+/// in local drafts (replayed by `NoteSyncController`), other writes wait in the client's
+/// pending queue (see [`ApiClient::replay_pending_requests`]); reads and creates fail.
+fn browser_offline() -> bool {
+    web_sys::window()
+        .map(|w| !w.navigator().on_line())
+        .unwrap_or(false)
+}
+
+/// A write made while offline, replayed in order once the browser is back online.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub(crate) struct PendingRequest {
+    pub path: String,
+    pub body: serde_json::Value,
+}
+
+/// Backoff schedule for [`with_retry`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct RetryPolicy {
+    pub base_ms: u32,
+    pub max_ms: u32,
+    /// Total attempts including the first one; after that the breaker opens.
+    pub max_attempts: u32,
+}
+
+impl RetryPolicy {
+    /// List fetches (databases, notes): 1s, 2s, 4s, … capped at 30s, 6 attempts.
+    pub(crate) const LIST_FETCH: Self = Self {
+        base_ms: 1_000,
+        max_ms: 30_000,
+        max_attempts: 6,
+    };
+
+    /// Delay before the `retry`-th retry (1-based).
+    pub(crate) fn delay_ms(self, retry: u32) -> u32 {
+        let factor = 1u32 << retry.saturating_sub(1).min(31);
+        self.base_ms.saturating_mul(factor).min(self.max_ms)
+    }
+}
+
+/// Progress of a [`with_retry`] loop, for the UI.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) enum RetryState {
+    #[default]
+    Idle,
+    /// Attempt `attempt` failed with `error`; the next one starts in `delay_ms`.
+    Retrying {
+        attempt: u32,
+        delay_ms: u32,
+        error: String,
+    },
+    /// Circuit open: no more automatic retries until the caller starts a new run.
+    GaveUp,
+}
+
+/// Resolves after `ms` (browser `setTimeout`).
+fn sleep_ms(ms: u32) -> impl Future<Output = ()> {
+    #[derive(Default)]
+    struct Shared {
+        done: bool,
+        waker: Option<Waker>,
+    }
+
+    struct Sleep(Rc<RefCell<Shared>>);
+
+    impl Future for Sleep {
+        type Output = ();
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+            let mut shared = self.0.borrow_mut();
+            if shared.done {
+                Poll::Ready(())
+            } else {
+                shared.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+
+    let shared = Rc::new(RefCell::new(Shared::default()));
+    let fire = shared.clone();
+    let cb = Closure::once_into_js(move || {
+        let waker = {
+            let mut s = fire.borrow_mut();
+            s.done = true;
+            s.waker.take()
+        };
+        if let Some(w) = waker {
+            w.wake();
+        }
+    });
+    let scheduled = web_sys::window().and_then(|w| {
+        w.set_timeout_with_callback_and_timeout_and_arguments_0(cb.unchecked_ref(), ms as i32)
+            .ok()
+    });
+    if scheduled.is_none() {
+        shared.borrow_mut().done = true;
+    }
+    Sleep(shared)
+}
+
+#[derive(Default)]
+struct AbortShared {
+    aborted: bool,
+    wakers: Vec<Waker>,
+}
+
+/// Cancels the requests of an [`ApiClient::with_abort`] client. Dropping the request future
+/// aborts the underlying `fetch`, so the browser really stops waiting for the response.
+///
+/// `Arc<Mutex>` rather than `Rc<RefCell>` only so `ApiClient` can live in a signal.
+#[derive(Clone, Default)]
+pub(crate) struct AbortHandle(Arc<Mutex<AbortShared>>);
+
+impl AbortHandle {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    fn shared(&self) -> MutexGuard<'_, AbortShared> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Idempotent; requests started after this fail with `ApiError::Cancelled` right away.
+    pub(crate) fn abort(&self) {
+        let wakers = {
+            let mut shared = self.shared();
+            shared.aborted = true;
+            std::mem::take(&mut shared.wakers)
+        };
+        for w in wakers {
+            w.wake();
+        }
+    }
+
+    pub(crate) fn is_aborted(&self) -> bool {
+        self.shared().aborted
+    }
+}
+
+/// `request` raced against a timer and an optional abort handle; whichever loses is dropped.
+struct Bounded<R, S> {
+    request: Pin<Box<R>>,
+    timer: Pin<Box<S>>,
+    abort: Option<AbortHandle>,
+}
+
+impl<T, R, S> Future for Bounded<R, S>
+where
+    R: Future<Output = ApiResult<T>>,
+    S: Future<Output = ()>,
+{
+    type Output = ApiResult<T>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if let Some(abort) = &self.abort {
+            let mut shared = abort.shared();
+            if shared.aborted {
+                return Poll::Ready(Err(ApiError::Cancelled));
+            }
+            if !shared.wakers.iter().any(|w| w.will_wake(cx.waker())) {
+                shared.wakers.push(cx.waker().clone());
+            }
+        }
+        if let Poll::Ready(out) = self.request.as_mut().poll(cx) {
+            return Poll::Ready(out);
+        }
+        match self.timer.as_mut().poll(cx) {
+            Poll::Ready(()) => Poll::Ready(Err(ApiError::TimedOut)),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+/// Run `fetch` until it succeeds, with exponential backoff between attempts.
+///
+/// - Any `Ok` is terminal, including an empty list (that's the empty state, not a failure).
+/// - Errors rejected by `is_retryable` (auth, offline, …) are returned right away.
+/// - After `policy.max_attempts` failures the state becomes `GaveUp` and the last error is
+///   returned; the caller restarts with a fresh call (e.g. a Refresh button).
+/// - `is_stale` lets a newer run supersede this one: it is checked after every attempt and
+///   sleep, and a stale run returns without touching `state`.
+pub(crate) async fn with_retry<T, E, F, Fut>(
+    policy: RetryPolicy,
+    state: RwSignal<RetryState>,
+    is_retryable: impl Fn(&E) -> bool,
+    is_stale: impl Fn() -> bool,
+    fetch: F,
+) -> Result<T, E>
+where
+    E: std::fmt::Display,
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let mut attempt = 1;
+    loop {
+        let result = fetch().await;
+        if is_stale() {
+            return result;
+        }
+
+        let e = match result {
+            Ok(v) => {
+                state.set(RetryState::Idle);
+                return Ok(v);
+            }
+            Err(e) => e,
+        };
+
+        if !is_retryable(&e) {
+            state.set(RetryState::Idle);
+            return Err(e);
+        }
+        if attempt >= policy.max_attempts {
+            state.set(RetryState::GaveUp);
+            return Err(e);
+        }
+
+        let delay_ms = policy.delay_ms(attempt);
+        state.set(RetryState::Retrying {
+            attempt,
+            delay_ms,
+            error: e.to_string(),
+        });
+        sleep_ms(delay_ms).await;
+        if is_stale() {
+            return Err(e);
+        }
+        attempt += 1;
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub(crate) struct EnvConfig {
     pub api_url: String,
@@ -61,6 +366,17 @@ pub(crate) struct EnvConfig {
 
 impl EnvConfig {
     pub fn new() -> Self {
+        // A runtime override from the Settings page wins over the deployment config.
+        if let Some(api_url) = load_json_from_storage::<Settings>(SETTINGS_KEY)
+            .and_then(|s| s.api_url_override())
+        {
+            return Self { api_url };
+        }
+        Self::from_env()
+    }
+
+    /// Deployment config only (`window.ENV`), ignoring any user override.
+    pub fn from_env() -> Self {
         let default_api_url = "http://localhost:6689".to_string();
 
         // We support BOTH `window.ENV.API_URL` (documented in README) and
@@ -97,6 +413,25 @@ impl Default for EnvConfig {
     }
 }
 
+/// `exp` claim of a JWT in ms since epoch (payload decoded only; the signature is not checked).
+/// `None` when the token is not a JWT or carries no `exp`.
+pub(crate) fn jwt_expiry_ms(token: &str) -> Option<i64> {
+    let payload = token.split('.').nth(1)?;
+    let claims: serde_json::Value = serde_json::from_slice(&decode_base64url(payload)?).ok()?;
+    let exp = claims.get("exp")?.as_f64()?;
+    Some((exp * 1000.0) as i64)
+}
+
+/// Title for a duplicated note.
+pub(crate) fn duplicate_note_title(title: &str) -> String {
+    format!("{} (copy)", title.trim())
+}
+
+/// Error for a client-side copy that stopped partway (the partial note is kept).
+pub(crate) fn copy_incomplete_message(copied: usize, total: usize) -> String {
+    format!("Copy incomplete, {} of {} blocks copied", copied, total)
+}
+
 fn get_api_url() -> String {
     EnvConfig::new().api_url
 }
@@ -134,6 +469,9 @@ pub(crate) struct UpdateDatabaseRequest {
     #[serde(rename = "db-name", skip_serializing_if = "Option::is_none")]
     pub db_name: Option<String>,
 
+    #[serde(rename = "db-description", skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
     #[serde(rename = "is-public", skip_serializing_if = "Option::is_none")]
     pub is_public: Option<bool>,
     #[serde(rename = "is-default", skip_serializing_if = "Option::is_none")]
@@ -157,14 +495,55 @@ pub(crate) struct CreateNoteRequest {
     pub title: String,
 }
 
+/// `update-hulunote-note` body: only the fields that are `Some` are changed.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub(crate) struct UpdateNoteRequest {
+    #[serde(rename = "note-id")]
+    pub note_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Moves the note to another database, keeping its id (see `ApiClient::move_note`).
+    #[serde(rename = "database-id", skip_serializing_if = "Option::is_none")]
+    pub database_id: Option<String>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
-#[allow(dead_code)]
 pub(crate) struct GetNoteListRequest {
+    #[serde(rename = "database-id")]
     pub database_id: String,
     pub page: i32,
+    #[serde(rename = "size")]
     pub page_size: i32,
 }
 
+/// Page size used by the paginated note list (DbHomePage "Load more").
+pub(crate) const NOTE_LIST_PAGE_SIZE: i32 = 50;
+
+/// Databases per account allowed by the backend.
+pub(crate) const MAX_DATABASES: usize = 5;
+
+pub(crate) fn database_limit_message() -> String {
+    format!("You can have at most {MAX_DATABASES} databases. Delete one to create another.")
+}
+
+/// Phrases the backend uses when it refuses a database over the per-account count.
+const DATABASE_LIMIT_PHRASES: &[&str] = &[
+    "database limit",
+    "databases limit",
+    "number of databases",
+    "数据库数量",
+];
+
+/// Whether a `create_database` error is the backend rejecting the database count: a 4xx
+/// whose message names the database limit. Other limits (rate, size, name length) are not.
+pub(crate) fn is_database_limit_error(e: &ApiError) -> bool {
+    let ApiError::Validation(m) = e else {
+        return false;
+    };
+    let m = m.to_lowercase();
+    DATABASE_LIMIT_PHRASES.iter().any(|k| m.contains(k))
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub(crate) struct GetNoteNavsRequest {
     #[serde(rename = "note-id")]
@@ -206,53 +585,129 @@ pub(crate) struct SignupRequest {
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub(crate) struct SignupResponse {
-    pub token: String,
-    pub hulunote: AccountInfo,
+    /// Session for the new account; older backends don't sign the user in on signup.
+    #[serde(default)]
+    pub token: Option<String>,
+    #[serde(default)]
+    pub hulunote: Option<AccountInfo>,
+    /// Name of the database created along with the account, if any.
     pub database: Option<String>,
     pub region: Option<String>,
 }
 
+impl SignupResponse {
+    /// Token and account to sign in with, when the backend returned a usable session.
+    pub(crate) fn into_session(self) -> Option<(String, AccountInfo)> {
+        let token = self.token.filter(|t| !t.trim().is_empty())?;
+        Some((token, self.hulunote?))
+    }
+}
+
 #[derive(Clone)]
 pub(crate) struct ApiClient {
     pub(crate) base_url: String,
     pub(crate) token: Option<String>,
+    /// Shared by every request (and cheap to clone: it is reference-counted).
+    http: reqwest::Client,
+    /// Per request, including reading the body; `DEFAULT_REQUEST_TIMEOUT_MS` by default.
+    pub(crate) timeout_ms: u32,
+    /// Set on clients made by `with_abort`.
+    abort: Option<AbortHandle>,
+    /// Where writes made while offline wait (`AppState::pending_requests`); without one they
+    /// fail like any other request.
+    pending: Option<RwSignal<Vec<PendingRequest>>>,
 }
 
 impl ApiClient {
-    #[allow(dead_code)]
     pub fn new(base_url: String) -> Self {
         Self {
             base_url,
             token: None,
+            http: reqwest::Client::new(),
+            timeout_ms: DEFAULT_REQUEST_TIMEOUT_MS,
+            abort: None,
+            pending: None,
+        }
+    }
+
+    /// A copy whose requests fail with `ApiError::Cancelled` once `handle` is aborted, e.g.
+    /// a note list or outline fetch that a later navigation made pointless.
+    pub(crate) fn with_abort(&self, handle: &AbortHandle) -> Self {
+        Self {
+            abort: Some(handle.clone()),
+            ..self.clone()
         }
     }
 
+    /// Queue writes made while offline in `pending` instead of failing them.
+    pub(crate) fn with_pending_queue(self, pending: RwSignal<Vec<PendingRequest>>) -> Self {
+        Self {
+            pending: Some(pending),
+            ..self
+        }
+    }
+
+    /// Loads the token from localStorage ("remember me") or sessionStorage.
+    /// An already expired token is dropped (and storage cleared) so the app goes
+    /// straight to the login page.
     pub fn load_from_storage() -> Self {
         let base_url = get_api_url();
-        let token = leptos::web_sys::window()
+        let window = leptos::web_sys::window();
+        let token = window
+            .as_ref()
             .and_then(|w| w.local_storage().ok().flatten())
-            .and_then(|s| s.get_item(TOKEN_KEY).ok().flatten());
-
-        Self { base_url, token }
+            .and_then(|s| s.get_item(TOKEN_KEY).ok().flatten())
+            .or_else(|| {
+                window
+                    .as_ref()
+                    .and_then(|w| w.session_storage().ok().flatten())
+                    .and_then(|s| s.get_item(TOKEN_KEY).ok().flatten())
+            });
+
+        let mut client = Self::new(base_url);
+        client.token = token;
+        if client.token.is_some() && !client.has_valid_token() {
+            Self::clear_storage();
+            client.token = None;
+        }
+        client
     }
 
-    pub fn save_to_storage(&self) {
-        if let Some(storage) =
-            leptos::web_sys::window().and_then(|w| w.local_storage().ok().flatten())
-        {
-            if let Some(token) = &self.token {
+    /// `remember == false` keeps the token in sessionStorage, so it is gone once the
+    /// browser is closed.
+    pub fn save_to_storage(&self, remember: bool) {
+        let Some(window) = leptos::web_sys::window() else {
+            return;
+        };
+        let local = window.local_storage().ok().flatten();
+        let session = window.session_storage().ok().flatten();
+        let (keep, other) = if remember {
+            (local, session)
+        } else {
+            (session, local)
+        };
+
+        if let Some(token) = &self.token {
+            if let Some(storage) = keep {
                 let _ = storage.set_item(TOKEN_KEY, token);
             }
+            if let Some(storage) = other {
+                let _ = storage.remove_item(TOKEN_KEY);
+            }
         }
     }
 
     pub fn clear_storage() {
-        if let Some(storage) =
-            leptos::web_sys::window().and_then(|w| w.local_storage().ok().flatten())
-        {
+        let Some(window) = leptos::web_sys::window() else {
+            return;
+        };
+        if let Some(storage) = window.local_storage().ok().flatten() {
             let _ = storage.remove_item(TOKEN_KEY);
             let _ = storage.remove_item(USER_KEY);
         }
+        if let Some(storage) = window.session_storage().ok().flatten() {
+            let _ = storage.remove_item(TOKEN_KEY);
+        }
     }
 
     pub fn set_token(&mut self, token: String) {
@@ -262,8 +717,17 @@ impl ApiClient {
         self.token.clone()
     }
 
-    pub async fn login(&self, email: &str, password: &str) -> Result<LoginResponse, String> {
-        self.request("POST", "/login/web-login", Some(&LoginRequest {
+    /// A copy that sends no auth header (for the public database viewer).
+    pub(crate) fn anonymous(&self) -> Self {
+        Self {
+            token: None,
+            pending: None,
+            ..self.clone()
+        }
+    }
+
+    pub async fn login(&self, email: &str, password: &str) -> ApiResult<LoginResponse> {
+        self.post_json("/login/web-login", Some(&LoginRequest {
             email: email.to_string(),
             password: password.to_string(),
         })).await
@@ -279,56 +743,84 @@ impl ApiClient {
         req
     }
 
-    async fn request<T: serde::de::DeserializeOwned>(
+    /// POST `body` as JSON to `path` with the auth header; decodes a JSON response.
+    /// Every endpoint goes through here, so status mapping (401, 404, ...) lives in one place.
+    async fn post_json<T: serde::de::DeserializeOwned>(
         &self,
-        method: &str,
         path: &str,
         body: Option<&impl serde::Serialize>,
-    ) -> Result<T, String> {
-        let client = reqwest::Client::new();
+    ) -> ApiResult<T> {
+        if browser_offline() {
+            return Err(ApiError::offline());
+        }
+        if self.abort.as_ref().is_some_and(AbortHandle::is_aborted) {
+            return Err(ApiError::Cancelled);
+        }
         let url = format!("{}{}", self.base_url, path);
-        let mut req = client.request(method.parse().unwrap(), url);
-        req = Self::with_auth_headers(req, self.get_auth_token());
-        
+        let mut req = Self::with_auth_headers(self.http.post(url), self.get_auth_token());
         if let Some(b) = body {
             req = req.json(b);
         }
 
-        let res = req.send().await.map_err(|e| e.to_string())?;
-        
-        if res.status().is_success() {
-            res.json().await.map_err(|e| e.to_string())
-        } else {
+        let request = async move {
+            let res = req.send().await.map_err(ApiError::network)?;
+
             let status = res.status();
-            let body = res.text().await.unwrap_or_default();
-            Err(format!("Request failed ({status}): {body}"))
+            if status.is_success() {
+                res.json().await.map_err(ApiError::parse)
+            } else {
+                let body = res.text().await.unwrap_or_default();
+                Err(ApiError::from_status(status.as_u16(), body))
+            }
+        };
+
+        // reqwest has no timeout on wasm; dropping the losing request aborts its fetch.
+        Bounded {
+            request: Box::pin(request),
+            timer: Box::pin(sleep_ms(self.timeout_ms)),
+            abort: self.abort.clone(),
         }
+        .await
     }
 
-    async fn request_api<T: serde::de::DeserializeOwned>(
-        &self,
-        path: &str,
-        body: Option<&impl serde::Serialize>,
-    ) -> ApiResult<T> {
-        let client = reqwest::Client::new();
-        let url = format!("{}{}", self.base_url, path);
-        let mut req = client.post(url);
-        req = Self::with_auth_headers(req, self.get_auth_token());
-        
-        if let Some(b) = body {
-            req = req.json(b);
+    /// `post_json` for writes whose response nobody reads. Offline, the write joins the
+    /// pending queue and counts as done; it is sent by `replay_pending_requests`.
+    async fn post_write(&self, path: &str, body: &impl serde::Serialize) -> ApiResult<()> {
+        if let Some(pending) = self.pending.filter(|_| browser_offline()) {
+            let body = serde_json::to_value(body).map_err(ApiError::parse)?;
+            pending.update(|q| {
+                q.push(PendingRequest {
+                    path: path.to_string(),
+                    body,
+                })
+            });
+            return Ok(());
         }
+        self.post_json::<()>(path, Some(body)).await
+    }
 
-        let res = req.send().await.map_err(ApiError::network)?;
-        
-        if res.status().is_success() {
-            res.json().await.map_err(ApiError::parse)
-        } else if res.status().as_u16() == 401 {
-            Err(ApiError::unauthorized())
-        } else {
-            let status = res.status();
-            let body = res.text().await.unwrap_or_default();
-            Err(ApiError::http(status, body, "Request failed"))
+    /// Send the writes queued while offline, oldest first. Stops (keeping the rest) at the
+    /// first network or session error; a write the server rejects is dropped, since
+    /// resending it would fail the same way.
+    pub async fn replay_pending_requests(&self) {
+        let Some(pending) = self.pending else {
+            return;
+        };
+        while let Some(next) = pending.try_with_untracked(|q| q.first().cloned()).flatten() {
+            match self.post_json::<()>(&next.path, Some(&next.body)).await {
+                Err(e) if matches!(e, ApiError::Network(_) | ApiError::Unauthorized) => break,
+                Err(e) if e.is_transient() => break,
+                result => {
+                    if let Err(e) = result {
+                        leptos::logging::warn!("dropping queued {} after error: {}", next.path, e);
+                    }
+                    pending.update(|q| {
+                        if q.first() == Some(&next) {
+                            q.remove(0);
+                        }
+                    });
+                }
+            }
         }
     }
 
@@ -353,6 +845,14 @@ impl ApiClient {
                     description: get_s("hulunote-databases/description").unwrap_or_default(),
                     created_at: get_s("hulunote-databases/created-at").unwrap_or_default(),
                     updated_at: get_s("hulunote-databases/updated-at").unwrap_or_default(),
+                    is_default: item
+                        .get("hulunote-databases/is-default")
+                        .and_then(|v| v.as_bool())
+                        .unwrap_or(false),
+                    is_public: item
+                        .get("hulunote-databases/is-public")
+                        .and_then(|v| v.as_bool())
+                        .unwrap_or(false),
                 });
             }
         }
@@ -360,7 +860,41 @@ impl ApiClient {
         out
     }
 
+    /// The database echoed by `new-database`, either under `database` or at the top level.
+    ///
+    /// Accepts namespaced (`hulunote-databases/id`) or plain (`id`) keys; only the id is required.
+    pub(crate) fn parse_create_database_response(data: serde_json::Value) -> Option<Database> {
+        let item = data.get("database").unwrap_or(&data);
+        let get_s = |k: &str| {
+            [format!("hulunote-databases/{k}"), k.to_string()]
+                .iter()
+                .find_map(|key| item.get(key).and_then(|v| v.as_str()))
+                .map(|s| s.to_string())
+        };
+
+        let id = get_s("id").filter(|id| !id.trim().is_empty())?;
+        Some(Database {
+            id,
+            name: get_s("name").unwrap_or_default(),
+            description: get_s("description").unwrap_or_default(),
+            created_at: get_s("created-at").unwrap_or_default(),
+            updated_at: get_s("updated-at").unwrap_or_default(),
+            is_default: false,
+            is_public: false,
+        })
+    }
+
     pub(crate) fn parse_note_list_response(data: serde_json::Value) -> Vec<Note> {
+        Self::parse_note_list_items(data, false)
+    }
+
+    /// Only the soft-deleted notes of a list fetched with `include-deleted`.
+    pub(crate) fn parse_deleted_note_list_response(data: serde_json::Value) -> Vec<Note> {
+        Self::parse_note_list_items(data, true)
+    }
+
+    /// Notes whose `is-delete` flag (missing = not deleted) equals `deleted`.
+    fn parse_note_list_items(data: serde_json::Value, deleted: bool) -> Vec<Note> {
         let list = data
             .get("note-list")
             .and_then(|v| v.as_array())
@@ -373,8 +907,12 @@ impl ApiClient {
 
             let id = get_s("hulunote-notes/id").unwrap_or_default();
             let database_id = get_s("hulunote-notes/database-id").unwrap_or_default();
+            let is_delete = ["hulunote-notes/is-delete", "is-delete"]
+                .iter()
+                .find_map(|k| item.get(*k).and_then(|v| v.as_bool()))
+                .unwrap_or(false);
 
-            if !id.trim().is_empty() && !database_id.trim().is_empty() {
+            if !id.trim().is_empty() && !database_id.trim().is_empty() && is_delete == deleted {
                 out.push(Note {
                     id,
                     database_id,
@@ -389,9 +927,32 @@ impl ApiClient {
         out
     }
 
+    /// Accepts `{"content": ".."}`, `{"hulunote-notes/content": ".."}` or a bare string.
+    pub(crate) fn parse_note_content_response(data: serde_json::Value) -> String {
+        if let Some(s) = data.as_str() {
+            return s.to_string();
+        }
+        ["content", "hulunote-notes/content"]
+            .iter()
+            .find_map(|k| data.get(*k).and_then(|v| v.as_str()))
+            .unwrap_or_default()
+            .to_string()
+    }
+
+    /// Prose content of a note, stored separately from its outline (usually empty).
+    pub async fn get_note_content(&self, note_id: &str) -> ApiResult<String> {
+        let data: serde_json::Value = self
+            .post_json(
+                "/hulunote/get-note-content",
+                Some(&serde_json::json!({ "note-id": note_id })),
+            )
+            .await?;
+        Ok(Self::parse_note_content_response(data))
+    }
+
     pub async fn get_all_note_list(&self, database_id: &str) -> ApiResult<Vec<Note>> {
         let data: serde_json::Value = self
-            .request_api(
+            .post_json(
                 "/hulunote/get-all-note-list",
                 Some(&serde_json::json!({ "database-id": database_id })),
             )
@@ -399,10 +960,97 @@ impl ApiClient {
         Ok(Self::parse_note_list_response(data))
     }
 
-    pub async fn get_database_list(&mut self) -> Result<Vec<Database>, String> {
+    /// `get_all_note_list` without the auth header; only works for a public database
+    /// (401/403 otherwise).
+    pub async fn get_public_note_list(&self, database_id: &str) -> ApiResult<Vec<Note>> {
+        self.anonymous().get_all_note_list(database_id).await
+    }
+
+    /// Soft-deleted notes of a database, for the Trash view.
+    pub async fn get_deleted_note_list(&self, database_id: &str) -> ApiResult<Vec<Note>> {
         let data: serde_json::Value = self
-            .request(
-                "POST",
+            .post_json(
+                "/hulunote/get-all-note-list",
+                Some(&serde_json::json!({ "database-id": database_id, "include-deleted": true })),
+            )
+            .await?;
+        Ok(Self::parse_deleted_note_list_response(data))
+    }
+
+    /// One page of notes (1-based `page`). Same response shape as `get_all_note_list`.
+    pub async fn get_note_list(
+        &self,
+        database_id: &str,
+        page: i32,
+        page_size: i32,
+    ) -> ApiResult<Vec<Note>> {
+        let data: serde_json::Value = self
+            .post_json(
+                "/hulunote/get-note-list",
+                Some(&GetNoteListRequest {
+                    database_id: database_id.to_string(),
+                    page,
+                    page_size,
+                }),
+            )
+            .await?;
+        Ok(Self::parse_note_list_response(data))
+    }
+
+    /// Notes of a database whose block content matches `query` (full-text, server-side).
+    /// Same response shape as `get_all_note_list`.
+    pub async fn search_notes(&self, database_id: &str, query: &str) -> ApiResult<Vec<Note>> {
+        let data: serde_json::Value = self
+            .post_json(
+                "/hulunote/search-notes",
+                Some(&serde_json::json!({ "database-id": database_id, "query": query })),
+            )
+            .await?;
+        Ok(Self::parse_note_list_response(data))
+    }
+
+    /// The signed-in account, e.g. to pick up a username changed on another device.
+    pub async fn get_account_info(&self) -> ApiResult<AccountInfo> {
+        let data: serde_json::Value = self
+            .post_json(
+                "/hulunote/get-account-info",
+                Some(&serde_json::json!({})),
+            )
+            .await?;
+        Self::parse_account_info_response(data)
+            .ok_or_else(|| ApiError::Parse("account info without id, username or email".into()))
+    }
+
+    /// The account under `hulunote` (as in the login response) or at the top level.
+    pub(crate) fn parse_account_info_response(data: serde_json::Value) -> Option<AccountInfo> {
+        let item = data
+            .get("hulunote")
+            .or_else(|| data.get("account"))
+            .unwrap_or(&data)
+            .clone();
+        let info = AccountInfo { extra: item };
+        let known = info.id().is_some() || info.username().is_some() || info.email().is_some();
+        known.then_some(info)
+    }
+
+    /// Reachability probe (no token needed): any HTTP answer, even 401, means the server is
+    /// there. Only network failures (server down, wrong URL, CORS) and timeouts are errors.
+    pub async fn ping(&self) -> ApiResult<()> {
+        let res = self
+            .post_json::<serde_json::Value>(
+                "/hulunote/get-database-list",
+                Some(&serde_json::json!({})),
+            )
+            .await;
+        match res {
+            Err(e @ (ApiError::Network(_) | ApiError::TimedOut | ApiError::Cancelled)) => Err(e),
+            _ => Ok(()),
+        }
+    }
+
+    pub async fn get_database_list(&mut self) -> ApiResult<Vec<Database>> {
+        let data: serde_json::Value = self
+            .post_json(
                 "/hulunote/get-database-list",
                 Some(&serde_json::json!({})),
             )
@@ -414,37 +1062,89 @@ impl ApiClient {
         &self,
         database_name: &str,
         description: &str,
-    ) -> Result<serde_json::Value, String> {
-        self.request(
-            "POST",
-            "/hulunote/new-database",
-            Some(&CreateDatabaseRequest {
-                database_name: database_name.to_string(),
-                description: description.to_string(),
-            }),
+    ) -> ApiResult<Option<Database>> {
+        let data: serde_json::Value = self
+            .post_json(
+                "/hulunote/new-database",
+                Some(&CreateDatabaseRequest {
+                    database_name: database_name.to_string(),
+                    description: description.to_string(),
+                }),
+            )
+            .await?;
+        Ok(Self::parse_create_database_response(data))
+    }
+
+    pub async fn rename_database(&self, database_id: &str, name: &str) -> ApiResult<()> {
+        self.post_write(
+            "/hulunote/update-database",
+            &UpdateDatabaseRequest {
+                database_id: Some(database_id.to_string()),
+                id: None,
+                db_name: Some(name.to_string()),
+                description: None,
+                is_public: None,
+                is_default: None,
+                is_delete: None,
+            },
         )
         .await
     }
 
-    pub async fn rename_database(&self, database_id: &str, name: &str) -> Result<(), String> {
-        self.request::<()>(
-            "POST",
+    pub async fn update_database_description(
+        &self,
+        database_id: &str,
+        description: &str,
+    ) -> ApiResult<()> {
+        self.post_write(
             "/hulunote/update-database",
-            Some(&UpdateDatabaseRequest {
+            &UpdateDatabaseRequest {
                 database_id: Some(database_id.to_string()),
                 id: None,
-                db_name: Some(name.to_string()),
+                db_name: None,
+                description: Some(description.to_string()),
                 is_public: None,
                 is_default: None,
                 is_delete: None,
-            }),
+            },
+        )
+        .await
+    }
+
+    pub async fn set_database_default(&self, database_id: &str, is_default: bool) -> ApiResult<()> {
+        self.post_write(
+            "/hulunote/update-database",
+            &UpdateDatabaseRequest {
+                database_id: Some(database_id.to_string()),
+                id: None,
+                db_name: None,
+                description: None,
+                is_public: None,
+                is_default: Some(is_default),
+                is_delete: None,
+            },
+        )
+        .await
+    }
+
+    pub async fn set_database_public(&self, database_id: &str, is_public: bool) -> ApiResult<()> {
+        self.post_write(
+            "/hulunote/update-database",
+            &UpdateDatabaseRequest {
+                database_id: Some(database_id.to_string()),
+                id: None,
+                db_name: None,
+                description: None,
+                is_public: Some(is_public),
+                is_default: None,
+                is_delete: None,
+            },
         )
         .await
     }
 
-    pub async fn delete_database_by_id(&self, database_id: &str) -> Result<(), String> {
-        self.request(
-            "POST",
+    pub async fn delete_database_by_id(&self, database_id: &str) -> ApiResult<()> {
+        self.post_json(
             "/hulunote/delete-database",
             Some(&DeleteDatabaseRequest {
                 database_id: Some(database_id.to_string()),
@@ -454,9 +1154,8 @@ impl ApiClient {
         .await
     }
 
-    pub async fn create_note(&self, database_id: &str, title: &str) -> Result<Note, String> {
-        let data: serde_json::Value = self.request(
-            "POST",
+    pub async fn create_note(&self, database_id: &str, title: &str) -> ApiResult<Note> {
+        let data: serde_json::Value = self.post_json(
             "/hulunote/new-note",
             Some(&CreateNoteRequest {
                 database_id: database_id.to_string(),
@@ -465,9 +1164,27 @@ impl ApiClient {
         )
         .await?;
 
-        // Backend response has been observed with different shapes; accept a few common forms.
-        let id = data
-            .get("note")
+        let id = Self::note_id_from_response(&data);
+        if id.trim().is_empty() {
+            return Err(ApiError::Parse(format!(
+                "Create note succeeded but response is missing note id: {}",
+                data
+            )));
+        }
+
+        Ok(Note {
+            id,
+            database_id: database_id.to_string(),
+            title: title.to_string(),
+            content: String::new(),
+            created_at: String::new(),
+            updated_at: String::new(),
+        })
+    }
+
+    /// Backend response has been observed with different shapes; accept a few common forms.
+    pub(crate) fn note_id_from_response(data: &serde_json::Value) -> String {
+        data.get("note")
             .and_then(|n| {
                 n.get("hulunote-notes/id")
                     .or_else(|| n.get("id"))
@@ -478,37 +1195,281 @@ impl ApiClient {
             .or_else(|| data.get("id"))
             .and_then(|v| v.as_str())
             .unwrap_or_default()
-            .to_string();
+            .to_string()
+    }
 
-        if id.trim().is_empty() {
-            return Err(format!(
-                "Create note succeeded but response is missing note id: {}",
-                data
-            ));
+    /// Copy a note and its outline. Uses `duplicate-note` when the backend has it,
+    /// otherwise copies client-side (see `copy_note`), reporting `(copied, total)` blocks.
+    pub async fn duplicate_note(
+        &self,
+        source: &Note,
+        on_progress: &dyn Fn(usize, usize),
+    ) -> ApiResult<Note> {
+        match self
+            .post_json::<serde_json::Value>(
+                "/hulunote/duplicate-note",
+                Some(&serde_json::json!({ "note-id": source.id })),
+            )
+            .await
+        {
+            Ok(data) => {
+                let id = Self::note_id_from_response(&data);
+                if id.trim().is_empty() {
+                    return Err(ApiError::Parse(format!(
+                        "Duplicate note succeeded but response is missing note id: {}",
+                        data
+                    )));
+                }
+                let title = data
+                    .get("note")
+                    .and_then(|n| n.get("hulunote-notes/title"))
+                    .and_then(|v| v.as_str())
+                    .map(|s| s.to_string())
+                    .unwrap_or_else(|| duplicate_note_title(&source.title));
+                Ok(Note {
+                    id,
+                    database_id: source.database_id.clone(),
+                    title,
+                    content: String::new(),
+                    created_at: String::new(),
+                    updated_at: String::new(),
+                })
+            }
+            // Endpoint not available on this backend.
+            Err(ApiError::NotFound) => {
+                let title = duplicate_note_title(&source.title);
+                self.copy_note(source, &source.database_id, &title, on_progress, &|_| {})
+                    .await
+            }
+            Err(e) => Err(e),
         }
+    }
 
-        Ok(Note {
-            id,
-            database_id: database_id.to_string(),
-            title: title.to_string(),
-            content: String::new(),
-            created_at: String::new(),
-            updated_at: String::new(),
+    /// Move a note to another database by reassigning its `database-id`. The note keeps its
+    /// id (and so its blocks, pins and lock); the title gets a suffix on collision in the
+    /// target, like daily notes. Offline the move joins the pending queue (the flag is
+    /// `true`), checked for collisions against `cached_target_notes`; without that list it
+    /// fails like any other offline request.
+    pub async fn move_note(
+        &self,
+        source: &Note,
+        target_db_id: &str,
+        cached_target_notes: Option<&[Note]>,
+    ) -> ApiResult<(Note, bool)> {
+        let queued = self.pending.is_some() && browser_offline();
+        let mut existing = if queued {
+            cached_target_notes.ok_or_else(ApiError::offline)?.to_vec()
+        } else {
+            self.get_all_note_list(target_db_id).await?
+        };
+        // A retried move that went through the first time must not collide with itself.
+        existing.retain(|n| n.id != source.id);
+        let title = next_available_daily_note_title_for_date(&source.title, &existing);
+        self.update_note_metadata(UpdateNoteRequest {
+            note_id: source.id.clone(),
+            title: (title != source.title).then(|| title.clone()),
+            database_id: Some(target_db_id.to_string()),
         })
+        .await?;
+        let note = Note {
+            database_id: target_db_id.to_string(),
+            title,
+            ..source.clone()
+        };
+        Ok((note, queued))
+    }
+
+    /// Rollback for a move that may have gone through: put the note back in
+    /// `source.database_id` under `source.title`.
+    pub async fn restore_note_location(&self, source: &Note) -> ApiResult<()> {
+        self.update_note_metadata(UpdateNoteRequest {
+            note_id: source.id.clone(),
+            title: Some(source.title.clone()),
+            database_id: Some(source.database_id.clone()),
+        })
+        .await
+    }
+
+    /// Client-side copy: create a note titled `title` in `target_db_id`, then recreate each
+    /// nav (parents first) with its content and order, mapping old parent ids to the new ones.
+    async fn copy_note(
+        &self,
+        source: &Note,
+        target_db_id: &str,
+        title: &str,
+        on_progress: &dyn Fn(usize, usize),
+        on_created: &dyn Fn(&Note),
+    ) -> ApiResult<Note> {
+        let navs = self.get_note_navs(&source.id).await?;
+        let order = nav_copy_order(&navs);
+        // ROOT containers are structure, not blocks the user sees.
+        let total = order
+            .iter()
+            .filter(|n| n.parid != ROOT_CONTAINER_PARENT_ID)
+            .count();
+
+        let note = self.create_note(target_db_id, title).await?;
+        on_created(&note);
+        on_progress(0, total);
+
+        // `new-note` may already create the ROOT container; reuse it instead of adding another.
+        let dest_root = self
+            .get_note_navs(&note.id)
+            .await
+            .ok()
+            .and_then(|xs| {
+                xs.into_iter()
+                    .find(|n| n.parid == ROOT_CONTAINER_PARENT_ID && !n.is_delete)
+            })
+            .map(|n| n.id);
+
+        let mut id_map: std::collections::HashMap<String, String> =
+            std::collections::HashMap::new();
+        let mut copied = 0;
+        for nav in order {
+            let is_root = nav.parid == ROOT_CONTAINER_PARENT_ID;
+            if is_root {
+                if let Some(root) = &dest_root {
+                    id_map.insert(nav.id.clone(), root.clone());
+                    continue;
+                }
+            }
+
+            let parid = if is_root {
+                nav.parid.clone()
+            } else {
+                id_map.get(&nav.parid).cloned().unwrap_or_default()
+            };
+
+            let new_id = self
+                .upsert_nav(CreateOrUpdateNavRequest {
+                    note_id: note.id.clone(),
+                    id: None,
+                    parid: Some(parid),
+                    content: Some(nav.content.clone()),
+                    order: Some(nav.same_deep_order),
+                    is_display: Some(nav.is_display),
+                    is_delete: Some(false),
+                    properties: nav.properties.clone(),
+                })
+                .await
+                .ok()
+                .and_then(Self::parse_upsert_nav_response)
+                .map(|n| n.id);
+
+            let Some(new_id) = new_id else {
+                return Err(ApiError::Incomplete(copy_incomplete_message(copied, total)));
+            };
+            id_map.insert(nav.id.clone(), new_id);
+
+            if !is_root {
+                copied += 1;
+                on_progress(copied, total);
+            }
+        }
+
+        Ok(note)
+    }
+
+    /// Create a note titled `title` and add `template`'s blocks one by one (parents first),
+    /// mapping template ids to the new nav ids. Stops at the first block that fails.
+    pub async fn create_note_from_template(
+        &self,
+        database_id: &str,
+        title: &str,
+        template: &NoteTemplate,
+    ) -> ApiResult<Note> {
+        let note = self.create_note(database_id, title).await?;
+
+        // Top-level blocks go under the ROOT container (same fallback as the start node).
+        let root = self
+            .get_note_navs(&note.id)
+            .await
+            .ok()
+            .and_then(|xs| {
+                xs.into_iter()
+                    .find(|n| n.parid == ROOT_CONTAINER_PARENT_ID && !n.is_delete)
+            })
+            .map(|n| n.id)
+            .unwrap_or_else(|| ROOT_CONTAINER_PARENT_ID.to_string());
+
+        let order = template_nav_order(&template.navs);
+        let total = order.len();
+        let mut id_map: std::collections::HashMap<&str, String> =
+            std::collections::HashMap::new();
+        for (added, nav) in order.into_iter().enumerate() {
+            let parid = id_map
+                .get(nav.parid.as_str())
+                .cloned()
+                .unwrap_or_else(|| root.clone());
+
+            let incomplete = || {
+                ApiError::Incomplete(format!(
+                    "Created the note, but only {added} of {total} template blocks were added."
+                ))
+            };
+            let resp = self
+                .upsert_nav(CreateOrUpdateNavRequest {
+                    note_id: note.id.clone(),
+                    id: None,
+                    parid: Some(parid),
+                    content: Some(nav.content.clone()),
+                    order: Some(nav.same_deep_order),
+                    is_display: Some(true),
+                    is_delete: Some(false),
+                    properties: None,
+                })
+                .await
+                .map_err(|e| match e {
+                    ApiError::Unauthorized => e,
+                    _ => incomplete(),
+                })?;
+            let new_id = Self::parse_upsert_nav_response(resp)
+                .map(|n| n.id)
+                .ok_or_else(incomplete)?;
+            if !nav.id.is_empty() {
+                id_map.insert(nav.id.as_str(), new_id);
+            }
+        }
+
+        Ok(note)
+    }
+
+    /// Update a note's title and/or database (see `UpdateNoteRequest`).
+    pub async fn update_note_metadata(&self, req: UpdateNoteRequest) -> ApiResult<()> {
+        self.post_write("/hulunote/update-hulunote-note", &req).await
+    }
+
+    pub async fn update_note_title(&self, note_id: &str, title: &str) -> ApiResult<()> {
+        self.update_note_metadata(UpdateNoteRequest {
+            note_id: note_id.to_string(),
+            title: Some(title.to_string()),
+            database_id: None,
+        })
+        .await
+    }
+
+    /// Soft-delete a note (same endpoint as the title update).
+    pub async fn delete_note(&self, note_id: &str) -> ApiResult<()> {
+        self.post_write(
+            "/hulunote/update-hulunote-note",
+            &serde_json::json!({ "note-id": note_id, "is-delete": true }),
+        )
+        .await
     }
 
-    pub async fn update_note_title(&self, note_id: &str, title: &str) -> Result<(), String> {
-        self.request::<()>(
-            "POST",
+    /// Undo `delete_note`.
+    pub async fn restore_note(&self, note_id: &str) -> ApiResult<()> {
+        self.post_write(
             "/hulunote/update-hulunote-note",
-            Some(&serde_json::json!({ "note-id": note_id, "title": title })),
+            &serde_json::json!({ "note-id": note_id, "is-delete": false }),
         )
         .await
     }
 
     pub async fn get_note_navs(&self, note_id: &str) -> ApiResult<Vec<Nav>> {
         let data: serde_json::Value = self
-            .request_api(
+            .post_json(
                 "/hulunote/get-note-navs",
                 Some(&GetNoteNavsRequest {
                     note_id: note_id.to_string(),
@@ -518,21 +1479,72 @@ impl ApiClient {
         Ok(Self::parse_nav_list_response(data))
     }
 
+    /// `get_note_navs` without the auth header, for a note of a public database.
+    pub async fn get_public_note_navs(&self, note_id: &str) -> ApiResult<Vec<Nav>> {
+        self.anonymous().get_note_navs(note_id).await
+    }
+
     pub async fn get_all_navs(&self, database_id: &str) -> ApiResult<Vec<Nav>> {
         let data: serde_json::Value = self
-            .request_api(
+            .post_json(
                 "/hulunote/get-all-navs",
                 Some(&serde_json::json!({ "database-id": database_id })),
             )
             .await?;
-        Ok(Self::parse_nav_list_response(data))
+        Ok(Self::parse_all_navs_response(data))
+    }
+
+    /// Fetch every nav in a database (single request) and group them by `note_id`.
+    pub async fn get_all_navs_for_db(
+        &self,
+        database_id: &str,
+    ) -> ApiResult<std::collections::HashMap<String, Vec<Nav>>> {
+        let navs = self.get_all_navs(database_id).await?;
+        let mut by_note: std::collections::HashMap<String, Vec<Nav>> =
+            std::collections::HashMap::new();
+        for n in navs.into_iter() {
+            by_note.entry(n.note_id.clone()).or_default().push(n);
+        }
+        Ok(by_note)
+    }
+
+    /// One-line preview (first top-level block with text) per note id for a whole database,
+    /// from a single `get_all_navs` request. Notes without text are left out.
+    pub async fn get_note_previews(
+        &self,
+        database_id: &str,
+    ) -> ApiResult<std::collections::HashMap<String, String>> {
+        let navs = self.get_all_navs(database_id).await?;
+        Ok(note_previews(&navs))
     }
 
     pub async fn upsert_nav(
         &self,
         req_body: CreateOrUpdateNavRequest,
     ) -> ApiResult<serde_json::Value> {
-        self.request_api("/hulunote/create-or-update-nav", Some(&req_body)).await
+        self.post_json("/hulunote/create-or-update-nav", Some(&req_body)).await
+    }
+
+    /// Clear a nav's `is-delete` flag, optionally moving it under `parid` at `order`.
+    pub async fn restore_nav(
+        &self,
+        note_id: &str,
+        nav_id: &str,
+        reattach: Option<(String, f32)>,
+    ) -> ApiResult<()> {
+        let (parid, order) = reattach.unzip();
+        self.upsert_nav(CreateOrUpdateNavRequest {
+            note_id: note_id.to_string(),
+            id: Some(nav_id.to_string()),
+            parid,
+            content: None,
+            order,
+            is_display: None,
+            is_delete: Some(false),
+            properties: None,
+        })
+        .await
+        .map(|_| ())
     }
 
     pub async fn signup(
@@ -541,9 +1553,8 @@ impl ApiClient {
         username: &str,
         password: &str,
         registration_code: &str,
-    ) -> Result<SignupResponse, String> {
-        self.request(
-            "POST",
+    ) -> ApiResult<SignupResponse> {
+        self.post_json(
             "/login/web-signup",
             Some(&SignupRequest {
                 email: email.to_string(),
@@ -560,8 +1571,35 @@ impl ApiClient {
         Self::clear_storage();
     }
 
-    pub fn is_authenticated(&self) -> bool {
-        self.token.is_some()
+    /// When the token expires (ms since epoch), if it is a JWT with an `exp` claim.
+    pub fn token_expiry_ms(&self) -> Option<i64> {
+        self.token.as_deref().and_then(jwt_expiry_ms)
+    }
+
+    /// Whether there is a token that has not expired at `now_ms`.
+    /// Tokens without a readable `exp` are assumed valid; the backend has the final say.
+    pub fn has_valid_token_at(&self, now_ms: i64) -> bool {
+        self.token.is_some() && self.token_expiry_ms().is_none_or(|exp| exp > now_ms)
+    }
+
+    pub fn has_valid_token(&self) -> bool {
+        self.has_valid_token_at(now_ms())
+    }
+
+    /// `get-all-navs` returns the same nav shape as `get-note-navs`; accept the list under
+    /// `nav-list` (canonical), `navs`, or as a bare array.
+    pub(crate) fn parse_all_navs_response(data: serde_json::Value) -> Vec<Nav> {
+        let list = if data.is_array() {
+            data
+        } else if let Some(v) = data.get("nav-list").filter(|v| v.is_array()) {
+            v.clone()
+        } else {
+            data.get("navs")
+                .filter(|v| v.is_array())
+                .cloned()
+                .unwrap_or_default()
+        };
+        Self::parse_nav_list_response(serde_json::json!({ "nav-list": list }))
     }
 
     pub(crate) fn parse_nav_list_response(data: serde_json::Value) -> Vec<Nav> {
@@ -580,56 +1618,77 @@ impl ApiClient {
                 continue;
             }
 
-            let get_s = |k: &str| item.get(k).and_then(|v| v.as_str()).map(|s| s.to_string());
-            let get_f = |k: &str| item.get(k).and_then(|v| v.as_f64());
-            let get_b = |k: &str| item.get(k).and_then(|v| v.as_bool());
+            // The list is per note: a nav without a note id is unusable.
+            let nav = Self::nav_from_value(&item);
+            if let Some(nav) = nav.filter(|n| !n.note_id.trim().is_empty()) {
+                out.push(nav);
+            }
+        }
+
+        out
+    }
+
+    /// `create-or-update-nav` has been observed returning the nav directly, wrapped in
+    /// `nav`, or with namespaced keys (`hulunote-navs/id`). `None` when no id can be found.
+    pub(crate) fn parse_upsert_nav_response(data: serde_json::Value) -> Option<Nav> {
+        let item = data.get("nav").filter(|n| n.is_object()).unwrap_or(&data);
+        if let Ok(nav) = serde_json::from_value::<Nav>(item.clone()) {
+            if !nav.id.trim().is_empty() {
+                return Some(nav);
+            }
+        }
+        Self::nav_from_value(item)
+    }
 
-            let id = get_s("id")
-                .or_else(|| get_s("hulunote-navs/id"))
-                .unwrap_or_default();
+    /// A nav from an object with plain or `hulunote-navs/` keys; `None` without an id.
+    fn nav_from_value(item: &serde_json::Value) -> Option<Nav> {
+        let get_s = |k: &str| item.get(k).and_then(|v| v.as_str()).map(|s| s.to_string());
+        let get_f = |k: &str| item.get(k).and_then(|v| v.as_f64());
+        let get_b = |k: &str| item.get(k).and_then(|v| v.as_bool());
 
-            let note_id = get_s("note-id")
-                .or_else(|| get_s("hulunote-navs/note-id"))
-                .unwrap_or_default();
+        let id = get_s("id")
+            .or_else(|| get_s("hulunote-navs/id"))
+            .filter(|id| !id.trim().is_empty())?;
 
-            let parid = get_s("parid")
-                .or_else(|| get_s("hulunote-navs/parid"))
-                .unwrap_or_default();
+        let note_id = get_s("note-id")
+            .or_else(|| get_s("hulunote-navs/note-id"))
+            .unwrap_or_default();
 
-            let same_deep_order = get_f("same-deep-order")
-                .or_else(|| get_f("hulunote-navs/same-deep-order"))
-                .unwrap_or(0.0) as f32;
+        let parid = get_s("parid")
+            .or_else(|| get_s("hulunote-navs/parid"))
+            .unwrap_or_default();
 
-            let content = get_s("content")
-                .or_else(|| get_s("hulunote-navs/content"))
-                .unwrap_or_default();
+        let same_deep_order = get_f("same-deep-order")
+            .or_else(|| get_f("hulunote-navs/same-deep-order"))
+            .unwrap_or(0.0) as f32;
 
-            let is_display = get_b("is-display")
-                .or_else(|| get_b("hulunote-navs/is-display"))
-                .unwrap_or(true);
+        let content = get_s("content")
+            .or_else(|| get_s("hulunote-navs/content"))
+            .unwrap_or_default();
 
-            let is_delete = get_b("is-delete")
-                .or_else(|| get_b("hulunote-navs/is-delete"))
-                .unwrap_or(false);
+        let is_display = get_b("is-display")
+            .or_else(|| get_b("hulunote-navs/is-display"))
+            .unwrap_or(true);
 
-            if !id.trim().is_empty() && !note_id.trim().is_empty() {
-                let properties = get_s("properties")
-                    .or_else(|| get_s("hulunote-navs/properties"))
-                    .filter(|s| !s.trim().is_empty());
+        let is_delete = get_b("is-delete")
+            .or_else(|| get_b("hulunote-navs/is-delete"))
+            .unwrap_or(false);
 
-                out.push(Nav {
-                    id,
-                    note_id,
-                    parid,
-                    same_deep_order,
-                    content,
-                    is_display,
-                    is_delete,
-                    properties,
-                });
-            }
-        }
+        let properties = get_s("properties")
+            .or_else(|| get_s("hulunote-navs/properties"))
+            .filter(|s| !s.trim().is_empty());
 
-        out
+        Some(Nav {
+            id,
+            note_id,
+            parid,
+            same_deep_order,
+            content,
+            is_display,
+            is_delete,
+            properties,
+            created_at: get_s("created-at").or_else(|| get_s("hulunote-navs/created-at")),
+            updated_at: get_s("updated-at").or_else(|| get_s("hulunote-navs/updated-at")),
+        })
     }
 }
@@ -1,17 +1,69 @@
 use crate::pages::{
-    DbHomePage, LoginPage, NotePage, RegistrationPage, RootAuthed, RootPage, SearchPage,
-    SettingsPage, UnreferencedPages,
+    DailyNotesPage, DbHomePage, LoginPage, NotePage, PublicDatabasePage, RegistrationPage,
+    RootAuthed, RootPage, SearchPage, SettingsPage, TagPage, TrashPage, UnreferencedPages,
 };
+use crate::models::Theme;
 use crate::state::{AppContext, AppState};
+use crate::storage::save_user_to_storage;
+use crate::util::{apply_theme, PREFERS_DARK_QUERY};
+use leptos::ev;
 use leptos::prelude::*;
+use leptos::task::spawn_local;
 use leptos_router::components::{Route, Router, Routes};
 use leptos_router::path;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::JsCast;
 
 #[component]
 pub fn App() -> impl IntoView {
     let ctx = AppContext(AppState::new());
     provide_context(ctx.clone());
-    provide_context(crate::state::NoteSyncController::new(ctx));
+    provide_context(crate::state::NoteSyncController::new(ctx.clone()));
+
+    // Theme: re-apply on preference changes, and follow the OS scheme while on `System`.
+    let theme = ctx.0.theme;
+    Effect::new(move |_| apply_theme(theme.get()));
+    if let Some(mq) =
+        web_sys::window().and_then(|w| w.match_media(PREFERS_DARK_QUERY).ok().flatten())
+    {
+        let cb = Closure::<dyn FnMut()>::new(move || {
+            if theme.get_untracked() == Theme::System {
+                apply_theme(Theme::System);
+            }
+        });
+        let _ = mq.add_event_listener_with_callback("change", cb.as_ref().unchecked_ref());
+        // App lifetime listener.
+        cb.forget();
+    }
+
+    // Connectivity: the offline banner and the sync queue both key off this signal.
+    let is_online = ctx.0.is_online;
+    let api_client = ctx.0.api_client;
+    // App lifetime listeners; the handles are intentionally never removed.
+    let _ = window_event_listener(ev::online, move |_ev: web_sys::Event| {
+        is_online.set(true);
+        // Send the writes queued while offline.
+        let client = api_client.get_untracked();
+        spawn_local(async move { client.replay_pending_requests().await });
+    });
+    let _ = window_event_listener(ev::offline, move |_ev: web_sys::Event| is_online.set(false));
+
+    // Refresh the stored account once per session token, so a username changed elsewhere shows
+    // up without logging in again. Best-effort: on failure the stored copy stays.
+    let current_user = ctx.0.current_user;
+    Effect::new(move |prev: Option<Option<String>>| {
+        let token = api_client.with(|c| c.token.clone().filter(|_| c.has_valid_token()));
+        if token.is_some() && prev.as_ref() != Some(&token) {
+            let client = api_client.get_untracked();
+            spawn_local(async move {
+                if let Ok(user) = client.get_account_info().await {
+                    save_user_to_storage(&user);
+                    current_user.set(Some(user));
+                }
+            });
+        }
+        token
+    });
 
     // IMPORTANT:
     // - Leptos CSR requires the `csr` feature on `leptos`.
@@ -21,6 +73,9 @@ pub fn App() -> impl IntoView {
             <Routes fallback=|| view! { <div class="px-4 py-8 text-xs text-muted-foreground">"Not found"</div> }>
                 <Route path=path!("login") view=LoginPage />
                 <Route path=path!("signup") view=RegistrationPage />
+                // Read-only viewer of a public database; no login required.
+                <Route path=path!("public/:db_id") view=PublicDatabasePage />
+                <Route path=path!("public/:db_id/note/:note_id") view=PublicDatabasePage />
                 <Route path=path!("db/:db_id") view=move || view! {
                     <RootAuthed>
                         <DbHomePage />
@@ -36,11 +91,26 @@ pub fn App() -> impl IntoView {
                         <NotePage />
                     </RootAuthed>
                 } />
+                <Route path=path!("db/:db_id/daily") view=move || view! {
+                    <RootAuthed>
+                        <DailyNotesPage />
+                    </RootAuthed>
+                } />
                 <Route path=path!("db/:db_id/unreferenced") view=move || view! {
                     <RootAuthed>
                         <UnreferencedPages />
                     </RootAuthed>
                 } />
+                <Route path=path!("db/:db_id/trash") view=move || view! {
+                    <RootAuthed>
+                        <TrashPage />
+                    </RootAuthed>
+                } />
+                <Route path=path!("db/:db_id/tag/:tag") view=move || view! {
+                    <RootAuthed>
+                        <TagPage />
+                    </RootAuthed>
+                } />
                 <Route path=path!("search") view=move || view! {
                     <RootAuthed>
                         <SearchPage />
@@ -0,0 +1,156 @@
+use crate::models::Nav;
+use crate::storage::{load_json_from_storage, save_json_to_storage};
+use crate::util::ROOT_CONTAINER_PARENT_ID;
+use serde::{Deserialize, Serialize};
+
+// Client-side collapse state per note. The backend `is_display` flag is only a default:
+// reopening a note restores the collapsed set the user last saw on this device.
+const COLLAPSE_STATE_KEY: &str = "hulunote_collapsed_blocks";
+
+/// Entries for notes not opened within this window are dropped on the next write.
+pub(crate) const COLLAPSE_STATE_TTL_MS: i64 = 30 * 24 * 60 * 60 * 1000;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub(crate) struct CollapsedBlocks {
+    pub note_id: String,
+    pub collapsed: Vec<String>,
+    pub touched_ms: i64,
+}
+
+/// Ids of navs that have (non-deleted) children and are currently collapsed.
+pub(crate) fn collapsed_block_ids(navs: &[Nav]) -> Vec<String> {
+    let mut out = navs
+        .iter()
+        .filter(|n| !n.is_display && is_collapsible(navs, n))
+        .map(|n| n.id.clone())
+        .collect::<Vec<_>>();
+    out.sort();
+    out
+}
+
+/// `(id, is_display)` changes needed so exactly the navs in `collapsed` are collapsed.
+/// Only navs with children are considered; leaves keep whatever the backend says.
+pub(crate) fn display_state_diff(navs: &[Nav], collapsed: &[String]) -> Vec<(String, bool)> {
+    navs.iter()
+        .filter(|n| is_collapsible(navs, n))
+        .filter_map(|n| {
+            let want_display = !collapsed.iter().any(|id| id == &n.id);
+            (n.is_display != want_display).then(|| (n.id.clone(), want_display))
+        })
+        .collect()
+}
+
+/// Ids of every nav that has children (the "Collapse all" target set).
+fn all_parent_ids(navs: &[Nav]) -> Vec<String> {
+    navs.iter()
+        .filter(|n| is_collapsible(navs, n))
+        .map(|n| n.id.clone())
+        .collect()
+}
+
+// The backend ROOT container is never collapsed: the outline always renders its children.
+fn is_collapsible(navs: &[Nav], n: &Nav) -> bool {
+    !n.is_delete
+        && n.parid != ROOT_CONTAINER_PARENT_ID
+        && navs.iter().any(|c| !c.is_delete && c.parid == n.id)
+}
+
+/// Upsert `note_id`'s entry and drop entries older than the TTL (newest first).
+pub(crate) fn upsert_collapse_entry(
+    mut entries: Vec<CollapsedBlocks>,
+    note_id: &str,
+    collapsed: Vec<String>,
+    now_ms: i64,
+) -> Vec<CollapsedBlocks> {
+    entries.retain(|e| e.note_id != note_id && now_ms - e.touched_ms <= COLLAPSE_STATE_TTL_MS);
+    entries.insert(
+        0,
+        CollapsedBlocks {
+            note_id: note_id.to_string(),
+            collapsed,
+            touched_ms: now_ms,
+        },
+    );
+    entries
+}
+
+pub(crate) fn load_collapsed_blocks(note_id: &str) -> Option<Vec<String>> {
+    if note_id.trim().is_empty() {
+        return None;
+    }
+    load_json_from_storage::<Vec<CollapsedBlocks>>(COLLAPSE_STATE_KEY)?
+        .into_iter()
+        .find(|e| e.note_id == note_id)
+        .map(|e| e.collapsed)
+}
+
+pub(crate) fn save_collapsed_blocks(note_id: &str, collapsed: Vec<String>, now_ms: i64) {
+    if note_id.trim().is_empty() {
+        return;
+    }
+    let entries =
+        load_json_from_storage::<Vec<CollapsedBlocks>>(COLLAPSE_STATE_KEY).unwrap_or_default();
+    save_json_to_storage(
+        COLLAPSE_STATE_KEY,
+        &upsert_collapse_entry(entries, note_id, collapsed, now_ms),
+    );
+}
+
+/// Apply the locally remembered collapse state (if any) to freshly loaded navs.
+pub(crate) fn apply_collapsed_blocks(note_id: &str, navs: &mut [Nav]) {
+    let Some(collapsed) = load_collapsed_blocks(note_id) else {
+        return;
+    };
+    for (id, is_display) in display_state_diff(navs, &collapsed) {
+        if let Some(n) = navs.iter_mut().find(|n| n.id == id) {
+            n.is_display = is_display;
+        }
+    }
+}
+
+/// "Collapse all" / "Expand all": local only, so the backend isn't hit once per block.
+pub(crate) fn set_all_collapsed(navs: &mut [Nav], collapsed: bool) {
+    let target = if collapsed {
+        all_parent_ids(navs)
+    } else {
+        vec![]
+    };
+    for (id, is_display) in display_state_diff(navs, &target) {
+        if let Some(n) = navs.iter_mut().find(|n| n.id == id) {
+            n.is_display = is_display;
+        }
+    }
+}
+
+/// Cmd/Ctrl+Shift+[: collapse the top-level blocks (children of the ROOT container).
+/// Returns the ids whose `is_display` changed, so the caller can persist them.
+pub(crate) fn collapse_all(navs: &mut [Nav]) -> Vec<String> {
+    let root_ids = navs
+        .iter()
+        .filter(|n| n.parid == ROOT_CONTAINER_PARENT_ID)
+        .map(|n| n.id.clone())
+        .collect::<Vec<_>>();
+    let target = navs
+        .iter()
+        .filter(|n| root_ids.contains(&n.parid) && is_collapsible(navs, n) && n.is_display)
+        .map(|n| n.id.clone())
+        .collect::<Vec<_>>();
+    set_display(navs, &target, false)
+}
+
+/// Cmd/Ctrl+Shift+]: expand every block. Returns the ids whose `is_display` changed.
+pub(crate) fn expand_all(navs: &mut [Nav]) -> Vec<String> {
+    let target = navs
+        .iter()
+        .filter(|n| !n.is_display && is_collapsible(navs, n))
+        .map(|n| n.id.clone())
+        .collect::<Vec<_>>();
+    set_display(navs, &target, true)
+}
+
+fn set_display(navs: &mut [Nav], ids: &[String], is_display: bool) -> Vec<String> {
+    for n in navs.iter_mut().filter(|n| ids.contains(&n.id)) {
+        n.is_display = is_display;
+    }
+    ids.to_vec()
+}
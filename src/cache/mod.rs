@@ -1,6 +1,11 @@
+pub(crate) mod collapse_state;
 pub(crate) mod note_snapshot;
 
+pub(crate) use collapse_state::{
+    apply_collapsed_blocks, collapse_all, collapsed_block_ids, expand_all, save_collapsed_blocks,
+    set_all_collapsed,
+};
 pub(crate) use note_snapshot::{
-    load_note_snapshot, mark_navs_deleted_in_snapshot, remove_navs_from_snapshot, save_note_snapshot,
-    swap_tmp_nav_id_in_snapshot,
+    load_note_snapshot, mark_navs_deleted_in_snapshot, remove_navs_from_snapshot,
+    restore_navs_in_snapshot, save_note_snapshot, swap_tmp_nav_id_in_snapshot,
 };
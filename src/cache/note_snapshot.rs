@@ -114,3 +114,31 @@ pub(crate) fn mark_navs_deleted_in_snapshot(db_id: &str, note_id: &str, ids: &[S
         save_note_snapshot(db_id, note_id, snap.title, snap.navs, snap.saved_ms);
     }
 }
+
+/// Put restored navs (undeleted, possibly reattached) back into the offline snapshot, so a
+/// stale tombstone doesn't hide them before the next backend load. Navs the snapshot no
+/// longer has (unsynced blocks dropped on delete) are added back.
+pub(crate) fn restore_navs_in_snapshot(db_id: &str, note_id: &str, restored: &[Nav]) {
+    if db_id.trim().is_empty() || note_id.trim().is_empty() || restored.is_empty() {
+        return;
+    }
+
+    let Some(mut snap) = load_note_snapshot(db_id, note_id) else {
+        return;
+    };
+
+    for r in restored {
+        if let Some(n) = snap.navs.iter_mut().find(|n| n.id == r.id) {
+            n.is_delete = false;
+            n.parid = r.parid.clone();
+            n.same_deep_order = r.same_deep_order;
+        } else {
+            snap.navs.push(Nav {
+                is_delete: false,
+                ..r.clone()
+            });
+        }
+    }
+
+    save_note_snapshot(db_id, note_id, snap.title, snap.navs, snap.saved_ms);
+}
@@ -1,9 +1,13 @@
 mod note;
 
 pub(crate) use note::{
-    apply_nav_meta_overrides, get_due_unsynced_nav_drafts, get_due_unsynced_nav_meta_drafts,
-    get_nav_override, get_title_override, get_unsynced_nav_drafts, list_dirty_notes,
-    load_note_draft, mark_nav_meta_sync_failed, mark_nav_meta_synced, mark_nav_sync_failed,
-    mark_nav_synced, mark_title_synced, mark_title_sync_failed, remove_navs_from_drafts,
-    swap_tmp_nav_id_in_drafts, touch_nav, touch_nav_meta, touch_title, NavMetaDraft,
+    apply_nav_meta_overrides, count_unsynced_nav_drafts, get_due_unsynced_nav_drafts,
+    get_due_unsynced_nav_meta_drafts, get_nav_override, get_title_override,
+    get_unsynced_nav_drafts, list_dirty_notes, load_note_draft, mark_nav_meta_sync_failed,
+    mark_nav_meta_synced, mark_nav_sync_failed, mark_nav_synced, mark_title_sync_failed,
+    mark_title_synced, nav_content_edited_ms, nav_sync_statuses, remove_navs_from_drafts,
+    revert_title_draft, swap_tmp_nav_id_in_drafts, touch_nav, touch_nav_meta, touch_title,
+    NavMetaDraft, NavSyncStatus,
 };
+#[cfg(test)]
+pub(crate) use note::{unsynced_nav_count, FieldDraft, NoteDraft};
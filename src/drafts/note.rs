@@ -2,7 +2,7 @@ use crate::models::Nav;
 use crate::storage::{load_json_from_storage, save_json_to_storage};
 use crate::util::{now_ms, ROOT_CONTAINER_PARENT_ID};
 use serde::{Deserialize, Serialize};
-use std::collections::{BTreeMap, BTreeSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 
 #[derive(Serialize, Deserialize, Clone, Debug, Default)]
 pub(crate) struct FieldDraft {
@@ -119,6 +119,25 @@ pub(crate) fn list_dirty_notes(limit: usize) -> Vec<(String, String)> {
         .collect()
 }
 
+/// Navs of `d` with a content or metadata edit the backend hasn't confirmed yet.
+pub(crate) fn unsynced_nav_count(d: &NoteDraft) -> usize {
+    d.navs
+        .iter()
+        .chain(d.nav_meta.iter())
+        .filter(|(_, f)| f.updated_ms > f.synced_ms)
+        .map(|(id, _)| id)
+        .collect::<BTreeSet<_>>()
+        .len()
+}
+
+/// Unsynced navs across all dirty notes (see `unsynced_nav_count`).
+pub(crate) fn count_unsynced_nav_drafts() -> usize {
+    list_dirty_notes(usize::MAX)
+        .iter()
+        .map(|(db_id, note_id)| unsynced_nav_count(&load_note_draft(db_id, note_id)))
+        .sum()
+}
+
 pub(crate) fn load_note_draft(db_id: &str, note_id: &str) -> NoteDraft {
     if db_id.trim().is_empty() || note_id.trim().is_empty() {
         return NoteDraft::default();
@@ -222,6 +241,45 @@ pub(crate) fn touch_nav_meta(db_id: &str, note_id: &str, nav: &Nav) {
     save_note_draft(&d);
 }
 
+/// Sync state of a nav's local drafts (content and metadata).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum NavSyncStatus {
+    /// Nothing waiting to be sent.
+    Clean,
+    /// Edited locally; the upsert is debounced or in flight.
+    Pending,
+    /// The last upsert failed; the retry worker will try again.
+    Failed,
+}
+
+/// Non-clean navs of a note draft. A nav is `Failed` if either of its drafts failed.
+pub(crate) fn nav_sync_statuses(d: &NoteDraft) -> HashMap<String, NavSyncStatus> {
+    let mut out: HashMap<String, NavSyncStatus> = HashMap::new();
+    for (nav_id, f) in d.navs.iter().chain(d.nav_meta.iter()) {
+        if f.updated_ms <= f.synced_ms {
+            continue;
+        }
+        let status = if f.retry_count > 0 {
+            NavSyncStatus::Failed
+        } else {
+            NavSyncStatus::Pending
+        };
+        let entry = out.entry(nav_id.clone()).or_insert(status);
+        if status == NavSyncStatus::Failed {
+            *entry = status;
+        }
+    }
+    out
+}
+
+/// Local content-edit time of each nav with a content draft (synced or not).
+pub(crate) fn nav_content_edited_ms(d: &NoteDraft) -> HashMap<String, i64> {
+    d.navs
+        .iter()
+        .map(|(nav_id, f)| (nav_id.clone(), f.updated_ms))
+        .collect()
+}
+
 fn update_field_synced(f: &mut FieldDraft, synced_ms: i64) {
     f.synced_ms = f.synced_ms.max(synced_ms);
     f.retry_count = 0;
@@ -243,6 +301,26 @@ pub(crate) fn mark_title_synced(db_id: &str, note_id: &str, synced_ms: i64) {
     index_prune_if_synced(db_id, note_id);
 }
 
+/// Replace the title draft with `title` as already synced (the server rejected a rename and
+/// this is its current title), so it is neither shown over the server title nor retried.
+pub(crate) fn revert_title_draft(db_id: &str, note_id: &str, title: &str) {
+    if db_id.trim().is_empty() || note_id.trim().is_empty() {
+        return;
+    }
+
+    let mut d = load_note_draft(db_id, note_id);
+    let now = now_ms();
+    let mut f = d.title.unwrap_or_default();
+    f.value = title.to_string();
+    f.updated_ms = now;
+    update_field_synced(&mut f, now);
+    d.title = Some(f);
+    d.updated_ms = now;
+    save_note_draft(&d);
+
+    index_prune_if_synced(db_id, note_id);
+}
+
 pub(crate) fn mark_title_sync_failed(db_id: &str, note_id: &str) {
     if db_id.trim().is_empty() || note_id.trim().is_empty() {
         return;
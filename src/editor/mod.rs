@@ -1,18 +1,40 @@
 #[cfg(test)]
 use crate::api::CreateOrUpdateNavRequest;
-use crate::cache::{load_note_snapshot, save_note_snapshot};
+use crate::api::{AbortHandle, ApiError};
+use crate::cache::{
+    apply_collapsed_blocks, collapsed_block_ids, load_note_snapshot, save_note_snapshot,
+    save_collapsed_blocks,
+};
 use crate::components::hooks::use_random::use_random_id_for;
-use crate::components::ui::{Command, CommandItem, CommandList, Spinner};
-use crate::drafts::{apply_nav_meta_overrides, get_nav_override, touch_nav};
-use crate::models::{Nav, Note};
+use crate::components::ui::{
+    Button, ButtonSize, ButtonVariant, Command, CommandItem, CommandList, Spinner,
+};
+use crate::drafts::{
+    apply_nav_meta_overrides, get_nav_override, load_note_draft, nav_content_edited_ms,
+    nav_sync_statuses, touch_nav, NavSyncStatus,
+};
+use crate::markdown::{flatten_inline, parse_inline_markdown, InlineRun};
+use crate::models::{DeletedItemKind, Nav, NavProperties, Note, NoteTemplate};
+use crate::outline::{
+    apply_orders, cmp_order, insert_order_between, rebalance_for_insert, NavTree,
+};
 use crate::state::AppContext;
 use crate::state::NoteSyncController;
-use crate::util::ROOT_CONTAINER_PARENT_ID;
-use crate::wiki::{extract_wiki_links, normalize_roam_page_title, parse_wiki_tokens, WikiToken};
+use crate::util::{
+    collapsed_ancestor_ids, count_note_chars, deleted_navs_payload, expand_template_now,
+    format_edited_ago, nav_edited_ms, nav_id_from_fragment, navs_cache_is_fresh, note_preview,
+    template_outline_text, ROOT_CONTAINER_PARENT_ID,
+};
+use crate::wiki::{
+    cycle_todo, extract_block_refs, extract_tags, extract_wiki_links, find_block,
+    normalize_roam_page_title, parse_wiki_tokens, search_blocks, set_todo_done, split_todo,
+    wiki_link_href, WikiToken,
+};
 use leptos::ev;
 use leptos::html;
 use leptos::prelude::*;
 use leptos::task::spawn_local;
+use leptos_router::hooks::{use_location, use_navigate, use_query_map};
 use wasm_bindgen::closure::Closure;
 use wasm_bindgen::JsCast;
 
@@ -20,6 +42,8 @@ use wasm_bindgen::JsCast;
 struct AcItem {
     title: String,
     is_new: bool,
+    /// Text that replaces the trigger and query, e.g. `[[title]]` or `((nav-id))`.
+    insert: String,
 }
 
 #[derive(Clone)]
@@ -30,9 +54,13 @@ struct AutocompleteCtx {
     ac_index: RwSignal<usize>,
     // Start position (UTF-16 code units) of the `[[` trigger in the current input.
     ac_start_utf16: RwSignal<Option<u32>>,
+    // The open menu completes a `((block ref))` rather than a `[[page]]`.
+    ac_block_mode: RwSignal<bool>,
+    block_navs_loading: RwSignal<bool>,
 
-    // Cache all possible page titles for current DB (notes + wiki links from all navs).
-    titles_cache_db: RwSignal<Option<String>>,
+    // Cache all possible page titles for current DB (notes + wiki links from all navs),
+    // keyed by DB id and `AppState::titles_cache_version` at load time.
+    titles_cache_key: RwSignal<Option<(String, u64)>>,
     titles_cache: RwSignal<Vec<String>>,
     titles_loading: RwSignal<bool>,
 }
@@ -410,15 +438,21 @@ fn ensure_titles_loaded(app_state: &AppContext, ac: &AutocompleteCtx) {
 
     // Treat empty title lists as a valid loaded state.
     // Otherwise, when the backend returns no navs/notes, we would refetch on every keystroke.
-    if ac.titles_cache_db.get_untracked().as_deref() == Some(db_id.as_str()) {
+    // The version only moves when notes or saved `[[links]]` change.
+    let version = app_state.0.titles_cache_version.get_untracked();
+    let key = (db_id.clone(), version);
+    if ac.titles_cache_key.get_untracked().as_ref() == Some(&key) {
         return;
     }
 
     ac.titles_loading.set(true);
-    ac.titles_cache_db.set(Some(db_id.clone()));
+    ac.titles_cache_key.set(Some(key));
 
     let api_client = app_state.0.api_client.get_untracked();
     let notes = app_state.0.notes.get_untracked();
+    // Shared with the backlinks panel; only fetch when no page has loaded this DB yet.
+    let all_navs_cache = app_state.0.all_navs_cache;
+    let cached_navs = all_navs_cache.with_untracked(|m| m.get(&db_id).cloned());
 
     let ac2 = ac.clone();
     spawn_local(async move {
@@ -431,7 +465,19 @@ fn ensure_titles_loaded(app_state: &AppContext, ac: &AutocompleteCtx) {
         }
 
         // 2) Titles referenced via [[...]] across all navs in DB (includes unreferenced pages).
-        if let Ok(all_navs) = api_client.get_all_navs(&db_id).await {
+        let all_navs = match cached_navs {
+            Some(navs) => Some(navs),
+            None => match api_client.get_all_navs(&db_id).await {
+                Ok(navs) => {
+                    all_navs_cache.update(|m| {
+                        m.insert(db_id.clone(), navs.clone());
+                    });
+                    Some(navs)
+                }
+                Err(_) => None,
+            },
+        };
+        if let Some(all_navs) = all_navs {
             for nav in all_navs {
                 if nav.is_delete {
                     continue;
@@ -449,35 +495,88 @@ fn ensure_titles_loaded(app_state: &AppContext, ac: &AutocompleteCtx) {
     });
 }
 
-fn collect_visible_preorder_ids(all: &[Nav]) -> Vec<String> {
-    let root_container_parent_id = ROOT_CONTAINER_PARENT_ID;
+/// Fetch all navs of the current DB into `all_navs_cache` unless already cached.
+///
+/// Used by `((block ref))` autocomplete and rendering; `loading` guards concurrent fetches.
+fn ensure_db_navs_loaded(app_state: &AppContext, loading: RwSignal<bool>) {
+    let db_id = app_state
+        .0
+        .current_database_id
+        .get_untracked()
+        .unwrap_or_default();
+    if db_id.trim().is_empty() || loading.get_untracked() {
+        return;
+    }
 
-    fn children_sorted(all: &[Nav], parid: &str) -> Vec<Nav> {
-        let mut out = all
-            .iter()
-            .filter(|n| !n.is_delete && n.parid == parid)
-            .cloned()
-            .collect::<Vec<_>>();
-        out.sort_by(|a, b| {
-            a.same_deep_order
-                .partial_cmp(&b.same_deep_order)
-                .unwrap_or(std::cmp::Ordering::Equal)
-        });
-        out
+    let all_navs_cache = app_state.0.all_navs_cache;
+    if all_navs_cache.with_untracked(|m| m.contains_key(&db_id)) {
+        return;
     }
 
-    fn collect(all: &[Nav], parid: &str, out: &mut Vec<String>) {
-        for n in children_sorted(all, parid) {
-            out.push(n.id.clone());
-            if n.is_display {
-                collect(all, &n.id, out);
-            }
+    loading.set(true);
+    let api_client = app_state.0.api_client.get_untracked();
+    spawn_local(async move {
+        if let Ok(navs) = api_client.get_all_navs(&db_id).await {
+            all_navs_cache.update(|m| {
+                m.insert(db_id, navs);
+            });
         }
+        loading.set(false);
+    });
+}
+
+/// Store the `#tags` of `content` in the nav's `properties` when they changed.
+///
+/// The meta draft is upserted by the sync controller. The nav's entry in `all_navs_cache`
+/// gets the new properties so the sidebar tags index follows; its content stays as last
+/// fetched (empty for a new block) so `[[link]]` edits still invalidate backlinks.
+fn sync_nav_tags(
+    navs: RwSignal<Vec<Nav>>,
+    app_state: &AppContext,
+    sync: &NoteSyncController,
+    nav_id: &str,
+    content: &str,
+) {
+    if is_tmp_nav_id(nav_id) {
+        return;
+    }
+    let tags = extract_tags(content);
+    let Some(mut nav) = navs.with_untracked(|xs| xs.iter().find(|n| n.id == nav_id).cloned())
+    else {
+        return;
+    };
+
+    let mut props = NavProperties::parse(nav.properties.as_deref());
+    if props.tags == tags {
+        return;
     }
+    props.tags = tags;
+    nav.properties = props.to_json();
 
-    let mut out: Vec<String> = vec![];
-    collect(all, root_container_parent_id, &mut out);
-    out
+    navs.update(|xs| {
+        if let Some(x) = xs.iter_mut().find(|x| x.id == nav_id) {
+            x.properties = nav.properties.clone();
+        }
+    });
+    sync.on_nav_meta_changed(&nav);
+
+    let db_id = app_state
+        .0
+        .current_database_id
+        .get_untracked()
+        .unwrap_or_default();
+    app_state.0.all_navs_cache.update(|m| {
+        let Some(cached) = m.get_mut(&db_id) else {
+            return;
+        };
+        match cached.iter_mut().find(|n| n.id == nav_id) {
+            Some(x) => x.properties = nav.properties.clone(),
+            None => cached.push(Nav {
+                content: String::new(),
+                ..nav.clone()
+            }),
+        }
+    });
 }
 
 fn build_ac_items(titles: &[String], q: &str) -> Vec<AcItem> {
@@ -490,6 +589,7 @@ fn build_ac_items(titles: &[String], q: &str) -> Vec<AcItem> {
         items.push(AcItem {
             title: q.to_string(),
             is_new: true,
+            insert: format!("[[{q}]]"),
         });
     }
 
@@ -501,6 +601,7 @@ fn build_ac_items(titles: &[String], q: &str) -> Vec<AcItem> {
                 continue;
             }
             items.push(AcItem {
+                insert: format!("[[{t}]]"),
                 title: t,
                 is_new: false,
             });
@@ -513,14 +614,68 @@ fn build_ac_items(titles: &[String], q: &str) -> Vec<AcItem> {
     items
 }
 
+/// Rows moved by PageUp/PageDown in the autocomplete menu.
+const AC_PAGE_SIZE: usize = 8;
+
+/// Highlighted autocomplete row after a navigation key, or `None` if `key` doesn't move it.
+///
+/// Arrows wrap around at the ends; Home/End jump to the first/last row and PageUp/PageDown
+/// move by [`AC_PAGE_SIZE`] rows, stopping at the ends.
+pub(crate) fn ac_index_after_key(current: usize, key: &str, len: usize) -> Option<usize> {
+    if len == 0 {
+        return matches!(key, "ArrowDown" | "ArrowUp" | "Home" | "End" | "PageDown" | "PageUp")
+            .then_some(0);
+    }
+    let last = len - 1;
+    let current = current.min(last);
+    let next = match key {
+        "ArrowDown" => {
+            if current == last {
+                0
+            } else {
+                current + 1
+            }
+        }
+        "ArrowUp" => current.checked_sub(1).unwrap_or(last),
+        "Home" => 0,
+        "End" => last,
+        "PageDown" => (current + AC_PAGE_SIZE).min(last),
+        "PageUp" => current.saturating_sub(AC_PAGE_SIZE),
+        _ => return None,
+    };
+    Some(next)
+}
+
+/// `((` autocomplete items: blocks of the current DB whose content matches `q`.
+///
+/// `db_navs` may be stale for the open note, so its blocks come from `live_navs` instead.
+fn build_block_ac_items(
+    db_navs: &[Nav],
+    live_navs: &[Nav],
+    note_id: &str,
+    q: &str,
+    exclude_nav_id: &str,
+) -> Vec<AcItem> {
+    let navs = live_navs
+        .iter()
+        .chain(db_navs.iter().filter(|n| n.note_id != note_id))
+        .cloned()
+        .collect::<Vec<_>>();
+
+    search_blocks(&navs, q, exclude_nav_id, 20)
+        .into_iter()
+        .map(|n| AcItem {
+            title: n.content.split_whitespace().collect::<Vec<_>>().join(" "),
+            is_new: false,
+            insert: format!("(({}))", n.id),
+        })
+        .collect()
+}
+
 pub(crate) fn make_tmp_nav_id(now_ms: u64, rand: u64) -> String {
     format!("tmp-{now_ms}-{rand}")
 }
 
-/// Insert a soft line break at the current selection inside a contenteditable element.
-///
-/// This uses DOM Selection/Range APIs so caret movement is handled by the browser.
-/// Returns true if we inserted a break, false otherwise.
 pub(crate) fn should_exit_edit_on_focusout_related_target(
     related: Option<web_sys::EventTarget>,
 ) -> bool {
@@ -535,6 +690,19 @@ pub(crate) fn should_exit_edit_on_focusout_related_target(
     el.closest(".outline-editor").ok().flatten().is_none()
 }
 
+/// Whether focus left this editor for a different outline editor (several can share a page,
+/// e.g. daily notes).
+fn focus_moved_to_other_editor(ev: &web_sys::FocusEvent) -> bool {
+    let editor_of = |t: Option<web_sys::EventTarget>| {
+        t.and_then(|t| t.dyn_into::<web_sys::Element>().ok())
+            .and_then(|el| el.closest(".outline-editor").ok().flatten())
+    };
+    match (editor_of(ev.current_target()), editor_of(ev.related_target())) {
+        (Some(from), Some(to)) => from != to,
+        _ => false,
+    }
+}
+
 pub(crate) fn should_exit_edit_on_click_target(target: Option<web_sys::EventTarget>) -> bool {
     let Some(t) = target else {
         return false;
@@ -556,7 +724,10 @@ pub(crate) fn should_exit_edit_on_click_target(target: Option<web_sys::EventTarg
     true
 }
 
-#[cfg(test)]
+/// Insert a soft line break at the current selection inside a contenteditable element.
+///
+/// This uses DOM Selection/Range APIs so caret movement is handled by the browser.
+/// Returns true if we inserted a break, false otherwise.
 pub(crate) fn insert_soft_line_break_dom(input_el: &web_sys::HtmlElement) -> bool {
     let _ = input_el.focus();
 
@@ -740,22 +911,18 @@ pub(crate) fn compute_reorder_target(
         return None;
     }
 
-    let dragged = all.iter().find(|n| n.id == dragged_id)?;
-    let target = all.iter().find(|n| n.id == target_id)?;
+    let tree = NavTree::new(all);
+    let dragged = tree.get(dragged_id)?;
+    let target = tree.get(target_id)?;
 
     let new_parid = target.parid.clone();
 
     // Build siblings in target parent, excluding dragged node (since it will move).
-    let mut sibs = all
+    let sibs = tree
+        .all_children(&new_parid)
         .iter()
-        .filter(|n| n.parid == new_parid && n.id != dragged_id)
-        .cloned()
+        .filter(|n| n.id != dragged_id)
         .collect::<Vec<_>>();
-    sibs.sort_by(|a, b| {
-        a.same_deep_order
-            .partial_cmp(&b.same_deep_order)
-            .unwrap_or(std::cmp::Ordering::Equal)
-    });
 
     // Find insertion index relative to target.
     let tidx = sibs.iter().position(|n| n.id == target_id)?;
@@ -774,12 +941,7 @@ pub(crate) fn compute_reorder_target(
         Some(sibs[insert_idx].same_deep_order)
     };
 
-    let new_order = match (prev_order, next_order) {
-        (Some(p), Some(n)) => (p + n) / 2.0,
-        (Some(p), None) => p + 1.0,
-        (None, Some(n)) => n - 1.0,
-        (None, None) => 0.0,
-    };
+    let new_order = insert_order_between(prev_order, next_order);
 
     // No-op move detection: if staying in same parent and order is effectively unchanged, skip.
     if dragged.parid == new_parid && (dragged.same_deep_order - new_order).abs() < f32::EPSILON {
@@ -789,140 +951,1177 @@ pub(crate) fn compute_reorder_target(
     Some((new_parid, new_order))
 }
 
-#[component]
-pub fn OutlineEditor(
-    note_id: impl Fn() -> String + Clone + Send + Sync + 'static,
-    focused_nav_id: RwSignal<Option<String>>,
-) -> impl IntoView {
-    let app_state = expect_context::<AppContext>();
-
-    let navs: RwSignal<Vec<Nav>> = RwSignal::new(vec![]);
-    let loading: RwSignal<bool> = RwSignal::new(false);
-    let error: RwSignal<Option<String>> = RwSignal::new(None);
+/// New `same_deep_order` for moving `nav_id` past its previous (`up`) or next live sibling,
+/// using the same midpoint placement as a drop before/after that sibling. `None` at the
+/// first/last position.
+pub(crate) fn compute_sibling_move(all: &[Nav], nav_id: &str, up: bool) -> Option<f32> {
+    let tree = NavTree::new(all);
+    let neighbor = if up {
+        tree.previous_sibling(nav_id)?
+    } else {
+        tree.next_sibling(nav_id)?
+    };
+    compute_reorder_target(all, nav_id, &neighbor.id, !up).map(|(_, order)| order)
+}
 
-    // Wiki links: opening a missing page does not hit the backend (client-side navigation).
+/// Sibling renumbering to apply before `compute_reorder_target` places `dragged_id` next to
+/// `target_id`, when the midpoint there has run out of f32 precision.
+pub(crate) fn rebalance_for_reorder(
+    all: &[Nav],
+    dragged_id: &str,
+    target_id: &str,
+    insert_after: bool,
+) -> Vec<(String, f32)> {
+    let tree = NavTree::new(all);
+    let Some(target) = tree.get(target_id) else {
+        return vec![];
+    };
+    let after = if insert_after {
+        Some(target_id.to_string())
+    } else {
+        let sibs = tree
+            .all_children(&target.parid)
+            .iter()
+            .filter(|n| n.id != dragged_id)
+            .collect::<Vec<_>>();
+        let Some(idx) = sibs.iter().position(|n| n.id == target_id) else {
+            return vec![];
+        };
+        idx.checked_sub(1).map(|i| sibs[i].id.clone())
+    };
+    rebalance_for_insert(all, &target.parid, after.as_deref(), 1, Some(dragged_id))
+}
 
-    // Editing state
-    let editing_id: RwSignal<Option<String>> = RwSignal::new(None);
-    let editing_value: RwSignal<String> = RwSignal::new(String::new());
-    // Snapshot of the content when we entered edit mode (id, content).
-    // Used to avoid redundant backend saves when the user didn't change anything.
-    let editing_snapshot: RwSignal<Option<(String, String)>> = RwSignal::new(None);
+/// `rebalance_for_reorder` for `compute_sibling_move`.
+pub(crate) fn rebalance_for_sibling_move(
+    all: &[Nav],
+    nav_id: &str,
+    up: bool,
+) -> Vec<(String, f32)> {
+    let tree = NavTree::new(all);
+    let neighbor = if up {
+        tree.previous_sibling(nav_id)
+    } else {
+        tree.next_sibling(nav_id)
+    };
+    match neighbor {
+        Some(neighbor) => rebalance_for_reorder(all, nav_id, &neighbor.id, !up),
+        None => vec![],
+    }
+}
 
-    // Drag state (for highlighting drop targets only while dragging).
-    let dragging_nav_id: RwSignal<Option<String>> = RwSignal::new(None);
-    let drag_over_nav_id: RwSignal<Option<String>> = RwSignal::new(None);
+/// Apply a sibling renumbering (`rebalance_for_insert`) locally and queue it as one batch.
+/// Returns the updated navs, so the insert that follows computes its order from fresh gaps.
+fn apply_renumbered_orders(
+    navs: RwSignal<Vec<Nav>>,
+    sync_sv: StoredValue<NoteSyncController>,
+    renumbered: &[(String, f32)],
+) -> Vec<Nav> {
+    if renumbered.is_empty() {
+        return navs.get_untracked();
+    }
+    navs.update(|xs| apply_orders(xs, renumbered));
+    let all = navs.get_untracked();
+    let changed = all
+        .iter()
+        .filter(|n| renumbered.iter().any(|(id, _)| id == &n.id))
+        .cloned()
+        .collect::<Vec<_>>();
+    let _ = sync_sv.try_with_value(|s| s.on_navs_meta_changed(&changed));
+    all
+}
 
-    let target_cursor_col: RwSignal<Option<u32>> = RwSignal::new(None);
-    let editing_ref: NodeRef<html::Div> = NodeRef::new();
+/// Offer undo for a deleted subtree: `subtree` (preorder, from `all` as it was before the
+/// delete) goes to the snackbar under its first root.
+fn offer_undo_nav_delete(
+    app_state: &AppContext,
+    db_id: &str,
+    note_id: &str,
+    all: &[Nav],
+    subtree: &[String],
+) {
+    let Some(root_id) = subtree.first() else {
+        return;
+    };
+    let deleted = subtree
+        .iter()
+        .filter_map(|id| all.iter().find(|n| &n.id == id).cloned())
+        .collect::<Vec<_>>();
+    app_state.0.push_deleted(
+        DeletedItemKind::Nav,
+        root_id,
+        deleted_navs_payload(db_id, note_id, &deleted),
+    );
+}
 
-    // Autocomplete for `[[...]]` (wiki-style)
-    // - Data source is fixed: existing notes + titles extracted from all nav contents in current DB.
-    // - Supports creating new titles (insert text even if no existing note).
-    let ac_open: RwSignal<bool> = RwSignal::new(false);
-    let ac_query: RwSignal<String> = RwSignal::new(String::new());
-    let ac_items: RwSignal<Vec<AcItem>> = RwSignal::new(vec![]);
-    let ac_index: RwSignal<usize> = RwSignal::new(0);
-    // Start position (UTF-16 code units) of the `[[` trigger in the current input.
-    let ac_start_utf16: RwSignal<Option<u32>> = RwSignal::new(None);
+/// Where a dragged block lands relative to the row it is dropped on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum DropPosition {
+    Before,
+    After,
+    /// Reparent under the target (appended as its last child).
+    Inside,
+}
 
-    // Cache all possible page titles for current DB (notes + wiki links from all navs).
-    let titles_cache_db: RwSignal<Option<String>> = RwSignal::new(None);
-    let titles_cache: RwSignal<Vec<String>> = RwSignal::new(vec![]);
-    let titles_loading: RwSignal<bool> = RwSignal::new(false);
+/// Map the cursor position inside the target row (ratios in 0..=1) to a drop zone.
+///
+/// The right portion and the vertical center of the row mean "inside"; the remaining
+/// top/bottom bands mean before/after (sibling insert).
+pub(crate) fn drop_position_for(rel_x: f64, rel_y: f64) -> DropPosition {
+    if rel_x >= 0.6 || (0.3..=0.7).contains(&rel_y) {
+        DropPosition::Inside
+    } else if rel_y < 0.5 {
+        DropPosition::Before
+    } else {
+        DropPosition::After
+    }
+}
 
-    // Autocomplete recompute effect.
-    // This fixes the first-`[[` case where titles are still loading: we keep the menu open and
-    // populate items as soon as the async title load completes (without requiring extra typing).
-    Effect::new(move |_| {
-        let start = ac_start_utf16.get();
-        if start.is_none() {
-            return;
-        }
+/// Compute (new_parid, new_order) for dropping `dragged_id` as the last child of `target_id`.
+pub(crate) fn compute_reparent_target(
+    all: &[Nav],
+    dragged_id: &str,
+    target_id: &str,
+) -> Option<(String, f32)> {
+    if dragged_id == target_id {
+        return None;
+    }
 
-        let q = ac_query.get();
-        let loading_now = titles_loading.get();
-        let titles_now = titles_cache.get();
+    // Disallow dropping a node into its own subtree.
+    if is_ancestor_of(all, dragged_id, target_id) {
+        return None;
+    }
 
-        if loading_now {
-            ac_open.set(true);
-            // Keep items empty; UI will show a loading row.
-            return;
-        }
+    let dragged = all.iter().find(|n| n.id == dragged_id)?;
+    all.iter().find(|n| n.id == target_id)?;
 
-        let items = build_ac_items(&titles_now, &q);
-        if items.is_empty() {
-            ac_open.set(false);
-            ac_index.set(0);
-            return;
-        }
+    let last_child_order = all
+        .iter()
+        .filter(|n| n.parid == target_id && n.id != dragged_id)
+        .map(|n| n.same_deep_order)
+        .max_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+
+    // Already the last child: nothing to do.
+    if dragged.parid == target_id
+        && last_child_order
+            .map(|o| dragged.same_deep_order > o)
+            .unwrap_or(true)
+    {
+        return None;
+    }
 
-        ac_items.set(items);
-        ac_index.set(0);
-        ac_open.set(true);
-    });
+    Some((target_id.to_string(), last_child_order.unwrap_or(0.0) + 1.0))
+}
 
-    let offline: RwSignal<bool> = RwSignal::new(false);
-    let offline_missing_snapshot: RwSignal<bool> = RwSignal::new(false);
+/// Drop zone for a drag event over an outline row (uses the row's bounding rect).
+fn drop_position_from_event(ev: &web_sys::DragEvent) -> Option<DropPosition> {
+    let rect = ev
+        .current_target()
+        .and_then(|t| t.dyn_into::<web_sys::Element>().ok())?
+        .get_bounding_client_rect();
+    if rect.width() <= 0.0 || rect.height() <= 0.0 {
+        return None;
+    }
+    let rel_x = ((ev.client_x() as f64) - rect.left()) / rect.width();
+    let rel_y = ((ev.client_y() as f64) - rect.top()) / rect.height();
+    Some(drop_position_for(rel_x, rel_y))
+}
 
-    // Load navs when note_id changes.
-    let note_id_for_effect = note_id.clone();
-    Effect::new(move |_| {
-        let id = note_id_for_effect();
-        let db_id_now = app_state
-            .0
-            .current_database_id
-            .get_untracked()
-            .unwrap_or_default();
+/// Per-nav state read from the open note's draft.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct NavDraftInfo {
+    /// Sync state (see `nav_sync_statuses`), for the row status icons.
+    statuses: std::collections::HashMap<String, NavSyncStatus>,
+    /// Local content-edit times, for the row "edited ..." hint.
+    edited_ms: std::collections::HashMap<String, i64>,
+}
 
-        if id.trim().is_empty() {
-            navs.set(vec![]);
-            offline.set(false);
-            offline_missing_snapshot.set(false);
-            return;
-        }
+#[derive(Clone, Copy)]
+struct NavDraftCtx(Memo<NavDraftInfo>);
+
+/// Brief "Maximum nesting depth reached" toast, raised by a refused indent or drop.
+#[derive(Clone, Copy)]
+struct DepthLimitNotice(RwSignal<bool>);
+
+impl DepthLimitNotice {
+    fn flash(self) {
+        self.0.set(true);
+        set_timeout(
+            move || {
+                let _ = self.0.try_set(false);
+            },
+            std::time::Duration::from_secs(2),
+        );
+    }
+}
 
-        let sync = expect_context::<NoteSyncController>();
+/// Block and caret (UTF-16) where `/template` was typed; the template picker is open while
+/// this is set.
+#[derive(Clone, Copy)]
+struct TemplateInsertCtx(RwSignal<Option<(String, u32)>>);
+
+/// Row flashed by a `#nav-<id>` deep link; cleared once the CSS fade is over.
+#[derive(Clone, Copy)]
+struct DeepLinkHighlight(RwSignal<Option<String>>);
+
+impl DeepLinkHighlight {
+    fn flash(self, nav_id: String) {
+        self.0.set(Some(nav_id.clone()));
+        set_timeout(
+            move || {
+                if self.0.try_get_untracked().flatten().as_deref() == Some(nav_id.as_str()) {
+                    let _ = self.0.try_set(None);
+                }
+            },
+            std::time::Duration::from_secs(2),
+        );
+    }
+}
 
-        // Helper moved into NoteSyncController: ensure the note has a starting node.
+/// Multi-block selection (Shift+Click / Shift+Up/Down), shared by all OutlineNodes.
+#[derive(Clone, Copy)]
+struct SelectionCtx {
+    /// Selected nav ids in visible (preorder) order.
+    selected: RwSignal<Vec<String>>,
+    /// Block where the selection started.
+    anchor: RwSignal<Option<String>>,
+    /// Block the selection currently extends to.
+    focus: RwSignal<Option<String>>,
+}
 
-        // If we already know the backend is unreachable, don't even try fetching.
-        if !sync.is_backend_online() {
-            if let Some(snap) = load_note_snapshot(&db_id_now, &id) {
-                offline.set(true);
-                offline_missing_snapshot.set(false);
-                error.set(None);
-                let mut xs = snap.navs;
+impl SelectionCtx {
+    fn clear(&self) {
+        if !self.selected.get_untracked().is_empty() {
+            self.selected.set(vec![]);
+        }
+        self.anchor.set(None);
+        self.focus.set(None);
+    }
 
-                let maybe_tmp =
-                    sync.ensure_note_has_start_node_local(&db_id_now, &id, snap.title, &mut xs, "");
-                if let Some(tmp_id) = maybe_tmp {
-                    editing_id.set(Some(tmp_id.clone()));
-                    editing_value.set(String::new());
-                    editing_snapshot.set(Some((tmp_id.clone(), String::new())));
-                    target_cursor_col.set(Some(0));
-                }
+    /// Shift+Click: select the visible range from the anchor (or `fallback_anchor`, e.g. the
+    /// block being edited) to `clicked`.
+    fn extend_to(&self, visible: &[String], fallback_anchor: Option<String>, clicked: String) {
+        let anchor = self
+            .anchor
+            .get_untracked()
+            .or(fallback_anchor)
+            .unwrap_or_else(|| clicked.clone());
+        self.selected.set(selection_range(visible, &anchor, &clicked));
+        self.anchor.set(Some(anchor));
+        self.focus.set(Some(clicked));
+    }
+}
 
-                apply_nav_meta_overrides(&db_id_now, &id, &mut xs);
-                navs.set(xs);
-            } else {
-                offline.set(true);
-                offline_missing_snapshot.set(true);
-                error.set(None);
-                navs.set(vec![]);
-            }
-            loading.set(false);
-            return;
-        }
+/// Contiguous range of visible blocks between `anchor` and `focus` (inclusive, visible order).
+pub(crate) fn selection_range(visible: &[String], anchor: &str, focus: &str) -> Vec<String> {
+    let (Some(a), Some(f)) = (
+        visible.iter().position(|id| id == anchor),
+        visible.iter().position(|id| id == focus),
+    ) else {
+        return vec![];
+    };
+    let (lo, hi) = if a <= f { (a, f) } else { (f, a) };
+    visible[lo..=hi].to_vec()
+}
 
-        loading.set(true);
-        error.set(None);
+/// Selected blocks whose ancestors are not selected, in visible order.
+///
+/// Structural operations act on these; descendants move/delete with their root.
+pub(crate) fn selection_roots(all: &[Nav], selected: &[String]) -> Vec<String> {
+    selected
+        .iter()
+        .filter(|id| {
+            !selected
+                .iter()
+                .any(|other| other != *id && is_ancestor_of(all, other, id))
+        })
+        .cloned()
+        .collect()
+}
 
-        let api_client = app_state.0.api_client.get_untracked();
+/// Group `roots` by parent, keeping each group in sibling order.
+fn group_roots_by_parent<'a>(all: &'a [Nav], roots: &[String]) -> Vec<(String, Vec<&'a Nav>)> {
+    let mut groups: Vec<(String, Vec<&Nav>)> = vec![];
+    for id in roots {
+        let Some(n) = all.iter().find(|n| &n.id == id) else {
+            continue;
+        };
+        match groups.iter_mut().find(|(p, _)| p == &n.parid) {
+            Some((_, xs)) => xs.push(n),
+            None => groups.push((n.parid.clone(), vec![n])),
+        }
+    }
+    for (_, xs) in groups.iter_mut() {
+        xs.sort_by(|a, b| cmp_order(a.same_deep_order, b.same_deep_order));
+    }
+    groups
+}
+
+/// Indent selected roots: each run of siblings becomes the last children of the sibling right
+/// before the run. Returns `(id, new_parid, new_order)` moves in relative order.
+pub(crate) fn compute_indent_moves(all: &[Nav], roots: &[String]) -> Vec<(String, String, f32)> {
+    let tree = NavTree::new(all);
+    let mut moves = vec![];
+    for (parid, group) in group_roots_by_parent(all, roots) {
+        let sibs = tree.children(&parid);
+        let Some(first_idx) = sibs.iter().position(|s| s.id == group[0].id) else {
+            continue;
+        };
+        if first_idx == 0 {
+            continue;
+        }
+        let new_parid = sibs[first_idx - 1].id.clone();
+
+        let last_child_order = tree
+            .children(&new_parid)
+            .last()
+            .map(|n| n.same_deep_order)
+            .unwrap_or(0.0);
+        for (i, n) in group.iter().enumerate() {
+            moves.push((
+                n.id.clone(),
+                new_parid.clone(),
+                last_child_order + (i as f32) + 1.0,
+            ));
+        }
+    }
+    moves
+}
+
+/// Outdent selected roots: each group moves right after its parent, keeping relative order.
+/// Top-level blocks (children of `root_parid`) are left in place.
+pub(crate) fn compute_outdent_moves(
+    all: &[Nav],
+    roots: &[String],
+    root_parid: &str,
+) -> Vec<(String, String, f32)> {
+    let tree = NavTree::new(all);
+    let mut moves = vec![];
+    for (parent_id, group) in group_roots_by_parent(all, roots) {
+        if parent_id == root_parid || parent_id == ROOT_CONTAINER_PARENT_ID {
+            continue;
+        }
+        let Some(parent) = tree.get(&parent_id) else {
+            continue;
+        };
+
+        let next_order = tree.next_sibling(&parent.id).map(|s| s.same_deep_order);
+        let step = match next_order {
+            Some(no) => (no - parent.same_deep_order) / (group.len() as f32 + 1.0),
+            None => 1.0,
+        };
+        for (i, n) in group.iter().enumerate() {
+            moves.push((
+                n.id.clone(),
+                parent.parid.clone(),
+                parent.same_deep_order + step * (i as f32 + 1.0),
+            ));
+        }
+    }
+    moves
+}
+
+/// Sibling renumbering to apply before `compute_outdent_moves`, for every parent whose next
+/// sibling leaves too little room for the group moving in right after it.
+pub(crate) fn rebalance_for_outdent(
+    all: &[Nav],
+    roots: &[String],
+    root_parid: &str,
+) -> Vec<(String, f32)> {
+    let mut scratch = all.to_vec();
+    let mut out = vec![];
+    for (parent_id, group) in group_roots_by_parent(all, roots) {
+        if parent_id == root_parid || parent_id == ROOT_CONTAINER_PARENT_ID {
+            continue;
+        }
+        let Some(parent) = all.iter().find(|n| n.id == parent_id) else {
+            continue;
+        };
+        let renumbered =
+            rebalance_for_insert(&scratch, &parent.parid, Some(&parent.id), group.len(), None);
+        apply_orders(&mut scratch, &renumbered);
+        out.extend(renumbered);
+    }
+    out
+}
+
+/// Backspace at the start of a block: the previous visible block takes its text, and its
+/// children are promoted into its place under its parent.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct MergeIntoPrevious {
+    pub target_id: String,
+    /// The target's content followed by the merged block's.
+    pub content: String,
+    /// Caret (UTF-16) at the junction of the two texts.
+    pub caret: u32,
+    /// `(id, new_parid, new_order)` for the merged block's children.
+    pub child_moves: Vec<(String, String, f32)>,
+}
+
+/// Plan merging `nav_id` (current text `content`) into the block before it in `visible`.
+///
+/// `None` for the first visible block (the ROOT container is never a target), or when the
+/// previous block is collapsed over children (the merge would land out of view).
+pub(crate) fn plan_merge_into_previous(
+    all: &[Nav],
+    visible: &[String],
+    nav_id: &str,
+    content: &str,
+) -> Option<MergeIntoPrevious> {
+    let idx = visible.iter().position(|id| id == nav_id)?;
+    let target_id = visible.get(idx.checked_sub(1)?)?;
+    let tree = NavTree::new(all);
+    let target = tree.get(target_id)?;
+    let merged = tree.get(nav_id)?;
+    if target.parid == ROOT_CONTAINER_PARENT_ID
+        || (!target.is_display && !tree.children(&target.id).is_empty())
+    {
+        return None;
+    }
+
+    let children = tree.children(&merged.id);
+    let next_order = tree.next_sibling(&merged.id).map(|s| s.same_deep_order);
+    let step = match next_order {
+        Some(no) => (no - merged.same_deep_order) / (children.len() as f32 + 1.0),
+        None => 1.0,
+    };
+    let child_moves = children
+        .iter()
+        .enumerate()
+        .map(|(i, c)| {
+            (
+                c.id.clone(),
+                merged.parid.clone(),
+                merged.same_deep_order + step * i as f32,
+            )
+        })
+        .collect();
+
+    Some(MergeIntoPrevious {
+        target_id: target.id.clone(),
+        content: format!("{}{}", target.content, content),
+        caret: target.content.encode_utf16().count() as u32,
+        child_moves,
+    })
+}
+
+/// All ids in the subtrees of `roots` (including the roots).
+pub(crate) fn collect_subtree_ids(all: &[Nav], roots: &[String]) -> Vec<String> {
+    let tree = NavTree::new(all);
+    roots.iter().flat_map(|id| tree.subtree_ids(id)).collect()
+}
+
+/// Deepest nesting (`Nav::depth`) that indent and drag-and-drop may create; deeper trees
+/// also wear down `same_deep_order` midpoint precision.
+pub(crate) const MAX_NAV_DEPTH: usize = 10;
+
+/// Whether `nav_id` and its subtree can move under `new_parid` without any block ending up
+/// deeper than `MAX_NAV_DEPTH`.
+pub(crate) fn fits_max_nav_depth(all: &[Nav], nav_id: &str, new_parid: &str) -> bool {
+    let depth = Nav::depth(all, nav_id);
+    let below = collect_subtree_ids(all, &[nav_id.to_string()])
+        .iter()
+        .map(|id| Nav::depth(all, id).saturating_sub(depth))
+        .max()
+        .unwrap_or(0);
+    Nav::depth(all, new_parid) + 1 + below <= MAX_NAV_DEPTH
+}
+
+/// Selected blocks (with their subtrees) as indented plain text, two spaces per level.
+pub(crate) fn selection_plain_text(all: &[Nav], roots: &[String]) -> String {
+    fn depth_of(all: &[Nav], id: &str) -> usize {
+        let mut depth = 0;
+        let mut cur = id;
+        while let Some(n) = all.iter().find(|n| n.id == cur) {
+            if depth >= 2048 {
+                break;
+            }
+            depth += 1;
+            cur = &n.parid;
+        }
+        depth
+    }
+
+    fn push_block(tree: &NavTree, n: &Nav, level: usize, out: &mut Vec<String>) {
+        out.push(format!("{}{}", "  ".repeat(level), n.content));
+        for c in tree.children(&n.id) {
+            push_block(tree, c, level + 1, out);
+        }
+    }
+
+    let base = roots
+        .iter()
+        .map(|id| depth_of(all, id))
+        .min()
+        .unwrap_or(0);
+
+    let tree = NavTree::new(all);
+    let mut lines = vec![];
+    for id in roots {
+        if let Some(n) = tree.get(id).filter(|n| !n.is_delete) {
+            push_block(&tree, n, depth_of(all, id) - base, &mut lines);
+        }
+    }
+    lines.join("\n")
+}
+
+/// A block to create from a multi-line paste. `depth` is relative to the block being edited:
+/// 0 = following sibling, 1 = child, and so on.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct PastedBlock {
+    pub(crate) content: String,
+    pub(crate) depth: usize,
+}
+
+/// How a multi-line paste applies to the block being edited.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct PastePlan {
+    /// New content for the current block (first pasted line inserted at the caret).
+    pub(crate) current_content: String,
+    /// Caret position (UTF-16) in the current block after the paste.
+    pub(crate) caret_utf16: u32,
+    /// Remaining lines, in order.
+    pub(crate) blocks: Vec<PastedBlock>,
+}
+
+/// Indentation level of a pasted line: one level per tab or per two spaces.
+fn paste_indent_level(line: &str) -> (usize, &str) {
+    let mut spaces = 0;
+    let mut levels = 0;
+    for (i, c) in line.char_indices() {
+        match c {
+            '\t' => {
+                levels += 1;
+                spaces = 0;
+            }
+            ' ' => {
+                spaces += 1;
+                if spaces == 2 {
+                    levels += 1;
+                    spaces = 0;
+                }
+            }
+            _ => return (levels, &line[i..]),
+        }
+    }
+    (levels, "")
+}
+
+/// Split pasted text into block-creation operations.
+///
+/// Returns `None` for single-line pastes (the browser's default paste is kept).
+/// The selection `[sel_start_utf16, sel_end_utf16)` in `current` is replaced by the first line.
+pub(crate) fn plan_multiline_paste(
+    current: &str,
+    sel_start_utf16: u32,
+    sel_end_utf16: u32,
+    pasted: &str,
+) -> Option<PastePlan> {
+    let normalized = pasted.replace("\r\n", "\n").replace('\r', "\n");
+    let mut lines = normalized.lines().collect::<Vec<_>>();
+    while lines.last().is_some_and(|l| l.trim().is_empty()) {
+        lines.pop();
+    }
+    if lines.len() < 2 {
+        return None;
+    }
+
+    let (base_level, first) = paste_indent_level(lines[0]);
+
+    let start = utf16_to_byte_idx(current, sel_start_utf16.min(sel_end_utf16));
+    let end = utf16_to_byte_idx(current, sel_start_utf16.max(sel_end_utf16));
+    let before = &current[..start];
+    let after = &current[end..];
+
+    let mut blocks = vec![];
+    let mut prev_depth = 0;
+    for line in lines.iter().skip(1) {
+        let (level, text) = paste_indent_level(line);
+        // Never skip a level: a block can be at most one deeper than the one above it.
+        let depth = level.saturating_sub(base_level).min(prev_depth + 1);
+        blocks.push(PastedBlock {
+            content: text.trim_end().to_string(),
+            depth,
+        });
+        prev_depth = depth;
+    }
+
+    Some(PastePlan {
+        current_content: format!("{before}{first}{after}"),
+        caret_utf16: (before.encode_utf16().count() + first.encode_utf16().count()) as u32,
+        blocks,
+    })
+}
+
+/// Like `plan_multiline_paste` with the caret at `caret_utf16`, but a single line is a plan
+/// too (inserted at the caret, no new blocks). Used to expand templates.
+pub(crate) fn plan_text_insert(current: &str, caret_utf16: u32, text: &str) -> PastePlan {
+    plan_multiline_paste(current, caret_utf16, caret_utf16, text).unwrap_or_else(|| {
+        let line = text
+            .lines()
+            .find(|l| !l.trim().is_empty())
+            .unwrap_or_default()
+            .trim();
+        let at = utf16_to_byte_idx(current, caret_utf16);
+        PastePlan {
+            current_content: format!("{}{line}{}", &current[..at], &current[at..]),
+            caret_utf16: caret_utf16 + line.encode_utf16().count() as u32,
+            blocks: vec![],
+        }
+    })
+}
+
+/// Slash command that opens the template picker in a block.
+pub(crate) const TEMPLATE_SLASH_COMMAND: &str = "/template";
+
+/// If `content` has `/template` right before the caret (at the start or after whitespace),
+/// the content without it and the caret where it started.
+pub(crate) fn strip_template_command(content: &str, caret_utf16: u32) -> Option<(String, u32)> {
+    let caret = utf16_to_byte_idx(content, caret_utf16);
+    let start = content[..caret].strip_suffix(TEMPLATE_SLASH_COMMAND)?.len();
+    if !content[..start]
+        .chars()
+        .next_back()
+        .is_none_or(char::is_whitespace)
+    {
+        return None;
+    }
+    Some((
+        format!("{}{}", &content[..start], &content[caret..]),
+        byte_idx_to_utf16(content, start),
+    ))
+}
+
+/// Turn pasted blocks into new navs placed after `current_id` (siblings between it and its
+/// next sibling; deeper blocks nested under the block above them). Ids come from `make_id`.
+pub(crate) fn layout_pasted_blocks(
+    all: &[Nav],
+    current_id: &str,
+    blocks: &[PastedBlock],
+    mut make_id: impl FnMut() -> String,
+) -> Vec<Nav> {
+    let tree = NavTree::new(all);
+    let Some(me) = tree.get(current_id) else {
+        return vec![];
+    };
+
+    let next_order = tree.next_sibling(current_id).map(|s| s.same_deep_order);
+    let sibling_count = blocks.iter().filter(|b| b.depth == 0).count();
+    let sibling_step = match next_order {
+        Some(no) => (no - me.same_deep_order) / (sibling_count as f32 + 1.0),
+        None => 1.0,
+    };
+
+    let current_last_child_order = tree
+        .children(current_id)
+        .last()
+        .map(|n| n.same_deep_order)
+        .unwrap_or(0.0);
+
+    // (nav id, last child order) per depth; index 0 is the edited block's level.
+    let mut stack: Vec<(String, f32)> = vec![(me.id.clone(), current_last_child_order)];
+    let mut sibling_index = 0;
+    let mut out: Vec<Nav> = vec![];
+
+    for b in blocks {
+        let depth = b.depth.min(stack.len());
+        let (parid, order) = if depth == 0 {
+            sibling_index += 1;
+            (
+                me.parid.clone(),
+                me.same_deep_order + sibling_step * sibling_index as f32,
+            )
+        } else {
+            let (parent_id, last) = &mut stack[depth - 1];
+            *last += 1.0;
+            (parent_id.clone(), *last)
+        };
+
+        let id = make_id();
+        stack.truncate(depth);
+        stack.push((id.clone(), 0.0));
+
+        out.push(Nav {
+            id,
+            note_id: me.note_id.clone(),
+            parid,
+            same_deep_order: order,
+            content: b.content.clone(),
+            is_display: true,
+            is_delete: false,
+            properties: None,
+            created_at: None,
+            updated_at: None,
+        });
+    }
+
+    out
+}
+
+/// Create `blocks` (see `PastedBlock`) as optimistic local navs after `after_id`, making room
+/// among its siblings first. They are queued for sync and kept in the offline snapshot; the
+/// retry worker creates them on the backend and rewrites tmp parids once their parent gets a
+/// real id. Shared by multi-line paste and template insertion.
+fn insert_blocks_after(
+    app_state: &AppContext,
+    navs: RwSignal<Vec<Nav>>,
+    sync_sv: StoredValue<NoteSyncController>,
+    note_id: &str,
+    after_id: &str,
+    blocks: &[PastedBlock],
+) {
+    let sibling_count = blocks.iter().filter(|b| b.depth == 0).count();
+    let all = navs.get_untracked();
+    let renumbered = all
+        .iter()
+        .find(|n| n.id == after_id)
+        .map(|me| rebalance_for_insert(&all, &me.parid, Some(after_id), sibling_count, None))
+        .unwrap_or_default();
+    let all = apply_renumbered_orders(navs, sync_sv, &renumbered);
+    let created = layout_pasted_blocks(&all, after_id, blocks, || {
+        make_tmp_nav_id(
+            js_sys::Date::now() as u64,
+            (js_sys::Math::random() * 1e9) as u64,
+        )
+    });
+    if created.is_empty() {
+        return;
+    }
+
+    navs.update(|xs| xs.extend(created.iter().cloned()));
+    let _ = sync_sv.try_with_value(|s| {
+        s.on_navs_meta_changed(&created);
+        for n in created.iter() {
+            s.on_nav_changed(&n.id, &n.content);
+        }
+    });
+
+    // Persist snapshot so refresh won't drop the new tmp nodes.
+    let db_id = app_state
+        .0
+        .current_database_id
+        .get_untracked()
+        .unwrap_or_default();
+    let title = app_state
+        .0
+        .notes
+        .get_untracked()
+        .into_iter()
+        .find(|n| n.id == note_id)
+        .map(|n| n.title);
+    save_note_snapshot(
+        &db_id,
+        note_id,
+        title,
+        navs.get_untracked(),
+        crate::util::now_ms(),
+    );
+}
+
+/// Zoom breadcrumb for `zoom_id`: `(id, content)` from the top-level ancestor down to the
+/// zoomed nav itself. The backend ROOT container (parid all-zero) is skipped.
+pub(crate) fn zoom_breadcrumb(all: &[Nav], zoom_id: &str) -> Vec<(String, String)> {
+    let mut out = vec![];
+    let mut cur = zoom_id;
+    for _ in 0..2048 {
+        let Some(n) = all.iter().find(|n| n.id == cur) else {
+            break;
+        };
+        if n.parid == ROOT_CONTAINER_PARENT_ID && n.id != zoom_id {
+            break;
+        }
+        out.push((n.id.clone(), n.content.clone()));
+        cur = &n.parid;
+    }
+    out.reverse();
+    out
+}
+
+/// Note URL for a zoom state: `?block=<nav_id>` when zoomed, the bare path otherwise.
+pub(crate) fn zoom_href(path: &str, block: Option<&str>) -> String {
+    match block.map(str::trim).filter(|b| !b.is_empty()) {
+        Some(b) => format!("{path}?block={}", urlencoding::encode(b)),
+        None => path.to_string(),
+    }
+}
+
+/// Block to resume editing in when a note is reopened: the remembered block if it is still
+/// visible (within the zoom), otherwise the first visible block. `None` for an empty outline.
+pub(crate) fn resume_nav_id(
+    all: &[Nav],
+    zoom_root: Option<&str>,
+    remembered: &str,
+) -> Option<String> {
+    let visible = visible_preorder_within(all, zoom_root)
+        .into_iter()
+        .filter(|id| {
+            all.iter()
+                .any(|n| &n.id == id && n.parid != ROOT_CONTAINER_PARENT_ID)
+        })
+        .collect::<Vec<_>>();
+    visible
+        .iter()
+        .find(|id| id.as_str() == remembered)
+        .or_else(|| visible.first())
+        .cloned()
+}
+
+/// Visible preorder limited to the zoom boundary: the zoom root followed by its visible
+/// descendants. Without a zoom root (or if it is gone) this is the whole visible outline.
+pub(crate) fn visible_preorder_within(all: &[Nav], zoom_root: Option<&str>) -> Vec<String> {
+    NavTree::new(all).visible_preorder_within(zoom_root)
+}
+
+#[component]
+pub fn OutlineEditor(
+    note_id: impl Fn() -> String + Clone + Send + Sync + 'static,
+    focused_nav_id: RwSignal<Option<String>>,
+    /// Loaded outline for the current note (owned by the page so it can read it, e.g. export).
+    navs: RwSignal<Vec<Nav>>,
+    /// Receives the block being typed in (nav id, unsaved content), e.g. for live page stats.
+    #[prop(optional)]
+    live_edit: Option<RwSignal<Option<(String, String)>>>,
+    /// One of several editors on a page (daily notes): zoom stays local instead of following
+    /// `?block=`, window scroll and edit position are left alone, and the sync route follows
+    /// whichever editor is in use.
+    #[prop(optional)]
+    embedded: bool,
+    /// Set by the page (Cmd/Ctrl+Enter in the title) to start editing the first visible
+    /// block, caret at the start; reset once handled.
+    #[prop(optional)]
+    edit_first_block: Option<RwSignal<bool>>,
+) -> impl IntoView {
+    let app_state = expect_context::<AppContext>();
+
+    let loading: RwSignal<bool> = RwSignal::new(false);
+    let error: RwSignal<Option<String>> = RwSignal::new(None);
+
+    // Wiki links: opening a missing page does not hit the backend (client-side navigation).
+
+    // Editing state
+    let editing_id: RwSignal<Option<String>> = RwSignal::new(None);
+    let editing_value: RwSignal<String> = RwSignal::new(String::new());
+    // Snapshot of the content when we entered edit mode (id, content).
+    // Used to avoid redundant backend saves when the user didn't change anything.
+    let editing_snapshot: RwSignal<Option<(String, String)>> = RwSignal::new(None);
+
+    // Drag state (for highlighting drop targets only while dragging).
+    let dragging_nav_id: RwSignal<Option<String>> = RwSignal::new(None);
+    let drag_over_nav_id: RwSignal<Option<String>> = RwSignal::new(None);
+    let drag_over_position: RwSignal<DropPosition> = RwSignal::new(DropPosition::Inside);
+
+    let target_cursor_col: RwSignal<Option<u32>> = RwSignal::new(None);
+    let editing_ref: NodeRef<html::Div> = NodeRef::new();
+    // The `.outline-editor` root, to tell clicks in this editor from ones in another.
+    let editor_root: NodeRef<html::Div> = NodeRef::new();
+
+    // Zoom (focus mode): when set, only this nav and its descendants are rendered.
+    // Mirrored to the `?block=<nav_id>` query param so it survives refresh and can be shared.
+    let focused_nav_root: RwSignal<Option<String>> = RwSignal::new(None);
+    let query = use_query_map();
+    let location = use_location();
+    let navigate = use_navigate();
+    // `#nav-<id>` deep link (search results, backlinks); see the effect after collapse state.
+    let fragment_nav_id = Memo::new(move |_| nav_id_from_fragment(&location.hash.get()));
+
+    // URL -> zoom (initial load, back/forward, shared links).
+    Effect::new(move |_| {
+        if embedded {
+            return;
+        }
+        let block = query
+            .get()
+            .get("block")
+            .map(|b| b.trim().to_string())
+            .filter(|b| !b.is_empty());
+        if focused_nav_root.get_untracked() != block {
+            focused_nav_root.set(block);
+        }
+    });
+
+    // Autocomplete for `[[...]]` (wiki-style)
+    // - Data source is fixed: existing notes + titles extracted from all nav contents in current DB.
+    // - Supports creating new titles (insert text even if no existing note).
+    let ac_open: RwSignal<bool> = RwSignal::new(false);
+    let ac_query: RwSignal<String> = RwSignal::new(String::new());
+    let ac_items: RwSignal<Vec<AcItem>> = RwSignal::new(vec![]);
+    let ac_index: RwSignal<usize> = RwSignal::new(0);
+    // Start position (UTF-16 code units) of the `[[` trigger in the current input.
+    let ac_start_utf16: RwSignal<Option<u32>> = RwSignal::new(None);
+    // `((` block-ref mode: items come from the DB nav cache instead of page titles.
+    let ac_block_mode: RwSignal<bool> = RwSignal::new(false);
+    let block_navs_loading: RwSignal<bool> = RwSignal::new(false);
+
+    // Cache all possible page titles for current DB (notes + wiki links from all navs).
+    let titles_cache_key: RwSignal<Option<(String, u64)>> = RwSignal::new(None);
+    let titles_cache: RwSignal<Vec<String>> = RwSignal::new(vec![]);
+    let titles_loading: RwSignal<bool> = RwSignal::new(false);
+
+    let all_navs_cache = app_state.0.all_navs_cache;
+    let current_database_id = app_state.0.current_database_id;
+    let note_id_for_ac = note_id.clone();
+
+    // Autocomplete recompute effect.
+    // This fixes the first-`[[` case where titles are still loading: we keep the menu open and
+    // populate items as soon as the async title load completes (without requiring extra typing).
+    Effect::new(move |_| {
+        let start = ac_start_utf16.get();
+        if start.is_none() {
+            return;
+        }
+
+        let q = ac_query.get();
+
+        if ac_block_mode.get() {
+            let db_id = current_database_id.get_untracked().unwrap_or_default();
+            let Some(db_navs) = all_navs_cache.with(|m| m.get(&db_id).cloned()) else {
+                // Keep the menu open with a loading row until the fetch lands.
+                ac_open.set(block_navs_loading.get());
+                ac_items.set(vec![]);
+                return;
+            };
+            let items = navs.with_untracked(|live| {
+                build_block_ac_items(
+                    &db_navs,
+                    live,
+                    &note_id_for_ac(),
+                    &q,
+                    &editing_id.get_untracked().unwrap_or_default(),
+                )
+            });
+            ac_open.set(!items.is_empty());
+            ac_items.set(items);
+            ac_index.set(0);
+            return;
+        }
+
+        let loading_now = titles_loading.get();
+        let titles_now = titles_cache.get();
+
+        if loading_now {
+            ac_open.set(true);
+            // Keep items empty; UI will show a loading row.
+            return;
+        }
+
+        let items = build_ac_items(&titles_now, &q);
+        if items.is_empty() {
+            ac_open.set(false);
+            ac_index.set(0);
+            return;
+        }
+
+        ac_items.set(items);
+        ac_index.set(0);
+        ac_open.set(true);
+    });
+
+    // `((block refs))` render from the DB nav cache; fetch it once the note contains one.
+    // Re-runs when the cache entry is dropped (e.g. after a link edit) to refetch.
+    let app_state_for_refs = app_state.clone();
+    Effect::new(move |_| {
+        let has_refs = navs.with(|xs| {
+            xs.iter()
+                .any(|n| !n.is_delete && !extract_block_refs(&n.content).is_empty())
+        });
+        let db_id = current_database_id.get().unwrap_or_default();
+        let cached = all_navs_cache.with(|m| m.contains_key(&db_id));
+        if has_refs && !cached {
+            ensure_db_navs_loaded(&app_state_for_refs, block_navs_loading);
+        }
+    });
+
+    let offline: RwSignal<bool> = RwSignal::new(false);
+    let offline_missing_snapshot: RwSignal<bool> = RwSignal::new(false);
+
+    // Per-note editor position (last edited block, caret, scroll), restored when a note visited
+    // this session is opened again.
+    let editor_positions = app_state.0.editor_positions;
+    let remember_scroll = move |note_id: &str| {
+        if embedded || note_id.trim().is_empty() {
+            return;
+        }
+        let scroll_y = window().scroll_y().unwrap_or(0.0);
+        let _ = editor_positions.try_update(|m| {
+            m.entry(note_id.to_string()).or_default().scroll_y = scroll_y;
+        });
+    };
+    let note_id_for_position = note_id.clone();
+    Effect::new(move |_| {
+        let Some(nav_id) = editing_id.get() else {
+            return;
+        };
+        let caret_col = target_cursor_col.get();
+        let id = note_id_for_position();
+        // Skip the previous note's block while a switch is in flight.
+        if !navs.with_untracked(|xs| xs.iter().any(|n| n.id == nav_id && n.note_id == id)) {
+            return;
+        }
+        let unchanged = editor_positions.with_untracked(|m| {
+            m.get(&id).is_some_and(|p| {
+                p.nav_id.as_deref() == Some(nav_id.as_str()) && p.caret_col == caret_col
+            })
+        });
+        if !unchanged {
+            editor_positions.update(|m| {
+                let p = m.entry(id).or_default();
+                p.nav_id = Some(nav_id);
+                p.caret_col = caret_col;
+            });
+        }
+    });
+
+    // Runs after a note's navs are shown. Enters edit mode through the same signals as a click,
+    // so the node-level focus Effect places the caret; the scroll is restored two frames later,
+    // after that focus has scrolled the block into view. A `?block=` or `#nav-` link or a
+    // freshly seeded note (already editing) takes precedence.
+    let restore_position = move |note_id: &str, xs: &[Nav]| {
+        if embedded
+            || editing_id.get_untracked().is_some()
+            || focused_nav_id.get_untracked().is_some()
+            || fragment_nav_id.get_untracked().is_some()
+        {
+            return;
+        }
+        let Some(pos) = editor_positions.with_untracked(|m| m.get(note_id).cloned()) else {
+            return;
+        };
+
+        // Only notes that were edited resume editing; others just get their scroll back.
+        let zoom = focused_nav_root.get_untracked();
+        let target = pos
+            .nav_id
+            .as_deref()
+            .and_then(|remembered| resume_nav_id(xs, zoom.as_deref(), remembered));
+        if let Some(target) = target {
+            let db_id = current_database_id.get_untracked().unwrap_or_default();
+            let content = get_nav_override(
+                &db_id,
+                note_id,
+                &target,
+                &get_nav_content(xs, &target).unwrap_or_default(),
+            );
+            let len = content.encode_utf16().count() as u32;
+            let caret_col = if pos.nav_id.as_deref() == Some(target.as_str()) {
+                pos.caret_col.unwrap_or(len).min(len)
+            } else {
+                0
+            };
+
+            editing_value.set(content.clone());
+            editing_snapshot.set(Some((target.clone(), content)));
+            target_cursor_col.set(Some(caret_col));
+            editing_id.set(Some(target));
+        }
+
+        let scroll_y = pos.scroll_y;
+        let _ = window().request_animation_frame(
+            Closure::once_into_js(move || {
+                let _ = window().request_animation_frame(
+                    Closure::once_into_js(move || window().scroll_to_with_x_and_y(0.0, scroll_y))
+                        .as_ref()
+                        .unchecked_ref(),
+                );
+            })
+            .as_ref()
+            .unchecked_ref(),
+        );
+    };
+
+    // Load navs when note_id changes.
+    let note_id_for_effect = note_id.clone();
+    let last_loaded_note: StoredValue<Option<String>> = StoredValue::new(None);
+    // The outline fetch still in flight; a newer load aborts it.
+    let navs_abort: StoredValue<Option<AbortHandle>> = StoredValue::new(None);
+    Effect::new(move |_| {
+        let id = note_id_for_effect();
+        if let Some(prev) = navs_abort.get_value() {
+            prev.abort();
+        }
+
+        // Leaving a note: remember where the page was scrolled.
+        if let Some(prev) = last_loaded_note.get_value().filter(|prev| prev != &id) {
+            remember_scroll(&prev);
+        }
+        last_loaded_note.set_value(Some(id.clone()));
+        let db_id_now = app_state
+            .0
+            .current_database_id
+            .get_untracked()
+            .unwrap_or_default();
+
+        if id.trim().is_empty() {
+            navs.set(vec![]);
+            offline.set(false);
+            offline_missing_snapshot.set(false);
+            return;
+        }
+
+        let sync = expect_context::<NoteSyncController>();
+
+        // Helper moved into NoteSyncController: ensure the note has a starting node.
+
+        // If we already know the backend is unreachable, don't even try fetching.
+        if !sync.is_backend_online() {
+            if let Some(snap) = load_note_snapshot(&db_id_now, &id) {
+                offline.set(true);
+                offline_missing_snapshot.set(false);
+                error.set(None);
+                let mut xs = snap.navs;
+
+                let maybe_tmp =
+                    sync.ensure_note_has_start_node_local(&db_id_now, &id, snap.title, &mut xs, "");
+                if let Some(tmp_id) = maybe_tmp.filter(|_| !embedded) {
+                    editing_id.set(Some(tmp_id.clone()));
+                    editing_value.set(String::new());
+                    editing_snapshot.set(Some((tmp_id.clone(), String::new())));
+                    target_cursor_col.set(Some(0));
+                }
+
+                apply_nav_meta_overrides(&db_id_now, &id, &mut xs);
+
+                apply_collapsed_blocks(&id, &mut xs);
+                navs.set(xs.clone());
+                restore_position(&id, &xs);
+            } else {
+                offline.set(true);
+                offline_missing_snapshot.set(true);
+                error.set(None);
+                navs.set(vec![]);
+            }
+            loading.set(false);
+            return;
+        }
+
+        loading.set(true);
+        error.set(None);
+
+        // A note reopened shortly after its last fetch is served from `navs_cache`.
+        let navs_cache = app_state.0.navs_cache;
+        let now = crate::util::now_ms() as f64;
+        let cached = navs_cache.with_untracked(|m| {
+            m.get(&id)
+                .filter(|(_, loaded_at)| navs_cache_is_fresh(*loaded_at, now))
+                .map(|(xs, _)| xs.clone())
+        });
+
+        let abort = AbortHandle::new();
+        navs_abort.set_value(Some(abort.clone()));
+        let api_client = app_state.0.api_client.get_untracked().with_abort(&abort);
         let sync2 = sync.clone();
         let db_id2 = db_id_now.clone();
         spawn_local(async move {
-            match api_client.get_note_navs(&id).await {
+            let result = match cached {
+                Some(list) => Ok(list),
+                None => api_client.get_note_navs(&id).await.inspect(|list| {
+                    navs_cache.update(|m| {
+                        m.insert(id.clone(), (list.clone(), crate::util::now_ms() as f64));
+                    });
+                }),
+            };
+            match result {
+                // Superseded by a newer load, which owns `loading` now.
+                Err(ApiError::Cancelled) => return,
                 Ok(list) => {
                     sync2.mark_backend_online();
                     offline.set(false);
@@ -950,67 +2149,634 @@ pub fn OutlineEditor(
                         }
                     }
 
-                    let title2 = title.clone();
-                    let maybe_tmp =
-                        sync2.ensure_note_has_start_node_local(&db_id2, &id, title2, &mut xs, "");
+                    let title2 = title.clone();
+                    let maybe_tmp =
+                        sync2.ensure_note_has_start_node_local(&db_id2, &id, title2, &mut xs, "");
+
+                    if let Some(tmp_id) = maybe_tmp {
+                        // Several embedded editors can't all start editing.
+                        if !embedded {
+                            editing_id.set(Some(tmp_id.clone()));
+                            editing_value.set(String::new());
+                            editing_snapshot.set(Some((tmp_id.clone(), String::new())));
+                            target_cursor_col.set(Some(0));
+                        }
+                    } else {
+                        // Persist snapshot for normal notes.
+                        save_note_snapshot(&db_id2, &id, title, xs.clone(), crate::util::now_ms());
+                    }
+
+                    apply_nav_meta_overrides(&db_id2, &id, &mut xs);
+
+                    apply_collapsed_blocks(&id, &mut xs);
+                    navs.set(xs.clone());
+                    restore_position(&id, &xs);
+                }
+                Err(e) => {
+                    sync2.mark_backend_offline_api(&e);
+
+                    // Backend unreachable: fall back to snapshot (read cache), and suppress errors.
+                    if !sync2.is_backend_online() {
+                        if let Some(snap) = load_note_snapshot(&db_id2, &id) {
+                            offline.set(true);
+                            offline_missing_snapshot.set(false);
+                            error.set(None);
+                            let mut xs = snap.navs;
+                            apply_nav_meta_overrides(&db_id2, &id, &mut xs);
+                            apply_collapsed_blocks(&id, &mut xs);
+                            navs.set(xs.clone());
+                            restore_position(&id, &xs);
+                        } else {
+                            offline.set(true);
+                            offline_missing_snapshot.set(true);
+                            error.set(None);
+                            navs.set(vec![]);
+                        }
+                    } else {
+                        // Non-connectivity error.
+                        offline.set(false);
+                        offline_missing_snapshot.set(false);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            }
+            loading.set(false);
+        });
+    });
+
+    // Leaving the note page altogether.
+    on_cleanup(move || {
+        if let Some(Some(id)) = last_loaded_note.try_get_value() {
+            remember_scroll(&id);
+        }
+        if let Some(Some(abort)) = navs_abort.try_get_value() {
+            abort.abort();
+        }
+    });
+
+    // Remember which blocks are collapsed per note (client-side; see `cache::collapse_state`).
+    // Only writes when the collapsed set actually changes.
+    let note_id_for_collapse = note_id.clone();
+    let last_collapsed: StoredValue<Option<(String, Vec<String>)>> = StoredValue::new(None);
+    Effect::new(move |_| {
+        let id = note_id_for_collapse();
+        let collapsed = navs.with(|xs| {
+            // Skip while the previous note's outline is still shown.
+            if xs.is_empty() || xs.iter().any(|n| n.note_id != id) {
+                None
+            } else {
+                Some(collapsed_block_ids(xs))
+            }
+        });
+        let Some(collapsed) = collapsed else {
+            return;
+        };
+        let next = Some((id.clone(), collapsed.clone()));
+        if last_collapsed.get_value() == next {
+            return;
+        }
+        let first_for_note = last_collapsed
+            .get_value()
+            .map(|(prev_id, _)| prev_id != id)
+            .unwrap_or(true);
+        last_collapsed.set_value(next);
+        // The first pass after opening a note only records the restored state.
+        if !first_for_note {
+            save_collapsed_blocks(&id, collapsed, crate::util::now_ms());
+        }
+    });
+
+    if let Some(request) = edit_first_block {
+        let note_id_for_request = note_id.clone();
+        Effect::new(move |_| {
+            if !request.get() {
+                return;
+            }
+            request.set(false);
+
+            let zoom = focused_nav_root.get_untracked();
+            let first = navs.with_untracked(|xs| {
+                resume_nav_id(xs, zoom.as_deref(), "").map(|id| {
+                    let content = get_nav_content(xs, &id).unwrap_or_default();
+                    (id, content)
+                })
+            });
+            let Some((id, content)) = first else {
+                return;
+            };
+            let db_id = current_database_id.get_untracked().unwrap_or_default();
+            let content = get_nav_override(&db_id, &note_id_for_request(), &id, &content);
+
+            editing_value.set(content.clone());
+            editing_snapshot.set(Some((id.clone(), content)));
+            target_cursor_col.set(Some(0));
+            editing_id.set(Some(id));
+        });
+    }
+
+    // Deep link: once the outline is shown, expand the block's collapsed ancestors, scroll it
+    // into view and flash it. Only the fragment changes between blocks of one note, so the
+    // route param (and the nav list) stay put.
+    let deep_link_highlight = DeepLinkHighlight(RwSignal::new(None));
+    let block_not_found: RwSignal<Option<String>> = RwSignal::new(None);
+    let fragment_handled: StoredValue<Option<(String, String)>> = StoredValue::new(None);
+    let note_id_for_fragment = note_id.clone();
+    Effect::new(move |_| {
+        if embedded {
+            return;
+        }
+        let Some(target) = fragment_nav_id.get() else {
+            block_not_found.set(None);
+            fragment_handled.set_value(None);
+            return;
+        };
+        let id = note_id_for_fragment();
+        let loaded = !loading.get()
+            && navs.with(|xs| !xs.is_empty() && xs.iter().all(|n| n.note_id == id));
+        let key = Some((id.clone(), target.clone()));
+        if !loaded || fragment_handled.get_value() == key {
+            return;
+        }
+        fragment_handled.set_value(key);
+
+        if !navs.with_untracked(|xs| xs.iter().any(|n| n.id == target && !n.is_delete)) {
+            block_not_found.set(Some(target));
+            return;
+        }
+        block_not_found.set(None);
+
+        // Expanded for this view only: neither synced nor saved as the note's collapse state.
+        let hidden_by = navs.with_untracked(|xs| collapsed_ancestor_ids(xs, &target));
+        if !hidden_by.is_empty() {
+            navs.update(|xs| {
+                for x in xs.iter_mut().filter(|x| hidden_by.contains(&x.id)) {
+                    x.is_display = true;
+                }
+                last_collapsed.set_value(Some((id.clone(), collapsed_block_ids(xs))));
+            });
+        }
+        if let Some(zoom) = focused_nav_root.get_untracked() {
+            if zoom != target && !navs.with_untracked(|xs| is_ancestor_of(xs, &zoom, &target)) {
+                focused_nav_root.set(None);
+            }
+        }
+
+        deep_link_highlight.flash(target.clone());
+        let _ = window().request_animation_frame(
+            Closure::once_into_js(move || {
+                if let Some(el) = document().get_element_by_id(&format!("nav-{target}")) {
+                    el.scroll_into_view();
+                }
+            })
+            .as_ref()
+            .unchecked_ref(),
+        );
+    });
+
+    // Keep the per-note character count (note list badges) in sync with the loaded outline.
+    let note_id_for_counts = note_id.clone();
+    Effect::new(move |_| {
+        let id = note_id_for_counts();
+        if id.trim().is_empty() {
+            return;
+        }
+        let count = navs.with(|xs| count_note_chars(xs));
+        if app_state.0.note_char_counts.with_untracked(|m| m.get(&id) == Some(&count)) {
+            return;
+        }
+        app_state.0.note_char_counts.update(|m| {
+            m.insert(id, count);
+        });
+    });
+
+    // Same for the note list preview: edits to the first block show up without a reload.
+    let note_id_for_preview = note_id.clone();
+    Effect::new(move |_| {
+        let id = note_id_for_preview();
+        let Some(db_id) = current_database_id.get_untracked() else {
+            return;
+        };
+        if id.trim().is_empty() {
+            return;
+        }
+        let preview = navs.with(|xs| note_preview(xs));
+        let unchanged = app_state
+            .0
+            .note_previews
+            .with_untracked(|m| m.get(&db_id).is_none_or(|p| p.get(&id) == preview.as_ref()));
+        if unchanged {
+            return;
+        }
+        // Only a loaded database entry is patched; a missing one is fetched whole by the lists.
+        app_state.0.note_previews.update(|m| {
+            let Some(previews) = m.get_mut(&db_id) else {
+                return;
+            };
+            match preview {
+                Some(p) => previews.insert(id, p),
+                None => previews.remove(&id),
+            };
+        });
+    });
+
+    if let Some(live_edit) = live_edit {
+        Effect::new(move |_| {
+            let next = editing_id.get().map(|id| (id, editing_value.get()));
+            live_edit.set(next);
+        });
+    }
+
+    // Focus handled by OutlineNode (see below).
+    // (focus moved to OutlineNode)
+
+    // Sync controller (global, local-first)
+    let sync_sv = StoredValue::new(expect_context::<NoteSyncController>());
+
+    // Re-read the drafts whenever the sync controller writes them (one parse per change for
+    // the whole note; rows look themselves up in the result).
+    let drafts_version = sync_sv.with_value(|s| s.drafts_version());
+    let note_id_for_sync_status = note_id.clone();
+    let current_database_id_for_sync = app_state.0.current_database_id;
+    let nav_draft_memo = Memo::new(move |_| {
+        drafts_version.track();
+        let db_id = current_database_id_for_sync
+            .get_untracked()
+            .unwrap_or_default();
+        let draft = load_note_draft(&db_id, &note_id_for_sync_status());
+        NavDraftInfo {
+            statuses: nav_sync_statuses(&draft),
+            edited_ms: nav_content_edited_ms(&draft),
+        }
+    });
+
+    // Keep sync controller aware of which nav is being edited (for pagehide flush priority).
+    // Embedded editors only clear the nav they set, not one another editor set since.
+    Effect::new(move |prev: Option<Option<String>>| {
+        let id = editing_id.get();
+        let _ = sync_sv.try_with_value(|s| match (&id, prev.flatten()) {
+            (None, Some(prev)) if embedded => s.clear_editing_nav(&prev),
+            (None, None) if embedded => {}
+            _ => s.set_editing_nav(id.clone()),
+        });
+        id
+    });
+
+    // Embedded editors point the sync controller at their own note once used. Claimed on
+    // focusin / click (capture), i.e. after the previous editor's blur saved its block under
+    // the old route.
+    let note_id_for_route = StoredValue::new(note_id.clone());
+    let claim_route = move || {
+        if !embedded {
+            return;
+        }
+        let db_id = current_database_id.get_untracked().unwrap_or_default();
+        let id = note_id_for_route.with_value(|f| f());
+        let _ = sync_sv.try_with_value(|s| s.set_route(db_id, id));
+    };
+    Effect::new(move |_| {
+        if editing_id.get().is_some() {
+            claim_route();
+        }
+    });
+
+    // Zoom -> URL. Each zoom change is a history entry so Back leaves the zoom.
+    // Zooming into a collapsed block also expands it so its children are visible.
+    Effect::watch(
+        move || focused_nav_root.get(),
+        move |zoom, _, _| {
+            if !embedded && query.with_untracked(|q| q.get("block")).as_ref() != zoom.as_ref() {
+                let path = location.pathname.get_untracked();
+                navigate(&zoom_href(&path, zoom.as_deref()), Default::default());
+            }
+
+            let Some(zoom) = zoom.clone() else {
+                return;
+            };
+            let mut expanded = None;
+            navs.update(|xs| {
+                if let Some(x) = xs.iter_mut().find(|x| x.id == zoom && !x.is_display) {
+                    x.is_display = true;
+                    expanded = Some(x.clone());
+                }
+            });
+            if let Some(n) = expanded {
+                let _ = sync_sv.try_with_value(|s| s.on_nav_meta_changed(&n));
+            }
+        },
+        false,
+    );
+
+    let depth_notice = DepthLimitNotice(RwSignal::new(false));
+
+    // Multi-block selection (Shift+Click / Shift+Up/Down).
+    let selection = SelectionCtx {
+        selected: RwSignal::new(vec![]),
+        anchor: RwSignal::new(None),
+        focus: RwSignal::new(None),
+    };
+
+    // Switching notes drops any selection (zoom follows the URL).
+    let note_id_for_selection = note_id.clone();
+    Effect::new(move |_| {
+        let _ = note_id_for_selection();
+        selection.clear();
+    });
+
+    // Escape leaves zoom (after exiting edit mode / clearing a multi-selection first).
+    let _zoom_key_handle = window_event_listener(ev::keydown, move |ev: web_sys::KeyboardEvent| {
+        if ev.key() != "Escape" {
+            return;
+        }
+        let Some(Some(_)) = focused_nav_root.try_get_untracked() else {
+            return;
+        };
+        if editing_id.get_untracked().is_some()
+            || !selection.selected.with_untracked(|xs| xs.is_empty())
+        {
+            return;
+        }
+        focused_nav_root.set(None);
+    });
+
+    // Apply `(id, new_parid, new_order)` moves locally, then persist them as one batch.
+    let apply_moves = move |moves: Vec<(String, String, f32)>, expand_parents: bool| {
+        if moves.is_empty() {
+            return;
+        }
+        let mut touched: Vec<String> = vec![];
+        navs.update(|xs| {
+            for (id, parid, order) in moves.iter() {
+                if let Some(x) = xs.iter_mut().find(|x| &x.id == id) {
+                    x.parid = parid.clone();
+                    x.same_deep_order = *order;
+                    touched.push(id.clone());
+                }
+                if expand_parents {
+                    if let Some(p) = xs.iter_mut().find(|x| &x.id == parid && !x.is_display) {
+                        p.is_display = true;
+                        touched.push(p.id.clone());
+                    }
+                }
+            }
+        });
+
+        let all = navs.get_untracked();
+        let changed = touched
+            .iter()
+            .filter_map(|id| all.iter().find(|n| &n.id == id).cloned())
+            .collect::<Vec<_>>();
+        let _ = sync_sv.try_with_value(|s| s.on_navs_meta_changed(&changed));
+    };
 
-                    if let Some(tmp_id) = maybe_tmp {
-                        editing_id.set(Some(tmp_id.clone()));
-                        editing_value.set(String::new());
-                        editing_snapshot.set(Some((tmp_id.clone(), String::new())));
-                        target_cursor_col.set(Some(0));
-                    } else {
-                        // Persist snapshot for normal notes.
-                        save_note_snapshot(&db_id2, &id, title, xs.clone(), crate::util::now_ms());
-                    }
+    // Bulk actions on the multi-selection (keyboard and the selection toolbar).
+    let note_id_for_bulk = StoredValue::new(note_id.clone());
+    let current_db_id = app_state.0.current_database_id;
+    let app_state_sv = StoredValue::new(app_state.clone());
 
-                    apply_nav_meta_overrides(&db_id2, &id, &mut xs);
-                    navs.set(xs);
+    // Tab / Shift+Tab: indent or outdent the selected subtrees. Never moves blocks across the
+    // zoom boundary.
+    let move_selected = move |outdent: bool| {
+        let selected = selection.selected.get_untracked();
+        let all = navs.get_untracked();
+        let zoom = focused_nav_root.get_untracked();
+        let roots = selection_roots(&all, &selected)
+            .into_iter()
+            .filter(|id| {
+                let Some(z) = zoom.as_deref() else {
+                    return true;
+                };
+                let parid = all.iter().find(|n| &n.id == id).map(|n| n.parid.as_str());
+                id != z && !(outdent && parid == Some(z))
+            })
+            .collect::<Vec<_>>();
+        if outdent {
+            let root_candidates = all
+                .iter()
+                .filter(|n| n.parid == ROOT_CONTAINER_PARENT_ID)
+                .collect::<Vec<_>>();
+            let root_parid = if root_candidates.len() == 1 {
+                root_candidates[0].id.as_str()
+            } else {
+                ROOT_CONTAINER_PARENT_ID
+            };
+            let renumbered = rebalance_for_outdent(&all, &roots, root_parid);
+            let all = apply_renumbered_orders(navs, sync_sv, &renumbered);
+            apply_moves(compute_outdent_moves(&all, &roots, root_parid), false);
+        } else {
+            let moves = compute_indent_moves(&all, &roots);
+            if moves
+                .iter()
+                .any(|(id, parid, _)| !fits_max_nav_depth(&all, id, parid))
+            {
+                depth_notice.flash();
+                return;
+            }
+            apply_moves(moves, true);
+        }
+    };
+
+    // Delete the selected subtrees (same local-first tombstones as single-block delete).
+    let delete_selected = move || {
+        let selected = selection.selected.get_untracked();
+        let all = navs.get_untracked();
+        let roots = selection_roots(&all, &selected);
+        let subtree = collect_subtree_ids(&all, &roots);
+        selection.clear();
+
+        navs.update(|xs| xs.retain(|n| !subtree.iter().any(|id| id == &n.id)));
+
+        let db_id_now = current_db_id.get_untracked().unwrap_or_default();
+        let note_id_now = note_id_for_bulk.with_value(|f| f());
+
+        let tmp_ids: Vec<String> = subtree
+            .iter()
+            .filter(|id| is_tmp_nav_id(id))
+            .cloned()
+            .collect();
+        let real_ids: Vec<String> = subtree
+            .iter()
+            .filter(|id| !is_tmp_nav_id(id))
+            .cloned()
+            .collect();
+
+        if !tmp_ids.is_empty() {
+            crate::drafts::remove_navs_from_drafts(&db_id_now, &note_id_now, &tmp_ids);
+            crate::cache::remove_navs_from_snapshot(&db_id_now, &note_id_now, &tmp_ids);
+        }
+        crate::cache::mark_navs_deleted_in_snapshot(&db_id_now, &note_id_now, &real_ids);
+        app_state_sv
+            .with_value(|s| offer_undo_nav_delete(s, &db_id_now, &note_id_now, &all, &subtree));
+
+        let deleted = real_ids
+            .iter()
+            .filter_map(|id| all.iter().find(|n| &n.id == id).cloned())
+            .map(|mut n| {
+                n.is_delete = true;
+                n
+            })
+            .collect::<Vec<_>>();
+        let _ = sync_sv.try_with_value(|s| s.on_navs_meta_changed(&deleted));
+    };
+
+    // `/template`: the picked template goes in at the caret, first line into the block and
+    // the rest as blocks after it (same path as a multi-line paste).
+    let template_insert = TemplateInsertCtx(RwSignal::new(None));
+    provide_context(template_insert);
+    let resume_editing = move |nav_id: String, content: String, caret: u32| {
+        editing_value.set(content.clone());
+        editing_snapshot.set(Some((nav_id.clone(), content)));
+        target_cursor_col.set(Some(caret));
+        editing_id.set(Some(nav_id));
+    };
+    let on_pick_template = Callback::new(move |template: NoteTemplate| {
+        let Some((nav_id, caret)) = template_insert.0.get_untracked() else {
+            return;
+        };
+        template_insert.0.set(None);
+        let Some(current) = navs.with_untracked(|xs| get_nav_content(xs, &nav_id)) else {
+            return;
+        };
+
+        let text = template_outline_text(&expand_template_now(&template));
+        let plan = plan_text_insert(&current, caret, &text);
+        navs.update(|xs| {
+            let _ = apply_nav_content(xs, &nav_id, &plan.current_content);
+        });
+        let _ = sync_sv.try_with_value(|s| s.on_nav_changed(&nav_id, &plan.current_content));
+        let note_id_now = note_id_for_bulk.with_value(|f| f());
+        app_state_sv.with_value(|s| {
+            insert_blocks_after(s, navs, sync_sv, &note_id_now, &nav_id, &plan.blocks)
+        });
+        resume_editing(nav_id, plan.current_content, plan.caret_utf16);
+    });
+    let on_cancel_template = Callback::new(move |()| {
+        let Some((nav_id, caret)) = template_insert.0.get_untracked() else {
+            return;
+        };
+        template_insert.0.set(None);
+        if let Some(content) = navs.with_untracked(|xs| get_nav_content(xs, &nav_id)) {
+            resume_editing(nav_id, content, caret);
+        }
+    });
+
+    // Collapse (`display = false`) or expand the selected blocks that have children.
+    let set_selected_display = move |display: bool| {
+        let selected = selection.selected.get_untracked();
+        let mut changed: Vec<Nav> = vec![];
+        navs.update(|xs| {
+            let parents = xs
+                .iter()
+                .filter(|n| !n.is_delete)
+                .map(|n| n.parid.clone())
+                .collect::<std::collections::HashSet<_>>();
+            for x in xs.iter_mut() {
+                if x.is_display != display && parents.contains(&x.id) && selected.contains(&x.id) {
+                    x.is_display = display;
+                    changed.push(x.clone());
                 }
-                Err(e) => {
-                    sync2.mark_backend_offline_api(&e);
+            }
+        });
+        let _ = sync_sv.try_with_value(|s| s.on_navs_meta_changed(&changed));
+    };
 
-                    // Backend unreachable: fall back to snapshot (read cache), and suppress errors.
-                    if !sync2.is_backend_online() {
-                        if let Some(snap) = load_note_snapshot(&db_id2, &id) {
-                            offline.set(true);
-                            offline_missing_snapshot.set(false);
-                            error.set(None);
-                            let mut xs = snap.navs;
-                            apply_nav_meta_overrides(&db_id2, &id, &mut xs);
-                            navs.set(xs);
-                        } else {
-                            offline.set(true);
-                            offline_missing_snapshot.set(true);
-                            error.set(None);
-                            navs.set(vec![]);
-                        }
+    // Keyboard handling while a multi-selection exists (the editing surface is not focused).
+    let _selection_key_handle =
+        window_event_listener(ev::keydown, move |ev: web_sys::KeyboardEvent| {
+            let Some(selected) = selection.selected.try_get_untracked() else {
+                return;
+            };
+            if selected.is_empty() || editing_id.get_untracked().is_some() {
+                return;
+            }
+
+            // Don't hijack keys typed into other inputs (e.g. search).
+            let target_tag = ev
+                .target()
+                .and_then(|t| t.dyn_into::<web_sys::Element>().ok())
+                .map(|el| el.tag_name().to_lowercase());
+            if matches!(target_tag.as_deref(), Some("input") | Some("textarea")) {
+                return;
+            }
+
+            let key = ev.key();
+            let all = navs.get_untracked();
+
+            match key.as_str() {
+                "Escape" => {
+                    ev.prevent_default();
+                    selection.clear();
+                }
+                "ArrowUp" | "ArrowDown" if ev.shift_key() => {
+                    ev.prevent_default();
+                    let zoom = focused_nav_root.get_untracked();
+                    let visible = visible_preorder_within(&all, zoom.as_deref());
+                    let Some(anchor) = selection.anchor.get_untracked() else {
+                        return;
+                    };
+                    let focus = selection.focus.get_untracked().unwrap_or(anchor.clone());
+                    let Some(idx) = visible.iter().position(|id| id == &focus) else {
+                        return;
+                    };
+                    let next = if key == "ArrowUp" {
+                        idx.checked_sub(1)
                     } else {
-                        // Non-connectivity error.
-                        offline.set(false);
-                        offline_missing_snapshot.set(false);
-                        error.set(Some(e.to_string()));
+                        Some(idx + 1).filter(|i| *i < visible.len())
+                    };
+                    if let Some(next) = next {
+                        let next_id = visible[next].clone();
+                        selection
+                            .selected
+                            .set(selection_range(&visible, &anchor, &next_id));
+                        selection.focus.set(Some(next_id));
                     }
                 }
+                "Tab" => {
+                    ev.prevent_default();
+                    move_selected(ev.shift_key());
+                }
+                "Backspace" | "Delete" => {
+                    ev.prevent_default();
+                    delete_selected();
+                }
+                _ => {}
             }
-            loading.set(false);
         });
-    });
 
-    // Focus handled by OutlineNode (see below).
-    // (focus moved to OutlineNode)
-
-    // Sync controller (global, local-first)
-    let sync_sv = StoredValue::new(expect_context::<NoteSyncController>());
+    // Cmd/Ctrl+C with a multi-selection: copy the blocks as indented plain text.
+    let _selection_copy_handle = window_event_listener(ev::copy, move |ev: web_sys::ClipboardEvent| {
+        let Some(selected) = selection.selected.try_get_untracked() else {
+            return;
+        };
+        if selected.is_empty() || editing_id.get_untracked().is_some() {
+            return;
+        }
+        let Some(dt) = ev.clipboard_data() else {
+            return;
+        };
 
-    // Keep sync controller aware of which nav is being edited (for pagehide flush priority).
-    Effect::new(move |_| {
-        let _ = sync_sv.try_with_value(|s| s.set_editing_nav(editing_id.get()));
+        let all = navs.get_untracked();
+        let text = selection_plain_text(&all, &selection_roots(&all, &selected));
+        if dt.set_data("text/plain", &text).is_ok() {
+            ev.prevent_default();
+        }
     });
 
     // Click outside editor to exit editing mode.
     // Use a window click listener (bubble phase) so we don't swallow the target click (e.g. sidebar navigation).
     let _click_handle = window_event_listener(ev::click, move |ev: web_sys::MouseEvent| {
+        // Any plain click clears the multi-selection (Shift+Click extends it instead), except
+        // on the selection toolbar.
+        let on_toolbar = ev
+            .target()
+            .and_then(|t| t.dyn_into::<web_sys::Element>().ok())
+            .and_then(|el| el.closest("[data-selection-toolbar]").ok().flatten())
+            .is_some();
+        if !ev.shift_key() && !on_toolbar {
+            if let Some(true) = selection.selected.try_with_untracked(|xs| !xs.is_empty()) {
+                selection.clear();
+            }
+        }
+
         // Only act if currently editing.
         let Some(current) = editing_id.try_get_untracked().flatten() else {
             return;
@@ -1023,7 +2789,14 @@ pub fn OutlineEditor(
             return;
         };
 
-        if !should_exit_edit_on_click_target(Some(target.unchecked_into())) {
+        // A click in another editor on the page always ends editing here.
+        let in_other_editor = target
+            .closest(".outline-editor")
+            .ok()
+            .flatten()
+            .zip(editor_root.try_get_untracked().flatten())
+            .is_some_and(|(ed, root)| ed != web_sys::Element::from(root));
+        if !in_other_editor && !should_exit_edit_on_click_target(Some(target.unchecked_into())) {
             return;
         }
 
@@ -1047,18 +2820,26 @@ pub fn OutlineEditor(
         }
     });
 
-    // Provide autocomplete context to OutlineNode.
+    // Provide selection + autocomplete context to OutlineNode.
+    provide_context(selection);
+    provide_context(NavDraftCtx(nav_draft_memo));
+    provide_context(depth_notice);
+    provide_context(deep_link_highlight);
     provide_context(AutocompleteCtx {
         ac_open,
         ac_query,
         ac_items,
         ac_index,
         ac_start_utf16,
-        titles_cache_db,
+        ac_block_mode,
+        block_navs_loading,
+        titles_cache_key,
         titles_cache,
         titles_loading,
     });
 
+    let note_id_for_crumbs = note_id.clone();
+
     view! {
         <div class="rounded-md p-3">
 
@@ -1084,15 +2865,168 @@ pub fn OutlineEditor(
                 </div>
             </Show>
 
+            {move || block_not_found.get().map(|nav_id| view! {
+                <div
+                    role="status"
+                    class="mt-2 flex items-center gap-2 text-xs text-muted-foreground"
+                    title=nav_id
+                >
+                    "Block not found. It may have been deleted or moved to another note."
+                    <button
+                        type="button"
+                        class="rounded px-1 hover:bg-surface-hover hover:text-foreground"
+                        on:click=move |_| block_not_found.set(None)
+                    >
+                        "Dismiss"
+                    </button>
+                </div>
+            })}
+
+            <Show when=move || depth_notice.0.get() fallback=|| ().into_view()>
+                <div
+                    role="status"
+                    class="fixed bottom-4 right-4 z-50 rounded-md border border-border bg-card px-3 py-2 text-xs text-card-foreground shadow-lg"
+                >
+                    "Maximum nesting depth reached"
+                </div>
+            </Show>
+
             // Opening missing pages does not show an error banner here.
 
-            <div class=move || {
-                if editing_id.get().is_some() {
-                    "mt-2 outline-editor outline-editor--editing relative"
-                } else {
-                    "mt-2 outline-editor relative"
+            {move || {
+                let zoom_id = focused_nav_root.get()?;
+                let crumbs = navs.with(|xs| zoom_breadcrumb(xs, &zoom_id));
+                if crumbs.is_empty() {
+                    return None;
+                }
+                let last = crumbs.len() - 1;
+
+                // First crumb: the note title (leaves the zoom).
+                let nid = note_id_for_crumbs();
+                let db_id = app_state.0.current_database_id.get().unwrap_or_default();
+                let note_title = app_state
+                    .0
+                    .notes
+                    .with(|xs| xs.iter().find(|n| n.id == nid).map(|n| n.title.clone()))
+                    .map(|t| crate::drafts::get_title_override(&db_id, &nid, &t))
+                    .filter(|t| !t.trim().is_empty())
+                    .unwrap_or_else(|| "Untitled".to_string());
+
+                Some(view! {
+                    <nav class="mt-2 flex flex-wrap items-center gap-1 text-xs text-muted-foreground" aria-label="Zoom breadcrumb">
+                        <button
+                            class="rounded px-1 hover:bg-surface-hover hover:text-foreground"
+                            on:click=move |_| focused_nav_root.set(None)
+                        >
+                            {note_title}
+                        </button>
+                        {crumbs
+                            .into_iter()
+                            .enumerate()
+                            .map(|(i, (id, content))| {
+                                let label = content.lines().next().unwrap_or("").trim().to_string();
+                                let label = if label.is_empty() {
+                                    "Untitled".to_string()
+                                } else if label.chars().count() > 40 {
+                                    format!("{}…", label.chars().take(40).collect::<String>())
+                                } else {
+                                    label
+                                };
+
+                                let item = if i == last {
+                                    view! { <span class="px-1 text-foreground">{label}</span> }.into_any()
+                                } else {
+                                    view! {
+                                        <button
+                                            class="rounded px-1 hover:bg-surface-hover hover:text-foreground"
+                                            on:click=move |_| focused_nav_root.set(Some(id.clone()))
+                                        >
+                                            {label}
+                                        </button>
+                                    }
+                                    .into_any()
+                                };
+
+                                view! {
+                                    <span aria-hidden="true">"›"</span>
+                                    {item}
+                                }
+                            })
+                            .collect_view()}
+                    </nav>
+                })
+            }}
+
+            <Show when=move || selection.selected.with(|xs| !xs.is_empty()) fallback=|| ().into_view()>
+                <div
+                    data-selection-toolbar=""
+                    class="sticky top-2 z-20 mt-2 flex flex-wrap items-center gap-1 rounded-md border border-border bg-background p-1 text-xs shadow"
+                    role="toolbar"
+                    aria-label="Selected blocks"
+                >
+                    <span class="px-2 text-muted-foreground">
+                        {move || format!("{} selected", selection.selected.with(|xs| xs.len()))}
+                    </span>
+                    <Button
+                        variant=ButtonVariant::Ghost
+                        size=ButtonSize::Sm
+                        on:click=move |_| delete_selected()
+                    >
+                        "Delete selected"
+                    </Button>
+                    <Button
+                        variant=ButtonVariant::Ghost
+                        size=ButtonSize::Sm
+                        on:click=move |_| set_selected_display(false)
+                    >
+                        "Collapse"
+                    </Button>
+                    <Button
+                        variant=ButtonVariant::Ghost
+                        size=ButtonSize::Sm
+                        on:click=move |_| set_selected_display(true)
+                    >
+                        "Expand"
+                    </Button>
+                    <Button
+                        variant=ButtonVariant::Ghost
+                        size=ButtonSize::Sm
+                        attr:title="Tab"
+                        on:click=move |_| move_selected(false)
+                    >
+                        "Indent"
+                    </Button>
+                    <Button
+                        variant=ButtonVariant::Ghost
+                        size=ButtonSize::Sm
+                        attr:title="Shift+Tab"
+                        on:click=move |_| move_selected(true)
+                    >
+                        "Outdent"
+                    </Button>
+                    <Button
+                        variant=ButtonVariant::Ghost
+                        size=ButtonSize::Sm
+                        on:click=move |_| selection.clear()
+                    >
+                        "Clear"
+                    </Button>
+                </div>
+            </Show>
+
+            <div
+                node_ref=editor_root
+                class=move || {
+                    if editing_id.get().is_some() {
+                        "mt-2 outline-editor outline-editor--editing relative"
+                    } else {
+                        "mt-2 outline-editor relative"
+                    }
                 }
-            }>
+                on:focusin=move |_| claim_route()
+                on:click:capture=move |_| claim_route()
+                on:dragstart:capture=move |_| claim_route()
+            >
                 // Loading overlay (does not affect layout; avoids content shift).
                 <Show when=move || loading.get() fallback=|| ().into_view()>
                     <div class="absolute inset-0 z-10 flex items-center justify-center bg-background/40">
@@ -1130,6 +3064,14 @@ pub fn OutlineEditor(
                         .partial_cmp(&b.same_deep_order)
                         .unwrap_or(std::cmp::Ordering::Equal));
 
+                    // Zoomed: render only the focused subtree.
+                    if let Some(zoom) = focused_nav_root
+                        .get()
+                        .and_then(|z| all.iter().find(|n| n.id == z && !n.is_delete).cloned())
+                    {
+                        roots = vec![zoom];
+                    }
+
                     if roots.is_empty() {
                         // Intentionally render nothing here. Empty notes are auto-seeded with a first node,
                         // and during route/load transitions this avoids a distracting flash of "No nodes".
@@ -1158,9 +3100,11 @@ pub fn OutlineEditor(
                                                 editing_snapshot=editing_snapshot
                                                 dragging_nav_id=dragging_nav_id
                                                 drag_over_nav_id=drag_over_nav_id
+                                                drag_over_position=drag_over_position
                                                 target_cursor_col=target_cursor_col
                                                 editing_ref=editing_ref
                                                 focused_nav_id=focused_nav_id
+                                                focused_nav_root=focused_nav_root
                                             />
                                         }
                                     }
@@ -1171,10 +3115,156 @@ pub fn OutlineEditor(
                     }
                 }}
             </div>
+
+            <TemplatePicker
+                open=Signal::derive(move || template_insert.0.with(|t| t.is_some()))
+                on_pick=on_pick_template
+                on_cancel=on_cancel_template
+            />
         </div>
     }
 }
 
+/// Picker opened by `/template` in a block: filter the saved templates by name, Enter or
+/// click inserts one, Escape or a click outside cancels.
+#[component]
+fn TemplatePicker(
+    open: Signal<bool>,
+    on_pick: Callback<NoteTemplate>,
+    on_cancel: Callback<()>,
+) -> impl IntoView {
+    let app_state = expect_context::<AppContext>();
+    let templates = app_state.0.templates;
+
+    let query: RwSignal<String> = RwSignal::new(String::new());
+    let selected: RwSignal<usize> = RwSignal::new(0);
+    let input_ref: NodeRef<html::Input> = NodeRef::new();
+
+    Effect::new(move |_| {
+        if open.get() {
+            query.set(String::new());
+            selected.set(0);
+        }
+    });
+
+    Effect::new(move |_| {
+        if let Some(input) = input_ref.get() {
+            let _ = input.focus();
+        }
+    });
+
+    let results = Memo::new(move |_| {
+        let q = query.get().trim().to_lowercase();
+        templates.with(|ts| {
+            ts.iter()
+                .filter(|t| t.name.to_lowercase().contains(&q))
+                .cloned()
+                .collect::<Vec<_>>()
+        })
+    });
+
+    let on_keydown = move |ev: web_sys::KeyboardEvent| match ev.key().as_str() {
+        "ArrowDown" => {
+            ev.prevent_default();
+            let len = results.with_untracked(|xs| xs.len());
+            if len > 0 {
+                selected.update(|i| *i = (*i + 1).min(len - 1));
+            }
+        }
+        "ArrowUp" => {
+            ev.prevent_default();
+            selected.update(|i| *i = i.saturating_sub(1));
+        }
+        "Enter" => {
+            ev.prevent_default();
+            let picked = results.with_untracked(|xs| xs.get(selected.get_untracked()).cloned());
+            if let Some(t) = picked {
+                on_pick.run(t);
+            }
+        }
+        "Escape" => {
+            ev.prevent_default();
+            on_cancel.run(());
+        }
+        _ => {}
+    };
+
+    view! {
+        <Show when=move || open.get()>
+            <div
+                class="fixed inset-0 z-50 flex items-start justify-center bg-black/30 px-4 pt-24"
+                on:mousedown=move |_| on_cancel.run(())
+            >
+                <div
+                    class="w-full max-w-md rounded-md border border-border bg-background p-2 shadow-lg"
+                    on:mousedown=move |ev: web_sys::MouseEvent| ev.stop_propagation()
+                >
+                    <input
+                        node_ref=input_ref
+                        class="h-9 w-full rounded-md border border-input bg-transparent px-3 py-1 text-sm shadow-xs outline-none focus-visible:border-ring focus-visible:ring-2 focus-visible:ring-ring/50"
+                        placeholder="Insert template…"
+                        aria-label="Insert template"
+                        prop:value=move || query.get()
+                        on:input=move |ev| {
+                            query.set(event_target_value(&ev));
+                            selected.set(0);
+                        }
+                        on:keydown=on_keydown
+                    />
+                    {move || {
+                        let rows = results.get();
+                        if rows.is_empty() {
+                            let message = if templates.with(|ts| ts.is_empty()) {
+                                "No templates yet. Add one in Settings."
+                            } else {
+                                "No matches."
+                            };
+                            return view! {
+                                <div class="px-2 py-3 text-xs text-muted-foreground">{message}</div>
+                            }
+                            .into_any();
+                        }
+
+                        view! {
+                            <Command class="mt-2" should_filter=false disable_scripts=true>
+                                <CommandList class="max-h-80 min-h-0">
+                                    {rows
+                                        .into_iter()
+                                        .enumerate()
+                                        .map(|(i, t)| {
+                                            let is_selected = Signal::derive(move || selected.get() == i);
+                                            let blocks = t.navs.len();
+                                            let name = t.name.clone();
+                                            view! {
+                                                <CommandItem
+                                                    value=name.clone()
+                                                    selected=is_selected
+                                                    class="flex items-center justify-between gap-3 aria-selected:bg-accent"
+                                                    on_mousedown=Some(Callback::new(move |ev: web_sys::MouseEvent| {
+                                                        ev.prevent_default();
+                                                        on_pick.run(t.clone());
+                                                    }))
+                                                    on:mousemove=move |_| selected.set(i)
+                                                >
+                                                    <span class="min-w-0 truncate">{name}</span>
+                                                    <span class="shrink-0 text-xs text-muted-foreground">
+                                                        {if blocks == 1 { "1 block".to_string() } else { format!("{blocks} blocks") }}
+                                                    </span>
+                                                </CommandItem>
+                                            }
+                                        })
+                                        .collect_view()}
+                                </CommandList>
+                            </Command>
+                        }
+                        .into_any()
+                    }}
+                </div>
+            </div>
+        </Show>
+    }
+}
+
 #[component]
 pub fn OutlineNode(
     nav_id: String,
@@ -1186,13 +3276,17 @@ pub fn OutlineNode(
     editing_snapshot: RwSignal<Option<(String, String)>>,
     dragging_nav_id: RwSignal<Option<String>>,
     drag_over_nav_id: RwSignal<Option<String>>,
+    drag_over_position: RwSignal<DropPosition>,
     target_cursor_col: RwSignal<Option<u32>>,
     editing_ref: NodeRef<html::Div>,
     focused_nav_id: RwSignal<Option<String>>,
+    focused_nav_root: RwSignal<Option<String>>,
 ) -> impl IntoView {
     let app_state = expect_context::<AppContext>();
     let sync_sv = StoredValue::new(expect_context::<NoteSyncController>());
     let ac = expect_context::<AutocompleteCtx>();
+    let selection = expect_context::<SelectionCtx>();
+    let template_insert = expect_context::<TemplateInsertCtx>();
     let navigate = leptos_router::hooks::use_navigate();
 
     // Capture autocomplete signals directly for event handlers that may fire after unmount (e.g. blur).
@@ -1334,6 +3428,78 @@ pub fn OutlineNode(
         }
     });
 
+    let depth_notice = expect_context::<DepthLimitNotice>();
+    let deep_link_highlight = expect_context::<DeepLinkHighlight>();
+
+    // Row sync status; a just-finished save shows a check for 2s.
+    let nav_draft = expect_context::<NavDraftCtx>().0;
+    let nav_id_for_status = nav_id.clone();
+    let sync_status = Memo::new(move |_| {
+        nav_draft.with(|d| {
+            d.statuses
+                .get(&nav_id_for_status)
+                .copied()
+                .unwrap_or(NavSyncStatus::Clean)
+        })
+    });
+    let nav_sync_errors = sync_sv.with_value(|s| s.nav_sync_errors());
+    let nav_id_for_error = nav_id.clone();
+    let sync_error =
+        Memo::new(move |_| nav_sync_errors.with(|m| m.get(&nav_id_for_error).cloned()));
+    let show_saved: RwSignal<bool> = RwSignal::new(false);
+    Effect::new(move |prev: Option<NavSyncStatus>| {
+        let status = sync_status.get();
+        if status != NavSyncStatus::Clean {
+            show_saved.set(false);
+        } else if prev.is_some_and(|p| p != NavSyncStatus::Clean) {
+            show_saved.set(true);
+            set_timeout(
+                move || {
+                    let _ = show_saved.try_set(false);
+                },
+                std::time::Duration::from_secs(2),
+            );
+        }
+        status
+    });
+
+    // "edited 5m ago", refreshed each time the pointer enters the row.
+    let edited_hint: RwSignal<Option<String>> = RwSignal::new(None);
+    let refresh_edited_hint = move || {
+        let id = nav_id_sv.get_value();
+        let local_ms = nav_draft.with_untracked(|d| d.edited_ms.get(&id).copied());
+        let hint = navs
+            .with_untracked(|xs| xs.iter().find(|n| n.id == id).cloned())
+            .and_then(|n| nav_edited_ms(&n, local_ms))
+            .map(|then| format_edited_ago(then, crate::util::now_ms()));
+        edited_hint.set(hint);
+    };
+
+    // Task checkbox: rewrite the marker through the normal edit path (navs, draft, sync).
+    let set_task_done = move |done: bool| {
+        let id = nav_id_sv.get_value();
+        let db_id = app_state_sv
+            .get_value()
+            .0
+            .current_database_id
+            .get_untracked()
+            .unwrap_or_default();
+        let note_id = note_id_sv.get_value();
+        let Some(saved) = navs.with_untracked(|xs| get_nav_content(xs, &id)) else {
+            return;
+        };
+        let current = get_nav_override(&db_id, &note_id, &id, &saved);
+        let next = set_todo_done(&current, done);
+        if next == current {
+            return;
+        }
+        navs.update(|xs| {
+            let _ = apply_nav_content(xs, &id, &next);
+        });
+        touch_nav(&db_id, &note_id, &id, &next);
+        let _ = sync_sv.try_with_value(|s| s.on_nav_changed(&id, &next));
+    };
+
     let indent_px = (depth * 18) as i32;
 
     view! {
@@ -1361,15 +3527,18 @@ pub fn OutlineNode(
                 });
 
                 let has_kids = !kids.is_empty();
-                let (bullet, bullet_class) = if has_kids {
-                    (
-                        if n.is_display { "▾" } else { "▸" },
-                        // Bigger + clearly clickable
-                        "mt-0.5 h-5 w-5 text-base leading-none text-muted-foreground cursor-pointer hover:text-foreground/80",
-                    )
+                // Caret toggles expand/collapse; the bullet dot zooms into the block (Roam-style).
+                let caret = if n.is_display { "▾" } else { "▸" };
+                let caret_class = if has_kids {
+                    "mt-0.5 h-5 w-4 text-xs leading-none text-muted-foreground cursor-pointer hover:text-foreground/80"
+                } else {
+                    "mt-0.5 h-5 w-4 invisible"
+                };
+                let bullet_class = if has_kids && !n.is_display {
+                    // Collapsed blocks get a halo so hidden children are still discoverable.
+                    "mt-0.5 h-5 w-5 rounded-full bg-muted text-base leading-none text-muted-foreground cursor-pointer hover:text-foreground/80"
                 } else {
-                    // Make leaf bullets more visible than a tiny middle dot.
-                    ("•", "mt-0.5 h-5 w-5 text-base leading-none text-muted-foreground")
+                    "mt-0.5 h-5 w-5 text-base leading-none text-muted-foreground cursor-pointer hover:text-foreground/80"
                 };
 
                 let on_toggle_cb = on_toggle.clone();
@@ -1396,9 +3565,11 @@ pub fn OutlineNode(
                                         editing_snapshot=editing_snapshot
                                         dragging_nav_id=dragging_nav_id
                                         drag_over_nav_id=drag_over_nav_id
+                                        drag_over_position=drag_over_position
                                         target_cursor_col=target_cursor_col
                                         editing_ref=editing_ref
                                         focused_nav_id=focused_nav_id
+                                        focused_nav_root=focused_nav_root
                                     />
                                 }
                             }
@@ -1422,19 +3593,36 @@ pub fn OutlineNode(
                                     let is_dragging = dragging_nav_id.get().is_some();
                                     let is_drag_source = dragging_nav_id.get().as_deref() == Some(id.as_str());
                                     let is_drag_over = drag_over_nav_id.get().as_deref() == Some(id.as_str());
-
-                                    if is_editing {
-                                        "outline-row outline-row--editing flex items-center gap-2 py-1"
+                                    let is_selected = selection.selected.with(|xs| xs.contains(&id));
+                                    let is_highlighted = deep_link_highlight.0.with(|h| h.as_deref() == Some(id.as_str()));
+
+                                    let state = if is_editing {
+                                        // Left accent on the block being edited.
+                                        "outline-row--editing border-primary bg-accent/10"
+                                    } else if is_selected && !is_dragging {
+                                        "border-transparent rounded-md bg-accent-soft"
                                     } else if is_dragging && is_drag_source {
                                         // Make the dragged row semi-transparent (keep content visible).
-                                        "outline-row flex items-center gap-2 py-1 rounded-md bg-muted/30 opacity-40"
+                                        "border-transparent rounded-md bg-muted/30 opacity-40"
                                     } else if is_dragging && is_drag_over {
-                                        // Highlight drop target only while dragging.
-                                        "outline-row flex items-center gap-2 py-1 rounded-md bg-muted ring-1 ring-ring/40"
+                                        // Highlight drop target only while dragging; the indicator shows the drop zone.
+                                        match drag_over_position.get() {
+                                            DropPosition::Before => "border-transparent relative before:absolute before:inset-x-0 before:-top-px before:h-0.5 before:rounded-full before:bg-accent before:content-['']",
+                                            DropPosition::After => "border-transparent relative after:absolute after:inset-x-0 after:-bottom-px after:h-0.5 after:rounded-full after:bg-accent after:content-['']",
+                                            DropPosition::Inside => "border-transparent rounded-md bg-muted ring-1 ring-ring/40",
+                                        }
+                                    } else if is_highlighted {
+                                        "outline-row--highlight border-transparent"
                                     } else {
-                                        "outline-row flex items-center gap-2 py-1"
-                                    }
+                                        "border-transparent"
+                                    };
+                                    // Every row keeps the (transparent) left border, so the editing accent fades
+                                    // in without shifting the text.
+                                    format!(
+                                        "outline-row flex items-center gap-2 py-1 border-l-2 transition-colors duration-150 {state}"
+                                    )
                                 }
+                                on:mouseenter=move |_| refresh_edited_hint()
                                 // Drag is started from the bullet/triangle only (button below).
                                 on:dragenter=move |ev: web_sys::DragEvent| {
                                     let target_id = nav_id_sv.get_value();
@@ -1471,6 +3659,11 @@ pub fn OutlineNode(
 
                                     ev.prevent_default();
                                     drag_over_nav_id.set(Some(target_id));
+                                    if let Some(pos) = drop_position_from_event(&ev) {
+                                        if drag_over_position.get_untracked() != pos {
+                                            drag_over_position.set(pos);
+                                        }
+                                    }
                                     if let Some(dt) = ev.data_transfer() {
                                         dt.set_drop_effect("move");
                                     }
@@ -1508,24 +3701,36 @@ pub fn OutlineNode(
                                         return;
                                     }
 
-                                    // Decide before/after by cursor position inside target row.
-                                    let insert_after = ev
-                                        .current_target()
-                                        .and_then(|t| t.dyn_into::<web_sys::Element>().ok())
-                                        .map(|el| el.get_bounding_client_rect())
-                                        .map(|rect| {
-                                            let mid = rect.top() + rect.height() / 2.0;
-                                            (ev.client_y() as f64) >= mid
-                                        })
-                                        .unwrap_or(true);
+                                    // Decide before/after/inside by cursor position inside target row.
+                                    let pos = drop_position_from_event(&ev).unwrap_or(DropPosition::After);
 
                                     let _note_id_now = note_id_sv.get_value();
-                                    let all = navs.get_untracked();
-                                    let Some((new_parid, new_order)) =
-                                        compute_reorder_target(&all, &dragged_id, &target_id, insert_after)
-                                    else {
+                                    let renumbered = match pos {
+                                        DropPosition::Inside => vec![],
+                                        DropPosition::Before | DropPosition::After => rebalance_for_reorder(
+                                            &navs.get_untracked(),
+                                            &dragged_id,
+                                            &target_id,
+                                            pos == DropPosition::After,
+                                        ),
+                                    };
+                                    let all = apply_renumbered_orders(navs, sync_sv, &renumbered);
+                                    let target = match pos {
+                                        DropPosition::Inside => compute_reparent_target(&all, &dragged_id, &target_id),
+                                        DropPosition::Before => compute_reorder_target(&all, &dragged_id, &target_id, false),
+                                        DropPosition::After => compute_reorder_target(&all, &dragged_id, &target_id, true),
+                                    };
+                                    let Some((new_parid, new_order)) = target else {
                                         return;
                                     };
+                                    if !fits_max_nav_depth(&all, &dragged_id, &new_parid) {
+                                        depth_notice.flash();
+                                        return;
+                                    }
+
+                                    // Dropping inside a collapsed block expands it so the moved node stays visible.
+                                    let expand_target = pos == DropPosition::Inside
+                                        && all.iter().any(|n| n.id == target_id && !n.is_display);
 
                                     // Update local state.
                                     navs.update(|xs| {
@@ -1533,7 +3738,17 @@ pub fn OutlineNode(
                                             x.parid = new_parid.clone();
                                             x.same_deep_order = new_order;
                                         }
+                                        if expand_target {
+                                            if let Some(t) = xs.iter_mut().find(|x| x.id == target_id) {
+                                                t.is_display = true;
+                                            }
+                                        }
                                     });
+                                    if expand_target {
+                                        if let Some(t) = navs.get_untracked().into_iter().find(|n| n.id == target_id) {
+                                            let _ = sync_sv.try_with_value(|s| s.on_nav_meta_changed(&t));
+                                        }
+                                    }
 
                                     // Persist metadata change to local draft; sync controller handles network.
                                     let mut nav_for_meta = None;
@@ -1547,6 +3762,23 @@ pub fn OutlineNode(
                                     }
                                 }
                             >
+                            <button
+                                class=caret_class
+                                tabindex="-1"
+                                on:click=move |ev: web_sys::MouseEvent| {
+                                    if has_kids {
+                                        on_toggle_cb.run(ev)
+                                    }
+                                }
+                                aria-hidden=(!has_kids).to_string()
+                                title=if has_kids {
+                                    if n.is_display { "Collapse" } else { "Expand" }
+                                } else {
+                                    ""
+                                }
+                            >
+                                {caret}
+                            </button>
                             <button
                                 class=bullet_class
                                 draggable="true"
@@ -1586,22 +3818,29 @@ pub fn OutlineNode(
                                     dragging_nav_id.set(None);
                                     drag_over_nav_id.set(None);
                                 }
-                                on:click={
-                                    let has_kids = has_kids;
-                                    move |ev| {
-                                        if has_kids {
-                                            on_toggle_cb.run(ev)
+                                on:click=move |ev: web_sys::MouseEvent| {
+                                    // Shift+Click selects a range of blocks instead of zooming.
+                                    if ev.shift_key() {
+                                        ev.prevent_default();
+                                        let editing_now = editing_id.get_untracked();
+                                        if editing_now.is_some() {
+                                            editing_id.set(None);
+                                            editing_snapshot.set(None);
                                         }
+                                        let zoom = focused_nav_root.get_untracked();
+                                        let visible = visible_preorder_within(&navs.get_untracked(), zoom.as_deref());
+                                        selection.extend_to(&visible, editing_now, nav_id_sv.get_value());
+                                        return;
+                                    }
+                                    // Blocks with children zoom on a plain click; leaves need Cmd/Ctrl.
+                                    if has_kids || ev.meta_key() || ev.ctrl_key() {
+                                        ev.prevent_default();
+                                        focused_nav_root.set(Some(nav_id_sv.get_value()));
                                     }
                                 }
-                                aria-disabled=move || (!has_kids).to_string()
-                                title=move || if has_kids {
-                                    if n.is_display { "Collapse" } else { "Expand" }
-                                } else {
-                                    ""
-                                }
+                                title="Zoom in"
                             >
-                                {bullet}
+                                "•"
                             </button>
 
                             <div class="min-w-0 flex-1 text-sm">
@@ -1628,12 +3867,41 @@ pub fn OutlineNode(
                                         let id_for_click = nav_id_sv.get_value();
 
                                         // navigate provided by component scope
-                                        let tokens = parse_wiki_tokens(&content_display);
+                                        // A leading `{{TODO}}` / `{{DONE}}` renders as a checkbox; done tasks are
+                                        // struck through. Markdown next; wiki tokens are resolved inside its text runs.
+                                        let (todo, body) = split_todo(&content_display);
+                                        let body_class = if todo == Some(true) {
+                                            "text-muted-foreground line-through"
+                                        } else {
+                                            ""
+                                        };
+                                        let runs = flatten_inline(&parse_inline_markdown(body));
 
                                         return view! {
                                             <div
                                                 class="cursor-text whitespace-pre-wrap min-h-[28px] px-3 py-1"
-                                                on:mousedown=move |_ev: web_sys::MouseEvent| {
+                                                on:mousedown=move |ev: web_sys::MouseEvent| {
+                                                    // Shift+Click: extend the multi-block selection instead of editing.
+                                                    if ev.shift_key() {
+                                                        ev.prevent_default();
+                                                        let clicked = id_for_click.clone();
+                                                        let editing_now = editing_id.get_untracked();
+
+                                                        // Leave edit mode; the editing surface saves itself on blur.
+                                                        if editing_id.get_untracked().is_some() {
+                                                            if let Some(el) = editing_ref.get_untracked() {
+                                                                let _ = el.blur();
+                                                            }
+                                                            editing_id.set(None);
+                                                            editing_snapshot.set(None);
+                                                        }
+
+                                                        let zoom = focused_nav_root.get_untracked();
+                                                        let visible = visible_preorder_within(&navs.get_untracked(), zoom.as_deref());
+                                                        selection.extend_to(&visible, editing_now, clicked);
+                                                        return;
+                                                    }
+
                                                     // Use mousedown (not click) for single-click switching.
                                                     // IMPORTANT: don't rely on `blur` to save. When a focused input is
                                                     // unmounted by state updates, browsers may not fire blur reliably.
@@ -1704,19 +3972,96 @@ pub fn OutlineNode(
                                                     cb.forget();
                                                 }
                                             >
-                                                {{
-                                                    let app_state_for_tokens = app_state_sv.get_value();
-                                                    let navigate_for_tokens = navigate_sv.get_value();
-
-                                                    tokens
-                                                        .into_iter()
-                                                        .map(move |t| {
+                                                {todo.map(|done| view! {
+                                                    <input
+                                                        type="checkbox"
+                                                        class="mr-2 size-3.5 cursor-pointer align-middle"
+                                                        prop:checked=done
+                                                        aria-label=if done { "Mark as not done" } else { "Mark as done" }
+                                                        // Toggle without entering edit mode.
+                                                        on:mousedown=|ev: web_sys::MouseEvent| ev.stop_propagation()
+                                                        on:click=move |ev: web_sys::MouseEvent| {
+                                                            ev.stop_propagation();
+                                                            set_task_done(!done);
+                                                        }
+                                                    />
+                                                })}
+                                                <span class=body_class>
+                                                    {{
+                                                        let app_state_for_tokens = app_state_sv.get_value();
+                                                        let navigate_for_tokens = navigate_sv.get_value();
+
+                                                        let render_token = move |t: WikiToken| {
                                                             let app_state = app_state_for_tokens.clone();
                                                             let navigate = navigate_for_tokens.clone();
                                                             match t {
                                                                 WikiToken::Text(s) => {
                                                                     view! { <span>{s}</span> }.into_any()
                                                                 }
+                                                                // Leading markers are split off above; render any other as text.
+                                                                WikiToken::Todo { done } => {
+                                                                    let marker = if done { "{{DONE}}" } else { "{{TODO}}" };
+                                                                    view! { <span>{marker}</span> }.into_any()
+                                                                }
+                                                                WikiToken::BlockRef(ref_id) => {
+                                                                    // Live transclusion: this note's in-memory navs first (unsaved
+                                                                    // edits), then the DB nav cache.
+                                                                    let db_id = app_state
+                                                                        .0
+                                                                        .current_database_id
+                                                                        .get_untracked()
+                                                                        .unwrap_or_default();
+                                                                    let local = navs.with(|xs| find_block(xs, &ref_id).cloned());
+                                                                    let (block, cache_loaded) = match local {
+                                                                        Some(nav) => (Some(nav), true),
+                                                                        None => app_state.0.all_navs_cache.with(|m| match m.get(&db_id) {
+                                                                            Some(xs) => (find_block(xs, &ref_id).cloned(), true),
+                                                                            None => (None, false),
+                                                                        }),
+                                                                    };
+
+                                                                    let Some(block) = block else {
+                                                                        if !cache_loaded {
+                                                                            return view! {
+                                                                                <span class="text-muted-foreground">"(("{ref_id}"))"</span>
+                                                                            }
+                                                                            .into_any();
+                                                                        }
+                                                                        return view! {
+                                                                            <span
+                                                                                class="rounded bg-muted px-1 text-xs text-muted-foreground"
+                                                                                title=format!("Block {ref_id} no longer exists")
+                                                                            >
+                                                                                "missing block"
+                                                                            </span>
+                                                                        }
+                                                                        .into_any();
+                                                                    };
+
+                                                                    let href = format!(
+                                                                        "/db/{}/note/{}?focus_nav={}",
+                                                                        db_id, block.note_id, block.id
+                                                                    );
+                                                                    let href_for_click = href.clone();
+                                                                    view! {
+                                                                        <a
+                                                                            href=href
+                                                                            class="rounded bg-accent-soft/60 px-1 text-foreground no-underline hover:bg-accent-soft"
+                                                                            on:mousedown=move |ev: web_sys::MouseEvent| {
+                                                                                // Never let a ref click put the block into edit mode.
+                                                                                ev.stop_propagation();
+                                                                                if ev.button() != 0 || ev.ctrl_key() || ev.meta_key() || ev.shift_key() {
+                                                                                    return;
+                                                                                }
+                                                                                ev.prevent_default();
+                                                                                navigate(&href_for_click, leptos_router::NavigateOptions::default());
+                                                                            }
+                                                                        >
+                                                                            {block.content}
+                                                                        </a>
+                                                                    }
+                                                                    .into_any()
+                                                                }
                                                                 WikiToken::Link(label) => {
                                                                     let title_raw = label;
                                                                     if title_raw.is_empty() {
@@ -1726,6 +4071,19 @@ pub fn OutlineNode(
                                                                     let title_display = title_raw.clone();
                                                                     let title_preview_title = title_raw.clone();
 
+                                                                    // Real href so the link can be opened in a new tab; plain left
+                                                                    // clicks are handled on mousedown below (without entering edit mode).
+                                                                    let href = {
+                                                                        let db_id = app_state
+                                                                            .0
+                                                                            .current_database_id
+                                                                            .get_untracked()
+                                                                            .unwrap_or_default();
+                                                                        app_state.0.notes.with_untracked(|notes| {
+                                                                            wiki_link_href(&title_raw, &db_id, notes)
+                                                                        })
+                                                                    };
+
                                                                     let title_for_click = title_raw.clone();
                                                                     let _title_for_title = title_for_click.clone();
 
@@ -1757,17 +4115,17 @@ pub fn OutlineNode(
 
   let hideTimer = null;
   const show = () => {{
-    if (hideTimer) {{ clearTimeout(hideTimer); hideTimer = null; }}
-    if (!pop.matches(':popover-open')) pop.showPopover();
+if (hideTimer) {{ clearTimeout(hideTimer); hideTimer = null; }}
+if (!pop.matches(':popover-open')) pop.showPopover();
   }};
   const hideSoon = () => {{
-    if (hideTimer) clearTimeout(hideTimer);
-    hideTimer = setTimeout(() => {{
-      // Only hide if neither trigger nor popover is hovered.
-      if (!trigger.matches(':hover') && !pop.matches(':hover')) {{
-        try {{ pop.hidePopover(); }} catch (_) {{}}
-      }}
-    }}, 80);
+if (hideTimer) clearTimeout(hideTimer);
+hideTimer = setTimeout(() => {{
+  // Only hide if neither trigger nor popover is hovered.
+  if (!trigger.matches(':hover') && !pop.matches(':hover')) {{
+    try {{ pop.hidePopover(); }} catch (_) {{}}
+  }}
+}}, 80);
   }};
 
   trigger.addEventListener('mouseenter', show);
@@ -1791,10 +4149,10 @@ pub fn OutlineNode(
   left: anchor(left);
   margin-top: 8px;
   @position-try(flip-block) {{
-    bottom: anchor(top);
-    top: auto;
-    margin-bottom: 8px;
-    margin-top: 0;
+bottom: anchor(top);
+top: auto;
+margin-bottom: 8px;
+margin-top: 0;
   }}
   position-try-fallbacks: flip-block;
   position-try-order: most-height;
@@ -1807,11 +4165,21 @@ pub fn OutlineNode(
                                                                                 )}
                                                                             </style>
 
-                                                                            <button
+                                                                            <a
                                                                                 id=preview_trigger_id
-                                                                                type="button"
+                                                                                href=href
                                                                                 class="cursor-pointer text-primary underline underline-offset-2 hover:text-primary/80"
                                                                                 style=format!("anchor-name: {}", preview_anchor_name)
+                                                                                on:click=move |ev: web_sys::MouseEvent| {
+                                                                                    // Left click already navigated on mousedown; modified clicks
+                                                                                    // (new tab/window) keep the browser default.
+                                                                                    if ev.button() == 0
+                                                                                        && !(ev.ctrl_key() || ev.meta_key() || ev.shift_key())
+                                                                                    {
+                                                                                        ev.prevent_default();
+                                                                                    }
+                                                                                    ev.stop_propagation();
+                                                                                }
                                                                                 on:mouseenter=move |_ev: web_sys::MouseEvent| {
                                                                                     // Lazy-load preview data.
                                                                                     if preview_loaded_for.get_untracked().as_deref() == Some(title_for_hover.as_str()) {
@@ -1945,12 +4313,13 @@ pub fn OutlineNode(
                                                                                     });
                                                                                 }
                                                                                 on:mousedown=move |ev: web_sys::MouseEvent| {
-                                                                                    // Keep existing navigation behavior (left click only).
-                                                                                    if ev.button() != 0 {
+                                                                                    // Never let a link click put the block into edit mode.
+                                                                                    ev.stop_propagation();
+                                                                                    // Keep existing navigation behavior (plain left click only).
+                                                                                    if ev.button() != 0 || ev.ctrl_key() || ev.meta_key() || ev.shift_key() {
                                                                                         return;
                                                                                     }
                                                                                     ev.prevent_default();
-                                                                                    ev.stop_propagation();
 
                                                                                     let title = title_for_click.clone();
                                                                                     let title_norm = normalize_roam_page_title(&title);
@@ -2009,7 +4378,7 @@ pub fn OutlineNode(
                                                                                 }
                                                                             >
                                                                                 "[["{title_display}"]]"
-                                                                            </button>
+                                                                            </a>
 
                                                                             <div
                                                                                 id=preview_popover_id
@@ -2051,9 +4420,38 @@ pub fn OutlineNode(
                                                                     .into_any()
                                                                 }
                                                             }
-                                                        })
-                                                        .collect_view()
-                                                }}
+                                                        };
+
+                                                        runs.into_iter()
+                                                            .map(move |run| match run {
+                                                                InlineRun::Text(s, style) => {
+                                                                    let views = parse_wiki_tokens(&s)
+                                                                        .into_iter()
+                                                                        .map(render_token.clone())
+                                                                        .collect_view();
+                                                                    view! { <span class=style.class()>{views}</span> }.into_any()
+                                                                }
+                                                                InlineRun::Code(s, style) => view! {
+                                                                    <code class=format!("rounded bg-muted px-1 font-mono text-[0.9em] {}", style.class())>{s}</code>
+                                                                }
+                                                                .into_any(),
+                                                                InlineRun::Url(url, style) => view! {
+                                                                    <a
+                                                                        href=url.clone()
+                                                                        target="_blank"
+                                                                        rel="noopener noreferrer"
+                                                                        class=format!("text-primary underline underline-offset-2 break-all {}", style.class())
+                                                                        // Follow the link instead of entering edit mode.
+                                                                        on:mousedown=|ev: web_sys::MouseEvent| ev.stop_propagation()
+                                                                    >
+                                                                        {url}
+                                                                    </a>
+                                                                }
+                                                                .into_any(),
+                                                            })
+                                                            .collect_view()
+                                                    }}
+                                                </span>
                                             </div>
                                         }
                                         .into_any();
@@ -2079,7 +4477,16 @@ pub fn OutlineNode(
                                                     return;
                                                 };
 
-                                                let v = ce_text(&el);
+                                                let mut v = ce_text(&el);
+
+                                                // `/template` before the caret: drop it and open the picker there.
+                                                let (caret_now, _, _) = ce_selection_utf16(&el);
+                                                if let Some((stripped, caret)) = strip_template_command(&v, caret_now) {
+                                                    ce_set_text(&el, &stripped);
+                                                    ce_set_caret_utf16(&el, caret);
+                                                    v = stripped;
+                                                    template_insert.0.set(Some((nav_id_sv.get_value(), caret)));
+                                                }
                                                 editing_value.set(v.clone());
 
                                                 // Store draft at note-level aggregate.
@@ -2097,7 +4504,7 @@ pub fn OutlineNode(
                                                 // Schedule debounced autosave via global controller.
                                                 let _ = sync_sv.try_with_value(|s| s.on_nav_changed(&nav_id, &v));
 
-                                                // Autocomplete: detect an unclosed `[[...` immediately before the caret.
+                                                // Autocomplete: detect an unclosed `[[...` or `((...` immediately before the caret.
                                                 let (caret_utf16, _caret_end_utf16, _len) = ce_selection_utf16(&el);
 
                                                 let caret_byte = utf16_to_byte_idx(&v, caret_utf16);
@@ -2106,24 +4513,38 @@ pub fn OutlineNode(
                                                 let ac = ac_sv.get_value();
                                                 let app_state = app_state_sv.get_value();
 
-                                                let Some(start_byte) = prefix.rfind("[[") else {
-                                                    ac.ac_open.set(false);
-                                                    ac.ac_start_utf16.set(None);
-                                                    return;
+                                                // If the user already closed the link/ref before the caret, don't autocomplete.
+                                                let wiki_start = prefix
+                                                    .rfind("[[")
+                                                    .filter(|&i| !prefix[i..].contains("]]"));
+                                                let block_start = prefix
+                                                    .rfind("((")
+                                                    .filter(|&i| !prefix[i..].contains("))"));
+                                                // The trigger closest to the caret wins.
+                                                let trigger = match (wiki_start, block_start) {
+                                                    (Some(w), Some(b)) => Some((w.max(b), b > w)),
+                                                    (Some(w), None) => Some((w, false)),
+                                                    (None, Some(b)) => Some((b, true)),
+                                                    (None, None) => None,
                                                 };
-
-                                                // If the user already closed the link before the caret, don't autocomplete.
-                                                if prefix[start_byte..].contains("]]") {
+                                                let Some((start_byte, block_mode)) = trigger else {
                                                     ac.ac_open.set(false);
                                                     ac.ac_start_utf16.set(None);
                                                     return;
-                                                }
+                                                };
 
                                                 let q = prefix[start_byte + 2..].to_string();
+                                                ac.ac_block_mode.set(block_mode);
                                                 ac.ac_query.set(q.clone());
                                                 ac.ac_start_utf16
                                                     .set(Some(byte_idx_to_utf16(&v, start_byte)));
 
+                                                // Block refs: the recompute Effect builds items from the DB nav cache.
+                                                if block_mode {
+                                                    ensure_db_navs_loaded(&app_state, ac.block_navs_loading);
+                                                    return;
+                                                }
+
                                                 // Load titles lazily (notes + wiki links across DB).
                                                 ensure_titles_loaded(&app_state, &ac);
 
@@ -2149,6 +4570,45 @@ pub fn OutlineNode(
                                                 ac.ac_index.set(0);
                                                 ac.ac_open.set(true);
                                             }
+                                            // Multi-line paste: first line goes into this block at the caret, the rest
+                                            // become new blocks (leading indentation = nesting). Single-line pastes keep
+                                            // the browser default.
+                                            on:paste=move |ev: web_sys::ClipboardEvent| {
+                                                let Some(text) = ev
+                                                    .clipboard_data()
+                                                    .and_then(|dt| dt.get_data("text/plain").ok())
+                                                else {
+                                                    return;
+                                                };
+                                                let Some(input_el) = ev
+                                                    .current_target()
+                                                    .and_then(|t| t.dyn_into::<web_sys::HtmlElement>().ok())
+                                                else {
+                                                    return;
+                                                };
+
+                                                let current = ce_text(&input_el);
+                                                let (sel_start, sel_end, _len) = ce_selection_utf16(&input_el);
+                                                let Some(plan) = plan_multiline_paste(&current, sel_start, sel_end, &text) else {
+                                                    return;
+                                                };
+                                                ev.prevent_default();
+
+                                                let nav_id_now = nav_id_sv.get_value();
+                                                let note_id_now = note_id_sv.get_value();
+
+                                                ce_set_text(&input_el, &plan.current_content);
+                                                ce_set_caret_utf16(&input_el, plan.caret_utf16);
+                                                editing_value.set(plan.current_content.clone());
+                                                navs.update(|xs| {
+                                                    let _ = apply_nav_content(xs, &nav_id_now, &plan.current_content);
+                                                });
+                                                let _ = sync_sv.try_with_value(|s| {
+                                                    s.on_nav_changed(&nav_id_now, &plan.current_content);
+                                                });
+
+                                                insert_blocks_after(&app_state, navs, sync_sv, &note_id_now, &nav_id_now, &plan.blocks);
+                                            }
                                             on:compositionstart=move |_ev: web_sys::CompositionEvent| {
                                                 is_composing.set(true);
                                             }
@@ -2236,7 +4696,8 @@ pub fn OutlineNode(
                                             on:focusout=move |ev: web_sys::FocusEvent| {
                                                 if !should_exit_edit_on_focusout_related_target(
                                                     ev.related_target(),
-                                                ) {
+                                                ) && !focus_moved_to_other_editor(&ev)
+                                                {
                                                     return;
                                                 }
 
@@ -2274,20 +4735,15 @@ pub fn OutlineNode(
                                                 // Autocomplete menu key handling.
                                                 // NOTE: allow Shift+Enter to fall through for soft line breaks.
                                                 if ac.ac_open.get_untracked() && !(key == "Enter" && ev.shift_key()) {
+                                                    let len = ac.ac_items.get_untracked().len();
+                                                    if let Some(next) =
+                                                        ac_index_after_key(ac.ac_index.get_untracked(), &key, len)
+                                                    {
+                                                        ev.prevent_default();
+                                                        ac.ac_index.set(next);
+                                                        return;
+                                                    }
                                                     match key.as_str() {
-                                                        "ArrowDown" => {
-                                                            ev.prevent_default();
-                                                            let len = ac.ac_items.get_untracked().len();
-                                                            if len > 0 {
-                                                                ac.ac_index.update(|i| *i = (*i + 1).min(len - 1));
-                                                            }
-                                                            return;
-                                                        }
-                                                        "ArrowUp" => {
-                                                            ev.prevent_default();
-                                                            ac.ac_index.update(|i| *i = i.saturating_sub(1));
-                                                            return;
-                                                        }
                                                         "Escape" => {
                                                             ev.prevent_default();
                                                             ac.ac_open.set(false);
@@ -2298,7 +4754,7 @@ pub fn OutlineNode(
                                                             let items = ac.ac_items.get_untracked();
                                                             let idx = ac.ac_index.get_untracked();
                                                             if let Some(item) = items.get(idx) {
-                                                                let chosen = item.title.clone();
+                                                                let chosen = item.insert.clone();
 
                                                                 if let Some(input_el) = input() {
                                                                     let v = ce_text(&input_el);
@@ -2312,9 +4768,7 @@ pub fn OutlineNode(
 
                                                                     let mut next = String::new();
                                                                     next.push_str(&v[..start_byte.min(v.len())]);
-                                                                    next.push_str("[[");
                                                                     next.push_str(&chosen);
-                                                                    next.push_str("]]");
                                                                     next.push_str(&v[caret_byte.min(v.len())..]);
 
                                                                     ce_set_text(&input_el, &next);
@@ -2327,10 +4781,8 @@ pub fn OutlineNode(
                                                                         s.on_nav_changed(&nav_id_now, &next);
                                                                     });
 
-                                                                    let caret_after = start_utf16
-                                                                        + 2
-                                                                        + (chosen.encode_utf16().count() as u32)
-                                                                        + 2;
+                                                                    let caret_after =
+                                                                        start_utf16 + (chosen.encode_utf16().count() as u32);
                                                                     ce_set_caret_utf16(&input_el, caret_after);
                                                                 }
 
@@ -2343,6 +4795,19 @@ pub fn OutlineNode(
                                                     }
                                                 }
 
+                                                // Space ends a `#tag`: store the block's tags in its properties.
+                                                if key == " " {
+                                                    if let Some(input_el) = input() {
+                                                        let content = ce_text(&input_el);
+                                                        let nav_id_now = nav_id_sv.get_value();
+                                                        let _ = sync_sv.try_with_value(|s| {
+                                                            app_state_sv.with_value(|st| {
+                                                                sync_nav_tags(navs, st, s, &nav_id_now, &content)
+                                                            })
+                                                        });
+                                                    }
+                                                }
+
                                                 // Helpers for wiki-style navigation
 
                                                 let save_current = |nav_id_now: &str, _note_id_now: &str| {
@@ -2373,11 +4838,30 @@ pub fn OutlineNode(
                                                     }
                                                 };
 
-                                                fn visible_preorder(all: &[Nav]) -> Vec<String> {
-                                                    collect_visible_preorder_ids(all)
+                                                // Block navigation never leaves the zoomed subtree.
+                                                let zoom_root = focused_nav_root.get_untracked();
+                                                let visible_preorder =
+                                                    |all: &[Nav]| visible_preorder_within(all, zoom_root.as_deref());
+
+                                                // Escape (autocomplete already closed above): leave the block, saving it like
+                                                // a blur. Stop here so the window-level Escape handlers (zoom exit, search
+                                                // blur) wait for the next press.
+                                                if key == "Escape" {
+                                                    ev.prevent_default();
+                                                    ev.stop_propagation();
+
+                                                    let nav_id_now = nav_id_sv.get_value();
+                                                    let note_id_now = note_id_sv.get_value();
+                                                    save_current(&nav_id_now, &note_id_now);
+                                                    editing_id.set(None);
+                                                    editing_snapshot.set(None);
+                                                    if let Some(el) = input() {
+                                                        let _ = el.blur();
+                                                    }
+                                                    return;
                                                 }
 
-                                                // Alt+Up/Down: move current node among siblings (order only)
+                                                // Alt+Up/Down: move the block (with its subtree) past its previous/next sibling.
                                                 if ev.alt_key() && (key == "ArrowUp" || key == "ArrowDown") {
                                                     ev.prevent_default();
 
@@ -2388,75 +4872,29 @@ pub fn OutlineNode(
                                                     target_cursor_col.set(Some(cursor_col));
 
                                                     let nav_id_now = nav_id_sv.get_value();
-                                                    let _note_id_now = note_id_sv.get_value();
-                                                    let current_content = editing_value.get_untracked();
-
-                                                    let all = navs.get_untracked();
-                                                    let Some(me) = all.iter().find(|n| n.id == nav_id_now) else {
+                                                    let note_id_now = note_id_sv.get_value();
+                                                    let up = key == "ArrowUp";
+                                                    let renumbered =
+                                                        rebalance_for_sibling_move(&navs.get_untracked(), &nav_id_now, up);
+                                                    let all = apply_renumbered_orders(navs, sync_sv, &renumbered);
+                                                    let Some(new_order) = compute_sibling_move(&all, &nav_id_now, up) else {
+                                                        // Already first/last among its siblings.
                                                         return;
                                                     };
 
-                                                    // Siblings sorted by order.
-                                                    let parid = me.parid.clone();
-                                                    let mut sibs = all
-                                                        .iter()
-                                                        .filter(|n| n.parid == parid)
-                                                        .cloned()
-                                                        .collect::<Vec<_>>();
-                                                    sibs.sort_by(|a, b| {
-                                                        a.same_deep_order
-                                                            .partial_cmp(&b.same_deep_order)
-                                                            .unwrap_or(std::cmp::Ordering::Equal)
-                                                    });
-
-                                                    let idx = sibs.iter().position(|n| n.id == nav_id_now);
-                                                    let Some(idx) = idx else { return; };
-
-                                                    // Compute new order by placing between adjacent siblings.
-                                                    let new_order = if key == "ArrowUp" {
-                                                        if idx == 0 {
-                                                            // Already first.
-                                                            return;
-                                                        }
-                                                        let prev = &sibs[idx - 1];
-                                                        let prevprev_order = if idx >= 2 {
-                                                            sibs[idx - 2].same_deep_order
-                                                        } else {
-                                                            prev.same_deep_order - 1.0
-                                                        };
-                                                        (prevprev_order + prev.same_deep_order) / 2.0
-                                                    } else {
-                                                        if idx + 1 >= sibs.len() {
-                                                            // Already last.
-                                                            return;
-                                                        }
-                                                        let next = &sibs[idx + 1];
-                                                        let nextnext_order = if idx + 2 < sibs.len() {
-                                                            sibs[idx + 2].same_deep_order
-                                                        } else {
-                                                            next.same_deep_order + 1.0
-                                                        };
-                                                        (next.same_deep_order + nextnext_order) / 2.0
-                                                    };
+                                                    // Commit typed content first so the move doesn't drop it.
+                                                    save_current(&nav_id_now, &note_id_now);
+                                                    let current_content = editing_value.get_untracked();
 
-                                                    // Update local state.
+                                                    // Keep navs unsorted: rendering and navigation sort per-parent using
+                                                    // `same_deep_order`. Children key off `parid`, so the subtree follows.
                                                     navs.update(|xs| {
                                                         if let Some(x) = xs.iter_mut().find(|x| x.id == nav_id_now) {
-                                                            x.content = current_content.clone();
                                                             x.same_deep_order = new_order;
                                                         }
-
-                                                        // Keep navs unsorted: rendering and navigation sort per-parent using
-                                                        // `same_deep_order`, so globally sorting the whole list is unnecessary
-                                                        // work (and gets slower as the outline grows).
                                                     });
 
                                                     // Persist reorder meta; sync controller handles network.
-                                                    navs.update(|xs| {
-                                                        if let Some(x) = xs.iter_mut().find(|x| x.id == nav_id_now) {
-                                                            x.same_deep_order = new_order;
-                                                        }
-                                                    });
                                                     if let Some(n) = navs
                                                         .get_untracked()
                                                         .into_iter()
@@ -2502,6 +4940,60 @@ pub fn OutlineNode(
                                                     return;
                                                 }
 
+                                                // Ctrl/Cmd+.: zoom into the block being edited.
+                                                if key == "." && (ev.ctrl_key() || ev.meta_key()) {
+                                                    ev.prevent_default();
+                                                    focused_nav_root.set(Some(nav_id_sv.get_value()));
+                                                    return;
+                                                }
+
+                                                // Shift+Up/Down at the first/last line: start a multi-block selection.
+                                                if (key == "ArrowUp" || key == "ArrowDown") && ev.shift_key() && !ev.alt_key() && !ev.ctrl_key() && !ev.meta_key() {
+                                                    let Some(input_el) = input() else {
+                                                        return;
+                                                    };
+
+                                                    let (current_line, total_lines) = ce_current_line_info(&input_el);
+                                                    let at_edge = if key == "ArrowUp" {
+                                                        current_line == 0
+                                                    } else {
+                                                        total_lines > 0 && current_line >= total_lines - 1
+                                                    };
+                                                    if !at_edge {
+                                                        // Extend the text selection inside the block as usual.
+                                                        return;
+                                                    }
+
+                                                    let nav_id_now = nav_id_sv.get_value();
+                                                    let all = navs.get_untracked();
+                                                    let visible = visible_preorder(&all);
+                                                    let Some(idx) = visible.iter().position(|id| id == &nav_id_now) else {
+                                                        return;
+                                                    };
+                                                    let next = if key == "ArrowUp" {
+                                                        idx.checked_sub(1)
+                                                    } else {
+                                                        Some(idx + 1).filter(|i| *i < visible.len())
+                                                    };
+                                                    let Some(next) = next else {
+                                                        return;
+                                                    };
+
+                                                    ev.prevent_default();
+                                                    let note_id_now = note_id_sv.get_value();
+                                                    save_current(&nav_id_now, &note_id_now);
+
+                                                    let focus_id = visible[next].clone();
+                                                    selection.selected.set(selection_range(&visible, &nav_id_now, &focus_id));
+                                                    selection.anchor.set(Some(nav_id_now));
+                                                    selection.focus.set(Some(focus_id));
+
+                                                    let _ = input_el.blur();
+                                                    editing_id.set(None);
+                                                    editing_snapshot.set(None);
+                                                    return;
+                                                }
+
                                                 // Arrow Up/Down without modifiers: soft line navigation + adjacent block jump
                                                 // When cursor is at first line and ArrowUp, jump to previous block
                                                 // When cursor is at last line and ArrowDown, jump to next block
@@ -2585,38 +5077,23 @@ pub fn OutlineNode(
 
                                                     if key == "ArrowLeft" && cursor_start == 0 {
                                                         ev.prevent_default();
+                                                        if zoom_root.as_deref() == Some(nav_id_now.as_str()) {
+                                                            return;
+                                                        }
                                                         target_cursor_col.set(None);
                                                         save_current(&nav_id_now, &note_id_now);
 
                                                         let all = navs.get_untracked();
-                                                        let Some(me) = all.iter().find(|n| n.id == nav_id_now) else {
+                                                        let tree = NavTree::new(&all);
+                                                        let Some(me) = tree.get(&nav_id_now) else {
                                                             return;
                                                         };
 
-                                                        let root_container_parent_id = ROOT_CONTAINER_PARENT_ID;
-
                                                         // Prefer previous sibling when it exists.
                                                         // If there is no previous sibling (i.e. first child), go to parent.
-                                                        let parid = me.parid.clone();
-                                                        let mut sibs = all
-                                                            .iter()
-                                                            .filter(|n| n.parid == parid)
-                                                            .cloned()
-                                                            .collect::<Vec<_>>();
-                                                        sibs.sort_by(|a, b| a
-                                                            .same_deep_order
-                                                            .partial_cmp(&b.same_deep_order)
-                                                            .unwrap_or(std::cmp::Ordering::Equal));
-
-                                                        let prev = sibs
-                                                            .iter()
-                                                            .rev()
-                                                            .find(|s| s.same_deep_order < me.same_deep_order)
-                                                            .cloned();
-
-                                                        if prev.is_none() {
-                                                            if me.parid != root_container_parent_id {
-                                                                if let Some(parent) = all.iter().find(|n| n.id == me.parid) {
+                                                        let Some(prev) = tree.previous_sibling(&nav_id_now) else {
+                                                            if me.parid != ROOT_CONTAINER_PARENT_ID {
+                                                                if let Some(parent) = tree.get(&me.parid) {
                                                                     editing_id.set(Some(parent.id.clone()));
                                                                     editing_value.set(parent.content.clone());
                                                                     editing_snapshot.set(Some((parent.id.clone(), parent.content.clone())));
@@ -2624,31 +5101,10 @@ pub fn OutlineNode(
                                                                 }
                                                             }
                                                             return;
-                                                        }
-
-                                                        let prev = prev.unwrap();
+                                                        };
 
                                                         // Descend to last visible node in prev's subtree.
-                                                        fn last_visible_descendant(all: &[Nav], start: &Nav) -> Nav {
-                                                            if !start.is_display {
-                                                                return start.clone();
-                                                            }
-                                                            let mut children = all
-                                                                .iter()
-                                                                .filter(|n| n.parid == start.id)
-                                                                .cloned()
-                                                                .collect::<Vec<_>>();
-                                                            children.sort_by(|a, b| a
-                                                                .same_deep_order
-                                                                .partial_cmp(&b.same_deep_order)
-                                                                .unwrap_or(std::cmp::Ordering::Equal));
-                                                            if let Some(last) = children.last() {
-                                                                return last_visible_descendant(all, last);
-                                                            }
-                                                            start.clone()
-                                                        }
-
-                                                        let target = last_visible_descendant(&all, &prev);
+                                                        let target = tree.last_visible_descendant(&prev.id).unwrap_or(prev);
                                                         editing_id.set(Some(target.id.clone()));
                                                         editing_value.set(target.content.clone());
                                                         editing_snapshot.set(Some((target.id.clone(), target.content.clone())));
@@ -2663,24 +5119,12 @@ pub fn OutlineNode(
 
                                                         let all = navs.get_untracked();
 
+                                                        let tree = NavTree::new(&all);
+
                                                         // If the current node has children and is collapsed, expand it.
                                                         // If expanded, move into first child.
-                                                        let mut children = all
-                                                            .iter()
-                                                            .filter(|n| n.parid == nav_id_now)
-                                                            .cloned()
-                                                            .collect::<Vec<_>>();
-                                                        children.sort_by(|a, b| a
-                                                            .same_deep_order
-                                                            .partial_cmp(&b.same_deep_order)
-                                                            .unwrap_or(std::cmp::Ordering::Equal));
-
-                                                        if let Some(first_child) = children.first().cloned() {
-                                                            let is_display = all
-                                                                .iter()
-                                                                .find(|n| n.id == nav_id_now)
-                                                                .map(|n| n.is_display)
-                                                                .unwrap_or(true);
+                                                        if let Some(first_child) = tree.children(&nav_id_now).first().copied() {
+                                                            let is_display = tree.get(&nav_id_now).map(|n| n.is_display).unwrap_or(true);
 
                                                             if !is_display {
                                                                 // Expand current node AND descend into first child.
@@ -2729,10 +5173,18 @@ pub fn OutlineNode(
                                                     let _note_id_now = note_id_sv.get_value();
 
                                                     let all = navs.get_untracked();
-                                                    let Some(me) = all.iter().find(|x| x.id == nav_id_now) else {
+                                                    let tree = NavTree::new(&all);
+                                                    let Some(me) = tree.get(&nav_id_now) else {
                                                         return;
                                                     };
 
+                                                    // The zoom root stays put, and its children can't be outdented out of view.
+                                                    if let Some(z) = zoom_root.as_deref() {
+                                                        if me.id == z || (shift && me.parid == z) {
+                                                            return;
+                                                        }
+                                                    }
+
                                                     // Save current edit buffer into local state first.
                                                     let current_content = editing_value.get_untracked();
                                                     navs.update(|xs| {
@@ -2745,34 +5197,22 @@ pub fn OutlineNode(
 
                                                     if !shift {
                                                         // Indent: become child of previous sibling.
-                                                        let parid = me.parid.clone();
-                                                        let mut sibs = all
-                                                            .iter()
-                                                            .filter(|x| x.parid == parid)
-                                                            .cloned()
-                                                            .collect::<Vec<_>>();
-                                                        sibs.sort_by(|a, b| a.same_deep_order
-                                                            .partial_cmp(&b.same_deep_order)
-                                                            .unwrap_or(std::cmp::Ordering::Equal));
-
-                                                        let prev = sibs
-                                                            .iter()
-                                                            .rev()
-                                                            .find(|s| s.same_deep_order < me.same_deep_order)
-                                                            .cloned();
-
-                                                        let Some(prev) = prev else {
+                                                        let Some(prev) = tree.previous_sibling(&nav_id_now) else {
                                                             return;
                                                         };
 
                                                         let new_parid = prev.id.clone();
+                                                        if !fits_max_nav_depth(&all, &nav_id_now, &new_parid) {
+                                                            depth_notice.flash();
+                                                            return;
+                                                        }
 
-                                                        // Append to end of new parent's children.
-                                                        let last_child_order = all
+                                                        // Append to end of new parent's children (deleted ones too, so a restore can't collide).
+                                                        let last_child_order = tree
+                                                            .all_children(&new_parid)
                                                             .iter()
-                                                            .filter(|x| x.parid == new_parid)
                                                             .map(|x| x.same_deep_order)
-                                                            .max_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+                                                            .rfind(|o| o.is_finite());
                                                         let new_order = last_child_order.unwrap_or(0.0) + 1.0;
 
                                                         navs.update(|xs| {
@@ -2795,38 +5235,29 @@ pub fn OutlineNode(
                                                         }
                                                     } else {
                                                         // Outdent: become sibling of parent.
-                                                        let parent_id = me.parid.clone();
-                                                        let root_container_parent_id = ROOT_CONTAINER_PARENT_ID;
-                                                        if parent_id == root_container_parent_id {
+                                                        if me.parid == ROOT_CONTAINER_PARENT_ID {
                                                             return;
                                                         }
 
-                                                        let Some(parent) = all.iter().find(|x| x.id == parent_id) else {
+                                                        let Some(parent) = tree.get(&me.parid) else {
                                                             return;
                                                         };
 
                                                         let new_parid = parent.parid.clone();
+                                                        let renumbered = rebalance_for_insert(&all, &new_parid, Some(&parent.id), 1, None);
+                                                        let all = apply_renumbered_orders(navs, sync_sv, &renumbered);
+                                                        let tree = NavTree::new(&all);
+                                                        let Some(parent) = tree.get(&me.parid) else {
+                                                            return;
+                                                        };
 
                                                         // Put right after parent (midpoint between parent and parent's next sibling).
-                                                        let mut parent_sibs = all
-                                                            .iter()
-                                                            .filter(|x| x.parid == new_parid)
-                                                            .cloned()
-                                                            .collect::<Vec<_>>();
-                                                        parent_sibs.sort_by(|a, b| a.same_deep_order
-                                                            .partial_cmp(&b.same_deep_order)
-                                                            .unwrap_or(std::cmp::Ordering::Equal));
-
-                                                        let next_order = parent_sibs
+                                                        let next_order = tree
+                                                            .all_children(&new_parid)
                                                             .iter()
                                                             .find(|s| s.same_deep_order > parent.same_deep_order)
                                                             .map(|s| s.same_deep_order);
-
-                                                        let new_order = if let Some(no) = next_order {
-                                                            (parent.same_deep_order + no) / 2.0
-                                                        } else {
-                                                            parent.same_deep_order + 1.0
-                                                        };
+                                                        let new_order = insert_order_between(Some(parent.same_deep_order), next_order);
 
                                                         navs.update(|xs| {
                                                             if let Some(x) = xs.iter_mut().find(|x| x.id == nav_id_now) {
@@ -2965,15 +5396,7 @@ pub fn OutlineNode(
                                                     let idx = visible.iter().position(|id| id == &nav_id_now);
 
                                                     // Collect subtree ids (including self).
-                                                    fn collect_subtree(all: &[Nav], root_id: &str, out: &mut Vec<String>) {
-                                                        out.push(root_id.to_string());
-                                                        for c in all.iter().filter(|n| n.parid == root_id) {
-                                                            collect_subtree(all, &c.id, out);
-                                                        }
-                                                    }
-
-                                                    let mut subtree: Vec<String> = vec![];
-                                                    collect_subtree(&all, &nav_id_now, &mut subtree);
+                                                    let subtree = NavTree::new(&all).subtree_ids(&nav_id_now);
 
                                                     // Update local state: remove subtree nodes.
                                                     navs.update(|xs| xs.retain(|n| !subtree.iter().any(|id| id == &n.id)));
@@ -3028,6 +5451,7 @@ pub fn OutlineNode(
                                                         &note_id_now,
                                                         &real_ids,
                                                     );
+                                                    offer_undo_nav_delete(&app_state, &db_id_now, &note_id_now, &all, &subtree);
 
                                                     for id in real_ids.into_iter() {
                                                         if let Some(mut n) = all.iter().find(|n| n.id == id).cloned() {
@@ -3039,11 +5463,144 @@ pub fn OutlineNode(
                                                     return;
                                                 }
 
+                                                // Backspace at the start of a non-empty block (Roam): append its text to the
+                                                // previous visible block, promote its children into its place and delete it.
+                                                if key == "Backspace"
+                                                    && !(ev.shift_key() || ev.alt_key() || ev.meta_key() || ev.ctrl_key())
+                                                {
+                                                    let Some(el) = input() else {
+                                                        return;
+                                                    };
+                                                    let (start, end, _) = ce_selection_utf16(&el);
+                                                    if start != 0 || end != 0 {
+                                                        return;
+                                                    }
+
+                                                    let nav_id_now = nav_id_sv.get_value();
+                                                    let note_id_now = note_id_sv.get_value();
+                                                    let all = navs.get_untracked();
+                                                    // Merge target stays inside the zoomed subtree.
+                                                    let visible = NavTree::new(&all).visible_preorder_within(zoom_root.as_deref());
+                                                    let Some(plan) = plan_merge_into_previous(
+                                                        &all,
+                                                        &visible,
+                                                        &nav_id_now,
+                                                        &ce_text(&el),
+                                                    ) else {
+                                                        return;
+                                                    };
+                                                    ev.prevent_default();
+
+                                                    navs.update(|xs| {
+                                                        for (id, parid, order) in plan.child_moves.iter() {
+                                                            if let Some(x) = xs.iter_mut().find(|x| &x.id == id) {
+                                                                x.parid = parid.clone();
+                                                                x.same_deep_order = *order;
+                                                            }
+                                                        }
+                                                        if let Some(x) = xs.iter_mut().find(|x| x.id == plan.target_id) {
+                                                            x.content = plan.content.clone();
+                                                        }
+                                                        xs.retain(|x| x.id != nav_id_now);
+                                                    });
+
+                                                    // Persist via drafts; sync controller handles network.
+                                                    let db_id_now = app_state_sv
+                                                        .get_value()
+                                                        .0
+                                                        .current_database_id
+                                                        .get_untracked()
+                                                        .unwrap_or_default();
+                                                    touch_nav(&db_id_now, &note_id_now, &plan.target_id, &plan.content);
+                                                    let _ = sync_sv
+                                                        .try_with_value(|s| s.on_nav_changed(&plan.target_id, &plan.content));
+
+                                                    let moved = navs.get_untracked();
+                                                    for (id, _, _) in plan.child_moves.iter() {
+                                                        if let Some(n) = moved.iter().find(|n| &n.id == id) {
+                                                            let _ = sync_sv.try_with_value(|s| s.on_nav_meta_changed(n));
+                                                        }
+                                                    }
+
+                                                    // Tombstone the merged block (same as deleting an empty one).
+                                                    let merged_ids = vec![nav_id_now.clone()];
+                                                    if is_tmp_nav_id(&nav_id_now) {
+                                                        crate::drafts::remove_navs_from_drafts(&db_id_now, &note_id_now, &merged_ids);
+                                                        crate::cache::remove_navs_from_snapshot(&db_id_now, &note_id_now, &merged_ids);
+                                                    } else {
+                                                        crate::cache::mark_navs_deleted_in_snapshot(&db_id_now, &note_id_now, &merged_ids);
+                                                        if let Some(mut n) = all.iter().find(|n| n.id == nav_id_now).cloned() {
+                                                            n.is_delete = true;
+                                                            let _ = sync_sv.try_with_value(|s| s.on_nav_meta_changed(&n));
+                                                        }
+                                                    }
+
+                                                    editing_value.set(plan.content.clone());
+                                                    editing_snapshot.set(Some((plan.target_id.clone(), plan.content)));
+                                                    target_cursor_col.set(Some(plan.caret));
+                                                    editing_id.set(Some(plan.target_id));
+                                                    return;
+                                                }
+
+                                                // Cmd/Ctrl+Enter: cycle the task marker (plain -> TODO -> DONE -> plain).
+                                                if key == "Enter" && (ev.meta_key() || ev.ctrl_key()) {
+                                                    ev.prevent_default();
+                                                    let Some(el) = input() else {
+                                                        return;
+                                                    };
+                                                    let before = ce_text(&el);
+                                                    let (caret, _, _) = ce_selection_utf16(&el);
+                                                    let next = cycle_todo(&before);
+                                                    ce_set_text(&el, &next);
+                                                    // The marker sits at the start: shift the caret by its change in length.
+                                                    let len_before = before.encode_utf16().count() as i64;
+                                                    let len_next = next.encode_utf16().count() as i64;
+                                                    let caret = (caret as i64 + len_next - len_before).clamp(0, len_next);
+                                                    ce_set_caret_utf16(&el, caret as u32);
+                                                    editing_value.set(next.clone());
+
+                                                    let db_id = app_state_sv
+                                                        .get_value()
+                                                        .0
+                                                        .current_database_id
+                                                        .get_untracked()
+                                                        .unwrap_or_default();
+                                                    let note_id = note_id_sv.get_value();
+                                                    let nav_id = nav_id_sv.get_value();
+                                                    touch_nav(&db_id, &note_id, &nav_id, &next);
+                                                    let _ = sync_sv.try_with_value(|s| s.on_nav_changed(&nav_id, &next));
+                                                    return;
+                                                }
+
                                                 // Shift+Enter: soft line break inside a node (do NOT create a new Nav).
-                                                // Let the browser handle DOM mutations, and rely on the `on:input` handler
-                                                // to update drafts + schedule sync. This avoids regressions where a custom
-                                                // insertion interacts badly with the trailing placeholder `<br>`.
+                                                // The break is inserted via `insert_soft_line_break_dom`, which keeps the
+                                                // trailing placeholder `<br>` normalized; then the DOM text is mirrored into
+                                                // `editing_value` + drafts like `on:input` does. If insertion fails, the
+                                                // browser's own insertion goes through `on:input` instead.
                                                 if key == "Enter" && ev.shift_key() {
+                                                    let Some(el) = input().or_else(|| {
+                                                        editing_ref.get_untracked().map(|el| el.unchecked_into())
+                                                    }) else {
+                                                        return;
+                                                    };
+                                                    if !insert_soft_line_break_dom(&el) {
+                                                        return;
+                                                    }
+                                                    ev.prevent_default();
+
+                                                    let v = ce_text(&el);
+                                                    editing_value.set(v.clone());
+
+                                                    let db_id = app_state_sv
+                                                        .get_value()
+                                                        .0
+                                                        .current_database_id
+                                                        .get_untracked()
+                                                        .unwrap_or_default();
+                                                    let note_id = note_id_sv.get_value();
+                                                    let nav_id = nav_id_sv.get_value();
+                                                    touch_nav(&db_id, &note_id, &nav_id, &v);
+                                                    let _ = sync_sv.try_with_value(|s| s.on_nav_changed(&nav_id, &v));
                                                     return;
                                                 }
 
@@ -3068,29 +5625,38 @@ pub fn OutlineNode(
 
                                                     // Create sibling
                                                     let all = navs.get_untracked();
-                                                    let Some(me) = all.iter().find(|x| x.id == nav_id_now) else {
+                                                    let tree = NavTree::new(&all);
+                                                    let Some(me) = tree.get(&nav_id_now) else {
                                                         return;
                                                     };
 
-                                                    let parid = me.parid.clone();
-                                                    let mut sibs = all
-                                                        .iter()
-                                                        .filter(|x| x.parid == parid)
-                                                        .cloned()
-                                                        .collect::<Vec<_>>();
-                                                    sibs.sort_by(|a, b| a.same_deep_order
-                                                        .partial_cmp(&b.same_deep_order)
-                                                        .unwrap_or(std::cmp::Ordering::Equal));
-
-                                                    let next_order = sibs
-                                                        .iter()
-                                                        .find(|s| s.same_deep_order > me.same_deep_order)
-                                                        .map(|s| s.same_deep_order);
+                                                    // Enter on the zoom root adds its first child instead of a sibling
+                                                    // (a sibling would be created outside the zoomed view).
+                                                    // The "Enter creates a child" preference does the same everywhere.
+                                                    let as_child = zoom_root.as_deref() == Some(me.id.as_str())
+                                                        || app_state.0.settings.with_untracked(|s| s.enter_creates_child);
 
-                                                    let new_order = if let Some(no) = next_order {
-                                                        (me.same_deep_order + no) / 2.0
+                                                    // Renumber the siblings first if the midpoint below has run out of precision.
+                                                    let renumbered = if as_child {
+                                                        rebalance_for_insert(&all, &me.id, None, 1, None)
+                                                    } else {
+                                                        rebalance_for_insert(&all, &me.parid, Some(&me.id), 1, None)
+                                                    };
+                                                    let all = apply_renumbered_orders(navs, sync_sv, &renumbered);
+                                                    let tree = NavTree::new(&all);
+                                                    let Some(me) = tree.get(&nav_id_now) else {
+                                                        return;
+                                                    };
+                                                    let (parid, new_order) = if as_child {
+                                                        let first_order = tree.children(&me.id).first().map(|x| x.same_deep_order);
+                                                        (me.id.clone(), first_order.map(|o| o - 1.0).unwrap_or(1.0))
                                                     } else {
-                                                        me.same_deep_order + 1.0
+                                                        let next_order = tree
+                                                            .all_children(&me.parid)
+                                                            .iter()
+                                                            .find(|s| s.same_deep_order > me.same_deep_order)
+                                                            .map(|s| s.same_deep_order);
+                                                        (me.parid.clone(), insert_order_between(Some(me.same_deep_order), next_order))
                                                     };
 
                                                     // Optimistic UI: insert a temporary node locally and start editing it
@@ -3111,7 +5677,15 @@ pub fn OutlineNode(
                                                             is_display: true,
                                                             is_delete: false,
                                                             properties: None,
+                                                            created_at: None,
+                                                            updated_at: None,
                                                         });
+                                                        // A new first child must be visible.
+                                                        if as_child {
+                                                            if let Some(p) = xs.iter_mut().find(|x| x.id == parid) {
+                                                                p.is_display = true;
+                                                            }
+                                                        }
                                                     });
 
                                                     editing_id.set(Some(tmp_id.clone()));
@@ -3226,7 +5800,7 @@ pub fn OutlineNode(
                                                             let idx = ac.ac_index.get();
 
                                                             if items.is_empty() {
-                                                                if ac.titles_loading.get() {
+                                                                if ac.titles_loading.get() || ac.block_navs_loading.get() {
                                                                     return view! {
                                                                         <div class="px-2 py-1 text-muted-foreground">"Loading…"</div>
                                                                     }
@@ -3244,8 +5818,13 @@ pub fn OutlineNode(
                                                                             .enumerate()
                                                                             .map(|(i, it)| {
                                                                                 let title = it.title.clone();
-                                                                                let title_for_insert = title.clone();
-                                                                                let title_for_view = title.clone();
+                                                                                let insert = it.insert.clone();
+                                                                                // The create-new row reads as an action, not an existing page.
+                                                                                let title_for_view = if it.is_new {
+                                                                                    format!("Create '{title}'")
+                                                                                } else {
+                                                                                    title.clone()
+                                                                                };
                                                                                 let is_new = it.is_new;
                                                                                 let selected = Signal::derive(move || i == idx);
 
@@ -3271,32 +5850,31 @@ pub fn OutlineNode(
 
                                                                                                 let mut next = String::new();
                                                                                                 next.push_str(&v[..start_byte.min(v.len())]);
-                                                                                                next.push_str("[[");
-                                                                                                next.push_str(&title_for_insert);
-                                                                                                next.push_str("]]");
+                                                                                                next.push_str(&insert);
                                                                                                 next.push_str(&v[caret_byte.min(v.len())..]);
 
                                                                                                 ce_set_text(&he, &next);
                                                                                                 editing_value.set(next.clone());
 
-                                                                                                let caret_after = start_utf16
-                                                                                                    + 2
-                                                                                                    + (title_for_insert.encode_utf16().count() as u32)
-                                                                                                    + 2;
+                                                                                                let caret_after =
+                                                                                                    start_utf16 + (insert.encode_utf16().count() as u32);
                                                                                                 ce_set_caret_utf16(&he, caret_after);
                                                                                             }
 
                                                                                             ac.ac_open.set(false);
                                                                                             ac.ac_start_utf16.set(None);
                                                                                         }))
+                                                                                        // Hover moves the highlight so Enter/Tab accept what the pointer is on.
                                                                                         on:mousemove=move |_ev| {
-                                                                                            ac.ac_index.set(i);
+                                                                                            if ac.ac_index.get_untracked() != i {
+                                                                                                ac.ac_index.set(i);
+                                                                                            }
                                                                                         }
                                                                                         attr:data-ac-idx=i.to_string()
                                                                                     >
-                                                                                        <div class="truncate">{title_for_view.clone()}</div>
+                                                                                        <div class=if is_new { "truncate italic" } else { "truncate" }>{title_for_view.clone()}</div>
                                                                                         <Show when=move || is_new fallback=|| ().into_view()>
-                                                                                            <div class="ml-2 shrink-0 text-xs text-muted-foreground">"Create"</div>
+                                                                                            <div class="ml-2 shrink-0 text-xs text-muted-foreground">"New page"</div>
                                                                                         </Show>
                                                                                     </CommandItem>
                                                                                 }
@@ -3320,6 +5898,52 @@ pub fn OutlineNode(
                                     .into_any()
                                 }}
                             </div>
+                            // Sync status: nothing when clean, spinner while saving, a check that
+                            // fades after a save, red dot (error tooltip) when the last save failed.
+                            <span class="relative flex w-3 shrink-0 items-center justify-center" aria-live="polite">
+                                // Hover-only "edited ..." hint, overlaid so it never shifts the row.
+                                {move || edited_hint.get().map(|hint| view! {
+                                    <span class="outline-edited pointer-events-none absolute right-full mr-2 whitespace-nowrap rounded bg-background/90 px-1 text-[11px] text-muted-foreground">
+                                        {hint}
+                                    </span>
+                                })}
+                                {move || match sync_status.get() {
+                                    NavSyncStatus::Pending => view! {
+                                        <Spinner class="size-3 text-muted-foreground" />
+                                    }
+                                    .into_any(),
+                                    NavSyncStatus::Failed => {
+                                        let error = sync_error
+                                            .get()
+                                            .unwrap_or_else(|| "Not saved yet; will retry".to_string());
+                                        view! {
+                                            <span
+                                                class="size-2 rounded-full bg-destructive"
+                                                title=format!("Sync failed: {error}")
+                                                role="img"
+                                                aria-label="Sync failed"
+                                            />
+                                        }
+                                        .into_any()
+                                    }
+                                    NavSyncStatus::Clean => view! {
+                                        <span
+                                            class=move || {
+                                                if show_saved.get() {
+                                                    "text-xs text-green-600 transition-opacity duration-500 opacity-100"
+                                                } else {
+                                                    "text-xs text-green-600 transition-opacity duration-500 opacity-0"
+                                                }
+                                            }
+                                            title="Saved"
+                                            aria-hidden="true"
+                                        >
+                                            "✓"
+                                        </span>
+                                    }
+                                    .into_any(),
+                                }}
+                            </span>
                         </div>
                         </div>
 
@@ -3337,7 +5961,7 @@ mod editor_delete_behavior_tests {
     use super::*;
 
     #[test]
-    fn test_collect_visible_preorder_ids_filters_deleted() {
+    fn test_visible_preorder_filters_deleted() {
         let note_id = "note".to_string();
         let root = ROOT_CONTAINER_PARENT_ID.to_string();
 
@@ -3350,6 +5974,8 @@ mod editor_delete_behavior_tests {
             is_display: true,
             is_delete: false,
             properties: None,
+            created_at: None,
+            updated_at: None,
         };
         let b_deleted = Nav {
             id: "b".to_string(),
@@ -3360,6 +5986,8 @@ mod editor_delete_behavior_tests {
             is_display: true,
             is_delete: true,
             properties: None,
+            created_at: None,
+            updated_at: None,
         };
         let c = Nav {
             id: "c".to_string(),
@@ -3370,10 +5998,12 @@ mod editor_delete_behavior_tests {
             is_display: true,
             is_delete: false,
             properties: None,
+            created_at: None,
+            updated_at: None,
         };
 
         let all = vec![b_deleted, c, a];
-        let ids = collect_visible_preorder_ids(&all);
+        let ids = visible_preorder_within(&all, None);
 
         // Deleted node is excluded; children of visible nodes are included.
         assert_eq!(ids, vec!["a".to_string(), "c".to_string()]);
@@ -0,0 +1,179 @@
+use crate::models::Nav;
+use crate::util::ROOT_CONTAINER_PARENT_ID;
+
+/// Render a note's outline as a nested Markdown list.
+///
+/// - DFS over the nav tree, siblings ordered by `same_deep_order`.
+/// - Two spaces of indentation per level; soft line breaks stay inside the list item.
+/// - Collapsed nodes (`is_display == false`) are exported with their children.
+/// - Deleted nodes (and their subtrees) are skipped.
+pub(crate) fn navs_to_markdown(navs: &[Nav]) -> String {
+    fn children<'a>(navs: &'a [Nav], parid: &str) -> Vec<&'a Nav> {
+        let mut out = navs
+            .iter()
+            .filter(|n| !n.is_delete && n.parid == parid)
+            .collect::<Vec<_>>();
+        out.sort_by(|a, b| {
+            a.same_deep_order
+                .partial_cmp(&b.same_deep_order)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        out
+    }
+
+    fn walk(navs: &[Nav], parid: &str, depth: usize, out: &mut Vec<String>) {
+        let indent = "  ".repeat(depth);
+        for n in children(navs, parid) {
+            let mut lines = n.content.lines();
+            let first = lines.next().unwrap_or("");
+            out.push(format!("{indent}- {first}").trim_end().to_string());
+            for line in lines {
+                out.push(format!("{indent}  {line}").trim_end().to_string());
+            }
+            walk(navs, &n.id, depth + 1, out);
+        }
+    }
+
+    // Backend schema: explicit ROOT container node has parid == all-zero.
+    // Real top-level nodes have parid == root_container.id.
+    let root_candidates = navs
+        .iter()
+        .filter(|n| n.parid == ROOT_CONTAINER_PARENT_ID)
+        .collect::<Vec<_>>();
+    let root_parid = if root_candidates.len() == 1 {
+        root_candidates[0].id.as_str()
+    } else {
+        ROOT_CONTAINER_PARENT_ID
+    };
+
+    let mut out = vec![];
+    walk(navs, root_parid, 0, &mut out);
+    if out.is_empty() {
+        return String::new();
+    }
+    out.join("\n") + "\n"
+}
+
+/// `<title>.md`, with characters that are invalid in file names replaced.
+pub(crate) fn markdown_export_filename(title: &str) -> String {
+    let name = title
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '-',
+            c if c.is_control() => '-',
+            c => c,
+        })
+        .collect::<String>();
+    let name = name.trim().trim_matches('.');
+    if name.is_empty() {
+        "Untitled.md".to_string()
+    } else {
+        format!("{name}.md")
+    }
+}
+
+/// Trigger a browser download of `markdown` as `filename` (Blob + temporary object URL).
+pub(crate) fn download_markdown(filename: &str, markdown: &str) -> Result<(), String> {
+    use wasm_bindgen::JsCast;
+
+    let window = web_sys::window().ok_or("no window")?;
+    let document = window.document().ok_or("no document")?;
+
+    let parts = js_sys::Array::of1(&wasm_bindgen::JsValue::from_str(markdown));
+    let opts = web_sys::BlobPropertyBag::new();
+    opts.set_type("text/markdown;charset=utf-8");
+    let blob = web_sys::Blob::new_with_str_sequence_and_options(&parts, &opts)
+        .map_err(|_| "failed to create blob".to_string())?;
+    let url = web_sys::Url::create_object_url_with_blob(&blob)
+        .map_err(|_| "failed to create object URL".to_string())?;
+
+    let a = document
+        .create_element("a")
+        .map_err(|_| "failed to create link".to_string())?;
+    let _ = a.set_attribute("href", &url);
+    let _ = a.set_attribute("download", filename);
+    if let Ok(a) = a.dyn_into::<web_sys::HtmlElement>() {
+        a.click();
+    }
+
+    // Revoke after the click has been handled so the download isn't cancelled.
+    let revoke = wasm_bindgen::closure::Closure::once_into_js(move || {
+        let _ = web_sys::Url::revoke_object_url(&url);
+    });
+    let _ = window
+        .set_timeout_with_callback_and_timeout_and_arguments_0(revoke.as_ref().unchecked_ref(), 0);
+    Ok(())
+}
+
+/// Copy `text` to the clipboard and report the outcome through `on_done`.
+///
+/// Uses the async Clipboard API when available; if it is missing (insecure origin) or the
+/// write is rejected (permission denied), falls back to `execCommand("copy")`.
+pub(crate) fn copy_to_clipboard(text: &str, on_done: impl Fn(Result<(), String>) + 'static) {
+    use wasm_bindgen::JsCast;
+
+    let Some(window) = web_sys::window() else {
+        on_done(Err("no window".to_string()));
+        return;
+    };
+
+    let navigator = window.navigator();
+    let has_clipboard_api = js_sys::Reflect::get(&navigator, &"clipboard".into())
+        .map(|v| !v.is_undefined() && !v.is_null())
+        .unwrap_or(false);
+    if !has_clipboard_api {
+        on_done(copy_with_exec_command(text));
+        return;
+    }
+
+    let on_done = std::rc::Rc::new(on_done);
+    let on_ok = {
+        let on_done = on_done.clone();
+        wasm_bindgen::closure::Closure::once_into_js(move |_: wasm_bindgen::JsValue| {
+            (*on_done)(Ok(()))
+        })
+    };
+    let on_err = {
+        let text = text.to_string();
+        wasm_bindgen::closure::Closure::once_into_js(move |_: wasm_bindgen::JsValue| {
+            (*on_done)(copy_with_exec_command(&text))
+        })
+    };
+    let _ = navigator
+        .clipboard()
+        .write_text(text)
+        .then2(on_ok.unchecked_ref(), on_err.unchecked_ref());
+}
+
+/// Legacy copy: select a hidden, off-screen `<textarea>` and run `execCommand("copy")`.
+fn copy_with_exec_command(text: &str) -> Result<(), String> {
+    use wasm_bindgen::JsCast;
+
+    let document = web_sys::window()
+        .and_then(|w| w.document())
+        .ok_or("no document")?;
+    let body = document.body().ok_or("no body")?;
+
+    let textarea = document
+        .create_element("textarea")
+        .ok()
+        .and_then(|el| el.dyn_into::<web_sys::HtmlTextAreaElement>().ok())
+        .ok_or("failed to create textarea")?;
+    textarea.set_value(text);
+    let _ = textarea.set_attribute("readonly", "");
+    let _ = textarea.set_attribute("style", "position:fixed;top:0;left:-9999px;opacity:0");
+    let _ = body.append_child(&textarea);
+    textarea.select();
+
+    let copied = document
+        .dyn_ref::<web_sys::HtmlDocument>()
+        .and_then(|d| d.exec_command("copy").ok())
+        .unwrap_or(false);
+    textarea.remove();
+
+    if copied {
+        Ok(())
+    } else {
+        Err("Clipboard access was denied".to_string())
+    }
+}
@@ -4,7 +4,10 @@ mod cache;
 mod components;
 mod drafts;
 mod editor;
+mod export;
+mod markdown;
 mod models;
+mod outline;
 mod pages;
 mod state;
 mod storage;
@@ -22,8 +25,8 @@ use wasm_bindgen::prelude::wasm_bindgen;
 mod wasm_tests {
     use crate::api::ApiClient;
     use crate::drafts::{
-        get_nav_override, get_title_override, mark_nav_synced, mark_title_synced, touch_nav,
-        touch_title,
+        get_nav_override, get_title_override, load_note_draft, mark_nav_synced, mark_title_synced,
+        revert_title_draft, touch_nav, touch_title,
     };
     use crate::editor::{
         insert_soft_line_break_dom, should_exit_edit_on_click_target,
@@ -71,10 +74,10 @@ mod wasm_tests {
         ApiClient::clear_storage();
 
         let mut c = ApiClient::load_from_storage();
-        assert!(!c.is_authenticated());
+        assert!(!c.has_valid_token());
 
         c.set_token("t1".to_string());
-        c.save_to_storage();
+        c.save_to_storage(true);
 
         let c2 = ApiClient::load_from_storage();
         assert_eq!(c2.get_auth_token().as_deref(), Some("t1"));
@@ -139,6 +142,29 @@ mod wasm_tests {
         ApiClient::clear_storage();
     }
 
+    #[wasm_bindgen_test]
+    fn test_revert_title_draft_restores_server_title_as_synced() {
+        let db_id = "db-test-revert";
+        let note_id = "note-test-revert";
+        let key = format!("hulunote_draft_note::{db_id}::{note_id}");
+        if let Some(storage) = web_sys::window().and_then(|w| w.local_storage().ok().flatten()) {
+            let _ = storage.remove_item(&key);
+        }
+
+        touch_title(db_id, note_id, "rejected");
+        revert_title_draft(db_id, note_id, "server");
+        assert_eq!(get_title_override(db_id, note_id, "server"), "server");
+
+        // Not left dirty, so the retry worker won't resend it.
+        let title = load_note_draft(db_id, note_id).title.expect("title draft");
+        assert!(title.updated_ms <= title.synced_ms);
+        assert_eq!(title.retry_count, 0);
+
+        if let Some(storage) = web_sys::window().and_then(|w| w.local_storage().ok().flatten()) {
+            let _ = storage.remove_item(&key);
+        }
+    }
+
     #[wasm_bindgen_test]
     fn test_insert_soft_line_break_dom_twice_advances_caret() {
         with_test_root(|root| {
@@ -379,14 +405,94 @@ pub fn main() {
 
 #[cfg(test)]
 mod tests {
-    use crate::api::{ApiClient, LoginResponse, SignupRequest, SignupResponse};
+    use crate::api::{
+        auth_error_message, copy_incomplete_message, duplicate_note_title, is_database_limit_error,
+        jwt_expiry_ms, AbortHandle, ApiClient, ApiError, GetNoteListRequest, LoginResponse,
+        RetryPolicy, SignupRequest, SignupResponse, UpdateDatabaseRequest, UpdateNoteRequest,
+        DEFAULT_REQUEST_TIMEOUT_MS,
+    };
+    use crate::cache::collapse_state::{
+        collapse_all, collapsed_block_ids, display_state_diff, expand_all, set_all_collapsed,
+        upsert_collapse_entry, CollapsedBlocks, COLLAPSE_STATE_TTL_MS,
+    };
+    use crate::drafts::{
+        nav_sync_statuses, unsynced_nav_count, FieldDraft, NavSyncStatus, NoteDraft,
+    };
     use crate::editor::{
-        apply_nav_content, backfill_content_request, compute_reorder_target, get_nav_content,
-        is_tmp_nav_id, make_tmp_nav_id, swap_tmp_nav_id,
+        ac_index_after_key, apply_nav_content, backfill_content_request, collect_subtree_ids,
+        compute_indent_moves, compute_outdent_moves, compute_reorder_target,
+        compute_reparent_target, compute_sibling_move, drop_position_for, fits_max_nav_depth,
+        get_nav_content, is_tmp_nav_id, layout_pasted_blocks, make_tmp_nav_id,
+        plan_merge_into_previous, plan_multiline_paste, plan_text_insert, rebalance_for_outdent,
+        rebalance_for_reorder, rebalance_for_sibling_move, resume_nav_id, selection_plain_text,
+        selection_range, selection_roots, strip_template_command, swap_tmp_nav_id,
+        visible_preorder_within, zoom_breadcrumb, zoom_href, DropPosition, PastedBlock,
+        MAX_NAV_DEPTH,
+    };
+    use crate::export::{markdown_export_filename, navs_to_markdown};
+    use crate::markdown::{
+        flatten_inline, parse_inline_markdown, InlineRun, InlineSpan, InlineStyle,
+    };
+    use crate::models::{
+        AccountInfo, Database, DbName, DeletedItem, DeletedItemKind, Nav, NavProperties, Note,
+        NoteFilter, NoteSort, NoteTemplate, NoteTitleFormat, PaletteAction, PaletteCommand,
+        PartialNav, RecentDb, RecentNote, Settings, Theme,
     };
-    use crate::models::{Nav, Note, RecentDb, RecentNote};
-    use crate::storage::upsert_lru_by_key;
-    use crate::util::next_available_daily_note_title_for_date;
+    use crate::outline::{
+        apply_orders, cmp_order, insert_order_between, orders_crowded, rebalance_for_insert,
+        renumber_children, NavTree,
+    };
+    use crate::storage::{prune_recent_notes_not_in, retarget_recent_notes, upsert_lru_by_key};
+    use crate::util::{
+        collapsed_ancestor_ids, count_note_chars, created_nav_id, daily_note_key, daily_notes,
+        databases_need_refresh, db_id_from_path, decode_base64url, default_database_updates,
+        deleted_ancestor_ids, deleted_item_label, deleted_navs_payload, document_title,
+        expand_template, filter_databases, filter_notes_by_updated, find_database_by_name,
+        find_note_by_title, format_char_count_badge, format_edited_ago, format_month_day,
+        format_new_note_title, format_outline_stats, hash_pin, is_valid_pin, live_deleted_items,
+        live_outline, login_url_with_next, merge_notes_page, nav_copy_order, nav_id_from_fragment,
+        navs_cache_is_fresh, next_available_daily_note_title_for_date, note_filter_bounds,
+        note_id_from_path, note_preview, note_previews, notes_with_ids, outline_stats,
+        palette_items, parse_deleted_navs_payload, partition_pinned_notes, plain_text_preview,
+        prune_pinned_ids, public_database_url, quick_switch_rank, rank_databases,
+        rank_quick_switch, reconcile_recent_dbs, relative_time_label, resolve_db_name,
+        restore_nav_ids, safe_next_path, search_cache_key, search_create_title,
+        search_title_suggestions, should_follow_server_title, shown_note_preview, sort_notes,
+        template_nav_order, template_navs_from_outline, template_outline_text, theme_is_dark,
+        top_level_restore_target, trash_outline, truncate_title, DATABASES_STALE_AFTER_MS,
+        NAVS_CACHE_TTL_MS, SEARCH_SUGGESTION_LIMIT, UNDO_DELETE_WINDOW_MS,
+    };
+    use crate::wiki::{
+        build_tags_index, count_open_todos, cycle_todo, extract_block_refs, extract_tags,
+        find_backlinks, parse_wiki_tokens, search_blocks, set_todo_done, split_todo,
+        wiki_link_href, WikiToken,
+    };
+
+    fn nav(id: &str, parid: &str, order: f32) -> Nav {
+        Nav {
+            id: id.to_string(),
+            note_id: "n".to_string(),
+            parid: parid.to_string(),
+            same_deep_order: order,
+            content: "".to_string(),
+            is_display: true,
+            is_delete: false,
+            properties: None,
+            created_at: None,
+            updated_at: None,
+        }
+    }
+
+    fn note(id: &str, db: &str, title: &str) -> Note {
+        Note {
+            id: id.to_string(),
+            database_id: db.to_string(),
+            title: title.to_string(),
+            content: String::new(),
+            created_at: String::new(),
+            updated_at: String::new(),
+        }
+    }
 
     #[test]
     fn test_login_response_contract_deserialize() {
@@ -415,9 +521,78 @@ mod tests {
         }"#;
         let parsed: SignupResponse =
             serde_json::from_str(json).expect("signup response should parse");
-        assert_eq!(parsed.token, "jwt-token");
+        assert_eq!(parsed.token.as_deref(), Some("jwt-token"));
         assert_eq!(parsed.database.as_deref(), Some("u-1234"));
-        assert!(parsed.hulunote.extra.is_object());
+        let (token, user) = parsed.into_session().expect("signs the new account in");
+        assert_eq!(token, "jwt-token");
+        assert!(user.extra.is_object());
+
+        // Without a token the page falls back to "please log in".
+        let parsed: SignupResponse = serde_json::from_str(r#"{"database": null, "region": null}"#)
+            .expect("token-less signup response should parse");
+        assert!(parsed.into_session().is_none());
+        let parsed: SignupResponse =
+            serde_json::from_str(r#"{"token": "", "hulunote": {}}"#).expect("should parse");
+        assert!(parsed.into_session().is_none());
+    }
+
+    #[test]
+    fn test_account_info_accessors_and_profile_response() {
+        let plain = ApiClient::parse_account_info_response(serde_json::json!({
+            "hulunote": {"id": 1, "username": "Ada Lovelace", "mail": "ada@example.com"}
+        }))
+        .expect("account under `hulunote`");
+        assert_eq!(plain.id().as_deref(), Some("1"));
+        assert_eq!(plain.username().as_deref(), Some("Ada Lovelace"));
+        assert_eq!(plain.email().as_deref(), Some("ada@example.com"));
+        assert_eq!(plain.initials(), "AL");
+
+        // Namespaced keys at the top level; no email (older backends).
+        let namespaced = ApiClient::parse_account_info_response(serde_json::json!({
+            "accounts/id": "7", "accounts/username": " bob "
+        }))
+        .expect("namespaced account");
+        assert_eq!(namespaced.username().as_deref(), Some("bob"));
+        assert!(namespaced.email().is_none());
+        assert_eq!(namespaced.initials(), "BO");
+
+        let email_only = AccountInfo {
+            extra: serde_json::json!({"email": "carol.doe@example.com", "username": ""}),
+        };
+        assert_eq!(email_only.initials(), "CD");
+        assert_eq!(
+            AccountInfo {
+                extra: serde_json::json!({})
+            }
+            .initials(),
+            "?"
+        );
+
+        // The avatar color is stable per username and in the hue range.
+        assert_eq!(plain.avatar_hue(), plain.clone().avatar_hue());
+        assert_ne!(plain.avatar_hue(), namespaced.avatar_hue());
+        assert!(email_only.avatar_hue() < 360);
+
+        assert!(ApiClient::parse_account_info_response(serde_json::json!({"ok": true})).is_none());
+    }
+
+    #[test]
+    fn test_find_database_by_name() {
+        let db = |id: &str, name: &str| Database {
+            id: id.to_string(),
+            name: name.to_string(),
+            description: String::new(),
+            created_at: String::new(),
+            updated_at: String::new(),
+            is_default: false,
+            is_public: false,
+        };
+        let dbs = vec![db("1", "u-1234 notes"), db("2", "u-1234")];
+        assert_eq!(
+            find_database_by_name(&dbs, " u-1234 ").map(|d| d.id.as_str()),
+            Some("2")
+        );
+        assert!(find_database_by_name(&dbs, "u-12").is_none());
     }
 
     #[test]
@@ -470,16 +645,35 @@ mod tests {
     }
 
     #[test]
-    fn test_api_client_is_authenticated_false() {
+    fn test_api_client_has_valid_token_false() {
         let client = ApiClient::new("http://localhost:6689".to_string());
-        assert!(!client.is_authenticated());
+        assert!(!client.has_valid_token());
     }
 
     #[test]
-    fn test_api_client_is_authenticated_true() {
+    fn test_api_client_has_valid_token_true() {
         let mut client = ApiClient::new("http://localhost:6689".to_string());
         client.set_token("my-jwt-token".to_string());
-        assert!(client.is_authenticated());
+        assert!(client.has_valid_token());
+    }
+
+    #[test]
+    fn test_jwt_expiry_detection() {
+        // {"alg":"HS256","typ":"JWT"} . {"sub":"1","exp":1700000000}
+        let token =
+            "eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9.eyJzdWIiOiIxIiwiZXhwIjoxNzAwMDAwMDAwfQ.sig";
+        assert_eq!(jwt_expiry_ms(token), Some(1_700_000_000_000));
+        assert_eq!(jwt_expiry_ms("not-a-jwt"), None);
+        assert_eq!(jwt_expiry_ms("a.!!!.c"), None);
+
+        let mut client = ApiClient::new("http://localhost:6689".to_string());
+        assert!(!client.has_valid_token_at(0));
+        client.set_token(token.to_string());
+        assert!(client.has_valid_token_at(1_699_999_999_000));
+        assert!(!client.has_valid_token_at(1_700_000_000_000));
+
+        assert_eq!(decode_base64url("aGk").as_deref(), Some(&b"hi"[..]));
+        assert_eq!(decode_base64url("aGk=").as_deref(), Some(&b"hi"[..]));
     }
 
     #[test]
@@ -494,6 +688,8 @@ mod tests {
                 is_display: true,
                 is_delete: false,
                 properties: None,
+                created_at: None,
+                updated_at: None,
             },
             Nav {
                 id: "b".to_string(),
@@ -504,6 +700,8 @@ mod tests {
                 is_display: true,
                 is_delete: false,
                 properties: None,
+                created_at: None,
+                updated_at: None,
             },
         ];
 
@@ -523,12 +721,34 @@ mod tests {
             is_display: true,
             is_delete: false,
             properties: None,
+            created_at: None,
+            updated_at: None,
         }];
 
         assert!(!apply_nav_content(&mut navs, "missing", "new"));
         assert_eq!(navs[0].content, "old");
     }
 
+    #[test]
+    fn test_apply_nav_content_keeps_soft_breaks() {
+        let mut navs = vec![Nav {
+            id: "a".to_string(),
+            note_id: "n".to_string(),
+            parid: "root".to_string(),
+            same_deep_order: 1.0,
+            content: "one".to_string(),
+            is_display: true,
+            is_delete: false,
+            properties: None,
+            created_at: None,
+            updated_at: None,
+        }];
+
+        assert!(apply_nav_content(&mut navs, "a", "one\ntwo\n"));
+        assert_eq!(navs[0].content, "one\ntwo\n");
+        assert_eq!(navs[0].content.lines().count(), 2);
+    }
+
     #[test]
     fn test_is_tmp_nav_id() {
         assert!(is_tmp_nav_id("tmp-1-2"));
@@ -551,6 +771,8 @@ mod tests {
             is_display: true,
             is_delete: false,
             properties: None,
+            created_at: None,
+            updated_at: None,
         }];
 
         assert!(swap_tmp_nav_id(&mut navs, "tmp-1-2", "real"));
@@ -568,6 +790,8 @@ mod tests {
             is_display: true,
             is_delete: false,
             properties: None,
+            created_at: None,
+            updated_at: None,
         }];
 
         assert!(!swap_tmp_nav_id(&mut navs, "tmp-1-2", "real"));
@@ -585,6 +809,8 @@ mod tests {
             is_display: true,
             is_delete: false,
             properties: None,
+            created_at: None,
+            updated_at: None,
         }];
 
         assert_eq!(get_nav_content(&navs, "a"), Some("hello".to_string()));
@@ -620,6 +846,8 @@ mod tests {
                 is_display: true,
                 is_delete: false,
                 properties: None,
+                created_at: None,
+                updated_at: None,
             },
             Nav {
                 id: "t".to_string(),
@@ -630,6 +858,8 @@ mod tests {
                 is_display: true,
                 is_delete: false,
                 properties: None,
+                created_at: None,
+                updated_at: None,
             },
             Nav {
                 id: "u".to_string(),
@@ -640,6 +870,8 @@ mod tests {
                 is_display: true,
                 is_delete: false,
                 properties: None,
+                created_at: None,
+                updated_at: None,
             },
         ];
 
@@ -661,6 +893,8 @@ mod tests {
                 is_display: true,
                 is_delete: false,
                 properties: None,
+                created_at: None,
+                updated_at: None,
             },
             Nav {
                 id: "d".to_string(),
@@ -671,6 +905,8 @@ mod tests {
                 is_display: true,
                 is_delete: false,
                 properties: None,
+                created_at: None,
+                updated_at: None,
             },
             Nav {
                 id: "t".to_string(),
@@ -681,6 +917,8 @@ mod tests {
                 is_display: true,
                 is_delete: false,
                 properties: None,
+                created_at: None,
+                updated_at: None,
             },
             Nav {
                 id: "b".to_string(),
@@ -691,6 +929,8 @@ mod tests {
                 is_display: true,
                 is_delete: false,
                 properties: None,
+                created_at: None,
+                updated_at: None,
             },
         ];
 
@@ -722,6 +962,57 @@ mod tests {
         assert_eq!(out.len(), 1);
         assert_eq!(out[0].name, "ypyf-9361");
         assert!(out[0].id.starts_with("0a1dd8e1"));
+        assert!(!out[0].is_default);
+        assert!(!out[0].is_public);
+    }
+
+    #[test]
+    fn test_default_database_updates_keep_one_default() {
+        let v = serde_json::json!({
+            "database-list": [
+                { "hulunote-databases/id": "a", "hulunote-databases/name": "A" },
+                {
+                    "hulunote-databases/id": "b",
+                    "hulunote-databases/name": "B",
+                    "hulunote-databases/is-default": true
+                }
+            ]
+        });
+        let dbs = ApiClient::parse_database_list_response(v);
+        assert!(!dbs[0].is_default);
+        assert!(dbs[1].is_default);
+
+        // Marking another database clears the old default first.
+        assert_eq!(
+            default_database_updates(&dbs, "a"),
+            vec![("b".to_string(), false), ("a".to_string(), true)]
+        );
+        // Toggling the current default unsets it.
+        assert_eq!(
+            default_database_updates(&dbs, "b"),
+            vec![("b".to_string(), false)]
+        );
+    }
+
+    #[test]
+    fn test_parse_create_database_response_nested_and_plain() {
+        let v = serde_json::json!({
+            "database": {
+                "hulunote-databases/id": "db-new",
+                "hulunote-databases/name": "Work",
+                "hulunote-databases/created-at": "t1"
+            }
+        });
+        let db = ApiClient::parse_create_database_response(v).expect("database");
+        assert_eq!(db.id, "db-new");
+        assert_eq!(db.name, "Work");
+        assert_eq!(db.created_at, "t1");
+
+        let v = serde_json::json!({ "id": "db-2", "name": "Plain" });
+        let db = ApiClient::parse_create_database_response(v).expect("database");
+        assert_eq!((db.id.as_str(), db.name.as_str()), ("db-2", "Plain"));
+
+        assert!(ApiClient::parse_create_database_response(serde_json::json!({})).is_none());
     }
 
     // NOTE: note list parsing is intentionally strict to the canonical contract.
@@ -749,6 +1040,30 @@ mod tests {
         assert_eq!(out[0].updated_at, "t2");
     }
 
+    #[test]
+    fn test_parse_note_list_splits_deleted_notes() {
+        let v = serde_json::json!({
+            "note-list": [
+                { "hulunote-notes/id": "live", "hulunote-notes/database-id": "db" },
+                {
+                    "hulunote-notes/id": "gone",
+                    "hulunote-notes/database-id": "db",
+                    "hulunote-notes/is-delete": true
+                }
+            ]
+        });
+
+        let ids = |xs: Vec<Note>| xs.into_iter().map(|n| n.id).collect::<Vec<_>>();
+        assert_eq!(
+            ids(ApiClient::parse_note_list_response(v.clone())),
+            vec!["live"]
+        );
+        assert_eq!(
+            ids(ApiClient::parse_deleted_note_list_response(v)),
+            vec!["gone"]
+        );
+    }
+
     #[test]
     fn test_next_available_daily_note_title_adds_suffix() {
         let base = "20260209";
@@ -812,4 +1127,2423 @@ mod tests {
         let note2: RecentNote = serde_json::from_str(&note_json).unwrap();
         assert_eq!(note, note2);
     }
+
+    #[test]
+    fn test_format_char_count_badge_rounds_to_hundreds() {
+        assert_eq!(format_char_count_badge(0), "0 chars");
+        assert_eq!(format_char_count_badge(42), "42 chars");
+        assert_eq!(format_char_count_badge(349), "~300 chars");
+        assert_eq!(format_char_count_badge(1234), "~1.2k chars");
+        assert_eq!(format_char_count_badge(1960), "~2k chars");
+        assert_eq!(format_char_count_badge(12_340), "~12.3k chars");
+    }
+
+    #[test]
+    fn test_count_note_chars_skips_deleted_navs() {
+        let navs = vec![
+            Nav {
+                content: "hello".to_string(),
+                ..nav("a", "root", 1.0)
+            },
+            Nav {
+                content: "爱你".to_string(),
+                ..nav("b", "root", 1.0)
+            },
+            Nav {
+                content: "gone".to_string(),
+                is_delete: true,
+                ..nav("c", "root", 1.0)
+            },
+        ];
+        assert_eq!(count_note_chars(&navs), 7);
+    }
+
+    #[test]
+    fn test_plain_text_preview_and_note_previews() {
+        assert_eq!(
+            plain_text_preview("{{TODO}} Read **[[Rust Book]]** and `cargo` docs", 80),
+            "Read Rust Book and cargo docs"
+        );
+        assert_eq!(
+            plain_text_preview("See ((abc123))\n  *later*  https://x.io", 80),
+            "See later https://x.io"
+        );
+        assert_eq!(plain_text_preview("abcdefghij", 5), "abcd…");
+        assert_eq!(plain_text_preview("  ((ref-only))  ", 80), "");
+
+        let root = crate::util::ROOT_CONTAINER_PARENT_ID;
+        let navs = vec![
+            Nav {
+                note_id: "n1".to_string(),
+                content: "ROOT".to_string(),
+                ..nav("r1", root, 0.0)
+            },
+            Nav {
+                note_id: "n1".to_string(),
+                content: "Second".to_string(),
+                ..nav("b", "r1", 2.0)
+            },
+            Nav {
+                note_id: "n1".to_string(),
+                content: "  ".to_string(),
+                ..nav("a", "r1", 1.0)
+            },
+            Nav {
+                note_id: "n1".to_string(),
+                content: "Nested".to_string(),
+                ..nav("a1", "a", 0.0)
+            },
+            Nav {
+                is_delete: true,
+                ..mk("z", "n1", "r1", 0.0, "Deleted")
+            },
+            Nav {
+                note_id: "n2".to_string(),
+                content: "ROOT".to_string(),
+                ..nav("r2", root, 0.0)
+            },
+            Nav {
+                note_id: "n3".to_string(),
+                content: "Later".to_string(),
+                ..nav("l2", root, 2.0)
+            },
+            Nav {
+                note_id: "n3".to_string(),
+                content: "Legacy **top**".to_string(),
+                ..nav("l1", root, 1.0)
+            },
+        ];
+        // Empty top-level blocks are skipped; children never stand in for them. A lone ROOT
+        // container is never the preview itself.
+        assert_eq!(note_preview(&navs[..5]).as_deref(), Some("Second"));
+        assert_eq!(note_preview(&navs[5..6]), None);
+
+        let previews = note_previews(&navs);
+        assert_eq!(previews.len(), 2);
+        assert_eq!(previews.get("n1").map(String::as_str), Some("Second"));
+        assert_eq!(previews.get("n3").map(String::as_str), Some("Legacy top"));
+
+        let by_db = std::collections::HashMap::from([("db".to_string(), previews)]);
+        assert_eq!(
+            shown_note_preview(&by_db, "db", "n1", false).as_deref(),
+            Some("Second")
+        );
+        // A locked note never leaks its first block into the lists.
+        assert_eq!(shown_note_preview(&by_db, "db", "n1", true), None);
+        assert_eq!(shown_note_preview(&by_db, "other", "n1", false), None);
+    }
+
+    #[test]
+    fn test_partition_pinned_notes_keeps_pin_order() {
+        let notes = vec![
+            note("a", "db", "a"),
+            note("b", "db", "b"),
+            note("c", "db", "c"),
+            note("d", "db", "d"),
+        ];
+        let pinned_ids = vec!["c".to_string(), "a".to_string(), "missing".to_string()];
+
+        let (pinned, rest) = partition_pinned_notes(notes, &pinned_ids);
+        let ids = |xs: &[Note]| xs.iter().map(|n| n.id.clone()).collect::<Vec<_>>();
+        assert_eq!(ids(&pinned), vec!["c", "a"]);
+        assert_eq!(ids(&rest), vec!["b", "d"]);
+    }
+
+    #[test]
+    fn test_update_database_request_description_serialization() {
+        let req = UpdateDatabaseRequest {
+            database_id: Some("db1".to_string()),
+            id: None,
+            db_name: None,
+            description: Some("Work notes".to_string()),
+            is_public: None,
+            is_default: None,
+            is_delete: None,
+        };
+        let v = serde_json::to_value(req).expect("should serialize");
+        assert_eq!(v["database-id"], "db1");
+        assert_eq!(v["db-description"], "Work notes");
+        // Unset fields are omitted so a description update never renames the DB.
+        assert!(v.get("db-name").is_none());
+        assert!(v.get("is-public").is_none());
+    }
+
+    #[test]
+    fn test_update_note_request_serialization() {
+        let rename = UpdateNoteRequest {
+            note_id: "n1".to_string(),
+            title: Some("Ideas".to_string()),
+            database_id: None,
+        };
+        let v = serde_json::to_value(rename).expect("should serialize");
+        assert_eq!(v["note-id"], "n1");
+        assert_eq!(v["title"], "Ideas");
+        // A rename never moves the note.
+        assert!(v.get("database-id").is_none());
+
+        let moved = UpdateNoteRequest {
+            note_id: "n1".to_string(),
+            title: None,
+            database_id: Some("db2".to_string()),
+        };
+        let v = serde_json::to_value(moved).expect("should serialize");
+        assert_eq!(v["database-id"], "db2");
+        assert!(v.get("title").is_none());
+
+        // A move onto a taken title renames in the same request.
+        let moved = UpdateNoteRequest {
+            note_id: "n1".to_string(),
+            title: Some("Ideas-2".to_string()),
+            database_id: Some("db2".to_string()),
+        };
+        let v = serde_json::to_value(moved).expect("should serialize");
+        assert_eq!(
+            (v["title"].as_str(), v["database-id"].as_str()),
+            (Some("Ideas-2"), Some("db2"))
+        );
+    }
+
+    #[test]
+    fn test_theme_is_dark_and_serde() {
+        assert!(!theme_is_dark(Theme::Light, true));
+        assert!(theme_is_dark(Theme::Dark, false));
+        assert!(theme_is_dark(Theme::System, true));
+        assert!(!theme_is_dark(Theme::System, false));
+
+        assert_eq!(Theme::default(), Theme::System);
+        let json = serde_json::to_string(&Theme::Dark).unwrap();
+        assert_eq!(json, "\"Dark\"");
+        let back: Theme = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, Theme::Dark);
+    }
+
+    #[test]
+    fn test_drop_position_for_zones() {
+        assert_eq!(drop_position_for(0.1, 0.1), DropPosition::Before);
+        assert_eq!(drop_position_for(0.1, 0.9), DropPosition::After);
+        assert_eq!(drop_position_for(0.1, 0.5), DropPosition::Inside);
+        // Right portion of the row always nests.
+        assert_eq!(drop_position_for(0.8, 0.1), DropPosition::Inside);
+    }
+
+    #[test]
+    fn test_compute_reparent_target_appends_as_last_child() {
+        let all = vec![
+            nav("t", "root", 1.0),
+            nav("c1", "t", 1.0),
+            nav("c2", "t", 4.0),
+            nav("d", "root", 2.0),
+            nav("dc", "d", 1.0),
+        ];
+
+        let (parid, order) =
+            compute_reparent_target(&all, "d", "t").expect("should compute reparent target");
+        assert_eq!(parid, "t");
+        assert!(order > 4.0);
+
+        // Never into its own subtree (or onto itself).
+        assert!(compute_reparent_target(&all, "d", "dc").is_none());
+        assert!(compute_reparent_target(&all, "d", "d").is_none());
+
+        // Already the last child: no-op.
+        assert!(compute_reparent_target(&all, "c2", "t").is_none());
+        // Earlier child moves to the end.
+        let (_, order) = compute_reparent_target(&all, "c1", "t").unwrap();
+        assert!(order > 4.0);
+    }
+
+    #[test]
+    fn test_db_id_from_path() {
+        assert_eq!(db_id_from_path("/db/abc"), Some("abc".to_string()));
+        assert_eq!(db_id_from_path("/db/abc/note/n1"), Some("abc".to_string()));
+        assert_eq!(db_id_from_path("/db/"), None);
+        assert_eq!(db_id_from_path("/settings"), None);
+        assert_eq!(db_id_from_path("/"), None);
+    }
+
+    #[test]
+    fn test_document_title_parts() {
+        assert_eq!(note_id_from_path("/db/abc/note/n1"), Some("n1".to_string()));
+        assert_eq!(note_id_from_path("/db/abc/note"), None);
+        assert_eq!(note_id_from_path("/db/abc/tag/n1"), None);
+        assert_eq!(
+            document_title(Some("Ideas"), Some("Work"), false),
+            "Ideas – Work – Hulunote"
+        );
+        // Notes not loaded yet: only the database is known.
+        assert_eq!(
+            document_title(None, Some("Work"), true),
+            "• Work – Hulunote"
+        );
+        assert_eq!(document_title(Some("  "), None, false), "Hulunote");
+    }
+
+    fn selection_fixture() -> Vec<Nav> {
+        vec![
+            nav("root", "00000000-0000-0000-0000-000000000000", 0.0),
+            Nav {
+                content: "A".to_string(),
+                ..nav("a", "root", 1.0)
+            },
+            Nav {
+                content: "A1".to_string(),
+                ..nav("a1", "a", 1.0)
+            },
+            Nav {
+                content: "B".to_string(),
+                ..nav("b", "root", 2.0)
+            },
+            Nav {
+                content: "C".to_string(),
+                ..nav("c", "root", 3.0)
+            },
+            Nav {
+                content: "D".to_string(),
+                ..nav("d", "root", 4.0)
+            },
+        ]
+    }
+
+    #[test]
+    fn test_deep_link_fragment_and_collapsed_ancestors() {
+        assert_eq!(nav_id_from_fragment("#nav-a1"), Some("a1".to_string()));
+        assert_eq!(nav_id_from_fragment("nav-a%20b"), Some("a b".to_string()));
+        assert_eq!(nav_id_from_fragment("#nav-"), None);
+        assert_eq!(nav_id_from_fragment("#top"), None);
+        assert_eq!(nav_id_from_fragment(""), None);
+
+        // root > a (collapsed) > a1 (collapsed) > a1x; the ROOT container never counts.
+        let mut all = selection_fixture();
+        let mut a1x = all[2].clone();
+        a1x.id = "a1x".to_string();
+        a1x.parid = "a1".to_string();
+        all.push(a1x);
+        for x in all.iter_mut() {
+            x.is_display = !matches!(x.id.as_str(), "root" | "a" | "a1");
+        }
+        assert_eq!(collapsed_ancestor_ids(&all, "a1x"), vec!["a1", "a"]);
+        assert_eq!(collapsed_ancestor_ids(&all, "a1"), vec!["a"]);
+        assert!(collapsed_ancestor_ids(&all, "b").is_empty());
+        assert!(collapsed_ancestor_ids(&all, "missing").is_empty());
+    }
+
+    #[test]
+    fn test_nav_depth_and_max_nesting() {
+        let mut all = selection_fixture();
+        assert_eq!(Nav::depth(&all, "root"), 0);
+        assert_eq!(Nav::depth(&all, "a"), 1);
+        assert_eq!(Nav::depth(&all, "a1"), 2);
+        assert_eq!(Nav::depth(&all, "missing"), 0);
+
+        // Chain c1 > c2 > ... under `a1`, ending at MAX_NAV_DEPTH.
+        let block = all[1].clone();
+        let mut parid = "a1".to_string();
+        for i in 3..=MAX_NAV_DEPTH {
+            let id = format!("c{i}");
+            all.push(Nav {
+                id: id.clone(),
+                parid: parid.clone(),
+                ..block.clone()
+            });
+            parid = id;
+        }
+        let deepest = format!("c{MAX_NAV_DEPTH}");
+        assert_eq!(Nav::depth(&all, &deepest), MAX_NAV_DEPTH);
+
+        // Nothing goes below the deepest block, and `a` (whose subtree reaches the limit)
+        // can't be indented under `b`; shallower moves are fine.
+        assert!(!fits_max_nav_depth(&all, "d", &deepest));
+        assert!(!fits_max_nav_depth(&all, "a", "b"));
+        assert!(fits_max_nav_depth(&all, "a", "root"));
+        assert!(fits_max_nav_depth(&all, "d", "a1"));
+    }
+
+    #[test]
+    fn test_restore_deleted_blocks() {
+        // root > a > a1 > a1x, all but root deleted; b stays live.
+        let mut all = selection_fixture();
+        all.push(Nav {
+            id: "a1x".to_string(),
+            parid: "a1".to_string(),
+            ..all[2].clone()
+        });
+        for n in all.iter_mut() {
+            n.is_delete = matches!(n.id.as_str(), "a" | "a1" | "a1x");
+        }
+
+        assert_eq!(deleted_ancestor_ids(&all, "a1x"), vec!["a1", "a"]);
+        assert!(deleted_ancestor_ids(&all, "a").is_empty());
+        assert_eq!(restore_nav_ids(&all, "a1", true), vec!["a", "a1", "a1x"]);
+        assert_eq!(restore_nav_ids(&all, "a1", false), vec!["a1", "a1x"]);
+        assert_eq!(top_level_restore_target(&all), ("root".to_string(), 5.0));
+
+        let outline = trash_outline(&all)
+            .into_iter()
+            .map(|(depth, n)| (depth, n.id.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            outline,
+            vec![
+                (0, "a"),
+                (1, "a1"),
+                (2, "a1x"),
+                (0, "b"),
+                (0, "c"),
+                (0, "d")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_live_outline_skips_deleted_subtrees() {
+        // root > a (deleted) > a1 (live) > a1x; b, c (deleted), d.
+        let mut all = selection_fixture();
+        all.push(Nav {
+            id: "a1x".to_string(),
+            parid: "a1".to_string(),
+            ..all[2].clone()
+        });
+        for n in all.iter_mut() {
+            n.is_delete = matches!(n.id.as_str(), "a" | "c");
+        }
+
+        let outline = live_outline(&all)
+            .into_iter()
+            .map(|(depth, n)| (depth, n.id.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(outline, vec![(0, "b"), (0, "d")]);
+
+        assert_eq!(
+            public_database_url("https://notes.example.com/", "db1"),
+            "https://notes.example.com/public/db1"
+        );
+    }
+
+    #[test]
+    fn test_todo_marker_tokens_and_toggles() {
+        assert_eq!(
+            parse_wiki_tokens("{{TODO}} buy [[Milk]]"),
+            vec![
+                WikiToken::Todo { done: false },
+                WikiToken::Text(" buy ".to_string()),
+                WikiToken::Link("Milk".to_string()),
+            ]
+        );
+        assert_eq!(
+            parse_wiki_tokens("{{DONE}}")[0],
+            WikiToken::Todo { done: true }
+        );
+        // Only a leading marker counts.
+        assert_eq!(
+            parse_wiki_tokens("see {{TODO}}"),
+            vec![WikiToken::Text("see {{TODO}}".to_string())]
+        );
+
+        assert_eq!(split_todo("{{DONE}} ship it"), (Some(true), "ship it"));
+        assert_eq!(split_todo("plain"), (None, "plain"));
+        assert_eq!(set_todo_done("{{TODO}} ship it", true), "{{DONE}} ship it");
+        assert_eq!(set_todo_done("{{DONE}}", false), "{{TODO}}");
+
+        assert_eq!(cycle_todo("ship it"), "{{TODO}} ship it");
+        assert_eq!(cycle_todo("{{TODO}} ship it"), "{{DONE}} ship it");
+        assert_eq!(cycle_todo("{{DONE}} ship it"), "ship it");
+
+        let navs = vec![
+            Nav {
+                content: "{{TODO}} one".to_string(),
+                ..nav("a", "root", 0.0)
+            },
+            Nav {
+                content: "{{DONE}} two".to_string(),
+                ..nav("b", "root", 0.0)
+            },
+            Nav {
+                content: "{{TODO}} gone".to_string(),
+                is_delete: true,
+                ..nav("c", "root", 0.0)
+            },
+            Nav {
+                content: "not {{TODO}}".to_string(),
+                ..nav("d", "root", 0.0)
+            },
+        ];
+        assert_eq!(count_open_todos(&navs), 1);
+    }
+
+    #[test]
+    fn test_tags_extracted_stored_and_indexed() {
+        assert_eq!(
+            extract_tags("#Rust and #wasm-bindgen, #rust again"),
+            vec!["rust", "wasm-bindgen"]
+        );
+        // Not at a word start, bare `#`, and inside links/refs: not tags.
+        assert!(extract_tags("a#b # ## [[#page]] ((#x)) https://x.dev/#top").is_empty());
+
+        // Unknown keys survive a round-trip; no tags and no keys store nothing.
+        let mut props = NavProperties::parse(Some(r#"{"color":"red","tags":["old"]}"#));
+        assert_eq!(props.tags, vec!["old"]);
+        props.tags = vec!["new".to_string()];
+        let json = props.to_json().expect("properties should serialize");
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&json).unwrap(),
+            serde_json::json!({"color": "red", "tags": ["new"]})
+        );
+        assert_eq!(
+            NavProperties::parse(Some("not json")),
+            NavProperties::default()
+        );
+        assert_eq!(NavProperties::default().to_json(), None);
+
+        let mut all = selection_fixture();
+        let tagged = Some(r#"{"tags":["todo"]}"#.to_string());
+        all[1].properties = tagged.clone();
+        all[2].properties = tagged.clone();
+        all.push(Nav {
+            id: "x".to_string(),
+            note_id: "m".to_string(),
+            properties: tagged.clone(),
+            ..all[3].clone()
+        });
+        all.push(Nav {
+            id: "gone".to_string(),
+            note_id: "z".to_string(),
+            properties: tagged,
+            is_delete: true,
+            ..all[3].clone()
+        });
+        let index = build_tags_index(&all);
+        assert_eq!(index.len(), 1);
+        assert_eq!(index["todo"], vec!["n", "m"]);
+    }
+
+    #[test]
+    fn test_ac_index_after_key_wraps_and_jumps() {
+        assert_eq!(ac_index_after_key(0, "ArrowDown", 3), Some(1));
+        assert_eq!(ac_index_after_key(2, "ArrowDown", 3), Some(0));
+        assert_eq!(ac_index_after_key(0, "ArrowUp", 3), Some(2));
+        assert_eq!(ac_index_after_key(1, "ArrowUp", 3), Some(0));
+        assert_eq!(ac_index_after_key(1, "Home", 30), Some(0));
+        assert_eq!(ac_index_after_key(1, "End", 30), Some(29));
+        assert_eq!(ac_index_after_key(25, "PageDown", 30), Some(29));
+        assert_eq!(ac_index_after_key(10, "PageUp", 30), Some(2));
+        assert_eq!(ac_index_after_key(3, "PageUp", 30), Some(0));
+        // A stale index past the end is clamped first.
+        assert_eq!(ac_index_after_key(9, "ArrowDown", 3), Some(0));
+        assert_eq!(ac_index_after_key(0, "ArrowDown", 0), Some(0));
+        assert_eq!(ac_index_after_key(0, "Enter", 3), None);
+    }
+
+    #[test]
+    fn test_nav_sync_statuses_from_drafts() {
+        let field = |updated_ms: i64, synced_ms: i64, retry_count: u32| FieldDraft {
+            value: String::new(),
+            updated_ms,
+            synced_ms,
+            retry_count,
+            next_retry_ms: 0,
+        };
+        let mut d = NoteDraft::default();
+        d.navs.insert("clean".to_string(), field(10, 10, 0));
+        d.navs.insert("typing".to_string(), field(20, 10, 0));
+        d.navs.insert("failed".to_string(), field(20, 10, 2));
+        // A failed metadata upsert wins over pending content.
+        d.navs.insert("moved".to_string(), field(20, 10, 0));
+        d.nav_meta.insert("moved".to_string(), field(20, 10, 1));
+        d.nav_meta.insert("meta-only".to_string(), field(30, 0, 0));
+
+        let statuses = nav_sync_statuses(&d);
+        assert_eq!(statuses.len(), 4);
+        assert!(!statuses.contains_key("clean"));
+        assert_eq!(statuses["typing"], NavSyncStatus::Pending);
+        assert_eq!(statuses["failed"], NavSyncStatus::Failed);
+        assert_eq!(statuses["moved"], NavSyncStatus::Failed);
+        assert_eq!(statuses["meta-only"], NavSyncStatus::Pending);
+        // Content and metadata edits of one nav count once.
+        assert_eq!(unsynced_nav_count(&d), 4);
+    }
+
+    #[test]
+    fn test_resume_nav_id_falls_back_to_first_block() {
+        let mut all = selection_fixture();
+        assert_eq!(resume_nav_id(&all, None, "b").as_deref(), Some("b"));
+        // Gone (or swapped from a tmp id): the first block, never the ROOT container.
+        assert_eq!(resume_nav_id(&all, None, "tmp-1").as_deref(), Some("a"));
+        // Outside the zoomed subtree.
+        assert_eq!(resume_nav_id(&all, Some("b"), "a1").as_deref(), Some("b"));
+        // Hidden under a collapsed parent.
+        all[1].is_display = false;
+        assert_eq!(resume_nav_id(&all, None, "a1").as_deref(), Some("a"));
+        assert_eq!(resume_nav_id(&all[..1], None, "a"), None);
+    }
+
+    #[test]
+    fn test_selection_range_and_roots() {
+        let visible: Vec<String> = ["a", "a1", "b", "c"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(selection_range(&visible, "b", "a"), vec!["a", "a1", "b"]);
+        assert_eq!(selection_range(&visible, "c", "c"), vec!["c"]);
+        assert!(selection_range(&visible, "x", "c").is_empty());
+
+        let all = selection_fixture();
+        let selected = selection_range(&visible, "a", "b");
+        assert_eq!(selection_roots(&all, &selected), vec!["a", "b"]);
+        assert_eq!(
+            collect_subtree_ids(&all, &["a".to_string()]),
+            vec!["a", "a1"]
+        );
+    }
+
+    #[test]
+    fn test_indent_outdent_moves_keep_relative_order() {
+        let all = selection_fixture();
+        let roots = vec!["b".to_string(), "c".to_string()];
+
+        // Indent: b, c become the last children of a, after a1.
+        let moves = compute_indent_moves(&all, &roots);
+        assert_eq!(moves.len(), 2);
+        assert_eq!((moves[0].0.as_str(), moves[0].1.as_str()), ("b", "a"));
+        assert_eq!((moves[1].0.as_str(), moves[1].1.as_str()), ("c", "a"));
+        assert!(moves[0].2 > 1.0 && moves[1].2 > moves[0].2);
+
+        // First sibling has nothing to indent under.
+        assert!(compute_indent_moves(&all, &["a".to_string()]).is_empty());
+
+        // Outdent: top-level blocks stay; nested ones go right after their parent.
+        assert!(compute_outdent_moves(&all, &roots, "root").is_empty());
+        let moves = compute_outdent_moves(&all, &["a1".to_string()], "root");
+        assert_eq!(moves.len(), 1);
+        assert_eq!(moves[0].1, "root");
+        assert!(moves[0].2 > 1.0 && moves[0].2 < 2.0);
+    }
+
+    #[test]
+    fn test_plan_merge_into_previous_promotes_children() {
+        let mut all = selection_fixture();
+        let mut b1 = all[2].clone();
+        b1.id = "b1".to_string();
+        b1.parid = "b".to_string();
+        b1.content = "B1".to_string();
+        all.push(b1);
+        let visible = visible_preorder_within(&all, None);
+
+        // b merges into a1 (the previous visible block), caret at the junction; its child b1
+        // takes its place under root, before c.
+        let plan = plan_merge_into_previous(&all, &visible, "b", "B").expect("merge plan");
+        assert_eq!(plan.target_id, "a1");
+        assert_eq!(plan.content, "A1B");
+        assert_eq!(plan.caret, 2);
+        assert_eq!(plan.child_moves.len(), 1);
+        assert_eq!(
+            (
+                plan.child_moves[0].0.as_str(),
+                plan.child_moves[0].1.as_str()
+            ),
+            ("b1", "root")
+        );
+        assert!(plan.child_moves[0].2 >= 2.0 && plan.child_moves[0].2 < 3.0);
+
+        // The first block never merges into the ROOT container.
+        assert!(plan_merge_into_previous(&all, &visible, "a", "A").is_none());
+
+        // Nothing lands inside a collapsed block with children.
+        all[1].is_display = false;
+        let visible = visible_preorder_within(&all, None);
+        assert!(plan_merge_into_previous(&all, &visible, "b", "B").is_none());
+    }
+
+    #[test]
+    fn test_compute_sibling_move_swaps_with_live_neighbor() {
+        let mut all = selection_fixture();
+        assert_eq!(compute_sibling_move(&all, "b", true), Some(0.0));
+        assert_eq!(compute_sibling_move(&all, "b", false), Some(3.5));
+        // First/last sibling (a1 is an only child).
+        assert_eq!(compute_sibling_move(&all, "a", true), None);
+        assert_eq!(compute_sibling_move(&all, "d", false), None);
+        assert_eq!(compute_sibling_move(&all, "a1", false), None);
+
+        // Deleted siblings are skipped.
+        all[4].is_delete = true;
+        assert_eq!(compute_sibling_move(&all, "b", false), Some(5.0));
+    }
+
+    #[test]
+    fn test_rebalance_before_crowded_reorder_and_outdent() {
+        let mut all = selection_fixture();
+        all[4].same_deep_order = 2.0005; // c, right behind b
+
+        // Room enough: nothing to renumber.
+        assert!(rebalance_for_reorder(&all, "d", "b", false).is_empty());
+        assert!(rebalance_for_sibling_move(&all, "b", true).is_empty());
+
+        // Dropping d between b and c renumbers the siblings (d itself takes no slot).
+        let renumbered = rebalance_for_reorder(&all, "d", "c", false);
+        assert_eq!(renumbered, vec![("c".to_string(), 3.0)]);
+        assert_eq!(rebalance_for_sibling_move(&all, "d", true), renumbered);
+        for (id, order) in &renumbered {
+            all.iter_mut()
+                .find(|n| &n.id == id)
+                .unwrap()
+                .same_deep_order = *order;
+        }
+        assert_eq!(
+            compute_reorder_target(&all, "d", "c", false),
+            Some(("root".to_string(), 2.5))
+        );
+
+        // Outdenting a1 lands between a and a crowded b.
+        all[3].same_deep_order = 1.0004; // b
+        assert_eq!(
+            rebalance_for_outdent(&all, &["a1".to_string()], "root-parent"),
+            vec![("b".to_string(), 2.0)]
+        );
+        assert!(rebalance_for_outdent(&all, &["a1".to_string()], "a").is_empty());
+    }
+
+    /// root > [a > [a1 > [a1x], a2], b, c > [c1]], listed out of order.
+    fn outline_fixture() -> Vec<Nav> {
+        vec![
+            nav("c1", "c", 0.0),
+            nav("b", "root", 2.0),
+            nav("a2", "a", 2.0),
+            nav("a1x", "a1", 0.0),
+            nav("root", crate::util::ROOT_CONTAINER_PARENT_ID, 0.0),
+            nav("c", "root", 3.0),
+            nav("a1", "a", 1.0),
+            nav("a", "root", 1.0),
+        ]
+    }
+
+    fn nav_ids(xs: &[&Nav]) -> Vec<String> {
+        xs.iter().map(|n| n.id.clone()).collect()
+    }
+
+    fn id_of(n: Option<&Nav>) -> Option<&str> {
+        n.map(|n| n.id.as_str())
+    }
+
+    #[test]
+    fn test_visible_preorder_deep_tree() {
+        let all = outline_fixture();
+        let tree = NavTree::new(&all);
+        assert_eq!(
+            tree.visible_preorder(),
+            vec!["root", "a", "a1", "a1x", "a2", "b", "c", "c1"]
+        );
+        assert_eq!(tree.visible_preorder_within(Some("a1")), vec!["a1", "a1x"]);
+        assert_eq!(tree.visible_preorder_within(Some("missing")).len(), 8);
+    }
+
+    #[test]
+    fn test_collapsed_and_deleted_branches_are_hidden() {
+        let mut all = outline_fixture();
+        for n in all.iter_mut() {
+            n.is_display = n.id != "a1";
+            n.is_delete = n.id == "c";
+        }
+        let tree = NavTree::new(&all);
+        assert_eq!(tree.visible_preorder(), vec!["root", "a", "a1", "a2", "b"]);
+
+        // A collapsed zoom root shows only itself; a deleted one falls back to everything.
+        assert_eq!(tree.visible_preorder_within(Some("a1")), vec!["a1"]);
+        assert_eq!(tree.visible_preorder_within(Some("c")).len(), 5);
+
+        assert_eq!(id_of(tree.last_visible_descendant("a")), Some("a2"));
+        assert_eq!(id_of(tree.last_visible_descendant("a1")), Some("a1"));
+        assert_eq!(id_of(tree.next_visible("a1")), Some("a2"));
+        assert_eq!(id_of(tree.next_visible("b")), None);
+        assert_eq!(id_of(tree.previous_visible("b")), Some("a2"));
+        assert_eq!(id_of(tree.previous_visible("c1")), None);
+    }
+
+    #[test]
+    fn test_previous_and_next_visible_match_preorder() {
+        let mut all = outline_fixture();
+        all[1].is_display = false; // `b` is a collapsed leaf: no effect.
+        for n in all.iter_mut().filter(|n| n.id == "c") {
+            n.is_display = false;
+        }
+        let tree = NavTree::new(&all);
+        let visible = tree.visible_preorder();
+        for (i, cur) in visible.iter().enumerate() {
+            let prev = i.checked_sub(1).map(|j| visible[j].as_str());
+            let next = visible.get(i + 1).map(String::as_str);
+            assert_eq!(id_of(tree.previous_visible(cur)), prev, "before {cur}");
+            assert_eq!(id_of(tree.next_visible(cur)), next, "after {cur}");
+        }
+        assert!(tree.next_visible("missing").is_none());
+    }
+
+    #[test]
+    fn test_siblings_and_subtree() {
+        let mut all = outline_fixture();
+        all.push(Nav {
+            is_delete: true,
+            ..nav("a1y", "a1", 1.0)
+        });
+        let tree = NavTree::new(&all);
+
+        assert_eq!(nav_ids(&tree.siblings("b")), vec!["a", "b", "c"]);
+        assert_eq!(id_of(tree.previous_sibling("a")), None);
+        assert_eq!(id_of(tree.previous_sibling("b")), Some("a"));
+        assert_eq!(id_of(tree.next_sibling("b")), Some("c"));
+        assert_eq!(id_of(tree.next_sibling("c")), None);
+        assert!(tree.siblings("missing").is_empty());
+
+        // Deleted blocks are skipped by the live queries but kept in subtrees.
+        assert_eq!(nav_ids(&tree.children("a1")), vec!["a1x"]);
+        assert_eq!(nav_ids(tree.all_children("a1")), vec!["a1x", "a1y"]);
+        assert_eq!(tree.subtree_ids("a"), vec!["a", "a1", "a1x", "a1y", "a2"]);
+        assert_eq!(tree.subtree_ids("b"), vec!["b"]);
+    }
+
+    #[test]
+    fn test_equal_and_nan_orders() {
+        let all = vec![
+            nav("root", crate::util::ROOT_CONTAINER_PARENT_ID, 0.0),
+            nav("nan", "root", f32::NAN),
+            nav("x", "root", 1.0),
+            nav("y", "root", 1.0),
+            nav("first", "root", -1.0),
+        ];
+        let tree = NavTree::new(&all);
+        // Ties keep list order; NaN sorts last.
+        assert_eq!(
+            nav_ids(&tree.children("root")),
+            vec!["first", "x", "y", "nan"]
+        );
+
+        assert_eq!(cmp_order(f32::NAN, f32::NAN), std::cmp::Ordering::Equal);
+        assert_eq!(
+            cmp_order(f32::NAN, f32::INFINITY),
+            std::cmp::Ordering::Greater
+        );
+        assert_eq!(cmp_order(-0.0, 0.0), std::cmp::Ordering::Equal);
+    }
+
+    #[test]
+    fn test_insert_order_between() {
+        assert_eq!(insert_order_between(Some(1.0), Some(2.0)), 1.5);
+        assert_eq!(insert_order_between(Some(1.0), None), 2.0);
+        assert_eq!(insert_order_between(None, Some(1.0)), 0.0);
+        assert_eq!(insert_order_between(None, None), 0.0);
+        // Equal neighbors leave no room: the block ties with them.
+        assert_eq!(insert_order_between(Some(3.0), Some(3.0)), 3.0);
+        // Corrupt neighbors are ignored.
+        assert_eq!(insert_order_between(Some(f32::NAN), Some(4.0)), 3.0);
+        assert_eq!(insert_order_between(Some(2.0), Some(f32::INFINITY)), 3.0);
+        assert_eq!(insert_order_between(Some(f32::NAN), None), 0.0);
+    }
+
+    #[test]
+    fn test_orders_crowded() {
+        assert!(!orders_crowded(Some(1.0), Some(2.0), 1));
+        assert!(!orders_crowded(Some(1.0), None, 5));
+        assert!(!orders_crowded(None, None, 1));
+        assert!(orders_crowded(Some(1.0), Some(1.0), 1));
+        assert!(orders_crowded(Some(1.0), Some(1.0005), 1));
+        assert!(orders_crowded(Some(1.0), Some(1.002), 3));
+        assert!(orders_crowded(Some(f32::NAN), Some(2.0), 1));
+        assert!(orders_crowded(None, Some(f32::INFINITY), 1));
+    }
+
+    #[test]
+    fn test_rebalance_renumbers_crowded_siblings_only() {
+        let mut all = vec![
+            nav("root", crate::util::ROOT_CONTAINER_PARENT_ID, 0.0),
+            nav("a", "root", 0.5),
+            nav("b", "root", 0.5002),
+            nav("c", "root", 7.0),
+            nav("x", "c", 0.0),
+        ];
+        all[2].is_delete = true;
+
+        // Room after c; none between a and the (deleted) b.
+        assert!(rebalance_for_insert(&all, "root", Some("c"), 1, None).is_empty());
+        assert!(rebalance_for_insert(&all, "root", Some("missing"), 1, None).is_empty());
+        let renumbered = rebalance_for_insert(&all, "root", Some("a"), 1, None);
+        assert_eq!(
+            renumbered,
+            vec![
+                ("a".to_string(), 1.0),
+                ("b".to_string(), 2.0),
+                ("c".to_string(), 3.0)
+            ]
+        );
+
+        // A block being moved takes no slot: without b in the way, a..c has room.
+        assert!(rebalance_for_insert(&all, "root", Some("a"), 1, Some("b")).is_empty());
+        assert_eq!(
+            renumber_children(&all, "root", Some("b")),
+            vec![("a".to_string(), 1.0), ("c".to_string(), 2.0)]
+        );
+
+        apply_orders(&mut all, &renumbered);
+        let tree = NavTree::new(&all);
+        assert_eq!(nav_ids(tree.all_children("root")), vec!["a", "b", "c"]);
+        assert_eq!(tree.get("x").map(|n| n.same_deep_order), Some(0.0));
+    }
+
+    #[test]
+    fn test_repeated_inserts_at_same_position_stay_ordered() {
+        let mut all = vec![nav("a", "p", 1.0), nav("b", "p", 2.0)];
+        let mut expected = vec!["a".to_string(), "b".to_string()];
+        let mut rebalances = 0;
+
+        // Enter on `a` 100 times: each new block goes right after `a`, before the last one.
+        for i in 0..100 {
+            let renumbered = rebalance_for_insert(&all, "p", Some("a"), 1, None);
+            if !renumbered.is_empty() {
+                rebalances += 1;
+            }
+            apply_orders(&mut all, &renumbered);
+
+            let tree = NavTree::new(&all);
+            let prev = tree.get("a").map(|n| n.same_deep_order);
+            let next = tree.next_sibling("a").map(|n| n.same_deep_order);
+            let order = insert_order_between(prev, next);
+
+            let id = format!("n{i}");
+            all.push(nav(&id, "p", order));
+            expected.insert(1, id);
+
+            let tree = NavTree::new(&all);
+            let sibs = tree.all_children("p");
+            assert_eq!(nav_ids(sibs), expected);
+            assert!(sibs
+                .windows(2)
+                .all(|w| w[0].same_deep_order < w[1].same_deep_order));
+        }
+        assert!(rebalances > 0);
+    }
+
+    #[test]
+    fn test_parent_cycle_does_not_hang() {
+        let mut all = vec![nav("p", "q", 0.0), nav("q", "p", 0.0)];
+        for n in all.iter_mut() {
+            n.is_display = false;
+        }
+        let tree = NavTree::new(&all);
+        assert_eq!(tree.subtree_ids("p"), vec!["p", "q"]);
+        assert!(tree.last_visible_descendant("p").is_some());
+        assert!(tree.next_visible("q").is_none());
+        assert!(tree.visible_preorder().is_empty());
+    }
+
+    #[test]
+    fn test_selection_plain_text_indents_children() {
+        let all = selection_fixture();
+        let text = selection_plain_text(&all, &["a".to_string(), "b".to_string()]);
+        assert_eq!(text, "A\n  A1\nB");
+    }
+
+    #[test]
+    fn test_plan_multiline_paste_splits_lines_with_nesting() {
+        // Single-line pastes keep the default behavior.
+        assert!(plan_multiline_paste("abc", 1, 1, "xyz").is_none());
+        assert!(plan_multiline_paste("abc", 1, 1, "xyz\n\n").is_none());
+
+        let plan = plan_multiline_paste("ab", 1, 1, "one\r\ntwo\n  child\n\tgrand\nthree\n")
+            .expect("multi-line paste");
+        assert_eq!(plan.current_content, "aoneb");
+        assert_eq!(plan.caret_utf16, 4);
+        let got = plan
+            .blocks
+            .iter()
+            .map(|b| (b.content.as_str(), b.depth))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            got,
+            vec![("two", 0), ("child", 1), ("grand", 1), ("three", 0)]
+        );
+
+        // Selection is replaced; depth never jumps more than one level.
+        let plan = plan_multiline_paste("hello", 0, 5, "x\n      deep").unwrap();
+        assert_eq!(plan.current_content, "x");
+        assert_eq!(plan.blocks[0].depth, 1);
+    }
+
+    #[test]
+    fn test_layout_pasted_blocks_orders_between_siblings() {
+        let all = selection_fixture();
+        let blocks = vec![
+            PastedBlock {
+                content: "x".to_string(),
+                depth: 0,
+            },
+            PastedBlock {
+                content: "x1".to_string(),
+                depth: 1,
+            },
+            PastedBlock {
+                content: "y".to_string(),
+                depth: 0,
+            },
+        ];
+        let mut n = 0;
+        let created = layout_pasted_blocks(&all, "b", &blocks, || {
+            n += 1;
+            format!("tmp-{n}")
+        });
+
+        assert_eq!(created.len(), 3);
+        assert_eq!(
+            (created[0].id.as_str(), created[0].parid.as_str()),
+            ("tmp-1", "root")
+        );
+        assert_eq!(
+            (created[1].id.as_str(), created[1].parid.as_str()),
+            ("tmp-2", "tmp-1")
+        );
+        assert_eq!(
+            (created[2].id.as_str(), created[2].parid.as_str()),
+            ("tmp-3", "root")
+        );
+        // Siblings land between `b` (2.0) and `c` (3.0), in paste order.
+        assert!(created[0].same_deep_order > 2.0);
+        assert!(created[2].same_deep_order > created[0].same_deep_order);
+        assert!(created[2].same_deep_order < 3.0);
+    }
+
+    #[test]
+    fn test_zoom_breadcrumb_skips_root_container() {
+        let all = selection_fixture();
+        let crumbs = zoom_breadcrumb(&all, "a1");
+        let ids = crumbs.iter().map(|(id, _)| id.as_str()).collect::<Vec<_>>();
+        assert_eq!(ids, vec!["a", "a1"]);
+        assert_eq!(crumbs[0].1, "A");
+
+        assert_eq!(zoom_breadcrumb(&all, "b").len(), 1);
+        assert!(zoom_breadcrumb(&all, "missing").is_empty());
+    }
+
+    #[test]
+    fn test_parse_all_navs_response_shapes() {
+        let item = serde_json::json!({
+            "id": "n1",
+            "note-id": "note1",
+            "parid": "p",
+            "same-deep-order": 1.0,
+            "content": "x",
+            "is-display": true,
+            "is-delete": false
+        });
+
+        let canonical = serde_json::json!({ "nav-list": [item.clone()] });
+        let navs = ApiClient::parse_all_navs_response(canonical);
+        assert_eq!(navs.len(), 1);
+        assert_eq!(navs[0].note_id, "note1");
+
+        let alt = serde_json::json!({ "navs": [item.clone()] });
+        assert_eq!(ApiClient::parse_all_navs_response(alt).len(), 1);
+
+        let bare = serde_json::json!([item]);
+        assert_eq!(ApiClient::parse_all_navs_response(bare).len(), 1);
+
+        assert!(ApiClient::parse_all_navs_response(serde_json::json!({})).is_empty());
+    }
+
+    #[test]
+    fn test_collapse_state_diff_and_collapse_all() {
+        let mut all = selection_fixture();
+        // Only blocks with children count; the ROOT container never collapses.
+        assert!(collapsed_block_ids(&all).is_empty());
+        assert_eq!(
+            display_state_diff(&all, &["a".to_string(), "b".to_string()]),
+            vec![("a".to_string(), false)]
+        );
+
+        set_all_collapsed(&mut all, true);
+        assert_eq!(collapsed_block_ids(&all), vec!["a"]);
+        assert!(all[0].is_display);
+
+        set_all_collapsed(&mut all, false);
+        assert!(collapsed_block_ids(&all).is_empty());
+    }
+
+    #[test]
+    fn test_upsert_collapse_entry_prunes_stale_notes() {
+        let now = 100 * COLLAPSE_STATE_TTL_MS;
+        let entries = vec![
+            CollapsedBlocks {
+                note_id: "old".to_string(),
+                collapsed: vec![],
+                touched_ms: now - COLLAPSE_STATE_TTL_MS - 1,
+            },
+            CollapsedBlocks {
+                note_id: "n1".to_string(),
+                collapsed: vec!["x".to_string()],
+                touched_ms: now - 10,
+            },
+            CollapsedBlocks {
+                note_id: "n2".to_string(),
+                collapsed: vec![],
+                touched_ms: now - 20,
+            },
+        ];
+
+        let next = upsert_collapse_entry(entries, "n1", vec!["y".to_string()], now);
+        let ids = next.iter().map(|e| e.note_id.as_str()).collect::<Vec<_>>();
+        assert_eq!(ids, vec!["n1", "n2"]);
+        assert_eq!(next[0].collapsed, vec!["y"]);
+        assert_eq!(next[0].touched_ms, now);
+    }
+
+    #[test]
+    fn test_zoom_href() {
+        assert_eq!(
+            zoom_href("/db/d1/note/n1", Some("nav 1")),
+            "/db/d1/note/n1?block=nav%201"
+        );
+        assert_eq!(zoom_href("/db/d1/note/n1", None), "/db/d1/note/n1");
+        assert_eq!(zoom_href("/db/d1/note/n1", Some("  ")), "/db/d1/note/n1");
+    }
+
+    #[test]
+    fn test_visible_preorder_within_zoom_root() {
+        let mut all = selection_fixture();
+        assert_eq!(
+            visible_preorder_within(&all, None),
+            vec!["root", "a", "a1", "b", "c", "d"]
+        );
+        assert_eq!(visible_preorder_within(&all, Some("a")), vec!["a", "a1"]);
+        assert_eq!(visible_preorder_within(&all, Some("b")), vec!["b"]);
+        // Unknown zoom root falls back to the whole outline.
+        assert_eq!(visible_preorder_within(&all, Some("missing")).len(), 6);
+
+        all[1].is_display = false;
+        assert_eq!(visible_preorder_within(&all, Some("a")), vec!["a"]);
+    }
+
+    #[test]
+    fn test_navs_to_markdown_nested_list() {
+        let mut all = selection_fixture();
+        // Collapsed nodes are still exported; deleted ones are not.
+        all[1].is_display = false;
+        all[3].content = "B\nmore".to_string();
+        all[5].is_delete = true;
+
+        assert_eq!(navs_to_markdown(&all), "- A\n  - A1\n- B\n  more\n- C\n");
+        assert_eq!(navs_to_markdown(&[]), "");
+    }
+
+    #[test]
+    fn test_markdown_export_filename() {
+        assert_eq!(markdown_export_filename("20240101"), "20240101.md");
+        assert_eq!(markdown_export_filename("a/b: c?"), "a-b- c-.md");
+        assert_eq!(markdown_export_filename("   "), "Untitled.md");
+    }
+
+    #[test]
+    fn test_note_list_pagination_request_and_merge() {
+        let req = GetNoteListRequest {
+            database_id: "db1".to_string(),
+            page: 2,
+            page_size: 50,
+        };
+        let v = serde_json::to_value(req).expect("should serialize");
+        assert_eq!(v["database-id"], "db1");
+        assert_eq!(v["page"], 2);
+        assert_eq!(v["size"], 50);
+
+        let mut notes = vec![note("a", "db1", "a"), note("b", "db1", "b")];
+        merge_notes_page(
+            &mut notes,
+            vec![note("b", "db1", "b"), note("c", "db1", "c")],
+        );
+        let ids = notes.iter().map(|n| n.id.as_str()).collect::<Vec<_>>();
+        assert_eq!(ids, vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn test_find_backlinks_groups_by_note_case_insensitive() {
+        let navs = vec![
+            Nav {
+                note_id: "n2".to_string(),
+                content: "see [[project x]]".to_string(),
+                ..nav("1", "p", 1.0)
+            },
+            Nav {
+                note_id: "n1".to_string(),
+                content: "about [[Project X]] and [[Other]]".to_string(),
+                ..nav("2", "p", 1.0)
+            },
+            Nav {
+                note_id: "n2".to_string(),
+                content: "again [[Project X]]".to_string(),
+                ..nav("3", "p", 1.0)
+            },
+            Nav {
+                note_id: "n1".to_string(),
+                content: "[[Project X]] (deleted)".to_string(),
+                is_delete: true,
+                ..nav("4", "p", 1.0)
+            },
+            Nav {
+                note_id: "n3".to_string(),
+                content: "no links here".to_string(),
+                ..nav("5", "p", 1.0)
+            },
+            Nav {
+                note_id: "n4".to_string(),
+                content: "[[Project X]] from an unloaded note".to_string(),
+                ..nav("6", "p", 1.0)
+            },
+        ];
+        let notes = vec![note("n1", "db1", "Alpha"), note("n2", "db1", "Beta")];
+
+        let got = find_backlinks(&navs, &notes, "Project X");
+        let summary = got
+            .iter()
+            .map(|(note, xs)| {
+                (
+                    note.title.as_str(),
+                    xs.iter().map(|n| n.id.as_str()).collect::<Vec<_>>(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![
+                ("Alpha", vec!["2"]),
+                ("Beta", vec!["1", "3"]),
+                ("n4", vec!["6"])
+            ]
+        );
+
+        assert!(find_backlinks(&navs, &notes, "  ").is_empty());
+    }
+
+    #[test]
+    fn test_settings_defaults_and_overrides() {
+        let s: Settings = serde_json::from_str("{}").unwrap();
+        assert_eq!(s, Settings::default());
+        assert_eq!(s.autosave_ms, 1200);
+        assert!(!s.enter_creates_child);
+        assert_eq!(s.api_url_override(), None);
+
+        let s: Settings =
+            serde_json::from_str(r#"{"api_url":" https://api.example.com/ ","autosave_ms":5}"#)
+                .unwrap();
+        assert_eq!(
+            s.api_url_override().as_deref(),
+            Some("https://api.example.com")
+        );
+        assert_eq!(s.autosave_delay_ms(), Settings::MIN_AUTOSAVE_MS as i32);
+        assert_eq!(s.new_note_title, NoteTitleFormat::Compact);
+
+        let blank = Settings {
+            api_url: Some("   ".to_string()),
+            ..Settings::default()
+        };
+        assert_eq!(blank.api_url_override(), None);
+
+        assert_eq!(
+            format_new_note_title(NoteTitleFormat::Compact, 2024, 1, 5),
+            "20240105"
+        );
+        assert_eq!(
+            format_new_note_title(NoteTitleFormat::IsoDate, 2024, 1, 5),
+            "2024-01-05"
+        );
+        assert_eq!(
+            format_new_note_title(NoteTitleFormat::Untitled, 2024, 1, 5),
+            "Untitled"
+        );
+    }
+
+    #[test]
+    fn test_format_edited_ago_boundaries() {
+        const SEC: i64 = 1000;
+        const MIN: i64 = 60 * SEC;
+        const HOUR: i64 = 60 * MIN;
+        const DAY: i64 = 24 * HOUR;
+        let now = 10 * DAY;
+
+        assert_eq!(format_edited_ago(now + 5 * SEC, now), "edited just now");
+        assert_eq!(format_edited_ago(now - 59 * SEC, now), "edited just now");
+        assert_eq!(format_edited_ago(now - MIN, now), "edited 1m ago");
+        assert_eq!(
+            format_edited_ago(now - 59 * MIN - 59 * SEC, now),
+            "edited 59m ago"
+        );
+        assert_eq!(format_edited_ago(now - HOUR, now), "edited 1h ago");
+        assert_eq!(format_edited_ago(now - DAY + SEC, now), "edited 23h ago");
+        assert_eq!(format_edited_ago(now - DAY, now), "edited 1d ago");
+        assert_eq!(format_edited_ago(now - 9 * DAY, now), "edited 9d ago");
+    }
+
+    #[test]
+    fn test_parse_nav_list_response_direct_and_namespaced() {
+        let direct = serde_json::json!({
+            "id": "a", "note-id": "n", "parid": "p", "same-deep-order": 2.5,
+            "content": "x", "is-display": false, "is-delete": false
+        });
+        let namespaced = serde_json::json!({
+            "hulunote-navs/id": "b", "hulunote-navs/note-id": "n", "hulunote-navs/parid": "a",
+            "hulunote-navs/same-deep-order": 3, "hulunote-navs/content": "y",
+            "hulunote-navs/is-display": false, "hulunote-navs/is-delete": true,
+            "hulunote-navs/properties": "{\"heading\":2}"
+        });
+        // Neither shape: no id, so it is dropped rather than becoming a blank block.
+        let junk = serde_json::json!({ "hulunote-navs/content": "z" });
+        let navs = ApiClient::parse_nav_list_response(
+            serde_json::json!({ "nav-list": [direct, namespaced, junk] }),
+        );
+        assert_eq!(navs.len(), 2);
+
+        assert_eq!(navs[0].id, "a");
+        assert_eq!(navs[0].same_deep_order, 2.5);
+        assert!(!navs[0].is_display);
+
+        let b = &navs[1];
+        assert_eq!(
+            (b.id.as_str(), b.note_id.as_str(), b.parid.as_str()),
+            ("b", "n", "a")
+        );
+        assert_eq!(b.same_deep_order, 3.0);
+        assert_eq!(b.content, "y");
+        assert!(!b.is_display);
+        assert!(b.is_delete);
+        assert_eq!(b.properties.as_deref(), Some("{\"heading\":2}"));
+    }
+
+    #[test]
+    fn test_parse_upsert_nav_response_shapes() {
+        let direct = serde_json::json!({
+            "id": "a", "note-id": "n", "parid": "p", "same-deep-order": 2.0,
+            "content": "x", "is-display": true, "is-delete": false
+        });
+        let nav = ApiClient::parse_upsert_nav_response(direct).expect("direct nav");
+        assert_eq!((nav.id.as_str(), nav.parid.as_str()), ("a", "p"));
+
+        let wrapped = serde_json::json!({ "nav": { "id": "b", "parid": "p" }, "ok": true });
+        let nav = ApiClient::parse_upsert_nav_response(wrapped).expect("wrapped nav");
+        assert_eq!(nav.id, "b");
+
+        let namespaced = serde_json::json!({
+            "hulunote-navs/id": "c", "hulunote-navs/note-id": "n",
+            "hulunote-navs/same-deep-order": 4
+        });
+        let nav = ApiClient::parse_upsert_nav_response(namespaced).expect("namespaced nav");
+        assert_eq!((nav.id.as_str(), nav.note_id.as_str()), ("c", "n"));
+        assert_eq!(nav.same_deep_order, 4.0);
+
+        // No id anywhere: the caller has to recover some other way.
+        for garbage in [
+            serde_json::json!({ "ok": true }),
+            serde_json::json!({ "nav": "created", "id": "" }),
+            serde_json::json!("created"),
+            serde_json::Value::Null,
+        ] {
+            assert!(ApiClient::parse_upsert_nav_response(garbage).is_none());
+        }
+    }
+
+    #[test]
+    fn test_created_nav_id_needs_a_single_unknown_match() {
+        let mut all = selection_fixture();
+        let known = all.iter().map(|n| n.id.clone()).collect::<Vec<_>>();
+        all.push(Nav {
+            id: "new".to_string(),
+            same_deep_order: 1.5,
+            ..all[1].clone()
+        });
+        assert_eq!(
+            created_nav_id(&all, &known, "root", 1.5),
+            Some("new".to_string())
+        );
+        // Known navs at the same spot don't count; nothing new there.
+        assert_eq!(created_nav_id(&all, &known, "root", 1.0), None);
+
+        // Two candidates: ambiguous.
+        all.push(Nav {
+            id: "other".to_string(),
+            ..all[6].clone()
+        });
+        assert_eq!(created_nav_id(&all, &known, "root", 1.5), None);
+    }
+
+    #[test]
+    fn test_nav_timestamps_are_optional() {
+        let with = serde_json::json!({
+            "id": "a", "note-id": "n", "parid": "p", "same-deep-order": 1.0,
+            "content": "x", "is-display": true, "is-delete": false,
+            "created-at": "2026-01-01T00:00:00Z", "updated-at": "2026-01-02T00:00:00Z"
+        });
+        let navs = ApiClient::parse_nav_list_response(serde_json::json!({ "nav-list": [with] }));
+        assert_eq!(navs[0].updated_at.as_deref(), Some("2026-01-02T00:00:00Z"));
+        assert_eq!(navs[0].created_at.as_deref(), Some("2026-01-01T00:00:00Z"));
+
+        let without = serde_json::json!({
+            "hulunote-navs/id": "b", "hulunote-navs/note-id": "n", "hulunote-navs/parid": "p",
+            "hulunote-navs/content": "y"
+        });
+        let navs = ApiClient::parse_nav_list_response(serde_json::json!({ "nav-list": [without] }));
+        assert_eq!(navs[0].id, "b");
+        assert!(navs[0].updated_at.is_none());
+        // Outgoing payloads don't grow new keys.
+        let out = serde_json::to_value(&navs[0]).unwrap();
+        assert!(out.get("updated-at").is_none());
+    }
+
+    #[test]
+    fn test_relative_time_label_buckets() {
+        const MIN: i64 = 60 * 1000;
+        assert_eq!(relative_time_label(-5 * MIN).as_deref(), Some("just now"));
+        assert_eq!(relative_time_label(30 * 1000).as_deref(), Some("just now"));
+        assert_eq!(relative_time_label(5 * MIN).as_deref(), Some("5 min ago"));
+        assert_eq!(relative_time_label(60 * MIN).as_deref(), Some("1 hour ago"));
+        assert_eq!(
+            relative_time_label(3 * 60 * MIN + 59 * MIN).as_deref(),
+            Some("3 hours ago")
+        );
+        assert_eq!(
+            relative_time_label(30 * 60 * MIN).as_deref(),
+            Some("yesterday")
+        );
+        assert_eq!(relative_time_label(48 * 60 * MIN), None);
+
+        assert_eq!(format_month_day(0, 3), "Jan 3");
+        assert_eq!(format_month_day(11, 31), "Dec 31");
+    }
+
+    #[test]
+    fn test_nav_copy_order_parents_first_and_note_id_shapes() {
+        let mut all = selection_fixture();
+        all.reverse();
+        all[0].is_delete = true; // "d"
+        all.push(Nav {
+            id: "orphan".to_string(),
+            parid: "missing".to_string(),
+            ..all[1].clone()
+        });
+
+        let order = nav_copy_order(&all)
+            .into_iter()
+            .map(|n| n.id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(order, vec!["root", "a", "a1", "b", "c"]);
+
+        assert_eq!(duplicate_note_title(" Plan "), "Plan (copy)");
+        assert_eq!(
+            copy_incomplete_message(40, 120),
+            "Copy incomplete, 40 of 120 blocks copied"
+        );
+        assert_eq!(
+            ApiClient::note_id_from_response(
+                &serde_json::json!({"note": {"hulunote-notes/id": "n1"}})
+            ),
+            "n1"
+        );
+        assert_eq!(
+            ApiClient::note_id_from_response(&serde_json::json!({"id": "n2"})),
+            "n2"
+        );
+        assert_eq!(ApiClient::note_id_from_response(&serde_json::json!({})), "");
+    }
+
+    #[test]
+    fn test_wiki_link_href_existing_and_missing_notes() {
+        let notes = vec![note("n1", "db1", "Project X"), note("n2", "db2", "Other")];
+
+        assert_eq!(
+            wiki_link_href("Project X", "db1", &notes),
+            "/db/db1/note/n1"
+        );
+        // Same title in another database does not count.
+        assert_eq!(
+            wiki_link_href("Other", "db1", &notes),
+            "/db/db1/note?title=Other"
+        );
+        assert_eq!(
+            wiki_link_href("New page", "db1", &notes),
+            "/db/db1/note?title=New%20page"
+        );
+    }
+
+    #[test]
+    fn test_parse_wiki_tokens_block_refs() {
+        let text = |s: &str| WikiToken::Text(s.to_string());
+        assert_eq!(
+            parse_wiki_tokens("see ((abc-1)) and [[Page]]"),
+            vec![
+                text("see "),
+                WikiToken::BlockRef("abc-1".to_string()),
+                text(" and "),
+                WikiToken::Link("Page".to_string()),
+            ]
+        );
+        // Not ids: empty, whitespace, unclosed.
+        assert_eq!(
+            parse_wiki_tokens("(()) ((a b))"),
+            vec![text("(()) ((a b))")]
+        );
+        assert_eq!(parse_wiki_tokens("((abc"), vec![text("((abc")]);
+        // Extra parens around a valid ref stay text.
+        assert_eq!(
+            parse_wiki_tokens("(((x)))"),
+            vec![text("("), WikiToken::BlockRef("x".to_string()), text(")")]
+        );
+        // Inside a link it's part of the title.
+        assert_eq!(
+            parse_wiki_tokens("[[((x))]]"),
+            vec![WikiToken::Link("((x))".to_string())]
+        );
+        assert_eq!(extract_block_refs("((a)) [[b]] ((c))"), vec!["a", "c"]);
+    }
+
+    #[test]
+    fn test_parse_inline_markdown_nesting() {
+        let text = |s: &str| InlineSpan::Text(s.to_string());
+        assert_eq!(
+            parse_inline_markdown("a **b *c* d** `e*f`"),
+            vec![
+                text("a "),
+                InlineSpan::Bold(vec![
+                    text("b "),
+                    InlineSpan::Italic(vec![text("c")]),
+                    text(" d"),
+                ]),
+                text(" "),
+                InlineSpan::Code("e*f".to_string()),
+            ]
+        );
+        // Links stay atomic so wiki tokenization still sees them.
+        assert_eq!(
+            parse_inline_markdown("**[[a**b]]**"),
+            vec![InlineSpan::Bold(vec![text("[[a**b]]")])]
+        );
+
+        let bold = InlineStyle {
+            bold: true,
+            italic: false,
+        };
+        let both = InlineStyle {
+            bold: true,
+            italic: true,
+        };
+        assert_eq!(
+            flatten_inline(&parse_inline_markdown("***x* y**")),
+            vec![
+                InlineRun::Text("x".to_string(), both),
+                InlineRun::Text(" y".to_string(), bold),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_inline_markdown_unterminated_and_urls() {
+        let text = |s: &str| InlineSpan::Text(s.to_string());
+        assert_eq!(parse_inline_markdown("**open"), vec![text("**open")]);
+        assert_eq!(parse_inline_markdown("*open `x"), vec![text("*open `x")]);
+        assert_eq!(parse_inline_markdown("2 * 3 * 4"), vec![text("2 * 3 * 4")]);
+        assert_eq!(parse_inline_markdown("``"), vec![text("``")]);
+
+        assert_eq!(
+            parse_inline_markdown("see (https://example.com/a?b=1)."),
+            vec![
+                text("see ("),
+                InlineSpan::Url("https://example.com/a?b=1".to_string()),
+                text(")."),
+            ]
+        );
+        assert_eq!(
+            parse_inline_markdown("xhttps://no https://"),
+            vec![text("xhttps://no https://")]
+        );
+    }
+
+    #[test]
+    fn test_parse_inline_markdown_multibyte() {
+        let text = |s: &str| InlineSpan::Text(s.to_string());
+        assert_eq!(
+            parse_inline_markdown("中文*斜体*和**粗体**🙂"),
+            vec![
+                text("中文"),
+                InlineSpan::Italic(vec![text("斜体")]),
+                text("和"),
+                InlineSpan::Bold(vec![text("粗体")]),
+                text("🙂"),
+            ]
+        );
+        assert_eq!(parse_inline_markdown("é*"), vec![text("é*")]);
+    }
+
+    #[test]
+    fn test_search_blocks_filters_and_ranks() {
+        let navs = vec![
+            Nav {
+                content: "Ship the release".to_string(),
+                ..nav("a", "root", 0.0)
+            },
+            Nav {
+                content: "release notes".to_string(),
+                ..nav("b", "root", 0.0)
+            },
+            Nav {
+                content: "release draft".to_string(),
+                is_delete: true,
+                ..nav("c", "root", 0.0)
+            },
+            nav("d", "root", 0.0),
+            Nav {
+                content: "Release me".to_string(),
+                ..nav("e", "root", 0.0)
+            },
+        ];
+
+        let ids = |hits: Vec<&Nav>| hits.iter().map(|n| n.id.clone()).collect::<Vec<_>>();
+        assert_eq!(
+            ids(search_blocks(&navs, "release", "e", 10)),
+            vec!["b", "a"]
+        );
+        assert_eq!(ids(search_blocks(&navs, "", "", 2)), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_sort_notes_orders_and_sort_keys() {
+        let mut notes = vec![
+            Note {
+                created_at: "2024-01-02T00:00:00Z".to_string(),
+                updated_at: "2024-03-01T00:00:00Z".to_string(),
+                ..note("a", "db", "beta")
+            },
+            Note {
+                created_at: "2024-01-03T00:00:00Z".to_string(),
+                updated_at: "2024-02-01T00:00:00Z".to_string(),
+                ..note("b", "db", "Alpha")
+            },
+            Note {
+                created_at: "2024-01-01T00:00:00Z".to_string(),
+                updated_at: "2024-04-01T00:00:00Z".to_string(),
+                ..note("c", "db", "gamma")
+            },
+        ];
+        let ids = |xs: &[Note]| xs.iter().map(|n| n.id.clone()).collect::<Vec<_>>();
+
+        sort_notes(&mut notes, NoteSort::UpdatedAtDesc);
+        assert_eq!(ids(&notes), vec!["c", "a", "b"]);
+        sort_notes(&mut notes, NoteSort::CreatedAtDesc);
+        assert_eq!(ids(&notes), vec!["b", "a", "c"]);
+        sort_notes(&mut notes, NoteSort::TitleAsc);
+        assert_eq!(ids(&notes), vec!["b", "a", "c"]);
+
+        assert_eq!(NoteSort::default(), NoteSort::UpdatedAtDesc);
+        for sort in NoteSort::ALL {
+            assert_eq!(NoteSort::from_key(sort.key()), Some(sort));
+        }
+        assert_eq!(NoteSort::from_key("nope"), None);
+    }
+
+    #[test]
+    fn test_note_filter_bounds_and_updated_filter() {
+        let notes = vec![
+            Note {
+                updated_at: "2024-05-15T08:00:00Z".to_string(),
+                ..note("today", "db", "today")
+            },
+            Note {
+                updated_at: "2024-05-13T23:59:59Z".to_string(),
+                ..note("monday", "db", "monday")
+            },
+            Note {
+                updated_at: "2024-05-01T00:00:00Z".to_string(),
+                ..note("month", "db", "month")
+            },
+            Note {
+                updated_at: "2024-04-30T12:00:00Z".to_string(),
+                ..note("april", "db", "april")
+            },
+            note("unknown", "db", "unknown"),
+        ];
+        let ids = |filter: NoteFilter| {
+            let bounds = note_filter_bounds(&filter, "2024-05-15", "2024-05-13", "2024-05-01");
+            filter_notes_by_updated(&notes, bounds.as_ref())
+                .into_iter()
+                .map(|n| n.id)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(ids(NoteFilter::All).len(), 5);
+        assert_eq!(ids(NoteFilter::Today), vec!["today"]);
+        assert_eq!(ids(NoteFilter::ThisWeek), vec!["today", "monday"]);
+        assert_eq!(ids(NoteFilter::ThisMonth), vec!["today", "monday", "month"]);
+        assert_eq!(
+            ids(NoteFilter::Custom(
+                "2024-04-30".to_string(),
+                "2024-05-13".to_string()
+            )),
+            vec!["monday", "month", "april"]
+        );
+        assert_eq!(
+            ids(NoteFilter::Custom(String::new(), "2024-04-30".to_string())),
+            vec!["april"]
+        );
+
+        for filter in NoteFilter::PRESETS {
+            assert_eq!(NoteFilter::from_key(filter.key()), Some(filter));
+        }
+        assert_eq!(NoteFilter::from_key("custom"), None);
+    }
+
+    #[test]
+    fn test_retarget_recent_notes_rewrites_moved_note_only() {
+        let recent = |db: &str, note: &str, title: &str| RecentNote {
+            db_id: db.to_string(),
+            note_id: note.to_string(),
+            title: title.to_string(),
+            last_opened_ms: 1,
+        };
+        let items = vec![
+            recent("db1", "n1", "Plan"),
+            recent("db1", "n2", "Other"),
+            recent("db2", "n1", "Same id, other db"),
+        ];
+
+        let out = retarget_recent_notes(items, "db1", "n1", "db3", "m9", "Plan-2");
+        assert_eq!(out[0], recent("db3", "m9", "Plan-2"));
+        assert_eq!(out[1], recent("db1", "n2", "Other"));
+        assert_eq!(out[2], recent("db2", "n1", "Same id, other db"));
+    }
+
+    #[test]
+    fn test_prune_recent_notes_not_in_keeps_order() {
+        let recent = |db: &str, note: &str| RecentNote {
+            db_id: db.to_string(),
+            note_id: note.to_string(),
+            title: note.to_string(),
+            last_opened_ms: 1,
+        };
+        let items = vec![
+            recent("db1", "n3"),
+            recent("db1", "gone"),
+            recent("db2", "n1"),
+        ];
+
+        let out =
+            prune_recent_notes_not_in(&[note("n1", "db1", "n1"), note("n3", "db1", "n3")], items);
+        assert_eq!(out, vec![recent("db1", "n3"), recent("db2", "n1")]);
+        assert!(prune_recent_notes_not_in(&[], vec![recent("db1", "n1")]).is_empty());
+    }
+
+    #[test]
+    fn test_offline_error_is_a_network_error() {
+        // NoteSyncController only pauses (and later replays drafts) on network errors.
+        let e = ApiError::offline();
+        assert!(matches!(e, ApiError::Network(_)));
+        assert!(e.is_offline());
+        assert!(!e.is_transient());
+        assert!(e.to_string().starts_with("Offline"));
+    }
+
+    #[test]
+    fn test_timeout_and_cancel_errors() {
+        // A hung backend is worth retrying; a superseded request is not.
+        assert!(ApiError::TimedOut.is_transient());
+        assert!(ApiError::TimedOut
+            .to_string()
+            .starts_with("Request timed out"));
+        assert!(!ApiError::Cancelled.is_transient());
+
+        let handle = AbortHandle::new();
+        let client = ApiClient::new("http://x".into()).with_abort(&handle);
+        assert_eq!(client.timeout_ms, DEFAULT_REQUEST_TIMEOUT_MS);
+        assert!(!handle.is_aborted());
+        handle.abort();
+        handle.abort();
+        assert!(handle.is_aborted());
+    }
+
+    #[test]
+    fn test_filter_databases_by_name() {
+        let db = |id: &str, name: &str| Database {
+            id: id.to_string(),
+            name: name.to_string(),
+            description: String::new(),
+            created_at: String::new(),
+            updated_at: String::new(),
+            is_default: false,
+            is_public: false,
+        };
+        let dbs = vec![
+            db("1", "Work"),
+            db("2", "Reading list"),
+            db("3", "Homework"),
+        ];
+
+        let ids = |q: &str| -> Vec<String> {
+            filter_databases(&dbs, q)
+                .into_iter()
+                .map(|d| d.id)
+                .collect()
+        };
+        assert_eq!(ids("  "), vec!["1", "2", "3"]);
+        assert_eq!(ids("WORK"), vec!["1", "3"]);
+        assert_eq!(ids("list"), vec!["2"]);
+        assert!(ids("zzz").is_empty());
+    }
+
+    #[test]
+    fn test_rank_databases_fuzzy() {
+        let db = |id: &str, name: &str| Database {
+            id: id.to_string(),
+            name: name.to_string(),
+            description: String::new(),
+            created_at: String::new(),
+            updated_at: String::new(),
+            is_default: false,
+            is_public: false,
+        };
+        let dbs = vec![
+            db("1", "Homework"),
+            db("2", "Work"),
+            db("3", "Reading list"),
+        ];
+
+        let ids = |q: &str| -> Vec<String> {
+            rank_databases(&dbs, q).into_iter().map(|d| d.id).collect()
+        };
+        assert_eq!(ids(""), vec!["1", "2", "3"]);
+        // Prefix before substring.
+        assert_eq!(ids("wo"), vec!["2", "1"]);
+        // Characters in order.
+        assert_eq!(ids("rdl"), vec!["3"]);
+        assert!(ids("xyz").is_empty());
+    }
+
+    #[test]
+    fn test_template_outline_round_trip() {
+        let navs = template_navs_from_outline("- Agenda\n  Item one\n\n\tItem two\nNotes\n");
+        let shape = navs
+            .iter()
+            .map(|n| (n.id.as_str(), n.parid.as_str(), n.content.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            shape,
+            vec![
+                ("t1", "", "Agenda"),
+                ("t2", "t1", "Item one"),
+                ("t3", "t1", "Item two"),
+                ("t4", "", "Notes"),
+            ]
+        );
+        assert_eq!(navs[2].same_deep_order, 2.0);
+
+        let template = NoteTemplate {
+            name: "Meeting".to_string(),
+            navs,
+        };
+        assert_eq!(
+            template_outline_text(&template),
+            "Agenda\n  Item one\n  Item two\nNotes"
+        );
+    }
+
+    #[test]
+    fn test_template_nav_order_parents_first() {
+        let nav = |id: &str, parid: &str, order: f32| PartialNav {
+            id: id.to_string(),
+            parid: parid.to_string(),
+            same_deep_order: order,
+            content: id.to_string(),
+        };
+        // Out of order, with an orphan whose parent is missing.
+        let navs = vec![
+            nav("c", "b", 1.0),
+            nav("b", "", 2.0),
+            nav("orphan", "gone", 3.0),
+            nav("a", "", 1.0),
+        ];
+        let ids = template_nav_order(&navs)
+            .into_iter()
+            .map(|n| n.id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec!["a", "b", "c", "orphan"]);
+    }
+
+    #[test]
+    fn test_template_insert_expands_placeholders_at_the_caret() {
+        let template = NoteTemplate {
+            name: "Standup".to_string(),
+            navs: template_navs_from_outline("Standup {{date}}\n  Started {{time}}\n  Blockers"),
+        };
+        let text = template_outline_text(&expand_template(&template, "2026-10-16", "09:30"));
+        assert_eq!(text, "Standup 2026-10-16\n  Started 09:30\n  Blockers");
+
+        // `/template` typed at the end of "Notes /template".
+        let (current, caret) = strip_template_command("Notes /template", 15).unwrap();
+        assert_eq!((current.as_str(), caret), ("Notes ", 6));
+        assert!(strip_template_command("a/template", 10).is_none());
+        assert!(strip_template_command("/template x", 11).is_none());
+        assert_eq!(
+            strip_template_command("/template", 9),
+            Some((String::new(), 0))
+        );
+
+        let plan = plan_text_insert(&current, caret, &text);
+        assert_eq!(plan.current_content, "Notes Standup 2026-10-16");
+        assert_eq!(
+            plan.blocks,
+            vec![
+                PastedBlock {
+                    content: "Started 09:30".to_string(),
+                    depth: 1,
+                },
+                PastedBlock {
+                    content: "Blockers".to_string(),
+                    depth: 1,
+                },
+            ]
+        );
+
+        // One-line templates only fill the block.
+        let plan = plan_text_insert("ab", 1, "X\n");
+        assert_eq!(
+            (plan.current_content.as_str(), plan.caret_utf16),
+            ("aXb", 2)
+        );
+        assert!(plan.blocks.is_empty());
+    }
+
+    #[test]
+    fn test_login_next_round_trip_and_rejects_foreign_targets() {
+        assert_eq!(
+            login_url_with_next("/db/a b/note/n1?focus_nav=x"),
+            "/login?next=/db/a%20b/note/n1%3Ffocus_nav%3Dx"
+        );
+        assert_eq!(login_url_with_next("/"), "/login");
+        assert_eq!(login_url_with_next("//evil.com"), "/login");
+
+        assert_eq!(safe_next_path(Some("/db/1/note/2")), "/db/1/note/2");
+        assert_eq!(safe_next_path(None), "/");
+        assert_eq!(safe_next_path(Some("https://evil.com")), "/");
+        assert_eq!(safe_next_path(Some("//evil.com")), "/");
+        assert_eq!(safe_next_path(Some("/\\evil.com")), "/");
+        assert_eq!(safe_next_path(Some("/login?next=/x")), "/");
+    }
+
+    #[test]
+    fn test_api_error_from_status() {
+        assert_eq!(
+            ApiError::from_status(401, "x".into()),
+            ApiError::Unauthorized
+        );
+        assert_eq!(ApiError::from_status(404, "".into()), ApiError::NotFound);
+        assert_eq!(
+            ApiError::from_status(400, "bad title".into()),
+            ApiError::Validation("bad title".into())
+        );
+        let e = ApiError::from_status(502, "gateway".into());
+        assert_eq!(
+            e,
+            ApiError::Server {
+                status: 502,
+                body: "gateway".into()
+            }
+        );
+        assert!(e.is_transient());
+        assert!(!ApiError::Unauthorized.is_transient());
+        assert!(ApiError::Parse("eof".into()).to_string().contains("report"));
+    }
+
+    #[test]
+    fn test_databases_need_refresh_after_five_minutes() {
+        let t = 1_700_000_000_000.0;
+        assert!(databases_need_refresh(None, t));
+        assert!(!databases_need_refresh(Some(t), t));
+        assert!(!databases_need_refresh(
+            Some(t),
+            t + DATABASES_STALE_AFTER_MS
+        ));
+        assert!(databases_need_refresh(
+            Some(t),
+            t + DATABASES_STALE_AFTER_MS + 1.0
+        ));
+    }
+
+    #[test]
+    fn test_navs_cache_is_fresh_for_a_minute() {
+        let t = 1_700_000_000_000.0;
+        assert!(navs_cache_is_fresh(t, t));
+        assert!(navs_cache_is_fresh(t, t + NAVS_CACHE_TTL_MS - 1.0));
+        assert!(!navs_cache_is_fresh(t, t + NAVS_CACHE_TTL_MS));
+    }
+
+    #[test]
+    fn test_auth_error_message_by_cause() {
+        let rejected = "Invalid email or password";
+        assert_eq!(ApiError::from_status(403, "".into()), ApiError::Forbidden);
+        assert_eq!(
+            auth_error_message(&ApiError::Unauthorized, rejected),
+            rejected
+        );
+        assert_eq!(auth_error_message(&ApiError::Forbidden, rejected), rejected);
+        assert_eq!(
+            auth_error_message(&ApiError::Validation(" code expired ".into()), rejected),
+            "code expired"
+        );
+        assert_eq!(
+            auth_error_message(&ApiError::Validation("".into()), rejected),
+            rejected
+        );
+        assert!(
+            auth_error_message(&ApiError::Network("Failed to fetch".into()), rejected)
+                .starts_with("Cannot reach the server")
+        );
+        assert!(auth_error_message(&ApiError::TimedOut, rejected)
+            .starts_with("Cannot reach the server"));
+        assert!(auth_error_message(&ApiError::offline(), rejected).contains("offline"));
+        let server = ApiError::Server {
+            status: 503,
+            body: "down".into(),
+        };
+        assert!(auth_error_message(&server, rejected).contains("(503)"));
+    }
+
+    #[test]
+    fn test_notes_with_ids_keeps_list_order() {
+        let notes = vec![
+            note("a", "db", "a"),
+            note("b", "db", "b"),
+            note("c", "db", "c"),
+        ];
+        let ids: std::collections::HashSet<String> =
+            ["c", "a", "gone"].iter().map(|s| s.to_string()).collect();
+
+        let picked = notes_with_ids(&notes, &ids);
+        assert_eq!(
+            picked.iter().map(|n| n.id.as_str()).collect::<Vec<_>>(),
+            vec!["a", "c"]
+        );
+        assert!(notes_with_ids(&notes, &Default::default()).is_empty());
+    }
+
+    #[test]
+    fn test_prune_pinned_ids_drops_missing_notes() {
+        let notes = vec![
+            note("a", "db", "a"),
+            note("b", "db", "b"),
+            note("c", "db", "c"),
+        ];
+        let pinned = vec!["c".to_string(), "gone".to_string(), "a".to_string()];
+
+        assert_eq!(prune_pinned_ids(&pinned, &notes), vec!["c", "a"]);
+        assert!(prune_pinned_ids(&pinned, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_reconcile_recent_dbs_drops_deleted_databases() {
+        let recent = |id: &str, ms: i64| RecentDb {
+            id: id.to_string(),
+            name: id.to_string(),
+            last_opened_ms: ms,
+        };
+        let db = |id: &str| Database {
+            id: id.to_string(),
+            name: id.to_string(),
+            description: String::new(),
+            created_at: String::new(),
+            updated_at: String::new(),
+            is_default: false,
+            is_public: false,
+        };
+        let recents = vec![recent("b", 3), recent("gone", 2), recent("a", 1)];
+
+        assert_eq!(
+            reconcile_recent_dbs(&recents, &[db("a"), db("b")]),
+            vec![recent("b", 3), recent("a", 1)]
+        );
+        assert!(reconcile_recent_dbs(&recents, &[db("x")]).is_empty());
+    }
+
+    #[test]
+    fn test_resolve_db_name_never_falls_back_to_the_id() {
+        let dbs = vec![Database {
+            id: "db-1".to_string(),
+            name: "Work".to_string(),
+            description: String::new(),
+            created_at: String::new(),
+            updated_at: String::new(),
+            is_default: false,
+            is_public: false,
+        }];
+
+        assert_eq!(
+            resolve_db_name(&dbs, true, "db-1"),
+            DbName::Known("Work".to_string())
+        );
+        // A list still on screen from before a reload already knows the name.
+        assert_eq!(
+            resolve_db_name(&dbs, false, "db-1"),
+            DbName::Known("Work".to_string())
+        );
+        // Deep link before the list arrives, then after it arrived without the id.
+        assert_eq!(resolve_db_name(&[], false, "db-2"), DbName::Loading);
+        assert_eq!(resolve_db_name(&dbs, false, "db-2"), DbName::Loading);
+        assert_eq!(resolve_db_name(&dbs, true, "db-2"), DbName::Missing);
+        assert_eq!(resolve_db_name(&[], true, ""), DbName::Missing);
+    }
+
+    #[test]
+    fn test_is_database_limit_error() {
+        let rejected = |m: &str| ApiError::Validation(m.to_string());
+        assert!(is_database_limit_error(&rejected("Database limit reached")));
+        assert!(is_database_limit_error(&rejected(
+            "Maximum number of databases (5) reached"
+        )));
+        assert!(is_database_limit_error(&rejected("数据库数量已达上限")));
+
+        // Other limits and other failures are shown as they are.
+        assert!(!is_database_limit_error(&rejected("Rate limit exceeded")));
+        assert!(!is_database_limit_error(&rejected(
+            "Payload too large: max size exceeded"
+        )));
+        assert!(!is_database_limit_error(&rejected(
+            "Database name exceeds maximum length"
+        )));
+        assert!(!is_database_limit_error(&ApiError::Server {
+            status: 500,
+            body: "database limit".to_string(),
+        }));
+        assert!(!is_database_limit_error(&ApiError::Network(
+            "Database limit".to_string()
+        )));
+    }
+
+    #[test]
+    fn test_retry_policy_delay_doubles_and_caps() {
+        let p = RetryPolicy::LIST_FETCH;
+        let delays: Vec<u32> = (1..=7).map(|n| p.delay_ms(n)).collect();
+        assert_eq!(
+            delays,
+            vec![1_000, 2_000, 4_000, 8_000, 16_000, 30_000, 30_000]
+        );
+        assert_eq!(p.delay_ms(100), 30_000);
+    }
+
+    #[test]
+    fn test_parse_note_content_response_shapes() {
+        let parse = ApiClient::parse_note_content_response;
+        assert_eq!(parse(serde_json::json!({ "content": "hello" })), "hello");
+        assert_eq!(
+            parse(serde_json::json!({ "hulunote-notes/content": "prose" })),
+            "prose"
+        );
+        assert_eq!(parse(serde_json::json!("bare")), "bare");
+        assert_eq!(parse(serde_json::json!({ "other": 1 })), "");
+    }
+
+    #[test]
+    fn test_quick_switch_ranks_prefix_before_substring_before_fuzzy() {
+        assert_eq!(quick_switch_rank("Project plan", "proj"), Some(0));
+        assert_eq!(quick_switch_rank("My project", "PROJ"), Some(1));
+        assert_eq!(quick_switch_rank("Pre-rollout journal", "proj"), Some(2));
+        assert_eq!(quick_switch_rank("Plan", "proj"), None);
+        assert_eq!(quick_switch_rank("Anything", "  "), Some(0));
+
+        let notes = vec![
+            Note {
+                updated_at: "t1".to_string(),
+                ..note("fuzzy", "db", "Pre-rollout journal")
+            },
+            Note {
+                updated_at: "t2".to_string(),
+                ..note("sub", "db", "My project")
+            },
+            Note {
+                updated_at: "t3".to_string(),
+                ..note("prefix", "db", "Project plan")
+            },
+            Note {
+                updated_at: "t4".to_string(),
+                ..note("none", "db", "Groceries")
+            },
+        ];
+        let ids = |xs: Vec<Note>| xs.into_iter().map(|n| n.id).collect::<Vec<_>>();
+
+        assert_eq!(
+            ids(rank_quick_switch(notes.clone(), "proj", 10)),
+            vec!["prefix", "sub", "fuzzy"]
+        );
+        assert_eq!(
+            ids(rank_quick_switch(notes.clone(), "proj", 1)),
+            vec!["prefix"]
+        );
+        // Empty query: most recently updated first.
+        assert_eq!(ids(rank_quick_switch(notes, "", 2)), vec!["none", "prefix"]);
+    }
+
+    #[test]
+    fn test_palette_items_group_then_rank() {
+        let dbs = vec![Database {
+            id: "db".to_string(),
+            name: "Work".to_string(),
+            description: String::new(),
+            created_at: String::new(),
+            updated_at: String::new(),
+            is_default: false,
+            is_public: false,
+        }];
+        let recent = vec![RecentNote {
+            db_id: "db".to_string(),
+            note_id: "n1".to_string(),
+            title: "Weekly notes".to_string(),
+            last_opened_ms: 0,
+        }];
+        let notes = vec![
+            note("n1", "db", "Weekly notes"),
+            note("n2", "db", "New ideas"),
+        ];
+        let commands = [PaletteCommand::NewNote, PaletteCommand::SignOut];
+        let labels = |q: &str| {
+            palette_items(q, &commands, &recent, &dbs, notes.clone(), 10)
+                .into_iter()
+                .map(|i| i.label)
+                .collect::<Vec<_>>()
+        };
+
+        // Commands, recents, databases, then notes (the recent one isn't repeated).
+        assert_eq!(
+            labels(""),
+            vec!["New note", "Sign out", "Weekly notes", "Work", "New ideas"]
+        );
+        // Better ranks first; ties keep the group order.
+        assert_eq!(labels("note"), vec!["New note", "Weekly notes"]);
+        assert_eq!(labels("new"), vec!["New note", "New ideas"]);
+        assert!(labels("zzz").is_empty());
+
+        let items = palette_items("work", &commands, &recent, &dbs, notes, 10);
+        assert_eq!(
+            items[0].action,
+            PaletteAction::OpenDatabase("db".to_string())
+        );
+    }
+
+    #[test]
+    fn test_find_note_by_title_is_scoped_to_database() {
+        let notes = vec![
+            note("a", "db1", "20240131-2"),
+            note("b", "db2", "20240131"),
+            note("c", "db1", " 20240131 "),
+        ];
+
+        assert_eq!(
+            find_note_by_title(&notes, "db1", "20240131").map(|n| n.id.as_str()),
+            Some("c")
+        );
+        assert_eq!(
+            find_note_by_title(&notes, "db2", "20240131").map(|n| n.id.as_str()),
+            Some("b")
+        );
+        assert!(find_note_by_title(&notes, "db3", "20240131").is_none());
+    }
+
+    #[test]
+    fn test_outline_stats_counts_live_edit() {
+        let navs = vec![
+            Nav {
+                content: "ROOT".to_string(),
+                ..nav("root", crate::util::ROOT_CONTAINER_PARENT_ID, 0.0)
+            },
+            Nav {
+                content: "hello  world".to_string(),
+                ..nav("a", "root", 0.0)
+            },
+            Nav {
+                content: "one".to_string(),
+                ..nav("b", "a", 0.0)
+            },
+            Nav {
+                content: "gone for good".to_string(),
+                is_delete: true,
+                ..nav("c", "root", 0.0)
+            },
+        ];
+
+        assert_eq!(outline_stats(&navs, None), (2, 3));
+        assert_eq!(outline_stats(&navs, Some(("b", "one two three"))), (2, 5));
+        assert_eq!(format_outline_stats(2, 5), "2 nodes · ~5 words");
+        assert_eq!(format_outline_stats(1, 1), "1 node · ~1 word");
+    }
+
+    #[test]
+    fn test_truncate_title_adds_ellipsis() {
+        assert_eq!(truncate_title("Short", 40), "Short");
+        let exact = "a".repeat(40);
+        assert_eq!(truncate_title(&exact, 40), exact);
+        let long = format!("{} tail", "b".repeat(38));
+        let out = truncate_title(&long, 40);
+        assert_eq!(out.chars().count(), 39);
+        assert!(out.ends_with("b…"));
+        // Counts characters, not bytes, and drops a dangling space before the ellipsis.
+        assert_eq!(truncate_title("日本語 のタイトル", 5), "日本語…");
+    }
+
+    #[test]
+    fn test_daily_notes_newest_first() {
+        let notes = vec![
+            note("a", "db1", "20240130"),
+            note("b", "db1", "Rust"),
+            note("c", "db1", "20240131"),
+            note("d", "db1", "20240131-2"),
+            note("e", "db2", "20240201"),
+            note("f", "db1", "20241331"),
+            note("g", "db1", "2024-01-31"),
+        ];
+
+        let ids = daily_notes(&notes, "db1")
+            .into_iter()
+            .map(|n| n.id)
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec!["d", "c", "a"]);
+        assert_eq!(daily_note_key(" 20240131 "), Some((20240131, 1)));
+        assert_eq!(daily_note_key("20240131-1"), None);
+        assert_eq!(daily_note_key("2024013"), None);
+    }
+
+    #[test]
+    fn test_hash_pin_and_pin_validation() {
+        assert_eq!(
+            hash_pin("1234"),
+            "f24b0ca33ebbc07488ea6834926333c9410c32a172a4e63cf4ae5328d259fb5c"
+        );
+        assert_ne!(hash_pin("1234"), hash_pin("12345"));
+
+        assert!(is_valid_pin("1234"));
+        assert!(is_valid_pin("123456789012"));
+        assert!(!is_valid_pin("123"));
+        assert!(!is_valid_pin("1234567890123"));
+        assert!(!is_valid_pin("12a4"));
+        assert!(!is_valid_pin("１２３４"));
+    }
+
+    #[test]
+    fn test_search_cache_key_trims_query() {
+        assert_eq!(search_cache_key("db1", "  rust "), "db1:rust");
+        assert_ne!(
+            search_cache_key("db1", "rust"),
+            search_cache_key("db2", "rust")
+        );
+    }
+
+    #[test]
+    fn test_search_create_title_skips_exact_matches() {
+        let notes = vec![Note {
+            id: "a".to_string(),
+            database_id: "db1".to_string(),
+            title: "Rust".to_string(),
+            content: "".to_string(),
+            created_at: "t1".to_string(),
+            updated_at: "t2".to_string(),
+        }];
+
+        assert_eq!(search_create_title(&notes, "db1", " Rust "), None);
+        assert_eq!(
+            search_create_title(&notes, "db1", "Rus"),
+            Some("Rus".to_string())
+        );
+        assert_eq!(
+            search_create_title(&notes, "db2", "Rust"),
+            Some("Rust".to_string())
+        );
+        assert_eq!(search_create_title(&notes, "db1", "   "), None);
+    }
+
+    #[test]
+    fn test_search_title_suggestions_match_and_cap() {
+        let mut notes = (0..10)
+            .map(|i| note(&format!("n{i}"), "db1", &format!("Meeting {i}")))
+            .collect::<Vec<_>>();
+        notes.insert(0, note("x", "db2", "Meeting elsewhere"));
+        notes.push(note("r", "db1", "Reading list"));
+
+        let ids = |q: &str| {
+            search_title_suggestions(&notes, "db1", q)
+                .into_iter()
+                .map(|n| n.id)
+                .collect::<Vec<_>>()
+        };
+        let meetings = ids(" meeting ");
+        assert_eq!(meetings.len(), SEARCH_SUGGESTION_LIMIT);
+        assert_eq!(meetings[0], "n0");
+        assert_eq!(ids("LIST"), vec!["r"]);
+        assert!(ids("").is_empty());
+        assert!(ids("nothing").is_empty());
+    }
+
+    #[test]
+    fn test_recently_deleted_items_expire_and_round_trip() {
+        let subtree = selection_fixture()
+            .into_iter()
+            .filter(|n| n.id == "a" || n.id == "a1")
+            .collect::<Vec<_>>();
+        let data = deleted_navs_payload("db1", "note1", &subtree);
+        let (db_id, note_id, navs) = parse_deleted_navs_payload(&data).unwrap();
+        assert_eq!((db_id.as_str(), note_id.as_str()), ("db1", "note1"));
+        assert_eq!(
+            navs.iter().map(|n| n.id.as_str()).collect::<Vec<_>>(),
+            vec!["a", "a1"]
+        );
+        assert!(parse_deleted_navs_payload(&deleted_navs_payload("db1", "note1", &[])).is_none());
+
+        let nav_item = DeletedItem {
+            kind: DeletedItemKind::Nav,
+            id: "a".to_string(),
+            data,
+            expires_ms: 1_000 + UNDO_DELETE_WINDOW_MS,
+        };
+        let note_item = DeletedItem {
+            kind: DeletedItemKind::Note,
+            id: "n1".to_string(),
+            data: serde_json::json!({ "id": "n1", "title": "Inbox" }),
+            expires_ms: 5_000 + UNDO_DELETE_WINDOW_MS,
+        };
+        assert_eq!(deleted_item_label(&nav_item), "Deleted 2 blocks");
+        assert_eq!(deleted_item_label(&note_item), "Deleted \"Inbox\"");
+
+        let items = vec![nav_item, note_item];
+        assert_eq!(live_deleted_items(&items, 1_000).len(), 2);
+        // The window is exclusive at its end.
+        let live = live_deleted_items(&items, 1_000 + UNDO_DELETE_WINDOW_MS);
+        assert_eq!(live.len(), 1);
+        assert_eq!(live[0].id, "n1");
+        assert!(live_deleted_items(&items, 5_000 + UNDO_DELETE_WINDOW_MS).is_empty());
+    }
+
+    #[test]
+    fn test_should_follow_server_title_keeps_unsaved_edits() {
+        // Renamed elsewhere, nothing typed here: follow.
+        assert!(should_follow_server_title("Old", "Old", "New"));
+        // Empty field always takes the server title.
+        assert!(should_follow_server_title("  ", "Old", "Old"));
+        // Unsaved local edit wins.
+        assert!(!should_follow_server_title("Typing", "Old", "New"));
+        // Nothing changed.
+        assert!(!should_follow_server_title("Same", "Same", "Same"));
+    }
+
+    #[test]
+    fn test_collapse_all_folds_top_level_and_expand_all_unfolds_everything() {
+        let mut all = selection_fixture();
+        // a > a1 > a1x: only the top-level `a` folds.
+        all.push(Nav {
+            content: "a1x".to_string(),
+            ..nav("a1x", "a1", 1.0)
+        });
+
+        assert_eq!(collapse_all(&mut all), vec!["a"]);
+        assert_eq!(collapsed_block_ids(&all), vec!["a"]);
+        // Already folded: nothing to persist.
+        assert!(collapse_all(&mut all).is_empty());
+
+        if let Some(n) = all.iter_mut().find(|n| n.id == "a1") {
+            n.is_display = false;
+        }
+        let mut changed = expand_all(&mut all);
+        changed.sort();
+        assert_eq!(changed, vec!["a", "a1"]);
+        assert!(collapsed_block_ids(&all).is_empty());
+    }
 }
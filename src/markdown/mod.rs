@@ -0,0 +1,216 @@
+/// Inline Markdown span (MVP subset) of a block's content.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum InlineSpan {
+    Text(String),
+    Bold(Vec<InlineSpan>),
+    Italic(Vec<InlineSpan>),
+    Code(String),
+    Url(String),
+}
+
+/// Emphasis active on a flattened run.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct InlineStyle {
+    pub bold: bool,
+    pub italic: bool,
+}
+
+impl InlineStyle {
+    pub(crate) fn class(self) -> &'static str {
+        match (self.bold, self.italic) {
+            (true, true) => "font-semibold italic",
+            (true, false) => "font-semibold",
+            (false, true) => "italic",
+            (false, false) => "",
+        }
+    }
+}
+
+/// A leaf of the span tree with the emphasis of its ancestors.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum InlineRun {
+    Text(String, InlineStyle),
+    Code(String, InlineStyle),
+    Url(String, InlineStyle),
+}
+
+/// Parse inline Markdown from block content.
+///
+/// Rules (MVP):
+/// - `**bold**`, `*italic*` (may nest), `` `code` `` (contents are literal).
+/// - Bare `http://` / `https://` URLs up to the next whitespace; trailing punctuation is
+///   left out of the URL.
+/// - `[[links]]` and `((refs))` are atomic text, so markers inside them are not parsed
+///   and wiki tokenization still sees them intact.
+/// - Unterminated or empty markers stay plain text.
+pub(crate) fn parse_inline_markdown(input: &str) -> Vec<InlineSpan> {
+    let mut out: Vec<InlineSpan> = Vec::new();
+    let mut text = String::new();
+    let mut i = 0;
+
+    let flush = |text: &mut String, out: &mut Vec<InlineSpan>| {
+        if !text.is_empty() {
+            out.push(InlineSpan::Text(std::mem::take(text)));
+        }
+    };
+
+    while i < input.len() {
+        let rest = &input[i..];
+
+        if let Some(len) = atomic_len(rest) {
+            text.push_str(&rest[..len]);
+            i += len;
+            continue;
+        }
+
+        if let Some(inner) = rest.strip_prefix('`') {
+            if let Some(end) = inner.find('`').filter(|&e| e > 0) {
+                flush(&mut text, &mut out);
+                out.push(InlineSpan::Code(inner[..end].to_string()));
+                i += end + 2;
+                continue;
+            }
+        }
+
+        if let Some(inner) = rest.strip_prefix("**") {
+            if let Some(end) = find_closing(inner, "**") {
+                flush(&mut text, &mut out);
+                out.push(InlineSpan::Bold(parse_inline_markdown(&inner[..end])));
+                i += end + 4;
+                continue;
+            }
+            // Unterminated `**`: keep both stars literal (don't reopen as italic).
+            text.push_str("**");
+            i += 2;
+            continue;
+        }
+
+        if let Some(inner) = rest.strip_prefix('*') {
+            if let Some(end) = find_closing(inner, "*") {
+                flush(&mut text, &mut out);
+                out.push(InlineSpan::Italic(parse_inline_markdown(&inner[..end])));
+                i += end + 2;
+                continue;
+            }
+        }
+
+        if at_word_start(input, i) {
+            if let Some(len) = url_len(rest) {
+                flush(&mut text, &mut out);
+                out.push(InlineSpan::Url(rest[..len].to_string()));
+                i += len;
+                continue;
+            }
+        }
+
+        let ch = rest.chars().next().unwrap_or_default();
+        text.push(ch);
+        i += ch.len_utf8();
+    }
+
+    flush(&mut text, &mut out);
+    out
+}
+
+/// Flatten a span tree into styled leaf runs (for rendering).
+pub(crate) fn flatten_inline(spans: &[InlineSpan]) -> Vec<InlineRun> {
+    fn walk(spans: &[InlineSpan], style: InlineStyle, out: &mut Vec<InlineRun>) {
+        for span in spans {
+            match span {
+                InlineSpan::Text(s) => out.push(InlineRun::Text(s.clone(), style)),
+                InlineSpan::Code(s) => out.push(InlineRun::Code(s.clone(), style)),
+                InlineSpan::Url(s) => out.push(InlineRun::Url(s.clone(), style)),
+                InlineSpan::Bold(xs) => walk(
+                    xs,
+                    InlineStyle {
+                        bold: true,
+                        ..style
+                    },
+                    out,
+                ),
+                InlineSpan::Italic(xs) => walk(
+                    xs,
+                    InlineStyle {
+                        italic: true,
+                        ..style
+                    },
+                    out,
+                ),
+            }
+        }
+    }
+
+    let mut out = vec![];
+    walk(spans, InlineStyle::default(), &mut out);
+    out
+}
+
+/// Byte length of a `[[link]]` or `((ref))` at the start of `s`.
+fn atomic_len(s: &str) -> Option<usize> {
+    if let Some(inner) = s.strip_prefix("[[") {
+        return inner.find("]]").map(|end| end + 4);
+    }
+    let inner = s.strip_prefix("((")?;
+    let end = inner.find("))")?;
+    let id = &inner[..end];
+    let is_ref = !id.is_empty() && !id.contains(|c: char| c.is_whitespace() || c == '(');
+    is_ref.then_some(end + 4)
+}
+
+/// Byte offset in `s` of the `marker` closing an emphasis opened just before `s`.
+///
+/// The content must be non-empty and not start or end with whitespace. Code spans and
+/// atomic links are skipped; for `*`, a `**` pair is nested bold and is skipped too.
+fn find_closing(s: &str, marker: &str) -> Option<usize> {
+    if s.starts_with(char::is_whitespace) {
+        return None;
+    }
+
+    let mut i = 0;
+    while i < s.len() {
+        let rest = &s[i..];
+        if let Some(len) = atomic_len(rest) {
+            i += len;
+            continue;
+        }
+        if let Some(inner) = rest.strip_prefix('`') {
+            if let Some(end) = inner.find('`') {
+                i += end + 2;
+                continue;
+            }
+        }
+        if rest.starts_with(marker) && i > 0 {
+            if marker == "*" && rest.starts_with("**") {
+                i += 2;
+                continue;
+            }
+            if !s[..i].ends_with(char::is_whitespace) {
+                return Some(i);
+            }
+        }
+        i += rest.chars().next().map(char::len_utf8).unwrap_or(1);
+    }
+    None
+}
+
+fn at_word_start(input: &str, i: usize) -> bool {
+    input[..i]
+        .chars()
+        .next_back()
+        .is_none_or(|c| c.is_whitespace() || matches!(c, '(' | '*'))
+}
+
+/// Byte length of a bare `http(s)://` URL at the start of `s`.
+fn url_len(s: &str) -> Option<usize> {
+    let scheme = if s.starts_with("https://") {
+        8
+    } else if s.starts_with("http://") {
+        7
+    } else {
+        return None;
+    };
+
+    let end = s.find(char::is_whitespace).unwrap_or(s.len());
+    let url = s[..end].trim_end_matches(['.', ',', ';', ':', '!', '?', ')', '\'', '"', '*']);
+    (url.len() > scheme).then_some(url.len())
+}
@@ -10,16 +10,94 @@ pub(crate) struct AccountInfo {
     pub extra: serde_json::Value,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+impl AccountInfo {
+    /// First non-blank value among `keys`, plain or namespaced (`accounts/…`,
+    /// `hulunote-accounts/…`). Numbers (ids) are rendered as text.
+    fn field(&self, keys: &[&str]) -> Option<String> {
+        keys.iter()
+            .flat_map(|k| {
+                [
+                    k.to_string(),
+                    format!("accounts/{k}"),
+                    format!("hulunote-accounts/{k}"),
+                ]
+            })
+            .find_map(|k| match self.extra.get(&k)? {
+                serde_json::Value::String(s) if !s.trim().is_empty() => Some(s.trim().to_string()),
+                serde_json::Value::Number(n) => Some(n.to_string()),
+                _ => None,
+            })
+    }
+
+    pub fn id(&self) -> Option<String> {
+        self.field(&["id"])
+    }
+
+    pub fn username(&self) -> Option<String> {
+        self.field(&["username", "name", "nickname"])
+    }
+
+    /// Older backends don't return it.
+    pub fn email(&self) -> Option<String> {
+        self.field(&["mail", "email"])
+    }
+
+    /// The username, else the email's local part (what the avatar is derived from).
+    fn avatar_source(&self) -> String {
+        self.username()
+            .or_else(|| {
+                self.email()
+                    .map(|e| e.split('@').next().unwrap_or_default().to_string())
+            })
+            .unwrap_or_default()
+    }
+
+    /// Up to two letters for the avatar: the first letters of the first two words, or the
+    /// first two letters of a single word.
+    pub fn initials(&self) -> String {
+        let source = self.avatar_source();
+        let words: Vec<&str> = source
+            .split(|c: char| c.is_whitespace() || matches!(c, '.' | '_' | '-'))
+            .filter(|w| !w.is_empty())
+            .collect();
+        let letters: Vec<char> = match words.as_slice() {
+            [] => vec![],
+            [word] => word.chars().take(2).collect(),
+            [a, b, ..] => a.chars().take(1).chain(b.chars().take(1)).collect(),
+        };
+        let out: String = letters.into_iter().flat_map(char::to_uppercase).collect();
+        if out.is_empty() {
+            "?".to_string()
+        } else {
+            out
+        }
+    }
+
+    /// Avatar background hue (0–359), stable per username (FNV-1a of the avatar source).
+    pub fn avatar_hue(&self) -> u16 {
+        let hash = self.avatar_source().bytes().fold(0x811c_9dc5_u32, |h, b| {
+            (h ^ u32::from(b)).wrapping_mul(0x0100_0193)
+        });
+        (hash % 360) as u16
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub(crate) struct Database {
     pub id: String,
     pub name: String,
     pub description: String,
     pub created_at: String,
     pub updated_at: String,
+    /// Opened automatically after login (at most one database).
+    #[serde(default)]
+    pub is_default: bool,
+    /// Readable by anyone with the link (`/public/:db_id`), without logging in.
+    #[serde(default)]
+    pub is_public: bool,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub(crate) struct Note {
     pub id: String,
     pub database_id: String,
@@ -54,6 +132,118 @@ pub(crate) struct Nav {
     /// Kept as a string to avoid coupling to a specific schema.
     #[serde(default)]
     pub properties: Option<String>,
+
+    /// Backend timestamps; older responses omit them.
+    #[serde(
+        rename = "created-at",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub created_at: Option<String>,
+
+    #[serde(
+        rename = "updated-at",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub updated_at: Option<String>,
+}
+
+impl Nav {
+    /// Nesting depth of `nav_id`: 1 for top-level blocks (children of the ROOT container),
+    /// 2 for their children, and so on; 0 for the ROOT container itself or an unknown id.
+    pub(crate) fn depth(all: &[Nav], nav_id: &str) -> usize {
+        let mut depth = 0;
+        let mut cur = nav_id;
+        // Bounded walk: a corrupt parent cycle must not hang the editor.
+        for _ in 0..2048 {
+            let Some(n) = all.iter().find(|n| n.id == cur) else {
+                break;
+            };
+            if n.parid == crate::util::ROOT_CONTAINER_PARENT_ID {
+                break;
+            }
+            depth += 1;
+            cur = &n.parid;
+        }
+        depth
+    }
+}
+
+/// Parsed form of `Nav::properties`. Keys this client doesn't know are kept in `extra`
+/// so a round-trip never drops metadata written by other clients.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub(crate) struct NavProperties {
+    /// `#tags` found in the block content (lowercase, deduped, in order of appearance).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl NavProperties {
+    /// Parse a nav's `properties`; missing or malformed JSON yields the defaults.
+    pub(crate) fn parse(raw: Option<&str>) -> Self {
+        raw.filter(|s| !s.trim().is_empty())
+            .and_then(|s| serde_json::from_str(s).ok())
+            .unwrap_or_default()
+    }
+
+    /// Serialize back to `Nav::properties`; `None` when there is nothing to store.
+    pub(crate) fn to_json(&self) -> Option<String> {
+        if self.tags.is_empty() && self.extra.is_empty() {
+            return None;
+        }
+        serde_json::to_string(self).ok()
+    }
+}
+
+/// Where the user left a note's outline: the block last edited, its caret column (UTF-16)
+/// and the window scroll offset. Kept in memory for the session (`AppState::editor_positions`).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct EditorPosition {
+    pub nav_id: Option<String>,
+    pub caret_col: Option<u32>,
+    pub scroll_y: f64,
+}
+
+/// What a `DeletedItem` puts back on undo.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum DeletedItemKind {
+    Note,
+    Nav,
+}
+
+/// A delete that the snackbar can still undo (`AppState::recently_deleted`).
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct DeletedItem {
+    pub kind: DeletedItemKind,
+    /// The note id, or the root block id of a deleted subtree.
+    pub id: String,
+    /// The deleted `Note`, or the blocks of the subtree (see `deleted_navs_payload`).
+    pub data: serde_json::Value,
+    /// When the undo window closes (ms since epoch).
+    pub expires_ms: i64,
+}
+
+/// A block of a note template. `parid` is another template block's `id`, or empty for a
+/// top-level block.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub(crate) struct PartialNav {
+    pub id: String,
+    #[serde(default)]
+    pub parid: String,
+    #[serde(default)]
+    pub same_deep_order: f32,
+    pub content: String,
+}
+
+/// Outline used to pre-populate new notes (stored locally, see `TEMPLATES_KEY`).
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub(crate) struct NoteTemplate {
+    pub name: String,
+    pub navs: Vec<PartialNav>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
@@ -70,3 +260,198 @@ pub(crate) struct RecentNote {
     pub title: String,
     pub last_opened_ms: i64,
 }
+
+/// UI color theme preference (persisted in localStorage).
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum Theme {
+    Light,
+    Dark,
+    /// Follow the OS `prefers-color-scheme`.
+    #[default]
+    System,
+}
+
+/// Note list ordering (persisted in localStorage); shared by DbHomePage and the sidebar.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum NoteSort {
+    #[default]
+    UpdatedAtDesc,
+    CreatedAtDesc,
+    TitleAsc,
+}
+
+impl NoteSort {
+    pub const ALL: [NoteSort; 3] = [
+        NoteSort::UpdatedAtDesc,
+        NoteSort::CreatedAtDesc,
+        NoteSort::TitleAsc,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            NoteSort::UpdatedAtDesc => "Recently updated",
+            NoteSort::CreatedAtDesc => "Recently created",
+            NoteSort::TitleAsc => "Alphabetical",
+        }
+    }
+
+    /// Stable key for `<select>` option values.
+    pub fn key(self) -> &'static str {
+        match self {
+            NoteSort::UpdatedAtDesc => "updated",
+            NoteSort::CreatedAtDesc => "created",
+            NoteSort::TitleAsc => "title",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|s| s.key() == key)
+    }
+}
+
+/// Date range applied to the note lists (DbHomePage and the sidebar) by `Note::updated_at`.
+/// `Custom` holds inclusive `YYYY-MM-DD` bounds; an empty bound is open.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) enum NoteFilter {
+    #[default]
+    All,
+    Today,
+    ThisWeek,
+    ThisMonth,
+    Custom(String, String),
+}
+
+impl NoteFilter {
+    /// Options of the filter dropdown.
+    pub const PRESETS: [NoteFilter; 4] = [
+        NoteFilter::All,
+        NoteFilter::Today,
+        NoteFilter::ThisWeek,
+        NoteFilter::ThisMonth,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            NoteFilter::All => "All",
+            NoteFilter::Today => "Today",
+            NoteFilter::ThisWeek => "This week",
+            NoteFilter::ThisMonth => "This month",
+            NoteFilter::Custom(..) => "Custom",
+        }
+    }
+
+    /// Stable key for `<select>` option values.
+    pub fn key(&self) -> &'static str {
+        match self {
+            NoteFilter::All => "all",
+            NoteFilter::Today => "today",
+            NoteFilter::ThisWeek => "week",
+            NoteFilter::ThisMonth => "month",
+            NoteFilter::Custom(..) => "custom",
+        }
+    }
+
+    /// Preset for a dropdown key (`Custom` has no key to parse from).
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::PRESETS.into_iter().find(|f| f.key() == key)
+    }
+}
+
+/// Built-in command palette commands.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum PaletteCommand {
+    NewNote,
+    ToggleSidebar,
+    SignOut,
+}
+
+impl PaletteCommand {
+    pub fn label(self) -> &'static str {
+        match self {
+            PaletteCommand::NewNote => "New note",
+            PaletteCommand::ToggleSidebar => "Toggle sidebar",
+            PaletteCommand::SignOut => "Sign out",
+        }
+    }
+}
+
+/// What picking a command palette row does.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum PaletteAction {
+    Run(PaletteCommand),
+    OpenDatabase(String),
+    OpenNote { db_id: String, note_id: String },
+}
+
+/// A command palette row: `label` is matched against the query, `detail` is shown dimmed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct PaletteItem {
+    pub label: String,
+    pub detail: String,
+    pub action: PaletteAction,
+}
+
+/// A database id looked up in the fetched database list (`resolve_db_name`).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum DbName {
+    /// The list has not loaded yet: show a placeholder, never the raw id.
+    Loading,
+    Known(String),
+    /// The list loaded without this id (deleted by another client, or a stale link).
+    Missing,
+}
+
+/// Base title for notes created with "New note" (a `-N` suffix is added on collisions).
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum NoteTitleFormat {
+    /// `20240131`
+    #[default]
+    Compact,
+    /// `2024-01-31`
+    IsoDate,
+    /// `Untitled`
+    Untitled,
+}
+
+/// User preferences (persisted in localStorage).
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(default)]
+pub(crate) struct Settings {
+    /// Overrides `window.ENV.API_URL` when set.
+    pub api_url: Option<String>,
+    /// Delay before a content edit is flushed to the backend.
+    pub autosave_ms: u32,
+    pub new_note_title: NoteTitleFormat,
+    /// Enter creates a child block instead of a sibling.
+    pub enter_creates_child: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            api_url: None,
+            autosave_ms: 1200,
+            new_note_title: NoteTitleFormat::default(),
+            enter_creates_child: false,
+        }
+    }
+}
+
+impl Settings {
+    pub const MIN_AUTOSAVE_MS: u32 = 200;
+    pub const MAX_AUTOSAVE_MS: u32 = 10_000;
+
+    /// The API URL override, if it is set to something non-blank (trailing `/` trimmed).
+    pub fn api_url_override(&self) -> Option<String> {
+        self.api_url
+            .as_deref()
+            .map(|u| u.trim().trim_end_matches('/'))
+            .filter(|u| !u.is_empty())
+            .map(|u| u.to_string())
+    }
+
+    pub fn autosave_delay_ms(&self) -> i32 {
+        self.autosave_ms
+            .clamp(Self::MIN_AUTOSAVE_MS, Self::MAX_AUTOSAVE_MS) as i32
+    }
+}
@@ -0,0 +1,272 @@
+use crate::models::Nav;
+use crate::util::ROOT_CONTAINER_PARENT_ID;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+
+/// `same_deep_order` comparison that is total: NaN orders sort last instead of making the
+/// sort inconsistent. Equal orders compare equal, so a stable sort keeps list order.
+pub(crate) fn cmp_order(a: f32, b: f32) -> Ordering {
+    match (a.is_nan(), b.is_nan()) {
+        (false, false) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+        (a_nan, b_nan) => a_nan.cmp(&b_nan),
+    }
+}
+
+/// `same_deep_order` for a block inserted between siblings with orders `prev` and `next`
+/// (`None` at either end): the midpoint, or one step past the only neighbor. Non-finite
+/// neighbors are ignored so a corrupt order never spreads to the new block.
+pub(crate) fn insert_order_between(prev: Option<f32>, next: Option<f32>) -> f32 {
+    match (
+        prev.filter(|p| p.is_finite()),
+        next.filter(|n| n.is_finite()),
+    ) {
+        (Some(p), Some(n)) => (p + n) / 2.0,
+        (Some(p), None) => p + 1.0,
+        (None, Some(n)) => n - 1.0,
+        (None, None) => 0.0,
+    }
+}
+
+/// Narrowest gap between neighboring `same_deep_order`s that still takes a midpoint insert.
+/// Repeated f32 midpoints stop moving after ~24 halvings of the same gap, so the siblings are
+/// renumbered well before that (see `rebalance_for_insert`).
+pub(crate) const MIN_ORDER_GAP: f32 = 1e-3;
+
+/// Whether `count` blocks inserted between orders `prev` and `next` would not get distinct
+/// orders strictly between them (or a neighbor's order is not finite).
+pub(crate) fn orders_crowded(prev: Option<f32>, next: Option<f32>, count: usize) -> bool {
+    if prev.is_some_and(|p| !p.is_finite()) || next.is_some_and(|n| !n.is_finite()) {
+        return true;
+    }
+    let (Some(p), Some(n)) = (prev, next) else {
+        return false;
+    };
+    let step = (n - p) / (count as f32 + 1.0);
+    if step < MIN_ORDER_GAP {
+        return true;
+    }
+    // The first and last inserted orders must still fall strictly inside the gap.
+    let first = p + step;
+    let last = p + step * count as f32;
+    !(p < first && last < n)
+}
+
+/// Children of `parid` (soft-deleted ones included, `exclude` left out) renumbered to 1.0,
+/// 2.0, … in display order: `(id, order)` for each whose order changes.
+pub(crate) fn renumber_children(
+    all: &[Nav],
+    parid: &str,
+    exclude: Option<&str>,
+) -> Vec<(String, f32)> {
+    NavTree::new(all)
+        .all_children(parid)
+        .iter()
+        .filter(|n| Some(n.id.as_str()) != exclude)
+        .enumerate()
+        .map(|(i, n)| (n, (i + 1) as f32))
+        .filter(|(n, order)| n.same_deep_order != *order)
+        .map(|(n, order)| (n.id.clone(), order))
+        .collect()
+}
+
+/// Renumbering of the children of `parid` to apply before inserting `count` blocks right
+/// after the sibling `after` (`None` = first position); empty while the gap is wide enough.
+/// `exclude` is a block being moved among these siblings, which takes no slot.
+pub(crate) fn rebalance_for_insert(
+    all: &[Nav],
+    parid: &str,
+    after: Option<&str>,
+    count: usize,
+    exclude: Option<&str>,
+) -> Vec<(String, f32)> {
+    if count == 0 {
+        return vec![];
+    }
+    let tree = NavTree::new(all);
+    let sibs = tree
+        .all_children(parid)
+        .iter()
+        .filter(|n| Some(n.id.as_str()) != exclude)
+        .collect::<Vec<_>>();
+    let idx = match after {
+        Some(id) => match sibs.iter().position(|n| n.id == id) {
+            Some(i) => i + 1,
+            None => return vec![],
+        },
+        None => 0,
+    };
+    let prev = idx.checked_sub(1).map(|i| sibs[i].same_deep_order);
+    let next = sibs.get(idx).map(|n| n.same_deep_order);
+    if !orders_crowded(prev, next, count) {
+        return vec![];
+    }
+    renumber_children(all, parid, exclude)
+}
+
+/// Set `same_deep_order` from `(id, order)` pairs (e.g. a `rebalance_for_insert` result).
+pub(crate) fn apply_orders(all: &mut [Nav], orders: &[(String, f32)]) {
+    for (id, order) in orders {
+        if let Some(n) = all.iter_mut().find(|n| &n.id == id) {
+            n.same_deep_order = *order;
+        }
+    }
+}
+
+/// Index over a note's navs, built once per operation instead of re-filtering and
+/// re-sorting the list for every lookup.
+///
+/// Children are kept per parent id in display order (`cmp_order`, ties in list order),
+/// soft-deleted ones included; the tree queries below skip them. The ROOT container is an
+/// ordinary node whose parent is `ROOT_CONTAINER_PARENT_ID`.
+pub(crate) struct NavTree<'a> {
+    nodes: HashMap<&'a str, &'a Nav>,
+    children: HashMap<&'a str, Vec<&'a Nav>>,
+}
+
+impl<'a> NavTree<'a> {
+    pub(crate) fn new(all: &'a [Nav]) -> Self {
+        let mut nodes: HashMap<&'a str, &'a Nav> = HashMap::with_capacity(all.len());
+        let mut children: HashMap<&'a str, Vec<&'a Nav>> = HashMap::new();
+        for n in all {
+            nodes.entry(n.id.as_str()).or_insert(n);
+            children.entry(n.parid.as_str()).or_default().push(n);
+        }
+        for xs in children.values_mut() {
+            xs.sort_by(|a, b| cmp_order(a.same_deep_order, b.same_deep_order));
+        }
+        Self { nodes, children }
+    }
+
+    pub(crate) fn get(&self, id: &str) -> Option<&'a Nav> {
+        self.nodes.get(id).copied()
+    }
+
+    /// Children of `parid` in display order, soft-deleted ones included.
+    pub(crate) fn all_children(&self, parid: &str) -> &[&'a Nav] {
+        self.children.get(parid).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Live children of `parid` in display order.
+    pub(crate) fn children(&self, parid: &str) -> Vec<&'a Nav> {
+        self.all_children(parid)
+            .iter()
+            .copied()
+            .filter(|n| !n.is_delete)
+            .collect()
+    }
+
+    /// Live siblings of `id` (itself included) in display order; empty if `id` is unknown.
+    pub(crate) fn siblings(&self, id: &str) -> Vec<&'a Nav> {
+        self.get(id)
+            .map(|n| self.children(&n.parid))
+            .unwrap_or_default()
+    }
+
+    /// The live sibling right before `id`.
+    pub(crate) fn previous_sibling(&self, id: &str) -> Option<&'a Nav> {
+        let sibs = self.siblings(id);
+        let idx = sibs.iter().position(|n| n.id == id)?;
+        sibs.get(idx.checked_sub(1)?).copied()
+    }
+
+    /// The live sibling right after `id`.
+    pub(crate) fn next_sibling(&self, id: &str) -> Option<&'a Nav> {
+        let sibs = self.siblings(id);
+        let idx = sibs.iter().position(|n| n.id == id)?;
+        sibs.get(idx + 1).copied()
+    }
+
+    /// Ids of the whole visible outline in preorder: live blocks, skipping the children of
+    /// collapsed (`!is_display`) ones.
+    pub(crate) fn visible_preorder(&self) -> Vec<String> {
+        let mut out = vec![];
+        self.collect_visible(ROOT_CONTAINER_PARENT_ID, &mut out);
+        out
+    }
+
+    /// Visible preorder limited to the zoom boundary: the zoom root followed by its visible
+    /// descendants. Without a zoom root (or if it is gone) this is the whole outline.
+    pub(crate) fn visible_preorder_within(&self, zoom_root: Option<&str>) -> Vec<String> {
+        let Some(root) = zoom_root.and_then(|z| self.get(z)).filter(|n| !n.is_delete) else {
+            return self.visible_preorder();
+        };
+
+        let mut out = vec![root.id.clone()];
+        if root.is_display {
+            self.collect_visible(&root.id, &mut out);
+        }
+        out
+    }
+
+    fn collect_visible(&self, parid: &str, out: &mut Vec<String>) {
+        for n in self.children(parid) {
+            out.push(n.id.clone());
+            if n.is_display {
+                self.collect_visible(&n.id, out);
+            }
+        }
+    }
+
+    /// Deepest last block shown under `id` (`id` itself when it is collapsed or a leaf).
+    pub(crate) fn last_visible_descendant(&self, id: &str) -> Option<&'a Nav> {
+        let mut cur = self.get(id)?;
+        // Bounded: a corrupt parent cycle must not hang the editor.
+        for _ in 0..self.nodes.len() {
+            if !cur.is_display {
+                break;
+            }
+            match self.children(&cur.id).last().copied() {
+                Some(last) => cur = last,
+                None => break,
+            }
+        }
+        Some(cur)
+    }
+
+    /// The block shown right before `id`: the previous sibling's last visible descendant,
+    /// else the parent. Matches the entry before `id` in `visible_preorder`.
+    pub(crate) fn previous_visible(&self, id: &str) -> Option<&'a Nav> {
+        let me = self.get(id).filter(|n| !n.is_delete)?;
+        match self.previous_sibling(id) {
+            Some(prev) => self.last_visible_descendant(&prev.id),
+            None => self.get(&me.parid).filter(|p| !p.is_delete),
+        }
+    }
+
+    /// The block shown right after `id`: its first child when expanded, else the next
+    /// sibling of the nearest ancestor that has one. Matches the entry after `id` in
+    /// `visible_preorder`.
+    pub(crate) fn next_visible(&self, id: &str) -> Option<&'a Nav> {
+        let me = self.get(id).filter(|n| !n.is_delete)?;
+        if me.is_display {
+            if let Some(first) = self.children(id).first().copied() {
+                return Some(first);
+            }
+        }
+        let mut cur = me;
+        for _ in 0..self.nodes.len() {
+            if let Some(next) = self.next_sibling(&cur.id) {
+                return Some(next);
+            }
+            cur = self.get(&cur.parid).filter(|p| !p.is_delete)?;
+        }
+        None
+    }
+
+    /// Ids of `id` and everything below it, soft-deleted blocks included, parents first.
+    pub(crate) fn subtree_ids(&self, id: &str) -> Vec<String> {
+        let mut out = vec![];
+        let mut seen: HashSet<&str> = HashSet::new();
+        let mut stack: Vec<&str> = vec![id];
+        while let Some(cur) = stack.pop() {
+            if !seen.insert(cur) {
+                continue;
+            }
+            out.push(cur.to_string());
+            for c in self.all_children(cur).iter().rev() {
+                stack.push(&c.id);
+            }
+        }
+        out
+    }
+}
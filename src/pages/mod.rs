@@ -1,27 +1,62 @@
-use crate::cache::load_note_snapshot;
+use crate::cache::{
+    collapse_all, expand_all, load_note_snapshot, restore_navs_in_snapshot, set_all_collapsed,
+};
 use crate::components::ui::{
     Alert, AlertDescription, Button, ButtonSize, ButtonVariant, Card, CardContent, CardDescription,
-    CardHeader, CardTitle, Input, Label, Spinner,
+    CardHeader, CardTitle, Command, CommandItem, CommandList, Input, Label, Spinner,
+};
+use crate::drafts::{
+    count_unsynced_nav_drafts, get_title_override, load_note_draft, mark_title_sync_failed,
+    mark_title_synced, nav_content_edited_ms, touch_title,
+};
+use crate::api::{
+    auth_error_message, database_limit_message, is_database_limit_error, with_retry, ApiError,
+    EnvConfig, RetryPolicy, RetryState, MAX_DATABASES, NOTE_LIST_PAGE_SIZE,
 };
-use crate::drafts::get_title_override;
 use crate::editor::OutlineEditor;
-use crate::models::{Nav, Note};
-use crate::state::{AppContext, DbUiActions};
+use crate::export::{
+    copy_to_clipboard, download_markdown, markdown_export_filename, navs_to_markdown,
+};
+use crate::models::{
+    AccountInfo, DbName, DeletedItem, DeletedItemKind, Nav, Note, NoteFilter, NoteSort,
+    NoteTemplate, NoteTitleFormat, PaletteAction, PaletteCommand, PaletteItem, RecentDb,
+    RecentNote, Settings, Theme,
+};
+use crate::state::{handle_unauthorized_response, AppContext, DbUiActions};
 use crate::storage::{
-    load_recent_notes, save_recent_notes, save_user_to_storage, write_recent_db, write_recent_note,
-    CURRENT_DB_KEY, SIDEBAR_COLLAPSED_KEY,
+    forget_recent_db, load_recent_dbs, load_recent_notes, prune_recent_notes_not_in,
+    retarget_recent_notes, save_recent_dbs, save_recent_notes, save_user_to_storage,
+    write_recent_db, write_recent_note, CURRENT_DB_KEY, SIDEBAR_COLLAPSED_KEY,
+};
+use crate::util::{
+    count_note_chars, daily_notes, databases_need_refresh, db_id_from_path,
+    default_database_updates, deleted_ancestor_ids, deleted_item_label, document_title,
+    expand_template_now, filter_databases, filter_notes_by_updated, find_database_by_name,
+    find_note_by_title, format_char_count_badge, format_outline_stats, format_relative_ms,
+    format_relative_time, is_valid_pin, live_deleted_items, live_outline, login_url_with_next,
+    merge_notes_page, nav_edited_ms, next_available_daily_note_title, note_filter_bounds_now,
+    note_id_from_path, note_preview, notes_with_ids, now_ms, outline_stats, palette_items,
+    parse_deleted_navs_payload, partition_pinned_notes, public_database_url, rank_databases,
+    reconcile_recent_dbs, resolve_db_name, restore_nav_ids, safe_next_path, search_cache_key,
+    search_create_title, search_title_suggestions, should_follow_server_title, sort_notes,
+    template_navs_from_outline, template_outline_text, today_yyyymmdd_local,
+    top_level_restore_target, trash_outline, truncate_title, UNDO_DELETE_WINDOW_MS,
 };
-use crate::util::next_available_daily_note_title;
 use crate::util::ROOT_CONTAINER_PARENT_ID;
-use crate::wiki::{extract_wiki_links, normalize_roam_page_title};
+use crate::wiki::{
+    build_tags_index, count_open_todos, extract_wiki_links, find_backlinks,
+    normalize_roam_page_title, parse_wiki_tokens, WikiToken,
+};
 use leptos::ev;
 use leptos::html;
+use leptos::portal::Portal;
 use leptos::prelude::*;
 use leptos::task::spawn_local;
-use leptos_dom::helpers::window_event_listener;
+use leptos_dom::helpers::{set_interval_with_handle, window_event_listener};
 use leptos_router::components::A;
 use leptos_router::hooks::{use_location, use_navigate, use_query_map};
 use leptos_router::params::Params;
+use std::collections::{HashMap, HashSet};
 use wasm_bindgen::JsCast;
 #[component]
 pub fn LoginPage() -> impl IntoView {
@@ -29,14 +64,51 @@ pub fn LoginPage() -> impl IntoView {
     let password: RwSignal<String> = RwSignal::new(String::new());
     let error: RwSignal<Option<String>> = RwSignal::new(None);
     let loading: RwSignal<bool> = RwSignal::new(false);
+    let remember_me: RwSignal<bool> = RwSignal::new(true);
 
     let app_state = expect_context::<AppContext>();
+    let app_state_sv = StoredValue::new(app_state.clone());
+    let query = use_query_map();
+
+    // Connectivity pre-check, so a wrong API URL shows up before the first login attempt.
+    let server_unreachable: RwSignal<bool> = RwSignal::new(false);
+    let api_url_editing: RwSignal<bool> = RwSignal::new(false);
+    let api_url_value: RwSignal<String> = RwSignal::new(
+        app_state
+            .0
+            .settings
+            .get_untracked()
+            .api_url
+            .unwrap_or_default(),
+    );
+    let api_base_url = move || app_state.0.api_client.with(|c| c.base_url.clone());
+    let check_server = move || {
+        let api_client = app_state.0.api_client.get_untracked();
+        spawn_local(async move {
+            let reachable = api_client.ping().await.is_ok();
+            let _ = server_unreachable.try_set(!reachable);
+        });
+    };
+    check_server();
+
+    // Same override as the Settings page; `set_settings` rebuilds the client for the new URL.
+    let save_api_url = move |api_url: Option<String>| {
+        app_state_sv.with_value(|st| {
+            let mut next = st.0.settings.get_untracked();
+            next.api_url = api_url.filter(|u| !u.trim().is_empty());
+            st.0.set_settings(next);
+        });
+        api_url_editing.set(false);
+        error.set(None);
+        check_server();
+    };
 
     let on_submit = move |ev: web_sys::SubmitEvent| {
         ev.prevent_default();
 
         let email_val = email.get();
         let password_val = password.get();
+        let remember = remember_me.get_untracked();
         let mut api_client = app_state.0.api_client.get_untracked();
 
         loading.set(true);
@@ -46,14 +118,20 @@ pub fn LoginPage() -> impl IntoView {
             match api_client.login(&email_val, &password_val).await {
                 Ok(response) => {
                     api_client.set_token(response.token);
-                    api_client.save_to_storage();
+                    api_client.save_to_storage(remember);
                     save_user_to_storage(&response.hulunote);
                     app_state.0.api_client.set(api_client);
                     app_state.0.current_user.set(Some(response.hulunote));
-                    let _ = window().location().set_href("/");
+                    app_state.0.session_expired.set(false);
+                    let next = query.with_untracked(|q| q.get("next"));
+                    let _ = window().location().set_href(&safe_next_path(next.as_deref()));
                 }
+                // On the login endpoint a 401/403 means bad credentials, not an expired session.
                 Err(e) => {
-                    error.set(Some(e));
+                    if matches!(e, ApiError::Network(_) | ApiError::TimedOut) && !e.is_offline() {
+                        server_unreachable.set(true);
+                    }
+                    error.set(Some(auth_error_message(&e, "Invalid email or password")));
                 }
             }
             loading.set(false);
@@ -74,6 +152,56 @@ pub fn LoginPage() -> impl IntoView {
                     </CardHeader>
 
                     <CardContent>
+                        <Show when=move || server_unreachable.get() fallback=|| ().into_view()>
+                            <Alert class="mb-3 border-amber-500/30">
+                                <AlertDescription class="flex flex-col gap-2 text-xs">
+                                    <span>
+                                        {move || format!(
+                                            "Cannot reach server at {} — check API URL.",
+                                            api_base_url(),
+                                        )}
+                                    </span>
+                                    <Show
+                                        when=move || api_url_editing.get()
+                                        fallback=move || view! {
+                                            <div class="flex items-center gap-3">
+                                                <button
+                                                    type="button"
+                                                    class="text-primary underline underline-offset-4"
+                                                    on:click=move |_| api_url_editing.set(true)
+                                                >
+                                                    "Change API URL"
+                                                </button>
+                                                <button
+                                                    type="button"
+                                                    class="text-primary underline underline-offset-4"
+                                                    on:click=move |_| check_server()
+                                                >
+                                                    "Retry"
+                                                </button>
+                                            </div>
+                                        }
+                                    >
+                                        <form
+                                            class="flex items-center gap-2"
+                                            on:submit=move |ev: web_sys::SubmitEvent| {
+                                                ev.prevent_default();
+                                                save_api_url(Some(api_url_value.get_untracked()));
+                                            }
+                                        >
+                                            <Input
+                                                r#type="url"
+                                                placeholder=EnvConfig::from_env().api_url
+                                                bind_value=api_url_value
+                                                class="h-7 min-w-0 flex-1 text-xs"
+                                            />
+                                            <Button size=ButtonSize::Sm>"Save"</Button>
+                                        </form>
+                                    </Show>
+                                </AlertDescription>
+                            </Alert>
+                        </Show>
+
                         <form class="flex flex-col gap-3" on:submit=on_submit>
                         <div class="flex flex-col gap-1.5">
                             <Label html_for="email" class="text-xs">"Email"</Label>
@@ -99,6 +227,17 @@ pub fn LoginPage() -> impl IntoView {
                             />
                         </div>
 
+                        <label class="flex items-center gap-2 text-xs text-muted-foreground">
+                            <input
+                                id="remember-me"
+                                type="checkbox"
+                                class="h-3.5 w-3.5 accent-primary"
+                                prop:checked=move || remember_me.get()
+                                on:change=move |ev| remember_me.set(event_target_checked(&ev))
+                            />
+                            "Remember me"
+                        </label>
+
                         <Show when=move || error.get().is_some() fallback=|| ().into_view()>
                             {move || {
                                 error.get().map(|e| {
@@ -184,12 +323,40 @@ pub fn RegistrationPage() -> impl IntoView {
                 .signup(&email_val, &username_val, &password_val, &reg_code_val)
                 .await
             {
-                Ok(_response) => {
-                    // Backend returns a token on signup; we keep UX simple and ask user to sign in.
-                    success.set(true);
+                Ok(response) => {
+                    let database = response.database.clone();
+                    let Some((token, user)) = response.into_session() else {
+                        // No session in the response: ask the user to log in.
+                        success.set(true);
+                        loading.set(false);
+                        return;
+                    };
+
+                    // Same as a remembered login.
+                    let mut api_client = api_client;
+                    api_client.set_token(token);
+                    api_client.save_to_storage(true);
+                    save_user_to_storage(&user);
+                    app_state.0.api_client.set(api_client.clone());
+                    app_state.0.current_user.set(Some(user));
+                    app_state.0.session_expired.set(false);
+
+                    // Open the database created with the account, if it can be found by name.
+                    let mut target = "/".to_string();
+                    if let Some(name) = database {
+                        if let Ok(dbs) = api_client.get_database_list().await {
+                            if let Some(db) = find_database_by_name(&dbs, &name) {
+                                target = format!("/db/{}", db.id);
+                            }
+                        }
+                    }
+                    let _ = window().location().set_href(&target);
                 }
                 Err(e) => {
-                    error.set(Some(e));
+                    error.set(Some(auth_error_message(
+                        &e,
+                        "Sign-up was rejected. Check the registration code.",
+                    )));
                 }
             }
             loading.set(false);
@@ -327,214 +494,875 @@ pub fn RegistrationPage() -> impl IntoView {
 pub fn HomeRecentsPage() -> impl IntoView {
     let app_state = expect_context::<AppContext>();
     let actions = expect_context::<DbUiActions>();
+    let navigate = StoredValue::new(use_navigate());
+    let at_db_limit = move || app_state.0.databases.with(|d| d.len() >= MAX_DATABASES);
+    // Only once a load has succeeded: an empty list while loading (or after an error) is
+    // not "no databases".
+    let no_databases = move || {
+        app_state.0.db_load_timestamp.get().is_some()
+            && app_state.0.databases.with(|d| d.is_empty())
+    };
+
+    let recent_dbs: RwSignal<Vec<RecentDb>> = RwSignal::new(load_recent_dbs());
+
+    // "Set as default" on a database card.
+    let app_state_sv = StoredValue::new(app_state.clone());
+    let default_saving: RwSignal<bool> = RwSignal::new(false);
+    let default_error: RwSignal<Option<String>> = RwSignal::new(None);
+    let toggle_default = move |id: String| {
+        if default_saving.get_untracked() {
+            return;
+        }
+        let updates = app_state
+            .0
+            .databases
+            .with_untracked(|dbs| default_database_updates(dbs, &id));
+        let api_client = app_state.0.api_client.get_untracked();
+        default_saving.set(true);
+        default_error.set(None);
+
+        spawn_local(async move {
+            // Apply each step as it lands so a failure halfway leaves the list accurate.
+            for (db_id, is_default) in updates {
+                if let Err(e) = api_client.set_database_default(&db_id, is_default).await {
+                    if e == ApiError::Unauthorized {
+                        app_state_sv.with_value(handle_unauthorized_response);
+                    } else {
+                        default_error.set(Some(e.to_string()));
+                    }
+                    break;
+                }
+                app_state.0.databases.update(|dbs| {
+                    if let Some(d) = dbs.iter_mut().find(|d| d.id == db_id) {
+                        d.is_default = is_default;
+                    }
+                });
+            }
+            default_saving.set(false);
+        });
+    };
+
+    // Drop recents whose database was deleted (possibly from another device).
+    // An empty list usually means "not loaded yet", so never reconcile against it.
+    Effect::new(move |_| {
+        let dbs = app_state.0.databases.get();
+        if dbs.is_empty() {
+            return;
+        }
+        let current = recent_dbs.get_untracked();
+        let next = reconcile_recent_dbs(&current, &dbs);
+        if next.len() != current.len() {
+            save_recent_dbs(&next);
+            recent_dbs.set(next);
+        }
+    });
 
     view! {
         <div class="space-y-3">
-            <div class="space-y-1">
-                <h1 class="text-xl font-semibold">"Databases"</h1>
-            </div>
-
             <Show
-                when=move || app_state.0.databases.get().is_empty()
+                when=move || !recent_dbs.get().is_empty()
                 fallback=|| ().into_view()
             >
-                <div class="text-sm text-muted-foreground">"No databases."</div>
+                <div class="space-y-2">
+                    <h2 class="text-sm font-medium text-muted-foreground">"Recent"</h2>
+                    <div class="grid gap-2 sm:grid-cols-3">
+                        <For
+                            each=move || recent_dbs.get().into_iter().take(3).collect::<Vec<_>>()
+                            key=|r| r.id.clone()
+                            children=move |r: RecentDb| {
+                                // Prefer the server name (the recent entry may predate a rename).
+                                let name = app_state
+                                    .0
+                                    .databases
+                                    .with_untracked(|dbs| {
+                                        dbs.iter().find(|d| d.id == r.id).map(|d| d.name.clone())
+                                    })
+                                    .unwrap_or_else(|| r.name.clone());
+                                let opened = format_relative_ms(r.last_opened_ms, now_ms());
+                                let href = format!("/db/{}", r.id);
+
+                                view! {
+                                    <Card class="flex items-center gap-2 p-3">
+                                        <div class="min-w-0 flex-1">
+                                            <div class="truncate text-sm font-medium">{name.clone()}</div>
+                                            <div class="truncate text-xs text-muted-foreground">{format!("Opened {opened}")}</div>
+                                        </div>
+                                        <Button
+                                            variant=ButtonVariant::Outline
+                                            size=ButtonSize::Sm
+                                            class="h-7 shrink-0 px-2 text-xs"
+                                            attr:aria-label=format!("Open database {name}")
+                                            on:click=move |_| navigate.with_value(|nav| nav(&href, Default::default()))
+                                        >
+                                            "Open"
+                                        </Button>
+                                    </Card>
+                                }
+                            }
+                        />
+                    </div>
+                </div>
             </Show>
 
-            <div class="grid gap-3 sm:grid-cols-2">
-                <For
-                    each=move || app_state.0.databases.get()
-                    key=|db| db.id.clone()
-                    children=move |db| {
-                        let id = db.id.clone();
-                        let name = db.name.clone();
-                        let desc = db.description.clone();
-
-                        let id_for_nav = id.clone();
-                        let id_for_rename = id.clone();
-                        let name_for_rename = name.clone();
-                        let id_for_delete = id.clone();
-                        let name_for_delete = name.clone();
+            <div class="space-y-1">
+                <h1 class="text-xl font-semibold">"Databases"</h1>
+                <Show when=move || default_error.get().is_some() fallback=|| ().into_view()>
+                    <div class="text-xs text-destructive">
+                        {move || default_error.get().unwrap_or_default()}
+                    </div>
+                </Show>
+            </div>
 
-                        view! {
-                            <Card class="group relative h-40 cursor-pointer transition-colors hover:bg-surface-hover hover:ring-1 hover:ring-border">
-                                // Router-native navigation area.
-                                <A
-                                    href={format!("/db/{}", id_for_nav)}
-                                    {..}
-                                    attr:aria-label={format!("Open database {}", name_for_rename)}
-                                    class="block h-full"
-                                >
-                                    <CardHeader class="p-4">
-                                        <CardTitle class="truncate text-sm">{name}</CardTitle>
-                                        <CardDescription class="line-clamp-2 text-xs">{desc}</CardDescription>
-                                    </CardHeader>
-                                </A>
+            <Show
+                when=no_databases
+                fallback=move || view! {
+                    <div class="grid gap-3 sm:grid-cols-2">
+                        <For
+                            each=move || app_state.0.databases.get()
+                            key=|db| db.id.clone()
+                            children=move |db| {
+                                let id = db.id.clone();
+                                let name = db.name.clone();
+                                let desc = db.description.clone();
+
+                                let id_for_nav = id.clone();
+                                let id_for_rename = id.clone();
+                                let name_for_rename = name.clone();
+                                let id_for_delete = id.clone();
+                                let name_for_delete = name.clone();
+                                let id_for_default = id.clone();
+                                let id_for_share = id.clone();
+                                let is_default = Memo::new(move |_| {
+                                    app_state
+                                        .0
+                                        .databases
+                                        .with(|dbs| dbs.iter().any(|d| d.id == id && d.is_default))
+                                });
 
-                                // Actions (outside the <A/>).
-                                <div class="absolute bottom-2 right-2 z-20 flex items-center gap-1 opacity-0 transition-opacity group-hover:opacity-100 hover:opacity-100 focus-within:opacity-100">
-                                    <Button
-                                        variant=ButtonVariant::Ghost
-                                        size=ButtonSize::Icon
-                                        class="h-7 w-7"
-                                        attr:title="Rename"
-                                        on:click=move |ev: web_sys::MouseEvent| {
-                                            ev.stop_propagation();
-                                            actions.open_rename.run((id_for_rename.clone(), name_for_rename.clone()));
-                                        }
-                                    >
-                                        <svg
-                                            xmlns="http://www.w3.org/2000/svg"
-                                            width="16"
-                                            height="16"
-                                            viewBox="0 0 24 24"
-                                            fill="none"
-                                            stroke="currentColor"
-                                            stroke-width="2"
-                                            stroke-linecap="round"
-                                            stroke-linejoin="round"
-                                            class="text-muted-foreground"
-                                            aria-hidden="true"
+                                view! {
+                                    <Card class="group relative h-40 cursor-pointer transition-colors hover:bg-surface-hover hover:ring-1 hover:ring-border">
+                                        // Router-native navigation area.
+                                        <A
+                                            href={format!("/db/{}", id_for_nav)}
+                                            {..}
+                                            attr:aria-label={format!("Open database {}", name_for_rename)}
+                                            class="block h-full"
                                         >
-                                            <path d="M12 20h9" />
-                                            <path d="M16.5 3.5a2.121 2.121 0 0 1 3 3L7 19l-4 1 1-4Z" />
-                                        </svg>
-                                    </Button>
+                                            <CardHeader class="p-4">
+                                                <div class="flex min-w-0 items-center gap-2">
+                                                    <CardTitle class="truncate text-sm">{name}</CardTitle>
+                                                    <Show when=move || is_default.get() fallback=|| ().into_view()>
+                                                        <span class="shrink-0 rounded bg-muted px-1.5 py-0.5 text-[10px] text-muted-foreground">
+                                                            "Default"
+                                                        </span>
+                                                    </Show>
+                                                </div>
+                                                <CardDescription class="line-clamp-2 text-xs">{desc}</CardDescription>
+                                            </CardHeader>
+                                        </A>
 
-                                    <Button
-                                        variant=ButtonVariant::Ghost
-                                        size=ButtonSize::Icon
-                                        class="h-7 w-7 text-destructive"
-                                        attr:title="Delete"
-                                        on:click=move |ev: web_sys::MouseEvent| {
-                                            ev.stop_propagation();
-                                            actions.open_delete.run((id_for_delete.clone(), name_for_delete.clone()));
-                                        }
-                                    >
-                                        <svg
-                                            xmlns="http://www.w3.org/2000/svg"
-                                            width="16"
-                                            height="16"
-                                            viewBox="0 0 24 24"
-                                            fill="none"
-                                            stroke="currentColor"
-                                            stroke-width="2"
-                                            stroke-linecap="round"
-                                            stroke-linejoin="round"
-                                            aria-hidden="true"
-                                        >
-                                            <path d="M3 6h18" />
-                                            <path d="M8 6V4h8v2" />
-                                            <path d="M19 6l-1 14H6L5 6" />
-                                            <path d="M10 11v6" />
-                                            <path d="M14 11v6" />
-                                        </svg>
-                                    </Button>
-                                </div>
-                            </Card>
-                        }
-                    }
-                />
+                                        // Actions (outside the <A/>).
+                                        <div class="absolute bottom-2 right-2 z-20 flex items-center gap-1 opacity-0 transition-opacity group-hover:opacity-100 hover:opacity-100 focus-within:opacity-100">
+                                            <Button
+                                                variant=ButtonVariant::Ghost
+                                                size=ButtonSize::Icon
+                                                class="h-7 w-7"
+                                                attr:title=move || if is_default.get() { "Unset default" } else { "Set as default" }
+                                                attr:aria-pressed=move || if is_default.get() { "true" } else { "false" }
+                                                attr:disabled=move || default_saving.get()
+                                                on:click=move |ev: web_sys::MouseEvent| {
+                                                    ev.stop_propagation();
+                                                    toggle_default(id_for_default.clone());
+                                                }
+                                            >
+                                                <svg
+                                                    xmlns="http://www.w3.org/2000/svg"
+                                                    width="16"
+                                                    height="16"
+                                                    viewBox="0 0 24 24"
+                                                    fill=move || if is_default.get() { "currentColor" } else { "none" }
+                                                    stroke="currentColor"
+                                                    stroke-width="2"
+                                                    stroke-linecap="round"
+                                                    stroke-linejoin="round"
+                                                    class="text-muted-foreground"
+                                                    aria-hidden="true"
+                                                >
+                                                    <path d="M12 2l3.09 6.26L22 9.27l-5 4.87 1.18 6.88L12 17.77l-6.18 3.25L7 14.14 2 9.27l6.91-1.01L12 2z" />
+                                                </svg>
+                                            </Button>
 
-                <Card
-                    class="group relative flex h-40 cursor-pointer items-center justify-center border-dashed transition-colors hover:bg-surface-hover hover:ring-1 hover:ring-border"
-                    on:click=move |_| actions.open_create.run(())
-                >
-                    <div class="flex flex-col items-center gap-2 p-6">
-                        <div class="flex h-10 w-10 items-center justify-center rounded-full border border-border bg-background">
-                            <span class="text-lg text-muted-foreground">"+"</span>
-                        </div>
-                        <div class="text-sm font-medium">"New database"</div>
+                                            <DatabaseShareButton db_id=id_for_share.clone() />
+
+                                            <Button
+                                                variant=ButtonVariant::Ghost
+                                                size=ButtonSize::Icon
+                                                class="h-7 w-7"
+                                                attr:title="Rename"
+                                                on:click=move |ev: web_sys::MouseEvent| {
+                                                    ev.stop_propagation();
+                                                    actions.open_rename.run((id_for_rename.clone(), name_for_rename.clone()));
+                                                }
+                                            >
+                                                <svg
+                                                    xmlns="http://www.w3.org/2000/svg"
+                                                    width="16"
+                                                    height="16"
+                                                    viewBox="0 0 24 24"
+                                                    fill="none"
+                                                    stroke="currentColor"
+                                                    stroke-width="2"
+                                                    stroke-linecap="round"
+                                                    stroke-linejoin="round"
+                                                    class="text-muted-foreground"
+                                                    aria-hidden="true"
+                                                >
+                                                    <path d="M12 20h9" />
+                                                    <path d="M16.5 3.5a2.121 2.121 0 0 1 3 3L7 19l-4 1 1-4Z" />
+                                                </svg>
+                                            </Button>
+
+                                            <Button
+                                                variant=ButtonVariant::Ghost
+                                                size=ButtonSize::Icon
+                                                class="h-7 w-7 text-destructive"
+                                                attr:title="Delete"
+                                                on:click=move |ev: web_sys::MouseEvent| {
+                                                    ev.stop_propagation();
+                                                    actions.open_delete.run((id_for_delete.clone(), name_for_delete.clone()));
+                                                }
+                                            >
+                                                <svg
+                                                    xmlns="http://www.w3.org/2000/svg"
+                                                    width="16"
+                                                    height="16"
+                                                    viewBox="0 0 24 24"
+                                                    fill="none"
+                                                    stroke="currentColor"
+                                                    stroke-width="2"
+                                                    stroke-linecap="round"
+                                                    stroke-linejoin="round"
+                                                    aria-hidden="true"
+                                                >
+                                                    <path d="M3 6h18" />
+                                                    <path d="M8 6V4h8v2" />
+                                                    <path d="M19 6l-1 14H6L5 6" />
+                                                    <path d="M10 11v6" />
+                                                    <path d="M14 11v6" />
+                                                </svg>
+                                            </Button>
+                                        </div>
+                                    </Card>
+                                }
+                            }
+                        />
+
+                        <Card
+                            class="group relative flex h-40 cursor-pointer items-center justify-center border-dashed transition-colors hover:bg-surface-hover hover:ring-1 hover:ring-border aria-disabled:cursor-not-allowed aria-disabled:opacity-50 aria-disabled:hover:bg-transparent aria-disabled:hover:ring-0"
+                            attr:title=move || at_db_limit().then(database_limit_message)
+                            attr:aria-disabled=move || at_db_limit().then_some("true")
+                            on:click=move |_| actions.open_create.run(())
+                        >
+                            <div class="flex flex-col items-center gap-2 p-6">
+                                <div class="flex h-10 w-10 items-center justify-center rounded-full border border-border bg-background">
+                                    <span class="text-lg text-muted-foreground">"+"</span>
+                                </div>
+                                <div class="text-sm font-medium">"New database"</div>
+                            </div>
+                        </Card>
                     </div>
+                }
+            >
+                <Card class="flex flex-col items-center gap-3 border-dashed px-6 py-10 text-center">
+                    <div class="flex h-14 w-14 items-center justify-center rounded-full border border-border bg-muted" aria-hidden="true">
+                        <svg
+                            xmlns="http://www.w3.org/2000/svg"
+                            width="24"
+                            height="24"
+                            viewBox="0 0 24 24"
+                            fill="none"
+                            stroke="currentColor"
+                            stroke-width="2"
+                            stroke-linecap="round"
+                            stroke-linejoin="round"
+                            class="text-muted-foreground"
+                        >
+                            <ellipse cx="12" cy="5" rx="9" ry="3" />
+                            <path d="M3 5v14a9 3 0 0 0 18 0V5" />
+                            <path d="M3 12a9 3 0 0 0 18 0" />
+                        </svg>
+                    </div>
+                    <div class="space-y-1">
+                        <h2 class="text-base font-semibold">"You don't have any databases yet"</h2>
+                        <p class="mx-auto max-w-sm text-sm text-muted-foreground">
+                            "A database holds your notes and daily pages. Create one to start writing."
+                        </p>
+                    </div>
+                    <Button on:click=move |_| actions.open_create.run(())>
+                        "Create your first database"
+                    </Button>
                 </Card>
-            </div>
+            </Show>
         </div>
     }
 }
 
+/// "Share" on a database card: makes the database readable by anyone with the link (no
+/// login, read-only at `/public/:db_id`) or private again, and copies the link.
 #[component]
-pub fn AppLayout(children: ChildrenFn) -> impl IntoView {
+fn DatabaseShareButton(db_id: String) -> impl IntoView {
     let app_state = expect_context::<AppContext>();
+    let app_state_sv = StoredValue::new(app_state.clone());
+    let db_id = StoredValue::new(db_id);
+    let open: RwSignal<bool> = RwSignal::new(false);
+    let saving: RwSignal<bool> = RwSignal::new(false);
+    let copied: RwSignal<bool> = RwSignal::new(false);
+    let error: RwSignal<Option<String>> = RwSignal::new(None);
 
-    let databases = app_state.0.databases;
-    let current_db_id = app_state.0.current_database_id;
-    let sidebar_collapsed = app_state.0.sidebar_collapsed;
-
-    let db_loading: RwSignal<bool> = RwSignal::new(false);
-    let db_error: RwSignal<Option<String>> = RwSignal::new(None);
-
-    // Avoid tight retry loops when backend is down.
-    // Backoff is reset once a request succeeds.
-    let db_retry_delay_ms: RwSignal<u32> = RwSignal::new(500);
-    let db_retry_timer_id: RwSignal<Option<i32>> = RwSignal::new(None);
-    let db_retry_tick: RwSignal<u64> = RwSignal::new(0);
-
-    // If the backend returns an empty database list, that is still a valid "loaded" state.
-    // Without this guard, Effects that try to "load when empty" can re-trigger forever.
-    let db_loaded_once: RwSignal<bool> = RwSignal::new(false);
-
-    // Phase 4: database create dialog state
-    let create_open: RwSignal<bool> = RwSignal::new(false);
-    let create_name: RwSignal<String> = RwSignal::new(String::new());
-    let create_desc: RwSignal<String> = RwSignal::new(String::new());
-    let create_error: RwSignal<Option<String>> = RwSignal::new(None);
-    let create_loading: RwSignal<bool> = RwSignal::new(false);
-
-    // Home sidebar: rename/delete actions (hover)
-    let rename_open: RwSignal<bool> = RwSignal::new(false);
-    let rename_db_id: RwSignal<Option<String>> = RwSignal::new(None);
-    let rename_value: RwSignal<String> = RwSignal::new(String::new());
-    let rename_loading: RwSignal<bool> = RwSignal::new(false);
-    let rename_error: RwSignal<Option<String>> = RwSignal::new(None);
-
-    let delete_open: RwSignal<bool> = RwSignal::new(false);
-    let delete_db_id: RwSignal<Option<String>> = RwSignal::new(None);
-    let delete_db_name: RwSignal<String> = RwSignal::new(String::new());
-    let delete_confirm: RwSignal<String> = RwSignal::new(String::new());
-    let delete_loading: RwSignal<bool> = RwSignal::new(false);
-    let delete_error: RwSignal<Option<String>> = RwSignal::new(None);
-
-    let search_query = app_state.0.search_query;
-    let search_ref: NodeRef<html::Input> = NodeRef::new();
-
-    // Create database dialog: focus name input on open.
-    let create_name_ref: NodeRef<html::Input> = NodeRef::new();
-
-    let navigate = StoredValue::new(use_navigate());
-    let location = use_location();
-    let pathname = move || location.pathname.get();
-    let pathname_untracked = move || location.pathname.get_untracked();
-
-    let sidebar_show_databases = move || {
-        let p = pathname();
-        // On Home, databases are shown in the main area (cards). In a DB, hide databases.
-        !p.starts_with("/db/") && p != "/"
+    let is_public = Memo::new(move |_| {
+        let id = db_id.get_value();
+        app_state
+            .0
+            .databases
+            .with(|dbs| dbs.iter().any(|d| d.id == id && d.is_public))
+    });
+    let url = move || {
+        let origin = window().location().origin().unwrap_or_default();
+        public_database_url(&origin, &db_id.get_value())
     };
 
-    let sidebar_show_recent_notes = move || pathname() == "/";
-
-    let sidebar_show_pages = move || {
-        let p = pathname();
-        p.starts_with("/db/")
+    let on_toggle = move |_| {
+        if saving.get_untracked() {
+            return;
+        }
+        let id = db_id.get_value();
+        let next = !is_public.get_untracked();
+        let api_client = app_state.0.api_client.get_untracked();
+        saving.set(true);
+        error.set(None);
+        spawn_local(async move {
+            match api_client.set_database_public(&id, next).await {
+                Ok(()) => app_state.0.databases.update(|dbs| {
+                    if let Some(d) = dbs.iter_mut().find(|d| d.id == id) {
+                        d.is_public = next;
+                    }
+                }),
+                Err(e) if e == ApiError::Unauthorized => {
+                    app_state_sv.with_value(handle_unauthorized_response)
+                }
+                Err(e) => error.set(Some(e.to_string())),
+            }
+            saving.set(false);
+        });
     };
 
-    let sidebar_width_class = move || {
-        if sidebar_collapsed.get() {
-            "w-14"
-        } else {
-            "w-64"
-        }
+    let on_copy = move |_| {
+        copy_to_clipboard(&url(), move |result| match result {
+            Ok(()) => {
+                copied.set(true);
+                set_timeout(move || copied.set(false), std::time::Duration::from_secs(2));
+            }
+            Err(e) => error.set(Some(format!("Copy failed: {e}"))),
+        });
     };
 
-    let persist_sidebar = move || {
-        if let Some(storage) = web_sys::window().and_then(|w| w.local_storage().ok().flatten()) {
-            let _ = storage.set_item(
+    view! {
+        <Button
+            variant=ButtonVariant::Ghost
+            size=ButtonSize::Icon
+            class="h-7 w-7"
+            attr:title="Share"
+            on:click=move |ev: web_sys::MouseEvent| {
+                ev.stop_propagation();
+                error.set(None);
+                copied.set(false);
+                open.set(true);
+            }
+        >
+            <svg
+                xmlns="http://www.w3.org/2000/svg"
+                width="16"
+                height="16"
+                viewBox="0 0 24 24"
+                fill="none"
+                stroke="currentColor"
+                stroke-width="2"
+                stroke-linecap="round"
+                stroke-linejoin="round"
+                class="text-muted-foreground"
+                aria-hidden="true"
+            >
+                <circle cx="18" cy="5" r="3" />
+                <circle cx="6" cy="12" r="3" />
+                <circle cx="18" cy="19" r="3" />
+                <path d="M8.59 13.51l6.83 3.98" />
+                <path d="M15.41 6.51l-6.82 3.98" />
+            </svg>
+        </Button>
+        <Portal>
+            <Show when=move || open.get() fallback=|| ().into_view()>
+                <div class="fixed inset-0 z-50 flex items-center justify-center bg-black/30 px-4">
+                    <div
+                        class="w-full max-w-sm space-y-3 rounded-md border border-border bg-background p-4 shadow-lg"
+                        role="dialog"
+                        aria-modal="true"
+                        aria-labelledby="share-db-title"
+                        aria-describedby="share-db-desc"
+                        on:keydown=move |ev: web_sys::KeyboardEvent| {
+                            if ev.key() == "Escape" {
+                                ev.stop_propagation();
+                                open.set(false);
+                            } else if let Some(dialog) = ev.current_target().and_then(|t| t.dyn_into().ok()) {
+                                trap_dialog_focus(&ev, &dialog);
+                            }
+                        }
+                    >
+                        <div class="space-y-1">
+                            <div id="share-db-title" class="text-sm font-medium">"Share database"</div>
+                            <div id="share-db-desc" class="text-xs text-muted-foreground">
+                                {move || if is_public.get() {
+                                    "Anyone with the link can read the notes in this database. They cannot edit them."
+                                } else {
+                                    "Only you can see this database. Make it public to get a read-only link."
+                                }}
+                            </div>
+                        </div>
+                        <Show when=move || is_public.get() fallback=|| ().into_view()>
+                            <div class="flex items-center gap-2">
+                                <div class="min-w-0 flex-1 select-all truncate rounded-md border border-border bg-muted px-2 py-1 font-mono text-xs">
+                                    {url}
+                                </div>
+                                <Button
+                                    variant=ButtonVariant::Outline
+                                    size=ButtonSize::Sm
+                                    attr:type="button"
+                                    on:click=on_copy
+                                >
+                                    {move || if copied.get() { "Copied" } else { "Copy" }}
+                                </Button>
+                            </div>
+                        </Show>
+                        {move || error.get().map(|e| view! {
+                            <div class="text-xs text-destructive">{e}</div>
+                        })}
+                        <div class="flex items-center justify-end gap-2">
+                            <Button
+                                variant=ButtonVariant::Outline
+                                size=ButtonSize::Sm
+                                attr:type="button"
+                                on:click=move |_| open.set(false)
+                            >
+                                "Close"
+                            </Button>
+                            <Button
+                                size=ButtonSize::Sm
+                                attr:type="button"
+                                attr:disabled=move || saving.get()
+                                on:click=on_toggle
+                            >
+                                {move || if is_public.get() { "Make private" } else { "Make public" }}
+                            </Button>
+                        </div>
+                    </div>
+                </div>
+            </Show>
+        </Portal>
+    }
+}
+
+/// Create the next free daily note (`YYYYMMDD`, `YYYYMMDD-2`, ...) in `db_id`, filled from
+/// `template` (placeholders expanded now) when one is given.
+///
+/// Shared by the DbHomePage "New" split button and the Cmd/Ctrl+N shortcut.
+async fn create_daily_note(
+    api_client: &crate::api::ApiClient,
+    db_id: &str,
+    existing: &[Note],
+    title_format: NoteTitleFormat,
+    template: Option<&NoteTemplate>,
+) -> Result<Note, ApiError> {
+    let title = next_available_daily_note_title(existing, title_format);
+    let note = match template {
+        Some(t) => {
+            let t = expand_template_now(t);
+            api_client
+                .create_note_from_template(db_id, &title, &t)
+                .await?
+        }
+        None => api_client.create_note(db_id, &title).await?,
+    };
+
+    if note.id.trim().is_empty() {
+        leptos::logging::error!(
+            "create_note succeeded but returned empty note id; refusing to navigate: title={}",
+            title
+        );
+        return Err(ApiError::Parse(
+            "Create note failed: empty note id in response".to_string(),
+        ));
+    }
+
+    Ok(note)
+}
+
+/// Round avatar with the account's initials on a color derived from the username.
+#[component]
+fn AccountAvatar(user: AccountInfo) -> impl IntoView {
+    let style = format!("background-color: hsl({} 55% 45%)", user.avatar_hue());
+    view! {
+        <div
+            class="flex h-7 w-7 shrink-0 items-center justify-center rounded-full text-[11px] font-medium text-white"
+            style=style
+            aria-hidden="true"
+        >
+            {user.initials()}
+        </div>
+    }
+}
+
+/// Note title matches shown under the sidebar search input. `highlighted` follows the input's
+/// arrow keys; picking a row (click or Enter) is left to `on_pick`.
+#[component]
+fn SearchSuggestions(
+    suggestions: Memo<Vec<Note>>,
+    highlighted: RwSignal<Option<usize>>,
+    on_pick: Callback<Note>,
+) -> impl IntoView {
+    view! {
+        <Show when=move || suggestions.with(|xs| !xs.is_empty())>
+            <div
+                id="search-suggestions"
+                role="listbox"
+                aria-label="Matching notes"
+                class="absolute inset-x-0 top-full z-20 mt-1 rounded-md border border-border bg-background p-1 shadow-lg"
+            >
+                {move || {
+                    suggestions
+                        .get()
+                        .into_iter()
+                        .enumerate()
+                        .map(|(i, n)| {
+                            let title = get_title_override(&n.database_id, &n.id, &n.title);
+                            let is_highlighted = move || highlighted.get() == Some(i);
+                            view! {
+                                <div
+                                    id=format!("search-suggestion-{i}")
+                                    role="option"
+                                    aria-selected=move || is_highlighted().to_string()
+                                    class=move || {
+                                        if is_highlighted() {
+                                            "cursor-pointer truncate rounded-sm bg-accent px-2 py-1.5 text-sm"
+                                        } else {
+                                            "cursor-pointer truncate rounded-sm px-2 py-1.5 text-sm"
+                                        }
+                                    }
+                                    // Before the input's blur hides the list.
+                                    on:mousedown=move |ev: web_sys::MouseEvent| {
+                                        ev.prevent_default();
+                                        on_pick.run(n.clone());
+                                    }
+                                    on:mousemove=move |_| highlighted.set(Some(i))
+                                >
+                                    {title}
+                                </div>
+                            }
+                        })
+                        .collect_view()
+                }}
+            </div>
+        </Show>
+    }
+}
+
+/// Focusable controls inside a modal, for the Tab focus trap.
+const DIALOG_FOCUSABLE: &str = "input:not([disabled]), textarea:not([disabled]), select:not([disabled]), button:not([disabled]), a[href], [tabindex]:not([tabindex='-1'])";
+
+/// Keep Tab / Shift+Tab cycling inside `dialog` (keydown handler of a modal panel).
+fn trap_dialog_focus(ev: &web_sys::KeyboardEvent, dialog: &web_sys::HtmlElement) {
+    if ev.key() != "Tab" {
+        return;
+    }
+    let Ok(list) = dialog.query_selector_all(DIALOG_FOCUSABLE) else {
+        return;
+    };
+    let items: Vec<web_sys::HtmlElement> = (0..list.length())
+        .filter_map(|i| list.get(i))
+        .filter_map(|n| n.dyn_into::<web_sys::HtmlElement>().ok())
+        .collect();
+    let (Some(first), Some(last)) = (items.first(), items.last()) else {
+        ev.prevent_default();
+        return;
+    };
+
+    let active = document().active_element();
+    let at = |el: &web_sys::HtmlElement| {
+        active
+            .as_ref()
+            .is_some_and(|a| a.is_same_node(Some(el.as_ref())))
+    };
+    let inside = active
+        .as_ref()
+        .is_some_and(|a| dialog.contains(Some(a.as_ref())));
+    if ev.shift_key() && (at(first) || !inside) {
+        ev.prevent_default();
+        let _ = last.focus();
+    } else if !ev.shift_key() && (at(last) || !inside) {
+        ev.prevent_default();
+        let _ = first.focus();
+    }
+}
+
+#[component]
+pub fn AppLayout(children: ChildrenFn) -> impl IntoView {
+    let app_state = expect_context::<AppContext>();
+
+    let databases = app_state.0.databases;
+    let current_db_id = app_state.0.current_database_id;
+    let sidebar_collapsed = app_state.0.sidebar_collapsed;
+    // Copyable handle for event handlers that need `AppState` methods.
+    let app_state_sv = StoredValue::new(app_state.clone());
+    // Sidebar Pages row currently being renamed inline (note id).
+    let sidebar_renaming: RwSignal<Option<String>> = RwSignal::new(None);
+
+    let db_loading: RwSignal<bool> = RwSignal::new(false);
+    let db_error: RwSignal<Option<String>> = RwSignal::new(None);
+
+    // Avoid tight retry loops when backend is down: failures back off and eventually stop
+    // (see `with_retry`). Each load bumps the run id so a fresh load supersedes a pending retry.
+    let db_retry_state: RwSignal<RetryState> = RwSignal::new(RetryState::Idle);
+    let db_load_run: StoredValue<u64> = StoredValue::new(0);
+
+    // If the backend returns an empty database list, that is still a valid "loaded" state.
+    // Without this guard, Effects that try to "load when empty" can re-trigger forever.
+    // A list fetched by an earlier mount counts too.
+    let db_loaded_once: RwSignal<bool> =
+        RwSignal::new(app_state.0.db_load_timestamp.get_untracked().is_some());
+
+    // Phase 4: database create dialog state
+    let create_open: RwSignal<bool> = RwSignal::new(false);
+    let create_name: RwSignal<String> = RwSignal::new(String::new());
+    let create_desc: RwSignal<String> = RwSignal::new(String::new());
+    let create_error: RwSignal<Option<String>> = RwSignal::new(None);
+    let create_loading: RwSignal<bool> = RwSignal::new(false);
+
+    // Home sidebar: rename/delete actions (hover)
+    let rename_open: RwSignal<bool> = RwSignal::new(false);
+    let rename_db_id: RwSignal<Option<String>> = RwSignal::new(None);
+    let rename_value: RwSignal<String> = RwSignal::new(String::new());
+    let rename_loading: RwSignal<bool> = RwSignal::new(false);
+    let rename_error: RwSignal<Option<String>> = RwSignal::new(None);
+
+    let delete_open: RwSignal<bool> = RwSignal::new(false);
+    let delete_db_id: RwSignal<Option<String>> = RwSignal::new(None);
+    let delete_db_name: RwSignal<String> = RwSignal::new(String::new());
+    let delete_confirm: RwSignal<String> = RwSignal::new(String::new());
+    let delete_loading: RwSignal<bool> = RwSignal::new(false);
+    let delete_error: RwSignal<Option<String>> = RwSignal::new(None);
+
+    // The element focused when a database dialog opened; focus returns there on close.
+    let dialog_trigger = StoredValue::new_local(None::<web_sys::HtmlElement>);
+    let remember_dialog_trigger = move || {
+        let el = document()
+            .active_element()
+            .and_then(|el| el.dyn_into::<web_sys::HtmlElement>().ok());
+        dialog_trigger.set_value(el);
+    };
+
+    let search_query = app_state.0.search_query;
+    // Sidebar Pages list, narrowed by the DbHomePage date filter.
+    let notes_filter = app_state.0.notes_filter;
+    let all_notes = app_state.0.notes;
+    let date_filtered_notes = Memo::new(move |_| {
+        let bounds = note_filter_bounds_now(&notes_filter.get());
+        all_notes.with(|ns| filter_notes_by_updated(ns, bounds.as_ref()))
+    });
+    let databases_search = app_state.0.databases_search;
+    let filtered_databases = Memo::new(move |_| {
+        let q = databases_search.get();
+        databases.with(|dbs| filter_databases(dbs, &q))
+    });
+    let search_ref: NodeRef<html::Input> = NodeRef::new();
+
+    // Cmd/Ctrl+N: in-flight guard for daily note creation.
+    let new_note_creating: RwSignal<bool> = RwSignal::new(false);
+
+    // Create database dialog: focus name input on open.
+    let create_name_ref: NodeRef<html::Input> = NodeRef::new();
+    let rename_name_ref: NodeRef<html::Input> = NodeRef::new();
+    let delete_confirm_ref: NodeRef<html::Input> = NodeRef::new();
+
+    let navigate = StoredValue::new(use_navigate());
+    let location = use_location();
+
+    // Sidebar search: note title suggestions under the input, picked with the arrow keys.
+    let search_focused = RwSignal::new(false);
+    let search_highlight: RwSignal<Option<usize>> = RwSignal::new(None);
+    let search_suggestions = Memo::new(move |_| {
+        if !search_focused.get() {
+            return vec![];
+        }
+        let db_id = current_db_id.get().unwrap_or_default();
+        let q = search_query.get();
+        all_notes.with(|ns| search_title_suggestions(ns, &db_id, &q))
+    });
+    Effect::new(move |_| {
+        search_query.track();
+        search_highlight.set(None);
+    });
+    let open_suggestion = Callback::new(move |n: Note| {
+        search_query.set(String::new());
+        search_highlight.set(None);
+        let href = format!("/db/{}/note/{}", n.database_id, n.id);
+        navigate.with_value(|nav| nav(&href, Default::default()));
+    });
+    let pathname = move || location.pathname.get();
+    let pathname_untracked = move || location.pathname.get_untracked();
+
+    let sidebar_show_databases = move || {
+        let p = pathname();
+        // On Home, databases are shown in the main area (cards). In a DB, hide databases.
+        !p.starts_with("/db/") && p != "/"
+    };
+
+    let sidebar_show_recent_notes = move || pathname() == "/";
+
+    let sidebar_show_pages = move || {
+        let p = pathname();
+        p.starts_with("/db/")
+    };
+
+    // Browser tab title: current note and database, "• " while nav edits wait to sync. The
+    // note title is simply left out until the notes list has loaded.
+    let drafts_version = expect_context::<crate::state::NoteSyncController>().drafts_version();
+    let pending_drafts = Memo::new(move |_| {
+        drafts_version.track();
+        count_unsynced_nav_drafts() > 0
+    });
+    Effect::new(move |_| {
+        let path = pathname();
+        let db_name = db_id_from_path(&path).and_then(|id| {
+            databases.with(|dbs| dbs.iter().find(|d| d.id == id).map(|d| d.name.clone()))
+        });
+        let note_title = note_id_from_path(&path).and_then(|id| {
+            app_state
+                .0
+                .notes
+                .with(|ns| ns.iter().find(|n| n.id == id).map(|n| n.title.clone()))
+        });
+        document().set_title(&document_title(
+            note_title.as_deref(),
+            db_name.as_deref(),
+            pending_drafts.get(),
+        ));
+    });
+
+    // Closing or reloading the tab while nav edits haven't reached the server: let the
+    // browser ask first (the sync controller flushes on pagehide). In-app navigation never
+    // unloads the page, so it doesn't prompt.
+    let _beforeunload_handle =
+        window_event_listener(ev::beforeunload, move |ev: web_sys::BeforeUnloadEvent| {
+            if pending_drafts.try_get_untracked() == Some(true) {
+                ev.prevent_default();
+                ev.set_return_value("");
+            }
+        });
+
+    // `[[` autocomplete caches page titles: reload them when a note is created, renamed or
+    // deleted (the first run only records the initial list).
+    let note_titles = Memo::new(move |_| {
+        app_state.0.notes.with(|ns| {
+            let mut titles = ns
+                .iter()
+                .map(|n| (n.id.clone(), n.title.clone()))
+                .collect::<Vec<_>>();
+            titles.sort();
+            titles
+        })
+    });
+    Effect::new(move |prev: Option<()>| {
+        note_titles.track();
+        if prev.is_some() {
+            app_state_sv.with_value(|st| st.0.invalidate_titles_cache());
+        }
+    });
+
+    // Tags panel: keep `tags_index` in step with the current database's navs, fetching them
+    // into `all_navs_cache` when no page has loaded them yet.
+    let tags_index = app_state.0.tags_index;
+    let all_navs_cache = app_state.0.all_navs_cache;
+    let tags_navs_loading: RwSignal<bool> = RwSignal::new(false);
+    Effect::new(move |_| {
+        let db_id = current_db_id.get().unwrap_or_default();
+        if !sidebar_show_pages() || db_id.trim().is_empty() {
+            return;
+        }
+
+        let index = all_navs_cache.with(|m| m.get(&db_id).map(|navs| build_tags_index(navs)));
+        if let Some(index) = index {
+            tags_index.set(index);
+            return;
+        }
+
+        tags_index.set(HashMap::new());
+        if tags_navs_loading.get_untracked() {
+            return;
+        }
+        tags_navs_loading.set(true);
+        let api_client = app_state.0.api_client.get_untracked();
+        spawn_local(async move {
+            if let Ok(navs) = api_client.get_all_navs(&db_id).await {
+                all_navs_cache.update(|m| {
+                    m.insert(db_id, navs);
+                });
+            }
+            tags_navs_loading.set(false);
+        });
+    });
+
+    // Note previews for the Pages list (current database) and Recent Notes (their databases).
+    Effect::new(move |_| {
+        let db_id = current_db_id.get().unwrap_or_default();
+        if sidebar_show_pages() {
+            app_state_sv.with_value(|st| load_note_previews(st, &db_id));
+        }
+    });
+    Effect::new(move |_| {
+        if !sidebar_show_recent_notes() {
+            return;
+        }
+        let db_ids = load_recent_notes()
+            .into_iter()
+            .map(|n| n.db_id)
+            .collect::<HashSet<_>>();
+        app_state_sv.with_value(|st| {
+            for db_id in db_ids {
+                load_note_previews(st, &db_id);
+            }
+        });
+    });
+
+    let sidebar_width_class = move || {
+        if sidebar_collapsed.get() {
+            "w-14"
+        } else {
+            "w-64"
+        }
+    };
+
+    let persist_sidebar = move || {
+        if let Some(storage) = web_sys::window().and_then(|w| w.local_storage().ok().flatten()) {
+            let _ = storage.set_item(
                 SIDEBAR_COLLAPSED_KEY,
                 if sidebar_collapsed.get() { "1" } else { "0" },
             );
         }
     };
 
+    // Focus mode only applies on note routes; elsewhere the flag just waits.
+    let focus_mode = app_state.0.focus_mode;
+    let in_focus_mode = move || focus_mode.get() && pathname().contains("/note/");
+    let toggle_focus_mode = move || focus_mode.update(|v| *v = !*v);
+
     let set_current_db = move |id: Option<String>| {
         current_db_id.set(id.clone());
         if let Some(storage) = web_sys::window().and_then(|w| w.local_storage().ok().flatten()) {
@@ -543,7 +1371,10 @@ pub fn AppLayout(children: ChildrenFn) -> impl IntoView {
         }
     };
 
+    let at_db_limit = move || app_state.0.databases.with(|d| d.len() >= MAX_DATABASES);
+
     let open_create_dialog = move || {
+        remember_dialog_trigger();
         create_name.set(String::new());
         create_desc.set(String::new());
         create_error.set(None);
@@ -562,16 +1393,22 @@ pub fn AppLayout(children: ChildrenFn) -> impl IntoView {
         });
     };
 
-    // Focus the create-db name input when the dialog opens.
+    // Focus the first input of a database dialog when it opens.
     Effect::new(move |_| {
-        if !create_open.get() {
+        let input_ref = if create_open.get() {
+            create_name_ref
+        } else if rename_open.get() {
+            rename_name_ref
+        } else if delete_open.get() {
+            delete_confirm_ref
+        } else {
             return;
-        }
+        };
 
         // Defer to next tick so the Input is mounted.
         let _ = window().set_timeout_with_callback_and_timeout_and_arguments_0(
             wasm_bindgen::closure::Closure::once_into_js(move || {
-                if let Some(el) = create_name_ref.get_untracked() {
+                if let Some(el) = input_ref.get_untracked() {
                     let _ = el.focus();
                 }
             })
@@ -581,7 +1418,20 @@ pub fn AppLayout(children: ChildrenFn) -> impl IntoView {
         );
     });
 
+    // ...and give focus back to whatever opened it once it closes.
+    Effect::new(move |was_open: Option<bool>| {
+        let open = create_open.get() || rename_open.get() || delete_open.get();
+        if was_open == Some(true) && !open {
+            if let Some(el) = dialog_trigger.get_value() {
+                let _ = el.focus();
+            }
+            dialog_trigger.set_value(None);
+        }
+        open
+    });
+
     let on_open_rename_db = move |id: String, name: String| {
+        remember_dialog_trigger();
         rename_db_id.set(Some(id));
         rename_value.set(name);
         rename_error.set(None);
@@ -613,13 +1463,14 @@ pub fn AppLayout(children: ChildrenFn) -> impl IntoView {
                     refresh_databases();
                     rename_open.set(false);
                 }
-                Err(e) => rename_error.set(Some(e)),
+                Err(e) => rename_error.set(Some(e.to_string())),
             }
             rename_loading.set(false);
         });
     };
 
     let on_open_delete_db = move |id: String, name: String| {
+        remember_dialog_trigger();
         delete_db_id.set(Some(id));
         delete_db_name.set(name);
         delete_confirm.set(String::new());
@@ -629,7 +1480,11 @@ pub fn AppLayout(children: ChildrenFn) -> impl IntoView {
 
     // Expose DB actions to pages (e.g. Home database cards).
     provide_context(DbUiActions {
-        open_create: Callback::new(move |_| open_create_dialog()),
+        open_create: Callback::new(move |_| {
+            if !at_db_limit() {
+                open_create_dialog();
+            }
+        }),
         open_rename: Callback::new(move |(id, name)| on_open_rename_db(id, name)),
         open_delete: Callback::new(move |(id, name)| on_open_delete_db(id, name)),
     });
@@ -672,7 +1527,7 @@ pub fn AppLayout(children: ChildrenFn) -> impl IntoView {
                         set_current_db(None);
                     }
                 }
-                Err(e) => delete_error.set(Some(e)),
+                Err(e) => delete_error.set(Some(e.to_string())),
             }
             delete_loading.set(false);
         });
@@ -683,6 +1538,11 @@ pub fn AppLayout(children: ChildrenFn) -> impl IntoView {
             return;
         }
 
+        if app_state.0.databases.with_untracked(|d| d.len() >= MAX_DATABASES) {
+            create_error.set(Some(database_limit_message()));
+            return;
+        }
+
         let name = create_name.get_untracked();
         if name.trim().is_empty() {
             create_error.set(Some("Database name is required".to_string()));
@@ -697,75 +1557,115 @@ pub fn AppLayout(children: ChildrenFn) -> impl IntoView {
 
         spawn_local(async move {
             match api_client.create_database(&name, &desc).await {
-                Ok(v) => {
-                    // Try to extract the created database id from the response.
-                    let new_id = v
-                        .get("database")
-                        .and_then(|d| {
-                            d.get("hulunote-databases/id")
-                                .or_else(|| d.get("id"))
-                                .and_then(|x| x.as_str())
-                        })
-                        .map(|s| s.to_string());
-
-                    // Refresh DB list from backend.
-                    let mut c = app_state.0.api_client.get_untracked();
-                    match c.get_database_list().await {
-                        Ok(dbs) => {
-                            app_state.0.databases.set(dbs);
-                            app_state.0.api_client.set(c);
+                Ok(created) => {
+                    // Show the new database right away; the list refresh below reconciles it.
+                    let created = created.map(|mut db| {
+                        if db.name.trim().is_empty() {
+                            db.name = name.clone();
                         }
-                        Err(_) => {
-                            app_state.0.api_client.set(c);
+                        if db.description.is_empty() {
+                            db.description = desc.clone();
                         }
-                    }
-
-                    if let Some(id) = new_id {
-                        set_current_db(Some(id.clone()));
-                        // Navigate to the new database home.
-                        // We cannot call navigate directly here; store selection and rely on caller UI.
-                        // (navigation is triggered below on the main thread)
+                        db
+                    });
+                    if let Some(db) = created.clone() {
+                        app_state.0.databases.update(|dbs| {
+                            if !dbs.iter().any(|d| d.id == db.id) {
+                                dbs.push(db);
+                            }
+                        });
+                        set_current_db(Some(db.id.clone()));
                         navigate.with_value(|nav| {
-                            nav(&format!("/db/{}", id), Default::default());
+                            nav(&format!("/db/{}", db.id), Default::default());
                         });
                     }
-
                     create_open.set(false);
+
+                    // Refresh DB list from backend, keeping the new database if the list lags.
+                    let mut c = app_state.0.api_client.get_untracked();
+                    if let Ok(mut dbs) = c.get_database_list().await {
+                        if let Some(db) = created {
+                            if !dbs.iter().any(|d| d.id == db.id) {
+                                dbs.push(db);
+                            }
+                        }
+                        app_state.0.databases.set(dbs);
+                    }
+                    app_state.0.api_client.set(c);
+                }
+                Err(e) if is_database_limit_error(&e) => {
+                    create_error.set(Some(database_limit_message()));
                 }
                 Err(e) => {
-                    create_error.set(Some(e));
+                    create_error.set(Some(e.to_string()));
                 }
             }
             create_loading.set(false);
         });
     };
 
-    let load_databases = move || {
-        // Avoid parallel loads.
-        if db_loading.get_untracked() {
+    // `silent`: a background revalidation of a list already on screen; failures keep the
+    // stale list without an error.
+    let load_databases = move |silent: bool| {
+        // Avoid parallel loads; a run that is only waiting out a backoff may be replaced.
+        let waiting_to_retry = matches!(
+            db_retry_state.get_untracked(),
+            RetryState::Retrying { .. }
+        );
+        if db_loading.get_untracked() && !waiting_to_retry {
             return;
         }
 
-        // Clear any scheduled retry; a manual/triggered call should run immediately.
-        if let Some(id) = db_retry_timer_id.get_untracked() {
-            let _ = window().clear_timeout_with_handle(id);
-            db_retry_timer_id.set(None);
-        }
-
-        let mut api_client = app_state.0.api_client.get_untracked();
-        if !api_client.is_authenticated() {
+        let api_client = app_state.0.api_client.get_untracked();
+        if !api_client.has_valid_token() {
             return;
         }
 
+        db_load_run.update_value(|x| *x = x.saturating_add(1));
+        let run = db_load_run.get_value();
+        let is_stale = move || db_load_run.get_value() != run;
+
         db_loading.set(true);
         db_error.set(None);
+        db_retry_state.set(RetryState::Idle);
 
         spawn_local(async move {
-            match api_client.get_database_list().await {
-                Ok(dbs) => {
-                    // Success: reset backoff.
-                    db_retry_delay_ms.set(500);
+            // `get_database_list` may refresh the client, so each attempt hands it back.
+            let fetch = || {
+                let mut c = api_client.clone();
+                async move { c.get_database_list().await.map(|dbs| (dbs, c)) }
+            };
+            // A background refresh makes one attempt; the next stale mount tries again.
+            let result = if silent {
+                fetch().await
+            } else {
+                with_retry(
+                    RetryPolicy::LIST_FETCH,
+                    db_retry_state,
+                    ApiError::is_transient,
+                    is_stale,
+                    fetch,
+                )
+                .await
+            };
+            if is_stale() {
+                return;
+            }
+
+            match result {
+                Ok((dbs, api_client)) => {
+                    // After login (a full reload) or on app start, land in the default
+                    // database instead of the Home grid.
+                    let first_load = app_state.0.db_load_timestamp.get_untracked().is_none();
+                    if first_load && pathname_untracked() == "/" {
+                        if let Some(db) = dbs.iter().find(|d| d.is_default) {
+                            let href = format!("/db/{}", db.id);
+                            navigate.with_value(|nav| nav(&href, Default::default()));
+                        }
+                    }
+
                     db_loaded_once.set(true);
+                    app_state.0.db_load_timestamp.set(Some(now_ms() as f64));
 
                     // Update app state.
                     app_state.0.databases.set(dbs.clone());
@@ -775,7 +1675,7 @@ pub fn AppLayout(children: ChildrenFn) -> impl IntoView {
                     // If a recent note's database or note-id no longer exists, remove it.
                     // On network errors, keep local recents (avoid destructive loss when offline).
                     spawn_local(async move {
-                        use std::collections::{HashMap, HashSet};
+                        use std::collections::HashSet;
 
                         let mut recents = load_recent_notes();
                         if recents.is_empty() {
@@ -792,21 +1692,26 @@ pub fn AppLayout(children: ChildrenFn) -> impl IntoView {
                         let unique_db_ids: HashSet<String> =
                             recents.iter().map(|n| n.db_id.clone()).collect();
 
-                        let mut note_ids_by_db: HashMap<String, HashSet<String>> = HashMap::new();
+                        let mut existing_notes: Vec<Note> = vec![];
+                        let mut unchecked_db_ids: HashSet<String> = HashSet::new();
                         for db_id in unique_db_ids {
-                            if let Ok(notes) = api_client.get_all_note_list(&db_id).await {
-                                let set: HashSet<String> =
-                                    notes.into_iter().map(|n| n.id).collect();
-                                note_ids_by_db.insert(db_id, set);
+                            match api_client.get_all_note_list(&db_id).await {
+                                Ok(notes) => existing_notes.extend(notes),
+                                Err(_) => {
+                                    unchecked_db_ids.insert(db_id);
+                                }
                             }
                         }
 
+                        // Only prune entries whose database list was fetched.
                         let before = recents.len();
+                        let kept: HashSet<String> =
+                            prune_recent_notes_not_in(&existing_notes, recents.clone())
+                                .into_iter()
+                                .map(|n| n.note_id)
+                                .collect();
                         recents.retain(|n| {
-                            note_ids_by_db
-                                .get(&n.db_id)
-                                .map(|set| set.contains(&n.note_id))
-                                .unwrap_or(true)
+                            unchecked_db_ids.contains(&n.db_id) || kept.contains(&n.note_id)
                         });
 
                         if recents.len() != before {
@@ -815,34 +1720,10 @@ pub fn AppLayout(children: ChildrenFn) -> impl IntoView {
                     });
                 }
                 Err(e) => {
-                    if e == "Unauthorized" {
-                        api_client.logout();
-                        app_state.0.api_client.set(api_client);
-                        app_state.0.current_user.set(None);
-                        let _ = window().location().set_href("/login");
-                    } else {
-                        // Failure: schedule retry with exponential backoff.
-                        let delay = db_retry_delay_ms.get_untracked().min(30_000);
-                        db_error.set(Some(format!(
-                            "Backend not reachable. Retrying in {:.1}s (or click ↻).\n{}",
-                            delay as f32 / 1000.0,
-                            e
-                        )));
-
-                        let next_delay = (delay.saturating_mul(2)).min(30_000);
-                        db_retry_delay_ms.set(next_delay);
-
-                        // Schedule the retry on the UI thread.
-                        let cb = wasm_bindgen::closure::Closure::once_into_js(move || {
-                            db_retry_tick.update(|x| *x = x.saturating_add(1));
-                        });
-                        let id = window()
-                            .set_timeout_with_callback_and_timeout_and_arguments_0(
-                                cb.as_ref().unchecked_ref(),
-                                delay as i32,
-                            )
-                            .unwrap_or(0);
-                        db_retry_timer_id.set(Some(id));
+                    if e == ApiError::Unauthorized {
+                        app_state_sv.with_value(handle_unauthorized_response);
+                    } else if !silent {
+                        db_error.set(Some(e.to_string()));
 
                         // NOTE: do not set api_client back into reactive state here.
                         // On transient network failures it is unchanged, but setting it would
@@ -855,11 +1736,9 @@ pub fn AppLayout(children: ChildrenFn) -> impl IntoView {
     };
 
     // Initial load when we enter the authenticated shell.
-    // Also used as the single place that triggers retries (via db_retry_tick) to avoid tight loops.
+    // Retries happen inside `load_databases`, so nothing here re-triggers on failure.
     Effect::new(move |_| {
-        let _tick = db_retry_tick.get();
-
-        let authed = app_state.0.api_client.get().is_authenticated();
+        let authed = app_state.0.api_client.get().has_valid_token();
         if !authed {
             return;
         }
@@ -871,8 +1750,33 @@ pub fn AppLayout(children: ChildrenFn) -> impl IntoView {
         }
 
         if !db_loaded_once.get_untracked() {
-            load_databases();
+            load_databases(false);
+        } else if databases_need_refresh(
+            app_state.0.db_load_timestamp.get_untracked(),
+            now_ms() as f64,
+        ) {
+            // Stale-while-revalidate: keep showing the list, swap in the fresh one on arrival.
+            load_databases(true);
+        }
+    });
+
+    // Reconnected: don't wait out the backoff, retry the database list right away.
+    let is_online = app_state.0.is_online;
+    Effect::new(move |prev: Option<bool>| {
+        let online = is_online.get();
+        if online && prev == Some(false) && !db_loaded_once.get_untracked() {
+            load_databases(false);
+        }
+        online
+    });
+
+    // Switching databases resets the sidebar database filter.
+    Effect::new(move |prev: Option<Option<String>>| {
+        let id = current_db_id.get();
+        if prev.is_some_and(|p| p != id) {
+            databases_search.set(String::new());
         }
+        id
     });
 
     // If there is no selection yet, we only pick a default when the user is inside a DB route.
@@ -894,19 +1798,149 @@ pub fn AppLayout(children: ChildrenFn) -> impl IntoView {
         persist_sidebar();
     };
 
+    let app_state_for_new_note = app_state.clone();
+    let create_note_from_shortcut = move || {
+        if new_note_creating.get_untracked() {
+            return;
+        }
+        let Some(db_id) = db_id_from_path(&pathname_untracked()) else {
+            return;
+        };
+
+        new_note_creating.set(true);
+        let app_state = app_state_for_new_note.clone();
+        spawn_local(async move {
+            let api_client = app_state.0.api_client.get_untracked();
+            let notes = app_state.0.notes.get_untracked();
+            let title_format = app_state.0.settings.with_untracked(|s| s.new_note_title);
+            match create_daily_note(&api_client, &db_id, &notes, title_format, None).await {
+                Ok(note) => {
+                    if let Ok(notes) = api_client.get_all_note_list(&db_id).await {
+                        app_state.0.notes.set(notes);
+                    }
+                    navigate.with_value(|nav| {
+                        nav(&format!("/db/{}/note/{}", db_id, note.id), Default::default());
+                    });
+                }
+                Err(e) => {
+                    if e == ApiError::Unauthorized {
+                        handle_unauthorized_response(&app_state);
+                    } else {
+                        leptos::logging::error!("Cmd/Ctrl+N: create note failed: {}", e);
+                    }
+                }
+            }
+            new_note_creating.set(false);
+        });
+    };
+
+    // "Today": open the daily note (`YYYYMMDD`) for the current database, creating it if needed.
+    let today_opening: RwSignal<bool> = RwSignal::new(false);
+    let open_today_note = move || {
+        if today_opening.get_untracked() {
+            return;
+        }
+        let Some(db_id) = current_db_id
+            .get_untracked()
+            .or_else(|| databases.with_untracked(|dbs| dbs.first().map(|d| d.id.clone())))
+        else {
+            return;
+        };
+
+        let title = today_yyyymmdd_local();
+        today_opening.set(true);
+        let app_state = app_state_sv.get_value();
+        spawn_local(async move {
+            let api_client = app_state.0.api_client.get_untracked();
+
+            // `notes` may hold only the first page; check the full list before creating.
+            let mut existing = app_state
+                .0
+                .notes
+                .with_untracked(|xs| find_note_by_title(xs, &db_id, &title).cloned());
+            if existing.is_none() {
+                if let Ok(all) = api_client.get_all_note_list(&db_id).await {
+                    existing = find_note_by_title(&all, &db_id, &title).cloned();
+                }
+            }
+
+            let result = match existing {
+                Some(note) => Ok(note),
+                None => match api_client.create_note(&db_id, &title).await {
+                    Ok(note) => {
+                        if let Ok(notes) = api_client.get_all_note_list(&db_id).await {
+                            app_state.0.notes.set(notes);
+                        }
+                        Ok(note)
+                    }
+                    Err(e) => Err(e),
+                },
+            };
+
+            match result {
+                Ok(note) if !note.id.trim().is_empty() => {
+                    navigate.with_value(|nav| {
+                        nav(&format!("/db/{}/note/{}", db_id, note.id), Default::default());
+                    });
+                }
+                Ok(_) => {
+                    leptos::logging::error!("Today: create note returned an empty note id");
+                }
+                Err(e) if e == ApiError::Unauthorized => {
+                    handle_unauthorized_response(&app_state);
+                }
+                Err(e) => {
+                    leptos::logging::error!("Today: open daily note failed: {}", e);
+                }
+            }
+            today_opening.set(false);
+        });
+    };
+
+    let palette_open: RwSignal<bool> = RwSignal::new(false);
+    // Shared with the command palette's "New note".
+    let create_note_from_shortcut = Callback::new(move |()| create_note_from_shortcut());
+
     // Keyboard shortcuts (Phase 3):
     // - Cmd/Ctrl+B: toggle sidebar
     // - Cmd/Ctrl+K: focus search
+    // - Cmd/Ctrl+P: command palette (notes, databases, commands)
+    // - Cmd/Ctrl+Shift+D: database switcher
+    // - Cmd/Ctrl+Shift+F: focus mode (note routes)
+    // - Cmd/Ctrl+N: new daily note (inside /db/:db_id routes)
+    // - Cmd/Ctrl+D: open (or create) today's note
+    // - Cmd/Ctrl+Shift+[ / ]: collapse top-level blocks / expand all (note routes)
     // - Esc: blur search
     let _key_handle = window_event_listener(ev::keydown, move |ev: web_sys::KeyboardEvent| {
         let is_meta = ev.meta_key() || ev.ctrl_key();
         let key = ev.key().to_lowercase();
 
+        // Works from anywhere (including inputs); also keeps the browser's print dialog away.
+        if is_meta && key == "p" {
+            ev.prevent_default();
+            palette_open.set(true);
+            return;
+        }
+
+        // Also from anywhere; checked before Cmd/Ctrl+D (today's note).
+        if is_meta && ev.shift_key() && key == "d" {
+            ev.prevent_default();
+            app_state_sv.with_value(|st| st.0.db_switcher_open.update(|open| *open = !*open));
+            return;
+        }
+
+        // From the editor too: that's where focus mode is wanted.
+        if is_meta && ev.shift_key() && key == "f" && pathname_untracked().contains("/note/") {
+            ev.prevent_default();
+            toggle_focus_mode();
+            return;
+        }
+
         // Avoid hijacking shortcuts while typing in inputs.
-        let target_tag = ev
+        let target_el = ev
             .target()
-            .and_then(|t| t.dyn_into::<web_sys::Element>().ok())
-            .map(|el| el.tag_name().to_lowercase());
+            .and_then(|t| t.dyn_into::<web_sys::Element>().ok());
+        let target_tag = target_el.as_ref().map(|el| el.tag_name().to_lowercase());
 
         if let Some(tag) = target_tag {
             if tag == "input" || tag == "textarea" {
@@ -917,6 +1951,54 @@ pub fn AppLayout(children: ChildrenFn) -> impl IntoView {
             }
         }
 
+        if is_meta && key == "n" {
+            // Don't steal the shortcut while editing an outline block.
+            let in_contenteditable = target_el
+                .and_then(|el| el.dyn_into::<web_sys::HtmlElement>().ok())
+                .map(|el| el.is_content_editable())
+                .unwrap_or(false);
+            if in_contenteditable || db_id_from_path(&pathname_untracked()).is_none() {
+                return;
+            }
+
+            ev.prevent_default();
+            create_note_from_shortcut.run(());
+            return;
+        }
+
+        if is_meta && key == "d" {
+            // Same as Cmd/Ctrl+N: leave outline blocks alone.
+            let in_contenteditable = target_el
+                .and_then(|el| el.dyn_into::<web_sys::HtmlElement>().ok())
+                .map(|el| el.is_content_editable())
+                .unwrap_or(false);
+            if in_contenteditable {
+                return;
+            }
+
+            // Also keeps the browser's bookmark dialog away.
+            ev.prevent_default();
+            open_today_note();
+            return;
+        }
+
+        // `code`, not `key`: Shift turns the brackets into braces on most layouts.
+        if is_meta && ev.shift_key() {
+            let collapse = match ev.code().as_str() {
+                "BracketLeft" => Some(true),
+                "BracketRight" => Some(false),
+                _ => None,
+            };
+            if let Some(collapse) = collapse {
+                // Only a mounted NotePage consumes the request; don't leave one pending elsewhere.
+                if pathname_untracked().contains("/note/") {
+                    ev.prevent_default();
+                    app_state_sv.with_value(|st| st.0.outline_fold_request.set(Some(collapse)));
+                }
+                return;
+            }
+        }
+
         if is_meta && key == "b" {
             ev.prevent_default();
             sidebar_collapsed.update(|v| *v = !*v);
@@ -932,14 +2014,20 @@ pub fn AppLayout(children: ChildrenFn) -> impl IntoView {
             return;
         }
 
+        // Escape blurs the search box; elsewhere (e.g. an outline block) it isn't ours.
         if key == "escape" {
             if let Some(input) = search_ref.get() {
-                let _ = input.blur();
+                let in_search = target_el
+                    .as_ref()
+                    .is_some_and(|el| el.is_same_node(Some(input.as_ref())));
+                if in_search {
+                    let _ = input.blur();
+                }
             }
         }
     });
 
-    let on_logout = move |_| {
+    let logout = move || {
         let mut api_client = app_state.0.api_client.get_untracked();
         api_client.logout();
         app_state.0.api_client.set(api_client);
@@ -948,17 +2036,87 @@ pub fn AppLayout(children: ChildrenFn) -> impl IntoView {
         set_current_db(None);
         let _ = window().location().set_href("/login");
     };
+    let on_logout = move |_| logout();
+
+    let on_palette_command = Callback::new(move |cmd: PaletteCommand| match cmd {
+        PaletteCommand::NewNote => create_note_from_shortcut.run(()),
+        PaletteCommand::ToggleSidebar => {
+            sidebar_collapsed.update(|v| *v = !*v);
+            persist_sidebar();
+        }
+        PaletteCommand::SignOut => logout(),
+    });
+
+    // Session-expired modal: drop the in-memory token and come back here after logging in.
+    let session_expired = app_state.0.session_expired;
+    let continue_to_login = move |_| {
+        let location = window().location();
+        let here = format!(
+            "{}{}",
+            location.pathname().unwrap_or_default(),
+            location.search().unwrap_or_default()
+        );
+        let mut api_client = app_state.0.api_client.get_untracked();
+        api_client.logout();
+        app_state.0.api_client.set(api_client);
+        session_expired.set(false);
+        let _ = location.set_href(&login_url_with_next(&here));
+    };
+
+    // Warn shortly before the session token expires; once it has, show the session-expired
+    // modal right away instead of letting the next request fail with a 401.
+    const SESSION_EXPIRY_WARNING_MS: i64 = 5 * 60 * 1000;
+    let session_expires_in_ms: RwSignal<Option<i64>> = RwSignal::new(None);
+    let app_state_for_expiry = app_state.clone();
+    let check_session_expiry = move || {
+        let api_client = app_state_for_expiry.0.api_client.get_untracked();
+        let Some(exp) = api_client.token_expiry_ms() else {
+            session_expires_in_ms.set(None);
+            return;
+        };
+
+        let left = exp - now_ms();
+        if left <= 0 {
+            session_expires_in_ms.set(None);
+            handle_unauthorized_response(&app_state_for_expiry);
+        } else if left <= SESSION_EXPIRY_WARNING_MS {
+            session_expires_in_ms.set(Some(left));
+        } else {
+            session_expires_in_ms.set(None);
+        }
+    };
+    check_session_expiry();
+    if let Ok(handle) =
+        set_interval_with_handle(check_session_expiry, std::time::Duration::from_secs(30))
+    {
+        on_cleanup(move || handle.clear());
+    }
 
+    // A deep link renders before the database list arrives: prefer the id in the path.
     let current_db_name = move || {
-        let id = current_db_id.get();
-        let dbs = databases.get();
-        id.and_then(|id| dbs.into_iter().find(|d| d.id == id).map(|d| d.name))
+        let id = db_id_from_path(&pathname())
+            .or_else(|| current_db_id.get())
+            .unwrap_or_default();
+        let loaded = app_state.0.db_load_timestamp.get().is_some();
+        databases.with(|dbs| resolve_db_name(dbs, loaded, &id))
     };
 
     view! {
         <div class="min-h-screen bg-background text-foreground">
-            <div class="mx-auto flex min-h-screen w-full max-w-5xl gap-4 px-4 py-6">
-                <aside class=move || format!("{} shrink-0", sidebar_width_class())>
+            <div class=move || {
+                if in_focus_mode() {
+                    "mx-auto flex min-h-screen w-full max-w-2xl gap-4 px-4 py-6"
+                } else {
+                    "mx-auto flex min-h-screen w-full max-w-5xl gap-4 px-4 py-6"
+                }
+            }>
+                <aside class=move || {
+                    if in_focus_mode() {
+                        "hidden".to_string()
+                    } else {
+                        format!("{} shrink-0", sidebar_width_class())
+                    }
+                }>
                     <div class="sticky top-6 space-y-4">
                         <div class="flex items-center justify-between">
                             <a href="/" class="text-sm font-medium text-foreground">
@@ -980,14 +2138,56 @@ pub fn AppLayout(children: ChildrenFn) -> impl IntoView {
                             </Button>
                         </div>
 
+                        // Kept outside the collapsible part so it stays reachable in the icon rail.
+                        <Show when=move || current_db_id.get().is_some() || !databases.get().is_empty()>
+                            <Button
+                                variant=ButtonVariant::Outline
+                                size=ButtonSize::Sm
+                                class="w-full"
+                                attr:title="Today's note (⌘D)"
+                                attr:aria-label="Today's note"
+                                attr:disabled=move || today_opening.get()
+                                on:click=move |_| open_today_note()
+                            >
+                                <svg
+                                    xmlns="http://www.w3.org/2000/svg"
+                                    width="14"
+                                    height="14"
+                                    viewBox="0 0 24 24"
+                                    fill="none"
+                                    stroke="currentColor"
+                                    stroke-width="2"
+                                    stroke-linecap="round"
+                                    stroke-linejoin="round"
+                                    class="shrink-0 text-muted-foreground"
+                                    aria-hidden="true"
+                                >
+                                    <rect x="3" y="4" width="18" height="18" rx="2"></rect>
+                                    <path d="M16 2v4M8 2v4M3 10h18"></path>
+                                </svg>
+                                <Show when=move || !sidebar_collapsed.get()>
+                                    <span class="text-sm">"Today"</span>
+                                </Show>
+                            </Button>
+                        </Show>
+
                         <Show
                             when=move || !sidebar_collapsed.get()
-                            fallback=|| view! {
+                            fallback=move || view! {
                                 <Card>
                                     <CardContent>
                                         <div class="text-xs text-muted-foreground">"Sidebar collapsed"</div>
                                     </CardContent>
                                 </Card>
+                                // Just the avatar in the icon rail.
+                                {move || app_state.0.current_user.get().map(|user| {
+                                    let name = user.username().unwrap_or_else(|| "Signed in".to_string());
+                                    view! {
+                                        <div class="flex justify-center" title=name>
+                                            <AccountAvatar user=user />
+                                        </div>
+                                    }
+                                })}
                             }
                         >
                             <Card>
@@ -1011,22 +2211,62 @@ pub fn AppLayout(children: ChildrenFn) -> impl IntoView {
                                             <path d="m21 21-4.3-4.3"></path>
                                         </svg>
 
-                                        <div class="min-w-0 flex-1">
+                                        <div class="relative min-w-0 flex-1">
                                             <Input
                                                 node_ref=search_ref
                                                 r#type="search"
                                                 placeholder="Search…"
                                                 bind_value=search_query
                                                 class="h-8 text-sm"
+                                                attr:role="combobox"
+                                                attr:aria-expanded=move || search_suggestions.with(|xs| !xs.is_empty()).to_string()
+                                                attr:aria-controls="search-suggestions"
+                                                attr:aria-activedescendant=move || {
+                                                    search_highlight.get().map(|i| format!("search-suggestion-{i}"))
+                                                }
+                                                on:focus=move |_| search_focused.set(true)
+                                                on:blur=move |_| search_focused.set(false)
                                                 on:keydown=move |ev: web_sys::KeyboardEvent| {
-                                                    if ev.key() == "Enter" {
-                                                        let q = search_query.get();
-                                                        navigate.with_value(|nav| {
-                                                            nav(&format!("/search?q={}", urlencoding::encode(&q)), Default::default());
-                                                        });
+                                                    let key = ev.key();
+                                                    let len = search_suggestions.with_untracked(|xs| xs.len());
+                                                    match key.as_str() {
+                                                        "ArrowDown" | "ArrowUp" if len > 0 => {
+                                                            ev.prevent_default();
+                                                            let down = key == "ArrowDown";
+                                                            search_highlight.update(|h| {
+                                                                *h = match (*h, down) {
+                                                                    (None, true) => Some(0),
+                                                                    (None, false) => Some(len - 1),
+                                                                    (Some(i), true) => Some((i + 1).min(len - 1)),
+                                                                    // Up from the first row returns to the typed query.
+                                                                    (Some(0), false) => None,
+                                                                    (Some(i), false) => Some(i - 1),
+                                                                };
+                                                            });
+                                                        }
+                                                        "Enter" => {
+                                                            let picked = search_highlight
+                                                                .get_untracked()
+                                                                .and_then(|i| search_suggestions.with_untracked(|xs| xs.get(i).cloned()));
+                                                            if let Some(n) = picked {
+                                                                ev.prevent_default();
+                                                                open_suggestion.run(n);
+                                                                return;
+                                                            }
+                                                            let q = search_query.get();
+                                                            navigate.with_value(|nav| {
+                                                                nav(&format!("/search?q={}", urlencoding::encode(&q)), Default::default());
+                                                            });
+                                                        }
+                                                        _ => {}
                                                     }
                                                 }
                                             />
+                                            <SearchSuggestions
+                                                suggestions=search_suggestions
+                                                highlighted=search_highlight
+                                                on_pick=open_suggestion
+                                            />
                                         </div>
 
                                         <div class="hidden shrink-0 items-center gap-1 text-xs text-muted-foreground sm:flex">
@@ -1050,20 +2290,24 @@ pub fn AppLayout(children: ChildrenFn) -> impl IntoView {
                                         >
                                             <div class="space-y-1">
                                                 {move || {
-                                                    let dbs = expect_context::<AppContext>().0.databases.get();
+                                                    let app_state = expect_context::<AppContext>();
+                                                    let dbs = app_state.0.databases.get();
+                                                    let loaded = app_state.0.db_load_timestamp.get().is_some();
                                                     load_recent_notes()
                                                         .into_iter()
-                                                        .map(|n| {
+                                                        // Notes of a deleted database are pruned from storage soon after.
+                                                        .filter_map(|n| match resolve_db_name(&dbs, loaded, &n.db_id) {
+                                                            DbName::Missing => None,
+                                                            db_name => Some((n, db_name)),
+                                                        })
+                                                        .map(|(n, db_name)| {
                                                             let db_id = n.db_id.clone();
                                                             let db_id_href = db_id.clone();
                                                             let note_id = n.note_id.clone();
                                                             // Use local draft if available (local-first).
                                                             let title = get_title_override(&db_id, &note_id, &n.title);
 
-                                                            let db_name_opt = dbs
-                                                                .iter()
-                                                                .find(|d| d.id == db_id)
-                                                                .map(|d| d.name.clone());
+                                                            let preview = app_state.0.note_list_preview(&db_id, &note_id);
 
                                                             view! {
                                                                 <a
@@ -1071,9 +2315,18 @@ pub fn AppLayout(children: ChildrenFn) -> impl IntoView {
                                                                     class="block rounded-md border border-border px-3 py-2 transition-colors hover:bg-accent-soft"
                                                                 >
                                                                     <div class="truncate text-sm font-medium">{title}</div>
+                                                                    {preview.map(|p| view! {
+                                                                        <div class="truncate text-xs text-muted-foreground">{p}</div>
+                                                                    })}
                                                                     // Only show database name (never show raw id). Keep height stable.
                                                                     <div class="min-h-[1rem] truncate text-xs text-muted-foreground">
-                                                                        {db_name_opt.unwrap_or_default()}
+                                                                        {match db_name {
+                                                                            DbName::Known(name) => name.into_any(),
+                                                                            _ => view! {
+                                                                                <span class="inline-block h-3 w-24 animate-pulse rounded bg-muted" aria-hidden="true" />
+                                                                            }
+                                                                            .into_any(),
+                                                                        }}
                                                                     </div>
                                                                 </a>
                                                             }
@@ -1095,7 +2348,14 @@ pub fn AppLayout(children: ChildrenFn) -> impl IntoView {
                                                 variant=ButtonVariant::Ghost
                                                 size=ButtonSize::Icon
                                                 on:click=move |_| open_create_dialog()
-                                                attr:title="New database"
+                                                attr:disabled=at_db_limit
+                                                attr:title=move || {
+                                                    if at_db_limit() {
+                                                        database_limit_message()
+                                                    } else {
+                                                        "New database".to_string()
+                                                    }
+                                                }
                                                 class="h-7 w-7"
                                             >
                                                 <span class="text-xs text-muted-foreground">"+"</span>
@@ -1103,7 +2363,7 @@ pub fn AppLayout(children: ChildrenFn) -> impl IntoView {
                                             <Button
                                                 variant=ButtonVariant::Ghost
                                                 size=ButtonSize::Icon
-                                                on:click=move |_| load_databases()
+                                                on:click=move |_| load_databases(false)
                                                 attr:title="Refresh"
                                                 class="h-7 w-7"
                                             >
@@ -1112,11 +2372,39 @@ pub fn AppLayout(children: ChildrenFn) -> impl IntoView {
                                         </div>
                                     </CardHeader>
                                     <CardContent class="p-3 pt-0">
+                                        <Show when=move || !sidebar_collapsed.get() fallback=|| ().into_view()>
+                                            <Input
+                                                r#type="search"
+                                                placeholder="Filter databases…"
+                                                bind_value=databases_search
+                                                class="h-7 text-xs"
+                                                on:keydown=move |ev: web_sys::KeyboardEvent| {
+                                                    if ev.key() == "Escape" {
+                                                        databases_search.set(String::new());
+                                                    }
+                                                }
+                                            />
+                                        </Show>
                                         <Show when=move || db_error.get().is_some() fallback=|| ().into_view()>
                                             {move || db_error.get().map(|e| view! {
                                                 <div class="mt-2 text-[11px] text-destructive">{e}</div>
                                             })}
                                         </Show>
+                                        {move || match db_retry_state.get() {
+                                            RetryState::Idle => ().into_any(),
+                                            RetryState::Retrying { delay_ms, error, .. } => view! {
+                                                <div class="mt-2 text-[11px] text-muted-foreground" title=error>
+                                                    {format!("Retrying in {}s…", delay_ms / 1000)}
+                                                </div>
+                                            }
+                                            .into_any(),
+                                            RetryState::GaveUp => view! {
+                                                <div class="mt-2 text-[11px] text-muted-foreground">
+                                                    "Stopped retrying after repeated failures. Click ↻ to retry."
+                                                </div>
+                                            }
+                                            .into_any(),
+                                        }}
 
                                         <div class="mt-2 space-y-1">
                                             <Show
@@ -1127,12 +2415,15 @@ pub fn AppLayout(children: ChildrenFn) -> impl IntoView {
                                                     </div>
                                                 }
                                             >
+                                                {move || filtered_databases.with(|xs| xs.is_empty()).then(|| view! {
+                                                    <div class="text-[11px] text-muted-foreground">"No matching databases"</div>
+                                                })}
                                                 {move || {
                                                     let selected = current_db_id.get();
                                                     let allow_highlight = pathname().starts_with("/db/");
                                                     let show_actions = pathname() == "/";
 
-                                                    databases
+                                                    filtered_databases
                                                         .get()
                                                         .into_iter()
                                                         .map(|db| {
@@ -1246,7 +2537,45 @@ pub fn AppLayout(children: ChildrenFn) -> impl IntoView {
                             <Show when=move || sidebar_show_pages() fallback=|| ().into_view()>
                                 <Card>
                                     <CardContent class="p-3">
-                                        <span class="sr-only">"Pages"</span>
+                                        <div class="mb-2 flex items-center justify-between">
+                                            <span class="text-xs font-medium text-muted-foreground">"Pages"</span>
+                                            <Button
+                                                variant=ButtonVariant::Ghost
+                                                size=ButtonSize::Icon
+                                                class="h-6 w-6"
+                                                attr:title="New note"
+                                                attr:aria-label="New note"
+                                                attr:disabled=move || new_note_creating.get()
+                                                on:click=move |_| create_note_from_shortcut.run(())
+                                            >
+                                                {move || if new_note_creating.get() {
+                                                    view! { <Spinner class="size-3" /> }.into_any()
+                                                } else {
+                                                    view! { <span class="text-base leading-none">"+"</span> }.into_any()
+                                                }}
+                                            </Button>
+                                        </div>
+                                        <Show when=move || !current_db_id.get().unwrap_or_default().trim().is_empty()>
+                                            <select
+                                                class="mb-2 h-7 w-full rounded-md border border-input bg-background px-2 text-xs"
+                                                aria-label="Sort pages"
+                                                prop:value=move || {
+                                                    let db_id = current_db_id.get().unwrap_or_default();
+                                                    app_state_sv.with_value(|st| st.0.notes_sort_for(&db_id)).key()
+                                                }
+                                                on:change=move |ev| {
+                                                    if let Some(sort) = NoteSort::from_key(&event_target_value(&ev)) {
+                                                        let db_id = current_db_id.get_untracked().unwrap_or_default();
+                                                        app_state_sv.with_value(|st| st.0.set_notes_sort(&db_id, sort));
+                                                    }
+                                                }
+                                            >
+                                                {NoteSort::ALL
+                                                    .into_iter()
+                                                    .map(|sort| view! { <option value=sort.key()>{sort.label()}</option> })
+                                                    .collect_view()}
+                                            </select>
+                                        </Show>
                                         <div class="space-y-1">
                                             {move || {
                                                 let db_id = current_db_id.get().unwrap_or_default();
@@ -1254,6 +2583,17 @@ pub fn AppLayout(children: ChildrenFn) -> impl IntoView {
                                                 // Utility links
                                                 let mut out: Vec<AnyView> = vec![];
                                                 if !db_id.trim().is_empty() {
+                                                    out.push(
+                                                        view! {
+                                                            <a
+                                                                href=format!("/db/{}/daily", db_id)
+                                                                class="block rounded-md border border-border bg-background px-3 py-2 text-sm transition-colors hover:bg-surface-hover"
+                                                            >
+                                                                "Daily notes"
+                                                            </a>
+                                                        }
+                                                        .into_any(),
+                                                    );
                                                     out.push(
                                                         view! {
                                                             <a
@@ -1265,13 +2605,70 @@ pub fn AppLayout(children: ChildrenFn) -> impl IntoView {
                                                         }
                                                         .into_any(),
                                                     );
+                                                    out.push(
+                                                        view! {
+                                                            <a
+                                                                href=format!("/db/{}/trash", db_id)
+                                                                class="block rounded-md border border-border bg-background px-3 py-2 text-sm transition-colors hover:bg-surface-hover"
+                                                            >
+                                                                "Trash"
+                                                            </a>
+                                                        }
+                                                        .into_any(),
+                                                    );
+
+                                                    let tags_db_id = db_id.clone();
+                                                    out.push(
+                                                        view! {
+                                                            {move || {
+                                                                let mut tags = tags_index.with(|m| {
+                                                                    m.iter()
+                                                                        .map(|(tag, ids)| (tag.clone(), ids.len()))
+                                                                        .collect::<Vec<_>>()
+                                                                });
+                                                                if tags.is_empty() {
+                                                                    return ().into_any();
+                                                                }
+                                                                tags.sort();
+                                                                let db_id = tags_db_id.clone();
+                                                                view! {
+                                                                    <div class="space-y-1 pt-1">
+                                                                        <div class="px-1 text-xs font-medium text-muted-foreground">"Tags"</div>
+                                                                        <div class="flex flex-wrap gap-1">
+                                                                            {tags
+                                                                                .into_iter()
+                                                                                .map(|(tag, count)| {
+                                                                                    let href = format!(
+                                                                                        "/db/{}/tag/{}",
+                                                                                        db_id,
+                                                                                        urlencoding::encode(&tag)
+                                                                                    );
+                                                                                    view! {
+                                                                                        <a
+                                                                                            href=href
+                                                                                            class="rounded-md border border-border bg-background px-2 py-0.5 text-xs transition-colors hover:bg-surface-hover"
+                                                                                        >
+                                                                                            {format!("#{tag}")}
+                                                                                            <span class="ml-1 text-muted-foreground">{count}</span>
+                                                                                        </a>
+                                                                                    }
+                                                                                })
+                                                                                .collect_view()}
+                                                                        </div>
+                                                                    </div>
+                                                                }
+                                                                .into_any()
+                                                            }}
+                                                        }
+                                                        .into_any(),
+                                                    );
 
                                                     // Divider
                                                     out.push(view! { <div class="h-px w-full bg-border" /> }.into_any());
                                                 }
 
                                                 let q = search_query.get().trim().to_lowercase();
-                                                let notes = expect_context::<AppContext>().0.notes.get();
+                                                let notes = date_filtered_notes.get();
 
                                                 // Highlight current note if we are on /db/:db_id/note/:note_id
                                                 let p = pathname();
@@ -1283,7 +2680,7 @@ pub fn AppLayout(children: ChildrenFn) -> impl IntoView {
                                                     .next()
                                                     .unwrap_or("");
 
-                                                let note_views = notes
+                                                let mut filtered = notes
                                                     .into_iter()
                                                     .filter(|n| n.database_id == db_id)
                                                     .filter(|n| {
@@ -1293,29 +2690,93 @@ pub fn AppLayout(children: ChildrenFn) -> impl IntoView {
                                                             n.title.to_lowercase().contains(&q)
                                                         }
                                                     })
-                                                    .map(|n| {
-                                                        let is_selected = n.id == current_note_id;
-                                                        let variant = if is_selected {
-                                                            ButtonVariant::Accent
-                                                        } else {
-                                                            ButtonVariant::Ghost
-                                                        };
-                                                        let id = n.id.clone();
-                                                        // Use title override to match note title behavior
-                                                        let display_title = get_title_override(&db_id, &id, &n.title);
-                                                        view! {
+                                                    .collect::<Vec<_>>();
+                                                let app_state = expect_context::<AppContext>();
+                                                sort_notes(&mut filtered, app_state.0.notes_sort_for(&db_id));
+                                                let pinned_ids = app_state.0.pinned_note_ids(&db_id);
+                                                let (pinned, rest) = partition_pinned_notes(filtered, &pinned_ids);
+
+                                                let row = |n: Note, is_pinned: bool| {
+                                                    let is_selected = n.id == current_note_id;
+                                                    let variant = if is_selected {
+                                                        ButtonVariant::Accent
+                                                    } else {
+                                                        ButtonVariant::Ghost
+                                                    };
+                                                    let id = n.id.clone();
+                                                    // Use title override to match note title behavior
+                                                    let display_title = get_title_override(&db_id, &id, &n.title);
+                                                    if sidebar_renaming.get().as_deref() == Some(id.as_str()) {
+                                                        return view! {
+                                                            <NoteRenameInput
+                                                                db_id=db_id.clone()
+                                                                note_id=id
+                                                                title=display_title
+                                                                on_done=move |()| sidebar_renaming.set(None)
+                                                            />
+                                                        }
+                                                        .into_any();
+                                                    }
+                                                    let char_count = expect_context::<AppContext>()
+                                                        .0
+                                                        .note_char_counts
+                                                        .with(|m| m.get(&id).copied());
+                                                    let preview = expect_context::<AppContext>()
+                                                        .0
+                                                        .note_list_preview(&db_id, &id);
+                                                    // A preview line makes the row two lines tall.
+                                                    let button_class = if preview.is_some() {
+                                                        "h-auto min-w-0 flex-1 justify-start py-1"
+                                                    } else {
+                                                        "min-w-0 flex-1 justify-start"
+                                                    };
+                                                    let rename_id = id.clone();
+                                                    view! {
+                                                        <div class="group flex items-center gap-1">
                                                             <Button
                                                                 variant=variant
                                                                 size=ButtonSize::Sm
-                                                                class="w-full justify-start"
+                                                                class=button_class
                                                                 attr:aria-current=move || if is_selected { Some("page") } else { None }
                                                                 href=format!("/db/{}/note/{}", db_id, id)
                                                             >
-                                                                {display_title}
+                                                                <span class="flex min-w-0 flex-1 flex-col text-left">
+                                                                    <span class="truncate">{display_title}</span>
+                                                                    {preview.map(|p| view! {
+                                                                        <span class="truncate text-[11px] font-normal text-muted-foreground">{p}</span>
+                                                                    })}
+                                                                </span>
+                                                                {char_count.map(|c| view! {
+                                                                    <span class="shrink-0 text-[10px] text-muted-foreground">
+                                                                        {format_char_count_badge(c)}
+                                                                    </span>
+                                                                })}
                                                             </Button>
-                                                        }
-                                                        .into_any()
-                                                    })
+                                                            <div class=if is_pinned { "flex" } else { "hidden group-hover:flex" }>
+                                                                <NoteRenameButton on_click=move |()| sidebar_renaming.set(Some(rename_id.clone())) />
+                                                                <NotePinButton
+                                                                    db_id=db_id.clone()
+                                                                    note_id=n.id.clone()
+                                                                    is_pinned=is_pinned
+                                                                />
+                                                            </div>
+                                                        </div>
+                                                    }
+                                                    .into_any()
+                                                };
+
+                                                if !pinned.is_empty() {
+                                                    out.push(
+                                                        view! { <div class="px-1 text-xs font-medium text-muted-foreground">"Pinned"</div> }
+                                                            .into_any(),
+                                                    );
+                                                    out.extend(pinned.into_iter().map(|n| row(n, true)));
+                                                    out.push(view! { <div class="h-px w-full bg-border" /> }.into_any());
+                                                }
+
+                                                let note_views = rest
+                                                    .into_iter()
+                                                    .map(|n| row(n, false))
                                                     .collect::<Vec<_>>();
 
                                                 out.extend(note_views);
@@ -1329,6 +2790,23 @@ pub fn AppLayout(children: ChildrenFn) -> impl IntoView {
                             <Card>
                                 <CardContent class="p-3">
                                     <span class="sr-only">"Account"</span>
+                                    {move || app_state.0.current_user.get().map(|user| {
+                                        let name = user.username().unwrap_or_else(|| "Signed in".to_string());
+                                        view! {
+                                            <div class="mb-2 flex min-w-0 items-center gap-2" title=name.clone()>
+                                                <AccountAvatar user=user.clone() />
+                                                // Narrow screens show only the avatar.
+                                                <div class="hidden min-w-0 sm:block">
+                                                    <div class="truncate text-xs font-medium" title=name.clone()>{name.clone()}</div>
+                                                    {user.email().map(|email| view! {
+                                                        <div class="truncate text-[11px] text-muted-foreground" title=email.clone()>
+                                                            {email}
+                                                        </div>
+                                                    })}
+                                                </div>
+                                            </div>
+                                        }
+                                    })}
                                     <Button
                                         variant=ButtonVariant::Outline
                                         size=ButtonSize::Sm
@@ -1344,7 +2822,53 @@ pub fn AppLayout(children: ChildrenFn) -> impl IntoView {
                 </aside>
 
                 <main class="min-w-0 flex-1">
-                    <div class="mb-4 flex items-center justify-between gap-3">
+                    <Show when=move || !is_online.get()>
+                        <Alert class="mb-3 border-yellow-500/40 bg-yellow-50 text-yellow-900 dark:bg-yellow-950/40 dark:text-yellow-200">
+                            <AlertDescription class="text-xs">
+                                "You are offline — edits will sync when reconnected. New notes and databases need a connection."
+                                {move || {
+                                    let queued = app_state_sv.with_value(|st| st.0.pending_requests.with(Vec::len));
+                                    (queued > 0).then(|| format!(" ({queued} queued)"))
+                                }}
+                            </AlertDescription>
+                        </Alert>
+                    </Show>
+                    {move || session_expires_in_ms.get().map(|left| {
+                        let minutes = (left + 59_999) / 60_000;
+                        view! {
+                            <Alert class="mb-3 border-amber-500/30">
+                                <AlertDescription class="flex items-center justify-between gap-3 text-xs">
+                                    <span>
+                                        {format!(
+                                            "Session expires in {} minute{}, please re-login.",
+                                            minutes,
+                                            if minutes == 1 { "" } else { "s" },
+                                        )}
+                                    </span>
+                                    <a class="shrink-0 text-primary underline underline-offset-4" href="/login">
+                                        "Log in again"
+                                    </a>
+                                </AlertDescription>
+                            </Alert>
+                        }
+                    })}
+                    <Show when=in_focus_mode fallback=|| ().into_view()>
+                        <button
+                            type="button"
+                            class="fixed right-4 top-4 z-40 rounded-full border border-border bg-background/90 px-3 py-1 text-xs text-muted-foreground shadow-sm hover:bg-surface-hover hover:text-foreground"
+                            title="Exit focus (Ctrl/Cmd+Shift+F)"
+                            on:click=move |_| focus_mode.set(false)
+                        >
+                            "Exit focus"
+                        </button>
+                    </Show>
+                    <div class=move || {
+                        if in_focus_mode() {
+                            "hidden"
+                        } else {
+                            "mb-4 flex items-center justify-between gap-3"
+                        }
+                    }>
                         <nav class="min-w-0" aria-label="Breadcrumb">
                             {move || {
                                 use leptos::prelude::IntoAny;
@@ -1359,14 +2883,23 @@ pub fn AppLayout(children: ChildrenFn) -> impl IntoView {
 
                                 // DB / Note
                                 if p.starts_with("/db/") {
-                                    let db_name = current_db_name()
-                                        .unwrap_or_else(|| "Database".to_string());
+                                    let db_name = match current_db_name() {
+                                        DbName::Known(name) => name.into_any(),
+                                        DbName::Loading => view! {
+                                            <span class="inline-block h-4 w-24 animate-pulse rounded bg-muted" aria-hidden="true" />
+                                        }
+                                        .into_any(),
+                                        DbName::Missing => "Database not found".into_any(),
+                                    };
 
                                     // If note route, show All databases > db > note
                                     if let Some(rest) = p.strip_prefix("/db/") {
                                         if let Some((db_id, tail)) = rest.split_once('/') {
-                                            if let Some(_note_rest) = tail.strip_prefix("note/") {
-                                                // Note route: do NOT show note title in breadcrumbs.
+                                            if let Some(note_rest) = tail.strip_prefix("note/") {
+                                                // NotePage publishes the open note; the path covers the first render.
+                                                let note_id = app_state_sv
+                                                    .with_value(|st| st.0.current_note_id.get())
+                                                    .unwrap_or_else(|| note_rest.split('/').next().unwrap_or("").to_string());
                                                 return view! {
                                                     <div class="flex min-w-0 items-center gap-2 text-sm">
                                                         <a
@@ -1382,6 +2915,10 @@ pub fn AppLayout(children: ChildrenFn) -> impl IntoView {
                                                         >
                                                             {db_name}
                                                         </a>
+                                                        {(!note_id.trim().is_empty()).then(|| view! {
+                                                            <span class="text-muted-foreground">"›"</span>
+                                                            <NoteBreadcrumbTitle db_id=db_id.to_string() note_id=note_id />
+                                                        })}
                                                     </div>
                                                 }
                                                 .into_any();
@@ -1430,11 +2967,43 @@ pub fn AppLayout(children: ChildrenFn) -> impl IntoView {
                     {children()}
                 </main>
 
+                <Show when=move || session_expired.get() fallback=|| ().into_view()>
+                    <div class="fixed inset-0 z-[60] flex items-center justify-center bg-black/30 px-4">
+                        <div class="w-full max-w-sm rounded-md border border-border bg-background p-4 shadow-lg">
+                            <div class="mb-3 space-y-1">
+                                <div class="text-sm font-medium">"Session expired"</div>
+                                <div class="text-xs text-muted-foreground">
+                                    "Your session has expired — please log in again."
+                                </div>
+                            </div>
+                            <div class="flex items-center justify-end pt-2">
+                                <Button size=ButtonSize::Sm on:click=continue_to_login>
+                                    "Log in"
+                                </Button>
+                            </div>
+                        </div>
+                    </div>
+                </Show>
+
                 <Show when=move || create_open.get() fallback=|| ().into_view()>
                     <div class="fixed inset-0 z-50 flex items-center justify-center bg-black/30 px-4">
-                        <div class="w-full max-w-sm rounded-md border border-border bg-background p-4 shadow-lg">
+                        <div
+                            class="w-full max-w-sm rounded-md border border-border bg-background p-4 shadow-lg"
+                            role="dialog"
+                            aria-modal="true"
+                            aria-labelledby="create-db-title"
+                            aria-describedby="create-db-desc"
+                            on:keydown=move |ev: web_sys::KeyboardEvent| {
+                                if let Some(dialog) = ev.current_target().and_then(|t| t.dyn_into().ok()) {
+                                    trap_dialog_focus(&ev, &dialog);
+                                }
+                            }
+                        >
                             <div class="mb-3 space-y-1">
-                                <div class="text-sm font-medium">"New database"</div>
+                                <div id="create-db-title" class="text-sm font-medium">"New database"</div>
+                                <div id="create-db-desc" class="text-xs text-muted-foreground">
+                                    {format!("Up to {MAX_DATABASES} databases per account.")}
+                                </div>
                             </div>
 
                             <div class="space-y-2">
@@ -1493,16 +3062,27 @@ pub fn AppLayout(children: ChildrenFn) -> impl IntoView {
 
                 <Show when=move || rename_open.get() fallback=|| ().into_view()>
                     <div class="fixed inset-0 z-50 flex items-center justify-center bg-black/30 px-4">
-                        <div class="w-full max-w-sm rounded-md border border-border bg-background p-4 shadow-lg">
+                        <div
+                            class="w-full max-w-sm rounded-md border border-border bg-background p-4 shadow-lg"
+                            role="dialog"
+                            aria-modal="true"
+                            aria-labelledby="rename-db-title"
+                            aria-describedby="rename-db-desc"
+                            on:keydown=move |ev: web_sys::KeyboardEvent| {
+                                if let Some(dialog) = ev.current_target().and_then(|t| t.dyn_into().ok()) {
+                                    trap_dialog_focus(&ev, &dialog);
+                                }
+                            }
+                        >
                             <div class="mb-3 space-y-1">
-                                <div class="text-sm font-medium">"Rename database"</div>
-                                <div class="text-xs text-muted-foreground">"Only the name can be updated (backend limitation)."</div>
+                                <div id="rename-db-title" class="text-sm font-medium">"Rename database"</div>
+                                <div id="rename-db-desc" class="text-xs text-muted-foreground">"Only the name can be updated (backend limitation)."</div>
                             </div>
 
                             <div class="space-y-2">
                                 <div class="space-y-1">
                                     <Label class="text-xs">"New name"</Label>
-                                    <Input bind_value=rename_value class="h-8 text-sm" />
+                                    <Input node_ref=rename_name_ref bind_value=rename_value class="h-8 text-sm" />
                                 </div>
 
                                 <Show when=move || rename_error.get().is_some() fallback=|| ().into_view()>
@@ -1542,10 +3122,21 @@ pub fn AppLayout(children: ChildrenFn) -> impl IntoView {
 
                 <Show when=move || delete_open.get() fallback=|| ().into_view()>
                     <div class="fixed inset-0 z-50 flex items-center justify-center bg-black/30 px-4">
-                        <div class="w-full max-w-sm rounded-md border border-border bg-background p-4 shadow-lg">
+                        <div
+                            class="w-full max-w-sm rounded-md border border-border bg-background p-4 shadow-lg"
+                            role="dialog"
+                            aria-modal="true"
+                            aria-labelledby="delete-db-title"
+                            aria-describedby="delete-db-desc"
+                            on:keydown=move |ev: web_sys::KeyboardEvent| {
+                                if let Some(dialog) = ev.current_target().and_then(|t| t.dyn_into().ok()) {
+                                    trap_dialog_focus(&ev, &dialog);
+                                }
+                            }
+                        >
                             <div class="mb-3 space-y-1">
-                                <div class="text-sm font-medium text-destructive">"Delete database"</div>
-                                <div class="text-xs text-muted-foreground">
+                                <div id="delete-db-title" class="text-sm font-medium text-destructive">"Delete database"</div>
+                                <div id="delete-db-desc" class="text-xs text-muted-foreground">
                                     "Type the database name to confirm deletion."
                                 </div>
                             </div>
@@ -1557,7 +3148,12 @@ pub fn AppLayout(children: ChildrenFn) -> impl IntoView {
 
                                 <div class="space-y-1">
                                     <Label class="text-xs">"Confirm name"</Label>
-                                    <Input bind_value=delete_confirm class="h-8 text-sm" placeholder="Type name exactly" />
+                                    <Input
+                                        node_ref=delete_confirm_ref
+                                        bind_value=delete_confirm
+                                        class="h-8 text-sm"
+                                        placeholder="Type name exactly"
+                                    />
                                 </div>
 
                                 <Show when=move || delete_error.get().is_some() fallback=|| ().into_view()>
@@ -1597,20 +3193,583 @@ pub fn AppLayout(children: ChildrenFn) -> impl IntoView {
                     </div>
                 </Show>
             </div>
+
+            <CommandPalette open=palette_open on_command=on_palette_command />
+            <DbSwitcher />
+            <UndoDeleteSnackbar />
         </div>
     }
 }
 
+/// Longest note title shown in the breadcrumbs before it is cut with "…".
+const BREADCRUMB_TITLE_MAX_CHARS: usize = 40;
+
+/// Last breadcrumb segment on note routes: the note title, cut to
+/// [`BREADCRUMB_TITLE_MAX_CHARS`]. Click scrolls to the top of the note, double-click
+/// renames it inline.
+#[component]
+fn NoteBreadcrumbTitle(db_id: String, note_id: String) -> impl IntoView {
+    let app_state = expect_context::<AppContext>();
+    let notes = app_state.0.notes;
+    let editing: RwSignal<bool> = RwSignal::new(false);
+    let ids = StoredValue::new((db_id, note_id));
+
+    // Same precedence as NotePage: local title draft, then the loaded list, then the snapshot.
+    // `None` until something is known, so the note id never flashes.
+    let title = Memo::new(move |_| {
+        ids.with_value(|(db_id, note_id)| {
+            let server = notes.with(|xs| {
+                xs.iter()
+                    .find(|n| &n.id == note_id)
+                    .map(|n| n.title.clone())
+            });
+            let server = server.or_else(|| load_note_snapshot(db_id, note_id).and_then(|s| s.title))?;
+            Some(get_title_override(db_id, note_id, &server))
+        })
+    });
+
+    view! {
+        {move || {
+            if editing.get() {
+                let (db_id, note_id) = ids.get_value();
+                return view! {
+                    <div class="w-48">
+                        <NoteRenameInput
+                            db_id=db_id
+                            note_id=note_id
+                            title=title.get_untracked().unwrap_or_default()
+                            on_done=move |()| editing.set(false)
+                        />
+                    </div>
+                }
+                .into_any();
+            }
+
+            match title.get() {
+                Some(t) => view! {
+                    <button
+                        type="button"
+                        class="min-w-0 max-w-[16rem] truncate text-left font-medium text-foreground"
+                        title=format!("{t} (double-click to rename)")
+                        on:click=move |_| window().scroll_to_with_x_and_y(0.0, 0.0)
+                        on:dblclick=move |_| editing.set(true)
+                    >
+                        {truncate_title(&t, BREADCRUMB_TITLE_MAX_CHARS)}
+                    </button>
+                }
+                .into_any(),
+                None => view! {
+                    <span class="inline-block h-4 w-24 animate-pulse rounded bg-muted" aria-hidden="true" />
+                }
+                .into_any(),
+            }
+        }}
+    }
+}
+
+/// Max rows shown by the command palette.
+const PALETTE_LIMIT: usize = 50;
+
+/// Cmd/Ctrl+P command palette: jump to any note (across all databases), a recent note or
+/// a database, or run a built-in command.
+#[component]
+fn CommandPalette(
+    open: RwSignal<bool>,
+    #[prop(into)] on_command: Callback<PaletteCommand>,
+) -> impl IntoView {
+    let app_state = expect_context::<AppContext>();
+    let navigate = StoredValue::new(use_navigate());
+    let location = use_location();
+
+    let databases = app_state.0.databases;
+    let notes = app_state.0.notes;
+    let api_client = app_state.0.api_client;
+    let index = app_state.0.quick_switch_index;
+
+    let query: RwSignal<String> = RwSignal::new(String::new());
+    let selected: RwSignal<usize> = RwSignal::new(0);
+    let recent: RwSignal<Vec<RecentNote>> = RwSignal::new(vec![]);
+    let index_loading: RwSignal<bool> = RwSignal::new(false);
+    let input_ref: NodeRef<html::Input> = NodeRef::new();
+
+    // Fetch every database's note list. A failed database keeps its previous entry.
+    let refresh_index = move || {
+        if index_loading.get_untracked() {
+            return;
+        }
+        let dbs = databases.get_untracked();
+        if dbs.is_empty() {
+            return;
+        }
+
+        index_loading.set(true);
+        let api_client = api_client.get_untracked();
+        spawn_local(async move {
+            let mut next: HashMap<String, Vec<Note>> = HashMap::new();
+            for db in dbs {
+                match api_client.get_all_note_list(&db.id).await {
+                    Ok(notes) => {
+                        next.insert(db.id, notes);
+                    }
+                    Err(e) => {
+                        leptos::logging::warn!("command palette: load notes for {} failed: {}", db.id, e);
+                        if let Some(prev) = index.with_untracked(|m| m.get(&db.id).cloned()) {
+                            next.insert(db.id, prev);
+                        }
+                    }
+                }
+            }
+            index.set(next);
+            index_loading.set(false);
+        });
+    };
+
+    // Each open starts fresh: the first one builds the index, later ones show the cached
+    // index right away and refresh it in the background.
+    Effect::new(move |_| {
+        if open.get() {
+            query.set(String::new());
+            selected.set(0);
+            recent.set(load_recent_notes());
+            refresh_index();
+        }
+    });
+
+    Effect::new(move |_| {
+        if let Some(input) = input_ref.get() {
+            let _ = input.focus();
+        }
+    });
+
+    let results = Memo::new(move |_| {
+        let q = query.get();
+        // "New note" needs a database route (same as Cmd/Ctrl+N).
+        let in_db = db_id_from_path(&location.pathname.get()).is_some();
+        let commands = [
+            PaletteCommand::NewNote,
+            PaletteCommand::ToggleSidebar,
+            PaletteCommand::SignOut,
+        ]
+        .into_iter()
+        .filter(|c| in_db || *c != PaletteCommand::NewNote)
+        .collect::<Vec<_>>();
+
+        // The loaded list is fresher than the index for the current database.
+        let mut all = notes.get();
+        let seen = all.iter().map(|n| n.id.clone()).collect::<HashSet<_>>();
+        index.with(|m| {
+            all.extend(m.values().flatten().filter(|n| !seen.contains(&n.id)).cloned());
+        });
+
+        databases.with(|dbs| {
+            recent.with(|rs| palette_items(&q, &commands, rs, dbs, all, PALETTE_LIMIT))
+        })
+    });
+
+    let pick = move |item: &PaletteItem| {
+        open.set(false);
+        let href = match &item.action {
+            PaletteAction::Run(cmd) => {
+                on_command.run(*cmd);
+                return;
+            }
+            PaletteAction::OpenDatabase(id) => format!("/db/{}", id),
+            PaletteAction::OpenNote { db_id, note_id } => format!("/db/{}/note/{}", db_id, note_id),
+        };
+        navigate.with_value(|nav| nav(&href, Default::default()));
+    };
+
+    let on_keydown = move |ev: web_sys::KeyboardEvent| match ev.key().as_str() {
+        "ArrowDown" => {
+            ev.prevent_default();
+            let len = results.with_untracked(|xs| xs.len());
+            if len > 0 {
+                selected.update(|i| *i = (*i + 1).min(len - 1));
+            }
+        }
+        "ArrowUp" => {
+            ev.prevent_default();
+            selected.update(|i| *i = i.saturating_sub(1));
+        }
+        "Enter" => {
+            ev.prevent_default();
+            let picked = results.with_untracked(|xs| xs.get(selected.get_untracked()).cloned());
+            if let Some(item) = picked {
+                pick(&item);
+            }
+        }
+        "Escape" => {
+            ev.prevent_default();
+            open.set(false);
+        }
+        _ => {}
+    };
+
+    view! {
+        <Show when=move || open.get()>
+            <div
+                class="fixed inset-0 z-50 flex items-start justify-center bg-black/30 px-4 pt-24"
+                on:mousedown=move |_| open.set(false)
+            >
+                <div
+                    class="w-full max-w-lg rounded-md border border-border bg-background p-2 shadow-lg"
+                    on:mousedown=move |ev: web_sys::MouseEvent| ev.stop_propagation()
+                >
+                    <input
+                        node_ref=input_ref
+                        class="h-9 w-full rounded-md border border-input bg-transparent px-3 py-1 text-sm shadow-xs outline-none focus-visible:border-ring focus-visible:ring-2 focus-visible:ring-ring/50"
+                        placeholder="Search notes, databases and commands…"
+                        aria-label="Command palette"
+                        prop:value=move || query.get()
+                        on:input=move |ev| {
+                            query.set(event_target_value(&ev));
+                            selected.set(0);
+                        }
+                        on:keydown=on_keydown
+                    />
+                    {move || {
+                        let rows = results.get();
+                        if rows.is_empty() {
+                            let msg = if index_loading.get() { "Loading notes…" } else { "No matches." };
+                            return view! {
+                                <div class="px-2 py-3 text-xs text-muted-foreground">{msg}</div>
+                            }
+                            .into_any();
+                        }
+
+                        view! {
+                            <Command class="mt-2" should_filter=false disable_scripts=true>
+                                <CommandList class="max-h-80 min-h-0">
+                                    {rows
+                                        .into_iter()
+                                        .enumerate()
+                                        .map(|(i, item)| {
+                                            let is_selected = Signal::derive(move || selected.get() == i);
+                                            let label = item.label.clone();
+                                            let detail = item.detail.clone();
+                                            view! {
+                                                <CommandItem
+                                                    value=label.clone()
+                                                    selected=is_selected
+                                                    class="flex items-center justify-between gap-3 aria-selected:bg-accent"
+                                                    on_mousedown=Some(Callback::new(move |ev: web_sys::MouseEvent| {
+                                                        ev.prevent_default();
+                                                        pick(&item);
+                                                    }))
+                                                    on:mousemove=move |_| selected.set(i)
+                                                >
+                                                    <span class="min-w-0 truncate">
+                                                        {label}
+                                                        <span class="text-muted-foreground">{format!(" — {}", detail)}</span>
+                                                    </span>
+                                                </CommandItem>
+                                            }
+                                        })
+                                        .collect_view()}
+                                </CommandList>
+                            </Command>
+                        }
+                        .into_any()
+                    }}
+                </div>
+            </div>
+        </Show>
+    }
+}
+
+/// Bottom-right "Deleted — Undo" bar for the latest entry of `AppState::recently_deleted`;
+/// the strip along its bottom runs down over the undo window.
+#[component]
+fn UndoDeleteSnackbar() -> impl IntoView {
+    let app_state = expect_context::<AppContext>();
+    let app_state_sv = StoredValue::new(app_state.clone());
+    let sync_sv = StoredValue::new(expect_context::<crate::state::NoteSyncController>());
+    let recently_deleted = app_state.0.recently_deleted;
+
+    let restoring: RwSignal<bool> = RwSignal::new(false);
+    let error: RwSignal<Option<String>> = RwSignal::new(None);
+
+    let now: RwSignal<i64> = RwSignal::new(now_ms());
+    if let Ok(handle) = set_interval_with_handle(
+        move || {
+            if recently_deleted.with_untracked(|xs| !xs.is_empty()) {
+                now.set(now_ms());
+            }
+        },
+        std::time::Duration::from_millis(250),
+    ) {
+        on_cleanup(move || handle.clear());
+    }
+
+    let current: Memo<Option<DeletedItem>> = Memo::new(move |_| {
+        let now = now.get();
+        recently_deleted.with(|xs| live_deleted_items(xs, now).pop())
+    });
+    Effect::new(move |_| {
+        current.track();
+        error.set(None);
+    });
+
+    let on_undo = move |_| {
+        let Some(item) = current.get_untracked() else {
+            return;
+        };
+        if restoring.get_untracked() {
+            return;
+        }
+
+        match item.kind {
+            DeletedItemKind::Note => {
+                let Ok(note) = serde_json::from_value::<Note>(item.data.clone()) else {
+                    app_state_sv.with_value(|s| s.0.take_deleted(item.kind, &item.id));
+                    return;
+                };
+                let api_client = app_state_sv.with_value(|s| s.0.api_client.get_untracked());
+                restoring.set(true);
+                spawn_local(async move {
+                    match api_client.restore_note(&note.id).await {
+                        Ok(()) => app_state_sv.with_value(|s| {
+                            s.0.take_deleted(item.kind, &item.id);
+                            let in_current_db = s
+                                .0
+                                .current_database_id
+                                .get_untracked()
+                                .is_some_and(|id| id == note.database_id);
+                            if in_current_db {
+                                s.0.notes.update(|xs| {
+                                    if !xs.iter().any(|n| n.id == note.id) {
+                                        xs.push(note.clone());
+                                    }
+                                });
+                            }
+                            s.0.invalidate_titles_cache();
+                        }),
+                        Err(e) if e == ApiError::Unauthorized => {
+                            app_state_sv.with_value(handle_unauthorized_response)
+                        }
+                        Err(e) => error.set(Some(format!("Undo failed: {e}"))),
+                    }
+                    restoring.set(false);
+                });
+            }
+            DeletedItemKind::Nav => {
+                app_state_sv.with_value(|s| s.0.take_deleted(item.kind, &item.id));
+                let Some((db_id, note_id, navs)) = parse_deleted_navs_payload(&item.data) else {
+                    return;
+                };
+                // Local-first like the delete itself: the sync controller sends the
+                // `is-delete: false` upserts.
+                sync_sv.with_value(|s| s.restore_deleted_navs(&db_id, &note_id, &navs));
+                app_state_sv.with_value(|s| {
+                    s.0.invalidate_restored_navs(&db_id, &note_id);
+                    if s.0.current_note_id.get_untracked().as_deref() == Some(note_id.as_str()) {
+                        s.0.nav_restore_request.set(Some((note_id, navs)));
+                    }
+                });
+            }
+        }
+    };
+
+    view! {
+        <Portal>
+            {move || current.get().map(|item| {
+                let expires_ms = item.expires_ms;
+                let remaining_pct = move || {
+                    let left = (expires_ms - now.get()).clamp(0, UNDO_DELETE_WINDOW_MS);
+                    left as f64 * 100.0 / UNDO_DELETE_WINDOW_MS as f64
+                };
+                view! {
+                    <div
+                        role="status"
+                        class="fixed bottom-4 right-4 z-50 w-72 overflow-hidden rounded-md border border-border bg-card text-xs text-card-foreground shadow-lg"
+                    >
+                        <div class="flex items-center gap-2 px-3 py-2">
+                            <span class="min-w-0 flex-1 truncate">{deleted_item_label(&item)}</span>
+                            <Button
+                                variant=ButtonVariant::Outline
+                                size=ButtonSize::Sm
+                                class="h-7 shrink-0 px-2 text-xs"
+                                attr:disabled=move || restoring.get()
+                                on:click=on_undo
+                            >
+                                {move || if restoring.get() { "Undoing..." } else { "Undo" }}
+                            </Button>
+                        </div>
+                        <Show when=move || error.get().is_some()>
+                            <div class="px-3 pb-2 text-destructive">
+                                {move || error.get().unwrap_or_default()}
+                            </div>
+                        </Show>
+                        <div class="h-0.5 bg-muted">
+                            <div
+                                class="h-full bg-primary transition-[width] duration-200 ease-linear"
+                                style=move || format!("width: {:.1}%", remaining_pct())
+                            ></div>
+                        </div>
+                    </div>
+                }
+            })}
+        </Portal>
+    }
+}
+
+/// Cmd/Ctrl+Shift+D database switcher: fuzzy-filter the databases by name, Enter opens one.
+#[component]
+fn DbSwitcher() -> impl IntoView {
+    let app_state = expect_context::<AppContext>();
+    let navigate = StoredValue::new(use_navigate());
+
+    let open = app_state.0.db_switcher_open;
+    let databases = app_state.0.databases;
+    let current_db_id = app_state.0.current_database_id;
+
+    let query: RwSignal<String> = RwSignal::new(String::new());
+    let selected: RwSignal<usize> = RwSignal::new(0);
+    let input_ref: NodeRef<html::Input> = NodeRef::new();
+
+    // Each open starts fresh, with the current database preselected.
+    Effect::new(move |_| {
+        if open.get() {
+            query.set(String::new());
+            let current = current_db_id.get_untracked();
+            let idx = databases.with_untracked(|dbs| {
+                dbs.iter()
+                    .position(|d| Some(&d.id) == current.as_ref())
+                    .unwrap_or(0)
+            });
+            selected.set(idx);
+        }
+    });
+
+    Effect::new(move |_| {
+        if let Some(input) = input_ref.get() {
+            let _ = input.focus();
+        }
+    });
+
+    let results = Memo::new(move |_| {
+        let q = query.get();
+        databases.with(|dbs| rank_databases(dbs, &q))
+    });
+
+    let pick = move |id: String| {
+        open.set(false);
+        current_db_id.set(Some(id.clone()));
+        if let Some(storage) = web_sys::window().and_then(|w| w.local_storage().ok().flatten()) {
+            let _ = storage.set_item(CURRENT_DB_KEY, &id);
+        }
+        navigate.with_value(|nav| nav(&format!("/db/{}", id), Default::default()));
+    };
+
+    let on_keydown = move |ev: web_sys::KeyboardEvent| match ev.key().as_str() {
+        "ArrowDown" => {
+            ev.prevent_default();
+            let len = results.with_untracked(|xs| xs.len());
+            if len > 0 {
+                selected.update(|i| *i = (*i + 1).min(len - 1));
+            }
+        }
+        "ArrowUp" => {
+            ev.prevent_default();
+            selected.update(|i| *i = i.saturating_sub(1));
+        }
+        "Enter" => {
+            ev.prevent_default();
+            let picked =
+                results.with_untracked(|xs| xs.get(selected.get_untracked()).map(|d| d.id.clone()));
+            if let Some(id) = picked {
+                pick(id);
+            }
+        }
+        "Escape" => {
+            ev.prevent_default();
+            open.set(false);
+        }
+        _ => {}
+    };
+
+    view! {
+        <Show when=move || open.get()>
+            <div
+                class="fixed inset-0 z-50 flex items-start justify-center bg-black/30 px-4 pt-24"
+                on:mousedown=move |_| open.set(false)
+            >
+                <div
+                    class="w-full max-w-md rounded-md border border-border bg-background p-2 shadow-lg"
+                    on:mousedown=move |ev: web_sys::MouseEvent| ev.stop_propagation()
+                >
+                    <input
+                        node_ref=input_ref
+                        class="h-9 w-full rounded-md border border-input bg-transparent px-3 py-1 text-sm shadow-xs outline-none focus-visible:border-ring focus-visible:ring-2 focus-visible:ring-ring/50"
+                        placeholder="Switch database…"
+                        aria-label="Switch database"
+                        prop:value=move || query.get()
+                        on:input=move |ev| {
+                            query.set(event_target_value(&ev));
+                            selected.set(0);
+                        }
+                        on:keydown=on_keydown
+                    />
+                    {move || {
+                        let rows = results.get();
+                        if rows.is_empty() {
+                            return view! {
+                                <div class="px-2 py-3 text-xs text-muted-foreground">"No matches."</div>
+                            }
+                            .into_any();
+                        }
+
+                        let current = current_db_id.get();
+                        view! {
+                            <Command class="mt-2" should_filter=false disable_scripts=true>
+                                <CommandList class="max-h-80 min-h-0">
+                                    {rows
+                                        .into_iter()
+                                        .enumerate()
+                                        .map(|(i, db)| {
+                                            let is_selected = Signal::derive(move || selected.get() == i);
+                                            let is_current = current.as_deref() == Some(db.id.as_str());
+                                            let id = db.id.clone();
+                                            view! {
+                                                <CommandItem
+                                                    value=db.name.clone()
+                                                    selected=is_selected
+                                                    class="flex items-center justify-between gap-3 aria-selected:bg-accent"
+                                                    on_mousedown=Some(Callback::new(move |ev: web_sys::MouseEvent| {
+                                                        ev.prevent_default();
+                                                        pick(id.clone());
+                                                    }))
+                                                    on:mousemove=move |_| selected.set(i)
+                                                >
+                                                    <span class="min-w-0 truncate">{db.name}</span>
+                                                    <Show when=move || is_current fallback=|| ().into_view()>
+                                                        <span class="shrink-0 text-xs text-muted-foreground">"Current"</span>
+                                                    </Show>
+                                                </CommandItem>
+                                            }
+                                        })
+                                        .collect_view()}
+                                </CommandList>
+                            </Command>
+                        }
+                        .into_any()
+                    }}
+                </div>
+            </div>
+        </Show>
+    }
+}
+
 #[component]
 pub fn RootAuthed(children: ChildrenFn) -> impl IntoView {
     let app_state = expect_context::<AppContext>();
-    let is_authenticated = move || app_state.0.api_client.get().is_authenticated();
+    let has_valid_token = move || app_state.0.api_client.get().has_valid_token();
 
     // Store children so the view macro sees an `Fn` (not an `FnOnce`).
     let children = StoredValue::new(children);
 
     view! {
-        <Show when=is_authenticated fallback=move || view! { <LoginPage /> }>
+        <Show when=has_valid_token fallback=move || view! { <LoginPage /> }>
             <AppLayout>
                 {move || children.with_value(|c| c())}
             </AppLayout>
@@ -1627,29 +3786,12 @@ pub fn RootPage() -> impl IntoView {
     }
 }
 
-#[derive(Params, PartialEq, Clone, Debug)]
-pub struct DbRouteParams {
-    pub db_id: Option<String>,
-}
-
-#[derive(Params, PartialEq, Clone, Debug)]
-pub struct NoteRouteParams {
-    pub db_id: Option<String>,
-    pub note_id: Option<String>,
-}
-
-#[derive(Params, PartialEq, Clone, Debug)]
-pub struct UnreferencedRouteParams {
-    pub db_id: Option<String>,
-}
-
+/// Read-only viewer of a public database (`/public/:db_id[/note/:note_id]`). Fetches without
+/// the auth header, so it works logged out; nothing here edits or syncs.
 #[component]
-pub fn NotePage() -> impl IntoView {
+pub fn PublicDatabasePage() -> impl IntoView {
     let app_state = expect_context::<AppContext>();
     let params = leptos_router::hooks::use_params::<NoteRouteParams>();
-    let navigate = StoredValue::new(use_navigate());
-
-    // Route params: keep both tracked (for Effects/views) and untracked (for event handlers).
     let db_id = move || params.get().ok().and_then(|p| p.db_id).unwrap_or_default();
     let note_id = move || {
         params
@@ -1658,15 +3800,6 @@ pub fn NotePage() -> impl IntoView {
             .and_then(|p| p.note_id)
             .unwrap_or_default()
     };
-
-    let db_id_untracked = move || {
-        params
-            .get_untracked()
-            .ok()
-            .and_then(|p| p.db_id)
-            .unwrap_or_default()
-    };
-
     let note_id_untracked = move || {
         params
             .get_untracked()
@@ -1675,42 +3808,391 @@ pub fn NotePage() -> impl IntoView {
             .unwrap_or_default()
     };
 
-    // Drive global sync controller from tracked route changes.
-    let sync = expect_context::<crate::state::NoteSyncController>();
-    let sync_for_route = sync.clone();
-    Effect::new(move |_| {
-        sync_for_route.set_route(db_id(), note_id());
-    });
-
-    let title_value: RwSignal<String> = RwSignal::new(String::new());
-    // Original title snapshot for the current note (used to avoid redundant saves).
-    let title_original: RwSignal<String> = RwSignal::new(String::new());
-    // Track which note the title_value currently belongs to.
-    let title_note_id: RwSignal<String> = RwSignal::new(String::new());
-
-    // Optional: focus a specific nav by id (from backlinks click).
-    let query = use_query_map();
-    let focus_nav = move || query.get().get("focus_nav").unwrap_or_default();
-    let focused_nav_id: RwSignal<Option<String>> = RwSignal::new(None);
+    let notes: RwSignal<Vec<Note>> = RwSignal::new(vec![]);
+    let notes_loading: RwSignal<bool> = RwSignal::new(false);
+    let notes_error: RwSignal<Option<ApiError>> = RwSignal::new(None);
+    let navs: RwSignal<Vec<Nav>> = RwSignal::new(vec![]);
+    let navs_loading: RwSignal<bool> = RwSignal::new(false);
+    let navs_error: RwSignal<Option<String>> = RwSignal::new(None);
 
-    // Draft note (Roam-style): open by title without creating until first input/Enter.
-    // Route: `/db/:db_id/note?title=...` (same NotePage UI shell).
-    let draft_title = move || query.get().get("title").unwrap_or_default();
-    let is_draft_mode = move || {
-        let id = note_id();
-        id.trim().is_empty() && !draft_title().trim().is_empty()
+    // The backend answers 401/403 for a database that isn't public.
+    let is_private = move || {
+        notes_error.with(|e| matches!(e, Some(ApiError::Unauthorized | ApiError::Forbidden)))
     };
 
-    let draft_title_value: RwSignal<String> = RwSignal::new(String::new());
-    let draft_value: RwSignal<String> = RwSignal::new(String::new());
-    let draft_ref: NodeRef<html::Input> = NodeRef::new();
-    let draft_loading: RwSignal<bool> = RwSignal::new(false);
-    let draft_creating: RwSignal<bool> = RwSignal::new(false);
-    let draft_error: RwSignal<Option<String>> = RwSignal::new(None);
-
-    // Initialize draft title input from query param when in draft mode.
     Effect::new(move |_| {
-        if !is_draft_mode() {
+        let id = db_id();
+        if id.trim().is_empty() {
+            return;
+        }
+        let api_client = app_state.0.api_client.get_untracked();
+        notes_loading.set(true);
+        notes_error.set(None);
+        spawn_local(async move {
+            match api_client.get_public_note_list(&id).await {
+                Ok(xs) => notes.set(xs),
+                Err(e) => {
+                    notes.set(vec![]);
+                    notes_error.set(Some(e));
+                }
+            }
+            notes_loading.set(false);
+        });
+    });
+
+    Effect::new(move |_| {
+        let id = note_id();
+        navs.set(vec![]);
+        navs_error.set(None);
+        if id.trim().is_empty() {
+            navs_loading.set(false);
+            return;
+        }
+        let api_client = app_state.0.api_client.get_untracked();
+        navs_loading.set(true);
+        spawn_local(async move {
+            let result = api_client.get_public_note_navs(&id).await;
+            // A newer selection owns the outline now.
+            if note_id_untracked() != id {
+                return;
+            }
+            match result {
+                Ok(xs) => navs.set(xs),
+                Err(e) => navs_error.set(Some(e.to_string())),
+            }
+            navs_loading.set(false);
+        });
+    });
+
+    let note_title = move || {
+        let id = note_id();
+        notes.with(|xs| xs.iter().find(|n| n.id == id).map(|n| n.title.clone()))
+    };
+
+    view! {
+        <div class="min-h-screen bg-background text-foreground">
+            <header class="flex items-center gap-2 border-b border-border px-4 py-2">
+                <A href=move || format!("/public/{}", db_id()) {..} class="text-sm font-medium">
+                    "Hulunote"
+                </A>
+                <span class="rounded bg-muted px-1.5 py-0.5 text-[10px] text-muted-foreground">
+                    "Read-only"
+                </span>
+            </header>
+
+            <Show
+                when=move || !is_private()
+                fallback=|| view! {
+                    <div class="mx-auto max-w-md space-y-2 px-4 py-16 text-center">
+                        <h1 class="text-lg font-semibold">"This database is private"</h1>
+                        <p class="text-sm text-muted-foreground">
+                            "Its owner hasn't shared it, or stopped sharing it."
+                        </p>
+                    </div>
+                }
+            >
+                <div class="mx-auto flex max-w-5xl gap-6 px-4 py-4">
+                    <nav class="w-56 shrink-0 space-y-1" aria-label="Notes">
+                        <Show when=move || notes_loading.get() fallback=|| ().into_view()>
+                            <Spinner />
+                        </Show>
+                        {move || notes_error.get().map(|e| view! {
+                            <div class="text-xs text-destructive">{e.to_string()}</div>
+                        })}
+                        <For
+                            each=move || notes.get()
+                            key=|n| n.id.clone()
+                            children=move |n: Note| {
+                                let href = format!("/public/{}/note/{}", n.database_id, n.id);
+                                let id = n.id.clone();
+                                let active = move || note_id() == id;
+                                view! {
+                                    <A
+                                        href=href
+                                        {..}
+                                        class=move || {
+                                            if active() {
+                                                "block truncate rounded-md bg-surface-hover px-2 py-1 text-sm font-medium"
+                                            } else {
+                                                "block truncate rounded-md px-2 py-1 text-sm text-muted-foreground hover:bg-surface-hover"
+                                            }
+                                        }
+                                    >
+                                        {n.title.clone()}
+                                    </A>
+                                }
+                            }
+                        />
+                    </nav>
+
+                    <main class="min-w-0 flex-1 space-y-3">
+                        {move || match note_title() {
+                            Some(title) => view! { <h1 class="text-xl font-semibold">{title}</h1> }.into_any(),
+                            None => view! {
+                                <div class="text-sm text-muted-foreground">"Pick a note to read it."</div>
+                            }
+                            .into_any(),
+                        }}
+                        <Show when=move || navs_loading.get() fallback=|| ().into_view()>
+                            <Spinner />
+                        </Show>
+                        {move || navs_error.get().map(|e| view! {
+                            <div class="text-xs text-destructive">{e}</div>
+                        })}
+                        <div class="space-y-1">
+                            {move || navs.with(|xs| {
+                                live_outline(xs)
+                                    .into_iter()
+                                    .map(|(depth, nav)| view! {
+                                        <div
+                                            class="flex gap-2 text-sm"
+                                            style=format!("padding-left: {}rem", depth as f32 * 1.25)
+                                        >
+                                            <span class="select-none text-muted-foreground" aria-hidden="true">"•"</span>
+                                            <span class="min-w-0 flex-1 whitespace-pre-wrap break-words">
+                                                {nav.content.clone()}
+                                            </span>
+                                        </div>
+                                    })
+                                    .collect_view()
+                            })}
+                        </div>
+                    </main>
+                </div>
+            </Show>
+        </div>
+    }
+}
+
+#[derive(Params, PartialEq, Clone, Debug)]
+pub struct DbRouteParams {
+    pub db_id: Option<String>,
+}
+
+#[derive(Params, PartialEq, Clone, Debug)]
+pub struct NoteRouteParams {
+    pub db_id: Option<String>,
+    pub note_id: Option<String>,
+}
+
+#[derive(Params, PartialEq, Clone, Debug)]
+pub struct UnreferencedRouteParams {
+    pub db_id: Option<String>,
+}
+
+#[derive(Params, PartialEq, Clone, Debug)]
+pub struct TagRouteParams {
+    pub db_id: Option<String>,
+    pub tag: Option<String>,
+}
+
+/// Shown by a database route once the database list has loaded without its id.
+#[component]
+fn DatabaseNotFound(db_id: String) -> impl IntoView {
+    // Deleted elsewhere or a stale link: it should not come back through the recents.
+    forget_recent_db(&db_id);
+
+    view! {
+        <div class="flex h-[40vh] flex-col items-center justify-center gap-2 text-center">
+            <div class="text-sm font-medium">"Database not found"</div>
+            <div class="text-xs text-muted-foreground">
+                "It may have been deleted, or the link is out of date."
+            </div>
+            <a href="/" class="text-sm font-medium text-primary hover:underline">
+                "Back to all databases"
+            </a>
+        </div>
+    }
+}
+
+#[component]
+pub fn NotePage() -> impl IntoView {
+    let app_state = expect_context::<AppContext>();
+    let app_state_sv = StoredValue::new(app_state.clone());
+    let params = leptos_router::hooks::use_params::<NoteRouteParams>();
+    let navigate = StoredValue::new(use_navigate());
+
+    // Route params: keep both tracked (for Effects/views) and untracked (for event handlers).
+    let db_id = move || params.get().ok().and_then(|p| p.db_id).unwrap_or_default();
+    let note_id = move || {
+        params
+            .get()
+            .ok()
+            .and_then(|p| p.note_id)
+            .unwrap_or_default()
+    };
+
+    let db_id_untracked = move || {
+        params
+            .get_untracked()
+            .ok()
+            .and_then(|p| p.db_id)
+            .unwrap_or_default()
+    };
+
+    let note_id_untracked = move || {
+        params
+            .get_untracked()
+            .ok()
+            .and_then(|p| p.note_id)
+            .unwrap_or_default()
+    };
+
+    // Only once the database list has loaded; a deep link waits for it instead.
+    let db_missing = move || {
+        let id = db_id();
+        let loaded = app_state.0.db_load_timestamp.get().is_some();
+        app_state
+            .0
+            .databases
+            .with(|dbs| resolve_db_name(dbs, loaded, &id))
+            == DbName::Missing
+    };
+
+    // Drive global sync controller from tracked route changes.
+    let sync = expect_context::<crate::state::NoteSyncController>();
+    let sync_for_route = sync.clone();
+    Effect::new(move |_| {
+        sync_for_route.set_route(db_id(), note_id());
+    });
+
+    // Publish the open note for the layout (breadcrumbs).
+    let current_note_id = app_state.0.current_note_id;
+    Effect::new(move |_| {
+        let id = note_id();
+        current_note_id.set((!id.trim().is_empty()).then_some(id));
+    });
+    on_cleanup(move || {
+        let _ = current_note_id.try_set(None);
+    });
+
+    let title_value: RwSignal<String> = RwSignal::new(String::new());
+    // Original title snapshot for the current note (used to avoid redundant saves).
+    let title_original: RwSignal<String> = RwSignal::new(String::new());
+    // Track which note the title_value currently belongs to.
+    let title_note_id: RwSignal<String> = RwSignal::new(String::new());
+
+    // Optional: focus a specific nav by id (from backlinks click).
+    let query = use_query_map();
+    let focus_nav = move || query.get().get("focus_nav").unwrap_or_default();
+    let focused_nav_id: RwSignal<Option<String>> = RwSignal::new(None);
+
+    // Outline of the current note (filled by OutlineEditor; read by Export/Copy).
+    let outline_navs: RwSignal<Vec<Nav>> = RwSignal::new(vec![]);
+    // Title Cmd/Ctrl+Enter -> edit the first block (handled by OutlineEditor).
+    let edit_first_block: RwSignal<bool> = RwSignal::new(false);
+
+    // PIN-locked note not yet unlocked in this tab: the outline stays unmounted.
+    let note_locked = move || app_state_sv.with_value(|s| s.0.is_note_locked(&note_id()));
+    Effect::new(move |_| {
+        if note_locked() {
+            // Don't let Export/Copy see a previously opened note's outline.
+            outline_navs.set(vec![]);
+        }
+    });
+    // Block being typed in (nav id, unsaved content), mirrored by OutlineEditor.
+    let outline_live_edit: RwSignal<Option<(String, String)>> = RwSignal::new(None);
+    // "42 nodes · ~350 words" under the title; follows typing before it is committed.
+    let outline_stats_label = Memo::new(move |_| {
+        let (nodes, words) = outline_live_edit.with(|edit| {
+            let editing = edit.as_ref().map(|(id, v)| (id.as_str(), v.as_str()));
+            outline_navs.with(|xs| outline_stats(xs, editing))
+        });
+        format_outline_stats(nodes, words)
+    });
+    let open_tasks = Memo::new(move |_| outline_navs.with(|xs| count_open_todos(xs)));
+    // "Last edited ..." under the title: the latest block edit, local or from the server.
+    let now: RwSignal<i64> = RwSignal::new(now_ms());
+    if let Ok(handle) = set_interval_with_handle(
+        move || now.set(now_ms()),
+        std::time::Duration::from_secs(60),
+    ) {
+        on_cleanup(move || handle.clear());
+    }
+    let drafts_version = sync.drafts_version();
+    let last_edited_label = Memo::new(move |_| {
+        drafts_version.track();
+        let draft = load_note_draft(&db_id_untracked(), &note_id_untracked());
+        let local = nav_content_edited_ms(&draft);
+        let last = outline_navs.with(|xs| {
+            xs.iter()
+                .filter(|n| !n.is_delete)
+                .filter_map(|n| nav_edited_ms(n, local.get(&n.id).copied()))
+                .max()
+        });
+        last.map(|then| format!("Last edited {}", format_relative_ms(then, now.get())))
+    });
+    // "Copied!" toast after Copy as Markdown.
+    let copied: RwSignal<bool> = RwSignal::new(false);
+    // Error toast when the server rejects a rename (the sync controller rolls it back).
+    let title_save_error = sync.title_save_error();
+    Effect::new(move |_| {
+        if title_save_error.get().is_some() {
+            set_timeout(move || title_save_error.set(None), std::time::Duration::from_secs(4));
+        }
+    });
+
+    // Cmd/Ctrl+Shift+[ / ] (raised in AppLayout). Unlike the header buttons, these persist
+    // `is_display` per changed block through the sync controller, like a single toggle.
+    let fold_request = app_state.0.outline_fold_request;
+    Effect::new(move |_| {
+        let Some(collapse) = fold_request.get() else {
+            return;
+        };
+        fold_request.set(None);
+
+        let mut changed: Vec<String> = vec![];
+        outline_navs.update(|xs| {
+            changed = if collapse { collapse_all(xs) } else { expand_all(xs) };
+        });
+        for nav in outline_navs
+            .get_untracked()
+            .into_iter()
+            .filter(|n| changed.contains(&n.id))
+        {
+            sync.on_nav_meta_changed(&nav);
+        }
+    });
+
+    // Blocks put back by the undo snackbar (drafts and snapshot are restored already).
+    let nav_restore_request = app_state.0.nav_restore_request;
+    Effect::new(move |_| {
+        let Some((restored_note_id, restored)) = nav_restore_request.get() else {
+            return;
+        };
+        nav_restore_request.set(None);
+        if restored_note_id != note_id_untracked() {
+            return;
+        }
+
+        outline_navs.update(|xs| {
+            for r in restored {
+                match xs.iter_mut().find(|n| n.id == r.id) {
+                    Some(n) => *n = r,
+                    None => xs.push(r),
+                }
+            }
+        });
+    });
+
+    // Draft note (Roam-style): open by title without creating until first input/Enter.
+    // Route: `/db/:db_id/note?title=...` (same NotePage UI shell).
+    let draft_title = move || query.get().get("title").unwrap_or_default();
+    let is_draft_mode = move || {
+        let id = note_id();
+        id.trim().is_empty() && !draft_title().trim().is_empty()
+    };
+
+    let draft_title_value: RwSignal<String> = RwSignal::new(String::new());
+    let draft_value: RwSignal<String> = RwSignal::new(String::new());
+    let draft_ref: NodeRef<html::Input> = NodeRef::new();
+    let draft_loading: RwSignal<bool> = RwSignal::new(false);
+    let draft_creating: RwSignal<bool> = RwSignal::new(false);
+    let draft_error: RwSignal<Option<String>> = RwSignal::new(None);
+
+    // Initialize draft title input from query param when in draft mode.
+    Effect::new(move |_| {
+        if !is_draft_mode() {
             return;
         }
         let t = draft_title();
@@ -1760,6 +4242,7 @@ pub fn NotePage() -> impl IntoView {
     let all_db_navs_loading: RwSignal<bool> = RwSignal::new(false);
     let all_db_navs_error: RwSignal<Option<String>> = RwSignal::new(None);
     let all_db_navs_req_id: RwSignal<u64> = RwSignal::new(0);
+    let backlinks_open: RwSignal<bool> = RwSignal::new(true);
 
     // If a focus_nav is provided (e.g. from backlinks click), scroll it into view and highlight it.
     Effect::new(move |_| {
@@ -1815,17 +4298,12 @@ pub fn NotePage() -> impl IntoView {
             // Kick off a load with stale-response protection.
             app_state.0.notes_last_loaded_db_id.set(Some(db.clone()));
 
-            let req_id = app_state
-                .0
-                .notes_request_id
-                .get_untracked()
-                .saturating_add(1);
-            app_state.0.notes_request_id.set(req_id);
+            let (req_id, abort) = app_state.0.begin_notes_request();
 
             app_state.0.notes_loading.set(true);
             app_state.0.notes_error.set(None);
 
-            let api_client = app_state.0.api_client.get_untracked();
+            let api_client = app_state.0.api_client.get_untracked().with_abort(&abort);
             let sync_sv = StoredValue::new(expect_context::<crate::state::NoteSyncController>());
             spawn_local(async move {
                 let result = api_client.get_all_note_list(&db).await;
@@ -1840,19 +4318,15 @@ pub fn NotePage() -> impl IntoView {
                         app_state.0.notes.set(notes);
                     }
                     Err(e) => {
-                        if e.kind == crate::api::ApiErrorKind::Unauthorized {
-                            let mut c = app_state.0.api_client.get_untracked();
-                            c.logout();
-                            app_state.0.api_client.set(c);
-                            app_state.0.current_user.set(None);
-                            let _ = window().location().set_href("/login");
+                        if e == ApiError::Unauthorized {
+                            app_state_sv.with_value(handle_unauthorized_response);
                         } else {
                             let _ = sync_sv.try_with_value(|s| s.mark_backend_offline_api(&e));
                             let offline_now = sync_sv
                                 .try_with_value(|s| !s.is_backend_online())
                                 .unwrap_or(false);
                             if !offline_now {
-                                app_state.0.notes_error.set(Some(e.to_string()));
+                                app_state.0.notes_error.set(Some(e));
                             }
                         }
                     }
@@ -1862,6 +4336,40 @@ pub fn NotePage() -> impl IntoView {
         }
     });
 
+    // Prose content stored on the note itself (separate from the outline); usually empty.
+    let note_content: RwSignal<String> = RwSignal::new(String::new());
+    Effect::new(move |_| {
+        let id = note_id();
+        if id.trim().is_empty() {
+            note_content.set(String::new());
+            return;
+        }
+
+        if let Some(c) = app_state
+            .0
+            .note_content_cache
+            .with_untracked(|m| m.get(&id).cloned())
+        {
+            note_content.set(c);
+            return;
+        }
+
+        note_content.set(String::new());
+        let api_client = app_state.0.api_client.get_untracked();
+        let cache = app_state.0.note_content_cache;
+        spawn_local(async move {
+            // Best-effort: failures just leave the section hidden.
+            if let Ok(content) = api_client.get_note_content(&id).await {
+                cache.update(|m| {
+                    m.insert(id.clone(), content.clone());
+                });
+                if note_id_untracked() == id {
+                    note_content.set(content);
+                }
+            }
+        });
+    });
+
     // Phase 7: load all navs in current DB for backlink computation.
     let sync_sv = StoredValue::new(expect_context::<crate::state::NoteSyncController>());
     Effect::new(move |_| {
@@ -1871,6 +4379,15 @@ pub fn NotePage() -> impl IntoView {
             return;
         }
 
+        // Cached per database; the sync controller drops the entry when a saved edit
+        // adds or removes a link.
+        if let Some(navs) = app_state.0.all_navs_cache.with(|m| m.get(&db).cloned()) {
+            all_db_navs.set(navs);
+            all_db_navs_loading.set(false);
+            all_db_navs_error.set(None);
+            return;
+        }
+
         // Local-first UX: when offline, we don't fetch backlinks.
         let offline_now = sync_sv
             .try_with_value(|s| !s.is_backend_online())
@@ -1901,15 +4418,14 @@ pub fn NotePage() -> impl IntoView {
             match result {
                 Ok(navs) => {
                     let _ = sync_sv.try_with_value(|s| s.mark_backend_online());
+                    app_state.0.all_navs_cache.update(|m| {
+                        m.insert(db.clone(), navs.clone());
+                    });
                     all_db_navs.set(navs)
                 }
                 Err(e) => {
-                    if e.kind == crate::api::ApiErrorKind::Unauthorized {
-                        let mut c = app_state.0.api_client.get_untracked();
-                        c.logout();
-                        app_state.0.api_client.set(c);
-                        app_state.0.current_user.set(None);
-                        let _ = window().location().set_href("/login");
+                    if e == ApiError::Unauthorized {
+                        app_state_sv.with_value(handle_unauthorized_response);
                     } else {
                         let _ = sync_sv.try_with_value(|s| s.mark_backend_offline_api(&e));
                         // Local-first UX: hide backlink errors when backend is unreachable.
@@ -1969,9 +4485,15 @@ pub fn NotePage() -> impl IntoView {
                 title_note_id.set(id.clone());
                 title_value.set(n.title.clone());
                 title_original.set(n.title.clone());
-            } else if title_value.get().trim().is_empty() {
-                title_value.set(n.title.clone());
-                title_original.set(n.title.clone());
+            } else {
+                // Renamed elsewhere (e.g. inline from a note list): follow the new title
+                // unless there are unsaved edits here.
+                let current = title_value.get();
+                let original = title_original.get_untracked();
+                if should_follow_server_title(&current, &original, &n.title) {
+                    title_value.set(n.title.clone());
+                    title_original.set(n.title.clone());
+                }
             }
             write_recent_note(&db, &id, &n.title);
         } else if let Some(snap) = load_note_snapshot(&db, &id) {
@@ -1989,11 +4511,9 @@ pub fn NotePage() -> impl IntoView {
             write_recent_note(&db, &id, &id);
         }
 
-        // Keep recent DB fresh too.
+        // Keep recent DB fresh too, once the list has its name (this re-runs when it loads).
         if let Some(d) = app_state.0.databases.get().into_iter().find(|d| d.id == db) {
             write_recent_db(&d.id, &d.name);
-        } else {
-            write_recent_db(&db, &db);
         }
     });
 
@@ -2019,12 +4539,21 @@ pub fn NotePage() -> impl IntoView {
         // Update UI immediately for responsive feedback.
         title_original.set(new_title.clone());
 
-        // Immediately update local notes cache so sidebar reflects the change.
-        app_state.0.notes.update(|xs| {
+        // Optimistic: note lists and Recent Notes show the new title right away; the sync
+        // controller rolls them back if the server rejects it.
+        let set_title = |xs: &mut Vec<Note>| {
             if let Some(n) = xs.iter_mut().find(|n| n.id == id) {
                 n.title = new_title.clone();
             }
+        };
+        app_state.0.notes.update(set_title);
+        let db = db_id_untracked();
+        app_state.0.quick_switch_index.update(|m| {
+            if let Some(xs) = m.get_mut(&db) {
+                set_title(xs);
+            }
         });
+        write_recent_note(&db, &id, &new_title);
 
         // Route through NoteSyncController for debounce + retry + offline handling.
         let _ = sync_sv.try_with_value(|s| s.on_title_changed(&new_title));
@@ -2079,12 +4608,8 @@ pub fn NotePage() -> impl IntoView {
                     );
                 }
                 Err(e) => {
-                    if e.kind == crate::api::ApiErrorKind::Unauthorized {
-                        let mut c = app_state.0.api_client.get_untracked();
-                        c.logout();
-                        app_state.0.api_client.set(c);
-                        app_state.0.current_user.set(None);
-                        let _ = window().location().set_href("/login");
+                    if e == ApiError::Unauthorized {
+                        app_state_sv.with_value(handle_unauthorized_response);
                     } else {
                         draft_error.set(Some(e.to_string()));
                     }
@@ -2159,7 +4684,7 @@ pub fn NotePage() -> impl IntoView {
                 Ok(n) => n,
                 Err(e) => {
                     draft_creating.set(false);
-                    draft_error.set(Some(e));
+                    draft_error.set(Some(e.to_string()));
                     return;
                 }
             };
@@ -2200,7 +4725,11 @@ pub fn NotePage() -> impl IntoView {
 
     view! {
         <>
-            <Show when=move || is_draft_mode() fallback=|| ().into_view()>
+            <Show when=db_missing fallback=|| ().into_view()>
+                <DatabaseNotFound db_id=db_id_untracked() />
+            </Show>
+
+            <Show when=move || is_draft_mode() && !db_missing() fallback=|| ().into_view()>
                 <div class="space-y-3">
                     <div class="space-y-2">
                         <div class="flex items-center gap-2">
@@ -2271,7 +4800,7 @@ pub fn NotePage() -> impl IntoView {
                 </div>
             </Show>
 
-            <Show when=move || !is_draft_mode() fallback=|| ().into_view()>
+            <Show when=move || !is_draft_mode() && !db_missing() fallback=|| ().into_view()>
                 <div class="space-y-3">
             <div class="space-y-2">
                 <div class="flex items-center gap-2">
@@ -2309,6 +4838,11 @@ pub fn NotePage() -> impl IntoView {
                                 {
                                     let _ = t.blur();
                                 }
+
+                                // Cmd/Ctrl+Enter: continue straight into the outline.
+                                if ev.meta_key() || ev.ctrl_key() {
+                                    edit_first_block.set(true);
+                                }
                             }
                         }
                     />
@@ -2321,6 +4855,97 @@ pub fn NotePage() -> impl IntoView {
                             </div>
                         </Show>
                     </div>
+
+                    <Button
+                        variant=ButtonVariant::Ghost
+                        size=ButtonSize::Sm
+                        attr:title="Collapse all blocks"
+                        on:click=move |_| outline_navs.update(|xs| set_all_collapsed(xs, true))
+                    >
+                        "Collapse all"
+                    </Button>
+                    <Button
+                        variant=ButtonVariant::Ghost
+                        size=ButtonSize::Sm
+                        attr:title="Expand all blocks"
+                        on:click=move |_| outline_navs.update(|xs| set_all_collapsed(xs, false))
+                    >
+                        "Expand all"
+                    </Button>
+
+                    <Button
+                        variant=ButtonVariant::Ghost
+                        size=ButtonSize::Sm
+                        attr:title="Export as Markdown"
+                        on:click=move |_| {
+                            let markdown = outline_navs.with_untracked(|xs| navs_to_markdown(xs));
+                            let filename = markdown_export_filename(&title_value.get_untracked());
+                            if let Err(e) = download_markdown(&filename, &markdown) {
+                                error.set(Some(format!("Export failed: {e}")));
+                            }
+                        }
+                    >
+                        "Export"
+                    </Button>
+                    <Button
+                        variant=ButtonVariant::Ghost
+                        size=ButtonSize::Sm
+                        attr:title="Copy as Markdown"
+                        attr:disabled=move || outline_navs.with(|xs| xs.iter().all(|n| n.is_delete))
+                        on:click=move |_| {
+                            let markdown = outline_navs.with_untracked(|xs| navs_to_markdown(xs));
+                            copy_to_clipboard(&markdown, move |result| match result {
+                                Ok(()) => {
+                                    copied.set(true);
+                                    set_timeout(move || copied.set(false), std::time::Duration::from_secs(2));
+                                }
+                                Err(e) => error.set(Some(format!("Copy failed: {e}"))),
+                            });
+                        }
+                    >
+                        "Copy"
+                    </Button>
+                    <NoteDuplicateButton
+                        source=Signal::derive(move || Note {
+                            id: note_id(),
+                            database_id: db_id(),
+                            title: title_value.get(),
+                            content: String::new(),
+                            created_at: String::new(),
+                            updated_at: String::new(),
+                        })
+                        on_error=move |e: String| error.set(Some(e))
+                    />
+                    <NoteMoveButton
+                        source=Signal::derive(move || Note {
+                            id: note_id(),
+                            database_id: db_id(),
+                            title: title_value.get(),
+                            content: String::new(),
+                            created_at: String::new(),
+                            updated_at: String::new(),
+                        })
+                        shortcut=true
+                        on_error=move |e: String| error.set(Some(e))
+                    />
+                    <NoteLockButton note_id=Signal::derive(note_id) />
+                </div>
+                <div class="flex items-center gap-2 text-xs text-muted-foreground" data-outline-stats>
+                    <span>{move || outline_stats_label.get()}</span>
+                    {move || last_edited_label.get().map(|label| view! {
+                        <>
+                            <span aria-hidden="true">"·"</span>
+                            <span>{label}</span>
+                        </>
+                    })}
+                    {move || {
+                        let n = open_tasks.get();
+                        (n > 0).then(|| view! {
+                            <span class="rounded-full bg-muted px-2 py-0.5 text-foreground" data-open-tasks>
+                                {if n == 1 { "1 open task".to_string() } else { format!("{n} open tasks") }}
+                            </span>
+                        })
+                    }}
                 </div>
 
                 <Show when=move || error.get().is_some() fallback=|| ().into_view()>
@@ -2331,7 +4956,41 @@ pub fn NotePage() -> impl IntoView {
                     })}
                 </Show>
 
-                <OutlineEditor note_id=note_id focused_nav_id=focused_nav_id />
+                <Show when=move || copied.get() fallback=|| ().into_view()>
+                    <div
+                        role="status"
+                        class="fixed bottom-4 right-4 z-50 rounded-md border border-border bg-card px-3 py-2 text-xs text-card-foreground shadow-lg"
+                    >
+                        "Copied!"
+                    </div>
+                </Show>
+                {move || title_save_error.get().map(|e| view! {
+                    <div
+                        role="alert"
+                        class="fixed bottom-4 right-4 z-50 rounded-md border border-destructive/30 bg-card px-3 py-2 text-xs text-destructive shadow-lg"
+                    >
+                        {e}
+                    </div>
+                })}
+
+                <Show
+                    when=move || !note_locked()
+                    fallback=move || view! { <NoteUnlockForm note_id=Signal::derive(note_id) /> }
+                >
+                    <OutlineEditor
+                        note_id=note_id
+                        focused_nav_id=focused_nav_id
+                        navs=outline_navs
+                        live_edit=outline_live_edit
+                        edit_first_block=edit_first_block
+                    />
+
+                    <Show when=move || !note_content.get().trim().is_empty()>
+                        <pre class="mt-4 whitespace-pre-wrap break-words rounded-md border border-border bg-muted/30 p-3 text-sm">
+                            {move || note_content.get()}
+                        </pre>
+                    </Show>
+                </Show>
 
                 <hr class="my-4 border-border" />
 
@@ -2367,141 +5026,176 @@ pub fn NotePage() -> impl IntoView {
                         nav_by_id.insert(n.id.clone(), n.clone());
                     }
 
-                    // Collect matching references (note_id -> list of (nav_id, content)).
-                    let mut refs: std::collections::BTreeMap<String, Vec<(String, String)>> =
-                        std::collections::BTreeMap::new();
-
-                    for nav in all_navs.into_iter() {
-                        if nav.is_delete {
-                            continue;
-                        }
-                        if nav.note_id == current_note_id {
-                            continue;
-                        }
-
-                        let links = extract_wiki_links(&nav.content);
-                        if links.into_iter().any(|l| l == title) {
-                            refs.entry(nav.note_id.clone())
-                                .or_default()
-                                .push((nav.id.clone(), nav.content.clone()));
-                        }
-                    }
+                    // Referencing notes (excluding this one) with their matching navs.
+                    let notes = app_state.0.notes.get();
+                    let mut refs = find_backlinks(&all_navs, &notes, &title);
+                    refs.retain(|(note, _)| note.id != current_note_id);
+
+                    let ref_count: usize = refs.iter().map(|(_, xs)| xs.len()).sum();
+                    let header = view! {
+                        <button
+                            class="flex w-full items-center gap-1 text-left text-sm font-medium"
+                            attr:aria-expanded=move || backlinks_open.get().to_string()
+                            on:click=move |_| backlinks_open.update(|v| *v = !*v)
+                        >
+                            <span class="w-4 text-muted-foreground">
+                                {move || if backlinks_open.get() { "▾" } else { "▸" }}
+                            </span>
+                            "Linked references"
+                            <span class="ml-1 text-xs text-muted-foreground">{ref_count}</span>
+                        </button>
+                    };
 
                     if refs.is_empty() {
-                        // If there are no backlinks, do not show the card at all.
-                        return ().into_view().into_any();
+                        return view! {
+                            <div class="mt-4 p-3">
+                                {header}
+                                <Show when=move || backlinks_open.get() fallback=|| ().into_view()>
+                                    <div class="mt-2 text-xs text-muted-foreground">"No linked references"</div>
+                                </Show>
+                            </div>
+                        }
+                        .into_any();
                     }
 
                     let db = db_id();
-                    let notes = app_state.0.notes.get();
-
-                    view! {
-                        <div class="mt-4 p-3">
-                            <div class="mt-2 space-y-2">
-                                {refs
-                                    .into_iter()
-                                    .map(|(note_id, items)| {
-                                        let note = notes.iter().find(|n| n.id == note_id).cloned();
-                                        let note_title = note
-                                            .as_ref()
-                                            .map(|n| n.title.clone())
-                                            .unwrap_or_else(|| note_id.clone());
-                                        let note_href = format!("/db/{}/note/{}", db, note_id);
+                    let title_norm = normalize_roam_page_title(&title).to_lowercase();
+
+                    let cards = refs
+                        .into_iter()
+                        .map(|(note, items)| {
+                            let note_id = note.id.clone();
+                            let note_title = get_title_override(&db, &note.id, &note.title);
+                            let note_href = format!("/db/{}/note/{}", db, note_id);
+                            let item_count = items.len();
+
+                            view! {
+                                <details class="group/ref p-2" open>
+                                    <summary class="flex cursor-pointer list-none items-center gap-1">
+                                        <span class="w-4 text-xs text-muted-foreground group-open/ref:rotate-90 transition-transform">"▸"</span>
+                                        <a
+                                            href=note_href
+                                            class="min-w-0 flex-1 truncate text-sm font-medium hover:underline"
+                                        >
+                                            {note_title}
+                                        </a>
+                                        <span class="text-xs text-muted-foreground">{item_count}</span>
+                                    </summary>
 
-                                        view! {
-                                            <div class="p-2">
-                                                <a
-                                                    href=note_href
-                                                    class="block truncate text-sm font-medium hover:underline"
-                                                >
-                                                    {note_title}
-                                                </a>
+                                    <div class="mt-1 space-y-1">
+                                        {items
+                                            .into_iter()
+                                            .map(|nav| {
+                                                let nav_id = nav.id;
+                                                let content = nav.content;
+                                                let href = format!(
+                                                    "/db/{}/note/{}?focus_nav={}",
+                                                    db,
+                                                    note_id,
+                                                    urlencoding::encode(&nav_id)
+                                                );
 
-                                                <div class="mt-1 space-y-1">
-                                                    {items
+                                                // Parent chain (context) for this nav.
+                                                let mut chain: Vec<String> = vec![];
+                                                let mut cur = nav_by_id.get(&nav_id).cloned();
+                                                let root_container_parent_id =
+                                                    ROOT_CONTAINER_PARENT_ID.to_string();
+                                                let mut guard = 0;
+                                                while let Some(n) = cur {
+                                                    guard += 1;
+                                                    if guard > 32 {
+                                                        break;
+                                                    }
+                                                    if n.parid == root_container_parent_id {
+                                                        break;
+                                                    }
+                                                    if let Some(p) = nav_by_id.get(&n.parid) {
+                                                        let c = p.content.trim().to_string();
+                                                        if !c.is_empty() {
+                                                            chain.push(c);
+                                                        }
+                                                        cur = Some(p.clone());
+                                                    } else {
+                                                        break;
+                                                    }
+                                                }
+                                                chain.reverse();
+
+                                                let chain_display = if chain.is_empty() {
+                                                    String::new()
+                                                } else {
+                                                    // Keep it short.
+                                                    let max = 3usize;
+                                                    let mut s = String::new();
+                                                    if chain.len() > max {
+                                                        s.push_str("… ");
+                                                    }
+                                                    for (i, part) in chain
                                                         .into_iter()
-                                                        .map(|(nav_id, content)| {
-                                                            let href = format!(
-                                                                "/db/{}/note/{}?focus_nav={}",
-                                                                db,
-                                                                note_id,
-                                                                urlencoding::encode(&nav_id)
-                                                            );
-
-                                                            // Parent chain (context) for this nav.
-                                                            let mut chain: Vec<String> = vec![];
-                                                            let mut cur = nav_by_id.get(&nav_id).cloned();
-                                                            let root_container_parent_id =
-                                                                ROOT_CONTAINER_PARENT_ID.to_string();
-                                                            let mut guard = 0;
-                                                            while let Some(n) = cur {
-                                                                guard += 1;
-                                                                if guard > 32 {
-                                                                    break;
-                                                                }
-                                                                if n.parid == root_container_parent_id {
-                                                                    break;
-                                                                }
-                                                                if let Some(p) = nav_by_id.get(&n.parid) {
-                                                                    let c = p.content.trim().to_string();
-                                                                    if !c.is_empty() {
-                                                                        chain.push(c);
-                                                                    }
-                                                                    cur = Some(p.clone());
-                                                                } else {
-                                                                    break;
-                                                                }
-                                                            }
-                                                            chain.reverse();
+                                                        .rev()
+                                                        .take(max)
+                                                        .collect::<Vec<_>>()
+                                                        .into_iter()
+                                                        .rev()
+                                                        .enumerate()
+                                                    {
+                                                        if i > 0 {
+                                                            s.push_str(" › ");
+                                                        }
+                                                        s.push_str(&part);
+                                                    }
+                                                    s
+                                                };
 
-                                                            let chain_display = if chain.is_empty() {
-                                                                String::new()
+                                                // Highlight the link(s) pointing at this note.
+                                                let content_view = parse_wiki_tokens(&content)
+                                                    .into_iter()
+                                                    .map(|t| match t {
+                                                        WikiToken::Text(s) => view! { <span>{s}</span> }.into_any(),
+                                                        WikiToken::BlockRef(id) => view! { <span>"(("{id}"))"</span> }.into_any(),
+                                                        WikiToken::Todo { done } => {
+                                                            view! { <span>{if done { "☑ " } else { "☐ " }}</span> }.into_any()
+                                                        }
+                                                        WikiToken::Link(label) => {
+                                                            let is_self = normalize_roam_page_title(&label).to_lowercase() == title_norm;
+                                                            let class = if is_self {
+                                                                "rounded bg-accent-soft px-0.5 font-medium text-foreground"
                                                             } else {
-                                                                // Keep it short.
-                                                                let max = 3usize;
-                                                                let mut s = String::new();
-                                                                if chain.len() > max {
-                                                                    s.push_str("… ");
-                                                                }
-                                                                for (i, part) in chain
-                                                                    .into_iter()
-                                                                    .rev()
-                                                                    .take(max)
-                                                                    .collect::<Vec<_>>()
-                                                                    .into_iter()
-                                                                    .rev()
-                                                                    .enumerate()
-                                                                {
-                                                                    if i > 0 {
-                                                                        s.push_str(" › ");
-                                                                    }
-                                                                    s.push_str(&part);
-                                                                }
-                                                                s
+                                                                ""
                                                             };
+                                                            view! { <span class=class>"[["{label}"]]"</span> }.into_any()
+                                                        }
+                                                    })
+                                                    .collect_view();
 
-                                                            let chain_display_for_show = chain_display.clone();
-                                                            view! {
-                                                                <a
-                                                                    href=href
-                                                                    class="block rounded-md border border-border/60 bg-background px-2 py-1 text-xs transition-colors hover:bg-surface-hover"
-                                                                >
-                                                                    <Show
-                                                                        when=move || !chain_display_for_show.is_empty()
-                                                                        fallback=|| ().into_view()
-                                                                    >
-                                                                        <div class="mb-1 truncate text-[11px] text-muted-foreground">{chain_display.clone()}</div>
-                                                                    </Show>
-                                                                    <span class="line-clamp-2 whitespace-pre-wrap text-muted-foreground">{content}</span>
-                                                                </a>
-                                                            }
-                                                        })
-                                                        .collect_view()}
-                                                </div>
-                                            </div>
-                                        }
-                                    })
-                                    .collect_view()}
+                                                let chain_display_for_show = chain_display.clone();
+                                                view! {
+                                                    <a
+                                                        href=href
+                                                        class="block rounded-md border border-border/60 bg-background px-2 py-1 text-xs transition-colors hover:bg-surface-hover"
+                                                    >
+                                                        <Show
+                                                            when=move || !chain_display_for_show.is_empty()
+                                                            fallback=|| ().into_view()
+                                                        >
+                                                            <div class="mb-1 truncate text-[11px] text-muted-foreground">{chain_display.clone()}</div>
+                                                        </Show>
+                                                        <span class="line-clamp-2 whitespace-pre-wrap text-muted-foreground">{content_view}</span>
+                                                    </a>
+                                                }
+                                            })
+                                            .collect_view()}
+                                    </div>
+                                </details>
+                            }
+                        })
+                        .collect_view();
+
+                    view! {
+                        <div class="mt-4 p-3">
+                            {header}
+                            <div class=move || if backlinks_open.get() { "mt-2 space-y-2" } else { "hidden" }>
+                                {cards}
                             </div>
                         </div>
                     }
@@ -2514,531 +5208,790 @@ pub fn NotePage() -> impl IntoView {
     }
 }
 
-#[component]
-pub fn DbHomePage() -> impl IntoView {
-    let app_state = expect_context::<AppContext>();
-    let params = leptos_router::hooks::use_params::<DbRouteParams>();
-    let navigate = StoredValue::new(use_navigate());
-    let location = use_location();
-    let pathname = move || location.pathname.get();
+/// Fetch the note list previews of `db_id` unless they are loaded or being loaded. An empty
+/// entry marks the request in flight; a failed one is dropped so a later call retries.
+fn load_note_previews(app_state: &AppContext, db_id: &str) {
+    let note_previews = app_state.0.note_previews;
+    if db_id.trim().is_empty() || note_previews.with_untracked(|m| m.contains_key(db_id)) {
+        return;
+    }
+    note_previews.update(|m| {
+        m.insert(db_id.to_string(), HashMap::new());
+    });
 
-    let rename_open: RwSignal<bool> = RwSignal::new(false);
+    let api_client = app_state.0.api_client.get_untracked();
+    let db_id = db_id.to_string();
+    spawn_local(async move {
+        match api_client.get_note_previews(&db_id).await {
+            Ok(previews) => note_previews.update(|m| {
+                m.insert(db_id, previews);
+            }),
+            Err(_) => note_previews.update(|m| {
+                m.remove(&db_id);
+            }),
+        }
+    });
+}
 
-    // Phase 5: create note (non-paginated)
-    let create_note_loading: RwSignal<bool> = RwSignal::new(false);
-    let create_note_error: RwSignal<Option<String>> = RwSignal::new(None);
-    let rename_value: RwSignal<String> = RwSignal::new(String::new());
-    let rename_loading: RwSignal<bool> = RwSignal::new(false);
-    let rename_error: RwSignal<Option<String>> = RwSignal::new(None);
+/// Rename a note from a list row without opening it. Local-first like the NotePage title:
+/// the title draft, note lists and recents change right away; a failed save is retried
+/// by `NoteSyncController` from the draft.
+fn rename_note_from_list(app_state: &AppContext, db_id: &str, note_id: &str, title: &str) {
+    touch_title(db_id, note_id, title);
+    let updated_ms = load_note_draft(db_id, note_id)
+        .title
+        .map(|f| f.updated_ms)
+        .unwrap_or_default();
+
+    let set_title = |xs: &mut Vec<Note>| {
+        if let Some(n) = xs.iter_mut().find(|n| n.id == note_id) {
+            n.title = title.to_string();
+        }
+    };
+    app_state.0.notes.update(set_title);
+    app_state.0.quick_switch_index.update(|m| {
+        if let Some(xs) = m.get_mut(db_id) {
+            set_title(xs);
+        }
+    });
+    save_recent_notes(&retarget_recent_notes(
+        load_recent_notes(),
+        db_id,
+        note_id,
+        db_id,
+        note_id,
+        title,
+    ));
+
+    let api_client = app_state.0.api_client.get_untracked();
+    let db_id = db_id.to_string();
+    let note_id = note_id.to_string();
+    let title = title.to_string();
+    spawn_local(async move {
+        match api_client.update_note_title(&note_id, &title).await {
+            Ok(()) => mark_title_synced(&db_id, &note_id, updated_ms),
+            Err(e) => {
+                leptos::logging::warn!("rename note {} failed, will retry: {}", note_id, e);
+                mark_title_sync_failed(&db_id, &note_id);
+            }
+        }
+    });
+}
 
-    let delete_open: RwSignal<bool> = RwSignal::new(false);
-    let delete_confirm: RwSignal<String> = RwSignal::new(String::new());
-    let delete_loading: RwSignal<bool> = RwSignal::new(false);
-    let delete_error: RwSignal<Option<String>> = RwSignal::new(None);
+/// Inline title editor for note list rows: Enter or blur saves, Escape reverts.
+#[component]
+fn NoteRenameInput(
+    db_id: String,
+    note_id: String,
+    title: String,
+    #[prop(into)] on_done: Callback<()>,
+) -> impl IntoView {
+    let app_state = StoredValue::new(expect_context::<AppContext>());
+    let ids = StoredValue::new((db_id, note_id, title.clone()));
+    let value: RwSignal<String> = RwSignal::new(title);
+    let error: RwSignal<Option<String>> = RwSignal::new(None);
+    let input_ref: NodeRef<html::Input> = NodeRef::new();
+    // Enter unmounts the input, which also fires blur; only the first one counts.
+    let finished = StoredValue::new(false);
 
-    // Params are reactive; read tracked in effects/views, and read untracked in event handlers.
-    let db_id = move || params.get().ok().and_then(|p| p.db_id).unwrap_or_default();
-    let db_id_untracked = move || {
-        params
-            .get_untracked()
-            .ok()
-            .and_then(|p| p.db_id)
-            .unwrap_or_default()
-    };
+    Effect::new(move |_| {
+        if let Some(input) = input_ref.get() {
+            let _ = input.focus();
+            input.select();
+        }
+    });
 
-    let persist_current_db = move |id: &str| {
-        if let Some(storage) = web_sys::window().and_then(|w| w.local_storage().ok().flatten()) {
-            let _ = storage.set_item(CURRENT_DB_KEY, id);
+    let commit = move || {
+        if finished.get_value() {
+            return;
         }
+        let next = value.get_untracked().trim().to_string();
+        if next.is_empty() {
+            error.set(Some("Title cannot be empty".to_string()));
+            return;
+        }
+
+        finished.set_value(true);
+        ids.with_value(|(db_id, note_id, original)| {
+            if &next != original {
+                app_state.with_value(|st| rename_note_from_list(st, db_id, note_id, &next));
+            }
+        });
+        on_done.run(());
     };
 
-    // Notes loading guards (avoid duplicate loads + ignore stale responses).
-    // Store guard state on AppState so it survives route changes.
-    let load_notes_for_sv = StoredValue::new(move |id: String, force: bool| {
-        if id.trim().is_empty() {
-            return;
+    let cancel = move || {
+        if !finished.get_value() {
+            finished.set_value(true);
+            on_done.run(());
         }
+    };
 
-        if !force {
-            let already_loaded = app_state
-                .0
-                .notes_last_loaded_db_id
-                .get_untracked()
-                .as_deref()
-                == Some(id.as_str());
-            let has_error = app_state.0.notes_error.get_untracked().is_some();
-            let is_loading = app_state.0.notes_loading.get_untracked();
+    view! {
+        <div class="min-w-0 flex-1">
+            <input
+                node_ref=input_ref
+                class="h-8 w-full rounded-md border border-input bg-transparent px-2 text-sm shadow-xs outline-none focus-visible:border-ring focus-visible:ring-2 focus-visible:ring-ring/50"
+                aria-label="Note title"
+                aria-invalid=move || error.get().is_some().to_string()
+                prop:value=move || value.get()
+                on:input=move |ev| {
+                    value.set(event_target_value(&ev));
+                    error.set(None);
+                }
+                on:keydown=move |ev: web_sys::KeyboardEvent| match ev.key().as_str() {
+                    "Enter" => {
+                        ev.prevent_default();
+                        commit();
+                    }
+                    "Escape" => {
+                        ev.prevent_default();
+                        cancel();
+                    }
+                    _ => {}
+                }
+                on:blur=move |_| commit()
+            />
+            {move || error.get().map(|e| view! {
+                <div class="mt-1 text-xs text-destructive">{e}</div>
+            })}
+        </div>
+    }
+}
 
-            if already_loaded && !has_error && !is_loading {
-                return;
+/// Pencil button that puts a note list row into inline rename mode.
+#[component]
+fn NoteRenameButton(#[prop(into)] on_click: Callback<()>) -> impl IntoView {
+    view! {
+        <Button
+            variant=ButtonVariant::Ghost
+            size=ButtonSize::Icon
+            class="h-7 w-7 shrink-0"
+            attr:title="Rename"
+            on:click=move |ev: web_sys::MouseEvent| {
+                // Don't let the click reach the row link.
+                ev.prevent_default();
+                ev.stop_propagation();
+                on_click.run(());
             }
-        }
+        >
+            <svg
+                xmlns="http://www.w3.org/2000/svg"
+                width="16"
+                height="16"
+                viewBox="0 0 24 24"
+                fill="none"
+                stroke="currentColor"
+                stroke-width="2"
+                stroke-linecap="round"
+                stroke-linejoin="round"
+                class="text-muted-foreground"
+                aria-hidden="true"
+            >
+                <path d="M12 20h9"></path>
+                <path d="M16.5 3.5a2.1 2.1 0 0 1 3 3L7 19l-4 1 1-4Z"></path>
+            </svg>
+        </Button>
+    }
+}
 
-        app_state.0.notes_last_loaded_db_id.set(Some(id.clone()));
+/// Pin/unpin toggle used in note lists (DbHomePage + sidebar Pages).
+#[component]
+fn NotePinButton(db_id: String, note_id: String, is_pinned: bool) -> impl IntoView {
+    let app_state = expect_context::<AppContext>();
+    let title = if is_pinned { "Unpin" } else { "Pin" };
 
-        let req_id = app_state
-            .0
-            .notes_request_id
-            .get_untracked()
-            .saturating_add(1);
-        app_state.0.notes_request_id.set(req_id);
+    view! {
+        <Button
+            variant=ButtonVariant::Ghost
+            size=ButtonSize::Icon
+            class="h-7 w-7 shrink-0"
+            attr:title=title
+            attr:aria-pressed=is_pinned.to_string()
+            on:click=move |ev: web_sys::MouseEvent| {
+                // Don't let the click reach the row link.
+                ev.prevent_default();
+                ev.stop_propagation();
+                if is_pinned {
+                    app_state.0.unpin_note(&db_id, &note_id);
+                } else {
+                    app_state.0.pin_note(&db_id, &note_id);
+                }
+            }
+        >
+            <svg
+                xmlns="http://www.w3.org/2000/svg"
+                width="16"
+                height="16"
+                viewBox="0 0 24 24"
+                fill=if is_pinned { "currentColor" } else { "none" }
+                stroke="currentColor"
+                stroke-width="2"
+                stroke-linecap="round"
+                stroke-linejoin="round"
+                class="text-muted-foreground"
+                aria-hidden="true"
+            >
+                <path d="M12 17v5" />
+                <path d="M9 10.76V6h6v4.76l2 2.24v2H7v-2Z" />
+                <path d="M8 6h8" />
+            </svg>
+        </Button>
+    }
+}
 
-        app_state.0.notes_loading.set(true);
-        app_state.0.notes_error.set(None);
+/// Duplicates `source` (note + outline), refreshes the note list and opens the copy.
+#[component]
+fn NoteDuplicateButton(
+    #[prop(into)] source: Signal<Note>,
+    /// Icon-only variant for list rows.
+    #[prop(optional)]
+    compact: bool,
+    #[prop(into)] on_error: Callback<String>,
+) -> impl IntoView {
+    let app_state = expect_context::<AppContext>();
+    let navigate = StoredValue::new(use_navigate());
+    let duplicating: RwSignal<bool> = RwSignal::new(false);
+    // (copied, total) blocks while a client-side copy runs.
+    let progress: RwSignal<Option<(usize, usize)>> = RwSignal::new(None);
 
-        let api_client = app_state.0.api_client.get_untracked();
-        spawn_local(async move {
-            let result = api_client.get_all_note_list(&id).await;
+    let on_click = move |ev: web_sys::MouseEvent| {
+        // Don't let the click reach the row link.
+        ev.prevent_default();
+        ev.stop_propagation();
+        if duplicating.get_untracked() {
+            return;
+        }
 
-            // Ignore stale responses.
-            if app_state.0.notes_request_id.get_untracked() != req_id {
-                return;
-            }
+        let source = source.get_untracked();
+        if source.id.trim().is_empty() || source.database_id.trim().is_empty() {
+            return;
+        }
+
+        duplicating.set(true);
+        progress.set(None);
+        let app_state = app_state.clone();
+        spawn_local(async move {
+            let api_client = app_state.0.api_client.get_untracked();
+            let db_id = source.database_id.clone();
+            let result = api_client
+                .duplicate_note(&source, &|copied, total| progress.set(Some((copied, total))))
+                .await;
 
             match result {
-                Ok(notes) => {
-                    app_state.0.notes.set(notes);
+                Ok(note) => {
+                    if let Ok(notes) = api_client.get_all_note_list(&db_id).await {
+                        app_state.0.notes.set(notes);
+                    }
+                    write_recent_note(&db_id, &note.id, &note.title);
+                    navigate.with_value(|nav| {
+                        nav(
+                            &format!("/db/{}/note/{}", db_id, note.id),
+                            Default::default(),
+                        );
+                    });
                 }
                 Err(e) => {
-                    if e.kind == crate::api::ApiErrorKind::Unauthorized {
-                        let mut c = app_state.0.api_client.get_untracked();
-                        c.logout();
-                        app_state.0.api_client.set(c);
-                        app_state.0.current_user.set(None);
-                        let _ = window().location().set_href("/login");
+                    if e == ApiError::Unauthorized {
+                        handle_unauthorized_response(&app_state);
+                    } else if progress.get_untracked().is_some() {
+                        // The partial copy exists; show it in the list.
+                        if let Ok(notes) = api_client.get_all_note_list(&db_id).await {
+                            app_state.0.notes.set(notes);
+                        }
+                        on_error.run(e.to_string());
                     } else {
-                        app_state.0.notes_error.set(Some(e.to_string()));
-                        app_state.0.notes.set(vec![]);
+                        on_error.run(format!("Duplicate failed: {e}"));
                     }
                 }
             }
-            app_state.0.notes_loading.set(false);
+            duplicating.set(false);
+            progress.set(None);
         });
-    });
+    };
 
-    // Keep global selection in sync with URL + write recent DB.
-    Effect::new(move |_| {
-        let id = db_id();
-        if id.trim().is_empty() {
-            return;
-        }
+    let progress_label = move || {
+        progress
+            .get()
+            .map(|(copied, total)| format!("Copying {}/{} blocks", copied, total))
+    };
 
-        if app_state.0.current_database_id.get() != Some(id.clone()) {
-            app_state.0.current_database_id.set(Some(id.clone()));
-            persist_current_db(&id);
+    if compact {
+        view! {
+            <span class="inline-flex items-center gap-1">
+                {move || progress_label().map(|label| view! {
+                    <span class="text-[10px] text-muted-foreground">{label}</span>
+                })}
+                <Button
+                    variant=ButtonVariant::Ghost
+                    size=ButtonSize::Icon
+                    class="h-7 w-7 shrink-0"
+                    attr:title="Duplicate"
+                    attr:disabled=move || duplicating.get()
+                    on:click=on_click
+                >
+                    <Show
+                        when=move || !duplicating.get()
+                        fallback=|| view! { <Spinner /> }
+                    >
+                        <svg
+                            xmlns="http://www.w3.org/2000/svg"
+                            width="16"
+                            height="16"
+                            viewBox="0 0 24 24"
+                            fill="none"
+                            stroke="currentColor"
+                            stroke-width="2"
+                            stroke-linecap="round"
+                            stroke-linejoin="round"
+                            class="text-muted-foreground"
+                            aria-hidden="true"
+                        >
+                            <rect x="9" y="9" width="13" height="13" rx="2" />
+                            <path d="M5 15H4a2 2 0 0 1-2-2V4a2 2 0 0 1 2-2h9a2 2 0 0 1 2 2v1" />
+                        </svg>
+                    </Show>
+                </Button>
+            </span>
         }
-
-        // Phase 5.5: recent databases (local)
-        if let Some(d) = app_state.0.databases.get().into_iter().find(|d| d.id == id) {
-            write_recent_db(&d.id, &d.name);
-        } else {
-            // Fallback: keep at least the id.
-            write_recent_db(&id, &id);
+        .into_any()
+    } else {
+        view! {
+            <Button
+                variant=ButtonVariant::Ghost
+                size=ButtonSize::Sm
+                attr:title="Duplicate note"
+                attr:disabled=move || duplicating.get()
+                on:click=on_click
+            >
+                <span class="inline-flex items-center gap-2">
+                    <Show when=move || duplicating.get() fallback=|| ().into_view()>
+                        <Spinner />
+                    </Show>
+                    {move || {
+                        if !duplicating.get() {
+                            "Duplicate".to_string()
+                        } else {
+                            progress_label().unwrap_or_else(|| "Duplicating...".to_string())
+                        }
+                    }}
+                </span>
+            </Button>
         }
-    });
+        .into_any()
+    }
+}
 
-    // Phase 5 (non-paginated): load notes for current database.
-    Effect::new(move |_| {
-        load_notes_for_sv.with_value(|f| {
-            f(db_id(), false);
-        });
-    });
+/// "Move to…" action: pick another database and reassign the note to it (same note id).
+/// A failed move leaves a toast offering Retry, and Roll back when it may have applied.
+#[component]
+fn NoteMoveButton(
+    #[prop(into)] source: Signal<Note>,
+    /// Icon-only variant for list rows.
+    #[prop(optional)]
+    compact: bool,
+    /// Also open the picker with Cmd/Ctrl+Shift+M (the open note's button only).
+    #[prop(optional)]
+    shortcut: bool,
+    #[prop(into)] on_error: Callback<String>,
+) -> impl IntoView {
+    let app_state = StoredValue::new(expect_context::<AppContext>());
+    let navigate = StoredValue::new(use_navigate());
+    let pathname = use_location().pathname;
+
+    let open: RwSignal<bool> = RwSignal::new(false);
+    let target_db_id: RwSignal<Option<String>> = RwSignal::new(None);
+    let moving: RwSignal<bool> = RwSignal::new(false);
+    // (note as it was, target db, error) after a failed move; shown as a toast offering a
+    // retry and, when the server may have applied it anyway, a rollback.
+    let failure: RwSignal<Option<(Note, String, ApiError)>> = RwSignal::new(None);
+    let rolling_back: RwSignal<bool> = RwSignal::new(false);
+    // (note path, moved note path) while a move made offline waits in the pending queue.
+    let queued_move: RwSignal<Option<(String, String)>> = RwSignal::new(None);
+    let panel_ref: NodeRef<html::Div> = NodeRef::new();
+
+    let databases = app_state.with_value(|a| a.0.databases);
+    let other_databases = move || {
+        let current = source.with(|n| n.database_id.clone());
+        databases
+            .get()
+            .into_iter()
+            .filter(|d| d.id != current)
+            .collect::<Vec<_>>()
+    };
+    let other_databases_untracked = move || {
+        let current = source.with_untracked(|n| n.database_id.clone());
+        databases.with_untracked(|dbs| {
+            dbs.iter()
+                .filter(|d| d.id != current)
+                .cloned()
+                .collect::<Vec<_>>()
+        })
+    };
 
-    // UX: when user enters /db/:db_id, auto-open the first note.
-    // This makes the main area show a note immediately and enables Pages highlight.
-    Effect::new(move |_| {
-        let id = db_id();
-        if id.trim().is_empty() {
-            return;
-        }
+    let on_open = move |ev: web_sys::MouseEvent| {
+        // Don't let the click reach the row link.
+        ev.prevent_default();
+        ev.stop_propagation();
+        target_db_id.set(None);
+        open.set(true);
+    };
 
-        let p = pathname();
-        if p != format!("/db/{}", id) {
-            return;
+    if shortcut {
+        let move_key_handle =
+            window_event_listener(ev::keydown, move |ev: web_sys::KeyboardEvent| {
+                if !((ev.meta_key() || ev.ctrl_key())
+                    && ev.shift_key()
+                    && ev.key().eq_ignore_ascii_case("m"))
+                {
+                    return;
+                }
+                // The page may be gone already (navigation in the same tick).
+                if open.try_get_untracked() != Some(false)
+                    || source.try_with_untracked(|_| ()).is_none()
+                {
+                    return;
+                }
+                ev.prevent_default();
+                // Keyboard flow: preselect the first database so Enter confirms.
+                target_db_id.set(other_databases_untracked().first().map(|d| d.id.clone()));
+                open.set(true);
+            });
+        // The handle does not detach on drop: without this every mount adds a listener.
+        on_cleanup(move || move_key_handle.remove());
+    }
+
+    // Focus the panel on open so arrow keys and Enter reach it.
+    Effect::new(move |_| {
+        if open.get() {
+            set_timeout(
+                move || {
+                    if let Some(el) = panel_ref.get_untracked() {
+                        let _ = el.focus();
+                    }
+                },
+                std::time::Duration::from_millis(0),
+            );
         }
+    });
 
-        if app_state.0.notes_loading.get() {
+    let submit = move || {
+        if moving.get_untracked() {
             return;
         }
-
-        let mut notes = app_state
-            .0
-            .notes
-            .get()
-            .into_iter()
-            .filter(|n| n.database_id == id)
-            .collect::<Vec<_>>();
-
-        if notes.is_empty() {
+        let Some(target) = target_db_id.get_untracked() else {
+            return;
+        };
+        let source = source.get_untracked();
+        if source.id.trim().is_empty() || source.database_id == target {
             return;
         }
 
-        // Prefer most recently updated (lexicographic works for ISO-ish timestamps).
-        notes.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
-        let first_id = notes[0].id.clone();
-
-        // Use replace=true so browser Back goes to the previous page (e.g. Home),
-        // instead of bouncing between /db/:db_id and /db/:db_id/note/:note_id.
-        navigate.with_value(|nav| {
-            nav(
-                &format!("/db/{}/note/{}", id, first_id),
-                leptos_router::NavigateOptions {
-                    replace: true,
-                    ..Default::default()
-                },
-            );
+        // Offline the title collision check can only use the target's last known list.
+        let cached_target_notes = app_state.with_value(|a| {
+            let loaded = a.0.notes_last_loaded_db_id.get_untracked();
+            let target_loaded = loaded.as_deref() == Some(target.as_str());
+            a.0.quick_switch_index
+                .with_untracked(|m| m.get(&target).cloned())
+                .or_else(|| target_loaded.then(|| a.0.notes.get_untracked()))
         });
-    });
-
-    let db = move || {
-        let id = db_id();
-        app_state.0.databases.get().into_iter().find(|d| d.id == id)
-    };
 
-    let refresh_databases = move || {
-        let mut c = app_state.0.api_client.get_untracked();
+        moving.set(true);
+        failure.set(None);
+        let app_state = app_state.get_value();
         spawn_local(async move {
-            match c.get_database_list().await {
-                Ok(dbs) => {
-                    app_state.0.databases.set(dbs);
+            let api_client = app_state.0.api_client.get_untracked();
+            match api_client
+                .move_note(&source, &target, cached_target_notes.as_deref())
+                .await
+            {
+                Ok((note, queued)) => {
+                    let recents = retarget_recent_notes(
+                        load_recent_notes(),
+                        &source.database_id,
+                        &source.id,
+                        &target,
+                        &note.id,
+                        &note.title,
+                    );
+                    save_recent_notes(&recents);
+
+                    // Pins and previews are kept per database; the note id stays the same.
+                    if app_state
+                        .0
+                        .is_note_pinned_untracked(&source.database_id, &source.id)
+                    {
+                        app_state.0.unpin_note(&source.database_id, &source.id);
+                        app_state.0.pin_note(&target, &note.id);
+                    }
+                    app_state.0.note_previews.update(|m| {
+                        let preview = m
+                            .get_mut(&source.database_id)
+                            .and_then(|p| p.remove(&source.id));
+                        if let (Some(p), Some(previews)) = (preview, m.get_mut(&target)) {
+                            previews.insert(note.id.clone(), p);
+                        }
+                    });
+
+                    // The target list, if loaded, gains the note; the source list refetches.
+                    app_state.0.quick_switch_index.update(|m| {
+                        if let Some(xs) = m.get_mut(&source.database_id) {
+                            xs.retain(|n| n.id != source.id);
+                        }
+                        if let Some(xs) = m.get_mut(&target) {
+                            xs.push(note.clone());
+                        }
+                    });
+                    app_state.0.notes.update(|xs| xs.retain(|n| n.id != source.id));
+                    // Only while the source database is still the one shown (and online); a
+                    // newer notes load (database switch) makes this response stale.
+                    let source_is_current = app_state
+                        .0
+                        .current_database_id
+                        .get_untracked()
+                        .is_some_and(|id| id == source.database_id);
+                    if source_is_current && !queued {
+                        let (req_id, abort) = app_state.0.begin_notes_request();
+                        let result = api_client
+                            .with_abort(&abort)
+                            .get_all_note_list(&source.database_id)
+                            .await;
+                        if app_state.0.notes_request_id.get_untracked() == req_id {
+                            if let Ok(notes) = result {
+                                app_state.0.notes.set(notes);
+                            }
+                        }
+                    }
+                    open.set(false);
+
+                    let old_path = format!("/db/{}/note/{}", source.database_id, source.id);
+                    let new_path = format!("/db/{}/note/{}", target, note.id);
+                    if queued {
+                        // The target can't load offline; follow the note once it has synced.
+                        queued_move.set(Some((old_path, new_path)));
+                    } else if pathname.get_untracked().starts_with(&old_path) {
+                        navigate.with_value(|nav| nav(&new_path, Default::default()));
+                    }
                 }
                 Err(e) => {
-                    if e == "Unauthorized" {
-                        c.logout();
-                        app_state.0.api_client.set(c);
-                        app_state.0.current_user.set(None);
-                        let _ = window().location().set_href("/login");
-                        return;
+                    if e == ApiError::Unauthorized {
+                        handle_unauthorized_response(&app_state);
+                    } else {
+                        open.set(false);
+                        failure.set(Some((source, target, e)));
                     }
                 }
             }
-            app_state.0.api_client.set(c);
-        });
-    };
-
-    let _refresh_databases = move || {
-        let mut c = app_state.0.api_client.get_untracked();
-        spawn_local(async move {
-            if let Ok(dbs) = c.get_database_list().await {
-                app_state.0.databases.set(dbs);
-            }
-            app_state.0.api_client.set(c);
+            moving.set(false);
         });
     };
 
-    let _on_open_rename = move |_: web_sys::MouseEvent| {
-        rename_error.set(None);
-        if let Some(d) = db() {
-            rename_value.set(d.name);
-        }
-        rename_open.set(true);
-    };
-
-    let on_submit_rename = move |_| {
-        if rename_loading.get_untracked() {
+    // A queued move is sent by the replay on reconnect; once the queue has drained, open
+    // the note in its new database (if it is still the page shown).
+    let pending_requests = app_state.with_value(|a| a.0.pending_requests);
+    Effect::new(move |_| {
+        if !pending_requests.with(Vec::is_empty) {
             return;
         }
-        let id = db_id();
-        let new_name = rename_value.get_untracked();
-        if new_name.trim().is_empty() {
-            rename_error.set(Some("Name cannot be empty".to_string()));
+        let Some((old_path, new_path)) = queued_move.get() else {
             return;
+        };
+        queued_move.set(None);
+        if pathname.get_untracked().starts_with(&old_path) {
+            navigate.with_value(|nav| nav(&new_path, Default::default()));
         }
-        let api_client = app_state.0.api_client.get_untracked();
-
-        rename_loading.set(true);
-        rename_error.set(None);
-
-        spawn_local(async move {
-            match api_client.rename_database(&id, &new_name).await {
-                Ok(_) => {
-                    refresh_databases();
-                    rename_open.set(false);
-                }
-                Err(e) => rename_error.set(Some(e)),
-            }
-            rename_loading.set(false);
-        });
-    };
+    });
 
-    let _on_open_delete = move |_: web_sys::MouseEvent| {
-        delete_confirm.set(String::new());
-        delete_error.set(None);
-        delete_open.set(true);
+    let retry = move |_| {
+        let Some((_, target, _)) = failure.get_untracked() else {
+            return;
+        };
+        target_db_id.set(Some(target));
+        submit();
     };
 
-    let on_submit_delete = move |_| {
-        if delete_loading.get_untracked() {
+    // A timed-out or 5xx move may still have been applied: put the note back where it was.
+    let roll_back = move |_| {
+        let Some((source, _, _)) = failure.get_untracked() else {
             return;
-        }
-
-        let id = db_id();
-        let name = db().map(|d| d.name).unwrap_or_default();
-        let confirm = delete_confirm.get_untracked();
-        if confirm.trim() != name.trim() {
-            delete_error.set(Some(
-                "Type the database name to confirm deletion".to_string(),
-            ));
+        };
+        if rolling_back.get_untracked() {
             return;
         }
-
-        let api_client = app_state.0.api_client.get_untracked();
-        delete_loading.set(true);
-        delete_error.set(None);
-
+        rolling_back.set(true);
+        let app_state = app_state.get_value();
         spawn_local(async move {
-            match api_client.delete_database_by_id(&id).await {
-                Ok(_) => {
-                    // Reload DBs and navigate to the first remaining DB (or /).
-                    let mut c = app_state.0.api_client.get_untracked();
-                    if let Ok(dbs) = c.get_database_list().await {
-                        app_state.0.databases.set(dbs.clone());
-                        if let Some(first) = dbs.first() {
-                            app_state.0.current_database_id.set(Some(first.id.clone()));
-                            persist_current_db(&first.id);
-                            navigate.with_value(|nav| {
-                                nav(&format!("/db/{}", first.id), Default::default());
-                            });
-                        } else {
-                            app_state.0.current_database_id.set(None);
-                            persist_current_db("");
-                            navigate.with_value(|nav| {
-                                nav("/", Default::default());
-                            });
-                        }
-                    }
-                    app_state.0.api_client.set(c);
-                    delete_open.set(false);
+            let api_client = app_state.0.api_client.get_untracked();
+            match api_client.restore_note_location(&source).await {
+                Ok(()) => {
+                    failure.set(None);
+                    // Note lists pick up the note's real place on their next load.
+                    app_state.0.notes_last_loaded_db_id.set(None);
+                }
+                Err(e) if e == ApiError::Unauthorized => handle_unauthorized_response(&app_state),
+                Err(e) => {
+                    failure.set(None);
+                    on_error.run(format!("Rollback failed: {e}"));
                 }
-                Err(e) => delete_error.set(Some(e)),
             }
-            delete_loading.set(false);
+            rolling_back.set(false);
         });
     };
 
-    let is_auto_opening_note = move || {
-        let id = db_id();
-        let p = pathname();
-        if id.trim().is_empty() {
-            return false;
-        }
-        if p != format!("/db/{}", id) {
-            return false;
+    // Arrow keys pick a database, Enter moves, Escape cancels.
+    let on_panel_keydown = move |ev: web_sys::KeyboardEvent| {
+        let key = ev.key();
+        match key.as_str() {
+            "ArrowDown" | "ArrowUp" => {
+                ev.prevent_default();
+                let dbs = other_databases_untracked();
+                if dbs.is_empty() {
+                    return;
+                }
+                let current = target_db_id.get_untracked();
+                let idx = current
+                    .as_deref()
+                    .and_then(|id| dbs.iter().position(|d| d.id == id));
+                let next = match (idx, key.as_str()) {
+                    (None, "ArrowDown") => 0,
+                    (None, _) => dbs.len() - 1,
+                    (Some(i), "ArrowDown") => (i + 1).min(dbs.len() - 1),
+                    (Some(i), _) => i.saturating_sub(1),
+                };
+                target_db_id.set(Some(dbs[next].id.clone()));
+            }
+            "Enter" => {
+                ev.prevent_default();
+                submit();
+            }
+            "Escape" if !moving.get_untracked() => {
+                ev.prevent_default();
+                // Not a zoom exit for the outline behind the dialog.
+                ev.stop_propagation();
+                open.set(false);
+            }
+            _ => {}
         }
+    };
 
-        // If notes are loading, or we already have notes for this DB, we're about to auto-navigate.
-        let has_notes = app_state
-            .0
-            .notes
-            .get()
-            .into_iter()
-            .any(|n| n.database_id == id);
-
-        app_state.0.notes_loading.get() || has_notes
+    let trigger = if compact {
+        view! {
+            <Button
+                variant=ButtonVariant::Ghost
+                size=ButtonSize::Icon
+                class="h-7 w-7 shrink-0"
+                attr:title="Move to…"
+                on:click=on_open
+            >
+                <svg
+                    xmlns="http://www.w3.org/2000/svg"
+                    width="16"
+                    height="16"
+                    viewBox="0 0 24 24"
+                    fill="none"
+                    stroke="currentColor"
+                    stroke-width="2"
+                    stroke-linecap="round"
+                    stroke-linejoin="round"
+                    class="text-muted-foreground"
+                    aria-hidden="true"
+                >
+                    <path d="M5 12h14" />
+                    <path d="m12 5 7 7-7 7" />
+                </svg>
+            </Button>
+        }
+        .into_any()
+    } else {
+        view! {
+            <Button
+                variant=ButtonVariant::Ghost
+                size=ButtonSize::Sm
+                attr:title="Move to another database"
+                on:click=on_open
+            >
+                "Move to…"
+            </Button>
+        }
+        .into_any()
     };
 
+    // Portal: list rows only show their actions on hover, which would hide an inline dialog.
     view! {
-        <Show
-            when=move || !is_auto_opening_note()
-            fallback=move || view! {
-                <div class="flex h-[40vh] items-center justify-center">
-                    <Spinner />
-                </div>
-            }
-        >
-            <div class="space-y-3">
-                <div class="flex items-start justify-between gap-3">
-                    <div class="space-y-1">
-                        <h1 class="text-xl font-semibold">
-                            {move || db().map(|d| d.name).unwrap_or_else(|| "Database".to_string())}
-                        </h1>
-                        <p class="text-xs text-muted-foreground">{move || format!("db_id: {}", db_id())}</p>
-                    </div>
-
-                    <div class="flex items-center gap-2"></div>
-                </div>
+        {trigger}
+        <Portal>
+            <Show when=move || open.get() fallback=|| ().into_view()>
+                <div class="fixed inset-0 z-50 flex items-center justify-center bg-black/30 px-4">
+                    <div
+                        node_ref=panel_ref
+                        tabindex="-1"
+                        class="w-full max-w-sm rounded-md border border-border bg-background p-4 shadow-lg outline-none"
+                        role="dialog"
+                        aria-modal="true"
+                        on:keydown=on_panel_keydown
+                    >
+                        <div class="mb-3 space-y-1">
+                            <div class="text-sm font-medium">"Move note"</div>
+                            <div class="truncate text-xs text-muted-foreground">
+                                {move || source.with(|n| n.title.clone())}
+                            </div>
+                        </div>
 
-            <Card>
-                <CardContent>
-                    <div class="flex items-center justify-between gap-3">
-                        <div class="text-sm font-medium">"Notes"</div>
-                        <Button
-                            variant=ButtonVariant::Outline
-                            size=ButtonSize::Sm
-                            attr:disabled=move || create_note_loading.get()
-                            on:click=move |_| {
-                                if create_note_loading.get_untracked() {
-                                    return;
+                        <div class="space-y-2">
+                            {move || {
+                                let dbs = other_databases();
+                                if dbs.is_empty() {
+                                    return view! {
+                                        <div class="text-xs text-muted-foreground">"No other databases."</div>
+                                    }
+                                    .into_any();
                                 }
-
-                                create_note_loading.set(true);
-                                create_note_error.set(None);
-
-                                let id = db_id_untracked();
-                                let title = next_available_daily_note_title(&app_state.0.notes.get_untracked());
-                                let api_client = app_state.0.api_client.get_untracked();
-                                let load_notes_for_sv = load_notes_for_sv;
-
-                                spawn_local(async move {
-                                    match api_client.create_note(&id, &title).await {
-                                        Ok(note) => {
-                                            // Refresh list then navigate to note.
-                                            load_notes_for_sv.with_value(|f| {
-                                                f(id.clone(), true);
-                                            });
-
-                                            if note.id.trim().is_empty() {
-                                                leptos::logging::error!(
-                                                    "create_note succeeded but returned empty note id; refusing to navigate: title={}",
-                                                    title
-                                                );
-                                                create_note_error.set(Some(
-                                                    "Create note failed: empty note id in response".to_string(),
-                                                ));
-                                                create_note_loading.set(false);
-                                                return;
-                                            }
-
-                                            navigate.with_value(|nav| {
-                                                nav(
-                                                    &format!("/db/{}/note/{}", id, note.id),
-                                                    Default::default(),
-                                                );
-                                            });
-                                        }
-                                        Err(e) => {
-                                            if e == "Unauthorized" {
-                                                let mut c = app_state.0.api_client.get_untracked();
-                                                c.logout();
-                                                app_state.0.api_client.set(c);
-                                                app_state.0.current_user.set(None);
-                                                let _ = window().location().set_href("/login");
+                                dbs.into_iter()
+                                    .map(|d| {
+                                        let id = d.id.clone();
+                                        let id_for_variant = d.id.clone();
+                                        let variant = move || {
+                                            if target_db_id.get().as_deref() == Some(id_for_variant.as_str()) {
+                                                ButtonVariant::Accent
                                             } else {
-                                                create_note_error.set(Some(e));
+                                                ButtonVariant::Ghost
                                             }
+                                        };
+                                        view! {
+                                            <Button
+                                                variant=Signal::derive(variant)
+                                                size=ButtonSize::Sm
+                                                class="w-full justify-start"
+                                                attr:disabled=move || moving.get()
+                                                on:click=move |_| target_db_id.set(Some(id.clone()))
+                                            >
+                                                <span class="truncate">{d.name}</span>
+                                            </Button>
                                         }
-                                    }
-                                    create_note_loading.set(false);
-                                });
-                            }
-                            attr:title="New note"
-                        >
-                            {move || if create_note_loading.get() { "Creating..." } else { "New" }}
-                        </Button>
-                    </div>
-
-                    <div class="mt-3 space-y-2">
-                        <Show when=move || create_note_error.get().is_some() fallback=|| ().into_view()>
-                            {move || {
-                                create_note_error.get().map(|e| {
-                                    view! {
-                                        <Alert class="border-destructive/30">
-                                            <AlertDescription class="text-destructive text-xs">{e}</AlertDescription>
-                                        </Alert>
-                                    }
-                                })
+                                    })
+                                    .collect_view()
+                                    .into_any()
                             }}
-                        </Show>
-
-                        <Show
-                            when=move || !app_state.0.notes_loading.get()
-                            fallback=move || view! {
-                                <div class="flex items-center gap-2 text-sm text-muted-foreground">
-                                    <Spinner />
-                                    "Loading notes…"
-                                </div>
-                            }
-                        >
-                            <Show
-                                when=move || app_state.0.notes_error.get().is_none()
-                                fallback=move || view! {
-                                    <Alert class="border-destructive/30">
-                                        <AlertDescription class="text-destructive text-xs">
-                                            {move || app_state.0.notes_error.get().unwrap_or_default()}
-                                        </AlertDescription>
-                                    </Alert>
-                                }
-                            >
-                                <Show
-                                    when=move || !app_state.0.notes.get().is_empty()
-                                    fallback=move || view! {
-                                        <div class="text-sm text-muted-foreground">"No notes yet."</div>
-                                    }
-                                >
-                                    <div class="space-y-1">
-                                        {move || {
-                                            let db = db_id();
-                                            app_state
-                                                .0
-                                                .notes
-                                                .get()
-                                                .into_iter()
-                                                .map(|n| {
-                                                    // Use title override to match note title behavior (local-first).
-                                                    let display_title = get_title_override(&db, &n.id, &n.title);
-                                                    view! {
-                                                        <a
-                                                            href=format!("/db/{}/note/{}", db, n.id)
-                                                            class="block rounded-md border border-border bg-background px-3 py-2 transition-colors hover:bg-surface-hover"
-                                                        >
-                                                            <div class="min-w-0">
-                                                                <div class="truncate text-sm font-medium">{display_title}</div>
-                                                                <div class="truncate text-xs text-muted-foreground">{n.updated_at}</div>
-                                                            </div>
-                                                        </a>
-                                                    }
-                                                })
-                                                .collect_view()
-                                        }}
-                                    </div>
-                                </Show>
-                            </Show>
-                        </Show>
-                    </div>
-                </CardContent>
-            </Card>
-
-            <Show when=move || rename_open.get() fallback=|| ().into_view()>
-                <div class="fixed inset-0 z-50 flex items-center justify-center bg-black/30 px-4">
-                    <div class="w-full max-w-sm rounded-md border border-border bg-background p-4 shadow-lg">
-                        <div class="mb-3 space-y-1">
-                            <div class="text-sm font-medium">"Rename database"</div>
-                            <div class="text-xs text-muted-foreground">"Only the name can be updated (backend limitation)."</div>
-                        </div>
-
-                        <div class="space-y-2">
-                            <div class="space-y-1">
-                                <Label class="text-xs">"New name"</Label>
-                                <Input bind_value=rename_value class="h-8 text-sm" />
-                            </div>
-
-                            <Show when=move || rename_error.get().is_some() fallback=|| ().into_view()>
-                                {move || rename_error.get().map(|e| view! {
-                                    <Alert class="border-destructive/30">
-                                        <AlertDescription class="text-destructive text-xs">{e}</AlertDescription>
-                                    </Alert>
-                                })}
-                            </Show>
 
                             <div class="flex items-center justify-end gap-2 pt-2">
                                 <Button
                                     variant=ButtonVariant::Outline
                                     size=ButtonSize::Sm
-                                    attr:disabled=move || rename_loading.get()
-                                    on:click=move |_| rename_open.set(false)
+                                    attr:disabled=move || moving.get()
+                                    on:click=move |_| open.set(false)
                                 >
                                     "Cancel"
                                 </Button>
                                 <Button
                                     size=ButtonSize::Sm
-                                    attr:disabled=move || rename_loading.get()
-                                    on:click=on_submit_rename
+                                    attr:disabled=move || moving.get() || target_db_id.get().is_none()
+                                    on:click=move |_| submit()
                                 >
                                     <span class="inline-flex items-center gap-2">
-                                        <Show when=move || rename_loading.get() fallback=|| ().into_view()>
+                                        <Show when=move || moving.get() fallback=|| ().into_view()>
                                             <Spinner />
                                         </Show>
-                                        {move || if rename_loading.get() { "Saving..." } else { "Save" }}
+                                        {move || if moving.get() { "Moving..." } else { "Move" }}
                                     </span>
                                 </Button>
                             </div>
@@ -3046,321 +5999,2921 @@ pub fn DbHomePage() -> impl IntoView {
                     </div>
                 </div>
             </Show>
+            <Show when=move || queued_move.with(Option::is_some) fallback=|| ().into_view()>
+                <div
+                    role="status"
+                    class="fixed bottom-4 right-4 z-50 max-w-sm rounded-md border border-border bg-card px-3 py-2 text-xs text-card-foreground shadow-lg"
+                >
+                    "Move queued: the note opens in its new database once you are back online."
+                </div>
+            </Show>
+            {move || failure.get().map(|(_, _, e)| {
+                let may_have_moved = e.is_transient();
+                view! {
+                    <div
+                        role="alert"
+                        class="fixed bottom-4 right-4 z-50 flex max-w-sm items-center gap-2 rounded-md border border-destructive/30 bg-card px-3 py-2 text-xs shadow-lg"
+                    >
+                        <span class="min-w-0 flex-1 text-destructive">
+                            {format!("Move failed: {e}")}
+                            {may_have_moved.then_some(" It may have gone through anyway.")}
+                        </span>
+                        <Button
+                            variant=ButtonVariant::Outline
+                            size=ButtonSize::Sm
+                            class="h-7 shrink-0 px-2 text-xs"
+                            attr:disabled=move || moving.get() || rolling_back.get()
+                            on:click=retry
+                        >
+                            "Retry"
+                        </Button>
+                        <Show when=move || may_have_moved fallback=|| ().into_view()>
+                            <Button
+                                variant=ButtonVariant::Outline
+                                size=ButtonSize::Sm
+                                class="h-7 shrink-0 px-2 text-xs"
+                                attr:disabled=move || moving.get() || rolling_back.get()
+                                on:click=roll_back
+                            >
+                                {move || if rolling_back.get() { "Rolling back..." } else { "Roll back" }}
+                            </Button>
+                        </Show>
+                        <Button
+                            variant=ButtonVariant::Ghost
+                            size=ButtonSize::Sm
+                            class="h-7 shrink-0 px-2 text-xs"
+                            attr:disabled=move || rolling_back.get()
+                            on:click=move |_| failure.set(None)
+                        >
+                            "Dismiss"
+                        </Button>
+                    </div>
+                }
+            })}
+        </Portal>
+    }
+}
 
-            <Show when=move || delete_open.get() fallback=|| ().into_view()>
-                <div class="fixed inset-0 z-50 flex items-center justify-center bg-black/30 px-4">
-                    <div class="w-full max-w-sm rounded-md border border-border bg-background p-4 shadow-lg">
-                        <div class="mb-3 space-y-1">
-                            <div class="text-sm font-medium">"Delete database"</div>
-                            <div class="text-xs text-muted-foreground">
-                                {move || {
-                                    let name = db().map(|d| d.name).unwrap_or_default();
-                                    format!("Type '{}' to confirm.", name)
-                                }}
-                            </div>
-                        </div>
+/// PIN lock controls for the note header: set a PIN, or (once unlocked in this tab) lock it
+/// again or remove the lock. Hidden while the note is locked; `NoteUnlockForm` handles that.
+#[component]
+fn NoteLockButton(#[prop(into)] note_id: Signal<String>) -> impl IntoView {
+    let app_state = StoredValue::new(expect_context::<AppContext>());
+    let open: RwSignal<bool> = RwSignal::new(false);
+    let pin: RwSignal<String> = RwSignal::new(String::new());
+    let confirm_pin: RwSignal<String> = RwSignal::new(String::new());
+    let error: RwSignal<Option<String>> = RwSignal::new(None);
 
-                        <div class="space-y-2">
-                            <Input bind_value=delete_confirm class="h-8 text-sm" />
+    let has_lock = move || app_state.with_value(|s| s.0.has_note_lock(&note_id.get()));
+    let locked = move || app_state.with_value(|s| s.0.is_note_locked(&note_id.get()));
 
-                            <Show when=move || delete_error.get().is_some() fallback=|| ().into_view()>
-                                {move || delete_error.get().map(|e| view! {
-                                    <Alert class="border-destructive/30">
-                                        <AlertDescription class="text-destructive text-xs">{e}</AlertDescription>
-                                    </Alert>
-                                })}
-                            </Show>
+    let on_open = move |_| {
+        pin.set(String::new());
+        confirm_pin.set(String::new());
+        error.set(None);
+        open.set(true);
+    };
 
-                            <div class="flex items-center justify-end gap-2 pt-2">
-                                <Button
-                                    variant=ButtonVariant::Outline
-                                    size=ButtonSize::Sm
-                                    attr:disabled=move || delete_loading.get()
-                                    on:click=move |_| delete_open.set(false)
-                                >
-                                    "Cancel"
-                                </Button>
-                                <Button
-                                    size=ButtonSize::Sm
-                                    attr:disabled=move || delete_loading.get()
-                                    on:click=on_submit_delete
-                                >
-                                    <span class="inline-flex items-center gap-2">
-                                        <Show when=move || delete_loading.get() fallback=|| ().into_view()>
-                                            <Spinner />
-                                        </Show>
-                                        {move || if delete_loading.get() { "Deleting..." } else { "Delete" }}
-                                    </span>
-                                </Button>
+    let on_submit = move |ev: web_sys::SubmitEvent| {
+        ev.prevent_default();
+        let p = pin.get_untracked();
+        if !is_valid_pin(&p) {
+            error.set(Some("PIN must be 4–12 digits.".to_string()));
+            return;
+        }
+        if p != confirm_pin.get_untracked() {
+            error.set(Some("PINs do not match.".to_string()));
+            return;
+        }
+        app_state.with_value(|s| s.0.lock_note(&note_id.get_untracked(), &p));
+        open.set(false);
+    };
+
+    view! {
+        {move || {
+            if locked() {
+                return ().into_view().into_any();
+            }
+            if has_lock() {
+                return view! {
+                    <Button
+                        variant=ButtonVariant::Ghost
+                        size=ButtonSize::Sm
+                        attr:title="Ask for the PIN again"
+                        on:click=move |_| app_state.with_value(|s| s.0.relock_note(&note_id.get_untracked()))
+                    >
+                        "Lock"
+                    </Button>
+                    <Button
+                        variant=ButtonVariant::Ghost
+                        size=ButtonSize::Sm
+                        attr:title="Remove the PIN lock"
+                        on:click=move |_| app_state.with_value(|s| s.0.remove_note_lock(&note_id.get_untracked()))
+                    >
+                        "Unlock"
+                    </Button>
+                }
+                .into_any();
+            }
+            view! {
+                <Button
+                    variant=ButtonVariant::Ghost
+                    size=ButtonSize::Sm
+                    attr:title="Lock this note with a PIN"
+                    on:click=on_open
+                >
+                    "Lock…"
+                </Button>
+            }
+            .into_any()
+        }}
+        <Portal>
+            <Show when=move || open.get() fallback=|| ().into_view()>
+                <div class="fixed inset-0 z-50 flex items-center justify-center bg-black/30 px-4">
+                    <form
+                        class="w-full max-w-sm space-y-3 rounded-md border border-border bg-background p-4 shadow-lg"
+                        on:submit=on_submit
+                    >
+                        <div class="space-y-1">
+                            <div class="text-sm font-medium">"Lock note"</div>
+                            <div class="text-xs text-muted-foreground">
+                                "The PIN is kept in this browser and only hides the note here; it is not encrypted on the server."
                             </div>
                         </div>
-                    </div>
+                        <Input
+                            r#type="password"
+                            placeholder="PIN (4–12 digits)"
+                            bind_value=pin
+                            attr:inputmode="numeric"
+                            attr:autocomplete="off"
+                            class="h-8 text-sm"
+                        />
+                        <Input
+                            r#type="password"
+                            placeholder="Confirm PIN"
+                            bind_value=confirm_pin
+                            attr:inputmode="numeric"
+                            attr:autocomplete="off"
+                            class="h-8 text-sm"
+                        />
+                        {move || error.get().map(|e| view! {
+                            <div class="text-xs text-destructive">{e}</div>
+                        })}
+                        <div class="flex items-center justify-end gap-2">
+                            <Button
+                                variant=ButtonVariant::Outline
+                                size=ButtonSize::Sm
+                                attr:type="button"
+                                on:click=move |_| open.set(false)
+                            >
+                                "Cancel"
+                            </Button>
+                            <Button size=ButtonSize::Sm>"Lock"</Button>
+                        </div>
+                    </form>
                 </div>
             </Show>
+        </Portal>
+    }
+}
+
+/// Shown instead of the outline of a locked note; a correct PIN unlocks it until the tab
+/// closes.
+#[component]
+fn NoteUnlockForm(#[prop(into)] note_id: Signal<String>) -> impl IntoView {
+    let app_state = StoredValue::new(expect_context::<AppContext>());
+    let pin: RwSignal<String> = RwSignal::new(String::new());
+    let error: RwSignal<Option<String>> = RwSignal::new(None);
+
+    let on_submit = move |ev: web_sys::SubmitEvent| {
+        ev.prevent_default();
+        let unlocked =
+            app_state.with_value(|s| s.0.unlock_note(&note_id.get_untracked(), &pin.get_untracked()));
+        if unlocked {
+            pin.set(String::new());
+            error.set(None);
+        } else {
+            error.set(Some("Wrong PIN.".to_string()));
+        }
+    };
+
+    view! {
+        <div class="rounded-md border border-border bg-muted p-4">
+            <form class="mx-auto flex max-w-xs flex-col gap-2" on:submit=on_submit>
+                <div class="text-sm font-medium">"This note is locked"</div>
+                <Input
+                    r#type="password"
+                    placeholder="PIN"
+                    bind_value=pin
+                    attr:inputmode="numeric"
+                    attr:autocomplete="off"
+                    attr:autofocus=true
+                    class="h-8 text-sm"
+                />
+                {move || error.get().map(|e| view! {
+                    <div class="text-xs text-destructive">{e}</div>
+                })}
+                <Button size=ButtonSize::Sm>"Unlock"</Button>
+            </form>
         </div>
-        </Show>
     }
 }
 
 #[component]
-pub fn SearchPage() -> impl IntoView {
+pub fn DbHomePage() -> impl IntoView {
     let app_state = expect_context::<AppContext>();
-    let query = use_query_map();
+    let params = leptos_router::hooks::use_params::<DbRouteParams>();
+    let navigate = StoredValue::new(use_navigate());
+    let location = use_location();
+    let pathname = move || location.pathname.get();
 
-    let q = move || query.get().get("q").unwrap_or_default();
-    let q_lower = move || q().trim().to_lowercase();
+    let rename_open: RwSignal<bool> = RwSignal::new(false);
 
-    let matched_dbs = move || {
-        let q = q_lower();
-        if q.is_empty() {
-            return vec![];
-        }
-        app_state
-            .0
-            .databases
-            .get()
-            .into_iter()
-            .filter(|d| d.name.to_lowercase().contains(&q))
-            .collect::<Vec<_>>()
-    };
+    // Phase 5: create note
+    let create_note_loading: RwSignal<bool> = RwSignal::new(false);
+    let create_note_error: RwSignal<Option<String>> = RwSignal::new(None);
+    let template_menu_open: RwSignal<bool> = RwSignal::new(false);
+    let templates = app_state.0.templates;
+    let rename_value: RwSignal<String> = RwSignal::new(String::new());
+    let rename_loading: RwSignal<bool> = RwSignal::new(false);
+    let rename_error: RwSignal<Option<String>> = RwSignal::new(None);
 
-    let matched_notes = move || {
-        let q = q_lower();
-        if q.is_empty() {
-            return vec![];
-        }
-        let db_id = app_state.0.current_database_id.get().unwrap_or_default();
-        if db_id.trim().is_empty() {
-            return vec![];
-        }
+    let delete_open: RwSignal<bool> = RwSignal::new(false);
+    let delete_confirm: RwSignal<String> = RwSignal::new(String::new());
+    let delete_loading: RwSignal<bool> = RwSignal::new(false);
+    let delete_error: RwSignal<Option<String>> = RwSignal::new(None);
 
-        app_state
-            .0
-            .notes
-            .get()
-            .into_iter()
-            .filter(|n| n.database_id == db_id)
-            .filter(|n| n.title.to_lowercase().contains(&q))
-            .collect::<Vec<_>>()
+    // Inline database description editor.
+    let description_editing: RwSignal<bool> = RwSignal::new(false);
+    let description_value: RwSignal<String> = RwSignal::new(String::new());
+    let description_loading: RwSignal<bool> = RwSignal::new(false);
+    let description_error: RwSignal<Option<String>> = RwSignal::new(None);
+
+    // Ticks every minute so relative "updated" times stay fresh.
+    let now: RwSignal<i64> = RwSignal::new(now_ms());
+    if let Ok(handle) = set_interval_with_handle(
+        move || now.set(now_ms()),
+        std::time::Duration::from_secs(60),
+    ) {
+        on_cleanup(move || handle.clear());
+    }
+
+    // Params are reactive; read tracked in effects/views, and read untracked in event handlers.
+    let db_id = move || params.get().ok().and_then(|p| p.db_id).unwrap_or_default();
+    let db_id_untracked = move || {
+        params
+            .get_untracked()
+            .ok()
+            .and_then(|p| p.db_id)
+            .unwrap_or_default()
     };
 
-    view! {
-        <div class="space-y-4">
-            <div class="space-y-1">
-                <h1 class="text-xl font-semibold">"Search"</h1>
-                <p class="text-xs text-muted-foreground">{move || format!("q = {}", q())}</p>
-            </div>
+    // Notes list row currently being renamed inline (note id).
+    let renaming: RwSignal<Option<String>> = RwSignal::new(None);
+
+    // Bulk selection (row checkboxes). The delete dialog works on a snapshot of it.
+    let selected_note_ids: RwSignal<std::collections::HashSet<String>> =
+        RwSignal::new(Default::default());
+    let selection_mode = move || selected_note_ids.with(|s| !s.is_empty());
+    let bulk_delete_open: RwSignal<bool> = RwSignal::new(false);
+    let bulk_delete_targets: RwSignal<Vec<Note>> = RwSignal::new(vec![]);
+    let bulk_delete_loading: RwSignal<bool> = RwSignal::new(false);
+    // (title, error) for each deletion that failed in the last run.
+    let bulk_delete_failures: RwSignal<Vec<(String, String)>> = RwSignal::new(vec![]);
+    let app_state_sv = StoredValue::new(app_state.clone());
+    let notes_retry_state: RwSignal<RetryState> = RwSignal::new(RetryState::Idle);
+
+    let notes_sort = app_state.0.notes_sort;
+    let notes = app_state.0.notes;
+    let current_sort = move || {
+        let db = db_id();
+        notes_sort.with(|m| m.get(&db).copied()).unwrap_or_default()
+    };
+    let sorted_notes = Memo::new(move |_| {
+        let mut xs = notes.get();
+        sort_notes(&mut xs, current_sort());
+        xs
+    });
+    let notes_filter = app_state.0.notes_filter;
+    let filtered_notes = Memo::new(move |_| {
+        let bounds = note_filter_bounds_now(&notes_filter.get());
+        sorted_notes.with(|xs| filter_notes_by_updated(xs, bounds.as_ref()))
+    });
 
-            <Show
-                when=move || !q_lower().is_empty()
-                fallback=|| view! {
+    let persist_current_db = move |id: &str| {
+        if let Some(storage) = web_sys::window().and_then(|w| w.local_storage().ok().flatten()) {
+            let _ = storage.set_item(CURRENT_DB_KEY, id);
+        }
+    };
+
+    // Notes loading guards (avoid duplicate loads + ignore stale responses).
+    // Store guard state on AppState so it survives route changes.
+    let load_notes_for_sv = StoredValue::new(move |id: String, force: bool| {
+        if id.trim().is_empty() {
+            return;
+        }
+
+        if !force {
+            let already_loaded = app_state
+                .0
+                .notes_last_loaded_db_id
+                .get_untracked()
+                .as_deref()
+                == Some(id.as_str());
+            let has_error = app_state.0.notes_error.get_untracked().is_some();
+            let is_loading = app_state.0.notes_loading.get_untracked();
+
+            if already_loaded && !has_error && !is_loading {
+                return;
+            }
+        }
+
+        app_state.0.notes_last_loaded_db_id.set(Some(id.clone()));
+
+        let (req_id, abort) = app_state.0.begin_notes_request();
+
+        app_state.0.notes_loading.set(true);
+        app_state.0.notes_error.set(None);
+        app_state.0.notes_loading_more.set(false);
+
+        let api_client = app_state.0.api_client.get_untracked().with_abort(&abort);
+        let notes_request_id = app_state.0.notes_request_id;
+        let is_stale = move || notes_request_id.get_untracked() != req_id;
+        notes_retry_state.set(RetryState::Idle);
+        spawn_local(async move {
+            // First page only; further pages are appended by "Load more".
+            let result = with_retry(
+                RetryPolicy::LIST_FETCH,
+                notes_retry_state,
+                ApiError::is_transient,
+                is_stale,
+                || api_client.get_note_list(&id, 1, NOTE_LIST_PAGE_SIZE),
+            )
+            .await;
+
+            // Ignore stale responses.
+            if is_stale() {
+                return;
+            }
+
+            match result {
+                Ok(notes) => {
+                    app_state.0.notes_page.set(1);
+                    let has_more = notes.len() as i32 >= NOTE_LIST_PAGE_SIZE;
+                    app_state.0.notes_has_more.set(has_more);
+                    // Only a complete list can tell that a pinned note is gone.
+                    if !has_more {
+                        app_state.0.prune_pinned_notes(&id, &notes);
+                    }
+                    app_state.0.notes.set(notes);
+
+                    // Best-effort: size badges for the note list (one request for the whole DB).
+                    // The same navs give the previews, so the list needs no second request.
+                    if let Ok(by_note) = api_client.get_all_navs_for_db(&id).await {
+                        app_state.0.note_char_counts.update(|m| {
+                            for (note_id, navs) in by_note.iter() {
+                                m.insert(note_id.clone(), count_note_chars(navs));
+                            }
+                        });
+                        let previews = by_note
+                            .iter()
+                            .filter_map(|(note_id, navs)| {
+                                note_preview(navs).map(|p| (note_id.clone(), p))
+                            })
+                            .collect::<HashMap<_, _>>();
+                        app_state.0.note_previews.update(|m| {
+                            m.insert(id.clone(), previews);
+                        });
+                    }
+                }
+                Err(e) => {
+                    if e == ApiError::Unauthorized {
+                        app_state_sv.with_value(handle_unauthorized_response);
+                    } else {
+                        app_state.0.notes_error.set(Some(e));
+                        app_state.0.notes.set(vec![]);
+                        app_state.0.notes_has_more.set(false);
+                    }
+                }
+            }
+            app_state.0.notes_loading.set(false);
+        });
+    });
+
+    // Fetch the next page of notes and append (dedup by id).
+    let load_more_notes = move || {
+        if app_state.0.notes_loading_more.get_untracked()
+            || app_state.0.notes_loading.get_untracked()
+            || !app_state.0.notes_has_more.get_untracked()
+        {
+            return;
+        }
+
+        let id = db_id_untracked();
+        if id.trim().is_empty() {
+            return;
+        }
+
+        let req_id = app_state.0.notes_request_id.get_untracked();
+        let page = app_state.0.notes_page.get_untracked() + 1;
+        app_state.0.notes_loading_more.set(true);
+
+        let api_client = app_state.0.api_client.get_untracked();
+        spawn_local(async move {
+            let result = api_client.get_note_list(&id, page, NOTE_LIST_PAGE_SIZE).await;
+
+            // A full reload (or DB switch) happened meanwhile.
+            if app_state.0.notes_request_id.get_untracked() != req_id {
+                return;
+            }
+
+            match result {
+                Ok(notes) => {
+                    app_state.0.notes_page.set(page);
+                    let has_more = notes.len() as i32 >= NOTE_LIST_PAGE_SIZE;
+                    app_state.0.notes_has_more.set(has_more);
+                    app_state.0.notes.update(|xs| merge_notes_page(xs, notes));
+                    if !has_more {
+                        app_state
+                            .0
+                            .notes
+                            .with_untracked(|xs| app_state.0.prune_pinned_notes(&id, xs));
+                    }
+                }
+                Err(e) => {
+                    if e == ApiError::Unauthorized {
+                        app_state_sv.with_value(handle_unauthorized_response);
+                    } else {
+                        app_state.0.notes_error.set(Some(e));
+                    }
+                }
+            }
+            app_state.0.notes_loading_more.set(false);
+        });
+    };
+
+    // "New" split button: a blank daily note, or one pre-filled from a template.
+    let create_note = move |template: Option<NoteTemplate>| {
+        if create_note_loading.get_untracked() {
+            return;
+        }
+
+        create_note_loading.set(true);
+        create_note_error.set(None);
+
+        let id = db_id_untracked();
+        let api_client = app_state.0.api_client.get_untracked();
+        let notes = app_state.0.notes.get_untracked();
+        let title_format = app_state.0.settings.with_untracked(|s| s.new_note_title);
+
+        spawn_local(async move {
+            match create_daily_note(&api_client, &id, &notes, title_format, template.as_ref())
+                .await
+            {
+                Ok(note) => {
+                    // Refresh list then navigate to note.
+                    load_notes_for_sv.with_value(|f| {
+                        f(id.clone(), true);
+                    });
+
+                    navigate.with_value(|nav| {
+                        nav(&format!("/db/{}/note/{}", id, note.id), Default::default());
+                    });
+                }
+                Err(e) => {
+                    if e == ApiError::Unauthorized {
+                        app_state_sv.with_value(handle_unauthorized_response);
+                    } else {
+                        // A partially filled note still exists; show it in the list.
+                        if matches!(e, ApiError::Incomplete(_)) {
+                            load_notes_for_sv.with_value(|f| f(id.clone(), true));
+                        }
+                        create_note_error.set(Some(e.to_string()));
+                    }
+                }
+            }
+            create_note_loading.set(false);
+        });
+    };
+
+    // Keep global selection in sync with URL + write recent DB.
+    Effect::new(move |_| {
+        let id = db_id();
+        if id.trim().is_empty() {
+            return;
+        }
+
+        if app_state.0.current_database_id.get() != Some(id.clone()) {
+            app_state.0.current_database_id.set(Some(id.clone()));
+            persist_current_db(&id);
+        }
+
+        // Phase 5.5: recent databases (local). Never under the raw id: this re-runs once the
+        // database list has the name.
+        if let Some(d) = app_state.0.databases.get().into_iter().find(|d| d.id == id) {
+            write_recent_db(&d.id, &d.name);
+        }
+    });
+
+    // Phase 5: load the first page of notes for the current database.
+    Effect::new(move |_| {
+        load_notes_for_sv.with_value(|f| {
+            f(db_id(), false);
+        });
+    });
+
+    // UX: when user enters /db/:db_id, auto-open the first note.
+    // This makes the main area show a note immediately and enables Pages highlight.
+    Effect::new(move |_| {
+        let id = db_id();
+        if id.trim().is_empty() {
+            return;
+        }
+
+        let p = pathname();
+        if p != format!("/db/{}", id) {
+            return;
+        }
+
+        if app_state.0.notes_loading.get() {
+            return;
+        }
+
+        // Pinned notes stay at the top of lists but are never auto-opened.
+        // Only the first page counts (later pages are appended by "Load more").
+        let pinned_ids = app_state
+            .0
+            .pinned_notes
+            .with_untracked(|m| m.get(&id).cloned())
+            .unwrap_or_default();
+        let mut notes = app_state
+            .0
+            .notes
+            .get()
+            .into_iter()
+            .take(NOTE_LIST_PAGE_SIZE as usize)
+            .filter(|n| n.database_id == id)
+            .filter(|n| !pinned_ids.iter().any(|p| p == &n.id))
+            .collect::<Vec<_>>();
+
+        if notes.is_empty() {
+            return;
+        }
+
+        // Prefer most recently updated (lexicographic works for ISO-ish timestamps).
+        notes.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+        let first_id = notes[0].id.clone();
+
+        // Use replace=true so browser Back goes to the previous page (e.g. Home),
+        // instead of bouncing between /db/:db_id and /db/:db_id/note/:note_id.
+        navigate.with_value(|nav| {
+            nav(
+                &format!("/db/{}/note/{}", id, first_id),
+                leptos_router::NavigateOptions {
+                    replace: true,
+                    ..Default::default()
+                },
+            );
+        });
+    });
+
+    let db = move || {
+        let id = db_id();
+        app_state.0.databases.get().into_iter().find(|d| d.id == id)
+    };
+
+    let refresh_databases = move || {
+        let mut c = app_state.0.api_client.get_untracked();
+        spawn_local(async move {
+            match c.get_database_list().await {
+                Ok(dbs) => {
+                    app_state.0.databases.set(dbs);
+                }
+                Err(e) => {
+                    if e == ApiError::Unauthorized {
+                        app_state_sv.with_value(handle_unauthorized_response);
+                        return;
+                    }
+                }
+            }
+            app_state.0.api_client.set(c);
+        });
+    };
+
+    let _refresh_databases = move || {
+        let mut c = app_state.0.api_client.get_untracked();
+        spawn_local(async move {
+            if let Ok(dbs) = c.get_database_list().await {
+                app_state.0.databases.set(dbs);
+            }
+            app_state.0.api_client.set(c);
+        });
+    };
+
+    let _on_open_rename = move |_: web_sys::MouseEvent| {
+        rename_error.set(None);
+        if let Some(d) = db() {
+            rename_value.set(d.name);
+        }
+        rename_open.set(true);
+    };
+
+    let on_submit_rename = move |_| {
+        if rename_loading.get_untracked() {
+            return;
+        }
+        let id = db_id();
+        let new_name = rename_value.get_untracked();
+        if new_name.trim().is_empty() {
+            rename_error.set(Some("Name cannot be empty".to_string()));
+            return;
+        }
+        let api_client = app_state.0.api_client.get_untracked();
+
+        rename_loading.set(true);
+        rename_error.set(None);
+
+        spawn_local(async move {
+            match api_client.rename_database(&id, &new_name).await {
+                Ok(_) => {
+                    refresh_databases();
+                    rename_open.set(false);
+                }
+                Err(e) => rename_error.set(Some(e.to_string())),
+            }
+            rename_loading.set(false);
+        });
+    };
+
+    let on_open_description = move |_: web_sys::MouseEvent| {
+        description_error.set(None);
+        description_value.set(db().map(|d| d.description).unwrap_or_default());
+        description_editing.set(true);
+    };
+
+    let on_save_description = move |_| {
+        if description_loading.get_untracked() {
+            return;
+        }
+        let id = db_id_untracked();
+        let description = description_value.get_untracked();
+        let api_client = app_state.0.api_client.get_untracked();
+
+        description_loading.set(true);
+        description_error.set(None);
+
+        spawn_local(async move {
+            match api_client
+                .update_database_description(&id, description.trim())
+                .await
+            {
+                Ok(_) => {
+                    refresh_databases();
+                    description_editing.set(false);
+                }
+                Err(e) => {
+                    if e == ApiError::Unauthorized {
+                        app_state_sv.with_value(handle_unauthorized_response);
+                    } else {
+                        description_error.set(Some(e.to_string()));
+                    }
+                }
+            }
+            description_loading.set(false);
+        });
+    };
+
+    let _on_open_delete = move |_: web_sys::MouseEvent| {
+        delete_confirm.set(String::new());
+        delete_error.set(None);
+        delete_open.set(true);
+    };
+
+    let on_submit_delete = move |_| {
+        if delete_loading.get_untracked() {
+            return;
+        }
+
+        let id = db_id();
+        let name = db().map(|d| d.name).unwrap_or_default();
+        let confirm = delete_confirm.get_untracked();
+        if confirm.trim() != name.trim() {
+            delete_error.set(Some(
+                "Type the database name to confirm deletion".to_string(),
+            ));
+            return;
+        }
+
+        let api_client = app_state.0.api_client.get_untracked();
+        delete_loading.set(true);
+        delete_error.set(None);
+
+        spawn_local(async move {
+            match api_client.delete_database_by_id(&id).await {
+                Ok(_) => {
+                    // Reload DBs and navigate to the first remaining DB (or /).
+                    let mut c = app_state.0.api_client.get_untracked();
+                    if let Ok(dbs) = c.get_database_list().await {
+                        app_state.0.databases.set(dbs.clone());
+                        if let Some(first) = dbs.first() {
+                            app_state.0.current_database_id.set(Some(first.id.clone()));
+                            persist_current_db(&first.id);
+                            navigate.with_value(|nav| {
+                                nav(&format!("/db/{}", first.id), Default::default());
+                            });
+                        } else {
+                            app_state.0.current_database_id.set(None);
+                            persist_current_db("");
+                            navigate.with_value(|nav| {
+                                nav("/", Default::default());
+                            });
+                        }
+                    }
+                    app_state.0.api_client.set(c);
+                    delete_open.set(false);
+                }
+                Err(e) => delete_error.set(Some(e.to_string())),
+            }
+            delete_loading.set(false);
+        });
+    };
+
+    // Selection belongs to one database's list.
+    Effect::new(move |_| {
+        let _ = db_id();
+        selected_note_ids.set(Default::default());
+    });
+
+    let on_open_bulk_delete = move |_| {
+        let db = db_id_untracked();
+        let targets = selected_note_ids
+            .with_untracked(|ids| notes_with_ids(&filtered_notes.get_untracked(), ids))
+            .into_iter()
+            .map(|n| Note {
+                title: get_title_override(&db, &n.id, &n.title),
+                ..n
+            })
+            .collect();
+        bulk_delete_targets.set(targets);
+        bulk_delete_failures.set(vec![]);
+        bulk_delete_open.set(true);
+    };
+
+    let on_submit_bulk_delete = move |_| {
+        if bulk_delete_loading.get_untracked() {
+            return;
+        }
+
+        let db = db_id_untracked();
+        let targets = bulk_delete_targets.get_untracked();
+        let api_client = app_state.0.api_client.get_untracked();
+        bulk_delete_loading.set(true);
+        bulk_delete_failures.set(vec![]);
+
+        spawn_local(async move {
+            // Sequential on purpose: the list shrinks as each deletion lands.
+            let mut failed: Vec<Note> = vec![];
+            let mut failures: Vec<(String, String)> = vec![];
+            for note in targets {
+                match api_client.delete_note(&note.id).await {
+                    Ok(()) => {
+                        notes.update(|xs| xs.retain(|n| n.id != note.id));
+                        app_state_sv.with_value(|s| {
+                            s.0.unpin_note(&db, &note.id);
+                            s.0.invalidate_navs_cache(&note.id);
+                            s.0.push_deleted(
+                                DeletedItemKind::Note,
+                                &note.id,
+                                serde_json::to_value(&note).unwrap_or_default(),
+                            );
+                        });
+
+                        let mut recents = load_recent_notes();
+                        let before = recents.len();
+                        recents.retain(|r| !(r.db_id == db && r.note_id == note.id));
+                        if recents.len() != before {
+                            save_recent_notes(&recents);
+                        }
+                    }
+                    Err(e) => {
+                        failures.push((note.title.clone(), e.to_string()));
+                        failed.push(note);
+                    }
+                }
+            }
+
+            selected_note_ids.set(Default::default());
+            bulk_delete_loading.set(false);
+            if failures.is_empty() {
+                bulk_delete_open.set(false);
+            }
+            // Leave only the failed notes in the dialog so "Delete" retries them.
+            bulk_delete_targets.set(failed);
+            bulk_delete_failures.set(failures);
+        });
+    };
+
+    let db_missing = move || {
+        let id = db_id();
+        let loaded = app_state.0.db_load_timestamp.get().is_some();
+        app_state
+            .0
+            .databases
+            .with(|dbs| resolve_db_name(dbs, loaded, &id))
+            == DbName::Missing
+    };
+
+    let is_auto_opening_note = move || {
+        let id = db_id();
+        let p = pathname();
+        if id.trim().is_empty() {
+            return false;
+        }
+        if p != format!("/db/{}", id) {
+            return false;
+        }
+
+        // If notes are loading, or we already have notes for this DB, we're about to auto-navigate.
+        let has_notes = app_state
+            .0
+            .notes
+            .get()
+            .into_iter()
+            .any(|n| n.database_id == id);
+
+        app_state.0.notes_loading.get() || has_notes
+    };
+
+    view! {
+        <Show
+            when=move || !db_missing() && !is_auto_opening_note()
+            fallback=move || {
+                if db_missing() {
+                    return view! { <DatabaseNotFound db_id=db_id_untracked() /> }.into_any();
+                }
+                view! {
+                    <div class="flex h-[40vh] items-center justify-center">
+                        <Spinner />
+                    </div>
+                }
+                .into_any()
+            }
+        >
+            <div class="space-y-3">
+                <div class="flex items-start justify-between gap-3">
+                    <div class="space-y-1">
+                        <h1 class="text-xl font-semibold">
+                            {move || match db() {
+                                Some(d) => d.name.into_any(),
+                                None => view! {
+                                    <span class="inline-block h-6 w-40 animate-pulse rounded bg-muted" aria-hidden="true" />
+                                }
+                                .into_any(),
+                            }}
+                        </h1>
+                        <Show
+                            when=move || description_editing.get()
+                            fallback=move || view! {
+                                <button
+                                    type="button"
+                                    class="block text-left text-sm text-muted-foreground hover:text-foreground"
+                                    title="Edit description"
+                                    on:click=on_open_description
+                                >
+                                    {move || {
+                                        let d = db().map(|d| d.description).unwrap_or_default();
+                                        if d.trim().is_empty() {
+                                            "Add a description…".to_string()
+                                        } else {
+                                            d
+                                        }
+                                    }}
+                                </button>
+                            }
+                        >
+                            <div class="space-y-2">
+                                <textarea
+                                    class="min-h-[64px] w-full rounded-md border border-input bg-transparent px-3 py-2 text-sm shadow-xs outline-none focus-visible:border-ring focus-visible:ring-2 focus-visible:ring-ring/50"
+                                    placeholder="Describe this database"
+                                    prop:value=move || description_value.get()
+                                    on:input=move |ev| description_value.set(event_target_value(&ev))
+                                ></textarea>
+                                <Show when=move || description_error.get().is_some() fallback=|| ().into_view()>
+                                    <div class="text-xs text-destructive">
+                                        {move || description_error.get().unwrap_or_default()}
+                                    </div>
+                                </Show>
+                                <div class="flex items-center gap-2">
+                                    <Button
+                                        size=ButtonSize::Sm
+                                        attr:disabled=move || description_loading.get()
+                                        on:click=on_save_description
+                                    >
+                                        {move || if description_loading.get() { "Saving…" } else { "Save" }}
+                                    </Button>
+                                    <Button
+                                        variant=ButtonVariant::Ghost
+                                        size=ButtonSize::Sm
+                                        on:click=move |_| description_editing.set(false)
+                                    >
+                                        "Cancel"
+                                    </Button>
+                                </div>
+                            </div>
+                        </Show>
+                        <p class="text-xs text-muted-foreground">{move || format!("db_id: {}", db_id())}</p>
+                    </div>
+
+                    <div class="flex items-center gap-2"></div>
+                </div>
+
+            <Card>
+                <CardContent>
+                    <div class="flex items-center justify-between gap-3">
+                        <div class="text-sm font-medium">"Notes"</div>
+                        <div class="flex items-center gap-2">
+                            <Show when=selection_mode fallback=|| ().into_view()>
+                                <Button
+                                    variant=ButtonVariant::Ghost
+                                    size=ButtonSize::Sm
+                                    on:click=move |_| selected_note_ids.set(Default::default())
+                                >
+                                    "Clear"
+                                </Button>
+                                <Button
+                                    variant=ButtonVariant::Destructive
+                                    size=ButtonSize::Sm
+                                    on:click=on_open_bulk_delete
+                                >
+                                    {move || format!("Delete selected ({})", selected_note_ids.with(|s| s.len()))}
+                                </Button>
+                            </Show>
+                            <select
+                                class="h-8 rounded-md border border-input bg-background px-2 text-xs"
+                                aria-label="Sort notes"
+                                prop:value=move || current_sort().key()
+                                on:change={
+                                    let app_state = app_state.clone();
+                                    move |ev| {
+                                        if let Some(sort) = NoteSort::from_key(&event_target_value(&ev)) {
+                                            app_state.0.set_notes_sort(&db_id_untracked(), sort);
+                                        }
+                                    }
+                                }
+                            >
+                                {NoteSort::ALL
+                                    .into_iter()
+                                    .map(|sort| view! { <option value=sort.key()>{sort.label()}</option> })
+                                    .collect_view()}
+                            </select>
+                            <select
+                                class="h-8 rounded-md border border-input bg-background px-2 text-xs"
+                                aria-label="Filter notes by last update"
+                                prop:value=move || notes_filter.with(|f| f.key())
+                                on:change=move |ev| {
+                                    if let Some(filter) = NoteFilter::from_key(&event_target_value(&ev)) {
+                                        notes_filter.set(filter);
+                                    }
+                                }
+                            >
+                                {NoteFilter::PRESETS
+                                    .into_iter()
+                                    .map(|filter| view! { <option value=filter.key()>{filter.label()}</option> })
+                                    .collect_view()}
+                                <Show when=move || notes_filter.with(|f| matches!(f, NoteFilter::Custom(..)))>
+                                    <option value="custom">"Custom"</option>
+                                </Show>
+                            </select>
+                            <div class="relative flex">
+                                <Button
+                                    variant=ButtonVariant::Outline
+                                    size=ButtonSize::Sm
+                                    attr:disabled=move || create_note_loading.get()
+                                    on:click=move |_| create_note(None)
+                                    attr:title="New note"
+                                    class="rounded-r-none"
+                                >
+                                    {move || if create_note_loading.get() { "Creating..." } else { "New" }}
+                                </Button>
+                                <Button
+                                    variant=ButtonVariant::Outline
+                                    size=ButtonSize::Sm
+                                    attr:disabled=move || create_note_loading.get()
+                                    on:click=move |_| template_menu_open.update(|v| *v = !*v)
+                                    attr:title="New from template"
+                                    attr:aria-haspopup="menu"
+                                    attr:aria-expanded=move || template_menu_open.get().to_string()
+                                    class="rounded-l-none border-l-0 px-2"
+                                >
+                                    "▾"
+                                </Button>
+                                <Show when=move || template_menu_open.get() fallback=|| ().into_view()>
+                                    <div
+                                        class="absolute right-0 top-full z-20 mt-1 w-56 rounded-md border border-border bg-background p-1 shadow-lg"
+                                        role="menu"
+                                    >
+                                        <Show
+                                            when=move || !templates.with(|ts| ts.is_empty())
+                                            fallback=|| view! {
+                                                <div class="px-2 py-1.5 text-xs text-muted-foreground">
+                                                    "No templates yet. "
+                                                    <A href="/settings" attr:class="text-primary underline underline-offset-4">
+                                                        "Add one in Settings"
+                                                    </A>
+                                                </div>
+                                            }
+                                        >
+                                            <For
+                                                each=move || templates.get().into_iter().enumerate()
+                                                key=|(i, t)| (*i, t.name.clone())
+                                                children=move |(_, t)| {
+                                                    let label = t.name.clone();
+                                                    let template = StoredValue::new(t);
+                                                    view! {
+                                                        <button
+                                                            type="button"
+                                                            role="menuitem"
+                                                            class="block w-full truncate rounded px-2 py-1.5 text-left text-xs hover:bg-surface-hover"
+                                                            on:click=move |_| {
+                                                                template_menu_open.set(false);
+                                                                create_note(Some(template.get_value()));
+                                                            }
+                                                        >
+                                                            {label}
+                                                        </button>
+                                                    }
+                                                }
+                                            />
+                                        </Show>
+                                    </div>
+                                </Show>
+                            </div>
+                        </div>
+                    </div>
+
+                    <div class="mt-3 space-y-2">
+                        <Show when=move || create_note_error.get().is_some() fallback=|| ().into_view()>
+                            {move || {
+                                create_note_error.get().map(|e| {
+                                    view! {
+                                        <Alert class="border-destructive/30">
+                                            <AlertDescription class="text-destructive text-xs">{e}</AlertDescription>
+                                        </Alert>
+                                    }
+                                })
+                            }}
+                        </Show>
+
+                        <Show
+                            when=move || !app_state.0.notes_loading.get()
+                            fallback=move || view! {
+                                <div class="flex items-center gap-2 text-sm text-muted-foreground">
+                                    <Spinner />
+                                    {move || match notes_retry_state.get() {
+                                        RetryState::Retrying { delay_ms, .. } => {
+                                            format!("Loading notes… (retrying in {}s)", delay_ms / 1000)
+                                        }
+                                        _ => "Loading notes…".to_string(),
+                                    }}
+                                </div>
+                            }
+                        >
+                            <Show
+                                when=move || app_state.0.notes_error.get().is_none()
+                                fallback=move || view! {
+                                    <Alert class="border-destructive/30">
+                                        <AlertDescription class="text-destructive text-xs">
+                                            {move || {
+                                                app_state
+                                                    .0
+                                                    .notes_error
+                                                    .get()
+                                                    .map(|e| e.to_string())
+                                                    .unwrap_or_default()
+                                            }}
+                                        </AlertDescription>
+                                        <Show when=move || {
+                                            app_state
+                                                .0
+                                                .notes_error
+                                                .with(|e| matches!(e, Some(ApiError::Network(_))))
+                                        }>
+                                            <Button
+                                                variant=ButtonVariant::Outline
+                                                size=ButtonSize::Sm
+                                                class="mt-2 h-7 text-xs"
+                                                on:click=move |_| {
+                                                    load_notes_for_sv.with_value(|f| f(db_id(), true));
+                                                }
+                                            >
+                                                "Retry"
+                                            </Button>
+                                        </Show>
+                                    </Alert>
+                                }
+                            >
+                                <Show
+                                    when=move || !app_state.0.notes.get().is_empty()
+                                    fallback=move || view! {
+                                        <div class="text-sm text-muted-foreground">"No notes yet."</div>
+                                    }
+                                >
+                                    <Show when=move || filtered_notes.with(|xs| xs.is_empty())>
+                                        <div class="text-sm text-muted-foreground">
+                                            "No notes updated in this period."
+                                        </div>
+                                    </Show>
+                                    <div class="space-y-1">
+                                        {move || {
+                                            let db = db_id();
+                                            let pinned_ids = app_state.0.pinned_note_ids(&db);
+                                            let (pinned, rest) =
+                                                partition_pinned_notes(filtered_notes.get(), &pinned_ids);
+                                            let has_pinned = !pinned.is_empty();
+
+                                            let row = |n: Note, is_pinned: bool| {
+                                                // Use title override to match note title behavior (local-first).
+                                                let display_title = get_title_override(&db, &n.id, &n.title);
+                                                if renaming.get().as_deref() == Some(n.id.as_str()) {
+                                                    return view! {
+                                                        <div class="rounded-md border border-border bg-background px-3 py-2">
+                                                            <NoteRenameInput
+                                                                db_id=db.clone()
+                                                                note_id=n.id.clone()
+                                                                title=display_title
+                                                                on_done=move |()| renaming.set(None)
+                                                            />
+                                                        </div>
+                                                    }
+                                                    .into_any();
+                                                }
+                                                let display_title_for_copy = display_title.clone();
+                                                let rename_id = n.id.clone();
+                                                let select_id = n.id.clone();
+                                                let toggle_id = n.id.clone();
+                                                let char_count = app_state.0.note_char_counts.with(|m| m.get(&n.id).copied());
+                                                let preview = app_state.0.note_list_preview(&db, &n.id);
+                                                view! {
+                                                    <div class="group flex items-center gap-2 rounded-md border border-border bg-background px-3 py-2 transition-colors hover:bg-surface-hover">
+                                                        <input
+                                                            type="checkbox"
+                                                            class=move || if selection_mode() {
+                                                                "h-4 w-4 shrink-0 cursor-pointer"
+                                                            } else {
+                                                                "h-4 w-4 shrink-0 cursor-pointer opacity-0 group-hover:opacity-100 focus:opacity-100"
+                                                            }
+                                                            aria-label=format!("Select {}", display_title)
+                                                            prop:checked=move || selected_note_ids.with(|s| s.contains(&select_id))
+                                                            on:change=move |ev| {
+                                                                let checked = event_target_checked(&ev);
+                                                                selected_note_ids.update(|s| {
+                                                                    if checked {
+                                                                        s.insert(toggle_id.clone());
+                                                                    } else {
+                                                                        s.remove(&toggle_id);
+                                                                    }
+                                                                });
+                                                            }
+                                                        />
+                                                        <a href=format!("/db/{}/note/{}", db, n.id) class="block min-w-0 flex-1">
+                                                            <div class="truncate text-sm font-medium">{display_title}</div>
+                                                            {preview.map(|p| view! {
+                                                                <div class="truncate text-xs text-muted-foreground">{p}</div>
+                                                            })}
+                                                            <div class="flex items-center gap-2 text-xs text-muted-foreground">
+                                                                <span class="truncate" title=n.updated_at.clone()>
+                                                                    {
+                                                                        let updated_at = n.updated_at.clone();
+                                                                        move || format_relative_time(&updated_at, now.get())
+                                                                    }
+                                                                </span>
+                                                                {char_count.map(|c| view! {
+                                                                    <span class="shrink-0 rounded bg-muted px-1.5 py-0.5 text-[10px]">
+                                                                        {format_char_count_badge(c)}
+                                                                    </span>
+                                                                })}
+                                                            </div>
+                                                        </a>
+                                                        <div class=if is_pinned { "flex" } else { "hidden group-hover:flex" }>
+                                                            <NoteRenameButton on_click=move |()| renaming.set(Some(rename_id.clone())) />
+                                                            <NoteMoveButton
+                                                                source=Note { title: display_title_for_copy.clone(), ..n.clone() }
+                                                                compact=true
+                                                                on_error=move |e: String| create_note_error.set(Some(e))
+                                                            />
+                                                            <NoteDuplicateButton
+                                                                source=Note { title: display_title_for_copy, ..n.clone() }
+                                                                compact=true
+                                                                on_error=move |e: String| create_note_error.set(Some(e))
+                                                            />
+                                                            <NotePinButton db_id=db.clone() note_id=n.id.clone() is_pinned=is_pinned />
+                                                        </div>
+                                                    </div>
+                                                }
+                                                .into_any()
+                                            };
+
+                                            let pinned_views = pinned
+                                                .into_iter()
+                                                .map(|n| row(n, true))
+                                                .collect::<Vec<_>>();
+                                            let rest_views = rest
+                                                .into_iter()
+                                                .map(|n| row(n, false))
+                                                .collect::<Vec<_>>();
+
+                                            view! {
+                                                <Show when=move || has_pinned fallback=|| ().into_view()>
+                                                    <div class="px-1 text-xs font-medium text-muted-foreground">"Pinned"</div>
+                                                </Show>
+                                                {pinned_views}
+                                                <Show when=move || has_pinned fallback=|| ().into_view()>
+                                                    <div class="h-px w-full bg-border" />
+                                                </Show>
+                                                {rest_views}
+                                            }
+                                        }}
+                                    </div>
+
+                                    <Show when=move || app_state.0.notes_has_more.get() fallback=|| ().into_view()>
+                                        <div class="mt-3 flex justify-center">
+                                            <Button
+                                                variant=ButtonVariant::Ghost
+                                                size=ButtonSize::Sm
+                                                attr:disabled=move || app_state.0.notes_loading_more.get()
+                                                on:click=move |_| load_more_notes()
+                                            >
+                                                <span class="inline-flex items-center gap-2">
+                                                    <Show when=move || app_state.0.notes_loading_more.get() fallback=|| ().into_view()>
+                                                        <Spinner />
+                                                    </Show>
+                                                    {move || if app_state.0.notes_loading_more.get() { "Loading…" } else { "Load more" }}
+                                                </span>
+                                            </Button>
+                                        </div>
+                                    </Show>
+                                </Show>
+                            </Show>
+                        </Show>
+                    </div>
+                </CardContent>
+            </Card>
+
+            <Show when=move || rename_open.get() fallback=|| ().into_view()>
+                <div class="fixed inset-0 z-50 flex items-center justify-center bg-black/30 px-4">
+                    <div class="w-full max-w-sm rounded-md border border-border bg-background p-4 shadow-lg">
+                        <div class="mb-3 space-y-1">
+                            <div class="text-sm font-medium">"Rename database"</div>
+                            <div class="text-xs text-muted-foreground">"Only the name can be updated (backend limitation)."</div>
+                        </div>
+
+                        <div class="space-y-2">
+                            <div class="space-y-1">
+                                <Label class="text-xs">"New name"</Label>
+                                <Input bind_value=rename_value class="h-8 text-sm" />
+                            </div>
+
+                            <Show when=move || rename_error.get().is_some() fallback=|| ().into_view()>
+                                {move || rename_error.get().map(|e| view! {
+                                    <Alert class="border-destructive/30">
+                                        <AlertDescription class="text-destructive text-xs">{e}</AlertDescription>
+                                    </Alert>
+                                })}
+                            </Show>
+
+                            <div class="flex items-center justify-end gap-2 pt-2">
+                                <Button
+                                    variant=ButtonVariant::Outline
+                                    size=ButtonSize::Sm
+                                    attr:disabled=move || rename_loading.get()
+                                    on:click=move |_| rename_open.set(false)
+                                >
+                                    "Cancel"
+                                </Button>
+                                <Button
+                                    size=ButtonSize::Sm
+                                    attr:disabled=move || rename_loading.get()
+                                    on:click=on_submit_rename
+                                >
+                                    <span class="inline-flex items-center gap-2">
+                                        <Show when=move || rename_loading.get() fallback=|| ().into_view()>
+                                            <Spinner />
+                                        </Show>
+                                        {move || if rename_loading.get() { "Saving..." } else { "Save" }}
+                                    </span>
+                                </Button>
+                            </div>
+                        </div>
+                    </div>
+                </div>
+            </Show>
+
+            <Show when=move || delete_open.get() fallback=|| ().into_view()>
+                <div class="fixed inset-0 z-50 flex items-center justify-center bg-black/30 px-4">
+                    <div class="w-full max-w-sm rounded-md border border-border bg-background p-4 shadow-lg">
+                        <div class="mb-3 space-y-1">
+                            <div class="text-sm font-medium">"Delete database"</div>
+                            <div class="text-xs text-muted-foreground">
+                                {move || {
+                                    let name = db().map(|d| d.name).unwrap_or_default();
+                                    format!("Type '{}' to confirm.", name)
+                                }}
+                            </div>
+                        </div>
+
+                        <div class="space-y-2">
+                            <Input bind_value=delete_confirm class="h-8 text-sm" />
+
+                            <Show when=move || delete_error.get().is_some() fallback=|| ().into_view()>
+                                {move || delete_error.get().map(|e| view! {
+                                    <Alert class="border-destructive/30">
+                                        <AlertDescription class="text-destructive text-xs">{e}</AlertDescription>
+                                    </Alert>
+                                })}
+                            </Show>
+
+                            <div class="flex items-center justify-end gap-2 pt-2">
+                                <Button
+                                    variant=ButtonVariant::Outline
+                                    size=ButtonSize::Sm
+                                    attr:disabled=move || delete_loading.get()
+                                    on:click=move |_| delete_open.set(false)
+                                >
+                                    "Cancel"
+                                </Button>
+                                <Button
+                                    size=ButtonSize::Sm
+                                    attr:disabled=move || delete_loading.get()
+                                    on:click=on_submit_delete
+                                >
+                                    <span class="inline-flex items-center gap-2">
+                                        <Show when=move || delete_loading.get() fallback=|| ().into_view()>
+                                            <Spinner />
+                                        </Show>
+                                        {move || if delete_loading.get() { "Deleting..." } else { "Delete" }}
+                                    </span>
+                                </Button>
+                            </div>
+                        </div>
+                    </div>
+                </div>
+            </Show>
+
+            <Show when=move || bulk_delete_open.get() fallback=|| ().into_view()>
+                <div class="fixed inset-0 z-50 flex items-center justify-center bg-black/30 px-4">
+                    <div class="w-full max-w-sm rounded-md border border-border bg-background p-4 shadow-lg">
+                        <div class="mb-3 space-y-1">
+                            <div class="text-sm font-medium">
+                                {move || {
+                                    let n = bulk_delete_targets.with(|xs| xs.len());
+                                    format!("Delete {} note{}", n, if n == 1 { "" } else { "s" })
+                                }}
+                            </div>
+                            <div class="text-xs text-muted-foreground">"The following notes will be deleted:"</div>
+                        </div>
+
+                        <div class="space-y-2">
+                            <ul class="max-h-48 space-y-1 overflow-auto rounded-md border border-border p-2 text-xs">
+                                {move || {
+                                    bulk_delete_targets
+                                        .get()
+                                        .into_iter()
+                                        .map(|n| view! { <li class="truncate">{n.title}</li> })
+                                        .collect_view()
+                                }}
+                            </ul>
+
+                            <Show when=move || !bulk_delete_failures.with(|xs| xs.is_empty()) fallback=|| ().into_view()>
+                                <Alert class="border-destructive/30">
+                                    <AlertDescription class="text-destructive text-xs">
+                                        <div>"Some notes could not be deleted:"</div>
+                                        {move || {
+                                            bulk_delete_failures
+                                                .get()
+                                                .into_iter()
+                                                .map(|(title, e)| view! { <div class="truncate">{format!("{}: {}", title, e)}</div> })
+                                                .collect_view()
+                                        }}
+                                    </AlertDescription>
+                                </Alert>
+                            </Show>
+
+                            <div class="flex items-center justify-end gap-2 pt-2">
+                                <Button
+                                    variant=ButtonVariant::Outline
+                                    size=ButtonSize::Sm
+                                    attr:disabled=move || bulk_delete_loading.get()
+                                    on:click=move |_| bulk_delete_open.set(false)
+                                >
+                                    "Cancel"
+                                </Button>
+                                <Button
+                                    variant=ButtonVariant::Destructive
+                                    size=ButtonSize::Sm
+                                    attr:disabled=move || bulk_delete_loading.get()
+                                    on:click=on_submit_bulk_delete
+                                >
+                                    <span class="inline-flex items-center gap-2">
+                                        <Show when=move || bulk_delete_loading.get() fallback=|| ().into_view()>
+                                            <Spinner />
+                                        </Show>
+                                        {move || if bulk_delete_loading.get() { "Deleting..." } else { "Delete" }}
+                                    </span>
+                                </Button>
+                            </div>
+                        </div>
+                    </div>
+                </div>
+            </Show>
+        </div>
+        </Show>
+    }
+}
+
+#[component]
+pub fn SearchPage() -> impl IntoView {
+    let app_state = expect_context::<AppContext>();
+    let query = use_query_map();
+
+    let q = move || query.get().get("q").unwrap_or_default();
+    let q_lower = move || q().trim().to_lowercase();
+
+    let matched_dbs = move || {
+        let q = q_lower();
+        if q.is_empty() {
+            return vec![];
+        }
+        app_state
+            .0
+            .databases
+            .get()
+            .into_iter()
+            .filter(|d| d.name.to_lowercase().contains(&q))
+            .collect::<Vec<_>>()
+    };
+
+    let matched_notes = move || {
+        let q = q_lower();
+        if q.is_empty() {
+            return vec![];
+        }
+        let db_id = app_state.0.current_database_id.get().unwrap_or_default();
+        if db_id.trim().is_empty() {
+            return vec![];
+        }
+
+        app_state
+            .0
+            .notes
+            .get()
+            .into_iter()
+            .filter(|n| n.database_id == db_id)
+            .filter(|n| n.title.to_lowercase().contains(&q))
+            .collect::<Vec<_>>()
+    };
+
+    // Content matches come from the backend; results are cached per (db, query) in app state.
+    let search_cache = app_state.0.search_results_cache;
+    let content_key = Memo::new(move |_| {
+        let q = q();
+        let db_id = app_state.0.current_database_id.get()?;
+        (!q.trim().is_empty() && !db_id.trim().is_empty()).then(|| search_cache_key(&db_id, &q))
+    });
+    // Key of the search in flight (the spinner shows while it is the current one).
+    let content_loading: RwSignal<Option<String>> = RwSignal::new(None);
+    let content_error: RwSignal<Option<String>> = RwSignal::new(None);
+    let content_notes = move || {
+        let key = content_key.get()?;
+        search_cache.with(|m| m.get(&key).cloned())
+    };
+
+    let app_state_for_search = StoredValue::new(app_state.clone());
+    Effect::new(move |_| {
+        let Some(key) = content_key.get() else {
+            return;
+        };
+        content_error.set(None);
+        if search_cache.with_untracked(|m| m.contains_key(&key))
+            || content_loading.get_untracked().as_deref() == Some(key.as_str())
+        {
+            return;
+        }
+        let Some(db_id) = app_state.0.current_database_id.get_untracked() else {
+            return;
+        };
+        let query = q().trim().to_string();
+
+        content_loading.set(Some(key.clone()));
+        let app_state = app_state_for_search.get_value();
+        spawn_local(async move {
+            let api_client = app_state.0.api_client.get_untracked();
+            match api_client.search_notes(&db_id, &query).await {
+                Ok(notes) => {
+                    search_cache.update(|m| {
+                        m.insert(key.clone(), notes);
+                    });
+                }
+                Err(e) if e == ApiError::Unauthorized => {
+                    handle_unauthorized_response(&app_state);
+                }
+                Err(e) => {
+                    if content_key.get_untracked().as_deref() == Some(key.as_str()) {
+                        content_error.set(Some(e.to_string()));
+                    }
+                }
+            }
+            if content_loading.get_untracked().as_deref() == Some(key.as_str()) {
+                content_loading.set(None);
+            }
+        });
+    });
+
+    // "Create note '<query>'": offered when no note in the current DB has exactly that title.
+    let create_title = move || {
+        let db_id = app_state.0.current_database_id.get()?;
+        app_state.0.notes.with(|xs| search_create_title(xs, &db_id, &q()))
+    };
+    let navigate = StoredValue::new(use_navigate());
+    let app_state_sv = StoredValue::new(app_state.clone());
+    let create_loading: RwSignal<bool> = RwSignal::new(false);
+    let create_error: RwSignal<Option<String>> = RwSignal::new(None);
+
+    let create_note = move |title: String| {
+        if create_loading.get_untracked() {
+            return;
+        }
+        let Some(db_id) = app_state.0.current_database_id.get_untracked() else {
+            return;
+        };
+
+        create_loading.set(true);
+        create_error.set(None);
+        let app_state = app_state_sv.get_value();
+        spawn_local(async move {
+            let api_client = app_state.0.api_client.get_untracked();
+
+            // `notes` may hold only the first page; check the full list before creating.
+            let all = api_client.get_all_note_list(&db_id).await.ok();
+            let existing = all
+                .as_deref()
+                .and_then(|xs| find_note_by_title(xs, &db_id, &title).cloned());
+
+            let result = match existing {
+                Some(note) => Ok(note),
+                None => api_client.create_note(&db_id, &title).await,
+            };
+
+            match result {
+                Ok(note) if !note.id.trim().is_empty() => {
+                    if let Ok(notes) = api_client.get_all_note_list(&db_id).await {
+                        app_state.0.notes.set(notes);
+                    }
+                    navigate.with_value(|nav| {
+                        nav(&format!("/db/{}/note/{}", db_id, note.id), Default::default());
+                    });
+                }
+                Ok(_) => {
+                    create_error.set(Some(
+                        "Create note failed: empty note id in response".to_string(),
+                    ));
+                }
+                Err(e) if e == ApiError::Unauthorized => {
+                    handle_unauthorized_response(&app_state);
+                }
+                Err(e) => create_error.set(Some(e.to_string())),
+            }
+            create_loading.set(false);
+        });
+    };
+
+    view! {
+        <div class="space-y-4">
+            <div class="space-y-1">
+                <h1 class="text-xl font-semibold">"Search"</h1>
+                <p class="text-xs text-muted-foreground">{move || format!("q = {}", q())}</p>
+            </div>
+
+            <Show
+                when=move || !q_lower().is_empty()
+                fallback=|| view! {
                     <div class="rounded-md border border-border bg-muted p-4 text-sm text-muted-foreground">
                         "Type a query in the sidebar search box and press Enter."
                     </div>
                 }
-            >
-                <div class="space-y-4">
-                    <Card>
-                        <CardHeader class="p-3">
-                            <CardTitle class="text-sm">"Databases"</CardTitle>
-                        </CardHeader>
-                        <CardContent class="p-3 pt-0">
-                            <Show
-                                when=move || !matched_dbs().is_empty()
-                                fallback=|| view! { <div class="text-sm text-muted-foreground">"No matching databases."</div> }
-                            >
-                                <div class="space-y-1">
-                                    {move || {
-                                        matched_dbs()
-                                            .into_iter()
-                                            .map(|db| {
-                                                let id = db.id.clone();
-                                                let id_href = id.clone();
-                                                let name = db.name.clone();
-                                                view! {
-                                                    <a
-                                                        href=format!("/db/{}", id_href)
-                                                        class="block rounded-md border border-border bg-background px-3 py-2 transition-colors hover:bg-surface-hover"
-                                                    >
-                                                        <div class="truncate text-sm font-medium">{name}</div>
-                                                        <div class="truncate text-xs text-muted-foreground">{id}</div>
-                                                    </a>
-                                                }
-                                            })
-                                            .collect_view()
-                                    }}
-                                </div>
+            >
+                <div class="space-y-4">
+                    <Card>
+                        <CardHeader class="p-3">
+                            <CardTitle class="text-sm">"Databases"</CardTitle>
+                        </CardHeader>
+                        <CardContent class="p-3 pt-0">
+                            <Show
+                                when=move || !matched_dbs().is_empty()
+                                fallback=|| view! { <div class="text-sm text-muted-foreground">"No matching databases."</div> }
+                            >
+                                <div class="space-y-1">
+                                    {move || {
+                                        matched_dbs()
+                                            .into_iter()
+                                            .map(|db| {
+                                                let id = db.id.clone();
+                                                let id_href = id.clone();
+                                                let name = db.name.clone();
+                                                view! {
+                                                    <a
+                                                        href=format!("/db/{}", id_href)
+                                                        class="block rounded-md border border-border bg-background px-3 py-2 transition-colors hover:bg-surface-hover"
+                                                    >
+                                                        <div class="truncate text-sm font-medium">{name}</div>
+                                                        <div class="truncate text-xs text-muted-foreground">{id}</div>
+                                                    </a>
+                                                }
+                                            })
+                                            .collect_view()
+                                    }}
+                                </div>
+                            </Show>
+                        </CardContent>
+                    </Card>
+
+                    <div class="h-px w-full bg-border" />
+
+                    <Card>
+                        <CardHeader class="p-3">
+                            <CardTitle class="text-sm">"Notes (current DB)"</CardTitle>
+                        </CardHeader>
+                        <CardContent class="p-3 pt-0">
+                            {move || {
+                                create_title().map(|title| {
+                                    let label = format!("Create note '{}'", title);
+                                    view! {
+                                        <button
+                                            type="button"
+                                            class="mb-1 flex w-full items-center gap-2 rounded-md border border-dashed border-border bg-background px-3 py-2 text-left text-sm transition-colors hover:bg-surface-hover disabled:opacity-50"
+                                            disabled=move || create_loading.get()
+                                            on:click=move |_| create_note(title.clone())
+                                        >
+                                            <span class="text-muted-foreground">"+"</span>
+                                            <span class="truncate italic">{label}</span>
+                                            <Show when=move || create_loading.get() fallback=|| ().into_view()>
+                                                <Spinner class="ml-auto size-3" />
+                                            </Show>
+                                        </button>
+                                    }
+                                })
+                            }}
+                            <Show when=move || create_error.get().is_some() fallback=|| ().into_view()>
+                                <div class="mb-1 text-xs text-destructive">
+                                    {move || create_error.get().unwrap_or_default()}
+                                </div>
+                            </Show>
+                            <Show
+                                when=move || !matched_notes().is_empty()
+                                fallback=move || view! {
+                                    <div class="text-sm text-muted-foreground">
+                                        {move || {
+                                            if app_state.0.current_database_id.get().is_none() {
+                                                "Select a database first."
+                                            } else {
+                                                "No matching notes in current DB."
+                                            }
+                                        }}
+                                    </div>
+                                }
+                            >
+                                <div class="space-y-1">
+                                    {move || {
+                                        let db_id = app_state.0.current_database_id.get().unwrap_or_default();
+                                        matched_notes()
+                                            .into_iter()
+                                            .map(|n| {
+                                                let id = n.id.clone();
+                                                let title = n.title.clone();
+                                                view! {
+                                                    <a
+                                                        href=format!("/db/{}/note/{}", db_id, id)
+                                                        class="block rounded-md border border-border bg-background px-3 py-2 transition-colors hover:bg-surface-hover"
+                                                    >
+                                                        <div class="truncate text-sm font-medium">{title}</div>
+                                                    </a>
+                                                }
+                                            })
+                                            .collect_view()
+                                    }}
+                                </div>
+                            </Show>
+                        </CardContent>
+                    </Card>
+
+                    <Card>
+                        <CardHeader class="p-3">
+                            <CardTitle class="text-sm">"Content matches"</CardTitle>
+                        </CardHeader>
+                        <CardContent class="p-3 pt-0">
+                            {move || {
+                                if app_state.0.current_database_id.get().is_none() {
+                                    return view! {
+                                        <div class="text-sm text-muted-foreground">"Select a database first."</div>
+                                    }
+                                    .into_any();
+                                }
+                                if let Some(err) = content_error.get() {
+                                    return view! { <div class="text-xs text-destructive">{err}</div> }.into_any();
+                                }
+                                let Some(notes) = content_notes() else {
+                                    let searching = content_loading.get() == content_key.get();
+                                    return view! {
+                                        <div class="flex items-center gap-2 text-sm text-muted-foreground">
+                                            <Show when=move || searching fallback=|| ().into_view()>
+                                                <Spinner class="size-3" />
+                                            </Show>
+                                            "Searching note content…"
+                                        </div>
+                                    }
+                                    .into_any();
+                                };
+                                if notes.is_empty() {
+                                    return view! {
+                                        <div class="text-sm text-muted-foreground">"No notes contain this text."</div>
+                                    }
+                                    .into_any();
+                                }
+                                let db_id = app_state.0.current_database_id.get().unwrap_or_default();
+                                view! {
+                                    <div class="space-y-1">
+                                        {notes
+                                            .into_iter()
+                                            .map(|n| {
+                                                let href = format!("/db/{}/note/{}", db_id, n.id);
+                                                view! {
+                                                    <a
+                                                        href=href
+                                                        class="block rounded-md border border-border bg-background px-3 py-2 transition-colors hover:bg-surface-hover"
+                                                    >
+                                                        <div class="truncate text-sm font-medium">{n.title}</div>
+                                                    </a>
+                                                }
+                                            })
+                                            .collect_view()}
+                                    </div>
+                                }
+                                .into_any()
+                            }}
+                        </CardContent>
+                    </Card>
+                </div>
+            </Show>
+        </div>
+    }
+}
+
+#[component]
+pub fn SettingsPage() -> impl IntoView {
+    let app_state = expect_context::<AppContext>();
+    let settings = app_state.0.settings;
+    let api_client = app_state.0.api_client;
+    let update_settings = {
+        let app_state = app_state.clone();
+        Callback::new(move |next: Settings| app_state.0.set_settings(next))
+    };
+    let templates = app_state.0.templates;
+    let save_templates = {
+        let app_state = app_state.clone();
+        Callback::new(move |next: Vec<NoteTemplate>| app_state.0.set_templates(next))
+    };
+
+    let current_theme = app_state.0.theme;
+    let theme_button = move |theme: Theme, label: &'static str| {
+        let app_state = app_state.clone();
+        let variant = move || {
+            if current_theme.get() == theme {
+                ButtonVariant::Accent
+            } else {
+                ButtonVariant::Outline
+            }
+        };
+        view! {
+            <Button
+                variant=Signal::derive(variant)
+                size=ButtonSize::Sm
+                attr:aria-pressed=move || (current_theme.get() == theme).to_string()
+                on:click=move |_| app_state.0.set_theme(theme)
+            >
+                {label}
+            </Button>
+        }
+    };
+
+    let env_api_url = EnvConfig::from_env().api_url;
+    let api_url_value: RwSignal<String> =
+        RwSignal::new(settings.get_untracked().api_url.unwrap_or_default());
+    let autosave_value: RwSignal<String> =
+        RwSignal::new(settings.get_untracked().autosave_ms.to_string());
+    let connection_testing: RwSignal<bool> = RwSignal::new(false);
+    // Ok(message) / Err(message) from the last "Test connection".
+    let connection_result: RwSignal<Option<Result<String, String>>> = RwSignal::new(None);
+
+    let env_api_url_sv = StoredValue::new(env_api_url.clone());
+    let test_connection = move |_| {
+        if connection_testing.get_untracked() {
+            return;
+        }
+        let url = Settings {
+            api_url: Some(api_url_value.get_untracked()),
+            ..Settings::default()
+        }
+        .api_url_override()
+        .unwrap_or_else(|| env_api_url_sv.get_value());
+
+        let mut client = crate::api::ApiClient::new(url);
+        client.token = api_client.get_untracked().token;
+
+        connection_testing.set(true);
+        connection_result.set(None);
+        spawn_local(async move {
+            let result = match client.get_database_list().await {
+                Ok(dbs) => Ok(format!("Connected ({} databases).", dbs.len())),
+                // The server answered; the token just isn't valid there.
+                Err(ApiError::Unauthorized) => {
+                    Ok("Server reachable. You will need to log in again.".to_string())
+                }
+                Err(e) => Err(e.to_string()),
+            };
+            connection_result.set(Some(result));
+            connection_testing.set(false);
+        });
+    };
+
+    let save_api_url = move |api_url: Option<String>| {
+        let mut next = settings.get_untracked();
+        next.api_url = api_url.filter(|u| !u.trim().is_empty());
+        if update_settings.run(next) {
+            let _ = window().location().set_href("/login");
+        }
+    };
+
+    let apply_autosave = move |_| {
+        let Ok(ms) = autosave_value.get_untracked().trim().parse::<u32>() else {
+            autosave_value.set(settings.get_untracked().autosave_ms.to_string());
+            return;
+        };
+        let ms = ms.clamp(Settings::MIN_AUTOSAVE_MS, Settings::MAX_AUTOSAVE_MS);
+        autosave_value.set(ms.to_string());
+        let mut next = settings.get_untracked();
+        next.autosave_ms = ms;
+        update_settings.run(next);
+    };
+
+    // Template editor: `None` while closed, `Some(None)` for a new template.
+    let template_editing: RwSignal<Option<Option<usize>>> = RwSignal::new(None);
+    let template_name: RwSignal<String> = RwSignal::new(String::new());
+    let template_outline: RwSignal<String> = RwSignal::new(String::new());
+    let template_error: RwSignal<Option<String>> = RwSignal::new(None);
+
+    let edit_template = move |index: Option<usize>| {
+        let existing = index.and_then(|i| templates.with_untracked(|ts| ts.get(i).cloned()));
+        template_name.set(existing.as_ref().map(|t| t.name.clone()).unwrap_or_default());
+        template_outline.set(existing.as_ref().map(template_outline_text).unwrap_or_default());
+        template_error.set(None);
+        template_editing.set(Some(index));
+    };
+
+    let save_template = move |_| {
+        let Some(index) = template_editing.get_untracked() else {
+            return;
+        };
+        let name = template_name.get_untracked().trim().to_string();
+        if name.is_empty() {
+            template_error.set(Some("Template name is required.".to_string()));
+            return;
+        }
+        let navs = template_navs_from_outline(&template_outline.get_untracked());
+        if navs.is_empty() {
+            template_error.set(Some("Add at least one block.".to_string()));
+            return;
+        }
+
+        let mut next = templates.get_untracked();
+        let template = NoteTemplate { name, navs };
+        match index.filter(|&i| i < next.len()) {
+            Some(i) => next[i] = template,
+            None => next.push(template),
+        }
+        save_templates.run(next);
+        template_editing.set(None);
+    };
+
+    let delete_template = move |index: usize| {
+        let mut next = templates.get_untracked();
+        if index < next.len() {
+            next.remove(index);
+            save_templates.run(next);
+        }
+        if template_editing.get_untracked() == Some(Some(index)) {
+            template_editing.set(None);
+        }
+    };
+
+    let title_format_button = move |format: NoteTitleFormat, label: &'static str| {
+        let variant = move || {
+            if settings.with(|s| s.new_note_title) == format {
+                ButtonVariant::Accent
+            } else {
+                ButtonVariant::Outline
+            }
+        };
+        view! {
+            <Button
+                variant=Signal::derive(variant)
+                size=ButtonSize::Sm
+                attr:aria-pressed=move || (settings.with(|s| s.new_note_title) == format).to_string()
+                on:click=move |_| {
+                    let mut next = settings.get_untracked();
+                    next.new_note_title = format;
+                    update_settings.run(next);
+                }
+            >
+                {label}
+            </Button>
+        }
+    };
+
+    let enter_button = move |as_child: bool, label: &'static str| {
+        let variant = move || {
+            if settings.with(|s| s.enter_creates_child) == as_child {
+                ButtonVariant::Accent
+            } else {
+                ButtonVariant::Outline
+            }
+        };
+        view! {
+            <Button
+                variant=Signal::derive(variant)
+                size=ButtonSize::Sm
+                attr:aria-pressed=move || (settings.with(|s| s.enter_creates_child) == as_child).to_string()
+                on:click=move |_| {
+                    let mut next = settings.get_untracked();
+                    next.enter_creates_child = as_child;
+                    update_settings.run(next);
+                }
+            >
+                {label}
+            </Button>
+        }
+    };
+
+    view! {
+        <div class="space-y-3">
+            <div class="space-y-1">
+                <h1 class="text-xl font-semibold">"Settings"</h1>
+            </div>
+            <Card>
+                <CardHeader>
+                    <CardTitle class="text-sm">"Server"</CardTitle>
+                    <CardDescription class="text-xs">
+                        "Override the API base URL for this browser. Changing it signs you out."
+                    </CardDescription>
+                </CardHeader>
+                <CardContent>
+                    <div class="flex flex-col gap-2">
+                        <Label html_for="api-url" class="text-xs">"API URL"</Label>
+                        <Input
+                            id="api-url"
+                            r#type="url"
+                            placeholder=env_api_url
+                            bind_value=api_url_value
+                            class="h-8 text-sm"
+                        />
+                        <div class="truncate text-xs text-muted-foreground">
+                            "In use: "
+                            <span class="font-mono">
+                                {move || {
+                                    settings
+                                        .with(|s| s.api_url_override())
+                                        .unwrap_or_else(|| env_api_url_sv.get_value())
+                                }}
+                            </span>
+                        </div>
+                        <div class="flex items-center gap-2">
+                            <Button
+                                variant=ButtonVariant::Outline
+                                size=ButtonSize::Sm
+                                attr:disabled=move || connection_testing.get()
+                                on:click=test_connection
+                            >
+                                {move || if connection_testing.get() { "Testing…" } else { "Test connection" }}
+                            </Button>
+                            <Button
+                                size=ButtonSize::Sm
+                                on:click=move |_| save_api_url(Some(api_url_value.get_untracked()))
+                            >
+                                "Save"
+                            </Button>
+                            <Button
+                                variant=ButtonVariant::Ghost
+                                size=ButtonSize::Sm
+                                on:click=move |_| {
+                                    api_url_value.set(String::new());
+                                    save_api_url(None);
+                                }
+                            >
+                                "Use default"
+                            </Button>
+                        </div>
+                        {move || connection_result.get().map(|r| match r {
+                            Ok(msg) => view! { <div class="text-xs text-muted-foreground">{msg}</div> }.into_any(),
+                            Err(e) => view! { <div class="text-xs text-destructive">{format!("Connection failed: {e}")}</div> }.into_any(),
+                        })}
+                    </div>
+                </CardContent>
+            </Card>
+            <Card>
+                <CardHeader>
+                    <CardTitle class="text-sm">"Editor"</CardTitle>
+                    <CardDescription class="text-xs">"Outline editing preferences."</CardDescription>
+                </CardHeader>
+                <CardContent>
+                    <div class="flex flex-col gap-4">
+                        <div class="flex flex-col gap-1.5">
+                            <Label html_for="autosave-ms" class="text-xs">"Autosave delay (ms)"</Label>
+                            <div class="flex items-center gap-2">
+                                <Input
+                                    id="autosave-ms"
+                                    r#type="number"
+                                    bind_value=autosave_value
+                                    class="h-8 w-32 text-sm"
+                                />
+                                <Button variant=ButtonVariant::Outline size=ButtonSize::Sm on:click=apply_autosave>
+                                    "Apply"
+                                </Button>
+                            </div>
+                        </div>
+                        <div class="flex flex-col gap-1.5">
+                            <div class="text-xs font-medium">"New note title"</div>
+                            <div class="flex items-center gap-2">
+                                {title_format_button(NoteTitleFormat::Compact, "20240131")}
+                                {title_format_button(NoteTitleFormat::IsoDate, "2024-01-31")}
+                                {title_format_button(NoteTitleFormat::Untitled, "Untitled")}
+                            </div>
+                        </div>
+                        <div class="flex flex-col gap-1.5">
+                            <div class="text-xs font-medium">"Enter creates"</div>
+                            <div class="flex items-center gap-2">
+                                {enter_button(false, "Sibling")}
+                                {enter_button(true, "Child")}
+                            </div>
+                        </div>
+                    </div>
+                </CardContent>
+            </Card>
+            <Card>
+                <CardHeader>
+                    <CardTitle class="text-sm">"Appearance"</CardTitle>
+                    <CardDescription class="text-xs">"Choose a color theme. System follows your OS setting."</CardDescription>
+                </CardHeader>
+                <CardContent>
+                    <div class="flex items-center gap-2">
+                        {theme_button(Theme::Light, "Light")}
+                        {theme_button(Theme::Dark, "Dark")}
+                        {theme_button(Theme::System, "System")}
+                    </div>
+                </CardContent>
+            </Card>
+            <Card>
+                <CardHeader>
+                    <CardTitle class="text-sm">"Templates"</CardTitle>
+                    <CardDescription class="text-xs">
+                        "Blocks to start a new note with (pick one from the arrow next to \"New\"), or to insert by typing /template in a block. {{date}} and {{time}} are filled in when a template is used."
+                    </CardDescription>
+                </CardHeader>
+                <CardContent>
+                    <div class="flex flex-col gap-3">
+                        <Show
+                            when=move || !templates.with(|ts| ts.is_empty())
+                            fallback=|| view! { <div class="text-xs text-muted-foreground">"No templates yet."</div> }
+                        >
+                            <div class="flex flex-col gap-1">
+                                {move || {
+                                    templates
+                                        .get()
+                                        .into_iter()
+                                        .enumerate()
+                                        .map(|(i, t)| {
+                                            let blocks = t.navs.len();
+                                            view! {
+                                                <div class="flex items-center gap-2 text-sm">
+                                                    <span class="min-w-0 flex-1 truncate">{t.name}</span>
+                                                    <span class="text-xs text-muted-foreground">
+                                                        {format!("{blocks} block{}", if blocks == 1 { "" } else { "s" })}
+                                                    </span>
+                                                    <Button
+                                                        variant=ButtonVariant::Ghost
+                                                        size=ButtonSize::Sm
+                                                        on:click=move |_| edit_template(Some(i))
+                                                    >
+                                                        "Edit"
+                                                    </Button>
+                                                    <Button
+                                                        variant=ButtonVariant::Ghost
+                                                        size=ButtonSize::Sm
+                                                        on:click=move |_| delete_template(i)
+                                                    >
+                                                        "Delete"
+                                                    </Button>
+                                                </div>
+                                            }
+                                        })
+                                        .collect_view()
+                                }}
+                            </div>
+                        </Show>
+                        <Show
+                            when=move || template_editing.get().is_some()
+                            fallback=move || view! {
+                                <div>
+                                    <Button variant=ButtonVariant::Outline size=ButtonSize::Sm on:click=move |_| edit_template(None)>
+                                        "Add template"
+                                    </Button>
+                                </div>
+                            }
+                        >
+                            <div class="flex flex-col gap-2">
+                                <Input
+                                    bind_value=template_name
+                                    placeholder="Template name"
+                                    class="h-8 text-sm"
+                                />
+                                <textarea
+                                    class="min-h-[120px] w-full rounded-md border border-input bg-transparent px-3 py-2 font-mono text-sm shadow-xs outline-none focus-visible:border-ring focus-visible:ring-2 focus-visible:ring-ring/50"
+                                    placeholder="One block per line; indent two spaces to nest. {{date}} and {{time}} are filled in."
+                                    prop:value=move || template_outline.get()
+                                    on:input=move |ev| template_outline.set(event_target_value(&ev))
+                                ></textarea>
+                                {move || template_error.get().map(|e| view! { <div class="text-xs text-destructive">{e}</div> })}
+                                <div class="flex items-center gap-2">
+                                    <Button size=ButtonSize::Sm on:click=save_template>
+                                        "Save"
+                                    </Button>
+                                    <Button
+                                        variant=ButtonVariant::Ghost
+                                        size=ButtonSize::Sm
+                                        on:click=move |_| template_editing.set(None)
+                                    >
+                                        "Cancel"
+                                    </Button>
+                                </div>
+                            </div>
+                        </Show>
+                    </div>
+                </CardContent>
+            </Card>
+        </div>
+    }
+}
+
+#[component]
+pub fn UnreferencedPages() -> impl IntoView {
+    let app_state = expect_context::<AppContext>();
+    let app_state_sv = StoredValue::new(app_state.clone());
+    let params = leptos_router::hooks::use_params::<UnreferencedRouteParams>();
+
+    let db_id = move || params.get().ok().and_then(|p| p.db_id).unwrap_or_default();
+
+    let loading: RwSignal<bool> = RwSignal::new(false);
+    let error: RwSignal<Option<String>> = RwSignal::new(None);
+    let loaded_db_id: RwSignal<Option<String>> = RwSignal::new(None);
+
+    // Use stable local caches so the view doesn't depend on global notes state.
+    let notes: RwSignal<Vec<Note>> = RwSignal::new(vec![]);
+    let navs: RwSignal<Vec<Nav>> = RwSignal::new(vec![]);
+
+    // Load notes + navs for this DB.
+    Effect::new(move |_| {
+        let db = db_id();
+        if db.trim().is_empty() {
+            notes.set(vec![]);
+            navs.set(vec![]);
+            return;
+        }
+
+        // Avoid duplicate loads for the same db.
+        if loaded_db_id.get_untracked().as_deref() == Some(db.as_str()) && !loading.get_untracked()
+        {
+            return;
+        }
+        loaded_db_id.set(Some(db.clone()));
+
+        // Keep global selected DB in sync (untracked to avoid re-fetch when other pages update it).
+        if app_state.0.current_database_id.get_untracked() != Some(db.clone()) {
+            app_state.0.current_database_id.set(Some(db.clone()));
+            if let Some(storage) = web_sys::window().and_then(|w| w.local_storage().ok().flatten())
+            {
+                let _ = storage.set_item(CURRENT_DB_KEY, &db);
+            }
+        }
+
+        loading.set(true);
+        error.set(None);
+
+        let api_client = app_state.0.api_client.get_untracked();
+        spawn_local(async move {
+            let notes_res = api_client.get_all_note_list(&db).await;
+            let navs_res = api_client.get_all_navs(&db).await;
+
+            match (notes_res, navs_res) {
+                (Ok(ns), Ok(vs)) => {
+                    notes.set(ns);
+                    navs.set(vs);
+                }
+                (Err(e), _) | (_, Err(e)) => {
+                    if e == ApiError::Unauthorized {
+                        app_state_sv.with_value(handle_unauthorized_response);
+                    } else {
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            }
+
+            loading.set(false);
+        });
+    });
+
+    let unreferenced = move || {
+        let ns = notes.get();
+        let vs = navs.get();
+
+        let mut referenced_titles: std::collections::BTreeSet<String> =
+            std::collections::BTreeSet::new();
+        for nav in vs.into_iter() {
+            if nav.is_delete {
+                continue;
+            }
+            for t in extract_wiki_links(&nav.content) {
+                referenced_titles.insert(normalize_roam_page_title(&t));
+            }
+        }
+
+        let mut out: Vec<Note> = ns
+            .into_iter()
+            .filter(|n| {
+                let t = normalize_roam_page_title(&n.title);
+                !referenced_titles.contains(&t)
+            })
+            .collect();
+
+        out.sort_by(|a, b| a.title.cmp(&b.title));
+        out
+    };
+
+    view! {
+        <div class="space-y-4">
+            <div class="space-y-1">
+                <h1 class="text-xl font-semibold">"Unreferenced Pages"</h1>
+                <p class="text-xs text-muted-foreground">{move || format!("db_id = {}", db_id())}</p>
+            </div>
+
+            <Show when=move || !loading.get() fallback=move || view! {
+                <div class="flex items-center gap-2 text-sm text-muted-foreground">
+                    <Spinner />
+                    "Loading…"
+                </div>
+            }>
+                <Show when=move || error.get().is_none() fallback=move || view! {
+                    <Alert class="border-destructive/30">
+                        <AlertDescription class="text-destructive text-xs">
+                            {move || error.get().unwrap_or_default()}
+                        </AlertDescription>
+                    </Alert>
+                }>
+                    <Show when=move || !unreferenced().is_empty() fallback=|| view! {
+                        <div class="rounded-md border border-border bg-muted p-4 text-sm text-muted-foreground">
+                            "No unreferenced pages."
+                        </div>
+                    }>
+                        <div class="space-y-1">
+                            {move || {
+                                let db = db_id();
+                                unreferenced()
+                                    .into_iter()
+                                    .map(|n| {
+                                        let href = format!("/db/{}/note/{}", db, n.id);
+                                        view! {
+                                            <a
+                                                href=href
+                                                class="block rounded-md border border-border bg-background px-3 py-2 transition-colors hover:bg-surface-hover"
+                                            >
+                                                <div class="truncate text-sm font-medium">{n.title}</div>
+                                            </a>
+                                        }
+                                    })
+                                    .collect_view()
+                            }}
+                        </div>
+                    </Show>
+                </Show>
+            </Show>
+        </div>
+    }
+}
+
+/// Notes of a database carrying `#tag` (from `AppState::tags_index`).
+#[component]
+pub fn TagPage() -> impl IntoView {
+    let app_state = expect_context::<AppContext>();
+    let app_state_sv = StoredValue::new(app_state.clone());
+    let params = leptos_router::hooks::use_params::<TagRouteParams>();
+
+    let db_id = move || params.get().ok().and_then(|p| p.db_id).unwrap_or_default();
+    let tag = move || {
+        let raw = params.get().ok().and_then(|p| p.tag).unwrap_or_default();
+        urlencoding::decode(&raw)
+            .map(|t| t.into_owned())
+            .unwrap_or(raw)
+            .to_lowercase()
+    };
+
+    let loading: RwSignal<bool> = RwSignal::new(false);
+    let error: RwSignal<Option<String>> = RwSignal::new(None);
+    let loaded_db_id: RwSignal<Option<String>> = RwSignal::new(None);
+    // All notes of the DB (the sidebar list may be paginated).
+    let notes: RwSignal<Vec<Note>> = RwSignal::new(vec![]);
+
+    Effect::new(move |_| {
+        let db = db_id();
+        if db.trim().is_empty() {
+            notes.set(vec![]);
+            return;
+        }
+
+        if loaded_db_id.get_untracked().as_deref() == Some(db.as_str()) && !loading.get_untracked()
+        {
+            return;
+        }
+        loaded_db_id.set(Some(db.clone()));
+
+        // The sidebar builds `tags_index` for the current DB.
+        if app_state.0.current_database_id.get_untracked() != Some(db.clone()) {
+            app_state.0.current_database_id.set(Some(db.clone()));
+            if let Some(storage) = web_sys::window().and_then(|w| w.local_storage().ok().flatten())
+            {
+                let _ = storage.set_item(CURRENT_DB_KEY, &db);
+            }
+        }
+
+        loading.set(true);
+        error.set(None);
+
+        let api_client = app_state.0.api_client.get_untracked();
+        spawn_local(async move {
+            match api_client.get_all_note_list(&db).await {
+                Ok(ns) => notes.set(ns),
+                Err(e) => {
+                    if e == ApiError::Unauthorized {
+                        app_state_sv.with_value(handle_unauthorized_response);
+                    } else {
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            }
+            loading.set(false);
+        });
+    });
+
+    let tagged = move || {
+        let ids = app_state
+            .0
+            .tags_index
+            .with(|m| m.get(&tag()).cloned())
+            .unwrap_or_default()
+            .into_iter()
+            .collect::<HashSet<_>>();
+        let mut out = notes_with_ids(&notes.get(), &ids);
+        out.sort_by_key(|n| n.title.to_lowercase());
+        out
+    };
+
+    view! {
+        <div class="space-y-4">
+            <div class="space-y-1">
+                <h1 class="text-xl font-semibold">{move || format!("#{}", tag())}</h1>
+                <p class="text-xs text-muted-foreground">"Notes with a block carrying this tag."</p>
+            </div>
+
+            <Show when=move || !loading.get() fallback=move || view! {
+                <div class="flex items-center gap-2 text-sm text-muted-foreground">
+                    <Spinner />
+                    "Loading…"
+                </div>
+            }>
+                <Show when=move || error.get().is_none() fallback=move || view! {
+                    <Alert class="border-destructive/30">
+                        <AlertDescription class="text-destructive text-xs">
+                            {move || error.get().unwrap_or_default()}
+                        </AlertDescription>
+                    </Alert>
+                }>
+                    <Show when=move || !tagged().is_empty() fallback=|| view! {
+                        <div class="rounded-md border border-border bg-muted p-4 text-sm text-muted-foreground">
+                            "No notes with this tag."
+                        </div>
+                    }>
+                        <div class="space-y-1">
+                            {move || {
+                                let db = db_id();
+                                tagged()
+                                    .into_iter()
+                                    .map(|n| {
+                                        let href = format!("/db/{}/note/{}", db, n.id);
+                                        view! {
+                                            <a
+                                                href=href
+                                                class="block rounded-md border border-border bg-background px-3 py-2 transition-colors hover:bg-surface-hover"
+                                            >
+                                                <div class="truncate text-sm font-medium">{n.title}</div>
+                                            </a>
+                                        }
+                                    })
+                                    .collect_view()
+                            }}
+                        </div>
+                    </Show>
+                </Show>
+            </Show>
+        </div>
+    }
+}
+
+/// Daily notes revealed per "load older days" step.
+const DAILY_NOTES_PAGE_SIZE: usize = 7;
+/// Distance from the bottom of the page at which older days start loading.
+const DAILY_NOTES_SCROLL_MARGIN_PX: f64 = 400.0;
+
+/// Recent daily notes (`YYYYMMDD` titles) of a database, newest first, each in its own
+/// embedded outline editor. Today's note is created on the first keystroke, not on visit.
+#[component]
+pub fn DailyNotesPage() -> impl IntoView {
+    let app_state = expect_context::<AppContext>();
+    let app_state_sv = StoredValue::new(app_state.clone());
+    let sync_sv = StoredValue::new(expect_context::<crate::state::NoteSyncController>());
+    let params = leptos_router::hooks::use_params::<DbRouteParams>();
+
+    let db_id = move || params.get().ok().and_then(|p| p.db_id).unwrap_or_default();
+    let db_id_untracked = move || {
+        params
+            .get_untracked()
+            .ok()
+            .and_then(|p| p.db_id)
+            .unwrap_or_default()
+    };
+
+    let loading: RwSignal<bool> = RwSignal::new(false);
+    let error: RwSignal<Option<String>> = RwSignal::new(None);
+    let loaded_db_id: RwSignal<Option<String>> = RwSignal::new(None);
+    // All notes of the DB (the sidebar list may be paginated).
+    let notes: RwSignal<Vec<Note>> = RwSignal::new(vec![]);
+    let shown: RwSignal<usize> = RwSignal::new(DAILY_NOTES_PAGE_SIZE);
+
+    let today_title = StoredValue::new(today_yyyymmdd_local());
+    let today_value: RwSignal<String> = RwSignal::new(String::new());
+    let today_creating: RwSignal<bool> = RwSignal::new(false);
+    let today_error: RwSignal<Option<String>> = RwSignal::new(None);
+
+    Effect::new(move |_| {
+        let db = db_id();
+        if db.trim().is_empty() {
+            notes.set(vec![]);
+            return;
+        }
+
+        if loaded_db_id.get_untracked().as_deref() == Some(db.as_str()) && !loading.get_untracked()
+        {
+            return;
+        }
+        loaded_db_id.set(Some(db.clone()));
+        shown.set(DAILY_NOTES_PAGE_SIZE);
+
+        // The editors resolve drafts and snapshots against the current DB.
+        if app_state.0.current_database_id.get_untracked() != Some(db.clone()) {
+            app_state.0.current_database_id.set(Some(db.clone()));
+            if let Some(storage) = web_sys::window().and_then(|w| w.local_storage().ok().flatten())
+            {
+                let _ = storage.set_item(CURRENT_DB_KEY, &db);
+            }
+        }
+
+        loading.set(true);
+        error.set(None);
+
+        let api_client = app_state.0.api_client.get_untracked();
+        spawn_local(async move {
+            match api_client.get_all_note_list(&db).await {
+                Ok(ns) => notes.set(ns),
+                Err(e) => {
+                    if e == ApiError::Unauthorized {
+                        app_state_sv.with_value(handle_unauthorized_response);
+                    } else {
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            }
+            loading.set(false);
+        });
+    });
+
+    let days = Memo::new(move |_| notes.with(|ns| daily_notes(ns, &db_id())));
+    let has_today = move || {
+        days.with(|xs| {
+            today_title.with_value(|t| xs.iter().any(|n| n.title.trim() == t.as_str()))
+        })
+    };
+    let visible_days = move || {
+        days.with(|xs| xs.iter().take(shown.get()).cloned().collect::<Vec<_>>())
+    };
+    let has_older = move || days.with(|xs| xs.len()) > shown.get();
+    let load_older = move || {
+        if let (Some(total), Some(n)) = (
+            days.try_with_untracked(|xs| xs.len()),
+            shown.try_get_untracked(),
+        ) {
+            if n < total {
+                shown.set(n + DAILY_NOTES_PAGE_SIZE);
+            }
+        }
+    };
+
+    // Infinite scroll: reveal older days once the bottom of the page comes into view.
+    let _scroll_handle = window_event_listener(ev::scroll, move |_ev: web_sys::Event| {
+        let w = window();
+        let viewport = w.inner_height().ok().and_then(|h| h.as_f64()).unwrap_or(0.0);
+        let bottom = w.scroll_y().unwrap_or(0.0) + viewport;
+        let height = document()
+            .document_element()
+            .map(|el| el.scroll_height() as f64)
+            .unwrap_or(0.0);
+        if bottom >= height - DAILY_NOTES_SCROLL_MARGIN_PX {
+            load_older();
+        }
+    });
+
+    // First keystroke in today's empty outline: create the note, seed its first block with
+    // what was typed so far, then hand over to a regular editor.
+    let create_today = move || {
+        if today_creating.get_untracked() {
+            return;
+        }
+        let db = db_id_untracked();
+        if db.trim().is_empty() {
+            return;
+        }
+
+        today_creating.set(true);
+        today_error.set(None);
+
+        let api_client = app_state_sv.with_value(|st| st.0.api_client.get_untracked());
+        let title = today_title.get_value();
+        spawn_local(async move {
+            // Another tab may have created it meanwhile.
+            let existing = match api_client.get_all_note_list(&db).await {
+                Ok(ns) => find_note_by_title(&ns, &db, &title).cloned(),
+                Err(ApiError::Unauthorized) => {
+                    app_state_sv.with_value(handle_unauthorized_response);
+                    today_creating.set(false);
+                    return;
+                }
+                Err(_) => None,
+            };
+            let note = match existing {
+                Some(n) => n,
+                None => match api_client.create_note(&db, &title).await {
+                    Ok(n) => n,
+                    Err(e) => {
+                        today_creating.set(false);
+                        today_error.set(Some(e.to_string()));
+                        return;
+                    }
+                },
+            };
+
+            let mut base_navs = api_client.get_note_navs(&note.id).await.unwrap_or_default();
+            let initial_content = today_value.get_untracked();
+            let _ = sync_sv.with_value(|s| {
+                s.ensure_note_has_start_node_local(
+                    &db,
+                    &note.id,
+                    Some(title.clone()),
+                    &mut base_navs,
+                    &initial_content,
+                )
+            });
+
+            // The editor mounts once the note is listed, and picks up the seeded snapshot.
+            app_state_sv.with_value(|st| {
+                st.0.notes.update(|xs| {
+                    if !xs.iter().any(|x| x.id == note.id) {
+                        xs.insert(0, note.clone());
+                    }
+                })
+            });
+            notes.update(|xs| {
+                if !xs.iter().any(|x| x.id == note.id) {
+                    xs.push(note);
+                }
+            });
+            today_value.set(String::new());
+            today_creating.set(false);
+        });
+    };
+
+    view! {
+        <div class="space-y-6">
+            <div class="space-y-1">
+                <h1 class="text-xl font-semibold">"Daily notes"</h1>
+                <p class="text-xs text-muted-foreground">"Recent days, newest first."</p>
+            </div>
+
+            <Show when=move || !loading.get() fallback=move || view! {
+                <div class="flex items-center gap-2 text-sm text-muted-foreground">
+                    <Spinner />
+                    "Loading…"
+                </div>
+            }>
+                <Show when=move || error.get().is_none() fallback=move || view! {
+                    <Alert class="border-destructive/30">
+                        <AlertDescription class="text-destructive text-xs">
+                            {move || error.get().unwrap_or_default()}
+                        </AlertDescription>
+                    </Alert>
+                }>
+                    <Show when=move || !has_today() fallback=|| ().into_view()>
+                        <section class="space-y-2" data-daily-note=move || today_title.get_value()>
+                            <div class="flex items-center gap-2">
+                                <h2 class="text-sm font-semibold">{move || today_title.get_value()}</h2>
+                                <Show when=move || today_creating.get() fallback=|| ().into_view()>
+                                    <Spinner class="size-3" />
+                                </Show>
+                            </div>
+                            <div class="flex items-center gap-2">
+                                <div class="text-muted-foreground">"•"</div>
+                                <input
+                                    class="h-7 w-full min-w-0 flex-1 rounded-md border border-input bg-transparent px-3 py-1 text-sm shadow-xs outline-none focus-visible:border-ring focus-visible:ring-2 focus-visible:ring-ring/50"
+                                    placeholder="Start typing…"
+                                    prop:value=move || today_value.get()
+                                    on:input=move |ev: web_sys::Event| {
+                                        if let Some(t) = ev
+                                            .target()
+                                            .and_then(|t| t.dyn_into::<web_sys::HtmlInputElement>().ok())
+                                        {
+                                            let v = t.value();
+                                            today_value.set(v.clone());
+                                            if !v.is_empty() {
+                                                create_today();
+                                            }
+                                        }
+                                    }
+                                />
+                            </div>
+                            <Show when=move || today_error.get().is_some() fallback=|| ().into_view()>
+                                <Alert class="border-destructive/30">
+                                    <AlertDescription class="text-destructive text-xs">
+                                        {move || today_error.get().unwrap_or_default()}
+                                    </AlertDescription>
+                                </Alert>
                             </Show>
-                        </CardContent>
-                    </Card>
-
-                    <div class="h-px w-full bg-border" />
+                        </section>
+                    </Show>
 
-                    <Card>
-                        <CardHeader class="p-3">
-                            <CardTitle class="text-sm">"Notes (current DB)"</CardTitle>
-                        </CardHeader>
-                        <CardContent class="p-3 pt-0">
-                            <Show
-                                when=move || !matched_notes().is_empty()
-                                fallback=move || view! {
-                                    <div class="text-sm text-muted-foreground">
-                                        {move || {
-                                            if app_state.0.current_database_id.get().is_none() {
-                                                "Select a database first."
-                                            } else {
-                                                "No matching notes in current DB."
-                                            }
-                                        }}
-                                    </div>
+                    <Show when=move || has_today() || !days.with(|xs| xs.is_empty()) fallback=|| view! {
+                        <div class="rounded-md border border-border bg-muted p-4 text-sm text-muted-foreground">
+                            "No earlier daily notes."
+                        </div>
+                    }>
+                        <For
+                            each=visible_days
+                            key=|n| n.id.clone()
+                            children=move |n: Note| {
+                                let href = format!("/db/{}/note/{}", n.database_id, n.id);
+                                let note_id = n.id.clone();
+                                let navs: RwSignal<Vec<Nav>> = RwSignal::new(vec![]);
+                                let focused_nav_id: RwSignal<Option<String>> = RwSignal::new(None);
+                                view! {
+                                    <section class="space-y-1" data-daily-note=n.title.clone()>
+                                        <a href=href class="text-sm font-semibold hover:underline">
+                                            {n.title}
+                                        </a>
+                                        <OutlineEditor
+                                            note_id=move || note_id.clone()
+                                            focused_nav_id=focused_nav_id
+                                            navs=navs
+                                            embedded=true
+                                        />
+                                    </section>
                                 }
+                            }
+                        />
+                    </Show>
+
+                    <Show when=has_older fallback=|| ().into_view()>
+                        <div class="flex justify-center">
+                            <Button
+                                variant=ButtonVariant::Ghost
+                                size=ButtonSize::Sm
+                                on:click=move |_| load_older()
                             >
-                                <div class="space-y-1">
-                                    {move || {
-                                        let db_id = app_state.0.current_database_id.get().unwrap_or_default();
-                                        matched_notes()
-                                            .into_iter()
-                                            .map(|n| {
-                                                let id = n.id.clone();
-                                                let title = n.title.clone();
-                                                view! {
-                                                    <a
-                                                        href=format!("/db/{}/note/{}", db_id, id)
-                                                        class="block rounded-md border border-border bg-background px-3 py-2 transition-colors hover:bg-surface-hover"
-                                                    >
-                                                        <div class="truncate text-sm font-medium">{title}</div>
-                                                    </a>
-                                                }
-                                            })
-                                            .collect_view()
-                                    }}
-                                </div>
-                            </Show>
-                        </CardContent>
-                    </Card>
-                </div>
+                                "Load older days"
+                            </Button>
+                        </div>
+                    </Show>
+                </Show>
             </Show>
         </div>
     }
 }
 
-#[component]
-pub fn SettingsPage() -> impl IntoView {
-    view! {
-        <div class="space-y-3">
-            <div class="space-y-1">
-                <h1 class="text-xl font-semibold">"Settings"</h1>
-                <p class="text-xs text-muted-foreground">"Phase 3 placeholder"</p>
-            </div>
-            <div class="rounded-md border border-border bg-muted p-4 text-sm text-muted-foreground">
-                "Appearance/editor/account settings will be implemented in later phases."
-            </div>
-        </div>
-    }
+/// A block restore waiting for the user's choice: its parent is deleted too.
+#[derive(Clone)]
+struct PendingRestore {
+    note_id: String,
+    nav_id: String,
+    deleted_ancestors: usize,
 }
 
+/// Per-database Trash: restore soft-deleted notes, and deleted blocks of live notes.
 #[component]
-pub fn UnreferencedPages() -> impl IntoView {
+pub fn TrashPage() -> impl IntoView {
     let app_state = expect_context::<AppContext>();
-    let params = leptos_router::hooks::use_params::<UnreferencedRouteParams>();
+    let app_state_sv = StoredValue::new(app_state.clone());
+    let params = leptos_router::hooks::use_params::<DbRouteParams>();
 
     let db_id = move || params.get().ok().and_then(|p| p.db_id).unwrap_or_default();
+    let db_id_untracked = move || {
+        params
+            .get_untracked()
+            .ok()
+            .and_then(|p| p.db_id)
+            .unwrap_or_default()
+    };
 
     let loading: RwSignal<bool> = RwSignal::new(false);
     let error: RwSignal<Option<String>> = RwSignal::new(None);
-    let loaded_db_id: RwSignal<Option<String>> = RwSignal::new(None);
-
-    // Use stable local caches so the view doesn't depend on global notes state.
-    let notes: RwSignal<Vec<Note>> = RwSignal::new(vec![]);
+    let deleted_notes: RwSignal<Vec<Note>> = RwSignal::new(vec![]);
+    let live_notes: RwSignal<Vec<Note>> = RwSignal::new(vec![]);
+    // Every nav in the database, deleted ones included.
     let navs: RwSignal<Vec<Nav>> = RwSignal::new(vec![]);
+    let opened_note: RwSignal<Option<String>> = RwSignal::new(None);
+    let pending_restore: RwSignal<Option<PendingRestore>> = RwSignal::new(None);
+    let restoring: RwSignal<bool> = RwSignal::new(false);
+
+    let on_error = move |e: ApiError| {
+        if e == ApiError::Unauthorized {
+            app_state_sv.with_value(handle_unauthorized_response);
+        } else {
+            error.set(Some(e.to_string()));
+        }
+    };
 
-    // Load notes + navs for this DB.
     Effect::new(move |_| {
         let db = db_id();
+        opened_note.set(None);
+        pending_restore.set(None);
         if db.trim().is_empty() {
-            notes.set(vec![]);
-            navs.set(vec![]);
-            return;
-        }
-
-        // Avoid duplicate loads for the same db.
-        if loaded_db_id.get_untracked().as_deref() == Some(db.as_str()) && !loading.get_untracked()
-        {
             return;
         }
-        loaded_db_id.set(Some(db.clone()));
-
-        // Keep global selected DB in sync (untracked to avoid re-fetch when other pages update it).
-        if app_state.0.current_database_id.get_untracked() != Some(db.clone()) {
-            app_state.0.current_database_id.set(Some(db.clone()));
-            if let Some(storage) = web_sys::window().and_then(|w| w.local_storage().ok().flatten())
-            {
-                let _ = storage.set_item(CURRENT_DB_KEY, &db);
-            }
-        }
 
         loading.set(true);
         error.set(None);
-
         let api_client = app_state.0.api_client.get_untracked();
         spawn_local(async move {
-            let notes_res = api_client.get_all_note_list(&db).await;
-            let navs_res = api_client.get_all_navs(&db).await;
+            let result = async {
+                Ok::<_, ApiError>((
+                    api_client.get_deleted_note_list(&db).await?,
+                    api_client.get_all_note_list(&db).await?,
+                    api_client.get_all_navs(&db).await?,
+                ))
+            }
+            .await;
 
-            match (notes_res, navs_res) {
-                (Ok(ns), Ok(vs)) => {
-                    notes.set(ns);
+            // Switched databases meanwhile.
+            if db_id_untracked() != db {
+                return;
+            }
+            match result {
+                Ok((deleted, live, vs)) => {
+                    deleted_notes.set(deleted);
+                    live_notes.set(live);
                     navs.set(vs);
                 }
-                (Err(e), _) | (_, Err(e)) => {
-                    if e.kind == crate::api::ApiErrorKind::Unauthorized {
-                        let mut c = app_state.0.api_client.get_untracked();
-                        c.logout();
-                        app_state.0.api_client.set(c);
-                        app_state.0.current_user.set(None);
-                        let _ = window().location().set_href("/login");
-                    } else {
-                        error.set(Some(e.to_string()));
-                    }
-                }
+                Err(e) => on_error(e),
             }
-
             loading.set(false);
         });
     });
 
-    let unreferenced = move || {
-        let ns = notes.get();
-        let vs = navs.get();
+    // (note id, title, deleted block count) for live notes with deleted blocks.
+    let notes_with_deleted_blocks = Memo::new(move |_| {
+        live_notes.with(|ns| {
+            navs.with(|vs| {
+                ns.iter()
+                    .filter_map(|n| {
+                        let count = vs
+                            .iter()
+                            .filter(|v| {
+                                v.note_id == n.id
+                                    && v.is_delete
+                                    && v.parid != ROOT_CONTAINER_PARENT_ID
+                            })
+                            .count();
+                        (count > 0).then(|| (n.id.clone(), n.title.clone(), count))
+                    })
+                    .collect::<Vec<_>>()
+            })
+        })
+    });
 
-        let mut referenced_titles: std::collections::BTreeSet<String> =
-            std::collections::BTreeSet::new();
-        for nav in vs.into_iter() {
-            if nav.is_delete {
-                continue;
-            }
-            for t in extract_wiki_links(&nav.content) {
-                referenced_titles.insert(normalize_roam_page_title(&t));
+    let restore_note = move |note: Note| {
+        let api_client = app_state.0.api_client.get_untracked();
+        spawn_local(async move {
+            match api_client.restore_note(&note.id).await {
+                Ok(()) => {
+                    deleted_notes.update(|xs| xs.retain(|n| n.id != note.id));
+                    live_notes.update(|xs| xs.push(note));
+                    // Note lists pick it up on their next load.
+                    app_state.0.notes_last_loaded_db_id.set(None);
+                }
+                Err(e) => on_error(e),
             }
+        });
+    };
+
+    // `with_ancestors = false` on a block under a deleted parent moves it to the top level.
+    let restore_block = move |note_id: String, nav_id: String, with_ancestors: bool| {
+        if restoring.get_untracked() {
+            return;
         }
+        pending_restore.set(None);
 
-        let mut out: Vec<Note> = ns
-            .into_iter()
-            .filter(|n| {
-                let t = normalize_roam_page_title(&n.title);
-                !referenced_titles.contains(&t)
-            })
-            .collect();
+        let note_navs = navs.with_untracked(|vs| {
+            vs.iter()
+                .filter(|n| n.note_id == note_id)
+                .cloned()
+                .collect::<Vec<_>>()
+        });
+        let ids = restore_nav_ids(&note_navs, &nav_id, with_ancestors);
+        let reattach = (!with_ancestors && !deleted_ancestor_ids(&note_navs, &nav_id).is_empty())
+            .then(|| top_level_restore_target(&note_navs));
 
-        out.sort_by(|a, b| a.title.cmp(&b.title));
-        out
+        let db = db_id_untracked();
+        let api_client = app_state.0.api_client.get_untracked();
+        restoring.set(true);
+        spawn_local(async move {
+            // Parents first, so a restored child never points at a deleted parent.
+            let mut restored: Vec<Nav> = vec![];
+            for id in ids {
+                let target = if id == nav_id { reattach.clone() } else { None };
+                if let Err(e) = api_client.restore_nav(&note_id, &id, target.clone()).await {
+                    on_error(e);
+                    break;
+                }
+                if let Some(mut n) = note_navs.iter().find(|n| n.id == id).cloned() {
+                    n.is_delete = false;
+                    if let Some((parid, order)) = target {
+                        n.parid = parid;
+                        n.same_deep_order = order;
+                    }
+                    restored.push(n);
+                }
+            }
+
+            restore_navs_in_snapshot(&db, &note_id, &restored);
+            navs.update(|vs| {
+                for r in &restored {
+                    if let Some(n) = vs.iter_mut().find(|n| n.id == r.id) {
+                        *n = r.clone();
+                    }
+                }
+            });
+            app_state_sv.with_value(|s| s.0.invalidate_restored_navs(&db, &note_id));
+            restoring.set(false);
+        });
+    };
+
+    let on_restore_block = move |note_id: String, nav_id: String| {
+        let deleted_ancestors = navs.with_untracked(|vs| deleted_ancestor_ids(vs, &nav_id).len());
+        if deleted_ancestors == 0 {
+            restore_block(note_id, nav_id, false);
+        } else {
+            pending_restore.set(Some(PendingRestore {
+                note_id,
+                nav_id,
+                deleted_ancestors,
+            }));
+        }
     };
 
     view! {
-        <div class="space-y-4">
+        <div class="space-y-6">
             <div class="space-y-1">
-                <h1 class="text-xl font-semibold">"Unreferenced Pages"</h1>
-                <p class="text-xs text-muted-foreground">{move || format!("db_id = {}", db_id())}</p>
+                <h1 class="text-xl font-semibold">"Trash"</h1>
+                <p class="text-xs text-muted-foreground">"Deleted notes and blocks in this database."</p>
             </div>
 
             <Show when=move || !loading.get() fallback=move || view! {
@@ -3369,40 +8922,190 @@ pub fn UnreferencedPages() -> impl IntoView {
                     "Loading…"
                 </div>
             }>
-                <Show when=move || error.get().is_none() fallback=move || view! {
+                {move || error.get().map(|e| view! {
                     <Alert class="border-destructive/30">
-                        <AlertDescription class="text-destructive text-xs">
-                            {move || error.get().unwrap_or_default()}
-                        </AlertDescription>
+                        <AlertDescription class="text-destructive text-xs">{e}</AlertDescription>
                     </Alert>
-                }>
-                    <Show when=move || !unreferenced().is_empty() fallback=|| view! {
+                })}
+
+                <section class="space-y-2">
+                    <h2 class="text-sm font-medium">"Deleted notes"</h2>
+                    <Show when=move || !deleted_notes.with(|xs| xs.is_empty()) fallback=|| view! {
                         <div class="rounded-md border border-border bg-muted p-4 text-sm text-muted-foreground">
-                            "No unreferenced pages."
+                            "No deleted notes."
                         </div>
                     }>
                         <div class="space-y-1">
                             {move || {
-                                let db = db_id();
-                                unreferenced()
+                                deleted_notes
+                                    .get()
                                     .into_iter()
                                     .map(|n| {
-                                        let href = format!("/db/{}/note/{}", db, n.id);
+                                        let title = n.title.clone();
+                                        let note = StoredValue::new(n);
                                         view! {
-                                            <a
-                                                href=href
-                                                class="block rounded-md border border-border bg-background px-3 py-2 transition-colors hover:bg-surface-hover"
-                                            >
-                                                <div class="truncate text-sm font-medium">{n.title}</div>
-                                            </a>
+                                            <div class="flex items-center gap-2 rounded-md border border-border bg-background px-3 py-2">
+                                                <div class="min-w-0 flex-1 truncate text-sm text-muted-foreground">{title}</div>
+                                                <Button
+                                                    variant=ButtonVariant::Outline
+                                                    size=ButtonSize::Sm
+                                                    on:click=move |_| restore_note(note.get_value())
+                                                >
+                                                    "Restore"
+                                                </Button>
+                                            </div>
                                         }
                                     })
                                     .collect_view()
                             }}
                         </div>
                     </Show>
-                </Show>
+                </section>
+
+                <section class="space-y-2">
+                    <h2 class="text-sm font-medium">"Deleted blocks"</h2>
+                    <Show when=move || !notes_with_deleted_blocks.with(|xs| xs.is_empty()) fallback=|| view! {
+                        <div class="rounded-md border border-border bg-muted p-4 text-sm text-muted-foreground">
+                            "No deleted blocks."
+                        </div>
+                    }>
+                        <div class="space-y-1">
+                            {move || {
+                                notes_with_deleted_blocks
+                                    .get()
+                                    .into_iter()
+                                    .map(|(note_id, title, count)| {
+                                        let note_id = StoredValue::new(note_id);
+                                        let is_open = move || {
+                                            opened_note.with(|o| note_id.with_value(|id| o.as_ref() == Some(id)))
+                                        };
+                                        view! {
+                                            <div class="rounded-md border border-border bg-background">
+                                                <button
+                                                    type="button"
+                                                    class="flex w-full items-center gap-2 px-3 py-2 text-left transition-colors hover:bg-surface-hover"
+                                                    aria-expanded=move || is_open().to_string()
+                                                    on:click=move |_| {
+                                                        let next = (!is_open()).then(|| note_id.get_value());
+                                                        opened_note.set(next);
+                                                        pending_restore.set(None);
+                                                    }
+                                                >
+                                                    <span class="min-w-0 flex-1 truncate text-sm font-medium">{title}</span>
+                                                    <span class="text-xs text-muted-foreground">
+                                                        {format!("{count} deleted block{}", if count == 1 { "" } else { "s" })}
+                                                    </span>
+                                                </button>
+                                                <Show when=is_open fallback=|| ().into_view()>
+                                                    <div class="space-y-0.5 border-t border-border px-3 py-2">
+                                                        {move || {
+                                                            let id = note_id.get_value();
+                                                            let note_navs = navs.with(|vs| {
+                                                                vs.iter().filter(|n| n.note_id == id).cloned().collect::<Vec<_>>()
+                                                            });
+                                                            trash_outline(&note_navs)
+                                                                .into_iter()
+                                                                .map(|(depth, nav)| {
+                                                                    let content = if nav.content.trim().is_empty() {
+                                                                        "(empty)".to_string()
+                                                                    } else {
+                                                                        nav.content.clone()
+                                                                    };
+                                                                    let is_deleted = nav.is_delete;
+                                                                    let content_class = if is_deleted {
+                                                                        "min-w-0 flex-1 truncate text-muted-foreground line-through opacity-60"
+                                                                    } else {
+                                                                        "min-w-0 flex-1 truncate"
+                                                                    };
+                                                                    let nav_id = nav.id.clone();
+                                                                    let ids = StoredValue::new((id.clone(), nav_id));
+                                                                    view! {
+                                                                        <div
+                                                                            class="flex items-center gap-2 text-sm"
+                                                                            style=format!("padding-left: {}rem", depth as f32 * 1.25)
+                                                                        >
+                                                                            <span class=content_class>{content}</span>
+                                                                            {is_deleted.then(|| view! {
+                                                                                <Button
+                                                                                    variant=ButtonVariant::Ghost
+                                                                                    size=ButtonSize::Sm
+                                                                                    class="h-6 px-2 text-xs"
+                                                                                    attr:disabled=move || restoring.get()
+                                                                                    on:click=move |_| {
+                                                                                        let (note_id, nav_id) = ids.get_value();
+                                                                                        on_restore_block(note_id, nav_id);
+                                                                                    }
+                                                                                >
+                                                                                    "Restore"
+                                                                                </Button>
+                                                                            })}
+                                                                        </div>
+                                                                    }
+                                                                })
+                                                                .collect_view()
+                                                        }}
+                                                    </div>
+                                                </Show>
+                                            </div>
+                                        }
+                                    })
+                                    .collect_view()
+                            }}
+                        </div>
+                    </Show>
+                </section>
             </Show>
+
+            {move || pending_restore.get().map(|p| {
+                let n = p.deleted_ancestors;
+                let p = StoredValue::new(p);
+                view! {
+                    <div
+                        class="fixed inset-0 z-50 flex items-center justify-center bg-black/30 px-4"
+                        on:mousedown=move |_| pending_restore.set(None)
+                    >
+                        <div
+                            class="w-full max-w-sm space-y-3 rounded-md border border-border bg-background p-4 shadow-lg"
+                            role="dialog"
+                            aria-modal="true"
+                            on:mousedown=move |ev: web_sys::MouseEvent| ev.stop_propagation()
+                        >
+                            <div class="text-sm font-medium">"This block's parent is deleted too"</div>
+                            <p class="text-xs text-muted-foreground">
+                                "Restore the deleted parent blocks with it, or move the block to the top level of the note."
+                            </p>
+                            <div class="flex flex-wrap items-center gap-2">
+                                <Button
+                                    size=ButtonSize::Sm
+                                    on:click=move |_| {
+                                        let PendingRestore { note_id, nav_id, .. } = p.get_value();
+                                        restore_block(note_id, nav_id, true);
+                                    }
+                                >
+                                    {format!("Restore with {n} parent block{}", if n == 1 { "" } else { "s" })}
+                                </Button>
+                                <Button
+                                    variant=ButtonVariant::Outline
+                                    size=ButtonSize::Sm
+                                    on:click=move |_| {
+                                        let PendingRestore { note_id, nav_id, .. } = p.get_value();
+                                        restore_block(note_id, nav_id, false);
+                                    }
+                                >
+                                    "Move to top level"
+                                </Button>
+                                <Button
+                                    variant=ButtonVariant::Ghost
+                                    size=ButtonSize::Sm
+                                    on:click=move |_| pending_restore.set(None)
+                                >
+                                    "Cancel"
+                                </Button>
+                            </div>
+                        </div>
+                    </div>
+                }
+            })}
         </div>
     }
 }
@@ -2,43 +2,181 @@ mod note_sync;
 
 pub(crate) use note_sync::NoteSyncController;
 
-use crate::api::ApiClient;
-use crate::models::{AccountInfo, Database, Note};
-use crate::storage::{load_user_from_storage, CURRENT_DB_KEY, SIDEBAR_COLLAPSED_KEY};
+use crate::api::{AbortHandle, ApiClient, ApiError, PendingRequest};
+use crate::models::{
+    AccountInfo, Database, DeletedItem, DeletedItemKind, EditorPosition, Nav, Note, NoteFilter,
+    NoteSort, NoteTemplate, Settings, Theme,
+};
+use crate::storage::{
+    load_json_from_session_storage, load_json_from_storage, load_user_from_storage,
+    save_json_to_session_storage, save_json_to_storage, CURRENT_DB_KEY, LOCKED_NOTES_KEY,
+    NOTES_SORT_KEY, PINNED_NOTES_KEY, SETTINGS_KEY, SIDEBAR_COLLAPSED_KEY, TEMPLATES_KEY,
+    THEME_KEY, UNLOCKED_NOTES_KEY,
+};
+use crate::util::{
+    apply_theme, hash_pin, live_deleted_items, now_ms, prune_pinned_ids, shown_note_preview,
+    UNDO_DELETE_WINDOW_MS,
+};
 use leptos::prelude::*;
+use std::collections::HashMap;
 
 #[derive(Clone)]
 pub(crate) struct AppState {
     pub api_client: RwSignal<ApiClient>,
     pub current_user: RwSignal<Option<AccountInfo>>,
+    /// The backend rejected the session (401) or the token ran out; AppLayout shows the
+    /// re-login modal while this is set.
+    pub session_expired: RwSignal<bool>,
 
     /// Loaded from backend.
     pub databases: RwSignal<Vec<Database>>,
+    /// When `databases` was last fetched (ms since epoch); `None` until the first load.
+    /// `AppLayout` refreshes a stale list in the background on mount.
+    pub db_load_timestamp: RwSignal<Option<f64>>,
 
-    /// Notes for the currently selected database (Phase 5, non-paginated).
+    /// Notes for the currently selected database (loaded page by page on DbHomePage).
     pub notes: RwSignal<Vec<Note>>,
     pub notes_loading: RwSignal<bool>,
-    pub notes_error: RwSignal<Option<String>>,
+    pub notes_error: RwSignal<Option<ApiError>>,
+
+    /// Note list ordering per database id (persisted); applied by DbHomePage and the
+    /// sidebar Pages list. Databases without an entry use `NoteSort::default()`.
+    pub notes_sort: RwSignal<HashMap<String, NoteSort>>,
+
+    /// `updated_at` date range shown by DbHomePage and the sidebar Pages list (this session
+    /// only; see `note_filter_bounds`).
+    pub notes_filter: RwSignal<NoteFilter>,
+
+    /// Pagination state for `notes`: last loaded page (1-based) and whether more may exist.
+    pub notes_page: RwSignal<i32>,
+    pub notes_has_more: RwSignal<bool>,
+    pub notes_loading_more: RwSignal<bool>,
 
     /// Notes load guards (avoid duplicate loads + ignore stale responses).
     pub notes_request_id: RwSignal<u64>,
+    /// Cancels the notes load still in flight; see `begin_notes_request`.
+    pub notes_abort: StoredValue<Option<AbortHandle>>,
     pub notes_last_loaded_db_id: RwSignal<Option<String>>,
 
     /// Current database selection (drives routing in later phases).
     pub current_database_id: RwSignal<Option<String>>,
 
+    /// Note open in `NotePage` (mirrors its route param; `None` elsewhere).
+    pub current_note_id: RwSignal<Option<String>>,
+
     /// Global UI state.
     pub sidebar_collapsed: RwSignal<bool>,
 
+    /// Database switcher overlay (Cmd/Ctrl+Shift+D), rendered by `AppLayout`.
+    pub db_switcher_open: RwSignal<bool>,
+
+    /// Distraction-free writing on note routes (Cmd/Ctrl+Shift+F): `AppLayout` hides the
+    /// sidebar and breadcrumbs. Kept for the session only, unlike `sidebar_collapsed`.
+    pub focus_mode: RwSignal<bool>,
+
     /// Sidebar search query (Phase 3: UI + routing only).
     pub search_query: RwSignal<String>,
+
+    /// Filter for the sidebar database list (by name); cleared when switching databases.
+    pub databases_search: RwSignal<String>,
+
+    /// Character count per note id (outline content), used for size badges in note lists.
+    pub note_char_counts: RwSignal<HashMap<String, usize>>,
+
+    /// First-block preview per note id, per database id (note list snippets). A database
+    /// entry is loaded once; the open note's entry follows its outline as it is edited.
+    pub note_previews: RwSignal<HashMap<String, HashMap<String, String>>>,
+
+    /// All navs per database id (backlink scans). An entry is dropped when a saved edit
+    /// adds or removes a `[[link]]`, so the next scan re-fetches.
+    pub all_navs_cache: RwSignal<HashMap<String, Vec<Nav>>>,
+
+    /// `#tag` -> note ids for the current database, rebuilt from `all_navs_cache` by
+    /// `AppLayout` (tags are stored in `Nav::properties`).
+    pub tags_index: RwSignal<HashMap<String, Vec<String>>>,
+
+    /// Bumped when notes are created, renamed or deleted, or a saved edit changes a block's
+    /// `[[links]]`; the editor's `[[` title cache reloads when it moves.
+    pub titles_cache_version: RwSignal<u64>,
+
+    /// Last edited block, caret column and scroll offset per note id (this session only);
+    /// `OutlineEditor` restores them when a visited note is opened again.
+    pub editor_positions: RwSignal<HashMap<String, EditorPosition>>,
+
+    /// Note lists per database id for the quick switcher (Cmd/Ctrl+P); built on first open
+    /// and refreshed in the background on later opens.
+    pub quick_switch_index: RwSignal<HashMap<String, Vec<Note>>>,
+
+    /// Pending outline fold from the Cmd/Ctrl+Shift+[ / ] shortcuts (`true` = collapse),
+    /// raised by `AppLayout` and consumed by the open `NotePage`.
+    pub outline_fold_request: RwSignal<Option<bool>>,
+
+    /// Deletes that can still be undone from the snackbar in `AppLayout`, oldest first;
+    /// see `push_deleted`.
+    pub recently_deleted: RwSignal<Vec<DeletedItem>>,
+
+    /// Blocks put back by an undo (note id, navs), consumed by the open `OutlineEditor`.
+    pub nav_restore_request: RwSignal<Option<(String, Vec<Nav>)>>,
+
+    /// Content search results (`search_notes`) per `search_cache_key`, so returning to a
+    /// search doesn't re-fetch.
+    pub search_results_cache: RwSignal<HashMap<String, Vec<Note>>>,
+
+    /// Prose content per note id (`get_note_content`), so revisiting a note doesn't re-fetch.
+    pub note_content_cache: RwSignal<HashMap<String, String>>,
+
+    /// Server navs per note id (`get_note_navs`) with their load time; reopening a note
+    /// within `NAVS_CACHE_TTL_MS` skips the fetch. Dropped once a nav of the note is saved.
+    pub navs_cache: RwSignal<HashMap<String, (Vec<Nav>, f64)>>,
+
+    /// Pinned note ids per database id (persisted); rendered above the regular note lists.
+    pub pinned_notes: RwSignal<HashMap<String, Vec<String>>>,
+
+    /// PIN hash per locked note id (persisted); `NotePage` asks for the PIN before showing
+    /// the outline. A UI gate only: content is not encrypted.
+    pub locked_notes: RwSignal<HashMap<String, String>>,
+    /// Locked notes already unlocked in this tab (sessionStorage).
+    pub unlocked_notes: RwSignal<Vec<String>>,
+
+    /// Color theme preference (persisted); applied as the `dark` class on `<html>`.
+    pub theme: RwSignal<Theme>,
+
+    /// User preferences (persisted); see `SettingsPage`.
+    pub settings: RwSignal<Settings>,
+
+    /// Browser connectivity (`navigator.onLine`); kept current by listeners in `App`.
+    pub is_online: RwSignal<bool>,
+
+    /// Writes made while offline (renames, deletes, moves, database settings), replayed by
+    /// `App` once the browser is back online. In memory only.
+    pub pending_requests: RwSignal<Vec<PendingRequest>>,
+
+    /// Note templates offered by the "New" split button (persisted).
+    pub templates: RwSignal<Vec<NoteTemplate>>,
 }
 
 impl AppState {
     pub fn new() -> Self {
-        let stored_client = ApiClient::load_from_storage();
+        let pending_requests = RwSignal::new(vec![]);
+        let stored_client = ApiClient::load_from_storage().with_pending_queue(pending_requests);
         let stored_user = load_user_from_storage();
 
+        let pinned_notes = load_json_from_storage::<HashMap<String, Vec<String>>>(PINNED_NOTES_KEY)
+            .unwrap_or_default();
+
+        // Apply before first render to avoid a flash of the wrong palette.
+        let theme = load_json_from_storage::<Theme>(THEME_KEY).unwrap_or_default();
+        apply_theme(theme);
+
+        let settings = load_json_from_storage::<Settings>(SETTINGS_KEY).unwrap_or_default();
+        let is_online = web_sys::window()
+            .map(|w| w.navigator().on_line())
+            .unwrap_or(true);
+        let notes_sort =
+            load_json_from_storage::<HashMap<String, NoteSort>>(NOTES_SORT_KEY).unwrap_or_default();
+        let templates =
+            load_json_from_storage::<Vec<NoteTemplate>>(TEMPLATES_KEY).unwrap_or_default();
+
         let (sidebar_collapsed, current_database_id) = if let Some(storage) =
             web_sys::window().and_then(|w| w.local_storage().ok().flatten())
         {
@@ -59,17 +197,297 @@ impl AppState {
         Self {
             api_client: RwSignal::new(stored_client),
             current_user: RwSignal::new(stored_user),
+            session_expired: RwSignal::new(false),
             databases: RwSignal::new(vec![]),
+            db_load_timestamp: RwSignal::new(None),
             notes: RwSignal::new(vec![]),
             notes_loading: RwSignal::new(false),
             notes_error: RwSignal::new(None),
+            notes_sort: RwSignal::new(notes_sort),
+            notes_filter: RwSignal::new(NoteFilter::default()),
+            notes_page: RwSignal::new(0),
+            notes_has_more: RwSignal::new(false),
+            notes_loading_more: RwSignal::new(false),
             notes_request_id: RwSignal::new(0),
+            notes_abort: StoredValue::new(None),
             notes_last_loaded_db_id: RwSignal::new(None),
             current_database_id: RwSignal::new(current_database_id),
+            current_note_id: RwSignal::new(None),
             sidebar_collapsed: RwSignal::new(sidebar_collapsed),
+            db_switcher_open: RwSignal::new(false),
+            focus_mode: RwSignal::new(false),
             search_query: RwSignal::new(String::new()),
+            databases_search: RwSignal::new(String::new()),
+            note_char_counts: RwSignal::new(HashMap::new()),
+            note_previews: RwSignal::new(HashMap::new()),
+            all_navs_cache: RwSignal::new(HashMap::new()),
+            tags_index: RwSignal::new(HashMap::new()),
+            titles_cache_version: RwSignal::new(0),
+            editor_positions: RwSignal::new(HashMap::new()),
+            quick_switch_index: RwSignal::new(HashMap::new()),
+            outline_fold_request: RwSignal::new(None),
+            recently_deleted: RwSignal::new(vec![]),
+            nav_restore_request: RwSignal::new(None),
+            search_results_cache: RwSignal::new(HashMap::new()),
+            note_content_cache: RwSignal::new(HashMap::new()),
+            navs_cache: RwSignal::new(HashMap::new()),
+            pinned_notes: RwSignal::new(pinned_notes),
+            locked_notes: RwSignal::new(
+                load_json_from_storage(LOCKED_NOTES_KEY).unwrap_or_default(),
+            ),
+            unlocked_notes: RwSignal::new(
+                load_json_from_session_storage(UNLOCKED_NOTES_KEY).unwrap_or_default(),
+            ),
+            theme: RwSignal::new(theme),
+            settings: RwSignal::new(settings),
+            is_online: RwSignal::new(is_online),
+            pending_requests,
+            templates: RwSignal::new(templates),
         }
     }
+
+    /// Make the `[[` autocomplete reload page titles on next use.
+    pub fn invalidate_titles_cache(&self) {
+        self.titles_cache_version.update(|v| *v += 1);
+    }
+
+    /// Start a full notes load: supersedes (and aborts) the previous one. Returns the new
+    /// request id for the stale check and a handle for `ApiClient::with_abort`.
+    pub fn begin_notes_request(&self) -> (u64, AbortHandle) {
+        let req_id = self.notes_request_id.get_untracked().saturating_add(1);
+        self.notes_request_id.set(req_id);
+
+        let handle = AbortHandle::new();
+        if let Some(prev) = self.notes_abort.get_value() {
+            prev.abort();
+        }
+        self.notes_abort.set_value(Some(handle.clone()));
+        (req_id, handle)
+    }
+
+    pub fn set_theme(&self, theme: Theme) {
+        self.theme.set(theme);
+        save_json_to_storage(THEME_KEY, &theme);
+    }
+
+    /// Sort order for a database's note lists (tracked).
+    pub fn notes_sort_for(&self, db_id: &str) -> NoteSort {
+        self.notes_sort
+            .with(|m| m.get(db_id).copied())
+            .unwrap_or_default()
+    }
+
+    pub fn set_notes_sort(&self, db_id: &str, sort: NoteSort) {
+        if db_id.trim().is_empty() {
+            return;
+        }
+        self.notes_sort.update(|m| {
+            m.insert(db_id.to_string(), sort);
+        });
+        save_json_to_storage(NOTES_SORT_KEY, &self.notes_sort.get_untracked());
+    }
+
+    /// Persist new settings. Switching the API URL also signs out: the stored token
+    /// belongs to the previous backend. Returns whether that happened.
+    pub fn set_settings(&self, next: Settings) -> bool {
+        let api_url_changed =
+            self.settings.with_untracked(|s| s.api_url_override()) != next.api_url_override();
+        save_json_to_storage(SETTINGS_KEY, &next);
+        self.settings.set(next);
+
+        if api_url_changed {
+            // Queued writes were meant for the previous backend.
+            self.pending_requests.set(vec![]);
+            let mut c = ApiClient::load_from_storage().with_pending_queue(self.pending_requests);
+            c.logout();
+            self.api_client.set(c);
+            self.current_user.set(None);
+        }
+        api_url_changed
+    }
+
+    pub fn set_templates(&self, templates: Vec<NoteTemplate>) {
+        save_json_to_storage(TEMPLATES_KEY, &templates);
+        self.templates.set(templates);
+    }
+
+    /// Pinned note ids for a database, in pin order (tracked).
+    pub fn pinned_note_ids(&self, db_id: &str) -> Vec<String> {
+        self.pinned_notes
+            .with(|m| m.get(db_id).cloned())
+            .unwrap_or_default()
+    }
+
+    pub fn is_note_pinned_untracked(&self, db_id: &str, note_id: &str) -> bool {
+        self.pinned_notes.with_untracked(|m| {
+            m.get(db_id)
+                .is_some_and(|xs| xs.iter().any(|id| id == note_id))
+        })
+    }
+
+    pub fn pin_note(&self, db_id: &str, note_id: &str) {
+        if db_id.trim().is_empty()
+            || note_id.trim().is_empty()
+            || self.is_note_pinned_untracked(db_id, note_id)
+        {
+            return;
+        }
+        self.pinned_notes.update(|m| {
+            m.entry(db_id.to_string())
+                .or_default()
+                .push(note_id.to_string())
+        });
+        save_json_to_storage(PINNED_NOTES_KEY, &self.pinned_notes.get_untracked());
+    }
+
+    pub fn unpin_note(&self, db_id: &str, note_id: &str) {
+        if !self.is_note_pinned_untracked(db_id, note_id) {
+            return;
+        }
+        self.pinned_notes.update(|m| {
+            if let Some(xs) = m.get_mut(db_id) {
+                xs.retain(|id| id != note_id);
+            }
+            m.retain(|_, xs| !xs.is_empty());
+        });
+        save_json_to_storage(PINNED_NOTES_KEY, &self.pinned_notes.get_untracked());
+    }
+
+    /// Forget the cached navs of `note_id` so the next open fetches them again.
+    pub fn invalidate_navs_cache(&self, note_id: &str) {
+        self.navs_cache.update(|m| {
+            m.remove(note_id);
+        });
+    }
+
+    /// Drop the caches that still hold restored blocks of `note_id` as deleted (every
+    /// restore path calls this, or the editor reopens the note from a stale list).
+    pub fn invalidate_restored_navs(&self, db_id: &str, note_id: &str) {
+        self.invalidate_navs_cache(note_id);
+        self.all_navs_cache.update(|m| {
+            m.remove(db_id);
+        });
+    }
+
+    /// Offer undo for a delete during `UNDO_DELETE_WINDOW_MS`; the entry is dropped when
+    /// the window closes.
+    pub fn push_deleted(&self, kind: DeletedItemKind, id: &str, data: serde_json::Value) {
+        let item = DeletedItem {
+            kind,
+            id: id.to_string(),
+            data,
+            expires_ms: now_ms() + UNDO_DELETE_WINDOW_MS,
+        };
+        self.recently_deleted.update(|xs| {
+            xs.retain(|d| !(d.kind == kind && d.id == id));
+            xs.push(item);
+        });
+
+        let recently_deleted = self.recently_deleted;
+        set_timeout(
+            move || {
+                let now = now_ms();
+                if recently_deleted.with_untracked(|xs| xs.iter().any(|d| d.expires_ms <= now)) {
+                    recently_deleted.update(|xs| *xs = live_deleted_items(xs, now));
+                }
+            },
+            std::time::Duration::from_millis(UNDO_DELETE_WINDOW_MS as u64),
+        );
+    }
+
+    /// Remove the undo entry for `id` (undone, or superseded); returns it if it was there.
+    pub fn take_deleted(&self, kind: DeletedItemKind, id: &str) -> Option<DeletedItem> {
+        let item = self
+            .recently_deleted
+            .with_untracked(|xs| xs.iter().find(|d| d.kind == kind && d.id == id).cloned())?;
+        self.recently_deleted
+            .update(|xs| xs.retain(|d| !(d.kind == kind && d.id == id)));
+        Some(item)
+    }
+
+    /// Whether `note_id` has a PIN lock (tracked).
+    pub fn has_note_lock(&self, note_id: &str) -> bool {
+        self.locked_notes.with(|m| m.contains_key(note_id))
+    }
+
+    /// Whether `note_id` is locked and not yet unlocked in this tab (tracked).
+    pub fn is_note_locked(&self, note_id: &str) -> bool {
+        self.has_note_lock(note_id)
+            && !self
+                .unlocked_notes
+                .with(|xs| xs.iter().any(|id| id == note_id))
+    }
+
+    /// Note list preview for `note_id` in `db_id` (tracked); hidden while the note is locked.
+    pub fn note_list_preview(&self, db_id: &str, note_id: &str) -> Option<String> {
+        let locked = self.is_note_locked(note_id);
+        self.note_previews
+            .with(|m| shown_note_preview(m, db_id, note_id, locked))
+    }
+
+    /// Lock `note_id` behind `pin`, effective immediately (also in this tab).
+    pub fn lock_note(&self, note_id: &str, pin: &str) {
+        if note_id.trim().is_empty() {
+            return;
+        }
+        self.locked_notes.update(|m| {
+            m.insert(note_id.to_string(), hash_pin(pin));
+        });
+        save_json_to_storage(LOCKED_NOTES_KEY, &self.locked_notes.get_untracked());
+        self.relock_note(note_id);
+    }
+
+    /// Unlock `note_id` for the rest of the session if `pin` matches; `false` otherwise.
+    pub fn unlock_note(&self, note_id: &str, pin: &str) -> bool {
+        let matches = self
+            .locked_notes
+            .with_untracked(|m| m.get(note_id).is_some_and(|h| *h == hash_pin(pin)));
+        if matches {
+            self.unlocked_notes.update(|xs| {
+                if !xs.iter().any(|id| id == note_id) {
+                    xs.push(note_id.to_string());
+                }
+            });
+            save_json_to_session_storage(UNLOCKED_NOTES_KEY, &self.unlocked_notes.get_untracked());
+        }
+        matches
+    }
+
+    /// Ask for the PIN again (forget this tab's unlock).
+    pub fn relock_note(&self, note_id: &str) {
+        self.unlocked_notes
+            .update(|xs| xs.retain(|id| id != note_id));
+        save_json_to_session_storage(UNLOCKED_NOTES_KEY, &self.unlocked_notes.get_untracked());
+    }
+
+    /// Drop the lock of `note_id` altogether.
+    pub fn remove_note_lock(&self, note_id: &str) {
+        self.locked_notes.update(|m| {
+            m.remove(note_id);
+        });
+        save_json_to_storage(LOCKED_NOTES_KEY, &self.locked_notes.get_untracked());
+        self.relock_note(note_id);
+    }
+
+    /// Drop pins whose notes no longer exist. `notes` must be the complete list for `db_id`.
+    pub fn prune_pinned_notes(&self, db_id: &str, notes: &[Note]) {
+        let current = self
+            .pinned_notes
+            .with_untracked(|m| m.get(db_id).cloned())
+            .unwrap_or_default();
+        let kept = prune_pinned_ids(&current, notes);
+        if kept.len() == current.len() {
+            return;
+        }
+        self.pinned_notes.update(|m| {
+            if kept.is_empty() {
+                m.remove(db_id);
+            } else {
+                m.insert(db_id.to_string(), kept);
+            }
+        });
+        save_json_to_storage(PINNED_NOTES_KEY, &self.pinned_notes.get_untracked());
+    }
 }
 
 impl Default for AppState {
@@ -81,6 +499,15 @@ impl Default for AppState {
 #[derive(Clone)]
 pub(crate) struct AppContext(pub AppState);
 
+/// A request came back 401 (or the token ran out): forget the stored session and let the
+/// session-expired modal in `AppLayout` ask for a new login. The in-memory token stays until
+/// the user continues, so the current page remains visible behind the modal.
+pub(crate) fn handle_unauthorized_response(app_state: &AppContext) {
+    ApiClient::clear_storage();
+    app_state.0.current_user.set(None);
+    app_state.0.session_expired.set(true);
+}
+
 #[derive(Clone)]
 pub(crate) struct DbUiActions {
     pub open_create: Callback<()>,
@@ -1,13 +1,16 @@
-use crate::api::CreateOrUpdateNavRequest;
-use crate::cache::swap_tmp_nav_id_in_snapshot;
+use crate::api::{ApiClient, ApiError, CreateOrUpdateNavRequest};
+use crate::cache::{load_note_snapshot, restore_navs_in_snapshot, swap_tmp_nav_id_in_snapshot};
 use crate::drafts::{
     get_due_unsynced_nav_drafts, get_due_unsynced_nav_meta_drafts, get_unsynced_nav_drafts,
     list_dirty_notes, mark_nav_meta_sync_failed, mark_nav_meta_synced, mark_nav_sync_failed,
-    mark_nav_synced, mark_title_synced, mark_title_sync_failed, swap_tmp_nav_id_in_drafts, touch_nav,
-    touch_nav_meta, touch_title, NavMetaDraft,
+    mark_nav_synced, mark_title_synced, mark_title_sync_failed, revert_title_draft,
+    swap_tmp_nav_id_in_drafts, touch_nav, touch_nav_meta, touch_title, NavMetaDraft,
 };
-use crate::state::AppContext;
-use crate::util::{is_uuid_like, now_ms};
+use crate::models::{Nav, Note};
+use crate::state::{handle_unauthorized_response, AppContext};
+use crate::storage::{load_recent_notes, retarget_recent_notes, save_recent_notes};
+use crate::util::{created_nav_id, is_uuid_like, now_ms};
+use crate::wiki::extract_wiki_links;
 use leptos::ev;
 use leptos::prelude::*;
 use leptos::task::spawn_local;
@@ -21,7 +24,7 @@ use wasm_bindgen::JsCast;
 /// - local draft writes (localStorage)
 /// - per-nav debounce autosave
 /// - retry queue (retry_count/next_retry_ms)
-/// - best-effort pagehide flush (beacon/keepalive-friendly)
+/// - best-effort pagehide / hidden-tab flush (beacon/keepalive-friendly)
 ///
 /// Non-responsibilities:
 /// - outline UI state (editing id, focus, etc.)
@@ -42,9 +45,19 @@ pub(crate) struct NoteSyncController {
     current_editing_nav_id: RwSignal<Option<String>>,
 
     /// Per-nav debounce timers.
-    autosave_ms: i32,
     autosave_timers: Arc<Mutex<HashMap<String, i32>>>,
 
+    /// Latest title save per note; responses to superseded saves are ignored.
+    title_save_seq: StoredValue<HashMap<String, u64>>,
+    /// Set when the server rejects a rename (the title is rolled back); shown by NotePage.
+    title_save_error: RwSignal<Option<String>>,
+
+    /// Bumped whenever nav drafts are written or their sync result recorded, so views can
+    /// re-read sync state from the drafts storage.
+    drafts_version: RwSignal<u64>,
+    /// Last sync error per nav id, cleared once the nav syncs.
+    nav_sync_errors: RwSignal<HashMap<String, String>>,
+
     /// Retry worker.
     retry_timer_id: RwSignal<Option<i32>>,
     retry_interval_ms: i32,
@@ -52,6 +65,7 @@ pub(crate) struct NoteSyncController {
     /// Global listeners (keep handles alive).
     _online_handle: StoredValue<Option<WindowListenerHandle>>,
     _pagehide_handle: StoredValue<Option<WindowListenerHandle>>,
+    _visibility_handle: StoredValue<Option<WindowListenerHandle>>,
 }
 
 impl NoteSyncController {
@@ -71,10 +85,64 @@ impl NoteSyncController {
     }
 
     pub(crate) fn mark_backend_offline_api(&self, e: &crate::api::ApiError) {
-        if e.kind == crate::api::ApiErrorKind::Network {
-            self.backend_online.set(false);
-            self.last_backend_error.set(Some(e.to_string()));
+        match e {
+            crate::api::ApiError::Network(_) => {
+                self.backend_online.set(false);
+                self.last_backend_error.set(Some(e.to_string()));
+            }
+            // Drafts stay local and are replayed after the next login.
+            crate::api::ApiError::Unauthorized => {
+                handle_unauthorized_response(&self.app_state);
+            }
+            _ => {}
+        }
+    }
+
+    pub fn title_save_error(&self) -> RwSignal<Option<String>> {
+        self.title_save_error
+    }
+
+    pub fn drafts_version(&self) -> RwSignal<u64> {
+        self.drafts_version
+    }
+
+    pub fn nav_sync_errors(&self) -> RwSignal<HashMap<String, String>> {
+        self.nav_sync_errors
+    }
+
+    fn drafts_changed(&self) {
+        self.drafts_version.update(|v| *v = v.wrapping_add(1));
+    }
+
+    fn nav_synced(&self, nav_id: &str) {
+        if self.nav_sync_errors.with_untracked(|m| m.contains_key(nav_id)) {
+            self.nav_sync_errors.update(|m| {
+                m.remove(nav_id);
+            });
         }
+        self.drafts_changed();
+    }
+
+    fn nav_sync_failed(&self, nav_id: &str, e: &crate::api::ApiError) {
+        self.nav_sync_errors.update(|m| {
+            m.insert(nav_id.to_string(), e.to_string());
+        });
+        self.drafts_changed();
+    }
+
+    fn next_title_save_seq(&self, note_id: &str) -> u64 {
+        let mut seq = 0;
+        self.title_save_seq.update_value(|m| {
+            let n = m.entry(note_id.to_string()).or_default();
+            *n = n.wrapping_add(1);
+            seq = *n;
+        });
+        seq
+    }
+
+    fn is_current_title_save(&self, note_id: &str, seq: u64) -> bool {
+        self.title_save_seq
+            .with_value(|m| m.get(note_id).copied() == Some(seq))
     }
 
     fn should_probe_offline(&self, now_ms: i64) -> bool {
@@ -104,8 +172,11 @@ impl NoteSyncController {
         let current_note_id = RwSignal::new(String::new());
         let current_editing_nav_id = RwSignal::new(None);
 
-        let autosave_ms = 1200;
         let autosave_timers = Arc::new(Mutex::new(HashMap::new()));
+        let title_save_seq = StoredValue::new(HashMap::new());
+        let title_save_error = RwSignal::new(None);
+        let drafts_version = RwSignal::new(0);
+        let nav_sync_errors = RwSignal::new(HashMap::new());
 
         let retry_timer_id = RwSignal::new(None);
         let retry_interval_ms = 2000;
@@ -113,6 +184,7 @@ impl NoteSyncController {
         // We'll fill these in start() so they can reference `self` via clones.
         let _online_handle = StoredValue::new(None);
         let _pagehide_handle = StoredValue::new(None);
+        let _visibility_handle = StoredValue::new(None);
 
         let s = Self {
             app_state,
@@ -122,12 +194,16 @@ impl NoteSyncController {
             current_db_id,
             current_note_id,
             current_editing_nav_id,
-            autosave_ms,
             autosave_timers,
+            title_save_seq,
+            title_save_error,
+            drafts_version,
+            nav_sync_errors,
             retry_timer_id,
             retry_interval_ms,
             _online_handle,
             _pagehide_handle,
+            _visibility_handle,
         };
 
         s.start_global_listeners();
@@ -200,6 +276,8 @@ impl NoteSyncController {
             is_display: true,
             is_delete: false,
             properties: None,
+            created_at: None,
+            updated_at: None,
         };
 
         navs.push(nav.clone());
@@ -216,6 +294,7 @@ impl NoteSyncController {
         // Persist drafts so sync worker can create it on backend when online.
         crate::drafts::touch_nav(db_id, note_id, &tmp_id, initial_content);
         crate::drafts::touch_nav_meta(db_id, note_id, &nav);
+        self.drafts_changed();
 
         Some(tmp_id)
     }
@@ -225,6 +304,13 @@ impl NoteSyncController {
         self.current_editing_nav_id.set(nav_id);
     }
 
+    /// Clears the editing nav if it is still `nav_id` (another editor may have set its own).
+    pub fn clear_editing_nav(&self, nav_id: &str) {
+        if self.current_editing_nav_id.get_untracked().as_deref() == Some(nav_id) {
+            self.current_editing_nav_id.set(None);
+        }
+    }
+
     /// Called by OutlineEditor on each input.
     pub fn on_nav_changed(&self, nav_id: &str, content: &str) {
         let Some((db_id, note_id)) = self.db_note_untracked() else {
@@ -232,6 +318,7 @@ impl NoteSyncController {
         };
 
         touch_nav(&db_id, &note_id, nav_id, content);
+        self.drafts_changed();
         self.schedule_autosave(nav_id.to_string());
     }
 
@@ -241,9 +328,55 @@ impl NoteSyncController {
         };
 
         touch_nav_meta(&db_id, &note_id, nav);
+        self.drafts_changed();
         self.schedule_autosave(format!("meta:{}", nav.id));
     }
 
+    /// Like `on_nav_meta_changed`, for structural edits touching several navs at once
+    /// (multi-block indent/outdent/delete). The upserts are flushed together, in order.
+    pub fn on_navs_meta_changed(&self, navs: &[crate::models::Nav]) {
+        let Some((db_id, note_id)) = self.db_note_untracked() else {
+            return;
+        };
+        if navs.is_empty() {
+            return;
+        }
+
+        for nav in navs {
+            touch_nav_meta(&db_id, &note_id, nav);
+        }
+        self.drafts_changed();
+        let ids = navs.iter().map(|n| n.id.as_str()).collect::<Vec<_>>();
+        self.schedule_autosave(format!("meta-batch:{}", ids.join(",")));
+    }
+
+    /// Undo a local subtree delete in any note: `navs` (parents first, `is_delete = false`)
+    /// are queued again as meta drafts and put back into the offline snapshot. Blocks that
+    /// never reached the backend get their content draft back so they are created again.
+    /// Flushed right away when the note is open, otherwise by the retry worker.
+    pub fn restore_deleted_navs(&self, db_id: &str, note_id: &str, navs: &[Nav]) {
+        if navs.is_empty() {
+            return;
+        }
+
+        for nav in navs {
+            if crate::editor::is_tmp_nav_id(&nav.id) {
+                touch_nav(db_id, note_id, &nav.id, &nav.content);
+            }
+            touch_nav_meta(db_id, note_id, nav);
+        }
+        restore_navs_in_snapshot(db_id, note_id, navs);
+        self.drafts_changed();
+
+        if self
+            .db_note_untracked()
+            .is_some_and(|(db, note)| db == db_id && note == note_id)
+        {
+            let ids = navs.iter().map(|n| n.id.as_str()).collect::<Vec<_>>();
+            self.schedule_autosave(format!("meta-batch:{}", ids.join(",")));
+        }
+    }
+
     /// Called by NotePage when note title changes.
     pub fn on_title_changed(&self, title: &str) {
         let Some((db_id, note_id)) = self.db_note_untracked() else {
@@ -255,6 +388,78 @@ impl NoteSyncController {
     }
 
 
+    /// The server rejected a rename: put its current title back in the note lists, recents
+    /// and the title draft, and report the error.
+    async fn roll_back_title(
+        &self,
+        api_client: &ApiClient,
+        db_id: &str,
+        note_id: &str,
+        seq: u64,
+        e: ApiError,
+    ) {
+        let server_title = api_client
+            .get_all_note_list(db_id)
+            .await
+            .ok()
+            .and_then(|notes| notes.into_iter().find(|n| n.id == note_id))
+            .map(|n| n.title);
+        if !self.is_current_title_save(note_id, seq) {
+            return;
+        }
+        let Some(title) = server_title else {
+            // Nothing known to restore; keep the draft and retry later.
+            mark_title_sync_failed(db_id, note_id);
+            return;
+        };
+
+        revert_title_draft(db_id, note_id, &title);
+        let set_title = |xs: &mut Vec<Note>| {
+            if let Some(n) = xs.iter_mut().find(|n| n.id == note_id) {
+                n.title = title.clone();
+            }
+        };
+        self.app_state.0.notes.update(set_title);
+        self.app_state.0.quick_switch_index.update(|m| {
+            if let Some(xs) = m.get_mut(db_id) {
+                set_title(xs);
+            }
+        });
+        save_recent_notes(&retarget_recent_notes(
+            load_recent_notes(),
+            db_id,
+            note_id,
+            db_id,
+            note_id,
+            &title,
+        ));
+        self.title_save_error
+            .set(Some(format!("Couldn't rename the note: {e}")));
+    }
+
+    /// Drop the cached DB-wide nav scan when a saved edit adds or removes a `[[link]]`,
+    /// so backlinks and `[[` autocomplete titles are re-fetched on next use.
+    fn invalidate_backlinks_on_link_change(&self, db_id: &str, nav_id: &str, content: &str) {
+        let changed = self.app_state.0.all_navs_cache.with_untracked(|m| {
+            let Some(navs) = m.get(db_id) else {
+                return false;
+            };
+            let old = navs
+                .iter()
+                .find(|n| n.id == nav_id)
+                .map(|n| n.content.as_str())
+                .unwrap_or("");
+            extract_wiki_links(old) != extract_wiki_links(content)
+        });
+
+        if changed {
+            self.app_state.0.all_navs_cache.update(|m| {
+                m.remove(db_id);
+            });
+            self.app_state.0.invalidate_titles_cache();
+        }
+    }
+
     fn flush_draft_item(&self, item_id: String) {
         // Never spam backend when offline; rely on retry worker probes.
         if !self.backend_online.get_untracked() {
@@ -268,6 +473,12 @@ impl NoteSyncController {
             return;
         }
 
+        // meta-batch:{id1,id2,...} is used for multi-nav structural edits.
+        if let Some(ids) = item_id.strip_prefix("meta-batch:") {
+            self.flush_nav_meta_batch(ids.split(',').map(|s| s.to_string()).collect());
+            return;
+        }
+
         // meta:{nav_id} is used for metadata autosave.
         if let Some(id) = item_id.strip_prefix("meta:") {
             self.flush_nav_meta_draft(id.to_string());
@@ -285,22 +496,35 @@ impl NoteSyncController {
                 return;
             }
 
+            // The note list and recents already show the new title (see NotePage);
+            // only a rejected save touches them again.
+            let seq = self.next_title_save_seq(note_id_for_title);
             let api_client = self.app_state.0.api_client.get_untracked();
             let db_id_clone = db_id.clone();
             let note_id_clone = note_id_for_title.to_string();
-            let app_state_notes = self.app_state.0.notes.clone();
+            let s2 = self.clone();
             spawn_local(async move {
-                match api_client.update_note_title(&note_id_clone, &title.value).await {
-                    Ok(_) => {
+                let result = api_client
+                    .update_note_title(&note_id_clone, &title.value)
+                    .await;
+                // Renamed again meanwhile: the newer save's response decides.
+                if !s2.is_current_title_save(&note_id_clone, seq) {
+                    return;
+                }
+                match result {
+                    Ok(()) => {
+                        s2.mark_backend_online();
                         mark_title_synced(&db_id_clone, &note_id_clone, title.updated_ms);
-                        // Refresh notes list after successful title update.
-                        if let Ok(notes) = api_client.get_all_note_list(&db_id_clone).await {
-                            app_state_notes.set(notes);
-                        }
                     }
-                    Err(_) => {
+                    // Offline or logged out: keep the draft; it is retried later.
+                    Err(e @ (ApiError::Network(_) | ApiError::Unauthorized)) => {
+                        s2.mark_backend_offline_api(&e);
                         mark_title_sync_failed(&db_id_clone, &note_id_clone);
                     }
+                    Err(e) => {
+                        s2.roll_back_title(&api_client, &db_id_clone, &note_id_clone, seq, e)
+                            .await;
+                    }
                 }
             });
             return;
@@ -327,7 +551,7 @@ impl NoteSyncController {
                 note_id: note_id.clone(),
                 id: Some(item_id.clone()),
                 parid: None,
-                content: Some(content),
+                content: Some(content.clone()),
                 order: None,
                 is_display: None,
                 is_delete: None,
@@ -337,11 +561,15 @@ impl NoteSyncController {
             match api_client.upsert_nav(req).await {
                 Ok(_) => {
                     s2.mark_backend_online();
+                    s2.app_state.0.invalidate_navs_cache(&note_id);
                     mark_nav_synced(&db_id, &note_id, &item_id, updated_ms);
+                    s2.nav_synced(&item_id);
+                    s2.invalidate_backlinks_on_link_change(&db_id, &item_id, &content);
                 }
                 Err(e) => {
                     s2.mark_backend_offline_api(&e);
                     mark_nav_sync_failed(&db_id, &note_id, &item_id);
+                    s2.nav_sync_failed(&item_id, &e);
                 }
             }
         });
@@ -390,11 +618,71 @@ impl NoteSyncController {
             match api_client.upsert_nav(req).await {
                 Ok(_) => {
                     s2.mark_backend_online();
+                    s2.app_state.0.invalidate_navs_cache(&note_id);
                     mark_nav_meta_synced(&db_id, &note_id, &nav_id, updated_ms);
+                    s2.nav_synced(&nav_id);
                 }
                 Err(e) => {
                     s2.mark_backend_offline_api(&e);
                     mark_nav_meta_sync_failed(&db_id, &note_id, &nav_id);
+                    s2.nav_sync_failed(&nav_id, &e);
+                }
+            }
+        });
+    }
+
+    fn flush_nav_meta_batch(&self, nav_ids: Vec<String>) {
+        // Never spam backend when offline; rely on retry worker probes.
+        if !self.backend_online.get_untracked() {
+            return;
+        }
+
+        let Some((db_id, note_id)) = self.db_note_untracked() else {
+            return;
+        };
+
+        // Keep the caller's order; local optimistic ids are created by the retry worker.
+        let due = get_due_unsynced_nav_meta_drafts(&db_id, &note_id, now_ms(), 500);
+        let items = nav_ids
+            .iter()
+            .filter(|id| is_uuid_like(id))
+            .filter_map(|id| due.iter().find(|(d, _, _)| d == id).cloned())
+            .collect::<Vec<_>>();
+        if items.is_empty() {
+            return;
+        }
+
+        let api_client = self.app_state.0.api_client.get_untracked();
+        let s2 = self.clone();
+        spawn_local(async move {
+            for (nav_id, meta, updated_ms) in items {
+                let req = CreateOrUpdateNavRequest {
+                    note_id: note_id.clone(),
+                    id: Some(nav_id.clone()),
+                    parid: Some(meta.parid),
+                    content: None,
+                    order: Some(meta.same_deep_order),
+                    is_display: Some(meta.is_display),
+                    is_delete: Some(meta.is_delete),
+                    properties: meta.properties,
+                };
+
+                match api_client.upsert_nav(req).await {
+                    Ok(_) => {
+                        s2.mark_backend_online();
+                        s2.app_state.0.invalidate_navs_cache(&note_id);
+                        mark_nav_meta_synced(&db_id, &note_id, &nav_id, updated_ms);
+                        s2.nav_synced(&nav_id);
+                    }
+                    Err(e) => {
+                        s2.mark_backend_offline_api(&e);
+                        mark_nav_meta_sync_failed(&db_id, &note_id, &nav_id);
+                        s2.nav_sync_failed(&nav_id, &e);
+                        // Leave the rest to the retry worker once offline.
+                        if !s2.is_backend_online() {
+                            break;
+                        }
+                    }
                 }
             }
         });
@@ -424,7 +712,7 @@ impl NoteSyncController {
         let tid = win
             .set_timeout_with_callback_and_timeout_and_arguments_0(
                 cb.as_ref().unchecked_ref(),
-                self.autosave_ms,
+                self.app_state.0.settings.with_untracked(|s| s.autosave_delay_ms()),
             )
             .unwrap_or(0);
 
@@ -509,8 +797,7 @@ impl NoteSyncController {
                         mark_title_synced(&db_id, &note_id, updated_ms);
                     }
                     Err(e) => {
-                        // Note: update_note_title returns String error, not ApiError.
-                        let _ = e;
+                        s2.mark_backend_offline_api(&e);
                         mark_title_sync_failed(&db_id, &note_id);
                     }
                 }
@@ -537,11 +824,30 @@ impl NoteSyncController {
                 match api_client.upsert_nav(req).await {
                     Ok(resp) => {
                         s2.mark_backend_online();
-                        let new_id = resp
-                            .get("id")
-                            .and_then(|v| v.as_str())
-                            .unwrap_or("")
-                            .to_string();
+                        s2.app_state.0.invalidate_navs_cache(&note_id);
+                        let new_id = match ApiClient::parse_upsert_nav_response(resp) {
+                            Some(nav) => nav.id,
+                            // No id in the response: look the new nav up in a fresh list rather
+                            // than keep a phantom block that the next retry creates again.
+                            None => {
+                                let known = load_note_snapshot(db_id, note_id)
+                                    .map(|s| s.navs.into_iter().map(|n| n.id).collect::<Vec<_>>())
+                                    .unwrap_or_default();
+                                api_client
+                                    .get_note_navs(note_id)
+                                    .await
+                                    .ok()
+                                    .and_then(|server| {
+                                        created_nav_id(
+                                            &server,
+                                            &known,
+                                            &meta.parid,
+                                            meta.same_deep_order,
+                                        )
+                                    })
+                                    .unwrap_or_default()
+                            }
+                        };
                         if new_id.trim().is_empty() {
                             continue;
                         }
@@ -551,10 +857,12 @@ impl NoteSyncController {
 
                         // Mark meta as synced under the real id.
                         mark_nav_meta_synced(db_id, note_id, &new_id, *updated_ms);
+                        s2.nav_synced(&new_id);
                     }
                     Err(e) => {
                         s2.mark_backend_offline_api(&e);
                         mark_nav_meta_sync_failed(db_id, note_id, nav_id);
+                        s2.nav_sync_failed(nav_id, &e);
                     }
                 }
             }
@@ -569,7 +877,7 @@ impl NoteSyncController {
                     note_id: note_id.clone(),
                     id: Some(nav_id.clone()),
                     parid: None,
-                    content: Some(content),
+                    content: Some(content.clone()),
                     order: None,
                     is_display: None,
                     is_delete: None,
@@ -579,11 +887,15 @@ impl NoteSyncController {
                 match api_client.upsert_nav(req).await {
                     Ok(_) => {
                         s2.mark_backend_online();
+                        s2.app_state.0.invalidate_navs_cache(&note_id);
                         mark_nav_synced(&db_id, &note_id, &nav_id, updated_ms);
+                        s2.nav_synced(&nav_id);
+                        s2.invalidate_backlinks_on_link_change(&db_id, &nav_id, &content);
                     }
                     Err(e) => {
                         s2.mark_backend_offline_api(&e);
                         mark_nav_sync_failed(&db_id, &note_id, &nav_id);
+                        s2.nav_sync_failed(&nav_id, &e);
                     }
                 }
             }
@@ -608,11 +920,14 @@ impl NoteSyncController {
                 match api_client.upsert_nav(req).await {
                     Ok(_) => {
                         s2.mark_backend_online();
+                        s2.app_state.0.invalidate_navs_cache(&note_id);
                         mark_nav_meta_synced(&db_id, &note_id, &nav_id, updated_ms);
+                        s2.nav_synced(&nav_id);
                     }
                     Err(e) => {
                         s2.mark_backend_offline_api(&e);
                         mark_nav_meta_sync_failed(&db_id, &note_id, &nav_id);
+                        s2.nav_sync_failed(&nav_id, &e);
                     }
                 }
             }
@@ -645,9 +960,10 @@ impl NoteSyncController {
     }
 
     fn start_global_listeners(&self) {
-        // online -> kick retry
+        // online -> skip the offline probe backoff and replay queued drafts right away
         let s2 = self.clone();
         let online = window_event_listener(ev::online, move |_ev: web_sys::Event| {
+            s2.mark_backend_online();
             s2.retry_tick();
         });
         self._online_handle.set_value(Some(online));
@@ -659,6 +975,16 @@ impl NoteSyncController {
                 s3.pagehide_flush();
             });
         self._pagehide_handle.set_value(Some(pagehide));
+
+        // Tab hidden -> same flush. Mobile browsers often discard a background tab without
+        // firing pagehide.
+        let s4 = self.clone();
+        let visibility = window_event_listener(ev::visibilitychange, move |_ev: web_sys::Event| {
+            if document().hidden() {
+                s4.pagehide_flush();
+            }
+        });
+        self._visibility_handle.set_value(Some(visibility));
     }
 
     fn pagehide_flush(&self) {
@@ -736,11 +1062,14 @@ impl NoteSyncController {
                 match api_client.upsert_nav(req).await {
                     Ok(_) => {
                         s2.mark_backend_online();
+                        s2.app_state.0.invalidate_navs_cache(&note_id);
                         mark_nav_synced(&db_id, &note_id, &nav_id, updated_ms);
+                        s2.nav_synced(&nav_id);
                     }
                     Err(e) => {
                         s2.mark_backend_offline_api(&e);
                         mark_nav_sync_failed(&db_id, &note_id, &nav_id);
+                        s2.nav_sync_failed(&nav_id, &e);
                     }
                 }
             }
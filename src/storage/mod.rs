@@ -1,4 +1,4 @@
-use crate::models::{AccountInfo, RecentDb, RecentNote};
+use crate::models::{AccountInfo, Note, RecentDb, RecentNote};
 use crate::util::now_ms;
 use serde::{Deserialize, Serialize};
 
@@ -11,6 +11,25 @@ pub(crate) const CURRENT_DB_KEY: &str = "hulunote_current_database_id";
 pub(crate) const RECENT_DBS_KEY: &str = "hulunote_recent_dbs";
 pub(crate) const RECENT_NOTES_KEY: &str = "hulunote_recent_notes";
 
+/// Pinned note ids keyed by database id.
+pub(crate) const PINNED_NOTES_KEY: &str = "hulunote_pinned_notes_by_db";
+
+pub(crate) const THEME_KEY: &str = "hulunote_theme";
+
+pub(crate) const SETTINGS_KEY: &str = "hulunote_settings";
+
+/// Note list ordering keyed by database id.
+pub(crate) const NOTES_SORT_KEY: &str = "hulunote_notes_sort_by_db";
+
+/// Note templates (`Vec<NoteTemplate>`), edited on the Settings page.
+pub(crate) const TEMPLATES_KEY: &str = "hulunote_templates";
+
+/// Locked notes: note id -> `hash_pin` of the PIN that unlocks it.
+pub(crate) const LOCKED_NOTES_KEY: &str = "hulunote_locked_notes";
+
+/// Note ids unlocked in this tab (sessionStorage), so the PIN is asked once per session.
+pub(crate) const UNLOCKED_NOTES_KEY: &str = "hulunote_unlocked_notes";
+
 pub(crate) fn save_user_to_storage(user: &AccountInfo) {
     if let Ok(json) = serde_json::to_string(user) {
         if let Some(storage) = web_sys::window().and_then(|w| w.local_storage().ok().flatten()) {
@@ -42,6 +61,20 @@ pub(crate) fn save_json_to_storage<T: Serialize>(key: &str, value: &T) {
     }
 }
 
+pub(crate) fn load_json_from_session_storage<T: for<'de> Deserialize<'de>>(key: &str) -> Option<T> {
+    let storage = web_sys::window().and_then(|w| w.session_storage().ok().flatten())?;
+    let json = storage.get_item(key).ok().flatten()?;
+    serde_json::from_str(&json).ok()
+}
+
+pub(crate) fn save_json_to_session_storage<T: Serialize>(key: &str, value: &T) {
+    if let Ok(json) = serde_json::to_string(value) {
+        if let Some(storage) = web_sys::window().and_then(|w| w.session_storage().ok().flatten()) {
+            let _ = storage.set_item(key, &json);
+        }
+    }
+}
+
 pub(crate) fn upsert_lru_by_key<T: Clone>(
     mut items: Vec<T>,
     item: T,
@@ -60,6 +93,10 @@ pub(crate) fn load_recent_dbs() -> Vec<RecentDb> {
     load_json_from_storage::<Vec<RecentDb>>(RECENT_DBS_KEY).unwrap_or_default()
 }
 
+pub(crate) fn save_recent_dbs(dbs: &[RecentDb]) {
+    save_json_to_storage(RECENT_DBS_KEY, &dbs);
+}
+
 pub(crate) fn load_recent_notes() -> Vec<RecentNote> {
     load_json_from_storage::<Vec<RecentNote>>(RECENT_NOTES_KEY).unwrap_or_default()
 }
@@ -68,8 +105,10 @@ pub(crate) fn save_recent_notes(notes: &[RecentNote]) {
     save_json_to_storage(RECENT_NOTES_KEY, &notes);
 }
 
+/// Record an opened database. A name that is blank or just the id is not recorded: callers
+/// wait until the database list has the real name.
 pub(crate) fn write_recent_db(id: &str, name: &str) {
-    if id.trim().is_empty() {
+    if id.trim().is_empty() || name.trim().is_empty() || name == id {
         return;
     }
 
@@ -98,8 +137,63 @@ pub(crate) fn write_recent_note(db_id: &str, note_id: &str, title: &str) {
     let next = upsert_lru_by_key(
         load_recent_notes(),
         item,
-        |a, b| a.db_id == b.db_id && a.note_id == b.note_id,
+        // Note ids are globally unique; keying on the id alone keeps a single entry even if
+        // the note is later opened under a different database.
+        |a, b| a.note_id == b.note_id,
         20,
     );
     save_json_to_storage(RECENT_NOTES_KEY, &next);
 }
+
+/// Drop Recent Notes entries whose note no longer exists in `existing_notes`.
+pub(crate) fn prune_recent_notes_not_in(
+    existing_notes: &[Note],
+    recent: Vec<RecentNote>,
+) -> Vec<RecentNote> {
+    let ids: std::collections::HashSet<&str> =
+        existing_notes.iter().map(|n| n.id.as_str()).collect();
+    recent
+        .into_iter()
+        .filter(|r| ids.contains(r.note_id.as_str()))
+        .collect()
+}
+
+/// Drop a database that no longer exists from Recent Databases and its notes from Recent
+/// Notes.
+pub(crate) fn forget_recent_db(db_id: &str) {
+    let mut dbs = load_recent_dbs();
+    let before = dbs.len();
+    dbs.retain(|d| d.id != db_id);
+    if dbs.len() != before {
+        save_recent_dbs(&dbs);
+    }
+
+    let mut notes = load_recent_notes();
+    let before = notes.len();
+    notes.retain(|n| n.db_id != db_id);
+    if notes.len() != before {
+        save_recent_notes(&notes);
+    }
+}
+
+/// Point Recent Notes entries for a moved note at its new location.
+pub(crate) fn retarget_recent_notes(
+    items: Vec<RecentNote>,
+    old_db_id: &str,
+    old_note_id: &str,
+    new_db_id: &str,
+    new_note_id: &str,
+    new_title: &str,
+) -> Vec<RecentNote> {
+    items
+        .into_iter()
+        .map(|mut r| {
+            if r.db_id == old_db_id && r.note_id == old_note_id {
+                r.db_id = new_db_id.to_string();
+                r.note_id = new_note_id.to_string();
+                r.title = new_title.to_string();
+            }
+            r
+        })
+        .collect()
+}
@@ -1,13 +1,8 @@
-use crate::models::Note;
-
-pub(crate) fn today_yyyymmdd_local() -> String {
-    // Use system local timezone (browser runtime).
-    let d = js_sys::Date::new_0();
-    let y = d.get_full_year();
-    let m = d.get_month() + 1;
-    let day = d.get_date();
-    format!("{:04}{:02}{:02}", y, m, day)
-}
+use crate::models::{
+    Database, DbName, DeletedItem, DeletedItemKind, Nav, Note, NoteFilter, NoteSort, NoteTemplate,
+    NoteTitleFormat, PaletteAction, PaletteCommand, PaletteItem, PartialNav, RecentDb, RecentNote,
+    Theme,
+};
 
 pub(crate) fn next_available_daily_note_title_for_date(
     base: &str,
@@ -42,8 +37,268 @@ pub(crate) fn next_available_daily_note_title_for_date(
     format!("{}-{}", base, max_suffix.saturating_add(1))
 }
 
-pub(crate) fn next_available_daily_note_title(existing_notes: &[Note]) -> String {
-    next_available_daily_note_title_for_date(&today_yyyymmdd_local(), existing_notes)
+/// Base title for a new note on the given (local) date.
+pub(crate) fn format_new_note_title(format: NoteTitleFormat, y: u32, m: u32, d: u32) -> String {
+    match format {
+        NoteTitleFormat::Compact => format!("{:04}{:02}{:02}", y, m, d),
+        NoteTitleFormat::IsoDate => format!("{:04}-{:02}-{:02}", y, m, d),
+        NoteTitleFormat::Untitled => "Untitled".to_string(),
+    }
+}
+
+pub(crate) fn next_available_daily_note_title(
+    existing_notes: &[Note],
+    format: NoteTitleFormat,
+) -> String {
+    let d = js_sys::Date::new_0();
+    let base = format_new_note_title(format, d.get_full_year(), d.get_month() + 1, d.get_date());
+    next_available_daily_note_title_for_date(&base, existing_notes)
+}
+
+/// Today's local date as `YYYYMMDD`, the title of the "Today" daily note.
+pub(crate) fn today_yyyymmdd_local() -> String {
+    let d = js_sys::Date::new_0();
+    format_new_note_title(
+        NoteTitleFormat::Compact,
+        d.get_full_year(),
+        d.get_month() + 1,
+        d.get_date(),
+    )
+}
+
+/// Local `YYYY-MM-DD` for `d`.
+fn iso_date_local(d: &js_sys::Date) -> String {
+    format_new_note_title(
+        NoteTitleFormat::IsoDate,
+        d.get_full_year(),
+        d.get_month() + 1,
+        d.get_date(),
+    )
+}
+
+/// Today's local date as `YYYY-MM-DD`.
+pub(crate) fn today_iso_local() -> String {
+    iso_date_local(&js_sys::Date::new_0())
+}
+
+/// Local date (`YYYY-MM-DD`) of this week's Monday.
+pub(crate) fn this_week_start_local() -> String {
+    let d = js_sys::Date::new_0();
+    let days_since_monday = (d.get_day() + 6) % 7;
+    let monday = js_sys::Date::new_with_year_month_day(
+        d.get_full_year(),
+        d.get_month() as i32,
+        d.get_date() as i32 - days_since_monday as i32,
+    );
+    iso_date_local(&monday)
+}
+
+/// Local date (`YYYY-MM-DD`) of the first day of this month.
+pub(crate) fn this_month_start_local() -> String {
+    let d = js_sys::Date::new_0();
+    format_new_note_title(
+        NoteTitleFormat::IsoDate,
+        d.get_full_year(),
+        d.get_month() + 1,
+        1,
+    )
+}
+
+/// Current local time as `HH:MM`.
+pub(crate) fn now_hhmm_local() -> String {
+    let d = js_sys::Date::new_0();
+    format!("{:02}:{:02}", d.get_hours(), d.get_minutes())
+}
+
+/// Inclusive `YYYY-MM-DD` bounds of `filter`, given the local dates of today and of the
+/// start of this week and month; `None` for `All`. An empty bound is open: presets leave the
+/// end open so a server timestamp a few hours ahead (UTC) still counts as today.
+pub(crate) fn note_filter_bounds(
+    filter: &NoteFilter,
+    today: &str,
+    week_start: &str,
+    month_start: &str,
+) -> Option<(String, String)> {
+    let from = match filter {
+        NoteFilter::All => return None,
+        NoteFilter::Today => today,
+        NoteFilter::ThisWeek => week_start,
+        NoteFilter::ThisMonth => month_start,
+        NoteFilter::Custom(from, to) => return Some((from.clone(), to.clone())),
+    };
+    Some((from.to_string(), String::new()))
+}
+
+/// `note_filter_bounds` for the current local date.
+pub(crate) fn note_filter_bounds_now(filter: &NoteFilter) -> Option<(String, String)> {
+    note_filter_bounds(
+        filter,
+        &today_iso_local(),
+        &this_week_start_local(),
+        &this_month_start_local(),
+    )
+}
+
+/// Notes whose `updated_at` day falls within `bounds` (see `note_filter_bounds`), in order.
+/// Notes without a parseable date are dropped once a range applies.
+pub(crate) fn filter_notes_by_updated(
+    notes: &[Note],
+    bounds: Option<&(String, String)>,
+) -> Vec<Note> {
+    let Some((from, to)) = bounds else {
+        return notes.to_vec();
+    };
+    notes
+        .iter()
+        .filter(|n| {
+            let Some(day) = n.updated_at.get(..10) else {
+                return false;
+            };
+            (from.is_empty() || day >= from.as_str()) && (to.is_empty() || day <= to.as_str())
+        })
+        .cloned()
+        .collect()
+}
+
+/// Sort key of a daily note title: `(YYYYMMDD, n)` for `20240131` (n = 1) or a same-day
+/// `20240131-n` (see `next_available_daily_note_title_for_date`).
+pub(crate) fn daily_note_key(title: &str) -> Option<(u32, u32)> {
+    let t = title.trim();
+    let (date, n) = match t.split_once('-') {
+        Some((date, n)) => (date, n.parse::<u32>().ok().filter(|n| *n >= 2)?),
+        None => (t, 1),
+    };
+    if date.len() != 8 || !date.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let ymd: u32 = date.parse().ok()?;
+    let (m, d) = (ymd / 100 % 100, ymd % 100);
+    ((1..=12).contains(&m) && (1..=31).contains(&d)).then_some((ymd, n))
+}
+
+/// Daily notes of `db_id` (titled `YYYYMMDD`), newest first.
+pub(crate) fn daily_notes(notes: &[Note], db_id: &str) -> Vec<Note> {
+    let mut out = notes
+        .iter()
+        .filter(|n| n.database_id == db_id)
+        .filter_map(|n| daily_note_key(&n.title).map(|k| (k, n.clone())))
+        .collect::<Vec<_>>();
+    out.sort_by(|a, b| b.0.cmp(&a.0));
+    out.into_iter().map(|(_, n)| n).collect()
+}
+
+/// The note in `db_id` titled exactly `title` (ignoring surrounding whitespace).
+pub(crate) fn find_note_by_title<'a>(
+    notes: &'a [Note],
+    db_id: &str,
+    title: &str,
+) -> Option<&'a Note> {
+    let title = title.trim();
+    notes
+        .iter()
+        .find(|n| n.database_id == db_id && n.title.trim() == title)
+}
+
+/// `title` cut to at most `max_chars` characters, ending in "…" when shortened.
+pub(crate) fn truncate_title(title: &str, max_chars: usize) -> String {
+    if title.chars().count() <= max_chars {
+        return title.to_string();
+    }
+    let mut out: String = title.chars().take(max_chars.saturating_sub(1)).collect();
+    out.truncate(out.trim_end().len());
+    out.push('…');
+    out
+}
+
+/// Title offered by the search page's "Create note" row: the trimmed query, unless a note in
+/// `db_id` already has exactly that title.
+pub(crate) fn search_create_title(notes: &[Note], db_id: &str, query: &str) -> Option<String> {
+    let title = query.trim();
+    (!title.is_empty() && find_note_by_title(notes, db_id, title).is_none())
+        .then(|| title.to_string())
+}
+
+/// Most note titles suggested under the sidebar search input.
+pub(crate) const SEARCH_SUGGESTION_LIMIT: usize = 8;
+
+/// Notes in `db_id` whose title contains `query` (case-insensitive), in list order, at most
+/// `SEARCH_SUGGESTION_LIMIT`. Empty for a blank query.
+pub(crate) fn search_title_suggestions(notes: &[Note], db_id: &str, query: &str) -> Vec<Note> {
+    let q = query.trim().to_lowercase();
+    if q.is_empty() {
+        return vec![];
+    }
+    notes
+        .iter()
+        .filter(|n| n.database_id == db_id && n.title.to_lowercase().contains(&q))
+        .take(SEARCH_SUGGESTION_LIMIT)
+        .cloned()
+        .collect()
+}
+
+/// Key of `search_results_cache`: the database and the trimmed query.
+pub(crate) fn search_cache_key(db_id: &str, query: &str) -> String {
+    format!("{db_id}:{}", query.trim())
+}
+
+/// Whether an open note's title field should take a title changed elsewhere (`server`):
+/// only when the field is empty or has no unsaved edits (`current == original`).
+pub(crate) fn should_follow_server_title(current: &str, original: &str, server: &str) -> bool {
+    current.trim().is_empty() || (current == original && server != original)
+}
+
+/// Append a page of notes to an already loaded list, skipping ids that are already present.
+pub(crate) fn merge_notes_page(existing: &mut Vec<Note>, page: Vec<Note>) {
+    for n in page {
+        if !existing.iter().any(|e| e.id == n.id) {
+            existing.push(n);
+        }
+    }
+}
+
+/// Database id from an in-app path (`/db/:db_id`, `/db/:db_id/note/...`).
+pub(crate) fn db_id_from_path(path: &str) -> Option<String> {
+    let id = path.strip_prefix("/db/")?.split('/').next()?.trim();
+    if id.is_empty() {
+        None
+    } else {
+        Some(id.to_string())
+    }
+}
+
+/// Note id from an in-app note path (`/db/:db_id/note/:note_id`).
+pub(crate) fn note_id_from_path(path: &str) -> Option<String> {
+    let mut parts = path.strip_prefix("/db/")?.split('/').skip(1);
+    if parts.next()? != "note" {
+        return None;
+    }
+    let id = parts.next()?.trim();
+    if id.is_empty() {
+        None
+    } else {
+        Some(id.to_string())
+    }
+}
+
+/// Browser tab title, "Note – Database – Hulunote" (unknown parts left out), prefixed with
+/// "• " while edits are waiting to sync.
+pub(crate) fn document_title(
+    note_title: Option<&str>,
+    db_name: Option<&str>,
+    pending: bool,
+) -> String {
+    let title = [note_title, db_name, Some("Hulunote")]
+        .into_iter()
+        .flatten()
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join(" – ");
+    if pending {
+        format!("• {title}")
+    } else {
+        title
+    }
 }
 
 /// Special *parent id* value used by backend to mark the (hidden) ROOT container node.
@@ -53,6 +308,282 @@ pub(crate) fn next_available_daily_note_title(existing_notes: &[Note]) -> String
 /// - Real top-level nodes have `parid == <root_container.id>` (not all-zero).
 pub(crate) const ROOT_CONTAINER_PARENT_ID: &str = "00000000-0000-0000-0000-000000000000";
 
+/// Non-deleted navs reachable from the ROOT container, parents before children and
+/// siblings by `same_deep_order` (the order in which a copy must recreate them).
+pub(crate) fn nav_copy_order(navs: &[Nav]) -> Vec<&Nav> {
+    use crate::outline::NavTree;
+
+    fn walk<'a>(tree: &NavTree<'a>, parid: &str, out: &mut Vec<&'a Nav>) {
+        for n in tree.children(parid) {
+            out.push(n);
+            walk(tree, &n.id, out);
+        }
+    }
+
+    let mut out = vec![];
+    walk(&NavTree::new(navs), ROOT_CONTAINER_PARENT_ID, &mut out);
+    out
+}
+
+/// A note's outline including soft-deleted blocks, as `(depth, nav)` in display order (for
+/// the Trash view). ROOT containers are left out; their children are at depth 0.
+pub(crate) fn trash_outline(navs: &[Nav]) -> Vec<(usize, &Nav)> {
+    fn walk<'a>(navs: &'a [Nav], parid: &str, depth: usize, out: &mut Vec<(usize, &'a Nav)>) {
+        // A parid cycle would never end; no real outline is this deep.
+        if depth > navs.len() {
+            return;
+        }
+        let mut children = navs.iter().filter(|n| n.parid == parid).collect::<Vec<_>>();
+        children.sort_by(|a, b| {
+            a.same_deep_order
+                .partial_cmp(&b.same_deep_order)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        for n in children {
+            out.push((depth, n));
+            walk(navs, &n.id, depth + 1, out);
+        }
+    }
+
+    let mut out = vec![];
+    for root in navs.iter().filter(|n| n.parid == ROOT_CONTAINER_PARENT_ID) {
+        walk(navs, &root.id, 0, &mut out);
+    }
+    out
+}
+
+/// A note's live outline as `(depth, nav)` in display order (for the read-only public
+/// viewer). Deleted blocks are left out with their subtrees; ROOT containers are too.
+pub(crate) fn live_outline(navs: &[Nav]) -> Vec<(usize, &Nav)> {
+    let mut out: Vec<(usize, &Nav)> = vec![];
+    // `trash_outline` lists parents right before their subtrees, so a deleted block's
+    // subtree is every following entry deeper than it.
+    let mut skip_deeper_than: Option<usize> = None;
+    for (depth, n) in trash_outline(navs) {
+        if skip_deeper_than.is_some_and(|d| depth > d) {
+            continue;
+        }
+        skip_deeper_than = None;
+        if n.is_delete {
+            skip_deeper_than = Some(depth);
+            continue;
+        }
+        out.push((depth, n));
+    }
+    out
+}
+
+/// Link to the read-only viewer of a public database.
+pub(crate) fn public_database_url(origin: &str, db_id: &str) -> String {
+    format!("{}/public/{db_id}", origin.trim_end_matches('/'))
+}
+
+/// Soft-deleted ancestors of `nav_id`, nearest first, up to the first live one (ROOT
+/// containers never count).
+pub(crate) fn deleted_ancestor_ids(navs: &[Nav], nav_id: &str) -> Vec<String> {
+    let mut out: Vec<String> = vec![];
+    let mut cur = navs.iter().find(|n| n.id == nav_id);
+    while let Some(parent) = cur.and_then(|n| navs.iter().find(|p| p.id == n.parid)) {
+        if !parent.is_delete || parent.parid == ROOT_CONTAINER_PARENT_ID || out.contains(&parent.id)
+        {
+            break;
+        }
+        out.push(parent.id.clone());
+        cur = Some(parent);
+    }
+    out
+}
+
+/// Collapsed ancestors of `nav_id`, nearest first: the blocks to expand so it is visible
+/// (ROOT containers never count).
+pub(crate) fn collapsed_ancestor_ids(navs: &[Nav], nav_id: &str) -> Vec<String> {
+    let mut out: Vec<String> = vec![];
+    let mut seen: Vec<&str> = vec![nav_id];
+    let mut cur = navs.iter().find(|n| n.id == nav_id);
+    while let Some(parent) = cur.and_then(|n| navs.iter().find(|p| p.id == n.parid)) {
+        if parent.parid == ROOT_CONTAINER_PARENT_ID || seen.contains(&parent.id.as_str()) {
+            break;
+        }
+        seen.push(&parent.id);
+        if !parent.is_display {
+            out.push(parent.id.clone());
+        }
+        cur = Some(parent);
+    }
+    out
+}
+
+/// Nav id of a `#nav-<id>` deep link (the row element ids in `OutlineEditor`).
+pub(crate) fn nav_id_from_fragment(hash: &str) -> Option<String> {
+    let raw = hash
+        .strip_prefix('#')
+        .unwrap_or(hash)
+        .strip_prefix("nav-")?;
+    let id = urlencoding::decode(raw)
+        .map(|s| s.into_owned())
+        .unwrap_or_else(|_| raw.to_string());
+    let id = id.trim();
+    (!id.is_empty()).then(|| id.to_string())
+}
+
+/// Navs to undelete when restoring `nav_id`, parents first: its deleted ancestors (when
+/// `with_ancestors`), the block itself, then its deleted descendants.
+pub(crate) fn restore_nav_ids(navs: &[Nav], nav_id: &str, with_ancestors: bool) -> Vec<String> {
+    fn descendants(navs: &[Nav], parid: &str, out: &mut Vec<String>) {
+        for n in navs.iter().filter(|n| n.parid == parid) {
+            if n.is_delete && !out.contains(&n.id) {
+                out.push(n.id.clone());
+                descendants(navs, &n.id, out);
+            }
+        }
+    }
+
+    let mut out = if with_ancestors {
+        let mut ancestors = deleted_ancestor_ids(navs, nav_id);
+        ancestors.reverse();
+        ancestors
+    } else {
+        vec![]
+    };
+    out.push(nav_id.to_string());
+    descendants(navs, nav_id, &mut out);
+    out
+}
+
+/// Where a block restored "to the top level" goes: the ROOT container (or the ROOT id when
+/// the note has none), after its last live child.
+pub(crate) fn top_level_restore_target(navs: &[Nav]) -> (String, f32) {
+    let root = navs
+        .iter()
+        .find(|n| n.parid == ROOT_CONTAINER_PARENT_ID && !n.is_delete)
+        .map(|n| n.id.clone())
+        .unwrap_or_else(|| ROOT_CONTAINER_PARENT_ID.to_string());
+    let order = navs
+        .iter()
+        .filter(|n| n.parid == root && !n.is_delete)
+        .map(|n| n.same_deep_order)
+        .fold(0.0, f32::max);
+    (root, order + 1.0)
+}
+
+/// Template blocks parents first, siblings by `same_deep_order` (the order in which they
+/// must be created). Blocks whose parent is missing are treated as top-level.
+pub(crate) fn template_nav_order(navs: &[PartialNav]) -> Vec<&PartialNav> {
+    fn by_order(a: &&PartialNav, b: &&PartialNav) -> std::cmp::Ordering {
+        a.same_deep_order
+            .partial_cmp(&b.same_deep_order)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    }
+
+    fn walk<'a>(navs: &'a [PartialNav], parid: &str, out: &mut Vec<&'a PartialNav>) {
+        let mut children = navs.iter().filter(|n| n.parid == parid).collect::<Vec<_>>();
+        children.sort_by(by_order);
+        for n in children {
+            out.push(n);
+            if !n.id.is_empty() {
+                walk(navs, &n.id, out);
+            }
+        }
+    }
+
+    let mut top = navs
+        .iter()
+        .filter(|n| n.parid.is_empty() || !navs.iter().any(|p| p.id == n.parid))
+        .collect::<Vec<_>>();
+    top.sort_by(by_order);
+
+    let mut out = vec![];
+    for n in top {
+        out.push(n);
+        if !n.id.is_empty() {
+            walk(navs, &n.id, &mut out);
+        }
+    }
+    out
+}
+
+/// Parse the template editor's text: one block per line, nested by indentation (two spaces
+/// or a tab per level). A leading `- ` bullet is optional; blank lines are skipped.
+pub(crate) fn template_navs_from_outline(text: &str) -> Vec<PartialNav> {
+    let mut out: Vec<PartialNav> = vec![];
+    // Ids of the current ancestors, one per depth.
+    let mut stack: Vec<String> = vec![];
+
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.trim().is_empty() {
+            continue;
+        }
+        let indent = &line[..line.len() - trimmed.len()];
+        let depth = indent
+            .chars()
+            .map(|c| if c == '\t' { 2 } else { 1 })
+            .sum::<usize>()
+            / 2;
+        let depth = depth.min(stack.len());
+        stack.truncate(depth);
+
+        let content = trimmed.strip_prefix("- ").unwrap_or(trimmed).trim_end();
+        let parid = stack.last().cloned().unwrap_or_default();
+        let order = out.iter().filter(|n| n.parid == parid).count() + 1;
+        let id = format!("t{}", out.len() + 1);
+        out.push(PartialNav {
+            id: id.clone(),
+            parid,
+            same_deep_order: order as f32,
+            content: content.to_string(),
+        });
+        stack.push(id);
+    }
+    out
+}
+
+/// `template` with the `{{date}}` (`YYYY-MM-DD`) and `{{time}}` (`HH:MM`) placeholders of its
+/// blocks filled in.
+pub(crate) fn expand_template(template: &NoteTemplate, date: &str, time: &str) -> NoteTemplate {
+    NoteTemplate {
+        name: template.name.clone(),
+        navs: template
+            .navs
+            .iter()
+            .map(|n| PartialNav {
+                content: n
+                    .content
+                    .replace("{{date}}", date)
+                    .replace("{{time}}", time),
+                ..n.clone()
+            })
+            .collect(),
+    }
+}
+
+/// `expand_template` at the current local date and time (insertion time).
+pub(crate) fn expand_template_now(template: &NoteTemplate) -> NoteTemplate {
+    expand_template(template, &today_iso_local(), &now_hhmm_local())
+}
+
+/// Inverse of `template_navs_from_outline`, for editing a saved template.
+pub(crate) fn template_outline_text(template: &NoteTemplate) -> String {
+    let navs = &template.navs;
+    let depth_of = |n: &PartialNav| {
+        let mut depth = 0;
+        let mut parid = n.parid.as_str();
+        while let Some(p) = navs.iter().find(|p| p.id == parid) {
+            depth += 1;
+            parid = p.parid.as_str();
+            if depth > navs.len() {
+                break;
+            }
+        }
+        depth
+    };
+    template_nav_order(navs)
+        .into_iter()
+        .map(|n| format!("{}{}", "  ".repeat(depth_of(n)), n.content))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 /// Cheap UUID format check (xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx).
 /// Used to distinguish backend ids from local optimistic tmp ids without relying on a magic prefix.
 pub(crate) fn is_uuid_like(s: &str) -> bool {
@@ -81,6 +612,654 @@ pub(crate) fn is_uuid_like(s: &str) -> bool {
     true
 }
 
+/// Id of the nav a create just added on the server, for create responses that didn't carry
+/// it: the one server nav at `parid`/`order` that isn't among `known_ids`. `None` if there
+/// is no such nav or more than one.
+pub(crate) fn created_nav_id(
+    server: &[Nav],
+    known_ids: &[String],
+    parid: &str,
+    order: f32,
+) -> Option<String> {
+    let mut found = server.iter().filter(|n| {
+        n.parid == parid
+            && (n.same_deep_order - order).abs() < f32::EPSILON
+            && !known_ids.contains(&n.id)
+    });
+    let first = found.next()?;
+    found.next().is_none().then(|| first.id.clone())
+}
+
+/// Decode base64url (padding optional), as used by JWT segments.
+pub(crate) fn decode_base64url(input: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(input.len() * 3 / 4);
+    let mut buf: u32 = 0;
+    let mut bits = 0;
+    for c in input.trim_end_matches('=').bytes() {
+        let v = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'-' | b'+' => 62,
+            b'_' | b'/' => 63,
+            _ => return None,
+        };
+        buf = (buf << 6) | v as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buf >> bits) as u8);
+            buf &= (1 << bits) - 1;
+        }
+    }
+    Some(out)
+}
+
 pub(crate) fn now_ms() -> i64 {
     js_sys::Date::now().round() as i64
 }
+
+/// How long a delete can be undone from the snackbar.
+pub(crate) const UNDO_DELETE_WINDOW_MS: i64 = 30_000;
+
+/// The deleted items whose undo window is still open at `now_ms`.
+pub(crate) fn live_deleted_items(items: &[DeletedItem], now_ms: i64) -> Vec<DeletedItem> {
+    items
+        .iter()
+        .filter(|d| d.expires_ms > now_ms)
+        .cloned()
+        .collect()
+}
+
+/// `DeletedItem::data` for a deleted subtree: the blocks as they were before the delete.
+pub(crate) fn deleted_navs_payload(db_id: &str, note_id: &str, navs: &[Nav]) -> serde_json::Value {
+    serde_json::json!({
+        "database-id": db_id,
+        "note-id": note_id,
+        "navs": navs,
+    })
+}
+
+/// Database id, note id and blocks of a `deleted_navs_payload`.
+pub(crate) fn parse_deleted_navs_payload(
+    data: &serde_json::Value,
+) -> Option<(String, String, Vec<Nav>)> {
+    let db_id = data.get("database-id")?.as_str()?.to_string();
+    let note_id = data.get("note-id")?.as_str()?.to_string();
+    let navs = serde_json::from_value::<Vec<Nav>>(data.get("navs")?.clone()).ok()?;
+    (!navs.is_empty()).then_some((db_id, note_id, navs))
+}
+
+/// Snackbar text for a deleted item, e.g. `Deleted "Inbox"` or `Deleted 3 blocks`.
+pub(crate) fn deleted_item_label(item: &DeletedItem) -> String {
+    match item.kind {
+        DeletedItemKind::Note => {
+            let title = item
+                .data
+                .get("title")
+                .and_then(|t| t.as_str())
+                .map(str::trim)
+                .unwrap_or_default();
+            if title.is_empty() {
+                "Deleted note".to_string()
+            } else {
+                format!("Deleted \"{}\"", truncate_title(title, 40))
+            }
+        }
+        DeletedItemKind::Nav => {
+            let count = item
+                .data
+                .get("navs")
+                .and_then(|v| v.as_array())
+                .map_or(1, |xs| xs.len().max(1));
+            if count == 1 {
+                "Deleted block".to_string()
+            } else {
+                format!("Deleted {count} blocks")
+            }
+        }
+    }
+}
+
+/// Age after which a shown database list is refreshed in the background.
+pub(crate) const DATABASES_STALE_AFTER_MS: f64 = 5.0 * 60.0 * 1000.0;
+
+/// Whether a database list fetched at `loaded_at` should be revalidated at `now`.
+pub(crate) fn databases_need_refresh(loaded_at: Option<f64>, now: f64) -> bool {
+    loaded_at.is_none_or(|t| now - t > DATABASES_STALE_AFTER_MS)
+}
+
+/// How long a note's fetched navs may be reused when it is reopened.
+pub(crate) const NAVS_CACHE_TTL_MS: f64 = 60.0 * 1000.0;
+
+/// Whether navs cached at `loaded_at` can still be served at `now`.
+pub(crate) fn navs_cache_is_fresh(loaded_at: f64, now: f64) -> bool {
+    now - loaded_at < NAVS_CACHE_TTL_MS
+}
+
+/// Relative label for a timestamp age, or `None` once it is older than "yesterday".
+/// Future timestamps (clock skew) count as "just now".
+pub(crate) fn relative_time_label(delta_ms: i64) -> Option<String> {
+    const MINUTE: i64 = 60 * 1000;
+    const HOUR: i64 = 60 * MINUTE;
+    const DAY: i64 = 24 * HOUR;
+
+    let label = if delta_ms < MINUTE {
+        "just now".to_string()
+    } else if delta_ms < HOUR {
+        format!("{} min ago", delta_ms / MINUTE)
+    } else if delta_ms < DAY {
+        let hours = delta_ms / HOUR;
+        if hours == 1 {
+            "1 hour ago".to_string()
+        } else {
+            format!("{} hours ago", hours)
+        }
+    } else if delta_ms < 2 * DAY {
+        "yesterday".to_string()
+    } else {
+        return None;
+    };
+    Some(label)
+}
+
+/// "Jan 3" (`month0` is 0-based, as returned by `Date.getMonth()`).
+pub(crate) fn format_month_day(month0: u32, day: u32) -> String {
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+    let month = MONTHS.get(month0 as usize).copied().unwrap_or("?");
+    format!("{} {}", month, day)
+}
+
+/// Backend timestamp rendered relative to `now_ms` ("5 min ago", "yesterday", "Jan 3").
+/// Unparseable input is returned as-is.
+pub(crate) fn format_relative_time(iso_str: &str, now_ms: i64) -> String {
+    match parse_timestamp_ms(iso_str) {
+        Some(then) => format_relative_ms(then, now_ms),
+        None => iso_str.to_string(),
+    }
+}
+
+/// Epoch milliseconds of a backend timestamp, if it parses.
+pub(crate) fn parse_timestamp_ms(iso_str: &str) -> Option<i64> {
+    // Some backend timestamps use a space instead of `T`, which `Date.parse` may reject.
+    let normalized = iso_str.trim().replacen(' ', "T", 1);
+    let then = js_sys::Date::parse(&normalized);
+    then.is_finite().then_some(then as i64)
+}
+
+/// When a block was last edited: its unsynced local edit (`local_ms`, from the note draft)
+/// or the backend `updated-at`, whichever is later.
+pub(crate) fn nav_edited_ms(nav: &Nav, local_ms: Option<i64>) -> Option<i64> {
+    let server_ms = nav.updated_at.as_deref().and_then(parse_timestamp_ms);
+    server_ms.max(local_ms)
+}
+
+/// Compact hover hint for a block: "edited just now", "edited 5m ago", "edited 3h ago",
+/// "edited 2d ago".
+pub(crate) fn format_edited_ago(then_ms: i64, now_ms: i64) -> String {
+    const MINUTE: i64 = 60 * 1000;
+    const HOUR: i64 = 60 * MINUTE;
+    const DAY: i64 = 24 * HOUR;
+
+    let delta = now_ms - then_ms;
+    if delta < MINUTE {
+        "edited just now".to_string()
+    } else if delta < HOUR {
+        format!("edited {}m ago", delta / MINUTE)
+    } else if delta < DAY {
+        format!("edited {}h ago", delta / HOUR)
+    } else {
+        format!("edited {}d ago", delta / DAY)
+    }
+}
+
+/// Epoch-millisecond timestamp rendered relative to `now_ms` (see [`format_relative_time`]).
+pub(crate) fn format_relative_ms(then_ms: i64, now_ms: i64) -> String {
+    if let Some(label) = relative_time_label(now_ms - then_ms) {
+        return label;
+    }
+    let d = js_sys::Date::new(&wasm_bindgen::JsValue::from_f64(then_ms as f64));
+    format_month_day(d.get_month(), d.get_date())
+}
+
+/// Total character count of a note's outline (non-deleted navs only).
+pub(crate) fn count_note_chars(navs: &[Nav]) -> usize {
+    navs.iter()
+        .filter(|n| !n.is_delete)
+        .map(|n| n.content.chars().count())
+        .sum()
+}
+
+/// Longest note preview shown under a title in the note lists.
+pub(crate) const NOTE_PREVIEW_MAX_CHARS: usize = 80;
+
+/// One-line plain text of a block for list previews: Markdown markers, `[[link]]` brackets,
+/// `((refs))` and task markers dropped, whitespace collapsed, cut to `max_len` characters.
+pub(crate) fn plain_text_preview(content: &str, max_len: usize) -> String {
+    use crate::markdown::{flatten_inline, parse_inline_markdown, InlineRun};
+    use crate::wiki::{parse_wiki_tokens, WikiToken};
+
+    let mut text = String::new();
+    for run in flatten_inline(&parse_inline_markdown(content)) {
+        match run {
+            InlineRun::Text(s, _) => {
+                for token in parse_wiki_tokens(&s) {
+                    match token {
+                        WikiToken::Text(t) | WikiToken::Link(t) => text.push_str(&t),
+                        WikiToken::BlockRef(_) | WikiToken::Todo { .. } => text.push(' '),
+                    }
+                }
+            }
+            InlineRun::Code(s, _) | InlineRun::Url(s, _) => text.push_str(&s),
+        }
+    }
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    truncate_title(&text, max_len)
+}
+
+/// Preview of a note: `plain_text_preview` of its first top-level block (children of the ROOT
+/// container, by order) that has any text. `None` when there is none.
+pub(crate) fn note_preview(navs: &[Nav]) -> Option<String> {
+    use crate::outline::cmp_order;
+
+    let by_order = |a: &&Nav, b: &&Nav| cmp_order(a.same_deep_order, b.same_deep_order);
+    let mut roots = navs
+        .iter()
+        .filter(|n| !n.is_delete && n.parid == ROOT_CONTAINER_PARENT_ID)
+        .collect::<Vec<_>>();
+    roots.sort_by(by_order);
+    // Legacy notes without a single container: their ROOT-level blocks are the top level.
+    let container = match roots.as_slice() {
+        [container] => Some(*container),
+        _ => None,
+    };
+    let top = match container {
+        Some(container) => {
+            let mut xs = navs
+                .iter()
+                .filter(|n| !n.is_delete && n.parid == container.id)
+                .collect::<Vec<_>>();
+            xs.sort_by(by_order);
+            xs
+        }
+        None => roots,
+    };
+    top.into_iter()
+        .map(|n| plain_text_preview(&n.content, NOTE_PREVIEW_MAX_CHARS))
+        .find(|p| !p.is_empty())
+}
+
+/// Preview line for a note list row from the per-database `previews`. Locked notes get
+/// none: their content stays behind the PIN.
+pub(crate) fn shown_note_preview(
+    previews: &std::collections::HashMap<String, std::collections::HashMap<String, String>>,
+    db_id: &str,
+    note_id: &str,
+    locked: bool,
+) -> Option<String> {
+    if locked {
+        return None;
+    }
+    previews.get(db_id)?.get(note_id).cloned()
+}
+
+/// `note_preview` per note id for the navs of a whole database; notes without one are left
+/// out.
+pub(crate) fn note_previews(navs: &[Nav]) -> std::collections::HashMap<String, String> {
+    let mut by_note: std::collections::HashMap<&str, Vec<Nav>> = std::collections::HashMap::new();
+    for n in navs {
+        by_note
+            .entry(n.note_id.as_str())
+            .or_default()
+            .push(n.clone());
+    }
+    by_note
+        .into_iter()
+        .filter_map(|(note_id, navs)| note_preview(&navs).map(|p| (note_id.to_string(), p)))
+        .collect()
+}
+
+/// Block and word counts of a note's outline (non-deleted navs, ROOT container excluded).
+///
+/// Words are whitespace-separated runs. `editing` (nav id, content) stands in for the saved
+/// content of the block being typed in.
+pub(crate) fn outline_stats(navs: &[Nav], editing: Option<(&str, &str)>) -> (usize, usize) {
+    navs.iter()
+        .filter(|n| !n.is_delete && n.parid != ROOT_CONTAINER_PARENT_ID)
+        .map(|n| match editing {
+            Some((id, content)) if id == n.id => content,
+            _ => n.content.as_str(),
+        })
+        .fold((0, 0), |(nodes, words), content| {
+            (nodes + 1, words + content.split_whitespace().count())
+        })
+}
+
+/// Stat line under the note title, e.g. "42 nodes · ~350 words".
+pub(crate) fn format_outline_stats(nodes: usize, words: usize) -> String {
+    let plural =
+        |n: usize, one: &str, many: &str| format!("{} {}", n, if n == 1 { one } else { many });
+    format!(
+        "{} · ~{}",
+        plural(nodes, "node", "nodes"),
+        plural(words, "word", "words")
+    )
+}
+
+/// Short size badge for note lists, rounded to the nearest hundred (e.g. "~1.2k chars").
+pub(crate) fn format_char_count_badge(count: usize) -> String {
+    if count < 100 {
+        return format!("{} chars", count);
+    }
+
+    let rounded = (count + 50) / 100 * 100;
+    if rounded < 1000 {
+        return format!("~{} chars", rounded);
+    }
+
+    let k = format!("{:.1}", rounded as f64 / 1000.0);
+    let k = k.strip_suffix(".0").unwrap_or(&k);
+    format!("~{}k chars", k)
+}
+
+/// Sort notes in place. Timestamps are ISO strings, so they compare lexicographically;
+/// ties keep their current (backend) order.
+pub(crate) fn sort_notes(notes: &mut [Note], sort: NoteSort) {
+    match sort {
+        NoteSort::UpdatedAtDesc => notes.sort_by(|a, b| b.updated_at.cmp(&a.updated_at)),
+        NoteSort::CreatedAtDesc => notes.sort_by(|a, b| b.created_at.cmp(&a.created_at)),
+        NoteSort::TitleAsc => notes.sort_by_cached_key(|n| n.title.to_lowercase()),
+    }
+}
+
+/// Split notes into (pinned, rest). Pinned notes keep the pin order; the rest keep their order.
+pub(crate) fn partition_pinned_notes(
+    notes: Vec<Note>,
+    pinned_ids: &[String],
+) -> (Vec<Note>, Vec<Note>) {
+    let (mut pinned, rest): (Vec<Note>, Vec<Note>) = notes
+        .into_iter()
+        .partition(|n| pinned_ids.iter().any(|id| id == &n.id));
+    pinned.sort_by_key(|n| pinned_ids.iter().position(|id| id == &n.id));
+    (pinned, rest)
+}
+
+/// Databases whose name contains `query` (case-insensitive), in list order.
+pub(crate) fn filter_databases(dbs: &[Database], query: &str) -> Vec<Database> {
+    let q = query.trim().to_lowercase();
+    if q.is_empty() {
+        return dbs.to_vec();
+    }
+    dbs.iter()
+        .filter(|d| d.name.to_lowercase().contains(&q))
+        .cloned()
+        .collect()
+}
+
+/// Database called `name` (exact match after trimming; the backend names them itself).
+pub(crate) fn find_database_by_name<'a>(dbs: &'a [Database], name: &str) -> Option<&'a Database> {
+    let name = name.trim();
+    dbs.iter().find(|d| d.name.trim() == name)
+}
+
+/// `update-database` calls (database id, `is-default`) that toggle `id` as the default.
+///
+/// Marking a database clears every other default first, so at most one stays marked even
+/// if the backend doesn't enforce it; toggling the current default just unsets it.
+pub(crate) fn default_database_updates(dbs: &[Database], id: &str) -> Vec<(String, bool)> {
+    if dbs.iter().any(|d| d.id == id && d.is_default) {
+        return vec![(id.to_string(), false)];
+    }
+    dbs.iter()
+        .filter(|d| d.is_default && d.id != id)
+        .map(|d| (d.id.clone(), false))
+        .chain(std::iter::once((id.to_string(), true)))
+        .collect()
+}
+
+/// Notes whose id is in `ids`, in list order (e.g. a bulk selection).
+pub(crate) fn notes_with_ids(notes: &[Note], ids: &std::collections::HashSet<String>) -> Vec<Note> {
+    notes
+        .iter()
+        .filter(|n| ids.contains(&n.id))
+        .cloned()
+        .collect()
+}
+
+/// `/login?next=<current>` so the login page can return the user where the session ended.
+pub(crate) fn login_url_with_next(current: &str) -> String {
+    if safe_next_path(Some(current)) == "/" {
+        return "/login".to_string();
+    }
+    let mut encoded = String::with_capacity(current.len());
+    for b in current.bytes() {
+        if b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.' | b'~' | b'/') {
+            encoded.push(b as char);
+        } else {
+            encoded.push_str(&format!("%{b:02X}"));
+        }
+    }
+    format!("/login?next={encoded}")
+}
+
+/// Where to go after logging in: `next` when it is a same-origin app path, otherwise `/`.
+pub(crate) fn safe_next_path(next: Option<&str>) -> String {
+    match next.map(str::trim) {
+        Some(p)
+            if p.starts_with('/')
+                && !p.starts_with("//")
+                && !p.starts_with("/\\")
+                && !p.starts_with("/login") =>
+        {
+            p.to_string()
+        }
+        _ => "/".to_string(),
+    }
+}
+
+/// Pinned ids that still refer to one of `notes`, in pin order.
+pub(crate) fn prune_pinned_ids(pinned_ids: &[String], notes: &[Note]) -> Vec<String> {
+    pinned_ids
+        .iter()
+        .filter(|id| notes.iter().any(|n| &n.id == *id))
+        .cloned()
+        .collect()
+}
+
+/// PINs for note locks: 4–12 digits.
+pub(crate) fn is_valid_pin(pin: &str) -> bool {
+    (4..=12).contains(&pin.len()) && pin.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// Hex SHA-256 of a note-lock PIN, as kept in `LOCKED_NOTES_KEY`.
+///
+/// Only gates the UI: the note itself is stored unencrypted on the server.
+pub(crate) fn hash_pin(pin: &str) -> String {
+    use sha2::{Digest, Sha256};
+
+    Sha256::digest(format!("hulunote-pin:{pin}").as_bytes())
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+/// Name to show for `db_id`. `loaded` is whether the database list has been fetched: until
+/// then an unknown id may still turn up, afterwards it is gone.
+pub(crate) fn resolve_db_name(dbs: &[Database], loaded: bool, db_id: &str) -> DbName {
+    match dbs.iter().find(|d| d.id == db_id) {
+        Some(d) => DbName::Known(d.name.clone()),
+        None if loaded => DbName::Missing,
+        None => DbName::Loading,
+    }
+}
+
+/// Recent databases that still exist in the server-side `dbs` list, in recency order.
+pub(crate) fn reconcile_recent_dbs(recents: &[RecentDb], dbs: &[Database]) -> Vec<RecentDb> {
+    recents
+        .iter()
+        .filter(|r| dbs.iter().any(|d| d.id == r.id))
+        .cloned()
+        .collect()
+}
+
+/// Quick switcher match rank for a title: 0 = prefix, 1 = substring, 2 = the query's
+/// characters appear in order (fuzzy). `None` when it doesn't match. Case-insensitive.
+pub(crate) fn quick_switch_rank(title: &str, query: &str) -> Option<u8> {
+    let q = query.trim().to_lowercase();
+    if q.is_empty() {
+        return Some(0);
+    }
+    let t = title.to_lowercase();
+    if t.starts_with(&q) {
+        return Some(0);
+    }
+    if t.contains(&q) {
+        return Some(1);
+    }
+
+    let mut chars = t.chars();
+    q.chars()
+        .filter(|c| !c.is_whitespace())
+        .all(|qc| chars.any(|tc| tc == qc))
+        .then_some(2)
+}
+
+/// Databases matching `query` for the database switcher, best `quick_switch_rank` first and
+/// in list order among equal ranks. An empty query keeps the whole list.
+pub(crate) fn rank_databases(dbs: &[Database], query: &str) -> Vec<Database> {
+    let mut ranked = dbs
+        .iter()
+        .filter_map(|d| quick_switch_rank(&d.name, query).map(|r| (r, d)))
+        .collect::<Vec<_>>();
+    ranked.sort_by_key(|(r, _)| *r);
+    ranked.into_iter().map(|(_, d)| d.clone()).collect()
+}
+
+/// Notes matching `query`, best first (rank, then shorter and alphabetical titles), at most
+/// `limit`. An empty query lists the most recently updated notes.
+pub(crate) fn rank_quick_switch(notes: Vec<Note>, query: &str, limit: usize) -> Vec<Note> {
+    if query.trim().is_empty() {
+        let mut notes = notes;
+        sort_notes(&mut notes, NoteSort::UpdatedAtDesc);
+        notes.truncate(limit);
+        return notes;
+    }
+
+    let mut ranked = notes
+        .into_iter()
+        .filter_map(|n| quick_switch_rank(&n.title, query).map(|r| (r, n)))
+        .collect::<Vec<_>>();
+    ranked.sort_by_cached_key(|(r, n)| (*r, n.title.chars().count(), n.title.to_lowercase()));
+    ranked.into_iter().take(limit).map(|(_, n)| n).collect()
+}
+
+/// Command palette rows matching `query`, at most `limit`. Rows are grouped as commands,
+/// recent notes, databases, then notes (by `rank_quick_switch`); a non-empty query sorts
+/// them by `quick_switch_rank`, keeping that order among equal ranks. Notes already listed
+/// as recent are skipped.
+pub(crate) fn palette_items(
+    query: &str,
+    commands: &[PaletteCommand],
+    recent: &[RecentNote],
+    databases: &[Database],
+    notes: Vec<Note>,
+    limit: usize,
+) -> Vec<PaletteItem> {
+    let db_name = |id: &str| {
+        databases
+            .iter()
+            .find(|d| d.id == id)
+            .map(|d| d.name.clone())
+            .unwrap_or_default()
+    };
+    let open_note = |db_id: &str, note_id: &str| PaletteAction::OpenNote {
+        db_id: db_id.to_string(),
+        note_id: note_id.to_string(),
+    };
+
+    let mut items = commands
+        .iter()
+        .map(|&c| PaletteItem {
+            label: c.label().to_string(),
+            detail: "Command".to_string(),
+            action: PaletteAction::Run(c),
+        })
+        .collect::<Vec<_>>();
+    items.extend(recent.iter().map(|r| PaletteItem {
+        label: r.title.clone(),
+        detail: format!("Recent · {}", db_name(&r.db_id)),
+        action: open_note(&r.db_id, &r.note_id),
+    }));
+    items.extend(databases.iter().map(|d| PaletteItem {
+        label: d.name.clone(),
+        detail: "Database".to_string(),
+        action: PaletteAction::OpenDatabase(d.id.clone()),
+    }));
+    items.extend(
+        rank_quick_switch(notes, query, limit)
+            .into_iter()
+            .filter(|n| {
+                !recent
+                    .iter()
+                    .any(|r| r.db_id == n.database_id && r.note_id == n.id)
+            })
+            .map(|n| PaletteItem {
+                detail: db_name(&n.database_id),
+                action: open_note(&n.database_id, &n.id),
+                label: n.title,
+            }),
+    );
+
+    if query.trim().is_empty() {
+        items.truncate(limit);
+        return items;
+    }
+
+    let mut ranked = items
+        .into_iter()
+        .filter_map(|item| quick_switch_rank(&item.label, query).map(|r| (r, item)))
+        .collect::<Vec<_>>();
+    ranked.sort_by_key(|(r, _)| *r);
+    ranked
+        .into_iter()
+        .take(limit)
+        .map(|(_, item)| item)
+        .collect()
+}
+
+pub(crate) const PREFERS_DARK_QUERY: &str = "(prefers-color-scheme: dark)";
+
+pub(crate) fn system_prefers_dark() -> bool {
+    web_sys::window()
+        .and_then(|w| w.match_media(PREFERS_DARK_QUERY).ok().flatten())
+        .map(|mq| mq.matches())
+        .unwrap_or(true)
+}
+
+/// Whether the `dark` class should be set on `<html>` for this theme.
+pub(crate) fn theme_is_dark(theme: Theme, system_dark: bool) -> bool {
+    match theme {
+        Theme::Light => false,
+        Theme::Dark => true,
+        Theme::System => system_dark,
+    }
+}
+
+/// Toggle the `dark` class on `<html>` (theme tokens live in `style/tailwind.css`).
+pub(crate) fn apply_theme(theme: Theme) {
+    let Some(root) = web_sys::window()
+        .and_then(|w| w.document())
+        .and_then(|d| d.document_element())
+    else {
+        return;
+    };
+
+    let classes = root.class_list();
+    if theme_is_dark(theme, system_prefers_dark()) {
+        let _ = classes.add_1("dark");
+    } else {
+        let _ = classes.remove_1("dark");
+    }
+}
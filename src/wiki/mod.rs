@@ -1,18 +1,36 @@
+use crate::models::{Nav, NavProperties, Note};
+use std::collections::HashMap;
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub(crate) enum WikiToken {
     Text(String),
     Link(String),
+    /// `((nav-id))` reference to another block.
+    BlockRef(String),
+    /// `{{TODO}}` / `{{DONE}}` task marker at the very start of a block.
+    Todo {
+        done: bool,
+    },
 }
 
+const TODO_MARKER: &str = "{{TODO}}";
+const DONE_MARKER: &str = "{{DONE}}";
+
 /// Parse `[[Wiki Links]]` from plain text.
 ///
 /// Rules (MVP):
 /// - Only `[[...]]` is recognized.
 /// - No nesting; the first `]]` closes the link.
 /// - Unclosed `[[` is treated as plain text.
+/// - Outside links, `((nav-id))` becomes a block reference (see [`parse_block_refs`]).
+/// - A leading `{{TODO}}` / `{{DONE}}` becomes a task marker; elsewhere it is text.
 pub(crate) fn parse_wiki_tokens(input: &str) -> Vec<WikiToken> {
     let mut out: Vec<WikiToken> = Vec::new();
     let mut i = 0;
+    if let Some(done) = todo_marker_state(input) {
+        out.push(WikiToken::Todo { done });
+        i = TODO_MARKER.len();
+    }
     let bytes = input.as_bytes();
 
     while i < bytes.len() {
@@ -29,13 +47,13 @@ pub(crate) fn parse_wiki_tokens(input: &str) -> Vec<WikiToken> {
 
         let Some(link_start) = start else {
             if i < bytes.len() {
-                out.push(WikiToken::Text(input[i..].to_string()));
+                out.extend(parse_block_refs(&input[i..]));
             }
             break;
         };
 
         if link_start > i {
-            out.push(WikiToken::Text(input[i..link_start].to_string()));
+            out.extend(parse_block_refs(&input[i..link_start]));
         }
 
         // Find closing `]]`
@@ -50,8 +68,9 @@ pub(crate) fn parse_wiki_tokens(input: &str) -> Vec<WikiToken> {
         }
 
         let Some(link_end) = end else {
-            // Unclosed link: treat the rest as text.
-            out.push(WikiToken::Text(input[link_start..].to_string()));
+            // Unclosed link: treat the `[[` as text, keep scanning the rest for block refs.
+            out.push(WikiToken::Text("[[".to_string()));
+            out.extend(parse_block_refs(&input[link_start + 2..]));
             break;
         };
 
@@ -63,6 +82,135 @@ pub(crate) fn parse_wiki_tokens(input: &str) -> Vec<WikiToken> {
     out
 }
 
+/// Split `((nav-id))` block references out of plain text.
+///
+/// The id must be non-empty and contain no whitespace or parentheses; anything else
+/// (e.g. `((some words))` or an unclosed `((`) stays text.
+pub(crate) fn parse_block_refs(input: &str) -> Vec<WikiToken> {
+    let mut out: Vec<WikiToken> = Vec::new();
+    let mut text_start = 0;
+    let mut i = 0;
+
+    while let Some(off) = input[i..].find("((") {
+        let start = i + off;
+        let id_start = start + 2;
+        let id_len = input[id_start..]
+            .find(|c: char| c.is_whitespace() || c == '(' || c == ')')
+            .unwrap_or(input.len() - id_start);
+        let id_end = id_start + id_len;
+
+        if id_len == 0 || !input[id_end..].starts_with("))") {
+            i = start + 1;
+            continue;
+        }
+
+        if start > text_start {
+            out.push(WikiToken::Text(input[text_start..start].to_string()));
+        }
+        out.push(WikiToken::BlockRef(input[id_start..id_end].to_string()));
+        i = id_end + 2;
+        text_start = i;
+    }
+
+    if text_start < input.len() {
+        out.push(WikiToken::Text(input[text_start..].to_string()));
+    }
+    out
+}
+
+fn todo_marker_state(content: &str) -> Option<bool> {
+    if content.starts_with(TODO_MARKER) {
+        Some(false)
+    } else if content.starts_with(DONE_MARKER) {
+        Some(true)
+    } else {
+        None
+    }
+}
+
+/// Task state of a block (`Some(done)` when it starts with `{{TODO}}` / `{{DONE}}`) and its
+/// content after the marker, without the single space that follows it.
+pub(crate) fn split_todo(content: &str) -> (Option<bool>, &str) {
+    match todo_marker_state(content) {
+        Some(done) => {
+            let rest = &content[TODO_MARKER.len()..];
+            (Some(done), rest.strip_prefix(' ').unwrap_or(rest))
+        }
+        None => (None, content),
+    }
+}
+
+/// `content` as a task that is `done` or open; a block without a marker becomes a task.
+pub(crate) fn set_todo_done(content: &str, done: bool) -> String {
+    let marker = if done { DONE_MARKER } else { TODO_MARKER };
+    let (_, rest) = split_todo(content);
+    if rest.is_empty() {
+        marker.to_string()
+    } else {
+        format!("{marker} {rest}")
+    }
+}
+
+/// Cmd/Ctrl+Enter cycle: plain block → `{{TODO}}` → `{{DONE}}` → plain block.
+pub(crate) fn cycle_todo(content: &str) -> String {
+    match split_todo(content) {
+        (None, _) => set_todo_done(content, false),
+        (Some(false), _) => set_todo_done(content, true),
+        (Some(true), rest) => rest.to_string(),
+    }
+}
+
+/// Number of live blocks that are open `{{TODO}}` tasks.
+pub(crate) fn count_open_todos(navs: &[Nav]) -> usize {
+    navs.iter()
+        .filter(|n| !n.is_delete && todo_marker_state(&n.content) == Some(false))
+        .count()
+}
+
+/// Nav ids referenced via `((...))`, in order of appearance.
+pub(crate) fn extract_block_refs(input: &str) -> Vec<String> {
+    parse_wiki_tokens(input)
+        .into_iter()
+        .filter_map(|t| match t {
+            WikiToken::BlockRef(id) => Some(id),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The live (non-deleted) nav with `id`, if any.
+pub(crate) fn find_block<'a>(navs: &'a [Nav], id: &str) -> Option<&'a Nav> {
+    navs.iter().find(|n| n.id == id && !n.is_delete)
+}
+
+/// Blocks whose content contains `query` (case-insensitive), for `((` autocomplete.
+///
+/// Skips deleted and empty blocks as well as `exclude_id` (the block being edited).
+/// Prefix matches come first; otherwise the input order is kept.
+pub(crate) fn search_blocks<'a>(
+    navs: &'a [Nav],
+    query: &str,
+    exclude_id: &str,
+    limit: usize,
+) -> Vec<&'a Nav> {
+    let q = query.trim().to_lowercase();
+    let mut hits: Vec<(bool, &Nav)> = navs
+        .iter()
+        .filter(|n| !n.is_delete && n.id != exclude_id && !n.content.trim().is_empty())
+        .filter_map(|n| {
+            let content = n.content.trim().to_lowercase();
+            if q.is_empty() || content.contains(&q) {
+                Some((!content.starts_with(&q), n))
+            } else {
+                None
+            }
+        })
+        .collect();
+
+    hits.sort_by_key(|(not_prefix, _)| *not_prefix);
+    hits.into_iter().take(limit).map(|(_, n)| n).collect()
+}
+
 pub(crate) fn extract_wiki_links(input: &str) -> Vec<String> {
     parse_wiki_tokens(input)
         .into_iter()
@@ -80,8 +228,124 @@ pub(crate) fn extract_wiki_links(input: &str) -> Vec<String> {
         .collect()
 }
 
+/// `#tags` in block content: lowercase, deduped, in order of appearance.
+///
+/// A tag starts with `#` at the start of a word and runs over letters, digits, `_` and
+/// `-`; a bare `#` or `##` is not a tag. `#` inside `[[links]]` and `((refs))` is ignored.
+pub(crate) fn extract_tags(input: &str) -> Vec<String> {
+    let mut out: Vec<String> = vec![];
+    for token in parse_wiki_tokens(input) {
+        let WikiToken::Text(text) = token else {
+            continue;
+        };
+
+        let mut prev: Option<char> = None;
+        let mut chars = text.char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            let at_word_start = prev.is_none_or(char::is_whitespace);
+            prev = Some(c);
+            if c != '#' || !at_word_start {
+                continue;
+            }
+
+            let rest = &text[i + 1..];
+            let len = rest
+                .find(|ch: char| !(ch.is_alphanumeric() || ch == '_' || ch == '-'))
+                .unwrap_or(rest.len());
+            if len == 0 {
+                continue;
+            }
+            let tag = rest[..len].to_lowercase();
+            if !out.contains(&tag) {
+                out.push(tag);
+            }
+            while chars.peek().is_some_and(|(j, _)| *j < i + 1 + len) {
+                prev = chars.next().map(|(_, ch)| ch);
+            }
+        }
+    }
+    out
+}
+
+/// Tag -> ids of the notes with a live block carrying it (from `Nav::properties`).
+///
+/// Note ids are listed once per tag, in order of first appearance.
+pub(crate) fn build_tags_index(navs: &[Nav]) -> HashMap<String, Vec<String>> {
+    let mut index: HashMap<String, Vec<String>> = HashMap::new();
+    for nav in navs.iter().filter(|n| !n.is_delete) {
+        for tag in NavProperties::parse(nav.properties.as_deref()).tags {
+            let note_ids = index.entry(tag).or_default();
+            if !note_ids.contains(&nav.note_id) {
+                note_ids.push(nav.note_id.clone());
+            }
+        }
+    }
+    index
+}
+
 pub(crate) fn normalize_roam_page_title(s: &str) -> String {
     // Roam-style uniqueness key (MVP): exact string.
     // Note: Roam historically treats leading/trailing whitespace as distinct (see issue #378).
     s.to_string()
 }
+
+/// Target of a `[[title]]` link: the matching note in `db_id`, or the draft-note route
+/// (`/db/:db_id/note?title=...`) that creates it on first edit.
+pub(crate) fn wiki_link_href(title: &str, db_id: &str, all_notes: &[Note]) -> String {
+    let title_norm = normalize_roam_page_title(title);
+    match all_notes
+        .iter()
+        .find(|n| n.database_id == db_id && normalize_roam_page_title(&n.title) == title_norm)
+    {
+        Some(n) => format!("/db/{}/note/{}", db_id, n.id),
+        None => format!("/db/{}/note?title={}", db_id, urlencoding::encode(title)),
+    }
+}
+
+/// Notes whose navs link to `current_title` via `[[...]]` (case-insensitive), each with the
+/// matching navs.
+///
+/// Deleted navs are ignored. A linking note missing from `all_notes` (e.g. not loaded yet)
+/// is returned with its id as title. Results are sorted by note title.
+pub(crate) fn find_backlinks(
+    all_navs: &[Nav],
+    all_notes: &[Note],
+    current_title: &str,
+) -> Vec<(Note, Vec<Nav>)> {
+    let target = current_title.trim().to_lowercase();
+    if target.is_empty() {
+        return vec![];
+    }
+
+    let mut groups: Vec<(Note, Vec<Nav>)> = vec![];
+    for nav in all_navs.iter().filter(|n| !n.is_delete) {
+        let links_here = extract_wiki_links(&nav.content)
+            .iter()
+            .any(|l| l.trim().to_lowercase() == target);
+        if !links_here {
+            continue;
+        }
+
+        match groups.iter_mut().find(|(note, _)| note.id == nav.note_id) {
+            Some((_, navs)) => navs.push(nav.clone()),
+            None => {
+                let note = all_notes
+                    .iter()
+                    .find(|n| n.id == nav.note_id)
+                    .cloned()
+                    .unwrap_or_else(|| Note {
+                        id: nav.note_id.clone(),
+                        database_id: String::new(),
+                        title: nav.note_id.clone(),
+                        content: String::new(),
+                        created_at: String::new(),
+                        updated_at: String::new(),
+                    });
+                groups.push((note, vec![nav.clone()]));
+            }
+        }
+    }
+
+    groups.sort_by_key(|(note, _)| note.title.to_lowercase());
+    groups
+}